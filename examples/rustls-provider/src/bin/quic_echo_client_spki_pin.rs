@@ -0,0 +1,114 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use ring::digest;
+use s2n_quic::{
+    client::Connect,
+    provider::tls::rustls::{self, CertificateValidator, ValidationError},
+    Client,
+};
+use std::{error::Error, net::SocketAddr};
+
+/// NOTE: this certificate is to be used for demonstration purposes only!
+pub static CERT_PEM: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../quic/s2n-quic-core/certs/cert.pem"
+));
+
+/// The SHA-256 digest of the SubjectPublicKeyInfo of the demonstration certificate
+///
+/// This can be computed with:
+///
+/// ```sh
+/// openssl x509 -in cert.pem -pubkey -noout \
+///   | openssl pkey -pubin -outform der \
+///   | openssl dgst -sha256 -hex
+/// ```
+const SPKI_PIN: &str = "b508720e4f42efb62494685a496a7bc99a3c1eb01d6a9015f257365ab9ae7dfe";
+
+/// Only accepts servers whose leaf certificate contains a pinned public key
+struct SpkiPinValidator {
+    pins: Vec<Vec<u8>>,
+}
+
+impl SpkiPinValidator {
+    fn new(pins: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let pins = pins
+            .iter()
+            .map(|pin| decode_hex(pin))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { pins })
+    }
+}
+
+impl CertificateValidator for SpkiPinValidator {
+    fn validate(
+        &self,
+        chain: &[rustls::Certificate],
+        _server_name: &str,
+    ) -> Result<(), ValidationError> {
+        let leaf = chain
+            .first()
+            .ok_or_else(|| ValidationError::new("empty certificate chain"))?;
+
+        let (_, cert) = x509_parser::parse_x509_certificate(&leaf.0)
+            .map_err(|err| ValidationError::new(format!("invalid certificate: {}", err)))?;
+
+        let spki = cert.tbs_certificate.subject_pki.raw;
+        let fingerprint = digest::digest(&digest::SHA256, spki);
+
+        if self.pins.iter().any(|pin| pin == fingerprint.as_ref()) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("public key does not match any pin"))
+        }
+    }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if value.len() % 2 != 0 {
+        return Err("invalid hex length".into());
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|idx| Ok(u8::from_str_radix(&value[idx..idx + 2], 16)?))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    // the pin validator runs after the chain has been validated against the root certificate
+    let tls = rustls::Client::builder()
+        .with_certificate(CERT_PEM)?
+        .with_certificate_validator(SpkiPinValidator::new(&[SPKI_PIN])?)?
+        .build()?;
+
+    let client = Client::builder()
+        .with_tls(tls)?
+        .with_io("0.0.0.0:0")?
+        .start()?;
+
+    let addr: SocketAddr = "127.0.0.1:4433".parse()?;
+    let connect = Connect::new(addr).with_server_name("localhost");
+    let mut connection = client.connect(connect).await?;
+
+    // ensure the connection doesn't time out with inactivity
+    connection.keep_alive(true)?;
+
+    // open a new stream and split the receiving and sending sides
+    let stream = connection.open_bidirectional_stream().await?;
+    let (mut receive_stream, mut send_stream) = stream.split();
+
+    // spawn a task that copies responses from the server to stdout
+    tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        let _ = tokio::io::copy(&mut receive_stream, &mut stdout).await;
+    });
+
+    // copy data from stdin and send it to the server
+    let mut stdin = tokio::io::stdin();
+    tokio::io::copy(&mut stdin, &mut send_stream).await?;
+
+    Ok(())
+}
@@ -6,9 +6,11 @@ use criterion::Criterion;
 mod aes;
 mod aesgcm;
 mod ghash;
+mod packet;
 
 pub fn benchmarks(c: &mut Criterion) {
     aes::benchmarks(c);
     aesgcm::benchmarks(c);
     ghash::benchmarks(c);
+    packet::benchmarks(c);
 }
@@ -0,0 +1,178 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Measures the cost of protecting 1-RTT packets with each of the negotiable cipher suites
+//!
+//! Unlike the `aesgcm` benchmarks, these go through the same keys the connection uses, so they
+//! include the nonce derivation and whichever AEAD implementation is selected for the platform.
+
+use criterion::{black_box, BatchSize, BenchmarkId, Criterion, Throughput};
+use s2n_quic_core::crypto::{HeaderKey as _, Key as _};
+use s2n_quic_crypto::{
+    one_rtt::{OneRttHeaderKey, OneRttKey},
+    ring::{aead, hkdf},
+    SecretPair,
+};
+
+/// The size of a packet on a path with the minimum QUIC MTU
+const PACKET_LEN: usize = 1200;
+
+/// The length of a short header with an 8 byte connection id and a 4 byte packet number
+const HEADER_LEN: usize = 1 + 8 + PACKET_NUMBER_LEN;
+
+const PACKET_NUMBER_LEN: usize = 4;
+
+/// The bitrate the `throughput` benchmark is sized for
+const TARGET_BITS_PER_SECOND: usize = 10_000_000_000;
+
+/// The number of packets sent in a millisecond at `TARGET_BITS_PER_SECOND`
+const PACKETS_PER_MILLISECOND: usize = TARGET_BITS_PER_SECOND / 8 / 1000 / PACKET_LEN;
+
+struct Suite {
+    name: &'static str,
+    algorithm: &'static aead::Algorithm,
+    hkdf: hkdf::Algorithm,
+    secret_len: usize,
+}
+
+impl Suite {
+    fn secrets(&self) -> SecretPair {
+        SecretPair {
+            server: hkdf::Prk::new_less_safe(self.hkdf, &vec![1; self.secret_len]),
+            client: hkdf::Prk::new_less_safe(self.hkdf, &vec![2; self.secret_len]),
+        }
+    }
+
+    /// Returns the keys of the sending and receiving endpoints
+    fn keys(&self) -> ((OneRttKey, OneRttHeaderKey), (OneRttKey, OneRttHeaderKey)) {
+        let sealer = OneRttKey::new_server(self.algorithm, self.secrets()).unwrap();
+        let opener = OneRttKey::new_client(self.algorithm, self.secrets()).unwrap();
+        (sealer, opener)
+    }
+}
+
+fn suites() -> [Suite; 2] {
+    [
+        Suite {
+            name: "TLS_AES_256_GCM_SHA384",
+            algorithm: &aead::AES_256_GCM,
+            hkdf: hkdf::HKDF_SHA384,
+            secret_len: 48,
+        },
+        Suite {
+            name: "TLS_CHACHA20_POLY1305_SHA256",
+            algorithm: &aead::CHACHA20_POLY1305,
+            hkdf: hkdf::HKDF_SHA256,
+            secret_len: 32,
+        },
+    ]
+}
+
+pub fn benchmarks(c: &mut Criterion) {
+    for suite in suites().iter() {
+        let mut group = c.benchmark_group(format!("crypto/packet/{}", suite.name));
+
+        packet(&mut group, suite);
+        header_protection(&mut group, suite);
+        key_derivation(&mut group, suite);
+        throughput(&mut group, suite);
+
+        group.finish();
+    }
+}
+
+type Group<'a> = criterion::BenchmarkGroup<'a, criterion::measurement::WallTime>;
+
+fn packet(group: &mut Group, suite: &Suite) {
+    let ((sealer, _), (opener, _)) = suite.keys();
+    let header = [0x40u8; HEADER_LEN];
+    let payload_len = PACKET_LEN - HEADER_LEN;
+
+    group.throughput(Throughput::Bytes(PACKET_LEN as _));
+
+    group.bench_function(BenchmarkId::new("encrypt", PACKET_LEN), |b| {
+        let mut payload = vec![123u8; payload_len];
+        b.iter(|| {
+            let _ = sealer.encrypt(black_box(1), &header, &mut payload);
+        });
+    });
+
+    group.bench_function(BenchmarkId::new("decrypt", PACKET_LEN), |b| {
+        // create a valid encrypted payload
+        let mut payload = vec![123u8; payload_len];
+        sealer.encrypt(1, &header, &mut payload).unwrap();
+
+        b.iter_batched(
+            || payload.clone(),
+            |mut payload| {
+                let _ = black_box(opener.decrypt(black_box(1), &header, &mut payload));
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+fn header_protection(group: &mut Group, suite: &Suite) {
+    let ((_, header_key), _) = suite.keys();
+    let sample = vec![123u8; header_key.sealing_sample_len()];
+
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("header_protection", |b| {
+        b.iter(|| black_box(header_key.sealing_header_protection_mask(black_box(&sample))));
+    });
+}
+
+fn key_derivation(group: &mut Group, suite: &Suite) {
+    group.throughput(Throughput::Elements(1));
+
+    // derives the packet and header protection keys in both directions from the TLS secrets
+    group.bench_function("key_derivation", |b| {
+        b.iter_batched(
+            || suite.secrets(),
+            |secrets| black_box(OneRttKey::new_server(suite.algorithm, secrets)),
+            BatchSize::SmallInput,
+        );
+    });
+
+    let ((key, _), _) = suite.keys();
+    group.bench_function("key_update", |b| b.iter(|| black_box(key.update())));
+}
+
+/// Protects and unprotects a millisecond worth of packets at `TARGET_BITS_PER_SECOND`
+///
+/// The benchmark keeps up with the target bitrate as long as the reported throughput stays
+/// above 1.25 GB/s.
+fn throughput(group: &mut Group, suite: &Suite) {
+    let ((sealer, sealer_header_key), (opener, opener_header_key)) = suite.keys();
+    let sample_len = sealer_header_key.sealing_sample_len();
+    let mut packets = vec![[123u8; PACKET_LEN]; PACKETS_PER_MILLISECOND];
+
+    group.throughput(Throughput::Bytes(
+        (PACKETS_PER_MILLISECOND * PACKET_LEN) as _,
+    ));
+    group.bench_function(BenchmarkId::new("throughput", "10Gbps"), |b| {
+        b.iter(|| {
+            for (packet_number, packet) in packets.iter_mut().enumerate() {
+                let packet_number = packet_number as u64;
+                let (header, payload) = packet.split_at_mut(HEADER_LEN);
+
+                sealer.encrypt(packet_number, header, payload).unwrap();
+                let mask = sealer_header_key.sealing_header_protection_mask(&payload[..sample_len]);
+                apply_mask(header, &mask);
+
+                let mask = opener_header_key.opening_header_protection_mask(&payload[..sample_len]);
+                apply_mask(header, &mask);
+                opener.decrypt(packet_number, header, payload).unwrap();
+            }
+        });
+    });
+}
+
+#[inline]
+fn apply_mask(header: &mut [u8], mask: &[u8]) {
+    header[0] ^= mask[0] & 0x1f;
+    let packet_number = &mut header[HEADER_LEN - PACKET_NUMBER_LEN..];
+    for (byte, mask) in packet_number.iter_mut().zip(&mask[1..]) {
+        *byte ^= mask;
+    }
+}
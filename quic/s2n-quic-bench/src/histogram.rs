@@ -0,0 +1,82 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{black_box, BatchSize, Criterion, Throughput};
+use s2n_quic_core::{
+    connection::ConnectionHistograms,
+    time::{testing::Clock, Clock as _, Timestamp},
+};
+use std::time::Duration;
+
+/// The number of packets recorded in each iteration
+const PACKETS: usize = 4096;
+
+/// The size of each packet
+const PACKET_LEN: usize = 1200;
+
+pub fn benchmarks(c: &mut Criterion) {
+    per_packet(c);
+}
+
+fn packets() -> Vec<(Duration, Timestamp)> {
+    let mut clock = Clock::default();
+
+    (0..PACKETS)
+        .map(|idx| {
+            // spread the packets over a few throughput intervals
+            clock.inc_by(Duration::from_millis(1));
+            let rtt = Duration::from_micros(20_000 + (idx as u64).wrapping_mul(7919) % 80_000);
+            (rtt, clock.get_time())
+        })
+        .collect()
+}
+
+/// Measures the cost of maintaining the histograms for each packet
+///
+/// Each iteration records `PACKETS` packets, so the per-element time reported by criterion is the
+/// cost per packet, which should stay under 50ns. Each iteration starts with empty histograms,
+/// which are created outside of the measurement.
+fn per_packet(c: &mut Criterion) {
+    let mut group = c.benchmark_group("histogram");
+    group.throughput(Throughput::Elements(PACKETS as u64));
+
+    let packets = packets();
+
+    group.bench_function("on_rtt_sample", |b| {
+        b.iter_batched_ref(
+            Box::<ConnectionHistograms>::default,
+            |histograms| {
+                for (rtt, _now) in packets.iter() {
+                    histograms.on_rtt_sample(black_box(*rtt));
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("on_bytes_sent", |b| {
+        b.iter_batched_ref(
+            Box::<ConnectionHistograms>::default,
+            |histograms| {
+                for (_rtt, now) in packets.iter() {
+                    histograms.on_bytes_sent(black_box(PACKET_LEN), *now);
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("per_packet", |b| {
+        b.iter_batched_ref(
+            Box::<ConnectionHistograms>::default,
+            |histograms| {
+                for (rtt, now) in packets.iter() {
+                    histograms.on_rtt_sample(black_box(*rtt));
+                    histograms.on_bytes_sent(black_box(PACKET_LEN), *now);
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
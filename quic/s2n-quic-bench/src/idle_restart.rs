@@ -0,0 +1,53 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares how quickly BBR delivers a response after a 10 second idle period with each
+//! `IdleRestartPolicy`
+//!
+//! The time is measured on a simulated lossless path, so it reflects the congestion window the
+//! connection restarts with rather than how fast the machine is. The path is lossless, so the
+//! `CarefulResume` policy never needs to retreat and performs the same as `Disabled`.
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use s2n_quic_core::recovery::bbr::{
+    testing::{response_time_after_idle, Path},
+    BbrConfig, IdleRestartPolicy,
+};
+use std::time::Duration;
+
+/// The amount of time the connection is idle before sending the response
+const IDLE: Duration = Duration::from_secs(10);
+
+/// The size of the response sent after the idle period
+const LEN: u64 = 1_000_000;
+
+pub fn benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("idle_restart");
+    group.throughput(Throughput::Bytes(LEN));
+
+    let path = Path {
+        rtt: Duration::from_millis(50),
+        link_rate: 100_000_000,
+    };
+
+    for policy in [
+        IdleRestartPolicy::Disabled,
+        IdleRestartPolicy::CwndReset,
+        IdleRestartPolicy::CarefulResume,
+    ] {
+        let mut config = BbrConfig::default();
+        config.idle_restart_policy = policy;
+
+        group.bench_function(
+            BenchmarkId::new("first_send", format!("{:?}", policy)),
+            |b| {
+                b.iter_custom(|iters| {
+                    (0..iters)
+                        .map(|_| response_time_after_idle(config.clone(), &path, LEN, IDLE))
+                        .sum()
+                })
+            },
+        );
+    }
+    group.finish();
+}
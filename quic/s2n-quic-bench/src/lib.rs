@@ -5,12 +5,22 @@ use criterion::Criterion;
 
 mod crypto;
 mod frame;
+mod histogram;
+mod idle_restart;
 mod packet;
+mod shard;
+mod stream_metrics;
+mod streams;
 mod varint;
 
 pub fn benchmarks(c: &mut Criterion) {
     crypto::benchmarks(c);
     frame::benchmarks(c);
+    histogram::benchmarks(c);
+    idle_restart::benchmarks(c);
     packet::benchmarks(c);
+    shard::benchmarks(c);
+    stream_metrics::benchmarks(c);
+    streams::benchmarks(c);
     varint::benchmarks(c);
 }
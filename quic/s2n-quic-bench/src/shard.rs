@@ -0,0 +1,125 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use s2n_quic_core::connection::id::structured::ConfigId;
+use s2n_quic_platform::shard::{shard_server_id, ShardRouter, SHARD_SERVER_ID_LEN};
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+/// The number of connections the datagrams are spread across
+const CONNECTIONS: u32 = 4096;
+
+/// The number of datagrams dispatched in each iteration
+const DATAGRAMS: usize = 16 * 1024;
+
+/// The size of each datagram
+const DATAGRAM_LEN: usize = 1200;
+
+/// The length of the connection IDs issued by the server
+const LOCAL_CONNECTION_ID_LEN: usize = 8;
+
+/// The number of passes over each datagram to simulate the cost of processing a packet
+const PROCESSING_ROUNDS: usize = 16;
+
+pub fn benchmarks(c: &mut Criterion) {
+    receive_throughput(c);
+}
+
+fn datagrams(config_id: ConfigId, shards: usize) -> Vec<Arc<[u8]>> {
+    (0..DATAGRAMS)
+        .map(|idx| {
+            let connection = idx as u32 % CONNECTIONS;
+            let shard = (connection as usize % shards) as u16;
+            let mut datagram = vec![0u8; DATAGRAM_LEN];
+            // short header packet
+            datagram[0] = 0x40;
+            datagram[1] = config_id.first_octet(LOCAL_CONNECTION_ID_LEN);
+            datagram[2..2 + SHARD_SERVER_ID_LEN].copy_from_slice(&shard_server_id(shard));
+            datagram[4..8].copy_from_slice(&connection.wrapping_mul(0x9e37_79b9).to_be_bytes());
+            Arc::from(datagram)
+        })
+        .collect()
+}
+
+fn process(datagram: &[u8]) -> u64 {
+    let mut checksum = 0u64;
+    for round in 0..PROCESSING_ROUNDS {
+        for chunk in datagram.chunks_exact(8) {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(chunk);
+            checksum = checksum
+                .rotate_left(5)
+                .wrapping_add(u64::from_le_bytes(value) ^ round as u64);
+        }
+    }
+    checksum
+}
+
+fn receive_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shard/receive");
+    group.throughput(Throughput::Bytes((DATAGRAMS * DATAGRAM_LEN) as u64));
+
+    let config_id = ConfigId::default();
+
+    let datagrams = datagrams(config_id, 1);
+    group.bench_function(BenchmarkId::new("inline", 1), |b| {
+        b.iter(|| {
+            for datagram in &datagrams {
+                black_box(process(datagram));
+            }
+        });
+    });
+
+    for threads in [1, 2, 4, 8] {
+        let datagrams = datagrams(config_id, threads);
+        let processed = Arc::new(AtomicUsize::new(0));
+
+        let (shards, workers): (Vec<_>, Vec<_>) = (0..threads)
+            .map(|_| {
+                let (shard, routed) = mpsc::sync_channel(1024);
+                let processed = processed.clone();
+                let worker = thread::spawn(move || {
+                    // the loop exits once the router has been dropped
+                    for routed in routed {
+                        let datagram: Arc<[u8]> = routed.into_datagram();
+                        black_box(process(&datagram));
+                        processed.fetch_add(1, Ordering::Release);
+                    }
+                });
+                (shard, worker)
+            })
+            .unzip();
+
+        let router = ShardRouter::new(shards, config_id);
+
+        group.bench_function(BenchmarkId::new("sharded", threads), |b| {
+            b.iter(|| {
+                processed.store(0, Ordering::Release);
+
+                for datagram in &datagrams {
+                    let mut datagram = datagram.clone();
+                    // retry until the shard has room for the datagram
+                    while let Err(rejected) = router.dispatch(datagram) {
+                        datagram = rejected;
+                        thread::yield_now();
+                    }
+                }
+
+                while processed.load(Ordering::Acquire) < DATAGRAMS {
+                    thread::yield_now();
+                }
+            });
+        });
+
+        drop(router);
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+
+    group.finish();
+}
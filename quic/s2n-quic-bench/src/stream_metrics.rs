@@ -0,0 +1,56 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Measures the cost of recording stream transfer counters
+//!
+//! Recording a frame should stay below 5ns, which is the reported time divided by `FRAMES`.
+
+use criterion::{black_box, Criterion, Throughput};
+use s2n_quic_core::stream::{StreamMetrics, StreamMetricsCollector};
+
+/// The number of frames recorded in each iteration
+const FRAMES: usize = 4096;
+
+/// The size of each frame
+const FRAME_LEN: usize = 1200;
+
+/// The encoded size of each frame, including the STREAM frame header
+const ENCODED_FRAME_LEN: usize = FRAME_LEN + 8;
+
+pub fn benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stream_metrics");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+
+    let metrics = StreamMetrics::default();
+
+    group.bench_function("per_frame", |b| {
+        b.iter(|| {
+            for idx in 0..FRAMES {
+                // every 16th frame is a retransmission
+                black_box(&metrics).on_frame_sent(FRAME_LEN, ENCODED_FRAME_LEN, idx % 16 == 0);
+            }
+        });
+    });
+
+    group.bench_function("per_received_frame", |b| {
+        b.iter(|| {
+            for _ in 0..FRAMES {
+                black_box(&metrics).on_bytes_received(FRAME_LEN);
+            }
+        });
+    });
+
+    group.throughput(Throughput::Elements(1));
+
+    let mut collector = StreamMetricsCollector::default();
+
+    group.bench_function("commit", |b| {
+        b.iter(|| {
+            metrics.on_frame_sent(FRAME_LEN, ENCODED_FRAME_LEN, false);
+            collector.commit(black_box(&metrics));
+        });
+    });
+
+    black_box(collector.totals());
+    group.finish();
+}
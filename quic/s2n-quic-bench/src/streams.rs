@@ -0,0 +1,187 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compares sending data on a single stream to multiplexing it over many streams
+//!
+//! The client and server run on the simulated network from the testing IO provider, so the
+//! throughput benchmarks measure the CPU cost of the transfer without any socket overhead.
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use s2n_quic::{
+    client::Connect,
+    provider::io::testing::{primary, spawn, test, time, Handle, Model, Result},
+    Client, Server,
+};
+use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// The amount of data sent when using a single stream
+const SINGLE_STREAM_LEN: u64 = 1_000_000_000;
+
+/// The number of concurrent streams when multiplexing
+const STREAMS: usize = 100;
+
+/// The amount of data sent on each of the multiplexed streams
+const MULTI_STREAM_LEN: u64 = 10_000_000;
+
+/// The amount of time the multiplexed streams send before a new stream is opened
+const WARMUP: Duration = Duration::from_millis(100);
+
+pub fn benchmarks(c: &mut Criterion) {
+    throughput(c);
+    time_to_first_byte(c);
+}
+
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("streams");
+    // each iteration transfers a gigabyte so use the minimum number of samples
+    group.sample_size(10);
+
+    for (streams, len) in [(1, SINGLE_STREAM_LEN), (STREAMS, MULTI_STREAM_LEN)] {
+        group.throughput(Throughput::Bytes(streams as u64 * len));
+        group.bench_with_input(
+            BenchmarkId::new("throughput", streams),
+            &(streams, len),
+            |b, &(streams, len)| b.iter(|| transfer(streams, len)),
+        );
+    }
+    group.finish();
+}
+
+/// Measures the time it takes for the first byte of a new stream to arrive at the server while
+/// `STREAMS` other streams are sending
+///
+/// The time is measured on the simulated network, so it reflects how the new stream is
+/// scheduled relative to the existing ones rather than how fast the machine is.
+fn time_to_first_byte(c: &mut Criterion) {
+    let mut group = c.benchmark_group("streams");
+
+    group.bench_function(BenchmarkId::new("time_to_first_byte", STREAMS), |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| first_byte_delay()).sum())
+    });
+    group.finish();
+}
+
+fn transfer(streams: usize, len: u64) {
+    test(Model::default(), |handle| {
+        let server_addr = server(handle, |mut connection| async move {
+            while let Ok(Some(mut stream)) = connection.accept_receive_stream().await {
+                primary::spawn(async move { while let Ok(Some(_)) = stream.receive().await {} });
+            }
+        })?;
+        let client = client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            for _ in 0..streams {
+                let mut stream = connection.open_send_stream().await.unwrap();
+                primary::spawn(async move {
+                    let mut data = Data::new(len);
+                    while let Some(chunk) = data.send_one(usize::MAX) {
+                        stream.send(chunk).await.unwrap();
+                    }
+                    stream.close().await.unwrap();
+                });
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+fn first_byte_delay() -> Duration {
+    let opened_at = Arc::new(Mutex::new(None));
+    let delay = Arc::new(Mutex::new(None));
+
+    test(Model::default(), |handle| {
+        let server_addr = {
+            let opened_at = opened_at.clone();
+            let delay = delay.clone();
+            server(handle, move |connection| async move {
+                let (_handle, acceptor) = connection.split();
+                // the existing streams are unidirectional and the new one is bidirectional
+                let (mut bidi, mut recv) = acceptor.split();
+
+                spawn(async move {
+                    while let Ok(Some(mut stream)) = recv.accept_receive_stream().await {
+                        spawn(async move { while let Ok(Some(_)) = stream.receive().await {} });
+                    }
+                });
+
+                let mut stream = bidi.accept_bidirectional_stream().await.unwrap().unwrap();
+                stream.receive().await.unwrap().unwrap();
+
+                let opened_at = opened_at.lock().unwrap().take().unwrap();
+                *delay.lock().unwrap() = Some(time::now() - opened_at);
+
+                let _ = stream.close().await;
+            })?
+        };
+        let client = client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            for _ in 0..STREAMS {
+                let mut stream = connection.open_send_stream().await.unwrap();
+                spawn(async move {
+                    let mut data = Data::new(u64::MAX);
+                    while let Some(chunk) = data.send_one(usize::MAX) {
+                        if stream.send(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+
+            time::delay(WARMUP).await;
+
+            *opened_at.lock().unwrap() = Some(time::now());
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+            stream.send(vec![42u8].into()).await.unwrap();
+
+            // the simulation ends once the server has received the byte and closed the stream
+            let _ = stream.receive().await;
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let delay = delay.lock().unwrap().take();
+    delay.expect("the new stream should receive its first byte")
+}
+
+fn server<F, Fut>(handle: &Handle, on_connection: F) -> Result<SocketAddr>
+where
+    F: 'static + Send + FnOnce(s2n_quic::Connection) -> Fut,
+    Fut: 'static + Send + core::future::Future<Output = ()>,
+{
+    let mut server = Server::builder()
+        .with_io(handle.builder().build()?)?
+        .with_tls((certificates::CERT_PEM, certificates::KEY_PEM))?
+        .start()?;
+    let server_addr = server.local_addr()?;
+
+    spawn(async move {
+        let connection = server.accept().await.unwrap();
+        on_connection(connection).await;
+    });
+
+    Ok(server_addr)
+}
+
+fn client(handle: &Handle) -> Result<Client> {
+    Ok(Client::builder()
+        .with_io(handle.builder().build()?)?
+        .with_tls(certificates::CERT_PEM)?
+        .start()?)
+}
@@ -16,6 +16,11 @@ const RECOMMENDED_ELICITATION_INTERVAL: u8 = 4;
 /// The recommended number of packet number ranges that an endpoint should store
 const RECOMMENDED_RANGES_LIMIT: u8 = 10;
 
+/// The recommended maximum encoded size of an ACK frame
+///
+/// By default, ACK frames are only limited by the space available in a packet.
+const RECOMMENDED_MAX_ACK_FRAME_SIZE: u16 = u16::MAX;
+
 /// Settings for ACK frames
 #[derive(Clone, Copy, Debug)]
 pub struct Settings {
@@ -39,6 +44,12 @@ pub struct Settings {
 
     /// The number of packet number intervals an endpoint is willing to store
     pub ack_ranges_limit: u8,
+
+    /// The maximum encoded size of an ACK frame, in bytes
+    ///
+    /// If the ACK frame for all of the stored intervals exceeds this size, the intervals with
+    /// the smallest packet numbers are left out of the frame.
+    pub max_ack_frame_size: u16,
 }
 
 impl Default for Settings {
@@ -72,6 +83,7 @@ impl Settings {
         ack_delay_exponent: AckDelayExponent::RECOMMENDED.as_u8(),
         ack_elicitation_interval: RECOMMENDED_ELICITATION_INTERVAL,
         ack_ranges_limit: RECOMMENDED_RANGES_LIMIT,
+        max_ack_frame_size: RECOMMENDED_MAX_ACK_FRAME_SIZE,
     };
 
     /// Decodes the peer's `Ack Delay` field
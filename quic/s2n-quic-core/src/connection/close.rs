@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{application, crypto, transport};
+use crate::{application, crypto, endpoint, transport};
 pub use crate::{frame::ConnectionClose, inet::SocketAddress};
 
 /// Provides a hook for applications to rewrite CONNECTION_CLOSE frames
@@ -39,11 +39,26 @@ pub trait Formatter: 'static + Send {
 #[derive(Debug)]
 pub struct Context<'a> {
     pub remote_address: &'a SocketAddress,
+    endpoint_type: Option<endpoint::Type>,
 }
 
 impl<'a> Context<'a> {
     pub fn new(remote_address: &'a SocketAddress) -> Self {
-        Self { remote_address }
+        Self {
+            remote_address,
+            endpoint_type: None,
+        }
+    }
+
+    /// Sets the type of the endpoint closing the connection
+    pub fn with_endpoint_type(mut self, endpoint_type: endpoint::Type) -> Self {
+        self.endpoint_type = Some(endpoint_type);
+        self
+    }
+
+    /// Returns the type of the endpoint closing the connection, if known
+    pub fn endpoint_type(&self) -> Option<endpoint::Type> {
+        self.endpoint_type
     }
 }
 
@@ -50,17 +50,33 @@ pub enum Error {
     },
 
     /// The connection was closed because the local connection's idle timer expired
+    #[deprecated(
+        note = "idle timeouts are reported as `Timeout` with `IdleTimeout` as the reason"
+    )]
     #[non_exhaustive]
     IdleTimerExpired {
         source: &'static panic::Location<'static>,
     },
 
     /// The connection was closed because there are no valid paths
+    #[deprecated(
+        note = "failed migrations are reported as `Timeout` with `MigrationTimeout` as the reason"
+    )]
     #[non_exhaustive]
     NoValidPath {
         source: &'static panic::Location<'static>,
     },
 
+    /// The connection was closed because one of its timers expired
+    ///
+    /// The reason describes which timer expired. Nothing is sent to the peer, except on a
+    /// handshake timeout.
+    #[non_exhaustive]
+    Timeout {
+        reason: ConnectionTimeoutReason,
+        source: &'static panic::Location<'static>,
+    },
+
     /// All Stream IDs for Streams on the given connection had been exhausted
     #[non_exhaustive]
     StreamIdExhausted {
@@ -68,6 +84,9 @@ pub enum Error {
     },
 
     /// The handshake has taken longer to complete than the configured max handshake duration
+    #[deprecated(
+        note = "handshake timeouts are reported as `Timeout` with `HandshakeTimeout` as the reason"
+    )]
     #[non_exhaustive]
     MaxHandshakeDurationExceeded {
         max_handshake_duration: Duration,
@@ -94,9 +113,42 @@ pub enum Error {
     },
 }
 
+/// The timer which caused a connection to time out
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub enum ConnectionTimeoutReason {
+    /// Nothing was received from the peer for the negotiated idle timeout
+    IdleTimeout { idle_duration: Duration },
+
+    /// The handshake took longer to complete than the configured max handshake duration
+    HandshakeTimeout { elapsed: Duration },
+
+    /// Validation of the peer's new address failed after it migrated, and there is no
+    /// previously validated path to fall back to
+    MigrationTimeout,
+}
+
+impl fmt::Display for ConnectionTimeoutReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::IdleTimeout { idle_duration } => {
+                write!(f, "the connection was idle for {:?}", idle_duration)
+            }
+            Self::HandshakeTimeout { elapsed } => {
+                write!(f, "the handshake did not complete after {:?}", elapsed)
+            }
+            Self::MigrationTimeout => write!(
+                f,
+                "the peer's new address could not be validated and there are no other valid paths"
+            ),
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+#[allow(deprecated)]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -136,6 +188,10 @@ impl fmt::Display for Error {
                 f,
                 "The connection was closed because there are no valid paths"
             ),
+            Self::Timeout { reason, .. } => write!(
+                f,
+                "The connection timed out because {}", reason
+            ),
             Self::StreamIdExhausted { .. } => write!(
                 f,
                 "All Stream IDs for Streams on the given connection had been exhausted"
@@ -161,6 +217,7 @@ impl fmt::Display for Error {
 
 impl Error {
     /// Returns the [`panic::Location`] for the error
+    #[allow(deprecated)]
     pub fn source(&self) -> &'static panic::Location<'static> {
         match self {
             Error::Closed { source, .. } => source,
@@ -169,6 +226,7 @@ impl Error {
             Error::StatelessReset { source } => source,
             Error::IdleTimerExpired { source } => source,
             Error::NoValidPath { source } => source,
+            Error::Timeout { source, .. } => source,
             Error::StreamIdExhausted { source } => source,
             Error::MaxHandshakeDurationExceeded { source, .. } => source,
             Error::ImmediateClose { source, .. } => source,
@@ -217,9 +275,9 @@ impl Error {
     #[inline]
     #[track_caller]
     #[doc(hidden)]
-    pub fn idle_timer_expired() -> Error {
+    pub fn timeout(reason: ConnectionTimeoutReason) -> Error {
         let source = panic::Location::caller();
-        Error::IdleTimerExpired { source }
+        Error::Timeout { reason, source }
     }
 
     #[inline]
@@ -230,14 +288,6 @@ impl Error {
         Error::StreamIdExhausted { source }
     }
 
-    #[inline]
-    #[track_caller]
-    #[doc(hidden)]
-    pub fn no_valid_path() -> Error {
-        let source = panic::Location::caller();
-        Error::NoValidPath { source }
-    }
-
     #[inline]
     #[track_caller]
     #[doc(hidden)]
@@ -246,17 +296,6 @@ impl Error {
         Error::StatelessReset { source }
     }
 
-    #[inline]
-    #[track_caller]
-    #[doc(hidden)]
-    pub fn max_handshake_duration_exceeded(max_handshake_duration: Duration) -> Error {
-        let source = panic::Location::caller();
-        Error::MaxHandshakeDurationExceeded {
-            max_handshake_duration,
-            source,
-        }
-    }
-
     #[inline]
     #[track_caller]
     #[doc(hidden)]
@@ -290,6 +329,7 @@ impl Error {
 ///
 /// The first item will be a close frame for an early (initial, handshake) packet.
 /// The second item will be a close frame for a 1-RTT (application data) packet.
+#[allow(deprecated)]
 pub fn as_frame<'a, F: connection::close::Formatter>(
     error: Error,
     formatter: &'a F,
@@ -344,9 +384,37 @@ pub fn as_frame<'a, F: connection::close::Formatter>(
         }
         // This error comes from the peer so we don't respond with a CONNECTION_CLOSE
         Error::StatelessReset { .. } => None,
-        // Nothing gets sent on idle timeouts
+        Error::Timeout {
+            reason: ConnectionTimeoutReason::HandshakeTimeout { .. },
+            ..
+        } => {
+            // Notify the peer so it doesn't hold onto a connection that will never complete,
+            // which can happen if the client's Finished message never reaches the server
+            let is_server = context
+                .endpoint_type()
+                .map_or(false, endpoint::Type::is_server);
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-20.1
+            //# CONNECTION_REFUSED (0x02):  The server refused to accept a new
+            //#    connection.
+            let error = if is_server {
+                transport::Error::CONNECTION_REFUSED
+            } else {
+                transport::Error::NO_ERROR
+            };
+            let error = error.with_reason("the handshake did not complete in time");
+
+            let early = formatter.format_early_transport_error(context, error);
+            let one_rtt = formatter.format_transport_error(context, error);
+
+            Some((early, one_rtt))
+        }
+        // Nothing gets sent on other timeouts
+        Error::Timeout { .. } => None,
+        // The deprecated timeout errors are no longer returned
         Error::IdleTimerExpired { .. } => None,
         Error::NoValidPath { .. } => None,
+        Error::MaxHandshakeDurationExceeded { .. } => None,
         Error::StreamIdExhausted { .. } => {
             let error =
                 transport::Error::PROTOCOL_VIOLATION.with_reason("stream IDs have been exhausted");
@@ -356,7 +424,6 @@ pub fn as_frame<'a, F: connection::close::Formatter>(
 
             Some((early, one_rtt))
         }
-        Error::MaxHandshakeDurationExceeded { .. } => None,
         Error::ImmediateClose { .. } => None,
         Error::EndpointClosing { .. } => None,
         Error::Unspecified { .. } => {
@@ -428,6 +495,7 @@ impl From<Error> for std::io::Error {
 
 #[cfg(feature = "std")]
 impl From<Error> for std::io::ErrorKind {
+    #[allow(deprecated)]
     fn from(error: Error) -> Self {
         use std::io::ErrorKind;
         match error {
@@ -440,6 +508,7 @@ impl From<Error> for std::io::ErrorKind {
             Error::StatelessReset { .. } => ErrorKind::ConnectionReset,
             Error::IdleTimerExpired { .. } => ErrorKind::TimedOut,
             Error::NoValidPath { .. } => ErrorKind::Other,
+            Error::Timeout { .. } => ErrorKind::TimedOut,
             Error::StreamIdExhausted { .. } => ErrorKind::Other,
             Error::MaxHandshakeDurationExceeded { .. } => ErrorKind::TimedOut,
             Error::ImmediateClose { .. } => ErrorKind::Other,
@@ -484,3 +553,28 @@ impl From<CryptoError> for ProcessingError {
         ProcessingError::CryptoError(inner_error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{connection::close, inet::SocketAddress};
+
+    #[test]
+    fn handshake_timeout_close_test() {
+        let error = Error::timeout(ConnectionTimeoutReason::HandshakeTimeout {
+            elapsed: Duration::from_secs(10),
+        });
+        let remote_address = SocketAddress::default();
+
+        for (endpoint_type, expected) in [
+            (endpoint::Type::Server, transport::Error::CONNECTION_REFUSED),
+            // only servers can refuse connections
+            (endpoint::Type::Client, transport::Error::NO_ERROR),
+        ] {
+            let context = close::Context::new(&remote_address).with_endpoint_type(endpoint_type);
+            let (early, one_rtt) = as_frame(error, &close::Development, &context).unwrap();
+            assert_eq!(early.error_code, expected.code.as_varint());
+            assert_eq!(one_rtt.error_code, expected.code.as_varint());
+        }
+    }
+}
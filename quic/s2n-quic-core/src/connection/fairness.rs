@@ -0,0 +1,316 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Balances the stream data a connection sends against the stream data it receives
+//!
+//! When both endpoints transfer bulk data at the same time, the direction which fills the
+//! bottleneck first can starve the other direction: the packets carrying the other direction's
+//! data and acknowledgements queue behind it. The controller counts the bytes sent and
+//! received over a window of a few round trips. While the peer is actively sending and the
+//! local endpoint has sent more than `fairness_ratio` times what it received, new stream data
+//! is held back until the peer catches up or stops sending. The controller is disabled unless
+//! a ratio is configured with `Limits::with_fairness_ratio`.
+
+use crate::{
+    recovery::{DEFAULT_INITIAL_RTT, K_GRANULARITY},
+    time::{timer, Timer, Timestamp},
+};
+use core::time::Duration;
+
+/// The number of round trips in a measurement window
+const WINDOW_RTTS: u32 = 2;
+
+/// The number of consecutive windows the peer needs to send data in before it is considered
+/// to be actively sending
+///
+/// This keeps short exchanges, such as a request followed by a large response, from being
+/// throttled.
+const ACTIVE_WINDOWS: u8 = 3;
+
+/// The number of bytes which can always be sent in excess of the ratio
+///
+/// This prevents the controller from stalling transmission while only a few bytes have been
+/// received.
+const BURST_ALLOWANCE: u64 = 10 * crate::path::MINIMUM_MTU as u64;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Counts {
+    sent: u64,
+    received: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct BiDirectionalFairnessController {
+    /// The maximum ratio of bytes sent to bytes received while the peer is actively sending
+    fairness_ratio: u32,
+    /// The duration of a measurement window
+    window: Duration,
+    /// The start of the current window
+    window_start: Option<Timestamp>,
+    /// The bytes transferred in the current window
+    current: Counts,
+    /// The bytes transferred in the previous window
+    previous: Counts,
+    /// The number of consecutive windows in which the peer sent data
+    active_windows: u8,
+    /// Expires at the end of the current window while transmission is held back
+    window_timer: Timer,
+}
+
+impl BiDirectionalFairnessController {
+    /// Returns `None` if the `fairness_ratio` is 0, which disables the controller
+    pub fn new(fairness_ratio: u32) -> Option<Self> {
+        if fairness_ratio == 0 {
+            return None;
+        }
+
+        Some(Self {
+            fairness_ratio,
+            window: DEFAULT_INITIAL_RTT * WINDOW_RTTS,
+            window_start: None,
+            current: Counts::default(),
+            previous: Counts::default(),
+            active_windows: 0,
+            window_timer: Timer::default(),
+        })
+    }
+
+    /// Returns the maximum ratio of bytes sent to bytes received
+    #[inline]
+    pub fn fairness_ratio(&self) -> u32 {
+        self.fairness_ratio
+    }
+
+    /// Returns `true` if the peer has been sending data for the last few windows
+    #[inline]
+    pub fn is_peer_active(&self) -> bool {
+        self.active_windows >= ACTIVE_WINDOWS
+    }
+
+    /// Returns `true` if new stream data can be sent
+    #[inline]
+    pub fn can_send(&self) -> bool {
+        if !self.is_peer_active() {
+            return true;
+        }
+
+        let sent = self.previous.sent + self.current.sent;
+        let received = self.previous.received + self.current.received;
+        let limit = received.saturating_mul(self.fairness_ratio as u64) + BURST_ALLOWANCE;
+
+        sent < limit
+    }
+
+    /// Adjusts the window to the smoothed RTT of the connection
+    #[inline]
+    pub fn on_rtt_update(&mut self, smoothed_rtt: Duration) {
+        self.window = (smoothed_rtt * WINDOW_RTTS).max(K_GRANULARITY);
+    }
+
+    /// Called when stream data is written to a packet
+    pub fn on_send(&mut self, now: Timestamp, bytes: u64) {
+        self.update_window(now);
+        self.current.sent += bytes;
+        self.arm_window_timer();
+    }
+
+    /// Called when stream data is received from the peer
+    #[inline]
+    pub fn on_receive(&mut self, bytes: u64) {
+        self.current.received += bytes;
+    }
+
+    /// Called when the connection timer expires
+    pub fn on_timeout(&mut self, now: Timestamp) {
+        if self.window_timer.poll_expiration(now).is_ready() {
+            self.update_window(now);
+            self.arm_window_timer();
+        }
+    }
+
+    /// Starts a new window if the current one has ended
+    pub fn update_window(&mut self, now: Timestamp) {
+        let window_start = *self.window_start.get_or_insert(now);
+        let elapsed = now.saturating_duration_since(window_start);
+
+        if elapsed < self.window {
+            return;
+        }
+
+        if self.current.received > 0 && elapsed < self.window * 2 {
+            self.active_windows = self.active_windows.saturating_add(1);
+            self.previous = self.current;
+        } else {
+            // the peer didn't send anything for at least a window
+            self.active_windows = 0;
+            self.previous = Counts::default();
+        }
+
+        self.current = Counts::default();
+        self.window_start = Some(now);
+    }
+
+    fn arm_window_timer(&mut self) {
+        match self.window_start {
+            Some(window_start) if !self.can_send() => {
+                self.window_timer.set(window_start + self.window);
+            }
+            _ => self.window_timer.cancel(),
+        }
+    }
+}
+
+impl timer::Provider for BiDirectionalFairnessController {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.window_timer.timers(query)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{testing::Clock, Clock as _};
+
+    const RTT: Duration = Duration::from_millis(50);
+    const RATIO: u32 = 10;
+
+    fn controller(fairness_ratio: u32) -> (Clock, BiDirectionalFairnessController) {
+        let clock = Clock::default();
+        let mut controller = BiDirectionalFairnessController::new(fairness_ratio).unwrap();
+        controller.on_rtt_update(RTT);
+        (clock, controller)
+    }
+
+    #[test]
+    fn disabled_test() {
+        assert!(BiDirectionalFairnessController::new(0).is_none());
+    }
+
+    #[test]
+    fn inactive_peer_test() {
+        let (mut clock, mut controller) = controller(RATIO);
+
+        // the peer doesn't send anything so the local endpoint is never held back
+        for _ in 0..100 {
+            controller.on_send(clock.get_time(), 100_000);
+            assert!(controller.can_send());
+            clock.inc_by(RTT);
+        }
+
+        // a single request doesn't make the peer active
+        controller.on_receive(100);
+        for _ in 0..10 {
+            controller.on_send(clock.get_time(), 100_000);
+            assert!(controller.can_send());
+            clock.inc_by(RTT);
+        }
+        assert!(!controller.is_peer_active());
+    }
+
+    #[test]
+    fn active_peer_test() {
+        let (mut clock, mut controller) = controller(RATIO);
+
+        // the peer sends data for ACTIVE_WINDOWS windows
+        for _ in 0..(ACTIVE_WINDOWS + 1) * WINDOW_RTTS as u8 {
+            controller.on_receive(1000);
+            controller.on_send(clock.get_time(), 1000);
+            clock.inc_by(RTT);
+        }
+        assert!(controller.is_peer_active());
+        assert!(controller.can_send());
+
+        // sending more than 10 times what was received holds back the local endpoint
+        controller.on_send(clock.get_time(), 100_000);
+        assert!(!controller.can_send());
+        assert!(controller.window_timer.is_armed());
+
+        // the peer catching up allows the local endpoint to send again
+        controller.on_receive(10_000);
+        assert!(controller.can_send());
+
+        controller.on_send(clock.get_time(), 100_000);
+        assert!(!controller.can_send());
+
+        // the peer stopping releases the backpressure once the windows expire
+        for _ in 0..2 {
+            clock.inc_by(RTT * WINDOW_RTTS);
+            controller.on_timeout(clock.get_time());
+        }
+        assert!(!controller.is_peer_active());
+        assert!(controller.can_send());
+        assert!(!controller.window_timer.is_armed());
+    }
+
+    /// Both endpoints try to send as much as possible over a shared bottleneck
+    ///
+    /// The local endpoint is more aggressive and fills the bottleneck before the peer gets a
+    /// chance to send. The controller gives the peer a fair share of the bottleneck.
+    #[test]
+    fn shared_bottleneck_test() {
+        const CAPACITY: u64 = 12_000;
+        const TICK: Duration = Duration::from_millis(1);
+        const DURATION: Duration = Duration::from_secs(10);
+
+        fn run(fairness_ratio: u32) -> (u64, u64) {
+            let (mut clock, mut controller) = controller(fairness_ratio);
+            let mut sent = 0;
+            let mut received = 0;
+
+            for _ in 0..DURATION.as_millis() {
+                clock.inc_by(TICK);
+                let now = clock.get_time();
+                controller.on_timeout(now);
+                controller.update_window(now);
+
+                let mut capacity = CAPACITY;
+
+                if controller.can_send() {
+                    controller.on_send(now, capacity);
+                    sent += capacity;
+                    capacity = 0;
+                }
+
+                // the peer gets whatever is left
+                controller.on_receive(capacity);
+                received += capacity;
+
+                // the peer always has a little bit of capacity for its acknowledgements and
+                // requests
+                controller.on_receive(100);
+                received += 100;
+            }
+
+            (sent, received)
+        }
+
+        // the local endpoint dominates the bottleneck with a high ratio
+        let (sent, received) = run(u32::MAX);
+        assert!(sent > received * 50, "{} {}", sent, received);
+
+        for fairness_ratio in [1, 2, RATIO] {
+            let (sent, received) = run(fairness_ratio);
+            let total = sent + received;
+            let ratio = fairness_ratio as u64;
+
+            // the local endpoint gets at most its fair share, with some slack for the window
+            // measurements
+            assert!(
+                sent <= total * ratio / (ratio + 1) * 11 / 10,
+                "ratio {}: sent {}, received {}",
+                fairness_ratio,
+                sent,
+                received
+            );
+            assert!(
+                sent >= total * ratio / (ratio + 1) * 9 / 10,
+                "ratio {}: sent {}, received {}",
+                fairness_ratio,
+                sent,
+                received
+            );
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixed-size histograms of the RTT and throughput observed on a connection
+//!
+//! The histograms use log-linear buckets: every power of two is split into
+//! [`SUB_BUCKETS`] equally sized buckets, so a recorded value is off by at most 1/8th of its
+//! magnitude. Recording a value is a couple of bit operations and an increment, which keeps the
+//! cost per packet low enough to be maintained for every connection that opts in.
+
+use crate::time::Timestamp;
+use core::{fmt, time::Duration};
+
+const SUB_BUCKET_BITS: u32 = 3;
+
+/// The number of buckets each power of two is split into
+pub const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+
+/// The number of buckets needed to cover every `u64` value
+const BUCKETS: usize = SUB_BUCKETS + (64 - SUB_BUCKET_BITS as usize) * SUB_BUCKETS;
+
+/// The length of the intervals the throughput of a connection is measured over
+pub const THROUGHPUT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: [u32; BUCKETS],
+    count: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("p50", &self.value_at_percentile(50.0))
+            .field("p99", &self.value_at_percentile(99.0))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; BUCKETS],
+            count: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Records a single occurrence of `value`
+    #[inline]
+    pub fn record(&mut self, value: u64) {
+        let bucket = &mut self.counts[Self::index(value)];
+        *bucket = bucket.saturating_add(1);
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Returns the number of recorded values
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the smallest recorded value, or `0` if nothing was recorded
+    #[inline]
+    pub fn min(&self) -> u64 {
+        if self.is_empty() {
+            0
+        } else {
+            self.min
+        }
+    }
+
+    /// Returns the largest recorded value, or `0` if nothing was recorded
+    #[inline]
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Returns the value below which `percentile` percent of the recorded values fall
+    ///
+    /// The result is the upper bound of the bucket the percentile falls into, and never exceeds
+    /// the largest recorded value. `0` is returned if nothing was recorded.
+    pub fn value_at_percentile(&self, percentile: f64) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let percentile = percentile.clamp(0.0, 100.0);
+        let rank = ((percentile / 100.0 * self.count as f64) as u64).clamp(1, self.count);
+
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += *count as u64;
+            if seen >= rank {
+                return Self::upper_bound(index).clamp(self.min, self.max);
+            }
+        }
+
+        self.max
+    }
+
+    /// Removes all of the recorded values
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    #[inline]
+    fn index(value: u64) -> usize {
+        if value < SUB_BUCKETS as u64 {
+            return value as usize;
+        }
+
+        let exponent = 63 - value.leading_zeros();
+        let shift = exponent - SUB_BUCKET_BITS;
+        // the bits following the leading one select the bucket within the power of two
+        let sub_bucket = (value >> shift) as usize & (SUB_BUCKETS - 1);
+        SUB_BUCKETS + shift as usize * SUB_BUCKETS + sub_bucket
+    }
+
+    fn upper_bound(index: usize) -> u64 {
+        if index < SUB_BUCKETS {
+            return index as u64;
+        }
+
+        let shift = (index - SUB_BUCKETS) / SUB_BUCKETS;
+        let sub_bucket = (index % SUB_BUCKETS) as u64;
+        let lower = (SUB_BUCKETS as u64 + sub_bucket) << shift;
+        lower + ((1u64 << shift) - 1)
+    }
+}
+
+/// The RTT and throughput histograms of a connection
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionHistograms {
+    /// The RTT samples of the connection, in microseconds
+    rtt: Histogram,
+    /// The number of bytes sent in each `THROUGHPUT_INTERVAL`
+    throughput: Histogram,
+    interval_start: Option<Timestamp>,
+    interval_bytes: u64,
+}
+
+impl ConnectionHistograms {
+    /// Returns the histogram of RTT samples, in microseconds
+    #[inline]
+    pub fn rtt(&self) -> &Histogram {
+        &self.rtt
+    }
+
+    /// Returns the histogram of the number of bytes sent per second
+    ///
+    /// Only seconds in which the connection sent at least one packet are recorded, so idle
+    /// periods don't skew the distribution towards zero.
+    #[inline]
+    pub fn throughput(&self) -> &Histogram {
+        &self.throughput
+    }
+
+    /// Records a new RTT sample
+    #[inline]
+    pub fn on_rtt_sample(&mut self, rtt: Duration) {
+        self.rtt
+            .record(rtt.as_micros().try_into().unwrap_or(u64::MAX));
+    }
+
+    /// Called when a packet of `bytes` length is sent
+    #[inline]
+    pub fn on_bytes_sent(&mut self, bytes: usize, now: Timestamp) {
+        match self.interval_start {
+            Some(start) if now.saturating_duration_since(start) >= THROUGHPUT_INTERVAL => {
+                self.throughput.record(self.interval_bytes);
+                self.interval_bytes = 0;
+
+                // skip over the idle intervals instead of recording them
+                if now.saturating_duration_since(start) >= THROUGHPUT_INTERVAL * 2 {
+                    self.interval_start = Some(now);
+                } else {
+                    self.interval_start = Some(start + THROUGHPUT_INTERVAL);
+                }
+            }
+            Some(_) => {}
+            None => self.interval_start = Some(now),
+        }
+
+        self.interval_bytes += bytes as u64;
+    }
+
+    /// Removes all of the recorded values from both histograms
+    pub fn reset(&mut self) {
+        self.rtt.reset();
+        self.throughput.reset();
+        self.interval_start = None;
+        self.interval_bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{testing::Clock, Clock as _};
+
+    #[test]
+    fn index_test() {
+        let mut previous = 0;
+        for value in (0..10_000).chain([u64::MAX / 2, u64::MAX - 1, u64::MAX]) {
+            let index = Histogram::index(value);
+            assert!(index < BUCKETS);
+            assert!(index >= previous, "buckets must be ordered");
+            assert!(
+                value <= Histogram::upper_bound(index),
+                "{} must fall into bucket {}",
+                value,
+                index
+            );
+            if index > 0 {
+                assert!(value > Histogram::upper_bound(index - 1));
+            }
+            previous = index;
+        }
+
+        assert_eq!(BUCKETS - 1, Histogram::index(u64::MAX));
+        assert_eq!(u64::MAX, Histogram::upper_bound(BUCKETS - 1));
+    }
+
+    #[test]
+    fn percentile_test() {
+        let mut histogram = Histogram::new();
+        assert_eq!(0, histogram.value_at_percentile(50.0));
+
+        for value in 1..=1000 {
+            histogram.record(value);
+        }
+
+        assert_eq!(1000, histogram.count());
+        assert_eq!(1, histogram.min());
+        assert_eq!(1000, histogram.max());
+        assert_eq!(1, histogram.value_at_percentile(0.0));
+        assert_eq!(1000, histogram.value_at_percentile(100.0));
+
+        // percentiles are accurate to within the width of a bucket
+        for (percentile, expected) in [(50.0, 500.0), (90.0, 900.0), (99.0, 990.0)] {
+            let actual = histogram.value_at_percentile(percentile) as f64;
+            assert!(actual >= expected, "p{} = {}", percentile, actual);
+            assert!(
+                actual <= expected * (1.0 + 1.0 / SUB_BUCKETS as f64),
+                "p{} = {}",
+                percentile,
+                actual
+            );
+        }
+
+        histogram.reset();
+        assert!(histogram.is_empty());
+        assert_eq!(0, histogram.max());
+        assert_eq!(0, histogram.min());
+    }
+
+    #[test]
+    fn rtt_test() {
+        let mut histograms = ConnectionHistograms::default();
+        for ms in [10, 10, 10, 100] {
+            histograms.on_rtt_sample(Duration::from_millis(ms));
+        }
+
+        let rtt = histograms.rtt();
+        assert_eq!(4, rtt.count());
+        assert_eq!(10_000, rtt.min());
+        assert_eq!(100_000, rtt.max());
+        assert!(rtt.value_at_percentile(50.0) < 12_000);
+    }
+
+    #[test]
+    fn throughput_test() {
+        let mut clock = Clock::default();
+        let mut histograms = ConnectionHistograms::default();
+
+        // 10 packets per 100ms for 3 seconds
+        for _ in 0..30 {
+            for _ in 0..10 {
+                histograms.on_bytes_sent(1000, clock.get_time());
+            }
+            clock.inc_by(Duration::from_millis(100));
+        }
+
+        // the last second is still in progress
+        let throughput = histograms.throughput();
+        assert_eq!(2, throughput.count());
+        assert_eq!(100_000, throughput.min());
+        assert_eq!(100_000, throughput.max());
+
+        // an idle period only closes the interval in progress
+        clock.inc_by(Duration::from_secs(60));
+        histograms.on_bytes_sent(1000, clock.get_time());
+        assert_eq!(3, histograms.throughput().count());
+
+        histograms.reset();
+        assert!(histograms.throughput().is_empty());
+        assert!(histograms.rtt().is_empty());
+    }
+}
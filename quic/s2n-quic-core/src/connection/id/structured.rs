@@ -0,0 +1,174 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Connection IDs which encode a server ID
+//!
+//! The format follows the plaintext algorithm from QUIC-LB, which allows a load balancer
+//! to route packets to the server that issued a connection ID without sharing any
+//! per-connection state. See
+//! <https://datatracker.ietf.org/doc/html/draft-ietf-quic-load-balancers>.
+//!
+//! ```text
+//! Connection ID {
+//!   Config Rotation (3),
+//!   Length (5),
+//!   Server ID (8..120),
+//!   Nonce (32..144),
+//! }
+//! ```
+//!
+//! The length field contains the length of the connection ID minus one, so the length
+//! can be recovered without state.
+
+use super::Error;
+
+/// The config rotation codepoint reserved for unroutable connection IDs
+const UNROUTABLE_CONFIG_ID: u8 = 0b111;
+
+pub const MIN_SERVER_ID_LEN: usize = 1;
+pub const MAX_SERVER_ID_LEN: usize = 15;
+
+pub const MIN_NONCE_LEN: usize = 4;
+pub const MAX_NONCE_LEN: usize = 18;
+
+/// Number of bits used by the length in the first octet
+const LEN_BITS: u32 = 5;
+const LEN_MASK: u8 = (1 << LEN_BITS) - 1;
+
+/// The config rotation codepoint encoded in the first 3 bits of each connection ID
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigId(u8);
+
+impl ConfigId {
+    /// Creates a config rotation codepoint
+    ///
+    /// `None` is returned for values which don't fit in 3 bits and for `0b111`, which is
+    /// reserved for unroutable connection IDs.
+    #[inline]
+    pub const fn new(value: u8) -> Option<Self> {
+        if value < UNROUTABLE_CONFIG_ID {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the first octet of a connection ID of `len` bytes
+    #[inline]
+    pub fn first_octet(self, len: usize) -> u8 {
+        debug_assert!((1..=super::MAX_LEN).contains(&len));
+        self.0 << LEN_BITS | (len - 1) as u8
+    }
+}
+
+impl From<ConfigId> for u8 {
+    #[inline]
+    fn from(config_id: ConfigId) -> Self {
+        config_id.0
+    }
+}
+
+/// Recovers the server ID from structured connection IDs
+///
+/// Load balancers can use the decoder to route packets to the server that issued
+/// the connection ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decoder {
+    config_id: ConfigId,
+    server_id_len: usize,
+}
+
+impl Decoder {
+    /// Creates a decoder for connection IDs with the given config rotation codepoint and
+    /// server ID length
+    pub fn new(config_id: ConfigId, server_id_len: usize) -> Result<Self, Error> {
+        if !(MIN_SERVER_ID_LEN..=MAX_SERVER_ID_LEN).contains(&server_id_len) {
+            return Err(Error::InvalidLength);
+        }
+
+        Ok(Self {
+            config_id,
+            server_id_len,
+        })
+    }
+
+    /// Returns the length of the server IDs recovered by the decoder
+    #[inline]
+    pub fn server_id_len(&self) -> usize {
+        self.server_id_len
+    }
+
+    /// Returns the server ID encoded in the connection ID at the start of `buffer`
+    ///
+    /// `None` is returned if the connection ID was not generated with the decoder's
+    /// configuration.
+    #[inline]
+    pub fn decode<'a>(&self, buffer: &'a [u8]) -> Option<&'a [u8]> {
+        self.len(buffer)?;
+        buffer.get(1..=self.server_id_len)
+    }
+
+    /// Returns the length of the connection ID at the start of `buffer`
+    ///
+    /// `None` is returned if the connection ID was not generated with the decoder's
+    /// configuration.
+    #[inline]
+    pub fn len(&self, buffer: &[u8]) -> Option<usize> {
+        let first = *buffer.first()?;
+
+        if first >> LEN_BITS != self.config_id.0 {
+            return None;
+        }
+
+        let len = (first & LEN_MASK) as usize + 1;
+
+        if len < 1 + self.server_id_len + MIN_NONCE_LEN || len > buffer.len() {
+            return None;
+        }
+
+        Some(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_id_test() {
+        for value in 0..UNROUTABLE_CONFIG_ID {
+            let config_id = ConfigId::new(value).unwrap();
+            assert_eq!(u8::from(config_id), value);
+            assert_eq!(config_id.first_octet(20), value << LEN_BITS | 19);
+        }
+
+        for value in UNROUTABLE_CONFIG_ID..=u8::MAX {
+            assert_eq!(ConfigId::new(value), None);
+        }
+    }
+
+    #[test]
+    fn decoder_test() {
+        let config_id = ConfigId::new(1).unwrap();
+
+        for len in [0, MAX_SERVER_ID_LEN + 1] {
+            assert_eq!(
+                Some(Error::InvalidLength),
+                Decoder::new(config_id, len).err()
+            );
+        }
+
+        let decoder = Decoder::new(config_id, 2).unwrap();
+        let id = [config_id.first_octet(7), 0xab, 0xcd, 1, 2, 3, 4];
+        assert_eq!(decoder.len(&id), Some(7));
+        assert_eq!(decoder.decode(&id), Some(&[0xab, 0xcd][..]));
+
+        // the nonce is too short to contain a server ID of this length
+        let decoder = Decoder::new(config_id, 3).unwrap();
+        assert_eq!(decoder.decode(&id), None);
+
+        // a different config rotation is rejected
+        let decoder = Decoder::new(ConfigId::default(), 2).unwrap();
+        assert_eq!(decoder.decode(&id), None);
+    }
+}
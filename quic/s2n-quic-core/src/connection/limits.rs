@@ -4,17 +4,20 @@
 use crate::{
     ack,
     event::{api::SocketAddress, IntoEvent},
-    inet, stream,
+    inet,
+    path::MAX_AMPLIFICATION_FACTOR,
+    recovery, stream,
     transport::parameters::{
         AckDelayExponent, ActiveConnectionIdLimit, InitialFlowControlLimits, InitialMaxData,
         InitialMaxStreamDataBidiLocal, InitialMaxStreamDataBidiRemote, InitialMaxStreamDataUni,
         InitialMaxStreamsBidi, InitialMaxStreamsUni, InitialStreamLimits, MaxAckDelay,
         MaxDatagramFrameSize, MaxIdleTimeout, TransportParameters,
     },
+    varint::VarInt,
 };
 use core::{convert::TryInto, time::Duration};
 
-pub use crate::transport::parameters::ValidationError;
+pub use crate::{transmission::TrafficShapingConfig, transport::parameters::ValidationError};
 
 const MAX_HANDSHAKE_DURATION_DEFAULT: Duration = Duration::from_secs(10);
 
@@ -28,6 +31,10 @@ const MAX_HANDSHAKE_DURATION_DEFAULT: Duration = Duration::from_secs(10);
 //# middleboxes from losing state for UDP flows [GATEWAY].
 const MAX_KEEP_ALIVE_PERIOD_DEFAULT: Duration = Duration::from_secs(30);
 
+const DEADLOCK_DETECTION_TIMEOUT_DEFAULT: Duration = Duration::from_secs(5);
+
+const MAX_DATA_WINDOW_DEFAULT: u64 = 64 * 1024 * 1024;
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub struct ConnectionInfo<'a> {
@@ -60,10 +67,22 @@ pub struct Limits {
     pub(crate) max_active_connection_ids: ActiveConnectionIdLimit,
     pub(crate) ack_elicitation_interval: u8,
     pub(crate) ack_ranges_limit: u8,
+    pub(crate) max_ack_frame_size: u16,
     pub(crate) max_send_buffer_size: stream::limits::MaxSendBufferSize,
     pub(crate) max_handshake_duration: Duration,
     pub(crate) max_keep_alive_period: Duration,
     pub(crate) max_datagram_frame_size: MaxDatagramFrameSize,
+    pub(crate) traffic_shaping: TrafficShapingConfig,
+    pub(crate) fairness_ratio: u32,
+    pub(crate) loss_window: u8,
+    pub(crate) histograms: bool,
+    pub(crate) stream_data_checksum: bool,
+    pub(crate) rack_loss_detection: bool,
+    pub(crate) deadlock_detection_timeout: Duration,
+    pub(crate) amplification_factor: u32,
+    pub(crate) max_buffered_recv_bytes: Option<u64>,
+    pub(crate) min_key_update_interval: Duration,
+    pub(crate) max_data_window: u64,
 }
 
 impl Default for Limits {
@@ -98,10 +117,26 @@ impl Limits {
             max_active_connection_ids: ActiveConnectionIdLimit::RECOMMENDED,
             ack_elicitation_interval: ack::Settings::RECOMMENDED.ack_elicitation_interval,
             ack_ranges_limit: ack::Settings::RECOMMENDED.ack_ranges_limit,
+            max_ack_frame_size: ack::Settings::RECOMMENDED.max_ack_frame_size,
             max_send_buffer_size: stream::Limits::RECOMMENDED.max_send_buffer_size,
             max_handshake_duration: MAX_HANDSHAKE_DURATION_DEFAULT,
             max_keep_alive_period: MAX_KEEP_ALIVE_PERIOD_DEFAULT,
             max_datagram_frame_size: MaxDatagramFrameSize::DEFAULT,
+            traffic_shaping: TrafficShapingConfig {
+                pad_to_mtu: false,
+                min_packet_size: 0,
+                quantize_to: 0,
+            },
+            fairness_ratio: 0,
+            loss_window: recovery::loss_rate::DEFAULT_LOSS_WINDOW,
+            histograms: false,
+            stream_data_checksum: false,
+            rack_loss_detection: false,
+            deadlock_detection_timeout: DEADLOCK_DETECTION_TIMEOUT_DEFAULT,
+            amplification_factor: MAX_AMPLIFICATION_FACTOR,
+            max_buffered_recv_bytes: None,
+            min_key_update_interval: Duration::ZERO,
+            max_data_window: MAX_DATA_WINDOW_DEFAULT,
         }
     }
 
@@ -176,6 +211,16 @@ impl Limits {
     );
     setter!(with_ack_elicitation_interval, ack_elicitation_interval, u8);
     setter!(with_max_ack_ranges, ack_ranges_limit, u8);
+
+    /// Sets the maximum encoded size of the ACK frames sent on the connection
+    ///
+    /// ACK frames that would exceed the size leave out the ranges with the smallest packet
+    /// numbers. The peer treats the packets in those ranges as unacknowledged.
+    pub fn with_max_ack_frame_size(mut self, value: u16) -> Result<Self, ValidationError> {
+        self.max_ack_frame_size = value;
+        Ok(self)
+    }
+
     setter!(with_max_send_buffer_size, max_send_buffer_size, u32);
     setter!(
         with_max_handshake_duration,
@@ -184,12 +229,154 @@ impl Limits {
     );
     setter!(with_max_keep_alive_period, max_keep_alive_period, Duration);
 
+    /// Sets the number of round trips the connection packet loss rates are averaged over
+    ///
+    /// The value is clamped to between 1 and
+    /// [`MAX_LOSS_WINDOW`](crate::recovery::loss_rate::MAX_LOSS_WINDOW).
+    pub fn with_loss_window(mut self, rounds: u8) -> Result<Self, ValidationError> {
+        self.loss_window = rounds;
+        Ok(self)
+    }
+
+    /// Enables the RTT and throughput histograms of the connection
+    ///
+    /// The histograms are disabled by default since they take up around 4KB per connection.
+    pub fn with_histograms(mut self, enabled: bool) -> Result<Self, ValidationError> {
+        self.histograms = enabled;
+        Ok(self)
+    }
+
+    /// Adds a CRC32 checksum to the data of each STREAM frame
+    ///
+    /// The checksums are only used if the peer also enables them. A frame received with an
+    /// invalid checksum closes the connection with an `INTERNAL_ERROR`.
+    pub fn with_stream_data_checksum(mut self, enabled: bool) -> Result<Self, ValidationError> {
+        self.stream_data_checksum = enabled;
+        Ok(self)
+    }
+
+    /// Detects lost 1-RTT packets with RACK (RFC 8985)
+    ///
+    /// Instead of declaring packets lost once three later packets were acknowledged, RACK waits
+    /// for a reordering window which adapts to the reordering observed on the path. This reduces
+    /// spurious retransmissions on paths which reorder packets.
+    pub fn with_rack_loss_detection(mut self, enabled: bool) -> Result<Self, ValidationError> {
+        self.rack_loss_detection = enabled;
+        Ok(self)
+    }
+
+    /// Sets how long streams can be stalled on each other before a potential deadlock is reported
+    ///
+    /// A `PotentialDeadlock` event is emitted when a stream has been blocked by the peer's flow
+    /// control limits while received stream data was left unread for the whole timeout. This
+    /// happens when both applications wait for their writes to complete before reading. The
+    /// default is 5 seconds. A timeout of zero disables the detection.
+    pub fn with_deadlock_detection_timeout(
+        mut self,
+        timeout: Duration,
+    ) -> Result<Self, ValidationError> {
+        self.deadlock_detection_timeout = timeout;
+        Ok(self)
+    }
+
+    /// Sets how many bytes a server can send for each byte received before the client's address
+    /// is validated
+    ///
+    /// The default is the factor of 3 allowed by RFC 9000. Lower factors reduce how much a server
+    /// can be used to amplify an attack on a spoofed address, at the cost of more round trips
+    /// during the handshake when the server's first flight is large. With a factor below 3,
+    /// the server never sends a datagram larger than its remaining allowance. The value is
+    /// clamped to between 1 and 3.
+    pub fn with_amplification_factor(mut self, factor: u32) -> Result<Self, ValidationError> {
+        self.amplification_factor = factor;
+        Ok(self)
+    }
+
+    /// Caps the number of received bytes which the application has not read across all streams
+    ///
+    /// Without a cap, an application which falls behind on its reads can end up buffering a
+    /// full connection flow control window, which grows as the window is scaled to the
+    /// throughput of the connection. With a cap, the window is never scaled past `max_bytes`.
+    /// Once `max_bytes` are buffered, no more `MAX_DATA` frames are sent until the application
+    /// has read at least half of them. There is no cap by default.
+    ///
+    /// The limit sent to the peer during the handshake can't be revoked, so the peer can always
+    /// send the initial data window before the cap applies.
+    ///
+    /// The cap must be at least [`MINIMUM_MTU`](crate::path::MINIMUM_MTU) bytes. Smaller caps
+    /// would hold the window before the peer could fill a single packet and stall the
+    /// connection.
+    pub fn with_max_buffered_recv_bytes(mut self, max_bytes: u64) -> Result<Self, ValidationError> {
+        if max_bytes < crate::path::MINIMUM_MTU as u64 {
+            return Err(ValidationError::new(
+                "max_buffered_recv_bytes must be at least MINIMUM_MTU",
+            ));
+        }
+        self.max_buffered_recv_bytes = Some(max_bytes);
+        Ok(self)
+    }
+
+    /// Sets the largest connection flow control window the window can be scaled up to
+    ///
+    /// The connection window is scaled to cover two round trips at the bandwidth estimated by
+    /// the congestion controller, or at the rate the application reads if there is no
+    /// estimate. The scaled window never exceeds `max_bytes`, which bounds the amount of data
+    /// the peer can send ahead of the application. A window configured with
+    /// [`with_data_window`](Self::with_data_window) is maintained even if it is larger. The
+    /// default is 64 MiB.
+    pub fn with_max_data_window(mut self, max_bytes: u64) -> Result<Self, ValidationError> {
+        if VarInt::new(max_bytes).is_err() {
+            return Err(ValidationError::new(
+                "max_data_window must be a valid VarInt",
+            ));
+        }
+        self.max_data_window = max_bytes;
+        Ok(self)
+    }
+
+    /// Sets the minimum amount of time after a key update before the next key update is accepted
+    ///
+    /// The keys for the next key update are derived once the interval or the PTO has elapsed,
+    /// whichever is longer. Packets which the peer protects with newer keys before then can't be
+    /// decrypted, so a long interval stalls peers which update their keys frequently. A peer
+    /// which updates its keys again before the previous update was confirmed closes the
+    /// connection with a `KEY_UPDATE_ERROR` regardless of the interval. The default is zero.
+    pub fn with_min_key_update_interval(
+        mut self,
+        interval: Duration,
+    ) -> Result<Self, ValidationError> {
+        self.min_key_update_interval = interval;
+        Ok(self)
+    }
+
+    /// Pads the datagrams sent on the connection according to `config`
+    pub fn with_traffic_shaping(
+        mut self,
+        config: TrafficShapingConfig,
+    ) -> Result<Self, ValidationError> {
+        self.traffic_shaping = config;
+        Ok(self)
+    }
+
+    /// Sets the maximum ratio of stream data sent to stream data received while the peer is
+    /// also sending
+    ///
+    /// Once the connection has sent more than `ratio` times the data it received, new stream data
+    /// is held back until the peer catches up. The limit is disabled by default, which is also
+    /// the case when the ratio is set to 0. It should only be enabled for applications where
+    /// both endpoints send bulk data at the same time; a ratio of 10 is a reasonable start.
+    pub fn with_fairness_ratio(mut self, ratio: u32) -> Result<Self, ValidationError> {
+        self.fairness_ratio = ratio;
+        Ok(self)
+    }
+
     // internal APIs
 
     #[doc(hidden)]
     pub fn load_peer<A, B, C, D>(&mut self, peer_parameters: &TransportParameters<A, B, C, D>) {
         self.max_idle_timeout
             .load_peer(&peer_parameters.max_idle_timeout);
+        self.stream_data_checksum &= peer_parameters.stream_data_checksum.is_enabled();
     }
 
     #[doc(hidden)]
@@ -199,6 +386,7 @@ impl Limits {
             max_ack_delay: self.max_ack_delay.as_duration(),
             ack_ranges_limit: self.ack_ranges_limit,
             ack_elicitation_interval: self.ack_elicitation_interval,
+            max_ack_frame_size: self.max_ack_frame_size,
         }
     }
 
@@ -248,6 +436,61 @@ impl Limits {
     pub fn max_keep_alive_period(&self) -> Duration {
         self.max_keep_alive_period
     }
+
+    #[doc(hidden)]
+    pub fn traffic_shaping(&self) -> TrafficShapingConfig {
+        self.traffic_shaping
+    }
+
+    #[doc(hidden)]
+    pub fn fairness_ratio(&self) -> u32 {
+        self.fairness_ratio
+    }
+
+    #[doc(hidden)]
+    pub fn loss_window(&self) -> u8 {
+        self.loss_window
+    }
+
+    #[doc(hidden)]
+    pub fn histograms(&self) -> bool {
+        self.histograms
+    }
+
+    #[doc(hidden)]
+    pub fn stream_data_checksum(&self) -> bool {
+        self.stream_data_checksum
+    }
+
+    #[doc(hidden)]
+    pub fn rack_loss_detection(&self) -> bool {
+        self.rack_loss_detection
+    }
+
+    #[doc(hidden)]
+    pub fn deadlock_detection_timeout(&self) -> Duration {
+        self.deadlock_detection_timeout
+    }
+
+    #[doc(hidden)]
+    pub fn max_buffered_recv_bytes(&self) -> Option<u64> {
+        self.max_buffered_recv_bytes
+    }
+
+    #[doc(hidden)]
+    pub fn max_data_window(&self) -> u64 {
+        self.max_data_window
+    }
+
+    #[doc(hidden)]
+    pub fn min_key_update_interval(&self) -> Duration {
+        self.min_key_update_interval
+    }
+
+    #[doc(hidden)]
+    pub fn amplification_factor(&self) -> u32 {
+        self.amplification_factor.clamp(1, MAX_AMPLIFICATION_FACTOR)
+    }
 }
 
 /// Creates limits for a given connection
@@ -261,3 +504,34 @@ impl Limiter for Limits {
         *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_buffered_recv_bytes_validation_test() {
+        assert!(Limits::new().with_max_buffered_recv_bytes(0).is_err());
+        assert!(Limits::new()
+            .with_max_buffered_recv_bytes(crate::path::MINIMUM_MTU as u64 - 1)
+            .is_err());
+
+        let limits = Limits::new()
+            .with_max_buffered_recv_bytes(crate::path::MINIMUM_MTU as u64)
+            .unwrap();
+        assert_eq!(
+            limits.max_buffered_recv_bytes(),
+            Some(crate::path::MINIMUM_MTU as u64)
+        );
+    }
+
+    #[test]
+    fn max_data_window_validation_test() {
+        assert!(Limits::new()
+            .with_max_data_window(VarInt::MAX.as_u64() + 1)
+            .is_err());
+
+        let limits = Limits::new().with_max_data_window(1_000_000).unwrap();
+        assert_eq!(limits.max_data_window(), 1_000_000);
+    }
+}
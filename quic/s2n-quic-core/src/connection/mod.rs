@@ -3,9 +3,17 @@
 
 pub mod close;
 pub mod error;
+pub mod fairness;
+pub mod histogram;
 pub mod id;
 pub mod limits;
+pub mod state;
+pub mod token_bucket;
 
-pub use error::{Error, ProcessingError};
+pub use error::{ConnectionTimeoutReason, Error, ProcessingError};
+pub use fairness::BiDirectionalFairnessController;
+pub use histogram::{ConnectionHistograms, Histogram};
 pub use id::{InitialId, LocalId, PeerId, UnboundedId};
 pub use limits::Limits;
+pub use state::ConnectionStateSerde;
+pub use token_bucket::ConnectionTokenBucket;
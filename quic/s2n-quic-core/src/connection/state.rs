@@ -0,0 +1,198 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Connection state which can be persisted across a server restart
+//!
+//! Only state which is safe to carry over into a *new* connection is captured here. The
+//! following is intentionally never serialized:
+//!
+//! * Packet protection keys of any kind. 1-RTT keys are bound to a single connection and
+//!   persisting them would break forward secrecy. 0-RTT keys are not stored either; they are
+//!   re-derived by the TLS provider from the resumption secret carried in the session ticket.
+//! * Packet numbers and ACK state. Each connection starts new packet number spaces, so the
+//!   largest acknowledged packet numbers of a previous connection have no meaning.
+//! * Connection IDs, stateless reset tokens and other per-connection transport parameters.
+//! * Stream state and consumed flow control credit. Streams do not outlive their connection.
+//!
+//! What remains is the subset of transport parameters that govern 0-RTT and the path RTT.
+
+use crate::{transport::parameters::ZeroRttParameters, varint::VarInt};
+use core::time::Duration;
+use s2n_codec::{decoder_value, DecoderError, Encoder, EncoderValue};
+
+/// The version of the serialized format
+///
+/// This is bumped any time the encoding changes so state written by an older process is rejected
+/// rather than misinterpreted.
+const VERSION: u8 = 1;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStateSerde {
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+    //# To enable 0-RTT, endpoints store the values of the server transport
+    //# parameters with any session tickets it receives on the connection.
+    pub zero_rtt_parameters: ZeroRttParameters,
+
+    //= https://www.rfc-editor.org/rfc/rfc9002#section-6.2.2
+    //# Resumed connections over the same network MAY use the previous
+    //# connection's final smoothed RTT value as the resumed connection's
+    //# initial RTT.
+    pub smoothed_rtt: Duration,
+}
+
+impl ConnectionStateSerde {
+    pub fn new(zero_rtt_parameters: ZeroRttParameters, smoothed_rtt: Duration) -> Self {
+        Self {
+            zero_rtt_parameters,
+            smoothed_rtt,
+        }
+    }
+
+    /// Returns `true` if the restored state can be honored with the `current` parameters
+    ///
+    /// If this returns `false` 0-RTT MUST be rejected for the resumed connection.
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+    //# In particular, a server that accepts 0-RTT data
+    //# MUST NOT set values for the following parameters (Section 18.2) that
+    //# are smaller than the remembered values of the parameters.
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.1
+    //# A server MUST reject 0-RTT data if the restored values for transport
+    //# parameters cannot be supported.
+    pub fn can_restore(&self, current: &ZeroRttParameters) -> bool {
+        let remembered = &self.zero_rtt_parameters;
+
+        current.active_connection_id_limit >= remembered.active_connection_id_limit
+            && current.initial_max_data >= remembered.initial_max_data
+            && current.initial_max_stream_data_bidi_local
+                >= remembered.initial_max_stream_data_bidi_local
+            && current.initial_max_stream_data_bidi_remote
+                >= remembered.initial_max_stream_data_bidi_remote
+            && current.initial_max_stream_data_uni >= remembered.initial_max_stream_data_uni
+            && current.initial_max_streams_bidi >= remembered.initial_max_streams_bidi
+            && current.initial_max_streams_uni >= remembered.initial_max_streams_uni
+            && current.max_datagram_frame_size >= remembered.max_datagram_frame_size
+    }
+}
+
+decoder_value!(
+    impl<'a> ConnectionStateSerde {
+        fn decode(buffer: Buffer) -> Result<Self> {
+            let (version, buffer) = buffer.decode::<u8>()?;
+
+            if version != VERSION {
+                return Err(DecoderError::InvariantViolation(
+                    "unsupported connection state version",
+                ));
+            }
+
+            let (active_connection_id_limit, buffer) = buffer.decode()?;
+            let (initial_max_data, buffer) = buffer.decode()?;
+            let (initial_max_stream_data_bidi_local, buffer) = buffer.decode()?;
+            let (initial_max_stream_data_bidi_remote, buffer) = buffer.decode()?;
+            let (initial_max_stream_data_uni, buffer) = buffer.decode()?;
+            let (initial_max_streams_bidi, buffer) = buffer.decode()?;
+            let (initial_max_streams_uni, buffer) = buffer.decode()?;
+            let (max_datagram_frame_size, buffer) = buffer.decode()?;
+            let (smoothed_rtt, buffer) = buffer.decode::<VarInt>()?;
+
+            let zero_rtt_parameters = ZeroRttParameters {
+                active_connection_id_limit,
+                initial_max_data,
+                initial_max_stream_data_bidi_local,
+                initial_max_stream_data_bidi_remote,
+                initial_max_stream_data_uni,
+                initial_max_streams_bidi,
+                initial_max_streams_uni,
+                max_datagram_frame_size,
+            };
+            let smoothed_rtt = Duration::from_micros(smoothed_rtt.as_u64());
+
+            let state = Self {
+                zero_rtt_parameters,
+                smoothed_rtt,
+            };
+
+            Ok((state, buffer))
+        }
+    }
+);
+
+impl EncoderValue for ConnectionStateSerde {
+    fn encode<E: Encoder>(&self, encoder: &mut E) {
+        let ZeroRttParameters {
+            active_connection_id_limit,
+            initial_max_data,
+            initial_max_stream_data_bidi_local,
+            initial_max_stream_data_bidi_remote,
+            initial_max_stream_data_uni,
+            initial_max_streams_bidi,
+            initial_max_streams_uni,
+            max_datagram_frame_size,
+        } = &self.zero_rtt_parameters;
+
+        // an RTT that doesn't fit in a VarInt is well beyond any idle timeout so saturate
+        let smoothed_rtt =
+            VarInt::try_from(self.smoothed_rtt.as_micros() as u64).unwrap_or(VarInt::MAX);
+
+        encoder.encode(&VERSION);
+        encoder.encode(active_connection_id_limit);
+        encoder.encode(initial_max_data);
+        encoder.encode(initial_max_stream_data_bidi_local);
+        encoder.encode(initial_max_stream_data_bidi_remote);
+        encoder.encode(initial_max_stream_data_uni);
+        encoder.encode(initial_max_streams_bidi);
+        encoder.encode(initial_max_streams_uni);
+        encoder.encode(max_datagram_frame_size);
+        encoder.encode(&smoothed_rtt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_codec::{assert_codec_round_trip_value, DecoderBuffer};
+
+    fn state() -> ConnectionStateSerde {
+        let zero_rtt_parameters = ZeroRttParameters {
+            active_connection_id_limit: VarInt::from_u8(4),
+            initial_max_data: VarInt::from_u32(1_000_000),
+            initial_max_stream_data_bidi_local: VarInt::from_u32(100_000),
+            initial_max_stream_data_bidi_remote: VarInt::from_u32(100_000),
+            initial_max_stream_data_uni: VarInt::from_u32(50_000),
+            initial_max_streams_bidi: VarInt::from_u8(100),
+            initial_max_streams_uni: VarInt::from_u8(100),
+            max_datagram_frame_size: VarInt::from_u16(1200),
+        };
+        ConnectionStateSerde::new(zero_rtt_parameters, Duration::from_millis(25))
+    }
+
+    #[test]
+    fn round_trip_test() {
+        assert_codec_round_trip_value!(ConnectionStateSerde, state());
+        assert_codec_round_trip_value!(ConnectionStateSerde, ConnectionStateSerde::default());
+    }
+
+    #[test]
+    fn unknown_version_test() {
+        let mut bytes = s2n_codec::testing::encode(&state()).unwrap();
+        bytes[0] = VERSION + 1;
+
+        let buffer = DecoderBuffer::new(&bytes);
+        assert!(buffer.decode::<ConnectionStateSerde>().is_err());
+    }
+
+    #[test]
+    fn can_restore_test() {
+        let state = state();
+        let mut current = state.zero_rtt_parameters;
+        assert!(state.can_restore(&current));
+
+        current.initial_max_data = VarInt::from_u32(2_000_000);
+        assert!(state.can_restore(&current));
+
+        // reducing any remembered limit prevents restoring the state
+        current.initial_max_streams_uni = VarInt::from_u8(99);
+        assert!(!state.can_restore(&current));
+    }
+}
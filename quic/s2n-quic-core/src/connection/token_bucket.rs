@@ -0,0 +1,314 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A token bucket that limits the rate at which a connection sends stream data
+//!
+//! A single bucket is shared by all of the streams on a connection. Tokens are added at the
+//! configured rate and every byte a stream writes to a packet removes one token. While the bucket
+//! holds fewer than [`MIN_SEND_TOKENS`] tokens, no stream is able to send new data, regardless of
+//! its own limits.
+
+use crate::{
+    recovery::{bandwidth::Bandwidth, K_GRANULARITY},
+    time::{timer, Timer, Timestamp},
+};
+use core::time::Duration;
+
+/// The amount of time worth of tokens the bucket can hold, which bounds the size of bursts
+pub const MAX_BURST_DURATION: Duration = Duration::from_millis(10);
+
+/// The number of tokens the bucket needs to hold before streams are allowed to send
+///
+/// This prevents many small packets from being written while the bucket is refilling.
+pub const MIN_SEND_TOKENS: u64 = crate::path::MINIMUM_MTU as u64;
+
+#[derive(Clone, Debug)]
+pub struct ConnectionTokenBucket {
+    /// The rate at which tokens are added to the bucket
+    rate: Bandwidth,
+    /// The maximum number of tokens the bucket can hold
+    capacity: u64,
+    /// The number of tokens currently in the bucket, in bytes
+    tokens: u64,
+    /// The time up to which tokens have been added to the bucket
+    last_refill: Option<Timestamp>,
+    /// Expires once the bucket holds at least `MIN_SEND_TOKENS` tokens again
+    refill_timer: Timer,
+}
+
+impl ConnectionTokenBucket {
+    /// Creates a full bucket that is filled at the given `rate`
+    pub fn new(rate: Bandwidth) -> Self {
+        let capacity = Self::capacity(rate);
+
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: None,
+            refill_timer: Timer::default(),
+        }
+    }
+
+    /// Returns the rate at which tokens are added to the bucket
+    #[inline]
+    pub fn rate(&self) -> Bandwidth {
+        self.rate
+    }
+
+    /// Changes the rate at which tokens are added to the bucket
+    ///
+    /// Tokens already in the bucket are kept, up to the capacity for the new rate.
+    pub fn set_rate(&mut self, rate: Bandwidth) {
+        self.rate = rate;
+        self.capacity = Self::capacity(rate);
+        self.tokens = self.tokens.min(self.capacity);
+        self.arm_refill_timer();
+    }
+
+    /// Returns the number of tokens in the bucket as of the last refill
+    #[inline]
+    pub fn tokens(&self) -> u64 {
+        self.tokens
+    }
+
+    /// Returns `true` if the bucket holds enough tokens for streams to send new data
+    #[inline]
+    pub fn can_send(&self) -> bool {
+        self.tokens >= MIN_SEND_TOKENS
+    }
+
+    /// Adds the tokens accumulated since the last refill
+    pub fn refill(&mut self, now: Timestamp) {
+        let last_refill = if let Some(last_refill) = self.last_refill {
+            last_refill
+        } else {
+            self.last_refill = Some(now);
+            return;
+        };
+
+        if self.tokens >= self.capacity {
+            self.last_refill = Some(now);
+            return;
+        }
+
+        let added = self.rate * now.saturating_duration_since(last_refill);
+
+        // keep the previous refill time so fractions of a token keep accumulating
+        if added == 0 {
+            return;
+        }
+
+        if self.tokens + added >= self.capacity {
+            self.tokens = self.capacity;
+            self.last_refill = Some(now);
+        } else {
+            self.tokens += added;
+            // only advance by the time it took to accumulate the added tokens
+            self.last_refill = Some(last_refill + added / self.rate);
+        }
+    }
+
+    /// Removes `bytes` tokens from the bucket after stream data was written to a packet
+    ///
+    /// The bucket is allowed to drain completely, even if `bytes` exceeds the number of
+    /// tokens in the bucket.
+    pub fn on_send(&mut self, bytes: u64) {
+        self.tokens = self.tokens.saturating_sub(bytes);
+        self.arm_refill_timer();
+    }
+
+    /// Called when the connection timer expires
+    pub fn on_timeout(&mut self, now: Timestamp) {
+        if self.refill_timer.poll_expiration(now).is_ready() {
+            self.refill(now);
+            self.arm_refill_timer();
+        }
+    }
+
+    fn arm_refill_timer(&mut self) {
+        match self.last_refill {
+            Some(last_refill) if !self.can_send() && self.rate != Bandwidth::ZERO => {
+                let missing = MIN_SEND_TOKENS - self.tokens;
+                // timers may expire up to K_GRANULARITY early so make sure enough tokens
+                // have accumulated by then
+                let wait = missing / self.rate + K_GRANULARITY;
+                self.refill_timer.set(last_refill + wait);
+            }
+            _ => self.refill_timer.cancel(),
+        }
+    }
+
+    fn capacity(rate: Bandwidth) -> u64 {
+        if rate == Bandwidth::ZERO {
+            return 0;
+        }
+
+        (rate * MAX_BURST_DURATION).max(MIN_SEND_TOKENS)
+    }
+}
+
+impl timer::Provider for ConnectionTokenBucket {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.refill_timer.timers(query)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{testing::Clock, Clock as _};
+
+    const MBPS: u64 = 1_000_000;
+
+    #[test]
+    fn refill_test() {
+        let mut clock = Clock::default();
+        let mut bucket = ConnectionTokenBucket::new(Bandwidth::from_bits_per_second(MBPS));
+
+        // 1 Mbps for 10ms is 1250 bytes
+        assert_eq!(1250, bucket.tokens());
+        assert!(bucket.can_send());
+
+        bucket.refill(clock.get_time());
+        bucket.on_send(1250);
+        assert!(!bucket.can_send());
+
+        // 1 Mbps for 4ms is 500 bytes
+        clock.inc_by(Duration::from_millis(4));
+        bucket.refill(clock.get_time());
+        assert_eq!(500, bucket.tokens());
+        assert!(!bucket.can_send());
+
+        // the bucket doesn't hold more than its capacity
+        clock.inc_by(Duration::from_secs(1));
+        bucket.refill(clock.get_time());
+        assert_eq!(1250, bucket.tokens());
+    }
+
+    #[test]
+    fn partial_token_test() {
+        let mut clock = Clock::default();
+        let mut bucket = ConnectionTokenBucket::new(Bandwidth::from_bits_per_second(MBPS));
+        bucket.refill(clock.get_time());
+        bucket.on_send(1250);
+
+        // 1 Mbps adds a byte every 8us so refilling every 5us should still add tokens
+        for _ in 0..1600 {
+            clock.inc_by(Duration::from_micros(5));
+            bucket.refill(clock.get_time());
+        }
+
+        assert_eq!(1000, bucket.tokens());
+    }
+
+    #[test]
+    fn refill_timer_test() {
+        let mut clock = Clock::default();
+        let mut bucket = ConnectionTokenBucket::new(Bandwidth::from_bits_per_second(MBPS));
+        bucket.refill(clock.get_time());
+        assert!(!bucket.refill_timer.is_armed());
+
+        bucket.on_send(2000);
+        assert!(!bucket.can_send());
+        assert!(bucket.refill_timer.is_armed());
+
+        // 1200 bytes at 1 Mbps takes 9.6ms
+        clock.inc_by(Duration::from_millis(9));
+        bucket.on_timeout(clock.get_time());
+        assert!(!bucket.can_send());
+
+        clock.inc_by(Duration::from_millis(2));
+        bucket.on_timeout(clock.get_time());
+        assert!(bucket.can_send());
+        assert!(!bucket.refill_timer.is_armed());
+    }
+
+    #[test]
+    fn zero_rate_test() {
+        let mut clock = Clock::default();
+        let mut bucket = ConnectionTokenBucket::new(Bandwidth::ZERO);
+        bucket.refill(clock.get_time());
+        assert!(!bucket.can_send());
+
+        clock.inc_by(Duration::from_secs(10));
+        bucket.refill(clock.get_time());
+        assert!(!bucket.can_send());
+        assert!(!bucket.refill_timer.is_armed());
+
+        bucket.set_rate(Bandwidth::from_bits_per_second(MBPS));
+        assert!(bucket.refill_timer.is_armed());
+    }
+
+    #[test]
+    fn set_rate_test() {
+        let mut bucket = ConnectionTokenBucket::new(Bandwidth::from_bits_per_second(10 * MBPS));
+        assert_eq!(12_500, bucket.tokens());
+
+        bucket.set_rate(Bandwidth::from_bits_per_second(MBPS));
+        assert_eq!(1250, bucket.tokens());
+        assert_eq!(Bandwidth::from_bits_per_second(MBPS), bucket.rate());
+    }
+
+    /// Ten streams, each limited to 500 Kbps, share a 1 Mbps connection bucket
+    #[test]
+    fn shared_bucket_test() {
+        const STREAMS: usize = 10;
+        const PACKET_LEN: u64 = 1200;
+        const DURATION: Duration = Duration::from_secs(10);
+
+        let mut clock = Clock::default();
+        let start = clock.get_time();
+
+        let connection_rate = Bandwidth::from_bits_per_second(MBPS);
+        let mut connection = ConnectionTokenBucket::new(connection_rate);
+        let mut streams: Vec<_> = (0..STREAMS)
+            .map(|_| ConnectionTokenBucket::new(Bandwidth::from_bits_per_second(MBPS / 2)))
+            .collect();
+
+        let mut sent = [0u64; STREAMS];
+
+        for tick in 0..DURATION.as_millis() as usize {
+            clock.inc_by(Duration::from_millis(1));
+            let now = clock.get_time();
+
+            connection.refill(now);
+
+            // rotate the first stream to send so every stream gets a turn
+            for offset in 0..STREAMS {
+                let index = (tick + offset) % STREAMS;
+                let stream = &mut streams[index];
+                stream.refill(now);
+
+                if !stream.can_send() || !connection.can_send() {
+                    continue;
+                }
+
+                let len = PACKET_LEN.min(stream.tokens()).min(connection.tokens());
+                stream.on_send(len);
+                connection.on_send(len);
+                sent[index] += len;
+            }
+        }
+
+        let elapsed = clock.get_time() - start;
+        let total: u64 = sent.iter().sum();
+        let limit = connection_rate * elapsed + connection.capacity;
+
+        // the streams would be able to send 5 Mbps without the connection limit
+        assert!(total <= limit, "sent {} bytes, limit {}", total, limit);
+        assert!(
+            total >= limit * 9 / 10,
+            "sent {} bytes, limit {}",
+            total,
+            limit
+        );
+
+        // every stream got a share of the connection bandwidth
+        for len in sent.iter() {
+            assert!(*len > 0);
+        }
+    }
+}
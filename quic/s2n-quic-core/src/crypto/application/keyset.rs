@@ -13,7 +13,7 @@ use crate::{
     time::{timer, Timer, Timestamp},
     transport,
 };
-use core::ops;
+use core::{ops, time::Duration};
 use s2n_codec::EncoderBuffer;
 
 pub struct KeySet<K> {
@@ -30,6 +30,8 @@ pub struct KeySet<K> {
     aead_integrity_limit: u64,
     /// The number of times the key has been rotated
     generation: u16,
+    /// Set when the peer initiated a key update until a packet is sent with the updated keys
+    peer_update_unconfirmed: bool,
 
     /// Set of keys for the current and next phase
     crypto: KeyArray<K>,
@@ -66,6 +68,7 @@ impl<K: OneRttKey> KeySet<K> {
             packet_decryption_failures: 0,
             aead_integrity_limit,
             generation: 0,
+            peer_update_unconfirmed: false,
             crypto: KeyArray([active_key, next_key]),
             limits,
         }
@@ -110,11 +113,16 @@ impl<K: OneRttKey> KeySet<K> {
     /// enforced.
     ///
     /// Returns the decrypted packet and generation if the key phase was rotated.
+    ///
+    /// After a rotation, the next key is derived once `pto` or the configured
+    /// `min_key_update_interval` has elapsed, whichever is longer. Until then, packets in the
+    /// other key phase are decrypted with the previous key.
     pub fn decrypt_packet<'a>(
         &mut self,
         packet: EncryptedShort<'a>,
         largest_acknowledged_packet_number: PacketNumber,
-        pto: Timestamp,
+        now: Timestamp,
+        pto: Duration,
     ) -> Result<(CleartextShort<'a>, Option<u16>), ProcessingError> {
         let mut phase_to_use = self.key_phase() as u8;
         let packet_phase = packet.key_phase();
@@ -148,6 +156,23 @@ impl<K: OneRttKey> KeySet<K> {
 
         match result {
             Ok(packet) => {
+                if packet_phase != self.key_phase() && self.key_update_in_progress() {
+                    // The other key phase still holds the previous key, so this packet was
+                    // delayed from before the last rotation and doesn't start a new key update.
+                    return Ok((packet, None));
+                }
+
+                if packet_phase != self.key_phase() && self.peer_update_unconfirmed {
+                    //= https://www.rfc-editor.org/rfc/rfc9001#section-6.2
+                    //# If an endpoint detects a second update before it has
+                    //# sent any packets with updated keys containing an acknowledgment for
+                    //# the packet that initiated the key update, it indicates that its peer
+                    //# has updated keys twice without awaiting confirmation.  An endpoint
+                    //# MAY treat such consecutive key updates as a connection error of type
+                    //# KEY_UPDATE_ERROR.
+                    return Err(transport::Error::KEY_UPDATE_ERROR.into());
+                }
+
                 let generation = if packet_phase != self.key_phase() {
                     //= https://www.rfc-editor.org/rfc/rfc9001#section-6.2
                     //# Sending keys MUST be updated before sending an
@@ -172,7 +197,8 @@ impl<K: OneRttKey> KeySet<K> {
                     //# An endpoint SHOULD
                     //# retain old keys for some time after unprotecting a packet sent using
                     //# the new keys.
-                    self.set_derivation_timer(pto);
+                    self.set_derivation_timer(now + pto.max(self.limits.min_key_update_interval));
+                    self.peer_update_unconfirmed = true;
                     Some(self.generation)
                 } else {
                     None
@@ -251,6 +277,11 @@ impl<K: OneRttKey> KeySet<K> {
 
         let r = f(buffer, self.crypto[phase].key(), phase)?;
 
+        // Any packet sent after the peer's key update is treated as confirming it. This is more
+        // lenient than waiting for a packet which acknowledges the update. If the packet
+        // initiated a local key update instead, the peer's response isn't a consecutive update.
+        self.peer_update_unconfirmed = false;
+
         //= https://www.rfc-editor.org/rfc/rfc9001#section-6.6
         //# Endpoints MUST count the number of encrypted packets for each set of
         //# keys.
@@ -339,6 +370,57 @@ mod tests {
     use core::time::Duration;
     use s2n_codec::{DecoderBufferMut, EncoderBuffer};
 
+    const PTO: Duration = Duration::from_millis(100);
+
+    /// Decrypts a packet protected in `key_phase` and returns the new generation if the key phase
+    /// was rotated
+    fn decrypt(
+        keyset: &mut KeySet<TestKey>,
+        key_phase: KeyPhase,
+        packet_number: u8,
+        now: Timestamp,
+    ) -> Result<Option<u16>, ProcessingError> {
+        let mut data = [0; 128];
+        data[0] = key_phase.into_packet_tag_mask();
+        // the packet number follows the tag and the 20 byte destination connection ID
+        data[21] = packet_number;
+
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let decoder_buffer = DecoderBufferMut::new(&mut data);
+        let (encoded_packet, _remaining) =
+            ProtectedShort::decode(0, decoder_buffer, &connection_info, &20).unwrap();
+
+        let largest_acked =
+            PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(0));
+        let encrypted_packet = encoded_packet
+            .unprotect(&TestHeaderKey::default(), largest_acked)
+            .unwrap();
+
+        keyset
+            .decrypt_packet(encrypted_packet, largest_acked, now, PTO)
+            .map(|(_packet, generation)| generation)
+    }
+
+    /// Encrypts a packet with the keyset and returns the key phase which was used
+    fn encrypt(keyset: &mut KeySet<TestKey>) -> KeyPhase {
+        let mut encoder_bytes = [0; 512];
+        let buffer = EncoderBuffer::new(&mut encoder_bytes);
+        let mut decoder_bytes = [0; 512];
+        let mut key_phase = None;
+
+        keyset
+            .encrypt_packet(buffer, |buffer, _key, phase| {
+                key_phase = Some(phase);
+                let payload = ProtectedPayload::new(0, &mut decoder_bytes);
+
+                Ok((payload, buffer))
+            })
+            .unwrap();
+
+        key_phase.unwrap()
+    }
+
     #[test]
     fn test_key_derivation_timer() {
         let mut clock = Clock::default();
@@ -367,6 +449,150 @@ mod tests {
         assert_eq!(keyset.crypto[KeyPhase::Zero].key().derivations, 2);
     }
 
+    //= https://www.rfc-editor.org/rfc/rfc9001#section-6.2
+    //= type=test
+    //# The endpoint MUST update its
+    //# send keys to the corresponding key phase in response, as described in
+    //# Section 6.1.
+    #[test]
+    fn test_peer_key_update() {
+        let mut clock = Clock::default();
+        let mut keyset = KeySet::new(TestKey::default(), Default::default());
+
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 1, clock.get_time()),
+            Ok(None)
+        );
+        assert_eq!(encrypt(&mut keyset), KeyPhase::Zero);
+
+        // The peer initiates a key update
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::One, 3, clock.get_time()),
+            Ok(Some(1))
+        );
+        assert_eq!(keyset.key_phase(), KeyPhase::One);
+        assert_eq!(keyset.active_key().key().derivations, 1);
+        assert!(keyset.key_update_in_progress());
+        assert_eq!(encrypt(&mut keyset), KeyPhase::One);
+
+        // The previous key is retained for packets which were delayed, and those don't rotate
+        // the key phase back
+        assert_eq!(keyset.crypto[KeyPhase::Zero].key().derivations, 0);
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 2, clock.get_time()),
+            Ok(None)
+        );
+        assert_eq!(keyset.key_phase(), KeyPhase::One);
+
+        // The previous key is retired after the PTO and replaced with the next key
+        clock.inc_by(PTO);
+        keyset.on_timeout(clock.get_time());
+        assert!(!keyset.key_update_in_progress());
+        assert_eq!(keyset.crypto[KeyPhase::Zero].key().derivations, 2);
+    }
+
+    //= https://www.rfc-editor.org/rfc/rfc9001#section-6.2
+    //= type=test
+    //# An endpoint
+    //# MAY treat such consecutive key updates as a connection error of type
+    //# KEY_UPDATE_ERROR.
+    #[test]
+    fn test_consecutive_peer_key_updates() {
+        let mut clock = Clock::default();
+        let mut keyset = KeySet::new(TestKey::default(), Default::default());
+
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::One, 1, clock.get_time()),
+            Ok(Some(1))
+        );
+        clock.inc_by(PTO);
+        keyset.on_timeout(clock.get_time());
+
+        // The peer updates its keys again before any packet was sent with the updated keys
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 2, clock.get_time()),
+            Err(ProcessingError::ConnectionError(
+                transport::Error::KEY_UPDATE_ERROR.into()
+            ))
+        );
+        assert_eq!(keyset.key_phase(), KeyPhase::One);
+    }
+
+    #[test]
+    fn test_confirmed_peer_key_updates() {
+        let mut clock = Clock::default();
+        let mut keyset = KeySet::new(TestKey::default(), Default::default());
+
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::One, 1, clock.get_time()),
+            Ok(Some(1))
+        );
+        assert_eq!(encrypt(&mut keyset), KeyPhase::One);
+        clock.inc_by(PTO);
+        keyset.on_timeout(clock.get_time());
+
+        // The peer waited for the update to be confirmed, so it can update again right away
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 2, clock.get_time()),
+            Ok(Some(2))
+        );
+        assert_eq!(keyset.key_phase(), KeyPhase::Zero);
+        assert_eq!(keyset.active_key().key().derivations, 2);
+    }
+
+    #[test]
+    fn test_local_key_update_after_peer_key_update() {
+        let mut clock = Clock::default();
+        let mut keyset = KeySet::new(TestKey::default(), Default::default());
+
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::One, 1, clock.get_time()),
+            Ok(Some(1))
+        );
+        clock.inc_by(PTO);
+        keyset.on_timeout(clock.get_time());
+
+        // The test key's confidentiality limit is within the key update window, so the
+        // encryption after the first one initiates a key update
+        assert_eq!(encrypt(&mut keyset), KeyPhase::One);
+        assert_eq!(encrypt(&mut keyset), KeyPhase::Zero);
+
+        // The peer's response completes the key update
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 2, clock.get_time()),
+            Ok(Some(2))
+        );
+        assert_eq!(keyset.key_phase(), KeyPhase::Zero);
+    }
+
+    #[test]
+    fn test_min_key_update_interval() {
+        let mut clock = Clock::default();
+        let mut limits = limited::Limits::default();
+        limits.min_key_update_interval = PTO * 10;
+        let mut keyset = KeySet::new(TestKey::default(), limits);
+
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::One, 1, clock.get_time()),
+            Ok(Some(1))
+        );
+        assert_eq!(encrypt(&mut keyset), KeyPhase::One);
+
+        // The next key isn't derived until the interval has elapsed
+        clock.inc_by(PTO);
+        keyset.on_timeout(clock.get_time());
+        assert!(keyset.key_update_in_progress());
+        assert_eq!(keyset.crypto[KeyPhase::Zero].key().derivations, 0);
+
+        clock.inc_by(PTO * 9);
+        keyset.on_timeout(clock.get_time());
+        assert!(!keyset.key_update_in_progress());
+        assert_eq!(
+            decrypt(&mut keyset, KeyPhase::Zero, 2, clock.get_time()),
+            Ok(Some(2))
+        );
+    }
+
     #[test]
     fn test_key_set() {
         //= https://www.rfc-editor.org/rfc/rfc9001#section-6.3
@@ -433,6 +659,7 @@ mod tests {
                 encrypted_packet,
                 PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(0)),
                 clock.get_time(),
+                PTO,
             )
             .is_err());
         assert_eq!(keyset.decryption_error_count(), 1);
@@ -476,6 +703,7 @@ mod tests {
                     encrypted_packet,
                     PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(0)),
                     clock.get_time(),
+                    PTO,
                 )
                 .err(),
             Some(ProcessingError::ConnectionError(
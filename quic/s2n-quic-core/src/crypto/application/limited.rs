@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{crypto::OneRttKey, path::MaxMtu};
+use core::time::Duration;
 
 //= https://www.rfc-editor.org/rfc/rfc9001#section-6.6
 //# Endpoints MUST count the number of encrypted packets for each set of
@@ -27,6 +28,10 @@ pub struct Limits {
     pub opener_optimization_threshold: u64,
     /// The maximum MTU the connection will ever encrypt/decrypt
     pub max_mtu: MaxMtu,
+    /// The minimum amount of time after a key update before the next key is derived
+    ///
+    /// Packets of a key update initiated by the peer within this time can't be decrypted.
+    pub min_key_update_interval: Duration,
 }
 
 impl Default for Limits {
@@ -36,6 +41,7 @@ impl Default for Limits {
             sealer_optimization_threshold: 100,
             opener_optimization_threshold: 100,
             max_mtu: MaxMtu::default(),
+            min_key_update_interval: Duration::ZERO,
         }
     }
 }
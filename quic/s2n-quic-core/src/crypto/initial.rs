@@ -23,11 +23,24 @@ pub trait InitialKey: crypto::Key + Sized {
 /// caught by the type system.
 pub trait InitialHeaderKey: crypto::HeaderKey {}
 
+/// The QUIC version 1 wire value
+pub const QUIC_VERSION_1: u32 = 0x0000_0001;
+
+/// The QUIC version 2 wire value
+///
+/// See https://www.rfc-editor.org/rfc/rfc9369#section-3.1
+pub const QUIC_VERSION_2: u32 = 0x6b33_43cf;
+
 //= https://www.rfc-editor.org/rfc/rfc9001#section-5.2
 //# initial_salt = 0x38762cf7f55934b34d179ae6a4c80cadccbb7f0a
 
 pub const INITIAL_SALT: [u8; 20] = hex!("38762cf7f55934b34d179ae6a4c80cadccbb7f0a");
 
+/// The salt used to derive the Initial secrets for QUIC version 2
+///
+/// See https://www.rfc-editor.org/rfc/rfc9369#section-3.3.1
+pub const INITIAL_SALT_V2: [u8; 20] = hex!("0dede3def700a6db819381be6e269dcbf9bd2ed9");
+
 //= https://www.rfc-editor.org/rfc/rfc9001#section-5.2
 //# client_initial_secret = HKDF-Expand-Label(initial_secret,
 //#                                           "client in", "",
@@ -74,6 +87,24 @@ pub const EXAMPLE_SERVER_INITIAL_SECRET: [u8; 32] = hex!(
     "
 );
 
+// The QUIC version 2 Initial secrets derived from `EXAMPLE_DCID`
+//
+// See https://www.rfc-editor.org/rfc/rfc9369#appendix-A.1
+
+pub const EXAMPLE_CLIENT_INITIAL_SECRET_V2: [u8; 32] = hex!(
+    "
+    14ec9d6eb9fd7af83bf5a668bc17a7e2
+    83766aade7ecd0891f70f9ff7f4bf47b
+    "
+);
+
+pub const EXAMPLE_SERVER_INITIAL_SECRET_V2: [u8; 32] = hex!(
+    "
+    0263db1782731bf4588e7e4d93b74639
+    07cb8cd8200b5da55a8bd488eafc37c1
+    "
+);
+
 //= https://www.rfc-editor.org/rfc/rfc9001#section-A.2
 //# The client sends an Initial packet.  The unprotected payload of this
 //# packet contains the following CRYPTO frame, plus enough PADDING
@@ -15,12 +15,27 @@ use core::{
     task::{Context, Poll, Waker},
 };
 
-#[derive(Debug, Default)]
+/// The default number of datagrams queued for sending
+const DEFAULT_SEND_QUEUE_CAPACITY: usize = 200;
+
+/// The default number of received datagrams queued for the application
+const DEFAULT_RECV_QUEUE_CAPACITY: usize = 1000;
+
+#[derive(Debug)]
 pub struct Endpoint {
     send_queue_capacity: usize,
     recv_queue_capacity: usize,
 }
 
+impl Default for Endpoint {
+    fn default() -> Self {
+        Self {
+            send_queue_capacity: DEFAULT_SEND_QUEUE_CAPACITY,
+            recv_queue_capacity: DEFAULT_RECV_QUEUE_CAPACITY,
+        }
+    }
+}
+
 impl Endpoint {
     /// Creates a builder for the default datagram endpoint
     pub fn builder() -> EndpointBuilder {
@@ -29,12 +44,21 @@ impl Endpoint {
 }
 
 /// A builder for the default datagram endpoint
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct EndpointBuilder {
     send_queue_capacity: usize,
     recv_queue_capacity: usize,
 }
 
+impl Default for EndpointBuilder {
+    fn default() -> Self {
+        Self {
+            send_queue_capacity: DEFAULT_SEND_QUEUE_CAPACITY,
+            recv_queue_capacity: DEFAULT_RECV_QUEUE_CAPACITY,
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum BuilderError {
@@ -109,6 +133,8 @@ pub struct Receiver {
     waker: Option<Waker>,
     max_datagram_frame_size: u64,
     error: Option<connection::Error>,
+    /// The length of the last datagram which was dropped from the full queue
+    dropped: Option<usize>,
 }
 
 impl Receiver {
@@ -122,6 +148,14 @@ impl Receiver {
         self.queue.pop_front()
     }
 
+    /// Returns the number of received datagrams waiting on the queue
+    ///
+    /// Once the depth reaches the queue capacity, each newly received datagram drops the oldest
+    /// one. Applications can use this to read more frequently before that happens.
+    pub fn datagram_queue_depth(&self) -> usize {
+        self.queue.len()
+    }
+
     /// Dequeues a datagram received from the peer.
     ///
     /// # Return value
@@ -154,8 +188,8 @@ impl super::Receiver for Receiver {
         }
         // The oldest datagram on the queue is popped off if the queue is full.
         // Configure this behavior by implementing a custom Receiver for datagrams.
-        if self.queue.len() == self.capacity {
-            self.queue.pop_front();
+        if self.queue.len() >= self.capacity {
+            self.dropped = self.queue.pop_front().map(|datagram| datagram.len());
         }
 
         self.queue
@@ -167,6 +201,11 @@ impl super::Receiver for Receiver {
         }
     }
 
+    #[inline]
+    fn take_dropped_datagram(&mut self) -> Option<usize> {
+        self.dropped.take()
+    }
+
     fn on_connection_error(&mut self, error: connection::Error) {
         self.error = Some(error);
         if let Some(w) = self.waker.take() {
@@ -188,7 +227,7 @@ pub struct ReceiverBuilder {
 impl Default for ReceiverBuilder {
     fn default() -> Self {
         Self {
-            queue_capacity: 200,
+            queue_capacity: DEFAULT_RECV_QUEUE_CAPACITY,
             max_datagram_frame_size: MaxDatagramFrameSize::RECOMMENDED,
         }
     }
@@ -214,6 +253,7 @@ impl ReceiverBuilder {
             waker: None,
             max_datagram_frame_size: self.max_datagram_frame_size,
             error: None,
+            dropped: None,
         })
     }
 }
@@ -226,6 +266,9 @@ pub struct Sender {
     max_packet_space: usize,
     smoothed_packet_size: f64,
     waker: Option<Waker>,
+    /// The largest datagram the peer accepts
+    peer_max_datagram_payload: u64,
+    /// The largest datagram that can currently be sent, given the peer's limit and the path MTU
     max_datagram_payload: u64,
     error: Option<connection::Error>,
 }
@@ -245,6 +288,10 @@ pub enum DatagramError {
     ExceedsPeerTransportLimits,
     #[non_exhaustive]
     ConnectionError { error: connection::Error },
+    /// The datagram does not fit in a DATAGRAM frame the peer accepts or in a packet of the
+    /// current path MTU
+    #[non_exhaustive]
+    DatagramTooLarge { max: u16, requested: u16 },
 }
 
 impl fmt::Display for DatagramError {
@@ -262,6 +309,13 @@ impl fmt::Display for DatagramError {
             Self::ConnectionError { .. } => {
                 write!(f, "Connection-level error occurred.")
             }
+            Self::DatagramTooLarge { max, requested } => {
+                write!(
+                    f,
+                    "Datagram of {} bytes is larger than the maximum of {} bytes.",
+                    requested, max
+                )
+            }
         }
     }
 }
@@ -290,8 +344,8 @@ impl Sender {
         data: &mut bytes::Bytes,
         cx: &mut Context,
     ) -> Poll<Result<(), DatagramError>> {
-        if data.len() as u64 > self.max_datagram_payload {
-            return Poll::Ready(Err(DatagramError::ExceedsPeerTransportLimits));
+        if let Err(error) = self.check_datagram_size(data.len()) {
+            return Poll::Ready(Err(error));
         }
 
         // If there was some connection-level error the user is not allowed to add
@@ -320,9 +374,7 @@ impl Sender {
         &mut self,
         data: bytes::Bytes,
     ) -> Result<Option<Bytes>, DatagramError> {
-        if data.len() as u64 > self.max_datagram_payload {
-            return Err(DatagramError::ExceedsPeerTransportLimits);
-        }
+        self.check_datagram_size(data.len())?;
 
         // If there was some connection-level error the user is not allowed to add
         // datagrams to the queue as they will never be sent.
@@ -349,9 +401,7 @@ impl Sender {
     ///
     /// If the queue is full the newest datagram is not added and an error is returned.
     pub fn send_datagram(&mut self, data: bytes::Bytes) -> Result<(), DatagramError> {
-        if data.len() as u64 > self.max_datagram_payload {
-            return Err(DatagramError::ExceedsPeerTransportLimits);
-        }
+        self.check_datagram_size(data.len())?;
 
         // If there was some connection-level error the user is not allowed to add
         // datagrams to the queue as they will never be sent.
@@ -368,6 +418,25 @@ impl Sender {
         Ok(())
     }
 
+    /// Returns the largest datagram that can currently be sent
+    ///
+    /// This is the smaller of the peer's `max_datagram_frame_size` transport parameter and the
+    /// space left for a DATAGRAM frame in a packet of the current path MTU.
+    pub fn max_datagram_payload(&self) -> u64 {
+        self.max_datagram_payload
+    }
+
+    #[inline]
+    fn check_datagram_size(&self, len: usize) -> Result<(), DatagramError> {
+        if len as u64 > self.max_datagram_payload {
+            return Err(DatagramError::DatagramTooLarge {
+                max: self.max_datagram_payload.try_into().unwrap_or(u16::MAX),
+                requested: len.try_into().unwrap_or(u16::MAX),
+            });
+        }
+        Ok(())
+    }
+
     /// Filter through the datagrams in the send queue and only keep those that
     /// match a predicate
     pub fn retain_datagrams<F>(&mut self, f: F)
@@ -468,6 +537,11 @@ impl super::Sender for Sender {
             w.wake();
         }
     }
+
+    #[inline]
+    fn on_max_datagram_payload_update(&mut self, max_datagram_payload: u64) {
+        self.max_datagram_payload = self.peer_max_datagram_payload.min(max_datagram_payload);
+    }
 }
 
 /// A builder for the default datagram sender
@@ -482,7 +556,7 @@ pub struct SenderBuilder {
 impl Default for SenderBuilder {
     fn default() -> Self {
         Self {
-            queue_capacity: 200,
+            queue_capacity: DEFAULT_SEND_QUEUE_CAPACITY,
             max_datagram_payload: 0,
         }
     }
@@ -506,6 +580,7 @@ impl SenderBuilder {
         Ok(Sender {
             queue: VecDeque::with_capacity(self.queue_capacity),
             capacity: self.queue_capacity,
+            peer_max_datagram_payload: self.max_datagram_payload,
             max_datagram_payload: self.max_datagram_payload,
             max_packet_space: 0,
             min_packet_space: 0,
@@ -671,6 +746,57 @@ mod tests {
         assert!(default_sender.queue.is_empty());
     }
 
+    #[test]
+    fn datagram_too_large() {
+        let conn_info = ConnectionInfo::new(100);
+        let mut default_sender = Sender::builder()
+            .with_connection_info(&conn_info)
+            .build()
+            .unwrap();
+
+        // A datagram of exactly the peer's limit is accepted and one byte over is rejected
+        assert_eq!(
+            default_sender.send_datagram(Bytes::from(vec![0; 100])),
+            Ok(())
+        );
+        assert_eq!(
+            default_sender.send_datagram(Bytes::from(vec![0; 101])),
+            Err(DatagramError::DatagramTooLarge {
+                max: 100,
+                requested: 101
+            })
+        );
+
+        // A path MTU which leaves less room than the peer's limit lowers the limit
+        crate::datagram::Sender::on_max_datagram_payload_update(&mut default_sender, 80);
+        assert_eq!(default_sender.max_datagram_payload(), 80);
+        assert_eq!(
+            default_sender.send_datagram_forced(Bytes::from(vec![0; 80])),
+            Ok(None)
+        );
+        assert_eq!(
+            default_sender.send_datagram_forced(Bytes::from(vec![0; 81])),
+            Err(DatagramError::DatagramTooLarge {
+                max: 80,
+                requested: 81
+            })
+        );
+
+        // The limit never exceeds the peer's limit, however large the path MTU gets
+        crate::datagram::Sender::on_max_datagram_payload_update(&mut default_sender, 1500);
+        assert_eq!(default_sender.max_datagram_payload(), 100);
+
+        let (waker, _wake_count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(
+            default_sender.poll_send_datagram(&mut Bytes::from(vec![0; 101]), &mut cx),
+            Poll::Ready(Err(DatagramError::DatagramTooLarge {
+                max: 100,
+                requested: 101
+            }))
+        );
+    }
+
     #[test]
     fn retain_datagrams() {
         let conn_info = ConnectionInfo {
@@ -744,6 +870,12 @@ mod tests {
         assert!(!default_sender.queue.is_empty());
     }
 
+    /// Passes a datagram to the receiver and returns the length of the datagram it dropped
+    fn receive(receiver: &mut Receiver, datagram: &[u8]) -> Option<usize> {
+        crate::datagram::Receiver::on_datagram(receiver, datagram);
+        crate::datagram::Receiver::take_dropped_datagram(receiver)
+    }
+
     #[test]
     fn on_datagram() {
         // Create a receiver with limited capacity
@@ -756,23 +888,53 @@ mod tests {
         let datagram_0 = vec![1, 2, 3];
         let datagram_1 = vec![4, 5, 6];
         let datagram_2 = vec![7, 8, 9];
-        crate::datagram::Receiver::on_datagram(&mut receiver, &datagram_0);
-        crate::datagram::Receiver::on_datagram(&mut receiver, &datagram_1);
-        // Datagram queue will be forced to drop a datagram to receive the newest one
-        crate::datagram::Receiver::on_datagram(&mut receiver, &datagram_2);
+        let datagram_3 = vec![10, 11];
+        assert_eq!(receive(&mut receiver, &datagram_0), None);
+        assert_eq!(receive(&mut receiver, &datagram_1), None);
+        assert_eq!(receiver.datagram_queue_depth(), 2);
 
-        // Oldest datagram has been dropped
-        assert_eq!(receiver.queue.pop_front().unwrap(), datagram_1);
-        assert_eq!(receiver.queue.pop_front().unwrap(), datagram_2);
-        assert!(receiver.queue.pop_front().is_none());
+        // Datagram queue will be forced to drop a datagram to receive the newest one
+        assert_eq!(receive(&mut receiver, &datagram_2), Some(datagram_0.len()));
+        assert_eq!(receive(&mut receiver, &datagram_3), Some(datagram_1.len()));
+        assert_eq!(receiver.datagram_queue_depth(), 2);
+
+        // Oldest datagrams have been dropped and the rest are received in order
+        assert_eq!(receiver.recv_datagram().unwrap(), datagram_2);
+        assert_eq!(receiver.datagram_queue_depth(), 1);
+        assert_eq!(receiver.recv_datagram().unwrap(), datagram_3);
+        assert!(receiver.recv_datagram().is_none());
+        assert_eq!(receiver.datagram_queue_depth(), 0);
 
         // Datagram sent by peer is larger than max_datagram_frame_size
-        let datagram_3 = vec![10, 11, 12, 13, 14, 15];
-        crate::datagram::Receiver::on_datagram(&mut receiver, &datagram_3);
+        let datagram_4 = vec![10, 11, 12, 13, 14, 15];
+        assert_eq!(receive(&mut receiver, &datagram_4), None);
         // Queue is empty as datagram was not accepted
         assert!(receiver.queue.pop_front().is_none());
     }
 
+    #[test]
+    fn default_recv_capacity() {
+        let mut endpoint = Endpoint::default();
+        let (_sender, mut receiver) =
+            crate::datagram::Endpoint::create_connection(&mut endpoint, &ConnectionInfo::new(100));
+
+        // The queue holds the default number of datagrams in the order they were received
+        for i in 0..DEFAULT_RECV_QUEUE_CAPACITY {
+            let datagram = (i as u32).to_be_bytes();
+            assert_eq!(receive(&mut receiver, &datagram), None);
+        }
+        assert_eq!(receiver.datagram_queue_depth(), DEFAULT_RECV_QUEUE_CAPACITY);
+
+        let datagram = (DEFAULT_RECV_QUEUE_CAPACITY as u32).to_be_bytes();
+        assert_eq!(receive(&mut receiver, &datagram), Some(4));
+
+        for i in 1..=DEFAULT_RECV_QUEUE_CAPACITY {
+            let expected = (i as u32).to_be_bytes();
+            assert_eq!(receiver.recv_datagram().unwrap(), &expected[..]);
+        }
+        assert!(receiver.recv_datagram().is_none());
+    }
+
     #[test]
     fn recv_datagram() {
         let mut receiver = Receiver::builder().build().unwrap();
@@ -58,6 +58,16 @@ pub trait Receiver: 'static + Send {
     /// A callback that gives users direct access to datagrams as they are read off a packet
     fn on_datagram(&mut self, datagram: &[u8]);
 
+    /// Returns the length of a received datagram which was dropped because the receiver's queue
+    /// was full
+    ///
+    /// This is called after each call to `on_datagram` until it returns `None`. The connection
+    /// emits a `DatagramDropped` event for each returned length.
+    #[inline]
+    fn take_dropped_datagram(&mut self) -> Option<usize> {
+        None
+    }
+
     /// A callback used to notify the application in the case of a connection error
     fn on_connection_error(&mut self, error: connection::Error);
 }
@@ -72,6 +82,15 @@ pub trait Sender: 'static + Send {
 
     /// A callback used to notify the application in the case of a connection error
     fn on_connection_error(&mut self, error: connection::Error);
+
+    /// A callback used to notify the sender of the largest datagram it can currently send
+    ///
+    /// This is the smaller of the peer's limit and the space left for a DATAGRAM frame in a
+    /// packet of the current path MTU. The limit changes as the path MTU is discovered.
+    #[inline]
+    fn on_max_datagram_payload_update(&mut self, max_datagram_payload: u64) {
+        let _ = max_datagram_payload;
+    }
 }
 
 /// A packet will be available during the on_transmit callback. Use the methods
@@ -32,6 +32,9 @@ pub enum Outcome {
 
     /// Cleanly close the connection
     ///
+    /// The peer is notified with a `CONNECTION_REFUSED` error in an Initial packet. No connection
+    /// state is created for the attempt.
+    ///
     /// Use `Outcome::close()` to construct this variant
     #[non_exhaustive]
     Close,
@@ -54,11 +54,12 @@ ident_into_event!(
     usize,
     isize,
     Duration,
+    f64,
     bool,
     connection::Error,
     endpoint::Location,
 );
-borrowed_into_event!([u8; 4], [u8; 16], [u8], [u32], [&'a [u8]]);
+borrowed_into_event!([u8; 4], [u8; 16], [u8], [u32], [u64], [&'a [u8]]);
 
 impl<T: IntoEvent<U>, U> IntoEvent<Option<U>> for Option<T> {
     #[inline]
@@ -171,6 +171,35 @@ pub mod api {
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
+    #[doc = " A frame contained in a packet that was declared lost"]
+    pub enum LostFrame {
+        #[non_exhaustive]
+        #[doc = " A STREAM frame carrying application data"]
+        StreamData {
+            stream_id: u64,
+            offset: u64,
+            len: u16,
+            is_fin: bool,
+        },
+        #[non_exhaustive]
+        #[doc = " A CRYPTO frame carrying handshake data"]
+        CryptoData {
+            level: KeySpace,
+            offset: u64,
+            len: u16,
+        },
+        #[non_exhaustive]
+        #[doc = " An ACK frame"]
+        Ack { largest_acknowledged: u64 },
+        #[non_exhaustive]
+        #[doc = " A DATAGRAM frame"]
+        Datagram { len: u16 },
+        #[non_exhaustive]
+        #[doc = " Any other frame"]
+        Other { frame: Frame },
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
     pub enum StreamType {
         #[non_exhaustive]
         Bidirectional {},
@@ -271,6 +300,11 @@ pub mod api {
         #[non_exhaustive]
         #[doc = " The peer initiated a connection migration without supplying enough connection IDs to use."]
         InsufficientConnectionIds {},
+        #[non_exhaustive]
+        #[doc = " A DATAGRAM frame was dropped from the application's receive queue because it was full."]
+        #[doc = ""]
+        #[doc = " The oldest queued DATAGRAM frame is dropped to make room for the newly received one."]
+        QueueFull {},
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
@@ -479,6 +513,20 @@ pub mod api {
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
+    #[doc = " The reason BBR estimated that it has fully utilized the available bandwidth"]
+    pub enum FullPipeReason {
+        #[non_exhaustive]
+        #[doc = " The bandwidth estimate stopped growing"]
+        BandwidthPlateau {},
+        #[non_exhaustive]
+        #[doc = " The loss rate exceeded the loss threshold"]
+        ExcessiveLoss {},
+        #[non_exhaustive]
+        #[doc = " The rate of ECN Congestion Experienced markings exceeded the ECN threshold"]
+        Ecn {},
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
     #[doc = " The reason the MTU was updated"]
     pub enum MtuUpdatedCause {
         #[non_exhaustive]
@@ -868,6 +916,144 @@ pub mod api {
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
+    #[doc = " The startup phase of the congestion controller has completed"]
+    #[doc = ""]
+    #[doc = " The event is emitted once, when BBR estimates it has filled the pipe and leaves Startup."]
+    pub struct StartupCompleted<'a> {
+        pub path: Path<'a>,
+        #[doc = " The time between the first packet sent in Startup and leaving Startup"]
+        pub duration: Duration,
+        pub reason: FullPipeReason,
+    }
+    impl<'a> Event for StartupCompleted<'a> {
+        const NAME: &'static str = "recovery:startup_completed";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " A lost packet was within the reordering observed on the path, so the loss may be spurious"]
+    pub struct SpuriousLossDetected<'a> {
+        pub packet_header: PacketHeader,
+        pub path: Path<'a>,
+        pub bytes_lost: u16,
+    }
+    impl<'a> Event for SpuriousLossDetected<'a> {
+        const NAME: &'static str = "recovery:spurious_loss_detected";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " The delivery rate of a path was measured during a bandwidth probe"]
+    pub struct BandwidthProbeMeasured<'a> {
+        pub path: Path<'a>,
+        #[doc = " The delivery rate over the measurement interval, in bits per second"]
+        pub delivery_rate: u64,
+        pub bytes_acked: u64,
+        pub bytes_lost: u64,
+        pub interval: Duration,
+    }
+    impl<'a> Event for BandwidthProbeMeasured<'a> {
+        const NAME: &'static str = "recovery:bandwidth_probe_measured";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " A frame contained in a lost packet"]
+    #[doc = ""]
+    #[doc = " The event is only emitted for connections with a subscriber that returns `true` from"]
+    #[doc = " `Subscriber::tracks_lost_frames`."]
+    pub struct FrameLost<'a> {
+        pub packet_header: PacketHeader,
+        pub path: Path<'a>,
+        pub frame: LostFrame,
+    }
+    impl<'a> Event for FrameLost<'a> {
+        const NAME: &'static str = "recovery:frame_lost";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " Sending data on a stream was blocked by the peer's stream flow control limit"]
+    #[doc = ""]
+    #[doc = " The event is emitted once when the stream becomes blocked, before any STREAM_DATA_BLOCKED"]
+    #[doc = " frame is sent. It is emitted again only after the peer raises the limit and the stream"]
+    #[doc = " becomes blocked again."]
+    pub struct StreamBlocked {
+        pub stream_id: u64,
+        #[doc = " The stream flow control limit, in bytes"]
+        pub limit: u64,
+        #[doc = " The number of bytes enqueued by the application beyond the limit"]
+        pub pending_len: u64,
+    }
+    impl Event for StreamBlocked {
+        const NAME: &'static str = "transport:stream_blocked";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " The peer raised the flow control limit of a stream which was reported as blocked"]
+    #[doc = ""]
+    #[doc = " The event is emitted the next time the stream transmits after the limit is raised."]
+    pub struct StreamUnblocked {
+        pub stream_id: u64,
+        #[doc = " The new stream flow control limit, in bytes"]
+        pub limit: u64,
+        #[doc = " The time between reporting the stream as blocked and the stream resuming"]
+        pub blocked_duration: Duration,
+    }
+    impl Event for StreamUnblocked {
+        const NAME: &'static str = "transport:stream_unblocked";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " Sending stream data was blocked by the peer's connection flow control limit"]
+    pub struct ConnectionBlocked {
+        pub limit: u64,
+    }
+    impl Event for ConnectionBlocked {
+        const NAME: &'static str = "transport:connection_blocked";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " The quality of the active path degraded"]
+    pub struct PathDegraded<'a> {
+        pub current_path: Path<'a>,
+        #[doc = " The averaged ratio of lost packets to sent packets"]
+        pub loss_rate: f64,
+        #[doc = " The averaged RTT divided by the minimum RTT of the path"]
+        pub rtt_ratio: f64,
+    }
+    impl<'a> Event for PathDegraded<'a> {
+        const NAME: &'static str = "connectivity:path_degraded";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " The connection migrated away from a degraded path"]
+    pub struct MigrationTriggered<'a> {
+        pub from_path: Path<'a>,
+        pub to_path: Path<'a>,
+    }
+    impl<'a> Event for MigrationTriggered<'a> {
+        const NAME: &'static str = "connectivity:migration_triggered";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " Streams may be deadlocked with the peer's streams"]
+    #[doc = ""]
+    #[doc = " The event is emitted when sending stream data was blocked by the peer's flow control limits"]
+    #[doc = " while received stream data was left unread for longer than the deadlock detection timeout."]
+    #[doc = " This happens when both applications wait for their writes to complete before reading. It is"]
+    #[doc = " emitted again only after either condition clears and the stall recurs."]
+    pub struct PotentialDeadlock<'a> {
+        #[doc = " The IDs of the streams blocked by the peer's flow control limits"]
+        pub blocked_stream_ids: &'a [u64],
+        #[doc = " The IDs of the streams with received data the application has not read"]
+        pub unread_stream_ids: &'a [u64],
+        #[doc = " The number of unread bytes of each stream in `unread_stream_ids`"]
+        pub unread_lens: &'a [u64],
+        #[doc = " How long the streams have been stalled"]
+        pub duration: Duration,
+    }
+    impl<'a> Event for PotentialDeadlock<'a> {
+        const NAME: &'static str = "transport:potential_deadlock";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
     #[doc = " QUIC version"]
     pub struct VersionInformation<'a> {
         pub server_versions: &'a [u32],
@@ -1364,6 +1550,51 @@ pub mod api {
             }
         }
     }
+    impl builder::LostFrame {
+        #[doc = " Summarizes a frame written to a packet in the given space so it can be reported if"]
+        #[doc = " the packet is lost"]
+        #[doc = ""]
+        #[doc = " Returns `None` for PADDING frames, since losing them has no effect on the connection."]
+        pub fn new(
+            frame: &builder::Frame,
+            space: crate::packet::number::PacketNumberSpace,
+        ) -> Option<builder::LostFrame> {
+            use crate::packet::number::PacketNumberSpace;
+            Some(match frame {
+                builder::Frame::Padding => return None,
+                builder::Frame::Stream {
+                    id,
+                    offset,
+                    len,
+                    is_fin,
+                } => builder::LostFrame::StreamData {
+                    stream_id: *id,
+                    offset: *offset,
+                    len: *len,
+                    is_fin: *is_fin,
+                },
+                builder::Frame::Crypto { offset, len } => builder::LostFrame::CryptoData {
+                    level: match space {
+                        PacketNumberSpace::Initial => builder::KeySpace::Initial,
+                        PacketNumberSpace::Handshake => builder::KeySpace::Handshake,
+                        PacketNumberSpace::ApplicationData => builder::KeySpace::OneRtt,
+                    },
+                    offset: *offset,
+                    len: *len,
+                },
+                builder::Frame::Ack {
+                    largest_acknowledged,
+                    ..
+                } => builder::LostFrame::Ack {
+                    largest_acknowledged: *largest_acknowledged,
+                },
+                builder::Frame::Datagram { len } => builder::LostFrame::Datagram { len: *len },
+                frame => builder::LostFrame::Other {
+                    frame: frame.clone(),
+                },
+            })
+        }
+    }
     impl IntoEvent<builder::StreamType> for &crate::stream::StreamType {
         fn into_event(self) -> builder::StreamType {
             match self {
@@ -1934,6 +2165,151 @@ pub mod tracing {
             tracing :: event ! (target : "slow_start_exited" , parent : id , tracing :: Level :: DEBUG , path = tracing :: field :: debug (path) , cause = tracing :: field :: debug (cause) , congestion_window = tracing :: field :: debug (congestion_window));
         }
         #[inline]
+        fn on_startup_completed(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::StartupCompleted,
+        ) {
+            let id = context.id();
+            let api::StartupCompleted {
+                path,
+                duration,
+                reason,
+            } = event;
+            tracing :: event ! (target : "startup_completed" , parent : id , tracing :: Level :: DEBUG , path = tracing :: field :: debug (path) , duration = tracing :: field :: debug (duration) , reason = tracing :: field :: debug (reason));
+        }
+        #[inline]
+        fn on_spurious_loss_detected(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::SpuriousLossDetected,
+        ) {
+            let id = context.id();
+            let api::SpuriousLossDetected {
+                packet_header,
+                path,
+                bytes_lost,
+            } = event;
+            tracing :: event ! (target : "spurious_loss_detected" , parent : id , tracing :: Level :: DEBUG , packet_header = tracing :: field :: debug (packet_header) , path = tracing :: field :: debug (path) , bytes_lost = tracing :: field :: debug (bytes_lost));
+        }
+        #[inline]
+        fn on_bandwidth_probe_measured(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::BandwidthProbeMeasured,
+        ) {
+            let id = context.id();
+            let api::BandwidthProbeMeasured {
+                path,
+                delivery_rate,
+                bytes_acked,
+                bytes_lost,
+                interval,
+            } = event;
+            tracing :: event ! (target : "bandwidth_probe_measured" , parent : id , tracing :: Level :: DEBUG , path = tracing :: field :: debug (path) , delivery_rate = tracing :: field :: debug (delivery_rate) , bytes_acked = tracing :: field :: debug (bytes_acked) , bytes_lost = tracing :: field :: debug (bytes_lost) , interval = tracing :: field :: debug (interval));
+        }
+        #[inline]
+        fn on_frame_lost(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::FrameLost,
+        ) {
+            let id = context.id();
+            let api::FrameLost {
+                packet_header,
+                path,
+                frame,
+            } = event;
+            tracing :: event ! (target : "frame_lost" , parent : id , tracing :: Level :: DEBUG , packet_header = tracing :: field :: debug (packet_header) , path = tracing :: field :: debug (path) , frame = tracing :: field :: debug (frame));
+        }
+        #[inline]
+        fn on_stream_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::StreamBlocked,
+        ) {
+            let id = context.id();
+            let api::StreamBlocked {
+                stream_id,
+                limit,
+                pending_len,
+            } = event;
+            tracing :: event ! (target : "stream_blocked" , parent : id , tracing :: Level :: DEBUG , stream_id = tracing :: field :: debug (stream_id) , limit = tracing :: field :: debug (limit) , pending_len = tracing :: field :: debug (pending_len));
+        }
+        #[inline]
+        fn on_stream_unblocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::StreamUnblocked,
+        ) {
+            let id = context.id();
+            let api::StreamUnblocked {
+                stream_id,
+                limit,
+                blocked_duration,
+            } = event;
+            tracing :: event ! (target : "stream_unblocked" , parent : id , tracing :: Level :: DEBUG , stream_id = tracing :: field :: debug (stream_id) , limit = tracing :: field :: debug (limit) , blocked_duration = tracing :: field :: debug (blocked_duration));
+        }
+        #[inline]
+        fn on_connection_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::ConnectionBlocked,
+        ) {
+            let id = context.id();
+            let api::ConnectionBlocked { limit } = event;
+            tracing :: event ! (target : "connection_blocked" , parent : id , tracing :: Level :: DEBUG , limit = tracing :: field :: debug (limit));
+        }
+        #[inline]
+        fn on_path_degraded(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::PathDegraded,
+        ) {
+            let id = context.id();
+            let api::PathDegraded {
+                current_path,
+                loss_rate,
+                rtt_ratio,
+            } = event;
+            tracing :: event ! (target : "path_degraded" , parent : id , tracing :: Level :: DEBUG , current_path = tracing :: field :: debug (current_path) , loss_rate = tracing :: field :: debug (loss_rate) , rtt_ratio = tracing :: field :: debug (rtt_ratio));
+        }
+        #[inline]
+        fn on_migration_triggered(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::MigrationTriggered,
+        ) {
+            let id = context.id();
+            let api::MigrationTriggered { from_path, to_path } = event;
+            tracing :: event ! (target : "migration_triggered" , parent : id , tracing :: Level :: DEBUG , from_path = tracing :: field :: debug (from_path) , to_path = tracing :: field :: debug (to_path));
+        }
+        #[inline]
+        fn on_potential_deadlock(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &api::ConnectionMeta,
+            event: &api::PotentialDeadlock,
+        ) {
+            let id = context.id();
+            let api::PotentialDeadlock {
+                blocked_stream_ids,
+                unread_stream_ids,
+                unread_lens,
+                duration,
+            } = event;
+            tracing :: event ! (target : "potential_deadlock" , parent : id , tracing :: Level :: DEBUG , blocked_stream_ids = tracing :: field :: debug (blocked_stream_ids) , unread_stream_ids = tracing :: field :: debug (unread_stream_ids) , unread_lens = tracing :: field :: debug (unread_lens) , duration = tracing :: field :: debug (duration));
+        }
+        #[inline]
         fn on_version_information(
             &mut self,
             meta: &api::EndpointMeta,
@@ -2499,6 +2875,64 @@ pub mod builder {
         }
     }
     #[derive(Clone, Debug)]
+    #[doc = " A frame contained in a packet that was declared lost"]
+    pub enum LostFrame {
+        #[doc = " A STREAM frame carrying application data"]
+        StreamData {
+            stream_id: u64,
+            offset: u64,
+            len: u16,
+            is_fin: bool,
+        },
+        #[doc = " A CRYPTO frame carrying handshake data"]
+        CryptoData {
+            level: KeySpace,
+            offset: u64,
+            len: u16,
+        },
+        #[doc = " An ACK frame"]
+        Ack { largest_acknowledged: u64 },
+        #[doc = " A DATAGRAM frame"]
+        Datagram { len: u16 },
+        #[doc = " Any other frame"]
+        Other { frame: Frame },
+    }
+    impl IntoEvent<api::LostFrame> for LostFrame {
+        #[inline]
+        fn into_event(self) -> api::LostFrame {
+            use api::LostFrame::*;
+            match self {
+                Self::StreamData {
+                    stream_id,
+                    offset,
+                    len,
+                    is_fin,
+                } => StreamData {
+                    stream_id: stream_id.into_event(),
+                    offset: offset.into_event(),
+                    len: len.into_event(),
+                    is_fin: is_fin.into_event(),
+                },
+                Self::CryptoData { level, offset, len } => CryptoData {
+                    level: level.into_event(),
+                    offset: offset.into_event(),
+                    len: len.into_event(),
+                },
+                Self::Ack {
+                    largest_acknowledged,
+                } => Ack {
+                    largest_acknowledged: largest_acknowledged.into_event(),
+                },
+                Self::Datagram { len } => Datagram {
+                    len: len.into_event(),
+                },
+                Self::Other { frame } => Other {
+                    frame: frame.into_event(),
+                },
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
     pub enum StreamType {
         Bidirectional,
         Unidirectional,
@@ -2642,6 +3076,10 @@ pub mod builder {
         PathLimitExceeded,
         #[doc = " The peer initiated a connection migration without supplying enough connection IDs to use."]
         InsufficientConnectionIds,
+        #[doc = " A DATAGRAM frame was dropped from the application's receive queue because it was full."]
+        #[doc = ""]
+        #[doc = " The oldest queued DATAGRAM frame is dropped to make room for the newly received one."]
+        QueueFull,
     }
     impl IntoEvent<api::DatagramDropReason> for DatagramDropReason {
         #[inline]
@@ -2660,6 +3098,7 @@ pub mod builder {
                 Self::RejectedConnectionMigration => RejectedConnectionMigration {},
                 Self::PathLimitExceeded => PathLimitExceeded {},
                 Self::InsufficientConnectionIds => InsufficientConnectionIds {},
+                Self::QueueFull => QueueFull {},
             }
         }
     }
@@ -2982,6 +3421,27 @@ pub mod builder {
         }
     }
     #[derive(Clone, Debug)]
+    #[doc = " The reason BBR estimated that it has fully utilized the available bandwidth"]
+    pub enum FullPipeReason {
+        #[doc = " The bandwidth estimate stopped growing"]
+        BandwidthPlateau,
+        #[doc = " The loss rate exceeded the loss threshold"]
+        ExcessiveLoss,
+        #[doc = " The rate of ECN Congestion Experienced markings exceeded the ECN threshold"]
+        Ecn,
+    }
+    impl IntoEvent<api::FullPipeReason> for FullPipeReason {
+        #[inline]
+        fn into_event(self) -> api::FullPipeReason {
+            use api::FullPipeReason::*;
+            match self {
+                Self::BandwidthPlateau => BandwidthPlateau {},
+                Self::ExcessiveLoss => ExcessiveLoss {},
+                Self::Ecn => Ecn {},
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
     #[doc = " The reason the MTU was updated"]
     pub enum MtuUpdatedCause {
         #[doc = " The MTU was initialized with the default value"]
@@ -3657,43 +4117,286 @@ pub mod builder {
         }
     }
     #[derive(Clone, Debug)]
-    #[doc = " QUIC version"]
-    pub struct VersionInformation<'a> {
-        pub server_versions: &'a [u32],
-        pub client_versions: &'a [u32],
-        pub chosen_version: Option<u32>,
+    #[doc = " The startup phase of the congestion controller has completed"]
+    #[doc = ""]
+    #[doc = " The event is emitted once, when BBR estimates it has filled the pipe and leaves Startup."]
+    pub struct StartupCompleted<'a> {
+        pub path: Path<'a>,
+        #[doc = " The time between the first packet sent in Startup and leaving Startup"]
+        pub duration: Duration,
+        pub reason: FullPipeReason,
     }
-    impl<'a> IntoEvent<api::VersionInformation<'a>> for VersionInformation<'a> {
+    impl<'a> IntoEvent<api::StartupCompleted<'a>> for StartupCompleted<'a> {
         #[inline]
-        fn into_event(self) -> api::VersionInformation<'a> {
-            let VersionInformation {
-                server_versions,
-                client_versions,
-                chosen_version,
+        fn into_event(self) -> api::StartupCompleted<'a> {
+            let StartupCompleted {
+                path,
+                duration,
+                reason,
             } = self;
-            api::VersionInformation {
-                server_versions: server_versions.into_event(),
-                client_versions: client_versions.into_event(),
-                chosen_version: chosen_version.into_event(),
+            api::StartupCompleted {
+                path: path.into_event(),
+                duration: duration.into_event(),
+                reason: reason.into_event(),
             }
         }
     }
     #[derive(Clone, Debug)]
-    #[doc = " Packet was sent by the endpoint"]
-    pub struct EndpointPacketSent {
+    #[doc = " A lost packet was within the reordering observed on the path, so the loss may be spurious"]
+    pub struct SpuriousLossDetected<'a> {
         pub packet_header: PacketHeader,
+        pub path: Path<'a>,
+        pub bytes_lost: u16,
     }
-    impl IntoEvent<api::EndpointPacketSent> for EndpointPacketSent {
+    impl<'a> IntoEvent<api::SpuriousLossDetected<'a>> for SpuriousLossDetected<'a> {
         #[inline]
-        fn into_event(self) -> api::EndpointPacketSent {
-            let EndpointPacketSent { packet_header } = self;
-            api::EndpointPacketSent {
+        fn into_event(self) -> api::SpuriousLossDetected<'a> {
+            let SpuriousLossDetected {
+                packet_header,
+                path,
+                bytes_lost,
+            } = self;
+            api::SpuriousLossDetected {
                 packet_header: packet_header.into_event(),
+                path: path.into_event(),
+                bytes_lost: bytes_lost.into_event(),
             }
         }
     }
     #[derive(Clone, Debug)]
-    #[doc = " Packet was received by the endpoint"]
+    #[doc = " The delivery rate of a path was measured during a bandwidth probe"]
+    pub struct BandwidthProbeMeasured<'a> {
+        pub path: Path<'a>,
+        #[doc = " The delivery rate over the measurement interval, in bits per second"]
+        pub delivery_rate: u64,
+        pub bytes_acked: u64,
+        pub bytes_lost: u64,
+        pub interval: Duration,
+    }
+    impl<'a> IntoEvent<api::BandwidthProbeMeasured<'a>> for BandwidthProbeMeasured<'a> {
+        #[inline]
+        fn into_event(self) -> api::BandwidthProbeMeasured<'a> {
+            let BandwidthProbeMeasured {
+                path,
+                delivery_rate,
+                bytes_acked,
+                bytes_lost,
+                interval,
+            } = self;
+            api::BandwidthProbeMeasured {
+                path: path.into_event(),
+                delivery_rate: delivery_rate.into_event(),
+                bytes_acked: bytes_acked.into_event(),
+                bytes_lost: bytes_lost.into_event(),
+                interval: interval.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " A frame contained in a lost packet"]
+    #[doc = ""]
+    #[doc = " The event is only emitted for connections with a subscriber that returns `true` from"]
+    #[doc = " `Subscriber::tracks_lost_frames`."]
+    pub struct FrameLost<'a> {
+        pub packet_header: PacketHeader,
+        pub path: Path<'a>,
+        pub frame: LostFrame,
+    }
+    impl<'a> IntoEvent<api::FrameLost<'a>> for FrameLost<'a> {
+        #[inline]
+        fn into_event(self) -> api::FrameLost<'a> {
+            let FrameLost {
+                packet_header,
+                path,
+                frame,
+            } = self;
+            api::FrameLost {
+                packet_header: packet_header.into_event(),
+                path: path.into_event(),
+                frame: frame.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " Sending data on a stream was blocked by the peer's stream flow control limit"]
+    #[doc = ""]
+    #[doc = " The event is emitted once when the stream becomes blocked, before any STREAM_DATA_BLOCKED"]
+    #[doc = " frame is sent. It is emitted again only after the peer raises the limit and the stream"]
+    #[doc = " becomes blocked again."]
+    pub struct StreamBlocked {
+        pub stream_id: u64,
+        #[doc = " The stream flow control limit, in bytes"]
+        pub limit: u64,
+        #[doc = " The number of bytes enqueued by the application beyond the limit"]
+        pub pending_len: u64,
+    }
+    impl IntoEvent<api::StreamBlocked> for StreamBlocked {
+        #[inline]
+        fn into_event(self) -> api::StreamBlocked {
+            let StreamBlocked {
+                stream_id,
+                limit,
+                pending_len,
+            } = self;
+            api::StreamBlocked {
+                stream_id: stream_id.into_event(),
+                limit: limit.into_event(),
+                pending_len: pending_len.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " The peer raised the flow control limit of a stream which was reported as blocked"]
+    #[doc = ""]
+    #[doc = " The event is emitted the next time the stream transmits after the limit is raised."]
+    pub struct StreamUnblocked {
+        pub stream_id: u64,
+        #[doc = " The new stream flow control limit, in bytes"]
+        pub limit: u64,
+        #[doc = " The time between reporting the stream as blocked and the stream resuming"]
+        pub blocked_duration: Duration,
+    }
+    impl IntoEvent<api::StreamUnblocked> for StreamUnblocked {
+        #[inline]
+        fn into_event(self) -> api::StreamUnblocked {
+            let StreamUnblocked {
+                stream_id,
+                limit,
+                blocked_duration,
+            } = self;
+            api::StreamUnblocked {
+                stream_id: stream_id.into_event(),
+                limit: limit.into_event(),
+                blocked_duration: blocked_duration.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " Sending stream data was blocked by the peer's connection flow control limit"]
+    pub struct ConnectionBlocked {
+        pub limit: u64,
+    }
+    impl IntoEvent<api::ConnectionBlocked> for ConnectionBlocked {
+        #[inline]
+        fn into_event(self) -> api::ConnectionBlocked {
+            let ConnectionBlocked { limit } = self;
+            api::ConnectionBlocked {
+                limit: limit.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " The quality of the active path degraded"]
+    pub struct PathDegraded<'a> {
+        pub current_path: Path<'a>,
+        #[doc = " The averaged ratio of lost packets to sent packets"]
+        pub loss_rate: f64,
+        #[doc = " The averaged RTT divided by the minimum RTT of the path"]
+        pub rtt_ratio: f64,
+    }
+    impl<'a> IntoEvent<api::PathDegraded<'a>> for PathDegraded<'a> {
+        #[inline]
+        fn into_event(self) -> api::PathDegraded<'a> {
+            let PathDegraded {
+                current_path,
+                loss_rate,
+                rtt_ratio,
+            } = self;
+            api::PathDegraded {
+                current_path: current_path.into_event(),
+                loss_rate: loss_rate.into_event(),
+                rtt_ratio: rtt_ratio.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " The connection migrated away from a degraded path"]
+    pub struct MigrationTriggered<'a> {
+        pub from_path: Path<'a>,
+        pub to_path: Path<'a>,
+    }
+    impl<'a> IntoEvent<api::MigrationTriggered<'a>> for MigrationTriggered<'a> {
+        #[inline]
+        fn into_event(self) -> api::MigrationTriggered<'a> {
+            let MigrationTriggered { from_path, to_path } = self;
+            api::MigrationTriggered {
+                from_path: from_path.into_event(),
+                to_path: to_path.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " Streams may be deadlocked with the peer's streams"]
+    #[doc = ""]
+    #[doc = " The event is emitted when sending stream data was blocked by the peer's flow control limits"]
+    #[doc = " while received stream data was left unread for longer than the deadlock detection timeout."]
+    #[doc = " This happens when both applications wait for their writes to complete before reading. It is"]
+    #[doc = " emitted again only after either condition clears and the stall recurs."]
+    pub struct PotentialDeadlock<'a> {
+        #[doc = " The IDs of the streams blocked by the peer's flow control limits"]
+        pub blocked_stream_ids: &'a [u64],
+        #[doc = " The IDs of the streams with received data the application has not read"]
+        pub unread_stream_ids: &'a [u64],
+        #[doc = " The number of unread bytes of each stream in `unread_stream_ids`"]
+        pub unread_lens: &'a [u64],
+        #[doc = " How long the streams have been stalled"]
+        pub duration: Duration,
+    }
+    impl<'a> IntoEvent<api::PotentialDeadlock<'a>> for PotentialDeadlock<'a> {
+        #[inline]
+        fn into_event(self) -> api::PotentialDeadlock<'a> {
+            let PotentialDeadlock {
+                blocked_stream_ids,
+                unread_stream_ids,
+                unread_lens,
+                duration,
+            } = self;
+            api::PotentialDeadlock {
+                blocked_stream_ids: blocked_stream_ids.into_event(),
+                unread_stream_ids: unread_stream_ids.into_event(),
+                unread_lens: unread_lens.into_event(),
+                duration: duration.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " QUIC version"]
+    pub struct VersionInformation<'a> {
+        pub server_versions: &'a [u32],
+        pub client_versions: &'a [u32],
+        pub chosen_version: Option<u32>,
+    }
+    impl<'a> IntoEvent<api::VersionInformation<'a>> for VersionInformation<'a> {
+        #[inline]
+        fn into_event(self) -> api::VersionInformation<'a> {
+            let VersionInformation {
+                server_versions,
+                client_versions,
+                chosen_version,
+            } = self;
+            api::VersionInformation {
+                server_versions: server_versions.into_event(),
+                client_versions: client_versions.into_event(),
+                chosen_version: chosen_version.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " Packet was sent by the endpoint"]
+    pub struct EndpointPacketSent {
+        pub packet_header: PacketHeader,
+    }
+    impl IntoEvent<api::EndpointPacketSent> for EndpointPacketSent {
+        #[inline]
+        fn into_event(self) -> api::EndpointPacketSent {
+            let EndpointPacketSent { packet_header } = self;
+            api::EndpointPacketSent {
+                packet_header: packet_header.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " Packet was received by the endpoint"]
     pub struct EndpointPacketReceived {
         pub packet_header: PacketHeader,
     }
@@ -4096,6 +4799,14 @@ mod traits {
         ) -> supervisor::Outcome {
             supervisor::Outcome::default()
         }
+        #[doc = r" Returns `true` if the subscriber consumes `FrameLost` events"]
+        #[doc = r""]
+        #[doc = r" Recording the frames contained in each sent packet has a cost, so frames are only"]
+        #[doc = r" tracked for connections where at least one `event::Subscriber` returns `true`."]
+        #[inline]
+        fn tracks_lost_frames(&self) -> bool {
+            false
+        }
         #[doc = "Called when the `ApplicationProtocolInformation` event is triggered"]
         #[inline]
         fn on_application_protocol_information(
@@ -4530,6 +5241,126 @@ mod traits {
             let _ = meta;
             let _ = event;
         }
+        #[doc = "Called when the `StartupCompleted` event is triggered"]
+        #[inline]
+        fn on_startup_completed(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StartupCompleted,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `SpuriousLossDetected` event is triggered"]
+        #[inline]
+        fn on_spurious_loss_detected(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &SpuriousLossDetected,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `BandwidthProbeMeasured` event is triggered"]
+        #[inline]
+        fn on_bandwidth_probe_measured(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &BandwidthProbeMeasured,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `FrameLost` event is triggered"]
+        #[inline]
+        fn on_frame_lost(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &FrameLost,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `StreamBlocked` event is triggered"]
+        #[inline]
+        fn on_stream_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StreamBlocked,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `StreamUnblocked` event is triggered"]
+        #[inline]
+        fn on_stream_unblocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StreamUnblocked,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `ConnectionBlocked` event is triggered"]
+        #[inline]
+        fn on_connection_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &ConnectionBlocked,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `PathDegraded` event is triggered"]
+        #[inline]
+        fn on_path_degraded(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &PathDegraded,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `MigrationTriggered` event is triggered"]
+        #[inline]
+        fn on_migration_triggered(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &MigrationTriggered,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `PotentialDeadlock` event is triggered"]
+        #[inline]
+        fn on_potential_deadlock(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &PotentialDeadlock,
+        ) {
+            let _ = context;
+            let _ = meta;
+            let _ = event;
+        }
         #[doc = "Called when the `VersionInformation` event is triggered"]
         #[inline]
         fn on_version_information(&mut self, meta: &EndpointMeta, event: &VersionInformation) {
@@ -4741,6 +5572,10 @@ mod traits {
             }
         }
         #[inline]
+        fn tracks_lost_frames(&self) -> bool {
+            self.0.tracks_lost_frames() || self.1.tracks_lost_frames()
+        }
+        #[inline]
         fn on_application_protocol_information(
             &mut self,
             context: &mut Self::ConnectionContext,
@@ -5102,6 +5937,106 @@ mod traits {
             (self.1).on_slow_start_exited(&mut context.1, meta, event);
         }
         #[inline]
+        fn on_startup_completed(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StartupCompleted,
+        ) {
+            (self.0).on_startup_completed(&mut context.0, meta, event);
+            (self.1).on_startup_completed(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_spurious_loss_detected(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &SpuriousLossDetected,
+        ) {
+            (self.0).on_spurious_loss_detected(&mut context.0, meta, event);
+            (self.1).on_spurious_loss_detected(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_bandwidth_probe_measured(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &BandwidthProbeMeasured,
+        ) {
+            (self.0).on_bandwidth_probe_measured(&mut context.0, meta, event);
+            (self.1).on_bandwidth_probe_measured(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_frame_lost(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &FrameLost,
+        ) {
+            (self.0).on_frame_lost(&mut context.0, meta, event);
+            (self.1).on_frame_lost(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_stream_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StreamBlocked,
+        ) {
+            (self.0).on_stream_blocked(&mut context.0, meta, event);
+            (self.1).on_stream_blocked(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_stream_unblocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &StreamUnblocked,
+        ) {
+            (self.0).on_stream_unblocked(&mut context.0, meta, event);
+            (self.1).on_stream_unblocked(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_connection_blocked(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &ConnectionBlocked,
+        ) {
+            (self.0).on_connection_blocked(&mut context.0, meta, event);
+            (self.1).on_connection_blocked(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_path_degraded(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &PathDegraded,
+        ) {
+            (self.0).on_path_degraded(&mut context.0, meta, event);
+            (self.1).on_path_degraded(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_migration_triggered(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &MigrationTriggered,
+        ) {
+            (self.0).on_migration_triggered(&mut context.0, meta, event);
+            (self.1).on_migration_triggered(&mut context.1, meta, event);
+        }
+        #[inline]
+        fn on_potential_deadlock(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            meta: &ConnectionMeta,
+            event: &PotentialDeadlock,
+        ) {
+            (self.0).on_potential_deadlock(&mut context.0, meta, event);
+            (self.1).on_potential_deadlock(&mut context.1, meta, event);
+        }
+        #[inline]
         fn on_version_information(&mut self, meta: &EndpointMeta, event: &VersionInformation) {
             (self.0).on_version_information(meta, event);
             (self.1).on_version_information(meta, event);
@@ -5474,10 +6409,32 @@ mod traits {
         fn on_mtu_updated(&mut self, event: builder::MtuUpdated);
         #[doc = "Publishes a `SlowStartExited` event to the publisher's subscriber"]
         fn on_slow_start_exited(&mut self, event: builder::SlowStartExited);
+        #[doc = "Publishes a `StartupCompleted` event to the publisher's subscriber"]
+        fn on_startup_completed(&mut self, event: builder::StartupCompleted);
+        #[doc = "Publishes a `SpuriousLossDetected` event to the publisher's subscriber"]
+        fn on_spurious_loss_detected(&mut self, event: builder::SpuriousLossDetected);
+        #[doc = "Publishes a `BandwidthProbeMeasured` event to the publisher's subscriber"]
+        fn on_bandwidth_probe_measured(&mut self, event: builder::BandwidthProbeMeasured);
+        #[doc = "Publishes a `FrameLost` event to the publisher's subscriber"]
+        fn on_frame_lost(&mut self, event: builder::FrameLost);
+        #[doc = "Publishes a `StreamBlocked` event to the publisher's subscriber"]
+        fn on_stream_blocked(&mut self, event: builder::StreamBlocked);
+        #[doc = "Publishes a `StreamUnblocked` event to the publisher's subscriber"]
+        fn on_stream_unblocked(&mut self, event: builder::StreamUnblocked);
+        #[doc = "Publishes a `ConnectionBlocked` event to the publisher's subscriber"]
+        fn on_connection_blocked(&mut self, event: builder::ConnectionBlocked);
+        #[doc = "Publishes a `PathDegraded` event to the publisher's subscriber"]
+        fn on_path_degraded(&mut self, event: builder::PathDegraded);
+        #[doc = "Publishes a `MigrationTriggered` event to the publisher's subscriber"]
+        fn on_migration_triggered(&mut self, event: builder::MigrationTriggered);
+        #[doc = "Publishes a `PotentialDeadlock` event to the publisher's subscriber"]
+        fn on_potential_deadlock(&mut self, event: builder::PotentialDeadlock);
         #[doc = r" Returns the QUIC version negotiated for the current connection, if any"]
         fn quic_version(&self) -> u32;
         #[doc = r" Returns the [`Subject`] for the current publisher"]
         fn subject(&self) -> Subject;
+        #[doc = r" Returns `true` if `FrameLost` events should be published for the current connection"]
+        fn tracks_lost_frames(&self) -> bool;
     }
     pub struct ConnectionPublisherSubscriber<'a, Sub: Subscriber> {
         meta: ConnectionMeta,
@@ -5842,6 +6799,96 @@ mod traits {
             self.subscriber.on_event(&self.meta, &event);
         }
         #[inline]
+        fn on_startup_completed(&mut self, event: builder::StartupCompleted) {
+            let event = event.into_event();
+            self.subscriber
+                .on_startup_completed(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_spurious_loss_detected(&mut self, event: builder::SpuriousLossDetected) {
+            let event = event.into_event();
+            self.subscriber
+                .on_spurious_loss_detected(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_bandwidth_probe_measured(&mut self, event: builder::BandwidthProbeMeasured) {
+            let event = event.into_event();
+            self.subscriber
+                .on_bandwidth_probe_measured(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_frame_lost(&mut self, event: builder::FrameLost) {
+            let event = event.into_event();
+            self.subscriber
+                .on_frame_lost(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_stream_blocked(&mut self, event: builder::StreamBlocked) {
+            let event = event.into_event();
+            self.subscriber
+                .on_stream_blocked(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_stream_unblocked(&mut self, event: builder::StreamUnblocked) {
+            let event = event.into_event();
+            self.subscriber
+                .on_stream_unblocked(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_connection_blocked(&mut self, event: builder::ConnectionBlocked) {
+            let event = event.into_event();
+            self.subscriber
+                .on_connection_blocked(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_path_degraded(&mut self, event: builder::PathDegraded) {
+            let event = event.into_event();
+            self.subscriber
+                .on_path_degraded(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_migration_triggered(&mut self, event: builder::MigrationTriggered) {
+            let event = event.into_event();
+            self.subscriber
+                .on_migration_triggered(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_potential_deadlock(&mut self, event: builder::PotentialDeadlock) {
+            let event = event.into_event();
+            self.subscriber
+                .on_potential_deadlock(self.context, &self.meta, &event);
+            self.subscriber
+                .on_connection_event(self.context, &self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
         fn quic_version(&self) -> u32 {
             self.quic_version
         }
@@ -5849,6 +6896,10 @@ mod traits {
         fn subject(&self) -> api::Subject {
             self.meta.subject()
         }
+        #[inline]
+        fn tracks_lost_frames(&self) -> bool {
+            self.subscriber.tracks_lost_frames()
+        }
     }
 }
 #[cfg(any(test, feature = "testing"))]
@@ -5894,6 +6945,16 @@ pub mod testing {
         pub keep_alive_timer_expired: u32,
         pub mtu_updated: u32,
         pub slow_start_exited: u32,
+        pub startup_completed: u32,
+        pub spurious_loss_detected: u32,
+        pub bandwidth_probe_measured: u32,
+        pub frame_lost: u32,
+        pub stream_blocked: u32,
+        pub stream_unblocked: u32,
+        pub connection_blocked: u32,
+        pub path_degraded: u32,
+        pub migration_triggered: u32,
+        pub potential_deadlock: u32,
         pub version_information: u32,
         pub endpoint_packet_sent: u32,
         pub endpoint_packet_received: u32,
@@ -5968,6 +7029,16 @@ pub mod testing {
                 keep_alive_timer_expired: 0,
                 mtu_updated: 0,
                 slow_start_exited: 0,
+                startup_completed: 0,
+                spurious_loss_detected: 0,
+                bandwidth_probe_measured: 0,
+                frame_lost: 0,
+                stream_blocked: 0,
+                stream_unblocked: 0,
+                connection_blocked: 0,
+                path_degraded: 0,
+                migration_triggered: 0,
+                potential_deadlock: 0,
                 version_information: 0,
                 endpoint_packet_sent: 0,
                 endpoint_packet_received: 0,
@@ -6390,6 +7461,116 @@ pub mod testing {
                 self.output.push(format!("{:?} {:?}", meta, event));
             }
         }
+        fn on_startup_completed(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::StartupCompleted,
+        ) {
+            self.startup_completed += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_spurious_loss_detected(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::SpuriousLossDetected,
+        ) {
+            self.spurious_loss_detected += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_bandwidth_probe_measured(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::BandwidthProbeMeasured,
+        ) {
+            self.bandwidth_probe_measured += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_frame_lost(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::FrameLost,
+        ) {
+            self.frame_lost += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_stream_blocked(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::StreamBlocked,
+        ) {
+            self.stream_blocked += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_stream_unblocked(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::StreamUnblocked,
+        ) {
+            self.stream_unblocked += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_connection_blocked(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::ConnectionBlocked,
+        ) {
+            self.connection_blocked += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_path_degraded(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::PathDegraded,
+        ) {
+            self.path_degraded += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_migration_triggered(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::MigrationTriggered,
+        ) {
+            self.migration_triggered += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
+        fn on_potential_deadlock(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            meta: &api::ConnectionMeta,
+            event: &api::PotentialDeadlock,
+        ) {
+            self.potential_deadlock += 1;
+            if self.location.is_some() {
+                self.output.push(format!("{:?} {:?}", meta, event));
+            }
+        }
         fn on_version_information(
             &mut self,
             meta: &api::EndpointMeta,
@@ -6527,6 +7708,16 @@ pub mod testing {
         pub keep_alive_timer_expired: u32,
         pub mtu_updated: u32,
         pub slow_start_exited: u32,
+        pub startup_completed: u32,
+        pub spurious_loss_detected: u32,
+        pub bandwidth_probe_measured: u32,
+        pub frame_lost: u32,
+        pub stream_blocked: u32,
+        pub stream_unblocked: u32,
+        pub connection_blocked: u32,
+        pub path_degraded: u32,
+        pub migration_triggered: u32,
+        pub potential_deadlock: u32,
         pub version_information: u32,
         pub endpoint_packet_sent: u32,
         pub endpoint_packet_received: u32,
@@ -6591,6 +7782,16 @@ pub mod testing {
                 keep_alive_timer_expired: 0,
                 mtu_updated: 0,
                 slow_start_exited: 0,
+                startup_completed: 0,
+                spurious_loss_detected: 0,
+                bandwidth_probe_measured: 0,
+                frame_lost: 0,
+                stream_blocked: 0,
+                stream_unblocked: 0,
+                connection_blocked: 0,
+                path_degraded: 0,
+                migration_triggered: 0,
+                potential_deadlock: 0,
                 version_information: 0,
                 endpoint_packet_sent: 0,
                 endpoint_packet_received: 0,
@@ -6946,12 +8147,85 @@ pub mod testing {
                 self.output.push(format!("{:?}", event));
             }
         }
+        fn on_startup_completed(&mut self, event: builder::StartupCompleted) {
+            self.startup_completed += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_spurious_loss_detected(&mut self, event: builder::SpuriousLossDetected) {
+            self.spurious_loss_detected += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_bandwidth_probe_measured(&mut self, event: builder::BandwidthProbeMeasured) {
+            self.bandwidth_probe_measured += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_frame_lost(&mut self, event: builder::FrameLost) {
+            self.frame_lost += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_stream_blocked(&mut self, event: builder::StreamBlocked) {
+            self.stream_blocked += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_stream_unblocked(&mut self, event: builder::StreamUnblocked) {
+            self.stream_unblocked += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_connection_blocked(&mut self, event: builder::ConnectionBlocked) {
+            self.connection_blocked += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_path_degraded(&mut self, event: builder::PathDegraded) {
+            self.path_degraded += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_migration_triggered(&mut self, event: builder::MigrationTriggered) {
+            self.migration_triggered += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
+        fn on_potential_deadlock(&mut self, event: builder::PotentialDeadlock) {
+            self.potential_deadlock += 1;
+            let event = event.into_event();
+            if self.location.is_some() {
+                self.output.push(format!("{:?}", event));
+            }
+        }
         fn quic_version(&self) -> u32 {
             1
         }
         fn subject(&self) -> api::Subject {
             api::Subject::Connection { id: 0 }
         }
+        fn tracks_lost_frames(&self) -> bool {
+            false
+        }
     }
     impl Drop for Publisher {
         fn drop(&mut self) {
@@ -16,6 +16,8 @@ use s2n_codec::{
 pub mod ack_elicitation;
 pub mod congestion_controlled;
 pub mod path_validation;
+#[cfg(feature = "alloc")]
+pub mod sequenced_datagram;
 
 //= https://www.rfc-editor.org/rfc/rfc9000#section-19
 //# As described in Section 12.4, packets contain one or more frames.
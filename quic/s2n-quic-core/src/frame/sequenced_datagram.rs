@@ -0,0 +1,330 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An optional ordering layer on top of DATAGRAM frames
+//!
+//! DATAGRAM frames are delivered in whatever order the packets carrying them arrive. Applications
+//! that prefer ordered delivery, but can't afford the head-of-line blocking of a stream, can
+//! prefix each datagram payload with a sequence number using [`SequencedDatagramSender`] and
+//! restore the order on the receiving side with [`SequencedDatagramReceiver`].
+//!
+//! The receiver only holds back datagrams within a bounded window. When a datagram arrives that
+//! is further ahead than the window allows, any missing datagrams are presumed lost and the
+//! buffered datagrams are released, skipping the gaps.
+
+use alloc::collections::VecDeque;
+use bytes::{BufMut, Bytes, BytesMut};
+
+/// The length of the sequence number prepended to each datagram payload
+pub const SEQUENCE_NUMBER_LEN: usize = 2;
+
+/// The default number of datagrams the receiver holds back while waiting for a missing datagram
+pub const DEFAULT_WINDOW: u16 = 32;
+
+/// The largest window that can be configured
+///
+/// The sequence number is compared using serial number arithmetic, so the window must cover less
+/// than half of the sequence number space.
+pub const MAX_WINDOW: u16 = 1 << 14;
+
+/// Prepends a 2-byte big-endian sequence number to outgoing datagram payloads
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SequencedDatagramSender {
+    next_sequence_number: u16,
+}
+
+impl SequencedDatagramSender {
+    /// Returns the `payload` prefixed with the next sequence number
+    pub fn encode(&mut self, payload: &[u8]) -> Bytes {
+        let mut datagram = BytesMut::with_capacity(SEQUENCE_NUMBER_LEN + payload.len());
+        datagram.put_u16(self.next_sequence_number);
+        datagram.put_slice(payload);
+        self.next_sequence_number = self.next_sequence_number.wrapping_add(1);
+        datagram.freeze()
+    }
+}
+
+/// Restores the order of datagrams encoded by a [`SequencedDatagramSender`]
+#[derive(Debug)]
+pub struct SequencedDatagramReceiver {
+    /// The sequence number of the next datagram to be delivered
+    expected: u16,
+    /// Out-of-order datagrams, indexed by their distance from `expected`
+    pending: VecDeque<Option<Bytes>>,
+    /// Datagrams that are ready to be delivered in order
+    ready: VecDeque<Bytes>,
+    window: u16,
+    /// The number of datagrams that were presumed lost
+    skipped: u64,
+    /// The number of datagrams that were discarded for arriving too late or more than once
+    discarded: u64,
+}
+
+impl Default for SequencedDatagramReceiver {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl SequencedDatagramReceiver {
+    /// Creates a receiver that holds back at most `window` datagrams while waiting for
+    /// a missing datagram
+    ///
+    /// The window is clamped to between 1 and [`MAX_WINDOW`].
+    pub fn new(window: u16) -> Self {
+        Self {
+            expected: 0,
+            pending: VecDeque::new(),
+            ready: VecDeque::new(),
+            window: window.clamp(1, MAX_WINDOW),
+            skipped: 0,
+            discarded: 0,
+        }
+    }
+
+    /// Processes a received datagram
+    ///
+    /// Datagrams too short to contain a sequence number are discarded.
+    pub fn on_datagram(&mut self, mut datagram: Bytes) {
+        if datagram.len() < SEQUENCE_NUMBER_LEN {
+            self.discarded += 1;
+            return;
+        }
+
+        let sequence_number = u16::from_be_bytes([datagram[0], datagram[1]]);
+        let payload = datagram.split_off(SEQUENCE_NUMBER_LEN);
+
+        let distance = sequence_number.wrapping_sub(self.expected);
+
+        if distance >= 1 << 15 {
+            // the datagram was either already delivered or skipped over
+            self.discarded += 1;
+            return;
+        }
+
+        if distance >= self.window {
+            // the datagram is beyond the window so give up on the oldest missing datagrams
+            self.advance(distance - self.window + 1);
+        }
+
+        let distance = sequence_number.wrapping_sub(self.expected) as usize;
+
+        if self.pending.len() <= distance {
+            self.pending.resize(distance + 1, None);
+        }
+
+        let slot = &mut self.pending[distance];
+        if slot.is_some() {
+            self.discarded += 1;
+            return;
+        }
+        *slot = Some(payload);
+
+        // deliver any datagrams that are now in order
+        while let Some(Some(_)) = self.pending.front() {
+            self.pop_pending();
+        }
+    }
+
+    /// Returns the next datagram payload in order, if any
+    pub fn pop(&mut self) -> Option<Bytes> {
+        self.ready.pop_front()
+    }
+
+    /// Returns the number of datagrams that were presumed lost
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Returns the number of datagrams that were discarded for arriving too late, more than once
+    /// or without a sequence number
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Moves `expected` forward by `count`, releasing any buffered datagrams in order
+    fn advance(&mut self, count: u16) {
+        for _ in 0..count {
+            self.pop_pending();
+        }
+    }
+
+    fn pop_pending(&mut self) {
+        match self.pending.pop_front().flatten() {
+            Some(payload) => self.ready.push_back(payload),
+            None => self.skipped += 1,
+        }
+
+        self.expected = self.expected.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use bolero::check;
+
+    fn payload(value: u32) -> Bytes {
+        Bytes::copy_from_slice(&value.to_be_bytes())
+    }
+
+    fn receive_all(receiver: &mut SequencedDatagramReceiver) -> Vec<Bytes> {
+        core::iter::from_fn(|| receiver.pop()).collect()
+    }
+
+    #[test]
+    fn in_order_test() {
+        let mut sender = SequencedDatagramSender::default();
+        let mut receiver = SequencedDatagramReceiver::default();
+
+        for i in 0..10 {
+            receiver.on_datagram(sender.encode(&payload(i)));
+            assert_eq!(Some(payload(i)), receiver.pop());
+        }
+        assert_eq!(None, receiver.pop());
+    }
+
+    #[test]
+    fn encode_test() {
+        let mut sender = SequencedDatagramSender::default();
+        assert_eq!(&[0, 0, 1, 2][..], &sender.encode(&[1, 2])[..]);
+        assert_eq!(&[0, 1][..], &sender.encode(&[])[..]);
+
+        sender.next_sequence_number = u16::MAX;
+        assert_eq!(&[0xff, 0xff, 3][..], &sender.encode(&[3])[..]);
+        assert_eq!(&[0, 0, 4][..], &sender.encode(&[4])[..]);
+    }
+
+    #[test]
+    fn reorder_test() {
+        let mut sender = SequencedDatagramSender::default();
+        let mut receiver = SequencedDatagramReceiver::default();
+
+        let datagrams: Vec<_> = (0..3).map(|i| sender.encode(&payload(i))).collect();
+
+        receiver.on_datagram(datagrams[0].clone());
+        receiver.on_datagram(datagrams[2].clone());
+        assert_eq!(vec![payload(0)], receive_all(&mut receiver));
+
+        receiver.on_datagram(datagrams[1].clone());
+        assert_eq!(vec![payload(1), payload(2)], receive_all(&mut receiver));
+
+        // duplicates and late datagrams are discarded
+        receiver.on_datagram(datagrams[1].clone());
+        assert_eq!(None, receiver.pop());
+        assert_eq!(1, receiver.discarded());
+    }
+
+    #[test]
+    fn gap_beyond_window_test() {
+        let mut sender = SequencedDatagramSender::default();
+        let mut receiver = SequencedDatagramReceiver::new(4);
+
+        receiver.on_datagram(sender.encode(&payload(0)));
+        assert_eq!(Some(payload(0)), receiver.pop());
+
+        // datagram 1 is lost
+        let _ = sender.encode(&payload(1));
+        for i in 2..5 {
+            receiver.on_datagram(sender.encode(&payload(i)));
+        }
+        assert_eq!(None, receiver.pop());
+
+        // datagram 5 is beyond the window so the gap is skipped
+        receiver.on_datagram(sender.encode(&payload(5)));
+        assert_eq!(
+            vec![payload(2), payload(3), payload(4), payload(5)],
+            receive_all(&mut receiver)
+        );
+        assert_eq!(1, receiver.skipped());
+
+        // the lost datagram arriving late is discarded
+        receiver.on_datagram({
+            let mut sender = SequencedDatagramSender {
+                next_sequence_number: 1,
+            };
+            sender.encode(&payload(1))
+        });
+        assert_eq!(None, receiver.pop());
+        assert_eq!(1, receiver.discarded());
+    }
+
+    #[test]
+    fn wrapping_test() {
+        let mut sender = SequencedDatagramSender {
+            next_sequence_number: u16::MAX - 1,
+        };
+        let mut receiver = SequencedDatagramReceiver::default();
+        receiver.expected = u16::MAX - 1;
+
+        let datagrams: Vec<_> = (0..4).map(|i| sender.encode(&payload(i))).collect();
+        for i in [1, 0, 3, 2] {
+            receiver.on_datagram(datagrams[i].clone());
+        }
+
+        assert_eq!(
+            (0..4).map(payload).collect::<Vec<_>>(),
+            receive_all(&mut receiver)
+        );
+    }
+
+    #[test]
+    fn short_datagram_test() {
+        let mut receiver = SequencedDatagramReceiver::default();
+        receiver.on_datagram(Bytes::from_static(&[1]));
+        assert_eq!(None, receiver.pop());
+        assert_eq!(1, receiver.discarded());
+    }
+
+    /// Delivers datagrams through a network that delays roughly 10% of them
+    #[test]
+    fn reordering_network_test() {
+        const COUNT: u32 = 1000;
+        const WINDOW: u16 = 8;
+
+        check!().with_type::<Vec<u8>>().for_each(|decisions| {
+            let mut sender = SequencedDatagramSender::default();
+            let mut receiver = SequencedDatagramReceiver::new(WINDOW);
+            let mut decisions = decisions.iter().copied().cycle();
+
+            // datagrams held back by the network along with the number of datagrams that
+            // still need to overtake them
+            let mut delayed: Vec<(Bytes, u8)> = Vec::new();
+
+            for i in 0..COUNT {
+                let datagram = sender.encode(&payload(i));
+                let decision = decisions.next().unwrap_or(u8::MAX);
+
+                if decision < 26 {
+                    // hold back the datagram while up to 3 later datagrams overtake it
+                    delayed.push((datagram, decision % 4));
+                } else {
+                    receiver.on_datagram(datagram);
+                }
+
+                let mut index = 0;
+                while index < delayed.len() {
+                    if delayed[index].1 == 0 {
+                        receiver.on_datagram(delayed.remove(index).0);
+                    } else {
+                        delayed[index].1 -= 1;
+                        index += 1;
+                    }
+                }
+            }
+
+            for (datagram, _) in delayed.drain(..) {
+                receiver.on_datagram(datagram);
+            }
+
+            // the reordering stays within the window so every datagram is delivered in order
+            assert_eq!(
+                (0..COUNT).map(payload).collect::<Vec<_>>(),
+                receive_all(&mut receiver)
+            );
+            assert_eq!(0, receiver.skipped());
+            assert_eq!(0, receiver.discarded());
+        });
+    }
+}
@@ -139,37 +139,102 @@ impl<Data: EncoderValue> Stream<Data> {
     /// fit.
     #[inline]
     pub fn try_fit(&mut self, capacity: usize) -> Result<usize, FitError> {
-        let mut fixed_len = 0;
-        fixed_len += size_of::<Tag>();
-        fixed_len += self.stream_id.encoding_size();
+        let planner = FrameSizePlanner::new(self.stream_id, self.offset);
+        let plan = planner.plan(capacity, self.data.encoding_size())?;
+        self.is_last_frame = plan.is_last_frame;
+        Ok(plan.data_len)
+    }
+}
 
-        if self.offset != 0u64 {
-            fixed_len += self.offset.encoding_size();
-        }
+/// The largest length which can be encoded with each size of a variable-length integer
+const LEN_PREFIX_LIMITS: [(usize, u64); 4] = [
+    (1, (1 << 6) - 1),
+    (2, (1 << 14) - 1),
+    (4, (1 << 30) - 1),
+    (8, (1 << 62) - 1),
+];
+
+/// The data length of a STREAM frame selected by the [`FrameSizePlanner`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameSizePlan {
+    /// The number of bytes of stream data to include in the frame
+    pub data_len: usize,
+    /// If true, the frame extends to the end of the packet and omits the Length field
+    pub is_last_frame: bool,
+}
 
-        let remaining_capacity = capacity.checked_sub(fixed_len).ok_or(FitError)?;
+/// Selects how much stream data a STREAM frame should carry to fill the space left in a packet
+///
+/// The space left is whatever remains after the packet headers and the frames which were already
+/// written. If the stream has at least that much data to send, the frame omits its Length field
+/// and fills the packet exactly. Otherwise the frame carries all of the data along with a Length
+/// field, so other frames can follow it.
+///
+/// The Length field is a variable-length integer, so the size of the prefix depends on the
+/// amount of data. The planner picks the largest data length which still fits along with its
+/// prefix, rather than reserving room for a prefix sized for the whole capacity.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameSizePlanner {
+    /// The length of the Type, Stream ID and Offset fields
+    header_len: usize,
+}
 
-        let data_len = self.data.encoding_size();
-        let max_data_len = remaining_capacity.min(data_len);
+impl FrameSizePlanner {
+    #[inline]
+    pub fn new(stream_id: VarInt, offset: VarInt) -> Self {
+        let mut header_len = 0;
+        header_len += size_of::<Tag>();
+        header_len += stream_id.encoding_size();
 
-        // If data fits exactly into the capacity, mark it as the last frame
-        if max_data_len == remaining_capacity {
-            self.is_last_frame = true;
-            return Ok(max_data_len);
+        if offset != 0u64 {
+            header_len += offset.encoding_size();
         }
 
-        self.is_last_frame = false;
+        Self { header_len }
+    }
+
+    /// Selects the data length of a frame written into `capacity` bytes
+    ///
+    /// `data_len` is the encoding size of all of the data the stream can send. A plan with a
+    /// `data_len` of 0 is returned if the space only fits the frame header.
+    #[inline]
+    pub fn plan(&self, capacity: usize, data_len: usize) -> Result<FrameSizePlan, FitError> {
+        let remaining_capacity = capacity.checked_sub(self.header_len).ok_or(FitError)?;
+
+        // If the data fills the rest of the packet, the Length field can be omitted
+        if data_len >= remaining_capacity {
+            return Ok(FrameSizePlan {
+                data_len: remaining_capacity,
+                is_last_frame: true,
+            });
+        }
 
-        let len_prefix_size = VarInt::try_from(max_data_len)
+        let len_prefix_size = VarInt::try_from(data_len)
             .map_err(|_| FitError)?
             .encoding_size();
 
-        let prefixed_data_len = remaining_capacity
-            .checked_sub(len_prefix_size)
+        // The remaining data is sent in full so other frames can follow it
+        if data_len + len_prefix_size <= remaining_capacity {
+            return Ok(FrameSizePlan {
+                data_len,
+                is_last_frame: false,
+            });
+        }
+
+        // Otherwise take as much data as fits along with the smallest prefix it needs
+        let data_len = LEN_PREFIX_LIMITS
+            .iter()
+            .filter_map(|(prefix_size, max_len)| {
+                let len = remaining_capacity.checked_sub(*prefix_size)?;
+                Some((len as u64).min(*max_len) as usize)
+            })
+            .max()
             .ok_or(FitError)?;
-        let data_len = prefixed_data_len.min(data_len);
 
-        Ok(data_len)
+        Ok(FrameSizePlan {
+            data_len,
+            is_last_frame: false,
+        })
     }
 }
 
@@ -344,6 +409,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn frame_size_planner_test() {
+        let planner = FrameSizePlanner::new(VarInt::from_u8(4), VarInt::from_u32(100_000));
+        // type + 1 byte stream id + 4 byte offset
+        let header_len = 6;
+
+        // there's more data than space so the frame fills the packet without a Length field
+        assert_eq!(
+            planner.plan(header_len + 1000, 2000).unwrap(),
+            FrameSizePlan {
+                data_len: 1000,
+                is_last_frame: true,
+            }
+        );
+
+        // the rest of the data is sent with a Length field
+        assert_eq!(
+            planner.plan(header_len + 1000, 500).unwrap(),
+            FrameSizePlan {
+                data_len: 500,
+                is_last_frame: false,
+            }
+        );
+
+        // the data doesn't fit with a 4 byte Length field but 16383 bytes fit with a 2 byte one
+        assert_eq!(
+            planner.plan(header_len + 16386, 16385).unwrap(),
+            FrameSizePlan {
+                data_len: 16383,
+                is_last_frame: false,
+            }
+        );
+
+        // only the header fits
+        assert_eq!(
+            planner.plan(header_len, 100).unwrap(),
+            FrameSizePlan {
+                data_len: 0,
+                is_last_frame: true,
+            }
+        );
+
+        // not even the header fits
+        assert!(planner.plan(header_len - 1, 100).is_err());
+    }
+
     #[test]
     fn try_fit_test() {
         check!()
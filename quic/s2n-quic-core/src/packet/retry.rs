@@ -135,9 +135,22 @@ impl<'a> Retry<'a> {
             return None;
         }
 
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.2
+        //# A client MUST discard a Retry packet with a zero-length
+        //# Retry Token field.
+        if T::TOKEN_LEN == 0 {
+            return None;
+        }
+
         let retry_packet = Retry::from_initial(packet, local_connection_id.as_ref());
         let pseudo_packet = retry_packet.pseudo_packet(packet.destination_connection_id());
 
+        // The pseudo packet is written to the buffer while computing the tag, so the buffer must
+        // also have room for the Original Destination Connection ID
+        if packet_buf.len() < pseudo_packet.encoding_size() + T::TOKEN_LEN + INTEGRITY_TAG_LEN {
+            return None;
+        }
+
         let mut buffer = EncoderBuffer::new(packet_buf);
         pseudo_packet.encode(&mut buffer);
 
@@ -0,0 +1,274 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detects when the quality of a path degrades
+//!
+//! The packet loss rate and RTT of a path are averaged with exponentially decaying weights, so
+//! samples lose half of their weight roughly every 42 seconds ([`EWMA_PERIOD`] × ln 2). A path
+//! is considered degraded once the averaged loss rate exceeds [`MAX_LOSS_RATE`] or the averaged
+//! RTT exceeds the minimum RTT of the path by more than [`MAX_RTT_RATIO`].
+
+use crate::time::Timestamp;
+use core::time::Duration;
+#[cfg(not(feature = "std"))]
+use num_traits::Float as _;
+
+/// The time constant of the exponentially weighted moving averages
+pub const EWMA_PERIOD: Duration = Duration::from_secs(60);
+
+/// The averaged packet loss rate above which a path is considered degraded
+pub const MAX_LOSS_RATE: f64 = 0.05;
+
+/// The ratio of the averaged RTT to the minimum RTT above which a path is considered degraded
+pub const MAX_RTT_RATIO: f64 = 2.0;
+
+/// The (weighted) number of packets needed before the loss rate is considered
+///
+/// This prevents a handful of losses on a new path from being reported as degradation.
+const MIN_PACKETS: f64 = 100.0;
+
+/// The (weighted) number of RTT samples needed before the RTT ratio is considered
+const MIN_RTT_SAMPLES: f64 = 10.0;
+
+/// The quality of a path at the time it was found to be degraded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Degradation {
+    pub loss_rate: f64,
+    pub rtt_ratio: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PathQualityMonitor {
+    /// The decayed number of acknowledged and lost packets
+    packets: f64,
+    /// The decayed number of lost packets
+    lost: f64,
+    /// The decayed sum of RTT samples, in seconds
+    rtt_sum: f64,
+    /// The decayed number of RTT samples
+    rtt_samples: f64,
+    /// The minimum RTT of the path, used as the baseline for the RTT ratio
+    min_rtt: Duration,
+    /// The time the sums were last decayed
+    last_update: Option<Timestamp>,
+    /// Set once degradation is reported and cleared once the path recovers
+    degraded: bool,
+}
+
+impl PathQualityMonitor {
+    /// Called when a congestion controlled packet sent on the path is acknowledged
+    #[inline]
+    pub fn on_packet_ack(&mut self, now: Timestamp) {
+        self.decay(now);
+        self.packets += 1.0;
+    }
+
+    /// Called when a congestion controlled packet sent on the path is declared lost
+    #[inline]
+    pub fn on_packet_lost(&mut self, now: Timestamp) {
+        self.decay(now);
+        self.packets += 1.0;
+        self.lost += 1.0;
+    }
+
+    /// Called when the RTT estimator of the path produces a new sample
+    #[inline]
+    pub fn on_rtt_sample(&mut self, rtt: Duration, min_rtt: Duration, now: Timestamp) {
+        self.decay(now);
+        self.rtt_sum += rtt.as_secs_f64();
+        self.rtt_samples += 1.0;
+        self.min_rtt = min_rtt;
+    }
+
+    /// Returns the averaged packet loss rate
+    ///
+    /// `0.0` is returned until enough packets have been observed.
+    #[inline]
+    pub fn loss_rate(&self) -> f64 {
+        if self.packets < MIN_PACKETS {
+            return 0.0;
+        }
+
+        self.lost / self.packets
+    }
+
+    /// Returns the averaged RTT divided by the minimum RTT
+    ///
+    /// `1.0` is returned until enough RTT samples have been observed.
+    #[inline]
+    pub fn rtt_ratio(&self) -> f64 {
+        if self.rtt_samples < MIN_RTT_SAMPLES || self.min_rtt.is_zero() {
+            return 1.0;
+        }
+
+        self.rtt_sum / self.rtt_samples / self.min_rtt.as_secs_f64()
+    }
+
+    /// Returns `true` if the path was degraded as of the last call to `poll_degradation`
+    #[inline]
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Returns the current quality of the path if it just became degraded
+    ///
+    /// Degradation is only returned once. It is returned again after the path recovers and
+    /// subsequently degrades again.
+    pub fn poll_degradation(&mut self) -> Option<Degradation> {
+        let loss_rate = self.loss_rate();
+        let rtt_ratio = self.rtt_ratio();
+        let degraded = loss_rate > MAX_LOSS_RATE || rtt_ratio > MAX_RTT_RATIO;
+
+        let newly_degraded = degraded && !self.degraded;
+        self.degraded = degraded;
+
+        if newly_degraded {
+            Some(Degradation {
+                loss_rate,
+                rtt_ratio,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Discards all of the samples
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    #[inline]
+    fn decay(&mut self, now: Timestamp) {
+        let last_update = if let Some(last_update) = self.last_update.replace(now) {
+            last_update
+        } else {
+            return;
+        };
+
+        let elapsed = now.saturating_duration_since(last_update);
+        if elapsed.is_zero() {
+            return;
+        }
+
+        let factor = (-elapsed.as_secs_f64() / EWMA_PERIOD.as_secs_f64()).exp();
+        self.packets *= factor;
+        self.lost *= factor;
+        self.rtt_sum *= factor;
+        self.rtt_samples *= factor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{testing::Clock, Clock as _};
+
+    const RTT: Duration = Duration::from_millis(50);
+
+    /// Sends `packets` packets over `duration`, losing one in every `loss_interval`
+    fn send(
+        monitor: &mut PathQualityMonitor,
+        clock: &mut Clock,
+        packets: u32,
+        duration: Duration,
+        loss_interval: u32,
+        rtt: Duration,
+    ) {
+        for packet in 0..packets {
+            clock.inc_by(duration / packets);
+            let now = clock.get_time();
+            if loss_interval > 0 && packet % loss_interval == 0 {
+                monitor.on_packet_lost(now);
+            } else {
+                monitor.on_packet_ack(now);
+                monitor.on_rtt_sample(rtt, RTT, now);
+            }
+        }
+    }
+
+    #[test]
+    fn healthy_path_test() {
+        let mut clock = Clock::default();
+        let mut monitor = PathQualityMonitor::default();
+
+        // 1% loss with a stable RTT
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 100, RTT);
+
+        assert!((monitor.loss_rate() - 0.01).abs() < 0.005);
+        assert!((monitor.rtt_ratio() - 1.0).abs() < 0.01);
+        assert_eq!(None, monitor.poll_degradation());
+        assert!(!monitor.is_degraded());
+    }
+
+    #[test]
+    fn loss_degradation_test() {
+        let mut clock = Clock::default();
+        let mut monitor = PathQualityMonitor::default();
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 100, RTT);
+        assert_eq!(None, monitor.poll_degradation());
+
+        // 10% loss for a minute pushes the average above the threshold
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 10, RTT);
+
+        let degradation = monitor.poll_degradation().unwrap();
+        assert!(degradation.loss_rate > MAX_LOSS_RATE);
+        assert!(degradation.loss_rate < 0.1);
+        assert!(monitor.is_degraded());
+
+        // degradation is only reported once
+        send(&mut monitor, &mut clock, 1000, EWMA_PERIOD / 10, 10, RTT);
+        assert_eq!(None, monitor.poll_degradation());
+
+        // the path recovers after the losses stop
+        send(&mut monitor, &mut clock, 50_000, EWMA_PERIOD * 5, 0, RTT);
+        assert_eq!(None, monitor.poll_degradation());
+        assert!(!monitor.is_degraded());
+    }
+
+    #[test]
+    fn short_loss_burst_test() {
+        let mut clock = Clock::default();
+        let mut monitor = PathQualityMonitor::default();
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 0, RTT);
+
+        // a second of 50% loss is smoothed out by the previous minute
+        send(
+            &mut monitor,
+            &mut clock,
+            200,
+            Duration::from_secs(1),
+            2,
+            RTT,
+        );
+        assert_eq!(None, monitor.poll_degradation());
+    }
+
+    #[test]
+    fn rtt_degradation_test() {
+        let mut clock = Clock::default();
+        let mut monitor = PathQualityMonitor::default();
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 0, RTT);
+        assert_eq!(None, monitor.poll_degradation());
+
+        send(&mut monitor, &mut clock, 10_000, EWMA_PERIOD, 0, RTT * 4);
+
+        let degradation = monitor.poll_degradation().unwrap();
+        assert_eq!(0.0, degradation.loss_rate);
+        assert!(degradation.rtt_ratio > MAX_RTT_RATIO);
+    }
+
+    #[test]
+    fn min_samples_test() {
+        let mut clock = Clock::default();
+        let mut monitor = PathQualityMonitor::default();
+
+        // every other packet is lost, but there are too few packets to tell
+        send(&mut monitor, &mut clock, 50, Duration::from_secs(1), 2, RTT);
+        assert_eq!(0.0, monitor.loss_rate());
+        assert_eq!(None, monitor.poll_degradation());
+
+        monitor.reset();
+        assert_eq!(None, monitor.last_update);
+    }
+}
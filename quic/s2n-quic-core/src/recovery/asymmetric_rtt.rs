@@ -0,0 +1,153 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splits the round-trip time into the delays of each direction of the path
+//!
+//! An RTT sample covers the delay towards the peer, the time the peer held back the ACK and the
+//! delay of the ACK on the way back. The `RttEstimator` already removes the peer's ACK delay, but
+//! can't tell how the rest is split between the two directions. On asymmetric paths, like a
+//! satellite link with a much slower uplink, half of the RTT is a poor estimate of either delay.
+//!
+//! Splitting the RTT requires knowing when the peer received the packet, measured against the
+//! local clock. QUIC doesn't carry this, so it is only available when the clocks of both
+//! endpoints are synchronized by other means, such as a timestamp extension. Without it, no
+//! delays are estimated. No such extension is implemented yet, so the estimator is internal
+//! until one is.
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// Estimates the forward and return delays of a path from synchronized RTT samples
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AsymmetricRttEstimator {
+    /// The smoothed delay from the local endpoint to the peer
+    forward_delay: Option<Duration>,
+    /// The smoothed delay from the peer to the local endpoint, excluding the peer's ACK delay
+    return_delay: Option<Duration>,
+}
+
+impl AsymmetricRttEstimator {
+    /// Returns the smoothed delay from the local endpoint to the peer
+    ///
+    /// Returns `None` until a sample with a synchronized peer receive time has been taken.
+    #[inline]
+    pub(crate) fn forward_delay(&self) -> Option<Duration> {
+        self.forward_delay
+    }
+
+    /// Returns the smoothed delay from the peer to the local endpoint
+    ///
+    /// Returns `None` until a sample with a synchronized peer receive time has been taken.
+    #[inline]
+    pub(crate) fn return_delay(&self) -> Option<Duration> {
+        self.return_delay
+    }
+
+    /// Called with the RTT sample of a newly acknowledged packet
+    ///
+    /// `ack_delay` is the delay reported by the peer in the ACK frame, limited in the same way as
+    /// it is for the `RttEstimator`. `peer_received` is the time the peer received the packet,
+    /// expressed in the local clock, if the endpoint clocks are synchronized.
+    ///
+    /// Samples which are inconsistent with the send and receive times are ignored, since they
+    /// indicate the clocks have drifted apart.
+    #[inline]
+    pub(crate) fn on_rtt_sample(
+        &mut self,
+        time_sent: Timestamp,
+        ack_received: Timestamp,
+        ack_delay: Duration,
+        peer_received: Option<Timestamp>,
+    ) {
+        let peer_received = match peer_received {
+            Some(peer_received) => peer_received,
+            None => return,
+        };
+
+        if peer_received < time_sent || peer_received + ack_delay > ack_received {
+            return;
+        }
+
+        let forward_delay = peer_received - time_sent;
+        let return_delay = ack_received - peer_received - ack_delay;
+
+        self.forward_delay = Some(smooth(self.forward_delay, forward_delay));
+        self.return_delay = Some(smooth(self.return_delay, return_delay));
+    }
+}
+
+/// Smooths the samples with the same weights as the smoothed RTT
+///
+/// See https://www.rfc-editor.org/rfc/rfc9002#section-5.3
+#[inline]
+fn smooth(smoothed: Option<Duration>, sample: Duration) -> Duration {
+    match smoothed {
+        Some(smoothed) => 7 * smoothed / 8 + sample / 8,
+        None => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    #[test]
+    fn asymmetric_path_test() {
+        // a 3:1 asymmetric path, like a satellite link
+        let forward = Duration::from_millis(600);
+        let backward = Duration::from_millis(200);
+
+        let mut estimator = AsymmetricRttEstimator::default();
+        let start = NoopClock.get_time();
+
+        for i in 0..100u32 {
+            let time_sent = start + Duration::from_millis(10) * i;
+            let peer_received = time_sent + forward;
+            // the peer delays some of the ACKs more than others
+            let ack_delay = Duration::from_millis(5) * (i % 6);
+            let ack_received = peer_received + ack_delay + backward;
+
+            estimator.on_rtt_sample(time_sent, ack_received, ack_delay, Some(peer_received));
+
+            // the ACK delay is only removed from the return delay
+            assert_eq!(estimator.forward_delay(), Some(forward));
+            assert_eq!(estimator.return_delay(), Some(backward));
+        }
+    }
+
+    #[test]
+    fn sample_test() {
+        let mut estimator = AsymmetricRttEstimator::default();
+        let time_sent = NoopClock.get_time() + Duration::from_secs(1);
+        let ack_received = time_sent + Duration::from_millis(100);
+        let ack_delay = Duration::from_millis(10);
+
+        // without synchronized clocks nothing is estimated
+        estimator.on_rtt_sample(time_sent, ack_received, ack_delay, None);
+        assert_eq!(estimator.forward_delay(), None);
+        assert_eq!(estimator.return_delay(), None);
+
+        // receive times outside of the round trip are ignored
+        for peer_received in [
+            time_sent - Duration::from_millis(1),
+            ack_received,
+            ack_received - ack_delay + Duration::from_millis(1),
+        ] {
+            estimator.on_rtt_sample(time_sent, ack_received, ack_delay, Some(peer_received));
+            assert_eq!(estimator.forward_delay(), None);
+            assert_eq!(estimator.return_delay(), None);
+        }
+
+        let peer_received = time_sent + Duration::from_millis(30);
+        estimator.on_rtt_sample(time_sent, ack_received, ack_delay, Some(peer_received));
+        assert_eq!(estimator.forward_delay(), Some(Duration::from_millis(30)));
+        assert_eq!(estimator.return_delay(), Some(Duration::from_millis(60)));
+
+        // later samples are smoothed
+        let peer_received = time_sent + Duration::from_millis(70);
+        estimator.on_rtt_sample(time_sent, ack_received, ack_delay, Some(peer_received));
+        assert_eq!(estimator.forward_delay(), Some(Duration::from_millis(35)));
+        assert_eq!(estimator.return_delay(), Some(Duration::from_millis(55)));
+    }
+}
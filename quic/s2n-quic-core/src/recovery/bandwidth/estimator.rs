@@ -57,6 +57,16 @@ impl Bandwidth {
             }
         }
     }
+
+    /// Constructs a new `Bandwidth` with the given number of bits per second
+    pub const fn from_bits_per_second(bits_per_second: u64) -> Self {
+        Self { bits_per_second }
+    }
+
+    /// Returns the number of bits per second
+    pub const fn as_bits_per_second(&self) -> u64 {
+        self.bits_per_second
+    }
 }
 
 impl core::ops::Mul<Ratio<u64>> for Bandwidth {
@@ -2,4 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub use estimator::*;
+pub use probe::*;
 mod estimator;
+mod probe;
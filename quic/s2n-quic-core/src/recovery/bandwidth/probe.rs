@@ -0,0 +1,94 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::recovery::bandwidth::Bandwidth;
+use core::time::Duration;
+
+/// The default amount of time a bandwidth probe runs for
+pub const DEFAULT_BANDWIDTH_PROBE_DURATION: Duration = Duration::from_secs(5);
+
+/// A summary of the delivery rates measured during a bandwidth probe
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BandwidthEstimate {
+    /// The lowest delivery rate measured
+    pub min: Bandwidth,
+    /// The highest delivery rate measured
+    pub max: Bandwidth,
+    /// The median delivery rate
+    pub p50: Bandwidth,
+    /// The 95th percentile delivery rate
+    pub p95: Bandwidth,
+}
+
+impl BandwidthEstimate {
+    /// Summarizes the given delivery rate samples
+    ///
+    /// The samples are sorted in place. `None` is returned if no samples were provided.
+    pub fn from_samples(samples: &mut [Bandwidth]) -> Option<Self> {
+        let min = *samples.iter().min()?;
+        let max = *samples.iter().max()?;
+
+        samples.sort_unstable();
+
+        Some(Self {
+            min,
+            max,
+            p50: percentile(samples, 50),
+            p95: percentile(samples, 95),
+        })
+    }
+}
+
+/// Returns the nearest-rank percentile of the sorted `samples`
+#[inline]
+fn percentile(samples: &[Bandwidth], percentile: usize) -> Bandwidth {
+    debug_assert!(!samples.is_empty());
+    debug_assert!(percentile <= 100);
+
+    let rank = (percentile * samples.len() + 99) / 100;
+    samples[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bps(value: u64) -> Bandwidth {
+        Bandwidth::from_bits_per_second(value)
+    }
+
+    #[test]
+    fn empty_test() {
+        assert_eq!(None, BandwidthEstimate::from_samples(&mut []));
+    }
+
+    #[test]
+    fn single_sample_test() {
+        let estimate = BandwidthEstimate::from_samples(&mut [bps(10)]).unwrap();
+
+        assert_eq!(bps(10), estimate.min);
+        assert_eq!(bps(10), estimate.max);
+        assert_eq!(bps(10), estimate.p50);
+        assert_eq!(bps(10), estimate.p95);
+    }
+
+    #[test]
+    fn percentile_test() {
+        // 1..=100 in reverse order
+        let mut samples: Vec<_> = (1..=100).rev().map(bps).collect();
+        let estimate = BandwidthEstimate::from_samples(&mut samples).unwrap();
+
+        assert_eq!(bps(1), estimate.min);
+        assert_eq!(bps(100), estimate.max);
+        assert_eq!(bps(50), estimate.p50);
+        assert_eq!(bps(95), estimate.p95);
+
+        let mut samples = [bps(4), bps(1), bps(3), bps(2)];
+        let estimate = BandwidthEstimate::from_samples(&mut samples).unwrap();
+
+        assert_eq!(bps(1), estimate.min);
+        assert_eq!(bps(4), estimate.max);
+        assert_eq!(bps(2), estimate.p50);
+        assert_eq!(bps(4), estimate.p95);
+    }
+}
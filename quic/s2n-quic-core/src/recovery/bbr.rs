@@ -3,13 +3,20 @@
 
 use crate::{
     counter::Counter,
+    packet::number::PacketNumberSpace,
     random,
     recovery::{
-        bandwidth, bandwidth::Bandwidth, bbr::probe_bw::CyclePhase, CongestionController,
-        RttEstimator,
+        bandwidth,
+        bandwidth::Bandwidth,
+        bbr::probe_bw::CyclePhase,
+        congestion_controller::{ReservedBandwidth, StartupCompleted},
+        CongestionController, RttEstimator,
     },
+    stream::StreamPriority,
     time::Timestamp,
 };
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 use core::{
     cmp::{max, min},
     convert::TryInto,
@@ -18,18 +25,40 @@ use core::{
 use num_rational::Ratio;
 use num_traits::One;
 
+mod app_limited;
+#[cfg(feature = "alloc")]
+mod bandwidth_kalman;
+mod careful_resume;
+mod confidence;
 mod congestion;
 mod data_rate;
 mod data_volume;
+mod decaying_min_rtt;
 mod drain;
 mod full_pipe;
+mod idle_restart;
+mod post_idle;
 mod probe_bw;
 mod probe_rtt;
 mod recovery;
 mod round;
 mod startup;
+mod startup_duration;
 mod windowed_filter;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "alloc")]
+pub use bandwidth_kalman::{BandwidthEstimator, KalmanBandwidthEstimator};
+pub use careful_resume::{CarefulResumeParams, MAX_PARAMS_AGE};
+pub use confidence::BandwidthConfidence;
+pub use decaying_min_rtt::DecayingMinRtt;
+pub use full_pipe::FullPipeReason;
+pub use idle_restart::IdleRestartPolicy;
+
 //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.8
 //# The maximum tolerated per-round-trip packet loss rate when probing for bandwidth (the default is 2%).
 const LOSS_THRESH: Ratio<u32> = Ratio::new_raw(1, 50);
@@ -158,8 +187,9 @@ impl State {
     fn transition_to(&mut self, new_state: State) {
         if cfg!(debug_assertions) {
             match &new_state {
-                // BBR is initialized in the Startup state, but may re-enter Startup after ProbeRtt
-                State::Startup => assert!(self.is_probing_rtt()),
+                // BBR is initialized in the Startup state, but may re-enter Startup after ProbeRtt,
+                // or from Drain if the pipe was only estimated to be full due to a spurious loss
+                State::Startup => assert!(self.is_probing_rtt() || self.is_drain()),
                 State::Drain => assert!(self.is_startup()),
                 State::ProbeBw(_) => assert!(self.is_drain() || self.is_probing_rtt()),
                 State::ProbeRtt(_) => {} // ProbeRtt may be entered anytime
@@ -170,6 +200,84 @@ impl State {
     }
 }
 
+/// Configuration for the BBR congestion controller
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BbrConfig {
+    /// Disables limiting the size of the burst sent after the connection has been idle
+    ///
+    /// By default, the first burst after an idle period longer than a round trip is limited to
+    /// twice the maximum datagram size and the limit is increased to the full congestion window
+    /// over the following 3 round trips.
+    pub disable_post_idle_burst_limiting: bool,
+    /// Overrides the initial congestion window, in bytes
+    ///
+    /// By default, the initial congestion window is ten times the maximum datagram size as
+    /// recommended in [RFC 9002](https://www.rfc-editor.org/rfc/rfc9002#section-7.2). Paths
+    /// with a large bandwidth-delay product, such as those within a datacenter, may benefit
+    /// from a larger initial window. Once the initial flight has been acknowledged, the
+    /// congestion window is reduced to the estimated bandwidth-delay product of the path if
+    /// the configured value turns out to be too large.
+    pub initial_congestion_window: Option<u64>,
+    /// Overrides the number of recent delivery rate samples used to compute the
+    /// [`BandwidthConfidence`] interval
+    ///
+    /// Defaults to 10 samples. Values are clamped to between 1 and 32 samples.
+    pub bw_probe_samples: Option<u8>,
+    /// Estimates the minimum RTT with an exponentially decaying minimum
+    ///
+    /// By default, the minimum RTT is the smallest RTT sampled over the last 10 seconds.
+    /// See [`DecayingMinRtt`] for an estimate better suited to long-lived connections.
+    pub decaying_min_rtt: Option<DecayingMinRtt>,
+    /// Seeds the congestion window with the parameters of a previous connection to the same peer
+    ///
+    /// The initial congestion window is increased up to the congestion window of the previous
+    /// connection and is paced out over the minimum RTT of the previous connection. If any
+    /// packet in the initial flight is lost, the congestion window immediately retreats to the
+    /// initial window that would have been used otherwise. Parameters older than
+    /// [`MAX_PARAMS_AGE`] are ignored.
+    pub careful_resume: Option<CarefulResumeParams>,
+    /// Reserves part of the pacing rate for streams at or above a priority
+    ///
+    /// By default, all streams share the pacing rate.
+    pub bandwidth_reservation: Option<BandwidthReservation>,
+    /// Adjusts the congestion window when the connection resumes sending after being idle
+    ///
+    /// By default, the congestion window is kept. See [`IdleRestartPolicy`] for the options.
+    pub idle_restart_policy: IdleRestartPolicy,
+    /// Estimates the bandwidth for reporting alongside the windowed maximum filter
+    #[cfg(feature = "alloc")]
+    bandwidth_estimator: Option<Box<dyn BandwidthEstimator>>,
+}
+
+impl BbrConfig {
+    /// Sets an estimator that tracks the bandwidth available to the connection for reporting
+    ///
+    /// BBR itself always paces from the largest delivery rate sampled over the current and
+    /// previous bandwidth probing cycle, which may be slow to reflect a sudden drop in
+    /// bandwidth. The estimator is provided the same samples and its estimate is returned by
+    /// [`BbrCongestionController::estimated_bandwidth`]. See [`KalmanBandwidthEstimator`] for
+    /// an estimator that tracks such drops more closely.
+    #[cfg(feature = "alloc")]
+    pub fn set_bandwidth_estimator(&mut self, bandwidth_estimator: Box<dyn BandwidthEstimator>) {
+        self.bandwidth_estimator = Some(bandwidth_estimator);
+    }
+}
+
+/// Sending rate reserved for streams at or above a priority
+///
+/// While BBR is in Startup or ProbeBW, the reserved rate is subtracted from the pacing rate
+/// available to streams below `for_priority`, so that higher priority streams always have
+/// budget available even when lower priority streams would otherwise saturate the connection.
+/// Streams at or above `for_priority` may use the full pacing rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BandwidthReservation {
+    /// The sending rate, in bytes per second, held back from lower priority streams
+    pub reserved_bytes_per_second: u64,
+    /// The lowest priority that may use the reserved rate
+    pub for_priority: StreamPriority,
+}
+
 /// A congestion controller that implements "Bottleneck Bandwidth and Round-trip propagation time"
 /// version 2 (BBRv2) as specified in <https://datatracker.ietf.org/doc/draft-cardwell-iccrg-bbr-congestion-control/>.
 ///
@@ -208,6 +316,31 @@ struct BbrCongestionController {
     next_departure_time: Option<Timestamp>,
     /// The maximum size of a data aggregate scheduled and transmitted together
     send_quantum: usize,
+    /// Limits the burst sent when the connection restarts after being idle
+    post_idle_burst_limiter: post_idle::PostIdleBurstLimiter,
+    /// The initial congestion window
+    initial_cwnd: u32,
+    /// True if the initial congestion window was configured to be larger than the default
+    /// and has not yet been checked against the bandwidth-delay product of the path
+    initial_cwnd_unchecked: bool,
+    /// The most recent delivery rate samples, used to compute the bandwidth confidence interval
+    bandwidth_samples: confidence::Samples,
+    /// Set if the congestion window was seeded from the parameters of a previous connection
+    /// and no packet loss has been detected since
+    careful_resume: Option<careful_resume::Unvalidated>,
+    /// Refines the app-limited signal from the transport based on the send buffer
+    app_limited_detector: app_limited::AppLimitedDetector,
+    /// Sending rate reserved for higher priority streams
+    bandwidth_reservation: Option<BandwidthReservation>,
+    /// Adjusts the congestion window when the connection resumes sending after being idle
+    idle_restart_policy: IdleRestartPolicy,
+    /// Detects when the connection resumes sending after being idle for longer than a PTO
+    idle_detector: idle_restart::IdleDetector,
+    /// The congestion window the connection restarts with after being idle, which is the
+    /// initial window before it was seeded by Careful Resume
+    restart_cwnd: u32,
+    /// Measures the time spent in Startup before entering Drain
+    startup_duration: startup_duration::StartupDurationMeasurer,
 }
 
 type BytesInFlight = Counter<u32>;
@@ -216,7 +349,11 @@ impl CongestionController for BbrCongestionController {
     type PacketInfo = bandwidth::PacketInfo;
 
     fn congestion_window(&self) -> u32 {
-        self.cwnd
+        self.post_idle_burst_limiter.limit(
+            self.cwnd,
+            self.round_counter.round_count(),
+            self.max_datagram_size,
+        )
     }
 
     fn bytes_in_flight(&self) -> u32 {
@@ -243,11 +380,30 @@ impl CongestionController for BbrCongestionController {
         time_sent: Timestamp,
         sent_bytes: usize,
         app_limited: Option<bool>,
-        _rtt_estimator: &RttEstimator,
+        rtt_estimator: &RttEstimator,
     ) -> Self::PacketInfo {
         if sent_bytes > 0 {
             self.recovery_state.on_packet_sent();
 
+            if self.state.is_startup() {
+                self.startup_duration.on_packet_sent(time_sent);
+            }
+
+            self.post_idle_burst_limiter.on_packet_sent(
+                time_sent,
+                *self.bytes_in_flight,
+                rtt_estimator.smoothed_rtt(),
+                self.round_counter.round_count(),
+            );
+
+            let pto = rtt_estimator.pto_period(1, PacketNumberSpace::ApplicationData);
+            if self
+                .idle_detector
+                .on_packet_sent(time_sent, *self.bytes_in_flight, pto)
+            {
+                self.apply_idle_restart_policy();
+            }
+
             //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#4.2.2
             //# BBROnTransmit():
             //#   BBRHandleRestartFromIdle()
@@ -259,6 +415,14 @@ impl CongestionController for BbrCongestionController {
             self.set_next_departure_time(sent_bytes, time_sent);
         }
 
+        let app_limited = self.app_limited_detector.on_packet_sent(
+            time_sent,
+            app_limited,
+            self.is_send_buffer_low(rtt_estimator.smoothed_rtt()),
+            rtt_estimator.smoothed_rtt(),
+            self.round_counter.round_count(),
+        );
+
         self.bw_estimator
             .on_packet_sent(*self.bytes_in_flight, app_limited, time_sent)
     }
@@ -284,16 +448,24 @@ impl CongestionController for BbrCongestionController {
         self.bytes_in_flight
             .try_sub(bytes_acknowledged)
             .expect("bytes_acknowledged should not exceed u32::MAX");
+        self.app_limited_detector.on_send_buffer_update(
+            ack_receive_time,
+            self.is_send_buffer_low(rtt_estimator.smoothed_rtt()),
+        );
         self.bw_estimator.on_ack(
             bytes_acknowledged,
             newest_acked_time_sent,
             newest_acked_packet_info,
             ack_receive_time,
         );
+        self.bandwidth_samples
+            .on_rate_sample(self.bw_estimator.rate_sample());
         self.round_counter.on_ack(
             newest_acked_packet_info,
             self.bw_estimator.delivered_bytes(),
         );
+        self.post_idle_burst_limiter
+            .on_ack(self.round_counter.round_count());
         if self
             .recovery_state
             .on_ack(self.round_counter.round_start(), newest_acked_time_sent)
@@ -337,7 +509,7 @@ impl CongestionController for BbrCongestionController {
         //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#4.2.3
         //# BBRCheckStartupDone()
         //# BBRCheckDrain()
-        self.check_startup_done();
+        self.check_startup_done(ack_receive_time);
         self.check_drain_done(random_generator, ack_receive_time);
 
         //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#4.2.3
@@ -355,8 +527,11 @@ impl CongestionController for BbrCongestionController {
         //# BBRCheckProbeRTT()
         //# BBRAdvanceLatestDeliverySignals()
         //# BBRBoundBWForModel()
-        self.data_volume_model
-            .update_min_rtt(rtt_estimator.latest_rtt(), ack_receive_time);
+        self.data_volume_model.update_min_rtt(
+            rtt_estimator.latest_rtt(),
+            self.round_counter.round_start(),
+            ack_receive_time,
+        );
         self.check_probe_rtt(random_generator, ack_receive_time);
         self.congestion_state
             .advance(self.bw_estimator.rate_sample());
@@ -369,6 +544,7 @@ impl CongestionController for BbrCongestionController {
         //#   BBRSetCwnd()
         self.set_pacing_rate(self.state.pacing_gain());
         self.set_send_quantum();
+        self.check_initial_cwnd();
         self.set_cwnd(bytes_acknowledged);
     }
 
@@ -393,6 +569,17 @@ impl CongestionController for BbrCongestionController {
         //# BBRUpdateOnLoss(packet):
         //#   BBRHandleLostPacket(packet)
         self.handle_lost_packet(lost_bytes, packet_info, random_generator, timestamp);
+
+        self.check_careful_resume_loss();
+    }
+
+    #[inline]
+    fn on_spurious_loss(&mut self) {
+        // Once BBR has left Drain, it has already started probing for more bandwidth
+        if self.state.is_drain() && self.full_pipe_estimator.on_spurious_loss() {
+            self.enter_startup();
+            self.startup_duration.on_resume_startup();
+        }
     }
 
     fn on_explicit_congestion(&mut self, ce_count: u64, event_time: Timestamp) {
@@ -418,12 +605,47 @@ impl CongestionController for BbrCongestionController {
     fn send_quantum(&self) -> Option<usize> {
         Some(self.send_quantum)
     }
+
+    fn reserved_bandwidth(&self) -> Option<ReservedBandwidth> {
+        let reservation = self.bandwidth_reservation?;
+
+        // Headroom is only reserved while BBR is sending at or above the estimated bandwidth.
+        // Drain and ProbeRTT already pace below the bottleneck bandwidth.
+        if !(self.state.is_startup() || self.state.is_probing_bw()) {
+            return None;
+        }
+
+        let reserved_bits_per_second = reservation.reserved_bytes_per_second.saturating_mul(8);
+        let unreserved = Bandwidth::from_bits_per_second(
+            self.pacing_rate
+                .as_bits_per_second()
+                .saturating_sub(reserved_bits_per_second),
+        );
+
+        Some(ReservedBandwidth {
+            for_priority: reservation.for_priority,
+            unreserved,
+        })
+    }
+
+    #[inline]
+    fn bandwidth_estimate(&self) -> Option<Bandwidth> {
+        Some(self.data_rate_model.bw()).filter(|bw| *bw > Bandwidth::ZERO)
+    }
+
+    #[inline]
+    fn startup_completed(&self) -> Option<StartupCompleted> {
+        Some(StartupCompleted {
+            duration: self.startup_duration.startup_duration()?,
+            reason: self.full_pipe_estimator.reason()?,
+        })
+    }
 }
 
 impl BbrCongestionController {
     /// Constructs a new `BbrCongestionController`
     #[allow(dead_code)] // TODO: Remove when used
-    pub fn new(max_datagram_size: u16, now: Timestamp) -> Self {
+    pub fn new(max_datagram_size: u16, config: BbrConfig, now: Timestamp) -> Self {
         //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#4.2.1
         //# BBROnInit():
         //#   init_windowed_max_filter(filter=BBR.MaxBwFilter, value=0, time=0)
@@ -451,9 +673,59 @@ impl BbrCongestionController {
         //# BBRInitPacingRate():
         //#   nominal_bandwidth = InitialCwnd / (SRTT ? SRTT : 1ms)
         //# BBR.pacing_rate =  BBRStartupPacingGain * nominal_bandwidth
-        let initial_cwnd = Self::initial_window(max_datagram_size);
+        let default_initial_cwnd = Self::initial_window(max_datagram_size);
+        let initial_cwnd = config
+            .initial_congestion_window
+            .map(|initial_cwnd| {
+                initial_cwnd
+                    .try_into()
+                    .unwrap_or(u32::MAX)
+                    .max((MIN_PIPE_CWND_PACKETS * max_datagram_size) as u32)
+            })
+            .unwrap_or(default_initial_cwnd);
         let nominal_bandwidth = Bandwidth::new(initial_cwnd as u64, Duration::from_millis(1));
-        let pacing_rate = nominal_bandwidth * State::Startup.pacing_gain();
+        let mut pacing_rate = nominal_bandwidth * State::Startup.pacing_gain();
+
+        let mut careful_resume = None;
+        let safe_cwnd = initial_cwnd;
+        let initial_cwnd = match config.careful_resume.filter(|params| params.is_fresh(now)) {
+            // the resumed window is capped at the congestion window of the previous connection
+            Some(params) if params.cwnd > safe_cwnd as u64 => {
+                careful_resume = Some(careful_resume::Unvalidated {
+                    safe_cwnd,
+                    safe_pacing_rate: pacing_rate,
+                    // the initial flight has been acknowledged once round 2 has started
+                    validated_round: 2,
+                });
+
+                let resume_cwnd = params.cwnd.try_into().unwrap_or(u32::MAX);
+                // the min RTT of the previous connection stands in for SRTT
+                let min_rtt = params.min_rtt.max(Duration::from_micros(1));
+                let nominal_bandwidth = Bandwidth::new(resume_cwnd as u64, min_rtt);
+                pacing_rate = nominal_bandwidth * State::Startup.pacing_gain();
+
+                resume_cwnd
+            }
+            _ => initial_cwnd,
+        };
+
+        #[cfg(feature = "alloc")]
+        let data_rate_model = match config.bandwidth_estimator {
+            Some(bandwidth_estimator) => {
+                data_rate::Model::with_bandwidth_estimator(bandwidth_estimator)
+            }
+            None => data_rate::Model::new(),
+        };
+        #[cfg(not(feature = "alloc"))]
+        let data_rate_model = data_rate::Model::new();
+
+        // initialize extra_acked_interval_start and extra_acked_delivered
+        let data_volume_model = match config.decaying_min_rtt {
+            Some(decaying_min_rtt) => {
+                data_volume::Model::with_decaying_min_rtt(now, decaying_min_rtt)
+            }
+            None => data_volume::Model::new(now),
+        };
 
         Self {
             state: State::Startup,
@@ -465,17 +737,101 @@ impl BbrCongestionController {
             prior_cwnd: 0,
             recovery_state: recovery::State::Recovered,
             congestion_state: Default::default(),
-            data_rate_model: data_rate::Model::new(),
-            // initialize extra_acked_interval_start and extra_acked_delivered
-            data_volume_model: data_volume::Model::new(now),
+            data_rate_model,
+            data_volume_model,
             max_datagram_size,
             idle_restart: false,
             bw_probe_samples: false,
             pacing_rate,
             next_departure_time: None,
             send_quantum: MAX_SEND_QUANTUM,
+            post_idle_burst_limiter: post_idle::PostIdleBurstLimiter::new(
+                !config.disable_post_idle_burst_limiting,
+            ),
+            initial_cwnd,
+            initial_cwnd_unchecked: initial_cwnd > default_initial_cwnd,
+            bandwidth_samples: confidence::Samples::new(
+                config
+                    .bw_probe_samples
+                    .unwrap_or(confidence::DEFAULT_SAMPLES),
+            ),
+            careful_resume,
+            app_limited_detector: Default::default(),
+            bandwidth_reservation: config.bandwidth_reservation,
+            idle_restart_policy: config.idle_restart_policy,
+            idle_detector: Default::default(),
+            restart_cwnd: safe_cwnd,
+            startup_duration: Default::default(),
         }
     }
+
+    /// Returns the parameters that a future connection to the same peer can be seeded with
+    ///
+    /// Returns `None` if the minimum RTT of the path has not been sampled yet.
+    #[allow(dead_code)] // TODO: Remove when used
+    pub fn careful_resume_params(&self, now: Timestamp) -> Option<CarefulResumeParams> {
+        let min_rtt = self.data_volume_model.min_rtt()?;
+
+        Some(CarefulResumeParams {
+            cwnd: self.cwnd as u64,
+            min_rtt,
+            timestamp: now,
+        })
+    }
+
+    /// Returns the range of the most recent delivery rate samples
+    ///
+    /// A narrow range indicates the bandwidth estimate reflects a stable path.
+    #[allow(dead_code)] // TODO: Remove when used
+    pub fn bandwidth_confidence(&self) -> BandwidthConfidence {
+        self.bandwidth_samples.confidence()
+    }
+
+    /// Returns the bandwidth estimated by the configured [`BandwidthEstimator`]
+    ///
+    /// Returns `None` if no estimator was configured with [`BbrConfig::set_bandwidth_estimator`].
+    /// The estimate is only reported and does not affect the pacing rate or congestion window.
+    #[cfg(feature = "alloc")]
+    #[allow(dead_code)] // TODO: Remove when used
+    pub fn estimated_bandwidth(&self) -> Option<Bandwidth> {
+        self.data_rate_model.estimated_bw()
+    }
+
+    /// Returns the number of consecutive rounds in Startup without significant bandwidth growth
+    ///
+    /// BBR estimates it has filled the pipe and exits Startup once this reaches 3, unless excessive
+    /// loss or ECN marking ended Startup first. The count is reset whenever the bandwidth grows by
+    /// at least 25% in a round, and it keeps its final value once the pipe has been filled.
+    #[allow(dead_code)] // TODO: Remove when used
+    pub fn startup_plateau_rounds(&self) -> u8 {
+        self.full_pipe_estimator.full_bw_count()
+    }
+
+    /// Returns true if a packet was marked as app-limited within the last 3 rounds
+    ///
+    /// Delivery rate samples taken while a bursty application had little data to send
+    /// underestimate the bandwidth of the path, so decisions based on the bandwidth estimate can
+    /// be made more conservative while this is true.
+    #[allow(dead_code)] // TODO: Remove when used
+    pub fn was_recently_app_limited(&self) -> bool {
+        self.app_limited_detector
+            .was_recently_app_limited(self.round_counter.round_count())
+    }
+
+    /// Returns true if the send buffer holds less than the minimum pipe cwnd
+    ///
+    /// Only the bytes in flight of the send buffer are visible to the congestion controller. A
+    /// congestion window or a pacing rate too small to send `minimum_window` within a round trip
+    /// also keeps the bytes in flight low, so the send buffer is only considered low if neither
+    /// of them is the limit.
+    #[inline]
+    fn is_send_buffer_low(&self, smoothed_rtt: Duration) -> bool {
+        let min_pipe_cwnd = self.minimum_window();
+        *self.bytes_in_flight < min_pipe_cwnd
+            && *self.bytes_in_flight < self.cwnd
+            && self.pacing_rate * smoothed_rtt >= min_pipe_cwnd as u64
+    }
+
     /// The bandwidth-delay product
     ///
     /// Based on the current estimate of maximum sending bandwidth and minimum RTT
@@ -495,7 +851,7 @@ impl BbrCongestionController {
         if let Some(min_rtt) = self.data_volume_model.min_rtt() {
             (gain * (bw * min_rtt)).to_integer()
         } else {
-            Self::initial_window(self.max_datagram_size).into()
+            self.initial_cwnd.into()
         }
     }
 
@@ -706,6 +1062,87 @@ impl BbrCongestionController {
         )
     }
 
+    /// Ensures a configured initial congestion window does not exceed the bandwidth-delay
+    /// product of the path
+    ///
+    /// The BDP is estimated from the bandwidth and RTT samples of the initial flight, so the
+    /// check is performed once the round containing the initial flight has ended. If the
+    /// initial window turned out to be larger than the estimated BDP, the congestion window
+    /// is reduced to the estimated BDP, though never below the default initial window.
+    #[inline]
+    fn check_initial_cwnd(&mut self) {
+        // round 1 starts with the first ack, so the initial flight has been acknowledged once
+        // a packet sent after the first ack is acknowledged
+        if !self.initial_cwnd_unchecked || self.round_counter.round_count() < 2 {
+            return;
+        }
+
+        self.initial_cwnd_unchecked = false;
+
+        let limit = self
+            .bdp()
+            .max(Self::initial_window(self.max_datagram_size) as u64)
+            .try_into()
+            .unwrap_or(u32::MAX);
+
+        if self.initial_cwnd > limit {
+            self.initial_cwnd = limit;
+            self.cwnd = self.cwnd.min(limit);
+        }
+    }
+
+    /// Retreats to the safe initial window if a packet in the initial flight of a resumed
+    /// connection was lost
+    ///
+    /// Loss in the initial flight indicates the path no longer has the capacity observed by the
+    /// previous connection, so the congestion window, the window restored upon exiting recovery
+    /// and the pacing rate are all reduced to what would have been used without Careful Resume.
+    #[inline]
+    fn check_careful_resume_loss(&mut self) {
+        let unvalidated = if let Some(unvalidated) = self.careful_resume.take() {
+            unvalidated
+        } else {
+            return;
+        };
+
+        if self.round_counter.round_count() >= unvalidated.validated_round {
+            return;
+        }
+
+        self.initial_cwnd = unvalidated.safe_cwnd;
+        self.initial_cwnd_unchecked = false;
+        self.cwnd = self.cwnd.min(unvalidated.safe_cwnd);
+        self.prior_cwnd = self.prior_cwnd.min(unvalidated.safe_cwnd);
+        self.pacing_rate = self.pacing_rate.min(unvalidated.safe_pacing_rate);
+    }
+
+    /// Adjusts the congestion window according to the `IdleRestartPolicy` when the connection
+    /// resumes sending after being idle for longer than a PTO
+    #[inline]
+    fn apply_idle_restart_policy(&mut self) {
+        if self.cwnd <= self.restart_cwnd {
+            return;
+        }
+
+        match self.idle_restart_policy {
+            IdleRestartPolicy::Disabled => {}
+            IdleRestartPolicy::CwndReset => {
+                // Similar to the restart window used by TCP, see
+                // https://www.rfc-editor.org/rfc/rfc5681#section-4.1
+                self.cwnd = self.restart_cwnd;
+            }
+            IdleRestartPolicy::CarefulResume => {
+                // the first flight after the idle period has been acknowledged once the round
+                // after the next one has started
+                self.careful_resume = Some(careful_resume::Unvalidated {
+                    safe_cwnd: self.restart_cwnd,
+                    safe_pacing_rate: self.pacing_rate,
+                    validated_round: self.round_counter.round_count() + 2,
+                });
+            }
+        }
+    }
+
     /// The minimal cwnd value BBR targets
     #[inline]
     fn minimum_window(&self) -> u32 {
@@ -734,7 +1171,7 @@ impl BbrCongestionController {
         //#     cwnd = max(cwnd, packets_in_flight + rs.newly_acked)
 
         let max_inflight = self.max_inflight().try_into().unwrap_or(u32::MAX);
-        let initial_cwnd = Self::initial_window(self.max_datagram_size);
+        let initial_cwnd = self.initial_cwnd;
         let mut cwnd = self.cwnd;
 
         if self.recovery_state.packet_conservation() {
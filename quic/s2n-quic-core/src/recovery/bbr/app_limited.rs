@@ -0,0 +1,168 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// The number of rounds after being app-limited during which the connection is considered to
+/// have been recently app-limited
+const RECENT_ROUNDS: u64 = 3;
+
+/// Detects when the application, rather than the network, limits the delivery rate
+///
+/// The transport reports a packet as app-limited when the application had no more data to send
+/// at the time the packet was sent. An application that trickles data into the connection, or is
+/// stalled by the peer's flow control limits, may always have a little data queued, so its
+/// packets are not reported as app-limited even though it isn't sending enough to measure the
+/// bandwidth of the path. The detector additionally marks packets as app-limited once the send
+/// buffer has held less than `min_pipe_cwnd` for more than a round trip.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AppLimitedDetector {
+    /// The time the send buffer dropped below `min_pipe_cwnd`, if it is still below
+    low_since: Option<Timestamp>,
+    /// The round in which a packet was most recently marked as app-limited
+    last_app_limited_round: Option<u64>,
+}
+
+impl AppLimitedDetector {
+    /// Called when a packet is sent
+    ///
+    /// `app_limited` is the value reported by the transport and `is_send_buffer_low` is true if
+    /// the send buffer holds less than `min_pipe_cwnd` after sending the packet. Returns whether
+    /// the packet should be marked as app-limited.
+    #[inline]
+    pub fn on_packet_sent(
+        &mut self,
+        now: Timestamp,
+        app_limited: Option<bool>,
+        is_send_buffer_low: bool,
+        smoothed_rtt: Duration,
+        round_count: u64,
+    ) -> Option<bool> {
+        // Initial and Handshake packets don't carry any application data
+        let app_limited = app_limited?;
+
+        self.on_send_buffer_update(now, is_send_buffer_low);
+
+        let is_app_limited = app_limited || self.is_low_for(now, smoothed_rtt);
+
+        if is_app_limited {
+            self.last_app_limited_round = Some(round_count);
+        }
+
+        Some(is_app_limited)
+    }
+
+    /// Called when packets are acknowledged, draining the send buffer
+    #[inline]
+    pub fn on_send_buffer_update(&mut self, now: Timestamp, is_send_buffer_low: bool) {
+        if is_send_buffer_low {
+            self.low_since.get_or_insert(now);
+        } else {
+            self.low_since = None;
+        }
+    }
+
+    /// Returns true if a packet was marked as app-limited within the last `RECENT_ROUNDS` rounds
+    #[inline]
+    pub fn was_recently_app_limited(&self, round_count: u64) -> bool {
+        self.last_app_limited_round.map_or(false, |round| {
+            round_count.saturating_sub(round) < RECENT_ROUNDS
+        })
+    }
+
+    /// Returns true if the send buffer has been low for longer than `duration`
+    #[inline]
+    fn is_low_for(&self, now: Timestamp, duration: Duration) -> bool {
+        self.low_since.map_or(false, |since| {
+            now.saturating_duration_since(since) > duration
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    const RTT: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn transport_app_limited_test() {
+        let now = NoopClock.get_time();
+        let mut detector = AppLimitedDetector::default();
+
+        assert_eq!(detector.on_packet_sent(now, None, true, RTT, 0), None);
+        assert_eq!(
+            detector.on_packet_sent(now, Some(true), false, RTT, 0),
+            Some(true)
+        );
+        assert_eq!(
+            detector.on_packet_sent(now, Some(false), false, RTT, 0),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn low_send_buffer_test() {
+        let now = NoopClock.get_time();
+        let mut detector = AppLimitedDetector::default();
+
+        // the send buffer has only just dropped below min_pipe_cwnd
+        assert_eq!(
+            detector.on_packet_sent(now, Some(false), true, RTT, 0),
+            Some(false)
+        );
+        assert_eq!(
+            detector.on_packet_sent(now + RTT, Some(false), true, RTT, 0),
+            Some(false)
+        );
+
+        // the send buffer has been low for more than a round trip
+        let now = now + RTT + Duration::from_millis(1);
+        assert_eq!(
+            detector.on_packet_sent(now, Some(false), true, RTT, 0),
+            Some(true)
+        );
+
+        // filling the send buffer resets the timer
+        assert_eq!(
+            detector.on_packet_sent(now, Some(false), false, RTT, 1),
+            Some(false)
+        );
+        assert_eq!(
+            detector.on_packet_sent(now + RTT, Some(false), true, RTT, 1),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn drained_send_buffer_test() {
+        let now = NoopClock.get_time();
+        let mut detector = AppLimitedDetector::default();
+
+        // the send buffer drains as packets are acknowledged while the application is idle
+        detector.on_send_buffer_update(now, true);
+
+        let now = now + RTT * 2;
+        assert_eq!(
+            detector.on_packet_sent(now, Some(false), true, RTT, 0),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn was_recently_app_limited_test() {
+        let now = NoopClock.get_time();
+        let mut detector = AppLimitedDetector::default();
+        assert!(!detector.was_recently_app_limited(0));
+
+        detector.on_packet_sent(now, Some(true), false, RTT, 5);
+        detector.on_packet_sent(now, Some(false), false, RTT, 6);
+
+        for round in 5..5 + RECENT_ROUNDS {
+            assert!(detector.was_recently_app_limited(round));
+        }
+        assert!(!detector.was_recently_app_limited(5 + RECENT_ROUNDS));
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::recovery::bandwidth::{Bandwidth, RateSample};
+use alloc::boxed::Box;
+use core::fmt;
+
+/// The default standard deviation of the change in bandwidth between samples,
+/// as a fraction of the estimated bandwidth
+const DEFAULT_PROCESS_NOISE: f64 = 0.1;
+
+/// The default standard deviation of the delivery rate measurement error,
+/// as a fraction of the estimated bandwidth
+const DEFAULT_MEASUREMENT_NOISE: f64 = 0.25;
+
+/// Estimates the bandwidth available to a BBR flow from delivery rate samples
+///
+/// BBR estimates the bandwidth using a windowed maximum of the delivery rate samples from
+/// the current and previous bandwidth probing cycle. A `BandwidthEstimator` is provided the
+/// same samples, but its estimate is only reported and is not used to pace the flow.
+pub trait BandwidthEstimator: 'static + Send + fmt::Debug {
+    /// Called with each rate sample that should be used to update the estimate
+    ///
+    /// Application-limited samples are only provided if their delivery rate
+    /// exceeds the current estimate.
+    fn on_rate_sample(&mut self, rate_sample: RateSample);
+
+    /// Returns the current bandwidth estimate
+    fn bandwidth(&self) -> Bandwidth;
+
+    /// Returns a boxed copy of the estimator
+    fn clone_box(&self) -> Box<dyn BandwidthEstimator>;
+}
+
+impl Clone for Box<dyn BandwidthEstimator> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Estimates the bandwidth using a one-dimensional Kalman filter over the delivery rate samples
+///
+/// The bandwidth is modeled as a random walk: between samples it changes by a normally
+/// distributed amount with a standard deviation of `process_noise` times the estimated
+/// bandwidth. Each delivery rate sample measures the bandwidth directly, with an error that
+/// has a standard deviation of `measurement_noise` times the estimated bandwidth.
+///
+/// Unlike the windowed maximum, which holds on to the largest sample until it falls out of
+/// the window, the filter weighs every sample against the current estimate and so tracks
+/// sudden drops in bandwidth within a few samples.
+#[derive(Clone, Copy, Debug)]
+pub struct KalmanBandwidthEstimator {
+    process_noise: f64,
+    measurement_noise: f64,
+    state: Option<State>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct State {
+    /// The estimated bandwidth, in bits per second
+    estimate: f64,
+    /// The variance of the estimate
+    variance: f64,
+}
+
+impl Default for KalmanBandwidthEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROCESS_NOISE, DEFAULT_MEASUREMENT_NOISE)
+    }
+}
+
+impl KalmanBandwidthEstimator {
+    /// Constructs a new `KalmanBandwidthEstimator`
+    ///
+    /// `process_noise` and `measurement_noise` are standard deviations expressed as a
+    /// fraction of the estimated bandwidth. A larger ratio of process noise to measurement
+    /// noise makes the estimate react faster to new samples, at the cost of following noisy
+    /// samples more closely.
+    pub fn new(process_noise: f64, measurement_noise: f64) -> Self {
+        debug_assert!(process_noise >= 0.0);
+        debug_assert!(measurement_noise > 0.0);
+
+        Self {
+            process_noise,
+            measurement_noise,
+            state: None,
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, measurement: f64) {
+        let state = if let Some(state) = self.state.as_mut() {
+            state
+        } else {
+            // Initialize the filter with the first sample
+            self.state = Some(State {
+                estimate: measurement,
+                variance: square(self.measurement_noise * measurement),
+            });
+            return;
+        };
+
+        // Predict: the bandwidth follows a random walk, so the estimate is unchanged
+        // while its uncertainty grows by the process noise
+        let variance = state.variance + square(self.process_noise * state.estimate);
+
+        // Update: blend the measurement into the estimate according to the relative
+        // uncertainty of the prediction and the measurement
+        let measurement_variance = square(self.measurement_noise * state.estimate);
+        let gain = if variance + measurement_variance > 0.0 {
+            variance / (variance + measurement_variance)
+        } else {
+            1.0
+        };
+
+        state.estimate += gain * (measurement - state.estimate);
+        state.variance = (1.0 - gain) * variance;
+    }
+}
+
+impl BandwidthEstimator for KalmanBandwidthEstimator {
+    #[inline]
+    fn on_rate_sample(&mut self, rate_sample: RateSample) {
+        self.update(rate_sample.delivery_rate().as_bits_per_second() as f64);
+    }
+
+    #[inline]
+    fn bandwidth(&self) -> Bandwidth {
+        self.state
+            .map(|state| Bandwidth::from_bits_per_second(state.estimate as u64))
+            .unwrap_or(Bandwidth::ZERO)
+    }
+
+    fn clone_box(&self) -> Box<dyn BandwidthEstimator> {
+        Box::new(*self)
+    }
+}
+
+#[inline]
+fn square(value: f64) -> f64 {
+    value * value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::bbr::data_rate;
+    use core::time::Duration;
+
+    fn rate_sample(bits_per_second: u64) -> RateSample {
+        RateSample {
+            interval: Duration::from_secs(1),
+            delivered_bytes: bits_per_second / 8,
+            ..Default::default()
+        }
+    }
+
+    fn mbps(value: u64) -> Bandwidth {
+        Bandwidth::from_bits_per_second(value * 1_000_000)
+    }
+
+    #[test]
+    fn kalman_estimate_test() {
+        let mut estimator = KalmanBandwidthEstimator::default();
+        assert_eq!(Bandwidth::ZERO, estimator.bandwidth());
+
+        // the first sample initializes the estimate
+        estimator.on_rate_sample(rate_sample(100_000_000));
+        assert_eq!(mbps(100), estimator.bandwidth());
+
+        // samples matching the estimate do not change it
+        estimator.on_rate_sample(rate_sample(100_000_000));
+        assert_eq!(mbps(100), estimator.bandwidth());
+
+        // a noisy sample only partially moves the estimate
+        estimator.on_rate_sample(rate_sample(120_000_000));
+        assert!(estimator.bandwidth() > mbps(100));
+        assert!(estimator.bandwidth() < mbps(120));
+    }
+
+    /// After a sudden 50% drop in bandwidth, the Kalman filter converges on the new
+    /// bandwidth faster than the windowed maximum filter, which BBR keeps using for `max_bw`
+    #[test]
+    fn kalman_converges_faster_than_windowed_max_test() {
+        // the number of rate samples in each bandwidth probing cycle
+        const SAMPLES_PER_CYCLE: usize = 10;
+
+        let mut model = data_rate::Model::with_bandwidth_estimator(Box::new(
+            KalmanBandwidthEstimator::default(),
+        ));
+        let kalman = |model: &data_rate::Model| model.estimated_bw().unwrap();
+
+        for _ in 0..2 * SAMPLES_PER_CYCLE {
+            model.update_max_bw(rate_sample(100_000_000));
+        }
+        model.advance_max_bw_filter();
+        assert_eq!(mbps(100), model.max_bw());
+        assert_eq!(mbps(100), kalman(&model));
+
+        // the bandwidth drops by half
+        let mut windowed_samples = None;
+        let mut kalman_samples = None;
+
+        for sample in 1..=4 * SAMPLES_PER_CYCLE {
+            model.update_max_bw(rate_sample(50_000_000));
+
+            if windowed_samples.is_none() && model.max_bw() <= mbps(55) {
+                windowed_samples = Some(sample);
+            }
+            if kalman_samples.is_none() && kalman(&model) <= mbps(55) {
+                kalman_samples = Some(sample);
+            }
+
+            if sample % SAMPLES_PER_CYCLE == 0 {
+                model.advance_max_bw_filter();
+            }
+        }
+
+        let windowed_samples = windowed_samples.expect("windowed max should converge");
+        let kalman_samples = kalman_samples.expect("kalman filter should converge");
+
+        // the windowed max holds on to the old bandwidth until it falls out of the window
+        assert!(windowed_samples > SAMPLES_PER_CYCLE);
+        assert!(
+            kalman_samples < windowed_samples,
+            "kalman: {}, windowed: {}",
+            kalman_samples,
+            windowed_samples
+        );
+
+        // both estimates settle on the new bandwidth
+        assert!(model.max_bw() <= mbps(50));
+        assert!(kalman(&model) <= mbps(51));
+        assert!(kalman(&model) >= mbps(49));
+    }
+}
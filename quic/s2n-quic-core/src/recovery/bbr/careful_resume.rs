@@ -0,0 +1,45 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{recovery::bandwidth::Bandwidth, time::Timestamp};
+use core::time::Duration;
+
+/// Parameters older than this are considered stale and are not used to seed a new connection
+pub const MAX_PARAMS_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Congestion control parameters observed on a previous connection to the same peer
+///
+/// Careful Resume (see <https://datatracker.ietf.org/doc/draft-ietf-tsvwg-careful-resume/>)
+/// allows a new connection to start with the congestion window of a previous connection,
+/// rather than probing for the available capacity from the default initial window. The
+/// parameters are typically stored alongside the TLS session ticket used to resume the
+/// connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CarefulResumeParams {
+    /// The congestion window of the previous connection, in bytes
+    pub cwnd: u64,
+    /// The minimum RTT observed on the previous connection
+    pub min_rtt: Duration,
+    /// The time at which the parameters were observed
+    pub timestamp: Timestamp,
+}
+
+impl CarefulResumeParams {
+    /// Returns `true` if the parameters are recent enough to be used at `now`
+    #[inline]
+    pub fn is_fresh(&self, now: Timestamp) -> bool {
+        now.saturating_duration_since(self.timestamp) <= MAX_PARAMS_AGE
+    }
+}
+
+/// The state of a congestion window that was seeded from `CarefulResumeParams`, or kept after
+/// an idle period, and has not yet been validated by the flight being acknowledged without loss
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Unvalidated {
+    /// The congestion window to retreat to if the resumed window turns out to be too large
+    pub safe_cwnd: u32,
+    /// The pacing rate to retreat to if the resumed window turns out to be too large
+    pub safe_pacing_rate: Bandwidth,
+    /// The round count at which the resumed flight has been acknowledged
+    pub validated_round: u64,
+}
@@ -0,0 +1,170 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::recovery::bandwidth::{Bandwidth, RateSample};
+
+/// The maximum number of recent delivery rate samples that can be retained
+pub(crate) const MAX_SAMPLES: usize = 32;
+
+/// The number of recent delivery rate samples retained by default
+pub(crate) const DEFAULT_SAMPLES: u8 = 10;
+
+/// Confidence intervals computed from fewer samples are marked as uncertain
+const MIN_CERTAIN_SAMPLES: u32 = 3;
+
+/// The range of recent delivery rate samples
+///
+/// A narrow range indicates the delivery rate of the path has been stable, so the bandwidth
+/// estimate can be relied on. A wide range indicates the delivery rate has been fluctuating.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BandwidthConfidence {
+    /// The lowest recent delivery rate sample
+    pub low: Bandwidth,
+    /// The highest recent delivery rate sample
+    pub high: Bandwidth,
+    /// The number of samples the range was computed from
+    pub sample_count: u32,
+    /// True if too few samples have been taken for the range to be meaningful
+    pub is_uncertain: bool,
+}
+
+/// A ring buffer of the most recent delivery rate samples
+#[derive(Clone, Debug)]
+pub(crate) struct Samples {
+    samples: [Bandwidth; MAX_SAMPLES],
+    /// The number of samples to retain
+    capacity: usize,
+    /// The number of samples currently retained
+    len: usize,
+    /// The index the next sample will be written to
+    next: usize,
+}
+
+impl Samples {
+    /// Constructs a new `Samples` retaining up to `capacity` samples
+    ///
+    /// The capacity is clamped to between 1 and `MAX_SAMPLES`.
+    pub fn new(capacity: u8) -> Self {
+        Self {
+            samples: [Bandwidth::ZERO; MAX_SAMPLES],
+            capacity: (capacity as usize).clamp(1, MAX_SAMPLES),
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Records the delivery rate of the given `rate_sample`
+    ///
+    /// Application-limited samples and samples without a measurement interval are
+    /// discarded, since they do not reflect the capacity of the path.
+    pub fn on_rate_sample(&mut self, rate_sample: RateSample) {
+        if rate_sample.is_app_limited || rate_sample.interval.is_zero() {
+            return;
+        }
+
+        self.samples[self.next] = rate_sample.delivery_rate();
+        self.next = (self.next + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+    }
+
+    /// Computes the confidence interval from the retained samples
+    pub fn confidence(&self) -> BandwidthConfidence {
+        let samples = &self.samples[..self.len];
+        let sample_count = self.len as u32;
+
+        BandwidthConfidence {
+            low: samples.iter().copied().min().unwrap_or(Bandwidth::ZERO),
+            high: samples.iter().copied().max().unwrap_or(Bandwidth::ZERO),
+            sample_count,
+            is_uncertain: sample_count < MIN_CERTAIN_SAMPLES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+
+    fn rate_sample(delivered_bytes: u64) -> RateSample {
+        RateSample {
+            interval: Duration::from_millis(100),
+            delivered_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn uncertain_test() {
+        let mut samples = Samples::new(DEFAULT_SAMPLES);
+
+        let confidence = samples.confidence();
+        assert_eq!(0, confidence.sample_count);
+        assert_eq!(Bandwidth::ZERO, confidence.low);
+        assert_eq!(Bandwidth::ZERO, confidence.high);
+        assert!(confidence.is_uncertain);
+
+        samples.on_rate_sample(rate_sample(1000));
+        samples.on_rate_sample(rate_sample(2000));
+        assert!(samples.confidence().is_uncertain);
+
+        samples.on_rate_sample(rate_sample(3000));
+        let confidence = samples.confidence();
+        assert_eq!(3, confidence.sample_count);
+        assert!(!confidence.is_uncertain);
+        assert_eq!(rate_sample(1000).delivery_rate(), confidence.low);
+        assert_eq!(rate_sample(3000).delivery_rate(), confidence.high);
+    }
+
+    #[test]
+    fn stable_path_test() {
+        let mut samples = Samples::new(DEFAULT_SAMPLES);
+
+        for _ in 0..100 {
+            samples.on_rate_sample(rate_sample(12_000));
+        }
+
+        let confidence = samples.confidence();
+        assert_eq!(DEFAULT_SAMPLES as u32, confidence.sample_count);
+        assert!(!confidence.is_uncertain);
+        assert_eq!(confidence.low, confidence.high);
+    }
+
+    #[test]
+    fn window_test() {
+        let mut samples = Samples::new(3);
+
+        for delivered_bytes in [100_000, 1000, 2000, 3000] {
+            samples.on_rate_sample(rate_sample(delivered_bytes));
+        }
+
+        // the oldest sample falls out of the window
+        let confidence = samples.confidence();
+        assert_eq!(3, confidence.sample_count);
+        assert_eq!(rate_sample(1000).delivery_rate(), confidence.low);
+        assert_eq!(rate_sample(3000).delivery_rate(), confidence.high);
+    }
+
+    #[test]
+    fn discarded_samples_test() {
+        let mut samples = Samples::new(DEFAULT_SAMPLES);
+
+        samples.on_rate_sample(RateSample {
+            is_app_limited: true,
+            ..rate_sample(1000)
+        });
+        samples.on_rate_sample(RateSample {
+            interval: Duration::ZERO,
+            ..rate_sample(1000)
+        });
+
+        assert_eq!(0, samples.confidence().sample_count);
+    }
+
+    #[test]
+    fn capacity_test() {
+        assert_eq!(1, Samples::new(0).capacity);
+        assert_eq!(MAX_SAMPLES, Samples::new(u8::MAX).capacity);
+    }
+}
@@ -1,10 +1,14 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "alloc")]
+use crate::recovery::bbr::BandwidthEstimator;
 use crate::recovery::{
     bandwidth::{Bandwidth, RateSample},
     bbr::{windowed_filter::WindowedMaxFilter, BETA},
 };
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 
 //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.9.1
 //# The data rate model parameters together estimate both the sending rate required to reach the
@@ -36,6 +40,9 @@ pub(crate) struct Model {
     //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.11
     //# The virtual time used by the BBR.max_bw filter window.
     cycle_count: core::num::Wrapping<u8>,
+    /// Estimates the bandwidth alongside the windowed maximum filter, if configured
+    #[cfg(feature = "alloc")]
+    bandwidth_estimator: Option<Box<dyn BandwidthEstimator>>,
 }
 
 impl Model {
@@ -52,6 +59,18 @@ impl Model {
             bw_lo: Bandwidth::MAX,
             bw: Bandwidth::ZERO,
             cycle_count: Default::default(),
+            #[cfg(feature = "alloc")]
+            bandwidth_estimator: None,
+        }
+    }
+
+    /// Constructs a new `data_rate::Model` that also provides the `max_bw` samples to the
+    /// given `bandwidth_estimator`
+    #[cfg(feature = "alloc")]
+    pub fn with_bandwidth_estimator(bandwidth_estimator: Box<dyn BandwidthEstimator>) -> Self {
+        Self {
+            bandwidth_estimator: Some(bandwidth_estimator),
+            ..Self::new()
         }
     }
 
@@ -60,6 +79,14 @@ impl Model {
         self.max_bw_filter.value().unwrap_or(Bandwidth::ZERO)
     }
 
+    /// The bandwidth estimated by the configured `BandwidthEstimator`, if any
+    #[cfg(feature = "alloc")]
+    pub fn estimated_bw(&self) -> Option<Bandwidth> {
+        self.bandwidth_estimator
+            .as_ref()
+            .map(|bandwidth_estimator| bandwidth_estimator.bandwidth())
+    }
+
     /// The long-term maximum sending bandwidth that the algorithm estimates
     /// will produce acceptable queue pressure
     pub fn bw_hi(&self) -> Bandwidth {
@@ -106,6 +133,11 @@ impl Model {
         if rate_sample.delivery_rate() > self.max_bw() || !rate_sample.is_app_limited {
             self.max_bw_filter
                 .update(rate_sample.delivery_rate(), self.cycle_count);
+
+            #[cfg(feature = "alloc")]
+            if let Some(bandwidth_estimator) = self.bandwidth_estimator.as_mut() {
+                bandwidth_estimator.on_rate_sample(rate_sample);
+            }
         }
     }
 
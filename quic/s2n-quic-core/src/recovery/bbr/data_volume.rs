@@ -6,7 +6,7 @@ use crate::{
         bandwidth::Bandwidth,
         bbr::{
             windowed_filter::{MinRttWindowedFilter, WindowedMaxFilter},
-            BETA,
+            DecayingMinRtt, BETA,
         },
     },
     time::Timestamp,
@@ -23,6 +23,8 @@ pub(crate) struct Model {
     //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.9.2
     //# The windowed minimum round-trip time sample measured over the last MinRTTFilterLen = 10 seconds.
     min_rtt_filter: MinRttWindowedFilter,
+    /// Replaces the windowed minimum RTT when configured
+    decaying_min_rtt: Option<DecayingMinRtt>,
     //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.9.2
     //# A volume of data that is the estimate of the recent degree of aggregation in the network path.
     extra_acked_filter: WindowedMaxFilter<u64, u64, u64>,
@@ -54,6 +56,7 @@ impl Model {
 
         Self {
             min_rtt_filter: MinRttWindowedFilter::new(),
+            decaying_min_rtt: None,
             extra_acked_filter: WindowedMaxFilter::new(EXTRA_ACKED_FILTER_LEN),
             extra_acked_interval_start: now,
             extra_acked_delivered: 0,
@@ -62,14 +65,28 @@ impl Model {
         }
     }
 
+    /// Constructs a new `data_volume::Model` that estimates the minimum RTT with the given
+    /// `DecayingMinRtt` instead of the windowed minimum
+    pub fn with_decaying_min_rtt(now: Timestamp, decaying_min_rtt: DecayingMinRtt) -> Self {
+        Self {
+            decaying_min_rtt: Some(decaying_min_rtt),
+            ..Self::new(now)
+        }
+    }
+
     /// The windowed maximum recent estimate in bytes of the degree of aggregation in the path
     pub fn extra_acked(&self) -> u64 {
         self.extra_acked_filter.value().unwrap_or(0)
     }
 
-    /// The windowed minimum round trip time
+    /// The minimum round trip time
+    ///
+    /// This is the windowed minimum, unless a `DecayingMinRtt` has been configured
     pub fn min_rtt(&self) -> Option<Duration> {
-        self.min_rtt_filter.min_rtt()
+        match &self.decaying_min_rtt {
+            Some(decaying_min_rtt) => decaying_min_rtt.min_rtt(),
+            None => self.min_rtt_filter.min_rtt(),
+        }
     }
 
     /// The long-term maximum volume of in-flight data that the algorithm
@@ -98,8 +115,13 @@ impl Model {
     }
 
     /// Update the min_rtt estimate with the given `rtt`
-    pub fn update_min_rtt(&mut self, rtt: Duration, now: Timestamp) {
-        self.min_rtt_filter.update(rtt, now)
+    pub fn update_min_rtt(&mut self, rtt: Duration, round_start: bool, now: Timestamp) {
+        // the windowed filter is always updated as it determines when to enter ProbeRTT
+        self.min_rtt_filter.update(rtt, now);
+
+        if let Some(decaying_min_rtt) = &mut self.decaying_min_rtt {
+            decaying_min_rtt.update(rtt, round_start);
+        }
     }
 
     /// Update the ack aggregation estimate
@@ -195,6 +217,21 @@ mod tests {
         assert_eq!(u64::MAX, model.inflight_lo());
     }
 
+    #[test]
+    fn decaying_min_rtt() {
+        let now = NoopClock.get_time();
+        let mut model = Model::with_decaying_min_rtt(now, DecayingMinRtt::new(0.5));
+
+        model.update_min_rtt(Duration::from_secs(1), true, now);
+        assert_eq!(Some(Duration::from_secs(1)), model.min_rtt());
+
+        // the windowed minimum would hold on to the 1 second sample
+        let now = now + Duration::from_secs(1);
+        model.update_min_rtt(Duration::from_secs(2), true, now);
+        assert_eq!(Some(Duration::from_millis(1500)), model.min_rtt());
+        assert_eq!(Some(Duration::from_secs(1)), model.min_rtt_filter.min_rtt());
+    }
+
     #[test]
     fn update_ack_aggregation() {
         let now = NoopClock.get_time();
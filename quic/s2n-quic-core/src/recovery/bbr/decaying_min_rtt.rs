@@ -0,0 +1,143 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::time::Duration;
+
+/// The default fraction the minimum RTT estimate is allowed to grow by each round trip
+const DEFAULT_DECAY: f64 = 0.001;
+
+/// Estimates the minimum RTT of a BBR flow with an exponentially decaying minimum
+///
+/// By default, BBR uses the minimum RTT sampled over a 10 second window, which is replaced
+/// wholesale when the window expires. On long-lived connections a single unusually low sample,
+/// such as one taken during a brief lull in cross traffic, is held for the full window and
+/// then abruptly discarded.
+///
+/// `DecayingMinRtt` instead forgets old samples gradually: at the start of every round trip
+/// the estimate is allowed to grow by a factor of `1 + decay`, and any RTT sample below the
+/// grown estimate replaces it. That is, each round trip
+/// `min_rtt = min(rtt, min_rtt * (1 + decay))`.
+///
+/// The timing of the ProbeRTT state is not affected.
+#[derive(Clone, Copy, Debug)]
+pub struct DecayingMinRtt {
+    decay: f64,
+    min_rtt: Option<Duration>,
+}
+
+impl Default for DecayingMinRtt {
+    fn default() -> Self {
+        Self::new(DEFAULT_DECAY)
+    }
+}
+
+impl DecayingMinRtt {
+    /// Constructs a new `DecayingMinRtt`
+    ///
+    /// `decay` is the fraction the estimate may grow by each round trip. It is clamped to
+    /// between 0 and 1; a value of 0 never forgets the lowest sample.
+    pub fn new(decay: f64) -> Self {
+        let decay = if decay.is_nan() {
+            DEFAULT_DECAY
+        } else {
+            decay.clamp(0.0, 1.0)
+        };
+
+        Self {
+            decay,
+            min_rtt: None,
+        }
+    }
+
+    /// Returns the current minimum RTT estimate
+    pub fn min_rtt(&self) -> Option<Duration> {
+        self.min_rtt
+    }
+
+    /// Updates the estimate with the given `rtt` sample
+    ///
+    /// The estimate decays once per round trip, indicated by `round_start`.
+    pub(super) fn update(&mut self, rtt: Duration, round_start: bool) {
+        let min_rtt = match self.min_rtt {
+            Some(min_rtt) if round_start => min_rtt.mul_f64(1.0 + self.decay),
+            Some(min_rtt) => min_rtt,
+            None => rtt,
+        };
+
+        self.min_rtt = Some(min_rtt.min(rtt));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_sample_test() {
+        let mut estimator = DecayingMinRtt::default();
+        assert_eq!(None, estimator.min_rtt());
+
+        estimator.update(Duration::from_millis(100), false);
+        assert_eq!(Some(Duration::from_millis(100)), estimator.min_rtt());
+    }
+
+    #[test]
+    fn lower_sample_test() {
+        let mut estimator = DecayingMinRtt::default();
+
+        estimator.update(Duration::from_millis(100), true);
+        estimator.update(Duration::from_millis(50), false);
+        assert_eq!(Some(Duration::from_millis(50)), estimator.min_rtt());
+    }
+
+    #[test]
+    fn decay_test() {
+        let mut estimator = DecayingMinRtt::new(0.5);
+
+        estimator.update(Duration::from_secs(1), true);
+
+        // the estimate only decays at the start of a round
+        estimator.update(Duration::from_secs(4), false);
+        assert_eq!(Some(Duration::from_secs(1)), estimator.min_rtt());
+
+        estimator.update(Duration::from_secs(4), true);
+        assert_eq!(Some(Duration::from_millis(1500)), estimator.min_rtt());
+
+        estimator.update(Duration::from_secs(4), true);
+        assert_eq!(Some(Duration::from_millis(2250)), estimator.min_rtt());
+
+        // the estimate converges on the RTT samples
+        estimator.update(Duration::from_secs(4), true);
+        estimator.update(Duration::from_secs(4), true);
+        assert_eq!(Some(Duration::from_secs(4)), estimator.min_rtt());
+    }
+
+    #[test]
+    fn long_lived_test() {
+        let mut estimator = DecayingMinRtt::default();
+
+        // a brief lull results in an unusually low sample
+        estimator.update(Duration::from_millis(20), true);
+
+        // after 100 rounds of 50ms samples the low sample is mostly still remembered
+        for _ in 0..100 {
+            estimator.update(Duration::from_millis(50), true);
+        }
+        let min_rtt = estimator.min_rtt().unwrap();
+        assert!(min_rtt > Duration::from_millis(22));
+        assert!(min_rtt < Duration::from_millis(23));
+
+        // but is eventually forgotten
+        for _ in 0..1000 {
+            estimator.update(Duration::from_millis(50), true);
+        }
+        assert_eq!(Some(Duration::from_millis(50)), estimator.min_rtt());
+    }
+
+    #[test]
+    fn clamp_test() {
+        assert_eq!(0.0, DecayingMinRtt::new(-1.0).decay);
+        assert_eq!(1.0, DecayingMinRtt::new(2.0).decay);
+        assert_eq!(DEFAULT_DECAY, DecayingMinRtt::new(f64::NAN).decay);
+    }
+}
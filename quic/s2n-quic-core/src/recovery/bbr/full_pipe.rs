@@ -3,10 +3,33 @@
 
 use crate::{
     counter::{Counter, Saturating},
+    event,
     recovery::{bandwidth, bandwidth::Bandwidth, bbr::BbrCongestionController},
 };
 use num_rational::Ratio;
 
+/// The reason BBR estimated that it has fully utilized the available bandwidth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FullPipeReason {
+    /// The bandwidth estimate stopped growing
+    BandwidthPlateau,
+    /// The loss rate exceeded the loss threshold
+    ExcessiveLoss,
+    /// The rate of ECN Congestion Experienced markings exceeded the ECN threshold
+    Ecn,
+}
+
+impl event::IntoEvent<event::builder::FullPipeReason> for FullPipeReason {
+    #[inline]
+    fn into_event(self) -> event::builder::FullPipeReason {
+        match self {
+            Self::BandwidthPlateau => event::builder::FullPipeReason::BandwidthPlateau,
+            Self::ExcessiveLoss => event::builder::FullPipeReason::ExcessiveLoss,
+            Self::Ecn => event::builder::FullPipeReason::Ecn,
+        }
+    }
+}
+
 /// Estimator for determining if BBR has fully utilized its available bandwidth ("filled the pipe")
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Estimator {
@@ -26,6 +49,8 @@ pub(crate) struct Estimator {
     //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.13
     //# The number of non-app-limited round trips without large increases in BBR.full_bw.
     full_bw_count: Counter<u8, Saturating>,
+    /// The reason the pipe was estimated to be full
+    reason: Option<FullPipeReason>,
     /// The number of discontiguous bursts of lost packets in the last round
     loss_bursts: Counter<u8, Saturating>,
     /// The number of rounds where the ECN CE markings exceed ECN_THRESH
@@ -41,6 +66,18 @@ impl Estimator {
         self.filled_pipe
     }
 
+    /// Returns the reason BBR estimated that it has filled the pipe, if it has
+    #[inline]
+    pub fn reason(&self) -> Option<FullPipeReason> {
+        self.reason
+    }
+
+    /// Returns the number of consecutive rounds without significant bandwidth growth
+    #[inline]
+    pub fn full_bw_count(&self) -> u8 {
+        *self.full_bw_count
+    }
+
     /// Called on each new BBR round
     #[inline]
     pub fn on_round_start(
@@ -54,9 +91,16 @@ impl Estimator {
             return;
         }
 
-        self.filled_pipe = self.bandwidth_plateaued(rate_sample, max_bw)
-            || self.excessive_loss(rate_sample, in_recovery)
-            || self.excessive_explicit_congestion(rate_sample, max_datagram_size);
+        self.reason = if self.bandwidth_plateaued(rate_sample, max_bw) {
+            Some(FullPipeReason::BandwidthPlateau)
+        } else if self.excessive_loss(rate_sample, in_recovery) {
+            Some(FullPipeReason::ExcessiveLoss)
+        } else if self.excessive_explicit_congestion(rate_sample, max_datagram_size) {
+            Some(FullPipeReason::Ecn)
+        } else {
+            None
+        };
+        self.filled_pipe = self.reason.is_some();
     }
 
     /// Determines if the rate of increase of bandwidth has decreased enough to estimate the
@@ -180,6 +224,26 @@ impl Estimator {
             self.loss_bursts += 1;
         }
     }
+
+    /// Called when a packet which was declared lost is acknowledged
+    ///
+    /// If the pipe was only estimated to be full because of excessive loss, the loss may have
+    /// been reordering instead, so the estimate is reset to give BBR a chance to probe for more
+    /// bandwidth. Returns true if the estimate was reset.
+    #[inline]
+    pub fn on_spurious_loss(&mut self) -> bool {
+        if self.reason != Some(FullPipeReason::ExcessiveLoss) {
+            return false;
+        }
+
+        // `full_bw` is kept, since it still reflects bandwidth that was actually delivered
+        self.filled_pipe = false;
+        self.reason = None;
+        self.full_bw_count = Counter::default();
+        self.loss_bursts = Counter::default();
+        self.in_recovery_last_round = false;
+        true
+    }
 }
 
 #[cfg(test)]
@@ -211,6 +275,51 @@ mod tests {
         fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
         // The pipe is considered full
         assert!(fp_estimator.filled_pipe());
+        assert_eq!(
+            Some(FullPipeReason::BandwidthPlateau),
+            fp_estimator.reason()
+        );
+    }
+
+    #[test]
+    fn full_bw_count() {
+        let mut fp_estimator = full_pipe::Estimator::default();
+        let rate_sample = RateSample::default();
+        let mut max_bw = Bandwidth::new(1000, Duration::from_secs(1));
+
+        // The first round records the baseline bandwidth
+        fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
+        assert_eq!(0, fp_estimator.full_bw_count());
+
+        // Each round without growth increments the count
+        for expected in 1..=2 {
+            fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
+            assert_eq!(expected, fp_estimator.full_bw_count());
+        }
+
+        // App limited rounds don't affect the count
+        let app_limited = RateSample {
+            is_app_limited: true,
+            ..Default::default()
+        };
+        fp_estimator.on_round_start(app_limited, max_bw, false, MINIMUM_MTU);
+        assert_eq!(2, fp_estimator.full_bw_count());
+
+        // Growing the bandwidth again resets the count
+        max_bw = max_bw * Ratio::new(2, 1);
+        fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
+        assert_eq!(0, fp_estimator.full_bw_count());
+        assert!(!fp_estimator.filled_pipe());
+
+        for expected in 1..=3 {
+            fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
+            assert_eq!(expected, fp_estimator.full_bw_count());
+        }
+        assert!(fp_estimator.filled_pipe());
+
+        // The count no longer changes once the pipe is filled
+        fp_estimator.on_round_start(rate_sample, max_bw, false, MINIMUM_MTU);
+        assert_eq!(3, fp_estimator.full_bw_count());
     }
 
     #[test]
@@ -263,6 +372,52 @@ mod tests {
         fp_estimator.on_round_start(rate_sample, max_bw, true, MINIMUM_MTU);
         // The pipe has not been filled yet since there were only 2 loss bursts
         assert!(fp_estimator.filled_pipe());
+        assert_eq!(Some(FullPipeReason::ExcessiveLoss), fp_estimator.reason());
+    }
+
+    #[test]
+    fn spurious_loss() {
+        let mut fp_estimator = full_pipe::Estimator::default();
+        let rate_sample = RateSample {
+            // More than 2% bytes lost
+            bytes_in_flight: 1000,
+            lost_bytes: 21,
+            ..Default::default()
+        };
+        let max_bw = Bandwidth::new(1000, Duration::from_secs(1));
+
+        // One round without bandwidth growth, then excessive loss fills the pipe
+        fp_estimator.on_round_start(rate_sample, max_bw, true, MINIMUM_MTU);
+        for _ in 0..3 {
+            fp_estimator.on_packet_lost(true);
+        }
+        fp_estimator.on_round_start(rate_sample, max_bw, true, MINIMUM_MTU);
+        assert_eq!(Some(FullPipeReason::ExcessiveLoss), fp_estimator.reason());
+        assert_eq!(1, fp_estimator.full_bw_count());
+
+        // Genuine losses don't change the estimate
+        fp_estimator.on_packet_lost(true);
+        assert!(fp_estimator.filled_pipe());
+
+        // A spurious loss resets the estimate
+        assert!(fp_estimator.on_spurious_loss());
+        assert!(!fp_estimator.filled_pipe());
+        assert_eq!(None, fp_estimator.reason());
+        assert_eq!(0, fp_estimator.full_bw_count());
+
+        // The bandwidth plateau still fills the pipe afterwards
+        for _ in 0..3 {
+            fp_estimator.on_round_start(RateSample::default(), max_bw, false, MINIMUM_MTU);
+        }
+        assert_eq!(
+            Some(FullPipeReason::BandwidthPlateau),
+            fp_estimator.reason()
+        );
+
+        // A spurious loss doesn't reset a pipe filled by a bandwidth plateau
+        assert!(!fp_estimator.on_spurious_loss());
+        assert!(fp_estimator.filled_pipe());
+        assert_eq!(3, fp_estimator.full_bw_count());
     }
 
     #[test]
@@ -352,5 +507,6 @@ mod tests {
         fp_estimator.on_round_start(high_ecn_rs, max_bw, false, MINIMUM_MTU);
         // After two consecutive rounds of high ECN markings, the pipe is full
         assert!(fp_estimator.filled_pipe());
+        assert_eq!(Some(FullPipeReason::Ecn), fp_estimator.reason());
     }
 }
@@ -0,0 +1,98 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// Determines how the congestion window is adjusted when a connection resumes sending after
+/// being idle
+///
+/// A connection is considered idle once nothing has been in flight for longer than a probe
+/// timeout (PTO), which is the QUIC counterpart of the retransmission timeout (RTO) TCP uses
+/// for [slow-start restart](https://www.rfc-editor.org/rfc/rfc5681#section-4.1). By that time,
+/// the congestion window may no longer reflect the capacity of the path.
+///
+/// The policy is applied in addition to the post-idle burst limiting, which can be turned off
+/// with `BbrConfig::disable_post_idle_burst_limiting`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdleRestartPolicy {
+    /// The congestion window is kept after an idle period
+    Disabled,
+    /// The congestion window is reduced to the initial window after an idle period
+    CwndReset,
+    /// The congestion window is kept after an idle period, but immediately retreats to the
+    /// initial window if any packet in the first flight after the idle period is lost
+    ///
+    /// This applies the [`CarefulResumeParams`](super::CarefulResumeParams) mechanism to
+    /// the connection itself, using the state from before the idle period.
+    CarefulResume,
+}
+
+impl Default for IdleRestartPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+/// Detects when a connection resumes sending after being idle
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IdleDetector {
+    /// The time the most recent packet was sent
+    last_sent_time: Option<Timestamp>,
+}
+
+impl IdleDetector {
+    /// Called before a packet is sent with the `bytes_in_flight` prior to sending the packet
+    ///
+    /// Returns `true` if nothing was in flight and no packet was sent for longer than `timeout`.
+    #[inline]
+    pub fn on_packet_sent(
+        &mut self,
+        now: Timestamp,
+        bytes_in_flight: u32,
+        timeout: Duration,
+    ) -> bool {
+        let last_sent_time = self.last_sent_time.replace(now);
+
+        if bytes_in_flight > 0 {
+            return false;
+        }
+
+        last_sent_time.map_or(false, |last_sent_time| {
+            now.saturating_duration_since(last_sent_time) > timeout
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    const PTO: Duration = Duration::from_millis(150);
+
+    #[test]
+    fn idle_detection() {
+        let mut detector = IdleDetector::default();
+        let now = NoopClock.get_time();
+
+        // the first packet is not a restart
+        assert!(!detector.on_packet_sent(now, 0, PTO));
+
+        // data still in flight
+        let now = now + PTO * 2;
+        assert!(!detector.on_packet_sent(now, 1200, PTO));
+
+        // idle for less than a PTO
+        let now = now + PTO / 2;
+        assert!(!detector.on_packet_sent(now, 0, PTO));
+
+        // idle for longer than a PTO
+        let now = now + PTO * 2;
+        assert!(detector.on_packet_sent(now, 0, PTO));
+
+        // sending continues without another restart
+        assert!(!detector.on_packet_sent(now, 0, PTO));
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// The number of packets that may be sent in the first burst after an idle period
+const INITIAL_BURST_PACKETS: u32 = 2;
+
+/// The number of round trips over which the limit is ramped up to the full congestion window
+const RAMP_UP_ROUNDS: u64 = 3;
+
+/// Limits the size of the burst sent when a connection resumes sending after being idle
+///
+/// A connection that has been idle for longer than a round trip no longer has an accurate
+/// picture of the state of the network, yet the congestion window would still allow the
+/// full window to be sent at once. Similar to the restart window described in
+/// [RFC 5681 Section 4.1](https://www.rfc-editor.org/rfc/rfc5681#section-4.1), the first
+/// burst after an idle period is limited to `INITIAL_BURST_PACKETS` and the limit is
+/// linearly increased to the full congestion window over the following `RAMP_UP_ROUNDS`
+/// round trips.
+#[derive(Clone, Debug)]
+pub(crate) struct PostIdleBurstLimiter {
+    /// True if the limiter is enabled
+    enabled: bool,
+    /// The time the most recent packet was sent
+    last_sent_time: Option<Timestamp>,
+    /// The round count at which the connection restarted from idle, if the
+    /// limit is currently being ramped up
+    restart_round: Option<u64>,
+}
+
+impl PostIdleBurstLimiter {
+    /// Constructs a new `PostIdleBurstLimiter`
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_sent_time: None,
+            restart_round: None,
+        }
+    }
+
+    /// Called before a packet is sent with the `bytes_in_flight` prior to sending the packet
+    #[inline]
+    pub fn on_packet_sent(
+        &mut self,
+        now: Timestamp,
+        bytes_in_flight: u32,
+        smoothed_rtt: Duration,
+        round_count: u64,
+    ) {
+        let last_sent_time = self.last_sent_time.replace(now);
+
+        if !self.enabled || bytes_in_flight > 0 {
+            return;
+        }
+
+        if let Some(last_sent_time) = last_sent_time {
+            if now.saturating_duration_since(last_sent_time) > smoothed_rtt {
+                self.restart_round = Some(round_count);
+            }
+        }
+    }
+
+    /// Called for each acknowledgement after the round count has been updated
+    #[inline]
+    pub fn on_ack(&mut self, round_count: u64) {
+        if let Some(restart_round) = self.restart_round {
+            if round_count.saturating_sub(restart_round) >= RAMP_UP_ROUNDS {
+                self.restart_round = None;
+            }
+        }
+    }
+
+    /// Returns the congestion window, limited if the connection recently restarted from idle
+    #[inline]
+    pub fn limit(&self, cwnd: u32, round_count: u64, max_datagram_size: u16) -> u32 {
+        let restart_round = if let Some(restart_round) = self.restart_round {
+            restart_round
+        } else {
+            return cwnd;
+        };
+
+        let initial_burst = INITIAL_BURST_PACKETS * max_datagram_size as u32;
+        if cwnd <= initial_burst {
+            return cwnd;
+        }
+
+        let rounds = round_count
+            .saturating_sub(restart_round)
+            .min(RAMP_UP_ROUNDS);
+        let ramp = (cwnd - initial_burst) as u64 * rounds / RAMP_UP_ROUNDS;
+
+        initial_burst + ramp as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    const MAX_DATAGRAM_SIZE: u16 = 1200;
+    const CWND: u32 = 100 * MAX_DATAGRAM_SIZE as u32;
+    const RTT: Duration = Duration::from_millis(50);
+    const IDLE: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn limit_burst_after_idle() {
+        let mut limiter = PostIdleBurstLimiter::new(true);
+        let now = NoopClock.get_time();
+        let mut round_count = 5;
+
+        limiter.on_packet_sent(now, 0, RTT, round_count);
+        assert!(limiter.restart_round.is_none());
+        assert_eq!(CWND, limiter.limit(CWND, round_count, MAX_DATAGRAM_SIZE));
+
+        // resume sending after being idle for 200ms
+        let now = now + IDLE;
+        limiter.on_packet_sent(now, 0, RTT, round_count);
+        assert!(limiter.restart_round.is_some());
+
+        // the first burst is limited to 2 * max_datagram_size
+        let initial_burst = 2 * MAX_DATAGRAM_SIZE as u32;
+        assert_eq!(
+            initial_burst,
+            limiter.limit(CWND, round_count, MAX_DATAGRAM_SIZE)
+        );
+
+        // the limit is linearly increased over the next 3 rounds
+        let step = (CWND - initial_burst) / 3;
+        for expected in [initial_burst + step, initial_burst + 2 * step] {
+            round_count += 1;
+            limiter.on_ack(round_count);
+            assert!(limiter.restart_round.is_some());
+            assert_eq!(
+                expected,
+                limiter.limit(CWND, round_count, MAX_DATAGRAM_SIZE)
+            );
+        }
+
+        round_count += 1;
+        limiter.on_ack(round_count);
+        assert!(limiter.restart_round.is_none());
+        assert_eq!(CWND, limiter.limit(CWND, round_count, MAX_DATAGRAM_SIZE));
+    }
+
+    #[test]
+    fn no_limit_without_idle() {
+        let mut limiter = PostIdleBurstLimiter::new(true);
+        let now = NoopClock.get_time();
+
+        limiter.on_packet_sent(now, 0, RTT, 0);
+
+        // data still in flight
+        limiter.on_packet_sent(now + IDLE, MAX_DATAGRAM_SIZE as u32, RTT, 0);
+        assert!(limiter.restart_round.is_none());
+
+        // idle for less than an RTT
+        limiter.on_packet_sent(now + IDLE + RTT / 2, 0, RTT, 0);
+        assert!(limiter.restart_round.is_none());
+        assert_eq!(CWND, limiter.limit(CWND, 0, MAX_DATAGRAM_SIZE));
+    }
+
+    #[test]
+    fn no_limit_when_disabled() {
+        let mut limiter = PostIdleBurstLimiter::new(false);
+        let now = NoopClock.get_time();
+
+        limiter.on_packet_sent(now, 0, RTT, 0);
+        limiter.on_packet_sent(now + IDLE, 0, RTT, 0);
+
+        assert!(limiter.restart_round.is_none());
+        assert_eq!(CWND, limiter.limit(CWND, 0, MAX_DATAGRAM_SIZE));
+    }
+
+    #[test]
+    fn small_cwnd() {
+        let mut limiter = PostIdleBurstLimiter::new(true);
+        let now = NoopClock.get_time();
+
+        limiter.on_packet_sent(now, 0, RTT, 0);
+        limiter.on_packet_sent(now + IDLE, 0, RTT, 0);
+
+        // the limit never exceeds the congestion window
+        let cwnd = MAX_DATAGRAM_SIZE as u32;
+        assert_eq!(cwnd, limiter.limit(cwnd, 0, MAX_DATAGRAM_SIZE));
+    }
+}
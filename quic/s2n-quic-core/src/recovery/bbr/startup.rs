@@ -1,7 +1,10 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::recovery::bbr::{BbrCongestionController, State};
+use crate::{
+    recovery::bbr::{BbrCongestionController, State},
+    time::Timestamp,
+};
 use num_rational::Ratio;
 
 //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#2.6
@@ -30,7 +33,7 @@ impl BbrCongestionController {
     }
 
     /// Checks if the `Startup` state is done and enters `Drain` if so
-    pub(super) fn check_startup_done(&mut self) {
+    pub(super) fn check_startup_done(&mut self, now: Timestamp) {
         //= https://tools.ietf.org/id/draft-cardwell-iccrg-bbr-congestion-control-02#4.3.1.1
         //# BBRCheckStartupDone():
         //#   BBRCheckStartupFullBandwidth()
@@ -46,6 +49,7 @@ impl BbrCongestionController {
             );
             if self.state.is_startup() && self.full_pipe_estimator.filled_pipe() {
                 self.enter_drain();
+                self.startup_duration.on_enter_drain(now);
             }
         }
     }
@@ -0,0 +1,82 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// Measures the time BBR spends in Startup before it enters Drain
+///
+/// Startup ends when BBR estimates it has filled the pipe. BBR only returns to Startup if that
+/// estimate is withdrawn before Drain completes, in which case the measurement continues.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StartupDurationMeasurer {
+    /// The time the first packet was sent in Startup
+    first_sent_time: Option<Timestamp>,
+    /// The time BBR transitioned from Startup to Drain
+    drain_time: Option<Timestamp>,
+}
+
+impl StartupDurationMeasurer {
+    /// Called when a packet is sent in Startup
+    #[inline]
+    pub fn on_packet_sent(&mut self, now: Timestamp) {
+        if self.first_sent_time.is_none() {
+            self.first_sent_time = Some(now);
+        }
+    }
+
+    /// Called when BBR transitions from Startup to Drain
+    #[inline]
+    pub fn on_enter_drain(&mut self, now: Timestamp) {
+        if self.drain_time.is_none() {
+            self.drain_time = Some(now);
+        }
+    }
+
+    /// Called when BBR returns from Drain to Startup
+    #[inline]
+    pub fn on_resume_startup(&mut self) {
+        self.drain_time = None;
+    }
+
+    /// Returns the time between the first packet sent in Startup and the transition to Drain
+    ///
+    /// Returns `None` while BBR has not left Startup yet.
+    #[inline]
+    pub fn startup_duration(&self) -> Option<Duration> {
+        let first_sent_time = self.first_sent_time?;
+        let drain_time = self.drain_time?;
+        Some(drain_time.saturating_duration_since(first_sent_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    #[test]
+    fn startup_duration() {
+        let now = NoopClock.get_time();
+        let mut measurer = StartupDurationMeasurer::default();
+        assert_eq!(None, measurer.startup_duration());
+
+        measurer.on_packet_sent(now);
+        measurer.on_packet_sent(now + Duration::from_millis(10));
+        assert_eq!(None, measurer.startup_duration());
+
+        measurer.on_enter_drain(now + Duration::from_millis(100));
+        assert_eq!(
+            Some(Duration::from_millis(100)),
+            measurer.startup_duration()
+        );
+
+        // Only the first transition is measured
+        measurer.on_packet_sent(now + Duration::from_millis(200));
+        measurer.on_enter_drain(now + Duration::from_millis(300));
+        assert_eq!(
+            Some(Duration::from_millis(100)),
+            measurer.startup_duration()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Simulates transfers on a lossless path to compare BBR configurations
+
+use super::*;
+use crate::time::{Clock, NoopClock};
+use std::collections::VecDeque;
+
+/// The maximum datagram size used on the simulated path
+pub const MAX_DATAGRAM_SIZE: u16 = 1200;
+
+/// A lossless path with a single bottleneck link
+pub struct Path {
+    /// The round trip time of the path, excluding the serialization delay
+    pub rtt: Duration,
+    /// The rate of the bottleneck link in bits per second
+    pub link_rate: u64,
+}
+
+impl Path {
+    /// The time it takes to transmit `bytes` on the bottleneck link
+    pub fn serialization_delay(&self, bytes: usize) -> Duration {
+        Duration::from_nanos(bytes as u64 * 8 * 1_000_000_000 / self.link_rate)
+    }
+
+    /// The bandwidth-delay product of the path in bytes
+    pub fn bdp(&self) -> u64 {
+        self.link_rate / 8 * self.rtt.as_nanos() as u64 / 1_000_000_000
+    }
+}
+
+/// A packet that is in flight on the simulated path
+pub(super) struct SentPacket {
+    pub time_sent: Timestamp,
+    pub ack_time: Timestamp,
+    pub bytes: usize,
+    pub packet_info: bandwidth::PacketInfo,
+}
+
+/// Sends a single packet on the `path` at `now`, queueing it behind previously sent packets
+pub(super) fn send_packet(
+    bbr: &mut BbrCongestionController,
+    path: &Path,
+    rtt_estimator: &RttEstimator,
+    link_available: &mut Timestamp,
+    bytes: usize,
+    now: Timestamp,
+) -> SentPacket {
+    let packet_info = bbr.on_packet_sent(now, bytes, Some(false), rtt_estimator);
+
+    let departure = (*link_available).max(now);
+    *link_available = departure + path.serialization_delay(bytes);
+
+    SentPacket {
+        time_sent: now,
+        ack_time: *link_available + path.rtt,
+        bytes,
+        packet_info,
+    }
+}
+
+/// Processes the acknowledgement for the given `packet`, returning the time it was acknowledged
+pub(super) fn ack_packet(
+    bbr: &mut BbrCongestionController,
+    rtt_estimator: &mut RttEstimator,
+    packet: SentPacket,
+) -> Timestamp {
+    let now = packet.ack_time;
+    rtt_estimator.update_rtt(
+        Duration::ZERO,
+        now - packet.time_sent,
+        now,
+        true,
+        PacketNumberSpace::ApplicationData,
+    );
+    bbr.on_rtt_update(packet.time_sent, now, rtt_estimator);
+    bbr.on_ack(
+        packet.time_sent,
+        packet.bytes,
+        packet.packet_info,
+        rtt_estimator,
+        &mut random::testing::Generator::default(),
+        now,
+    );
+    now
+}
+
+/// Returns the time it takes to deliver a response of `len` bytes on the `path` after the
+/// connection has been idle for `idle`
+///
+/// A response of the same size is delivered before the idle period, so the congestion window
+/// reflects the capacity of the path when the connection goes idle.
+pub fn response_time_after_idle(
+    config: BbrConfig,
+    path: &Path,
+    len: u64,
+    idle: Duration,
+) -> Duration {
+    let mut now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+
+    now = transfer(
+        &mut bbr,
+        path,
+        &mut rtt_estimator,
+        &mut link_available,
+        len,
+        now,
+    );
+
+    let start = now + idle;
+    transfer(
+        &mut bbr,
+        path,
+        &mut rtt_estimator,
+        &mut link_available,
+        len,
+        start,
+    ) - start
+}
+
+/// Sends `len` bytes on the `path` starting at `now`, returning the time the last byte
+/// was acknowledged
+pub(super) fn transfer(
+    bbr: &mut BbrCongestionController,
+    path: &Path,
+    rtt_estimator: &mut RttEstimator,
+    link_available: &mut Timestamp,
+    len: u64,
+    mut now: Timestamp,
+) -> Timestamp {
+    let mut in_flight = VecDeque::new();
+    let mut remaining = len;
+
+    loop {
+        while remaining > 0 && !bbr.is_congestion_limited() {
+            let bytes = remaining.min(MAX_DATAGRAM_SIZE as u64);
+            remaining -= bytes;
+            in_flight.push_back(send_packet(
+                bbr,
+                path,
+                rtt_estimator,
+                link_available,
+                bytes as usize,
+                now,
+            ));
+        }
+
+        match in_flight.pop_front() {
+            Some(packet) => now = ack_packet(bbr, rtt_estimator, packet),
+            None => return now,
+        }
+    }
+}
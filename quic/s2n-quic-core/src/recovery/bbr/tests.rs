@@ -0,0 +1,704 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{testing::*, *};
+use crate::{
+    recovery::HandshakeCongestionIsolation,
+    time::{Clock, NoopClock},
+};
+use std::collections::VecDeque;
+
+/// Returns the time it takes to deliver a response of `len` bytes on the `path`
+fn response_time(config: BbrConfig, path: &Path, len: u64) -> Duration {
+    let start = NoopClock.get_time();
+    let mut now = start;
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+    let mut in_flight = VecDeque::new();
+    let mut remaining = len;
+
+    loop {
+        while remaining > 0 && !bbr.is_congestion_limited() {
+            let bytes = remaining.min(MAX_DATAGRAM_SIZE as u64);
+            remaining -= bytes;
+            in_flight.push_back(send_packet(
+                &mut bbr,
+                path,
+                &rtt_estimator,
+                &mut link_available,
+                bytes as usize,
+                now,
+            ));
+        }
+
+        match in_flight.pop_front() {
+            Some(packet) => now = ack_packet(&mut bbr, &mut rtt_estimator, packet),
+            None => return now - start,
+        }
+    }
+}
+
+#[test]
+fn configured_initial_window() {
+    let now = NoopClock.get_time();
+    let default_initial_cwnd = BbrCongestionController::initial_window(MAX_DATAGRAM_SIZE);
+
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    assert_eq!(default_initial_cwnd, bbr.congestion_window());
+    assert!(!bbr.initial_cwnd_unchecked);
+
+    let config = BbrConfig {
+        initial_congestion_window: Some(100 * MAX_DATAGRAM_SIZE as u64),
+        ..Default::default()
+    };
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    assert_eq!(100 * MAX_DATAGRAM_SIZE as u32, bbr.congestion_window());
+    assert!(bbr.initial_cwnd_unchecked);
+
+    // the initial window is never smaller than the minimum window
+    let config = BbrConfig {
+        initial_congestion_window: Some(1),
+        ..Default::default()
+    };
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    assert_eq!(bbr.minimum_window(), bbr.congestion_window());
+    assert!(!bbr.initial_cwnd_unchecked);
+}
+
+/// A 100 segment initial window delivers the first response faster than the default
+/// initial window on a lossless 10 Gbps path with a 100µs RTT
+#[test]
+fn large_initial_window_reduces_response_time() {
+    let path = Path {
+        rtt: Duration::from_micros(100),
+        link_rate: 10_000_000_000,
+    };
+    let initial_congestion_window = 100 * MAX_DATAGRAM_SIZE as u64;
+    // the initial window fits within the BDP of the path
+    assert!(initial_congestion_window <= path.bdp());
+
+    let len = 256_000;
+    let default_time = response_time(BbrConfig::default(), &path, len);
+    let large_iw_time = response_time(
+        BbrConfig {
+            initial_congestion_window: Some(initial_congestion_window),
+            ..Default::default()
+        },
+        &path,
+        len,
+    );
+
+    assert!(
+        large_iw_time < default_time,
+        "{:?} should be less than {:?}",
+        large_iw_time,
+        default_time
+    );
+}
+
+/// An initial window larger than the BDP of the path is reduced once the initial flight
+/// has been acknowledged
+#[test]
+fn initial_window_limited_to_bdp() {
+    let path = Path {
+        rtt: Duration::from_millis(1),
+        link_rate: 100_000_000,
+    };
+    let initial_congestion_window = 1000 * MAX_DATAGRAM_SIZE as u64;
+    assert!(initial_congestion_window > 10 * path.bdp());
+
+    let mut now = NoopClock.get_time();
+    let config = BbrConfig {
+        initial_congestion_window: Some(initial_congestion_window),
+        ..Default::default()
+    };
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+
+    // send the initial flight
+    let mut in_flight = VecDeque::new();
+    while !bbr.is_congestion_limited() {
+        in_flight.push_back(send_packet(
+            &mut bbr,
+            &path,
+            &rtt_estimator,
+            &mut link_available,
+            MAX_DATAGRAM_SIZE as usize,
+            now,
+        ));
+    }
+
+    // the first ack starts round 1, so the initial flight is not checked until a packet
+    // sent after the first ack is acknowledged
+    now = ack_packet(&mut bbr, &mut rtt_estimator, in_flight.pop_front().unwrap());
+    let packet = send_packet(
+        &mut bbr,
+        &path,
+        &rtt_estimator,
+        &mut link_available,
+        MAX_DATAGRAM_SIZE as usize,
+        now,
+    );
+    while let Some(sent) = in_flight.pop_front() {
+        ack_packet(&mut bbr, &mut rtt_estimator, sent);
+    }
+    assert!(bbr.initial_cwnd_unchecked);
+
+    ack_packet(&mut bbr, &mut rtt_estimator, packet);
+
+    assert!(!bbr.initial_cwnd_unchecked);
+    assert!(bbr.initial_cwnd < initial_congestion_window as u32);
+    assert!(
+        (bbr.congestion_window() as u64) < initial_congestion_window / 2,
+        "{} should be limited by the path BDP",
+        bbr.congestion_window()
+    );
+}
+
+/// The bandwidth confidence interval is narrow once a bulk transfer on a stable path
+/// has reached the bottleneck rate
+#[test]
+fn bandwidth_confidence_stable_path() {
+    let path = Path {
+        rtt: Duration::from_millis(10),
+        link_rate: 10_000_000,
+    };
+
+    let mut now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+    let mut in_flight = VecDeque::new();
+
+    assert!(bbr.bandwidth_confidence().is_uncertain);
+
+    for _ in 0..2000 {
+        while !bbr.is_congestion_limited() {
+            in_flight.push_back(send_packet(
+                &mut bbr,
+                &path,
+                &rtt_estimator,
+                &mut link_available,
+                MAX_DATAGRAM_SIZE as usize,
+                now,
+            ));
+        }
+
+        now = ack_packet(&mut bbr, &mut rtt_estimator, in_flight.pop_front().unwrap());
+    }
+
+    let confidence = bbr.bandwidth_confidence();
+    assert!(!confidence.is_uncertain);
+    assert_eq!(confidence::DEFAULT_SAMPLES as u32, confidence.sample_count);
+
+    let low = confidence.low.as_bits_per_second();
+    let high = confidence.high.as_bits_per_second();
+    assert!(low > 0);
+    assert!(high - low <= low / 10, "{:?} should be narrow", confidence);
+    assert!(high <= path.link_rate + path.link_rate / 10);
+}
+
+fn careful_resume_config(cwnd: u64, min_rtt: Duration, timestamp: Timestamp) -> BbrConfig {
+    BbrConfig {
+        careful_resume: Some(CarefulResumeParams {
+            cwnd,
+            min_rtt,
+            timestamp,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn careful_resume_initial_window() {
+    let now = NoopClock.get_time();
+    let default_initial_cwnd = BbrCongestionController::initial_window(MAX_DATAGRAM_SIZE);
+    let default_bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let min_rtt = Duration::from_millis(10);
+
+    // the initial window is seeded from the previous connection and paced over its min RTT
+    let config = careful_resume_config(100 * MAX_DATAGRAM_SIZE as u64, min_rtt, now);
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    assert_eq!(100 * MAX_DATAGRAM_SIZE as u32, bbr.congestion_window());
+    assert!(bbr.careful_resume.is_some());
+    assert!(bbr.initial_cwnd_unchecked);
+    assert_eq!(
+        Bandwidth::new(100 * MAX_DATAGRAM_SIZE as u64, min_rtt) * startup::PACING_GAIN,
+        bbr.pacing_rate
+    );
+    assert_ne!(default_bbr.pacing_rate, bbr.pacing_rate);
+
+    // a smaller previous window doesn't reduce the initial window
+    let config = careful_resume_config(MAX_DATAGRAM_SIZE as u64, min_rtt, now);
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    assert_eq!(default_initial_cwnd, bbr.congestion_window());
+    assert!(bbr.careful_resume.is_none());
+    assert_eq!(default_bbr.pacing_rate, bbr.pacing_rate);
+
+    // stale parameters are ignored
+    let config = careful_resume_config(100 * MAX_DATAGRAM_SIZE as u64, min_rtt, now);
+    let later = now + MAX_PARAMS_AGE + Duration::from_secs(1);
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, later);
+    assert_eq!(default_initial_cwnd, bbr.congestion_window());
+    assert!(bbr.careful_resume.is_none());
+}
+
+/// Resuming with the window of a previous connection delivers a response faster than
+/// starting from the default initial window on a long, fat path
+#[test]
+fn careful_resume_reduces_response_time() {
+    let path = Path {
+        rtt: Duration::from_millis(50),
+        link_rate: 100_000_000,
+    };
+    let now = NoopClock.get_time();
+
+    let len = 500_000;
+    let default_time = response_time(BbrConfig::default(), &path, len);
+    let resume_time = response_time(careful_resume_config(path.bdp(), path.rtt, now), &path, len);
+
+    assert!(
+        resume_time < default_time,
+        "{:?} should be less than {:?}",
+        resume_time,
+        default_time
+    );
+}
+
+/// Losing a packet in the initial flight of a resumed connection immediately reduces the
+/// congestion window to the default initial window
+#[test]
+fn careful_resume_initial_flight_loss() {
+    let path = Path {
+        rtt: Duration::from_millis(10),
+        link_rate: 100_000_000,
+    };
+    let default_initial_cwnd = BbrCongestionController::initial_window(MAX_DATAGRAM_SIZE);
+    let resume_cwnd = 100 * MAX_DATAGRAM_SIZE as u64;
+
+    let mut now = NoopClock.get_time();
+    let config = careful_resume_config(resume_cwnd, path.rtt, now);
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    let default_pacing_rate =
+        BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now).pacing_rate;
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+
+    // send the initial flight
+    let mut in_flight = VecDeque::new();
+    while !bbr.is_congestion_limited() {
+        in_flight.push_back(send_packet(
+            &mut bbr,
+            &path,
+            &rtt_estimator,
+            &mut link_available,
+            MAX_DATAGRAM_SIZE as usize,
+            now,
+        ));
+    }
+    assert_eq!(100, in_flight.len());
+
+    now = ack_packet(&mut bbr, &mut rtt_estimator, in_flight.pop_front().unwrap());
+    assert!(bbr.round_counter.round_count() < 2);
+
+    let lost = in_flight.pop_front().unwrap();
+    bbr.on_packet_lost(
+        lost.bytes as u32,
+        lost.packet_info,
+        false,
+        true,
+        &mut random::testing::Generator::default(),
+        now,
+    );
+
+    assert!(bbr.careful_resume.is_none());
+    assert!(!bbr.initial_cwnd_unchecked);
+    assert_eq!(default_initial_cwnd, bbr.initial_cwnd);
+    assert!(bbr.congestion_window() <= default_initial_cwnd);
+    // exiting recovery doesn't restore the resumed window
+    assert!(bbr.prior_cwnd <= default_initial_cwnd);
+    assert!(bbr.pacing_rate <= default_pacing_rate);
+}
+
+/// Packets are marked as app-limited once the application keeps less than the minimum pipe
+/// cwnd in flight for more than a round trip, even though it always has data queued
+#[test]
+fn trickling_sender_is_app_limited() {
+    let path = Path {
+        rtt: Duration::from_millis(50),
+        link_rate: 100_000_000,
+    };
+    let mut now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+
+    assert!(!bbr.was_recently_app_limited());
+
+    // send a single packet each round trip
+    let mut app_limited = vec![];
+    for _ in 0..5 {
+        let packet = send_packet(
+            &mut bbr,
+            &path,
+            &rtt_estimator,
+            &mut link_available,
+            MAX_DATAGRAM_SIZE as usize,
+            now,
+        );
+        app_limited.push(packet.packet_info.is_app_limited);
+        now = ack_packet(&mut bbr, &mut rtt_estimator, packet);
+    }
+
+    // the send buffer hasn't been low for more than a round trip when the first packets are sent
+    assert_eq!(&app_limited[..2], &[false, false]);
+    assert!(app_limited[2..]
+        .iter()
+        .all(|is_app_limited| *is_app_limited));
+    assert!(bbr.was_recently_app_limited());
+}
+
+#[test]
+fn bandwidth_reservation() {
+    let now = NoopClock.get_time();
+
+    let bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    assert_eq!(None, bbr.reserved_bandwidth());
+
+    let reservation = BandwidthReservation {
+        reserved_bytes_per_second: 1_000_000,
+        for_priority: StreamPriority::Critical,
+    };
+    let config = BbrConfig {
+        bandwidth_reservation: Some(reservation),
+        ..Default::default()
+    };
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+
+    // the reserved rate is subtracted from the pacing rate in Startup
+    assert!(bbr.state.is_startup());
+    let expected =
+        Bandwidth::from_bits_per_second(bbr.pacing_rate.as_bits_per_second() - 8_000_000);
+    assert_eq!(
+        Some(ReservedBandwidth {
+            for_priority: StreamPriority::Critical,
+            unreserved: expected,
+        }),
+        bbr.reserved_bandwidth()
+    );
+
+    // the unreserved rate never goes below zero
+    bbr.pacing_rate = Bandwidth::new(100_000, Duration::from_secs(1));
+    assert_eq!(
+        Some(Bandwidth::ZERO),
+        bbr.reserved_bandwidth().map(|reserved| reserved.unreserved)
+    );
+
+    // no headroom is reserved while draining the queue built up in Startup
+    bbr.state = State::Drain;
+    assert_eq!(None, bbr.reserved_bandwidth());
+}
+
+/// Resuming after an idle period longer than a PTO only resets the congestion window to the
+/// initial window with the `CwndReset` policy
+#[test]
+fn idle_restart_policy() {
+    let path = Path {
+        rtt: Duration::from_millis(10),
+        link_rate: 100_000_000,
+    };
+    let initial_cwnd = BbrCongestionController::initial_window(MAX_DATAGRAM_SIZE);
+
+    for policy in [
+        IdleRestartPolicy::Disabled,
+        IdleRestartPolicy::CwndReset,
+        IdleRestartPolicy::CarefulResume,
+    ] {
+        let config = BbrConfig {
+            idle_restart_policy: policy,
+            disable_post_idle_burst_limiting: true,
+            ..Default::default()
+        };
+        let mut now = NoopClock.get_time();
+        let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+        let mut rtt_estimator = RttEstimator::default();
+        let mut link_available = now;
+
+        now = transfer(
+            &mut bbr,
+            &path,
+            &mut rtt_estimator,
+            &mut link_available,
+            path.bdp() * 10,
+            now,
+        );
+        let cwnd = bbr.congestion_window();
+        assert!(cwnd > initial_cwnd);
+
+        // resume sending after being idle for much longer than a PTO
+        now += Duration::from_secs(1);
+        send_packet(
+            &mut bbr,
+            &path,
+            &rtt_estimator,
+            &mut link_available,
+            MAX_DATAGRAM_SIZE as usize,
+            now,
+        );
+
+        match policy {
+            IdleRestartPolicy::Disabled => {
+                assert_eq!(cwnd, bbr.congestion_window());
+                assert!(bbr.careful_resume.is_none());
+            }
+            IdleRestartPolicy::CwndReset => {
+                assert_eq!(initial_cwnd, bbr.congestion_window());
+            }
+            IdleRestartPolicy::CarefulResume => {
+                assert_eq!(cwnd, bbr.congestion_window());
+                assert!(bbr.careful_resume.is_some());
+            }
+        }
+    }
+}
+
+/// Losing a packet in the first flight after an idle period retreats to the initial window
+/// with the `CarefulResume` policy
+#[test]
+fn idle_restart_careful_resume_loss() {
+    let path = Path {
+        rtt: Duration::from_millis(10),
+        link_rate: 100_000_000,
+    };
+    let initial_cwnd = BbrCongestionController::initial_window(MAX_DATAGRAM_SIZE);
+    let config = BbrConfig {
+        idle_restart_policy: IdleRestartPolicy::CarefulResume,
+        disable_post_idle_burst_limiting: true,
+        ..Default::default()
+    };
+    let mut now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, config, now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+
+    now = transfer(
+        &mut bbr,
+        &path,
+        &mut rtt_estimator,
+        &mut link_available,
+        path.bdp() * 10,
+        now,
+    );
+    assert!(bbr.congestion_window() > initial_cwnd);
+
+    // send the first flight after the idle period
+    now += Duration::from_secs(1);
+    let mut in_flight = VecDeque::new();
+    while !bbr.is_congestion_limited() {
+        in_flight.push_back(send_packet(
+            &mut bbr,
+            &path,
+            &rtt_estimator,
+            &mut link_available,
+            MAX_DATAGRAM_SIZE as usize,
+            now,
+        ));
+    }
+    assert!(bbr.careful_resume.is_some());
+
+    now = ack_packet(&mut bbr, &mut rtt_estimator, in_flight.pop_front().unwrap());
+    let lost = in_flight.pop_front().unwrap();
+    bbr.on_packet_lost(
+        lost.bytes as u32,
+        lost.packet_info,
+        false,
+        true,
+        &mut random::testing::Generator::default(),
+        now,
+    );
+
+    assert!(bbr.careful_resume.is_none());
+    assert!(bbr.congestion_window() <= initial_cwnd);
+    // exiting recovery doesn't restore the window from before the idle period
+    assert!(bbr.prior_cwnd <= initial_cwnd);
+}
+
+/// Heavy ECN marking of the handshake packets neither ends Startup nor reduces the congestion
+/// window once the CE marks are kept away from BBR
+#[test]
+fn handshake_ecn_marking_is_isolated() {
+    let path = Path {
+        rtt: Duration::from_millis(10),
+        link_rate: 10_000_000_000,
+    };
+    let mut now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    // receives the same packets, without any CE marks
+    let mut unmarked = bbr.clone();
+    let mut isolation = HandshakeCongestionIsolation::default();
+    let mut rtt_estimator = RttEstimator::default();
+    let mut unmarked_rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+    let mut unmarked_link_available = now;
+    let mut marked_packets = 0;
+
+    // every packet sent over several round trips of the handshake is marked CE
+    for space in [PacketNumberSpace::Initial, PacketNumberSpace::Handshake] {
+        for _ in 0..3 {
+            let mut in_flight = VecDeque::new();
+            let mut unmarked_in_flight = VecDeque::new();
+            while !bbr.is_congestion_limited() {
+                in_flight.push_back(send_packet(
+                    &mut bbr,
+                    &path,
+                    &rtt_estimator,
+                    &mut link_available,
+                    MAX_DATAGRAM_SIZE as usize,
+                    now,
+                ));
+                unmarked_in_flight.push_back(send_packet(
+                    &mut unmarked,
+                    &path,
+                    &unmarked_rtt_estimator,
+                    &mut unmarked_link_available,
+                    MAX_DATAGRAM_SIZE as usize,
+                    now,
+                ));
+            }
+
+            while let Some(packet) = in_flight.pop_front() {
+                assert!(!isolation.on_explicit_congestion(space, 1, packet.ack_time, &mut bbr));
+                marked_packets += 1;
+                now = ack_packet(&mut bbr, &mut rtt_estimator, packet);
+                ack_packet(
+                    &mut unmarked,
+                    &mut unmarked_rtt_estimator,
+                    unmarked_in_flight.pop_front().unwrap(),
+                );
+            }
+        }
+    }
+
+    assert_eq!(
+        marked_packets,
+        isolation.initial_ce_count() + isolation.handshake_ce_count()
+    );
+    assert!(isolation.initial_ce_count() > 0);
+    assert!(isolation.handshake_ce_count() > 0);
+    assert!(bbr.state.is_startup());
+    assert_eq!(unmarked.congestion_window(), bbr.congestion_window());
+    assert_eq!(unmarked.pacing_rate, bbr.pacing_rate);
+
+    // CE marks on application data still reach BBR
+    assert!(isolation.on_explicit_congestion(PacketNumberSpace::ApplicationData, 1, now, &mut bbr));
+    assert_eq!(1, bbr.bw_estimator.rate_sample().ecn_ce_count);
+}
+
+/// The time spent in Startup on a 100 Mbps path is a handful of round trips: enough for the
+/// window to grow to the bandwidth-delay product, plus the rounds without bandwidth growth
+/// that end Startup
+#[test]
+fn startup_duration() {
+    let path = Path {
+        rtt: Duration::from_millis(20),
+        link_rate: 100_000_000,
+    };
+    let now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let mut rtt_estimator = RttEstimator::default();
+    let mut link_available = now;
+    assert_eq!(None, bbr.startup_completed());
+
+    transfer(
+        &mut bbr,
+        &path,
+        &mut rtt_estimator,
+        &mut link_available,
+        20_000_000,
+        now,
+    );
+
+    assert!(!bbr.state.is_startup());
+    let startup = bbr.startup_completed().unwrap();
+    assert_eq!(FullPipeReason::BandwidthPlateau, startup.reason);
+    // the window doubles each round until it reaches the BDP and then 3 rounds pass
+    // without significant bandwidth growth
+    assert!(startup.duration >= path.rtt * 3, "{:?}", startup.duration);
+    assert!(startup.duration <= path.rtt * 20, "{:?}", startup.duration);
+}
+
+/// A pipe that was only estimated to be full because of losses which turn out to be spurious
+/// sends BBR back to Startup, as long as it hasn't left Drain yet
+#[test]
+fn spurious_loss_resumes_startup() {
+    let now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let max_bw = Bandwidth::new(1000, Duration::from_secs(1));
+    let lossy = bandwidth::RateSample {
+        is_app_limited: true,
+        bytes_in_flight: 1000,
+        lost_bytes: 21,
+        ..Default::default()
+    };
+
+    // Two rounds in recovery with excessive loss fill the pipe
+    bbr.startup_duration.on_packet_sent(now);
+    bbr.full_pipe_estimator
+        .on_round_start(lossy, max_bw, true, MAX_DATAGRAM_SIZE);
+    for _ in 0..3 {
+        bbr.full_pipe_estimator.on_packet_lost(true);
+    }
+    bbr.full_pipe_estimator
+        .on_round_start(lossy, max_bw, true, MAX_DATAGRAM_SIZE);
+    assert!(bbr.full_pipe_estimator.filled_pipe());
+    bbr.enter_drain();
+    bbr.startup_duration.on_enter_drain(now);
+    assert!(bbr.startup_completed().is_some());
+
+    bbr.on_spurious_loss();
+    assert!(bbr.state.is_startup());
+    assert!(bbr.is_slow_start());
+    assert!(!bbr.full_pipe_estimator.filled_pipe());
+    assert_eq!(None, bbr.startup_completed());
+
+    // A bandwidth plateau isn't undone by a spurious loss
+    for _ in 0..4 {
+        bbr.full_pipe_estimator.on_round_start(
+            Default::default(),
+            max_bw,
+            false,
+            MAX_DATAGRAM_SIZE,
+        );
+    }
+    assert_eq!(
+        Some(FullPipeReason::BandwidthPlateau),
+        bbr.full_pipe_estimator.reason()
+    );
+    bbr.enter_drain();
+
+    bbr.on_spurious_loss();
+    assert!(bbr.state.is_drain());
+    assert!(bbr.full_pipe_estimator.filled_pipe());
+}
+
+/// The send buffer isn't considered low while the congestion window limits the bytes in flight
+#[test]
+fn cwnd_limited_sender_is_not_app_limited() {
+    let now = NoopClock.get_time();
+    let mut bbr = BbrCongestionController::new(MAX_DATAGRAM_SIZE, BbrConfig::default(), now);
+    let rtt = Duration::from_millis(50);
+    bbr.pacing_rate = Bandwidth::new(100_000_000, Duration::from_secs(1));
+
+    // less than the minimum pipe cwnd is in flight, but that's all the cwnd allows
+    let in_flight = 2 * MAX_DATAGRAM_SIZE as u32;
+    assert!(in_flight < bbr.minimum_window());
+    bbr.bytes_in_flight = BytesInFlight::new(in_flight);
+    bbr.cwnd = in_flight;
+    assert!(!bbr.is_send_buffer_low(rtt));
+
+    // with room in the cwnd, the application is the limit
+    bbr.cwnd = bbr.minimum_window();
+    assert!(bbr.is_send_buffer_low(rtt));
+}
@@ -6,10 +6,11 @@ use crate::{
     inet,
     path::MINIMUM_MTU,
     random,
-    recovery::RttEstimator,
+    recovery::{bandwidth::Bandwidth, bbr::FullPipeReason, RttEstimator},
+    stream::StreamPriority,
     time::Timestamp,
 };
-use core::fmt::Debug;
+use core::{fmt::Debug, time::Duration};
 
 pub trait Endpoint: 'static + Debug + Send {
     type CongestionController: CongestionController;
@@ -115,6 +116,13 @@ pub trait CongestionController: 'static + Clone + Send + Debug {
         timestamp: Timestamp,
     );
 
+    /// Invoked when a packet which was declared lost is acknowledged
+    ///
+    /// The packet was most likely reordered rather than lost, so the congestion controller may
+    /// undo decisions it based on the loss.
+    #[inline]
+    fn on_spurious_loss(&mut self) {}
+
     /// Invoked when the Explicit Congestion Notification counter increases.
     ///
     /// `ce_count` represents the incremental number of packets marked with the ECN CE codepoint
@@ -140,6 +148,49 @@ pub trait CongestionController: 'static + Clone + Send + Debug {
     fn send_quantum(&self) -> Option<usize> {
         None
     }
+
+    /// Returns the sending rate available to streams below a reserved priority
+    ///
+    /// If the value is `None`, all streams share the sending rate of the connection.
+    fn reserved_bandwidth(&self) -> Option<ReservedBandwidth> {
+        None
+    }
+
+    /// Returns the estimated bandwidth of the path
+    ///
+    /// If the value is `None`, the congestion controller doesn't estimate the bandwidth or
+    /// hasn't measured it yet.
+    #[inline]
+    fn bandwidth_estimate(&self) -> Option<Bandwidth> {
+        None
+    }
+
+    /// Returns how long the startup phase lasted and why it ended, once it has ended
+    ///
+    /// If the value is `None`, the congestion controller is still in its startup phase or
+    /// doesn't estimate when it has filled the pipe.
+    #[inline]
+    fn startup_completed(&self) -> Option<StartupCompleted> {
+        None
+    }
+}
+
+/// The outcome of the startup phase of a congestion controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StartupCompleted {
+    /// The time between the first packet sent in the startup phase and the end of the phase
+    pub duration: Duration,
+    /// The reason the congestion controller estimated it has filled the pipe
+    pub reason: FullPipeReason,
+}
+
+/// Sending rate held back by a congestion controller for higher priority streams
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReservedBandwidth {
+    /// Streams at or above this priority may use the full sending rate
+    pub for_priority: StreamPriority,
+    /// The sending rate shared by streams below `for_priority`
+    pub unreserved: Bandwidth,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -273,6 +324,7 @@ pub mod testing {
             pub lost_bytes: u32,
             pub persistent_congestion: Option<bool>,
             pub on_packets_lost: u32,
+            pub on_spurious_loss: u32,
             pub on_rtt_update: u32,
             pub on_packet_ack: u32,
             pub on_mtu_update: u32,
@@ -282,6 +334,7 @@ pub mod testing {
             pub loss_bursts: u32,
             pub app_limited: Option<bool>,
             pub slow_start: bool,
+            pub bandwidth_estimate: Option<Bandwidth>,
         }
 
         impl Default for CongestionController {
@@ -291,6 +344,7 @@ pub mod testing {
                     lost_bytes: 0,
                     persistent_congestion: None,
                     on_packets_lost: 0,
+                    on_spurious_loss: 0,
                     on_rtt_update: 0,
                     on_packet_ack: 0,
                     on_mtu_update: 0,
@@ -300,6 +354,7 @@ pub mod testing {
                     loss_bursts: 0,
                     app_limited: None,
                     slow_start: true,
+                    bandwidth_estimate: None,
                 }
             }
         }
@@ -381,6 +436,10 @@ pub mod testing {
                 }
             }
 
+            fn on_spurious_loss(&mut self) {
+                self.on_spurious_loss += 1;
+            }
+
             fn on_explicit_congestion(&mut self, _ce_count: u64, _event_time: Timestamp) {
                 self.congestion_events += 1;
                 self.slow_start = false;
@@ -397,6 +456,10 @@ pub mod testing {
             fn earliest_departure_time(&self) -> Option<Timestamp> {
                 None
             }
+
+            fn bandwidth_estimate(&self) -> Option<Bandwidth> {
+                self.bandwidth_estimate
+            }
         }
     }
 }
@@ -7,6 +7,7 @@ use crate::{
     recovery::{
         congestion_controller::{self, CongestionController},
         cubic::{FastRetransmission::*, State::*},
+        delay_gradient::{DelayGradientMonitor, PreemptiveCwndReduction},
         hybrid_slow_start::HybridSlowStart,
         pacing::Pacer,
         RttEstimator,
@@ -161,6 +162,9 @@ pub struct CubicCongestionController {
     // The highest number of bytes in flight seen when an ACK was received,
     // since the last congestion event.
     bytes_in_flight_hi: BytesInFlight,
+    // Reduces the congestion window before packets are lost when the RTT increases steeply,
+    // if enabled
+    delay_gradient: Option<DelayGradientMonitor>,
 }
 
 type BytesInFlight = Counter<u32>;
@@ -275,6 +279,16 @@ impl CongestionController for CubicCongestionController {
             self.state = State::congestion_avoidance(now);
             self.cubic.on_slow_start_exit(self.congestion_window);
         }
+
+        if let Some(reduction) = self.delay_gradient.as_mut().and_then(|monitor| {
+            monitor.on_rtt_update(
+                now,
+                rtt_estimator.latest_rtt(),
+                rtt_estimator.smoothed_rtt(),
+            )
+        }) {
+            self.on_preemptive_reduction(reduction, now);
+        }
     }
 
     #[inline]
@@ -481,9 +495,18 @@ impl CubicCongestionController {
             time_of_last_sent_packet: None,
             under_utilized: true,
             bytes_in_flight_hi: Counter::new(0),
+            delay_gradient: None,
         }
     }
 
+    /// Reduces the congestion window by 10% whenever the delay gradient exceeds `threshold`
+    ///
+    /// See [`DelayGradientMonitor`] for details.
+    pub fn with_delay_gradient_threshold(mut self, threshold: f32) -> Self {
+        self.delay_gradient = Some(DelayGradientMonitor::new(threshold));
+        self
+    }
+
     //= https://www.rfc-editor.org/rfc/rfc9002#section-7.2
     //# Endpoints SHOULD use an initial congestion
     //# window of ten times the maximum datagram size (max_datagram_size),
@@ -617,6 +640,29 @@ impl CubicCongestionController {
         self.slow_start.on_congestion_event(self.congestion_window);
     }
 
+    /// Called when the delay gradient indicates the bottleneck queue is growing
+    ///
+    /// Unlike a congestion event, the reduction doesn't enter a recovery period and W_max is
+    /// kept, so the window grows back to where it was more quickly.
+    #[inline]
+    fn on_preemptive_reduction(&mut self, reduction: PreemptiveCwndReduction, now: Timestamp) {
+        // Losses in the recovery period have already reduced the window
+        if matches!(self.state, Recovery(_, _)) {
+            return;
+        }
+
+        let cwnd = reduction
+            .apply(self.congestion_window)
+            .max(self.cubic.minimum_window());
+        self.cubic
+            .on_preemptive_reduction(self.congestion_window, cwnd);
+        self.congestion_window = cwnd;
+        self.state = State::congestion_avoidance(now);
+
+        // Don't return to slow start after the reduction
+        self.slow_start.on_congestion_event(self.congestion_window);
+    }
+
     #[inline]
     fn packets_to_bytes(&self, cwnd: f32) -> f32 {
         cwnd * self.max_datagram_size as f32
@@ -802,6 +848,18 @@ impl Cubic {
         cwnd_start
     }
 
+    /// Called when the congestion window was reduced from `cwnd` to `cwnd_start` by a
+    /// preemptive reduction
+    ///
+    /// W_max is set to the window before the reduction, unless it was already larger, and K
+    /// is calculated for the smaller reduction.
+    #[inline]
+    fn on_preemptive_reduction(&mut self, cwnd: f32, cwnd_start: f32) {
+        self.w_max = self.w_max.max(self.bytes_to_packets(cwnd));
+        let w_start = self.bytes_to_packets(cwnd_start);
+        self.k = Duration::from_secs_f32(((self.w_max - w_start).max(0.0) / C).cbrt());
+    }
+
     //= https://www.rfc-editor.org/rfc/rfc8312#section-4.8
     //# In the case when CUBIC runs the hybrid slow start [HR08], it may exit
     //# the first slow start without incurring any packet loss and thus W_max
@@ -836,7 +894,20 @@ impl Cubic {
 }
 
 #[derive(Debug, Default)]
-pub struct Endpoint {}
+pub struct Endpoint {
+    delay_gradient_threshold: Option<f32>,
+}
+
+impl Endpoint {
+    /// Enables preemptive congestion window reductions when the delay gradient exceeds
+    /// `threshold`
+    ///
+    /// See [`DelayGradientMonitor`] for details.
+    pub fn with_delay_gradient_threshold(mut self, threshold: f32) -> Self {
+        self.delay_gradient_threshold = Some(threshold);
+        self
+    }
+}
 
 impl congestion_controller::Endpoint for Endpoint {
     type CongestionController = CubicCongestionController;
@@ -845,7 +916,12 @@ impl congestion_controller::Endpoint for Endpoint {
         &mut self,
         path_info: congestion_controller::PathInfo,
     ) -> Self::CongestionController {
-        CubicCongestionController::new(path_info.max_datagram_size)
+        let congestion_controller = CubicCongestionController::new(path_info.max_datagram_size);
+
+        match self.delay_gradient_threshold {
+            Some(threshold) => congestion_controller.with_delay_gradient_threshold(threshold),
+            None => congestion_controller,
+        }
     }
 }
 
@@ -1108,3 +1108,135 @@ fn on_packet_ack_congestion_avoidance_too_large_increase() {
     assert!(cc.cubic.w_cubic(t) > cc.cubic.w_est(t, rtt));
     assert_delta!(cc.congestion_window, 3_600_000.0 + 1000.0 / 2.0, 0.001);
 }
+
+/// Results of a bulk transfer simulated with `bufferbloat_simulation`
+struct BufferbloatResult {
+    max_rtt: Duration,
+    delivered: u64,
+}
+
+/// Simulates a bulk transfer over a path with a 50ms RTT and a 1000 packet per second
+/// bottleneck, which buffers every packet rather than dropping any
+fn bufferbloat_simulation(mut cc: CubicCongestionController) -> BufferbloatResult {
+    use std::collections::VecDeque;
+
+    const MAX_DATAGRAM_SIZE: u16 = 1200;
+    // the bottleneck forwards a packet every 1ms
+    const TRANSMISSION_TIME: Duration = Duration::from_millis(1);
+    const BASE_RTT: Duration = Duration::from_millis(50);
+    const DURATION: Duration = Duration::from_secs(20);
+
+    let start = NoopClock.get_time();
+    let random = &mut random::testing::Generator::default();
+    let mut rtt_estimator = RttEstimator::default();
+    let mut now = start;
+    let mut link_free_at = start;
+    // the time each packet was sent and the time it is acknowledged
+    let mut in_flight = VecDeque::new();
+    let mut result = BufferbloatResult {
+        max_rtt: Duration::ZERO,
+        delivered: 0,
+    };
+
+    loop {
+        // send as much as the congestion window allows
+        while !cc.is_congestion_limited() {
+            cc.on_packet_sent(now, MAX_DATAGRAM_SIZE as usize, Some(false), &rtt_estimator);
+            let departure = link_free_at.max(now) + TRANSMISSION_TIME;
+            link_free_at = departure;
+            in_flight.push_back((now, departure + BASE_RTT));
+        }
+
+        let (time_sent, ack_time) = in_flight.pop_front().unwrap();
+        now = ack_time;
+
+        if now - start > DURATION {
+            return result;
+        }
+
+        let rtt = now - time_sent;
+        result.max_rtt = result.max_rtt.max(rtt);
+        result.delivered += MAX_DATAGRAM_SIZE as u64;
+
+        rtt_estimator.update_rtt(
+            Duration::ZERO,
+            rtt,
+            now,
+            true,
+            PacketNumberSpace::ApplicationData,
+        );
+        cc.on_rtt_update(time_sent, now, &rtt_estimator);
+        cc.on_ack(
+            time_sent,
+            MAX_DATAGRAM_SIZE as usize,
+            (),
+            &rtt_estimator,
+            random,
+            now,
+        );
+    }
+}
+
+#[test]
+fn preemptive_reduction_bufferbloat() {
+    use crate::recovery::delay_gradient::DEFAULT_THRESHOLD;
+
+    let baseline = bufferbloat_simulation(CubicCongestionController::new(1200));
+    let preemptive = bufferbloat_simulation(
+        CubicCongestionController::new(1200).with_delay_gradient_threshold(DEFAULT_THRESHOLD),
+    );
+
+    // without any loss, the window keeps growing and fills the bottleneck buffer
+    assert!(baseline.max_rtt > Duration::from_secs(1));
+
+    // reducing the window when the queueing delay grows steeply keeps the queue shorter
+    assert!(
+        preemptive.max_rtt < baseline.max_rtt,
+        "{:?} >= {:?}",
+        preemptive.max_rtt,
+        baseline.max_rtt
+    );
+
+    // without giving up throughput, since the window stays above the path's capacity
+    assert!(
+        preemptive.delivered >= baseline.delivered * 9 / 10,
+        "{} < {}",
+        preemptive.delivered,
+        baseline.delivered
+    );
+}
+
+#[test]
+fn preemptive_reduction() {
+    let max_datagram_size = 1200;
+    let mut cc = CubicCongestionController::new(max_datagram_size);
+    let now = NoopClock.get_time();
+
+    cc.state = State::congestion_avoidance(now);
+    cc.congestion_window = 100_000.0;
+    cc.cubic.w_max = bytes_to_packets(80_000.0, max_datagram_size);
+
+    cc.on_preemptive_reduction(PreemptiveCwndReduction { gradient: 0.5 }, now);
+
+    assert_delta!(cc.congestion_window, 90_000.0, 0.001);
+    assert_eq!(cc.state, State::congestion_avoidance(now));
+    // W_max is raised to the window before the reduction
+    assert_delta!(
+        cc.cubic.w_max,
+        bytes_to_packets(100_000.0, max_datagram_size),
+        0.001
+    );
+    // K = cubic_root((W_max - cwnd_start) / C) = cubic_root(8.33 / 0.4)
+    assert_delta!(cc.cubic.k.as_secs_f32(), 2.752, 0.001);
+    // and the window grows back to W_max in K
+    assert_delta!(
+        cc.cubic.w_cubic(cc.cubic.k),
+        bytes_to_packets(100_000.0, max_datagram_size),
+        0.001
+    );
+
+    // the window isn't reduced again during a recovery period
+    cc.state = State::Recovery(now, Idle);
+    cc.on_preemptive_reduction(PreemptiveCwndReduction { gradient: 0.5 }, now);
+    assert_delta!(cc.congestion_window, 90_000.0, 0.001);
+}
@@ -0,0 +1,215 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Predicts congestion from the rate at which the round-trip time increases
+//!
+//! Before a bottleneck queue overflows and drops packets, it delays them. The delay gradient
+//! is the increase in RTT divided by the time over which it increased. A gradient above the
+//! threshold means the queue is growing quickly enough that loss is likely to follow, so the
+//! sender can back off slightly before any packets are lost.
+
+use crate::time::Timestamp;
+use core::time::Duration;
+
+/// The default delay gradient above which the congestion window is reduced
+///
+/// A gradient of 0.2 means the RTT increases by 0.2ms for every 1ms that passes.
+pub const DEFAULT_THRESHOLD: f32 = 0.2;
+
+/// The factor the congestion window is multiplied by in a preemptive reduction
+///
+/// This is a smaller reduction than the one applied in response to packet loss.
+pub const PREEMPTIVE_BETA: f32 = 0.9;
+
+/// The number of RTT samples in the sliding window
+///
+/// Samples are taken at most every `smoothed_rtt / SAMPLES`, so the window spans around one
+/// round trip.
+const SAMPLES: usize = 8;
+
+/// A reduction of the congestion window in response to a steep delay gradient
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreemptiveCwndReduction {
+    /// The delay gradient which triggered the reduction
+    pub gradient: f32,
+}
+
+impl PreemptiveCwndReduction {
+    /// Returns the reduced congestion window
+    #[inline]
+    pub fn apply(&self, congestion_window: f32) -> f32 {
+        congestion_window * PREEMPTIVE_BETA
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    timestamp: Timestamp,
+    rtt: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct DelayGradientMonitor {
+    /// The gradient above which a reduction is triggered
+    threshold: f32,
+    /// A ring buffer of the most recent samples
+    samples: [Option<Sample>; SAMPLES],
+    /// The index of the next sample in the ring buffer
+    next: usize,
+}
+
+impl DelayGradientMonitor {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            samples: [None; SAMPLES],
+            next: 0,
+        }
+    }
+
+    /// Returns the gradient above which a reduction is triggered
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Called when the RTT estimate is updated
+    ///
+    /// Returns a reduction if the delay gradient over the window exceeds the threshold. The
+    /// window is cleared after a reduction so it takes another round trip of samples before
+    /// the next one.
+    pub fn on_rtt_update(
+        &mut self,
+        now: Timestamp,
+        latest_rtt: Duration,
+        smoothed_rtt: Duration,
+    ) -> Option<PreemptiveCwndReduction> {
+        let newest = self.samples[(self.next + SAMPLES - 1) % SAMPLES];
+
+        if let Some(newest) = newest {
+            let interval = smoothed_rtt / SAMPLES as u32;
+            if now.saturating_duration_since(newest.timestamp) < interval {
+                return None;
+            }
+        }
+
+        self.samples[self.next] = Some(Sample {
+            timestamp: now,
+            rtt: latest_rtt,
+        });
+        self.next = (self.next + 1) % SAMPLES;
+
+        let gradient = self.gradient()?;
+
+        if gradient <= self.threshold {
+            return None;
+        }
+
+        self.samples = [None; SAMPLES];
+        self.next = 0;
+
+        Some(PreemptiveCwndReduction { gradient })
+    }
+
+    /// Returns the delay gradient between the oldest and newest samples in the window
+    ///
+    /// Returns `None` until the window is full.
+    #[inline]
+    pub fn gradient(&self) -> Option<f32> {
+        // `next` points at the oldest sample once the window is full
+        let oldest = self.samples[self.next]?;
+        let newest = self.samples[(self.next + SAMPLES - 1) % SAMPLES]?;
+
+        let elapsed = newest
+            .timestamp
+            .saturating_duration_since(oldest.timestamp)
+            .as_secs_f32();
+
+        if elapsed == 0.0 {
+            return None;
+        }
+
+        let increase = newest.rtt.as_secs_f32() - oldest.rtt.as_secs_f32();
+
+        Some(increase / elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{testing::Clock, Clock as _};
+
+    const RTT: Duration = Duration::from_millis(80);
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Samples an RTT which increases by `gradient` ms every ms
+    fn sample(
+        monitor: &mut DelayGradientMonitor,
+        clock: &mut Clock,
+        rtt: &mut Duration,
+        gradient: f32,
+    ) -> Option<PreemptiveCwndReduction> {
+        clock.inc_by(INTERVAL);
+        *rtt += INTERVAL.mul_f32(gradient);
+        monitor.on_rtt_update(clock.get_time(), *rtt, RTT)
+    }
+
+    #[test]
+    fn steady_rtt_test() {
+        let mut clock = Clock::default();
+        let mut monitor = DelayGradientMonitor::new(DEFAULT_THRESHOLD);
+        let mut rtt = RTT;
+
+        for _ in 0..100 {
+            assert_eq!(sample(&mut monitor, &mut clock, &mut rtt, 0.0), None);
+        }
+        assert_eq!(monitor.gradient(), Some(0.0));
+
+        // a gradient below the threshold doesn't trigger a reduction
+        for _ in 0..100 {
+            assert_eq!(sample(&mut monitor, &mut clock, &mut rtt, 0.1), None);
+        }
+    }
+
+    #[test]
+    fn steep_gradient_test() {
+        let mut clock = Clock::default();
+        let mut monitor = DelayGradientMonitor::new(DEFAULT_THRESHOLD);
+        let mut rtt = RTT;
+
+        // the window needs to be full before a reduction is triggered
+        for _ in 0..SAMPLES - 1 {
+            assert_eq!(sample(&mut monitor, &mut clock, &mut rtt, 0.5), None);
+        }
+
+        let reduction = sample(&mut monitor, &mut clock, &mut rtt, 0.5).unwrap();
+        assert!((reduction.gradient - 0.5).abs() < 0.01);
+        assert!((reduction.apply(100.0) - 90.0).abs() < 0.001);
+
+        // the window is cleared after a reduction
+        assert_eq!(monitor.gradient(), None);
+        for _ in 0..SAMPLES - 1 {
+            assert_eq!(sample(&mut monitor, &mut clock, &mut rtt, 0.5), None);
+        }
+        assert!(sample(&mut monitor, &mut clock, &mut rtt, 0.5).is_some());
+    }
+
+    #[test]
+    fn sample_interval_test() {
+        let mut clock = Clock::default();
+        let mut monitor = DelayGradientMonitor::new(DEFAULT_THRESHOLD);
+
+        // samples closer together than RTT / SAMPLES are ignored
+        monitor.on_rtt_update(clock.get_time(), RTT, RTT);
+        for _ in 1..INTERVAL.as_millis() {
+            clock.inc_by(Duration::from_millis(1));
+            assert_eq!(monitor.on_rtt_update(clock.get_time(), RTT * 2, RTT), None);
+        }
+        assert_eq!(monitor.samples.iter().flatten().count(), 1);
+
+        clock.inc_by(Duration::from_millis(1));
+        monitor.on_rtt_update(clock.get_time(), RTT * 2, RTT);
+        assert_eq!(monitor.samples.iter().flatten().count(), 2);
+    }
+}
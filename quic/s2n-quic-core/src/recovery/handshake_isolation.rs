@@ -0,0 +1,104 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{packet::number::PacketNumberSpace, recovery::CongestionController, time::Timestamp};
+
+/// Keeps ECN congestion signals received during the handshake away from the congestion controller
+///
+/// Initial and Handshake packets are few and have atypical sizes, so the share of them that are
+/// marked with the ECN Congestion Experienced (CE) codepoint says little about the capacity of
+/// the path. Feeding these marks into the congestion controller could end slow start before the
+/// path has been probed or reduce the congestion window the connection starts sending
+/// application data with. CE marks in the Initial and Handshake packet number spaces are counted
+/// separately instead, and only marks on application data packets reach the congestion
+/// controller.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HandshakeCongestionIsolation {
+    /// The number of CE marks reported for Initial packets
+    initial_ce_count: u64,
+    /// The number of CE marks reported for Handshake packets
+    handshake_ce_count: u64,
+}
+
+impl HandshakeCongestionIsolation {
+    /// Called when the peer reports `ce_count` new CE marks for packets sent in `space`
+    ///
+    /// Returns `true` if the `congestion_controller` was notified of the congestion.
+    #[inline]
+    pub fn on_explicit_congestion<CC: CongestionController>(
+        &mut self,
+        space: PacketNumberSpace,
+        ce_count: u64,
+        event_time: Timestamp,
+        congestion_controller: &mut CC,
+    ) -> bool {
+        match space {
+            PacketNumberSpace::Initial => {
+                self.initial_ce_count = self.initial_ce_count.saturating_add(ce_count);
+                false
+            }
+            PacketNumberSpace::Handshake => {
+                self.handshake_ce_count = self.handshake_ce_count.saturating_add(ce_count);
+                false
+            }
+            PacketNumberSpace::ApplicationData => {
+                congestion_controller.on_explicit_congestion(ce_count, event_time);
+                true
+            }
+        }
+    }
+
+    /// Returns the number of CE marks reported for Initial packets
+    #[inline]
+    pub fn initial_ce_count(&self) -> u64 {
+        self.initial_ce_count
+    }
+
+    /// Returns the number of CE marks reported for Handshake packets
+    #[inline]
+    pub fn handshake_ce_count(&self) -> u64 {
+        self.handshake_ce_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        recovery::congestion_controller::testing::mock::CongestionController as MockCC,
+        time::{Clock, NoopClock},
+    };
+
+    #[test]
+    fn only_application_data_reaches_congestion_controller() {
+        let now = NoopClock.get_time();
+        let mut isolation = HandshakeCongestionIsolation::default();
+        let mut congestion_controller = MockCC::default();
+
+        assert!(!isolation.on_explicit_congestion(
+            PacketNumberSpace::Initial,
+            2,
+            now,
+            &mut congestion_controller
+        ));
+        assert!(!isolation.on_explicit_congestion(
+            PacketNumberSpace::Handshake,
+            3,
+            now,
+            &mut congestion_controller
+        ));
+        assert_eq!(2, isolation.initial_ce_count());
+        assert_eq!(3, isolation.handshake_ce_count());
+        assert_eq!(0, congestion_controller.congestion_events);
+
+        assert!(isolation.on_explicit_congestion(
+            PacketNumberSpace::ApplicationData,
+            1,
+            now,
+            &mut congestion_controller
+        ));
+        assert_eq!(1, congestion_controller.congestion_events);
+        assert_eq!(2, isolation.initial_ce_count());
+        assert_eq!(3, isolation.handshake_ce_count());
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Connection-level packet loss rates
+//!
+//! The rates are averaged over a rolling window of "packet-timed" round trips: a round starts
+//! with the transmission of a packet and ends when that packet, or any packet sent after it,
+//! is acknowledged. This is the same notion of a round used by BBR.
+
+use crate::packet::number::PacketNumber;
+
+/// The default number of rounds the loss rates are averaged over
+pub const DEFAULT_LOSS_WINDOW: u8 = 10;
+
+/// The largest number of rounds the loss rates can be averaged over
+pub const MAX_LOSS_WINDOW: u8 = 64;
+
+/// Packet loss rates averaged over the most recent rounds
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LossRates {
+    /// The number of lost packets divided by the number of sent packets
+    pub packet_loss_rate: f64,
+    /// The number of loss bursts divided by the number of sent packets
+    ///
+    /// A loss burst is a run of one or more lost packets with consecutive packet numbers.
+    pub burst_loss_rate: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Round {
+    sent: u64,
+    lost: u64,
+    bursts: u64,
+}
+
+impl Round {
+    fn add(&mut self, other: &Round) {
+        self.sent += other.sent;
+        self.lost += other.lost;
+        self.bursts += other.bursts;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Estimator {
+    /// The completed rounds within the window, stored as a ring buffer
+    rounds: [Round; MAX_LOSS_WINDOW as usize],
+    /// The index in `rounds` the next completed round is written to
+    next_index: usize,
+    /// The number of rounds the rates are averaged over
+    window: usize,
+    /// The round currently in progress
+    current: Round,
+    /// The largest packet number sent so far
+    largest_sent: Option<PacketNumber>,
+    /// Acknowledging this packet number, or any larger one, ends the current round
+    round_end: Option<PacketNumber>,
+    /// The most recently lost packet number, used to detect loss bursts
+    last_lost: Option<PacketNumber>,
+    /// The rates as of the end of the last round
+    rates: LossRates,
+}
+
+impl Default for Estimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOSS_WINDOW)
+    }
+}
+
+impl Estimator {
+    /// Creates an estimator averaging the loss rates over `window` rounds
+    ///
+    /// The window is clamped to between 1 and [`MAX_LOSS_WINDOW`].
+    pub fn new(window: u8) -> Self {
+        Self {
+            rounds: [Round::default(); MAX_LOSS_WINDOW as usize],
+            next_index: 0,
+            window: window.clamp(1, MAX_LOSS_WINDOW) as usize,
+            current: Round::default(),
+            largest_sent: None,
+            round_end: None,
+            last_lost: None,
+            rates: LossRates::default(),
+        }
+    }
+
+    /// Returns the loss rates as of the end of the last round
+    #[inline]
+    pub fn rates(&self) -> LossRates {
+        self.rates
+    }
+
+    /// Called when a congestion controlled packet is sent
+    #[inline]
+    pub fn on_packet_sent(&mut self, packet_number: PacketNumber) {
+        self.current.sent += 1;
+        self.largest_sent = Some(packet_number);
+
+        if self.round_end.is_none() {
+            self.round_end = Some(packet_number);
+        }
+    }
+
+    /// Called with the largest packet number in a newly acknowledged range
+    #[inline]
+    pub fn on_packet_ack(&mut self, packet_number: PacketNumber) {
+        if self.round_end.map_or(false, |end| packet_number >= end) {
+            self.on_round_end();
+        }
+    }
+
+    /// Called when a congestion controlled packet is declared lost
+    #[inline]
+    pub fn on_packet_lost(&mut self, packet_number: PacketNumber) {
+        self.current.lost += 1;
+
+        let continues_burst = self
+            .last_lost
+            .and_then(PacketNumber::next)
+            .map_or(false, |next| next == packet_number);
+
+        if !continues_burst {
+            self.current.bursts += 1;
+        }
+
+        self.last_lost = Some(packet_number);
+    }
+
+    fn on_round_end(&mut self) {
+        self.rounds[self.next_index] = core::mem::take(&mut self.current);
+        self.next_index = (self.next_index + 1) % self.window;
+
+        // the next round ends once a packet sent from now on is acknowledged
+        self.round_end = self.largest_sent.and_then(PacketNumber::next);
+
+        let mut total = Round::default();
+        for round in &self.rounds[..self.window] {
+            total.add(round);
+        }
+
+        if total.sent == 0 {
+            self.rates = LossRates::default();
+            return;
+        }
+
+        // packets lost in this window may have been sent before it, so cap the rates
+        let sent = total.sent as f64;
+        self.rates = LossRates {
+            packet_loss_rate: (total.lost as f64 / sent).min(1.0),
+            burst_loss_rate: (total.bursts as f64 / sent).min(1.0),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{packet::number::PacketNumberSpace, varint::VarInt};
+
+    fn pn(value: u32) -> PacketNumber {
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u32(value))
+    }
+
+    /// Sends `count` packets starting at `start`, loses the packet numbers in `lost` and then
+    /// acknowledges the last packet, ending the round
+    fn round(estimator: &mut Estimator, start: u32, count: u32, lost: &[u32]) {
+        for i in start..start + count {
+            estimator.on_packet_sent(pn(i));
+        }
+        for i in lost {
+            estimator.on_packet_lost(pn(*i));
+        }
+        estimator.on_packet_ack(pn(start + count - 1));
+    }
+
+    #[test]
+    fn no_loss_test() {
+        let mut estimator = Estimator::default();
+        assert_eq!(LossRates::default(), estimator.rates());
+
+        round(&mut estimator, 0, 10, &[]);
+        assert_eq!(LossRates::default(), estimator.rates());
+    }
+
+    #[test]
+    fn rates_update_at_round_end_test() {
+        let mut estimator = Estimator::default();
+
+        estimator.on_packet_sent(pn(0));
+        estimator.on_packet_sent(pn(1));
+        estimator.on_packet_ack(pn(0));
+
+        // the next round ends once packet 2 is acknowledged
+        estimator.on_packet_sent(pn(2));
+        estimator.on_packet_sent(pn(3));
+        estimator.on_packet_lost(pn(1));
+        estimator.on_packet_ack(pn(0));
+        assert_eq!(LossRates::default(), estimator.rates());
+
+        estimator.on_packet_ack(pn(2));
+        assert_eq!(0.25, estimator.rates().packet_loss_rate);
+    }
+
+    #[test]
+    fn burst_test() {
+        let mut estimator = Estimator::default();
+
+        // 2 bursts across 4 lost packets
+        round(&mut estimator, 0, 10, &[2, 3, 4, 7]);
+        assert_eq!(
+            LossRates {
+                packet_loss_rate: 0.4,
+                burst_loss_rate: 0.2,
+            },
+            estimator.rates()
+        );
+    }
+
+    #[test]
+    fn window_test() {
+        let mut estimator = Estimator::new(2);
+
+        round(&mut estimator, 0, 10, &[0, 2]);
+        assert_eq!(0.2, estimator.rates().packet_loss_rate);
+
+        round(&mut estimator, 10, 10, &[]);
+        assert_eq!(0.1, estimator.rates().packet_loss_rate);
+
+        // the lossy round has left the window
+        round(&mut estimator, 20, 10, &[]);
+        assert_eq!(LossRates::default(), estimator.rates());
+    }
+
+    #[test]
+    fn window_clamp_test() {
+        assert_eq!(1, Estimator::new(0).window);
+        assert_eq!(MAX_LOSS_WINDOW as usize, Estimator::new(u8::MAX).window);
+    }
+}
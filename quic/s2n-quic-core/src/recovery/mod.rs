@@ -3,14 +3,20 @@
 
 pub use congestion_controller::CongestionController;
 pub use cubic::CubicCongestionController;
+pub use handshake_isolation::HandshakeCongestionIsolation;
 pub use rtt_estimator::*;
 pub use sent_packets::*;
 
+#[allow(dead_code)] // TODO: Remove when a one-way delay source is available
+pub(crate) mod asymmetric_rtt;
 pub mod bandwidth;
 pub mod bbr;
 pub mod congestion_controller;
 pub mod cubic;
+pub mod delay_gradient;
+mod handshake_isolation;
 mod hybrid_slow_start;
+pub mod loss_rate;
 mod pacing;
 mod rtt_estimator;
 mod sent_packets;
@@ -0,0 +1,139 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checksums of STREAM frame payloads
+//!
+//! When both endpoints enable the `stream_data_checksum` transport parameter, each STREAM frame
+//! carrying data starts with the CRC32 (IEEE) of the rest of the frame data, in network byte
+//! order. The checksum is not part of the stream and doesn't count towards the stream offsets or
+//! flow control. Frames without any data, like a FIN-only frame, don't carry a checksum.
+//!
+//! The packets are already authenticated, so the checksum only detects corruption introduced by
+//! the endpoints themselves, for example in the stream reassembly.
+
+/// The number of bytes a checksum adds to a STREAM frame payload
+pub const LEN: usize = 4;
+
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ POLYNOMIAL
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC32 of data spread over several chunks
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Adds `data` to the checksum
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            let index = (self.state ^ *byte as u32) as u8;
+            self.state = (self.state >> 8) ^ TABLE[index as usize];
+        }
+    }
+
+    /// Returns the checksum of all of the data added so far
+    #[inline]
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+/// Returns the checksum of `data`
+#[inline]
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+/// Verifies the checksum at the start of a STREAM frame payload
+///
+/// Returns the stream data following the checksum, or `None` if the payload is too short or the
+/// checksum doesn't match.
+#[inline]
+pub fn verify(payload: &[u8]) -> Option<&[u8]> {
+    if payload.len() < LEN {
+        return None;
+    }
+
+    let (expected, data) = payload.split_at(LEN);
+    let expected = u32::from_be_bytes([expected[0], expected[1], expected[2], expected[3]]);
+
+    if checksum(data) == expected {
+        Some(data)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value_test() {
+        assert_eq!(checksum(b""), 0);
+        assert_eq!(checksum(b"123456789"), 0xcbf4_3926);
+
+        // the checksum doesn't depend on how the data is chunked
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn verify_test() {
+        let data = b"hello world";
+        let mut payload = checksum(data).to_be_bytes().to_vec();
+        payload.extend_from_slice(data);
+        assert_eq!(verify(&payload), Some(&data[..]));
+
+        // a checksum without any data is valid
+        assert_eq!(verify(&[0, 0, 0, 0]), Some(&[][..]));
+
+        // a truncated checksum is rejected
+        assert_eq!(verify(&payload[..LEN - 1]), None);
+
+        // any corrupted byte is detected
+        for index in 0..payload.len() {
+            let mut corrupted = payload.clone();
+            corrupted[index] ^= 0x40;
+            assert_eq!(verify(&corrupted), None, "corruption at {}", index);
+        }
+    }
+}
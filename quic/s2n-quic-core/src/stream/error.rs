@@ -66,6 +66,16 @@ pub enum StreamError {
     NonEmptyOutput {
         source: &'static panic::Location<'static>,
     },
+    /// The stream does not have enough unused flow control credits to complete the operation
+    #[non_exhaustive]
+    InsufficientCredits {
+        source: &'static panic::Location<'static>,
+    },
+    /// The deadline of a send operation elapsed before any data could be enqueued
+    #[non_exhaustive]
+    DeadlineExceeded {
+        source: &'static panic::Location<'static>,
+    },
 }
 
 #[cfg(feature = "std")]
@@ -98,6 +108,14 @@ impl fmt::Display for StreamError {
                 f,
                 "The stream was provided a non-empty placeholder buffer for receiving data."
             ),
+            Self::InsufficientCredits { .. } => write!(
+                f,
+                "The stream does not have enough unused flow control credits"
+            ),
+            Self::DeadlineExceeded { .. } => write!(
+                f,
+                "The deadline elapsed before any data could be sent on the stream"
+            ),
         }
     }
 }
@@ -115,6 +133,8 @@ impl StreamError {
             StreamError::NonWritable { source } => source,
             StreamError::SendingBlocked { source } => source,
             StreamError::NonEmptyOutput { source } => source,
+            StreamError::InsufficientCredits { source } => source,
+            StreamError::DeadlineExceeded { source } => source,
         }
     }
 
@@ -181,6 +201,22 @@ impl StreamError {
         let source = panic::Location::caller();
         StreamError::NonEmptyOutput { source }
     }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn insufficient_credits() -> StreamError {
+        let source = panic::Location::caller();
+        StreamError::InsufficientCredits { source }
+    }
+
+    #[track_caller]
+    #[inline]
+    #[doc(hidden)]
+    pub fn deadline_exceeded() -> StreamError {
+        let source = panic::Location::caller();
+        StreamError::DeadlineExceeded { source }
+    }
 }
 
 impl application::error::TryInto for StreamError {
@@ -237,6 +273,8 @@ impl From<StreamError> for std::io::ErrorKind {
             StreamError::NonWritable { .. } => ErrorKind::Other,
             StreamError::SendingBlocked { .. } => ErrorKind::WouldBlock,
             StreamError::NonEmptyOutput { .. } => ErrorKind::InvalidInput,
+            StreamError::InsufficientCredits { .. } => ErrorKind::InvalidInput,
+            StreamError::DeadlineExceeded { .. } => ErrorKind::TimedOut,
         }
     }
 }
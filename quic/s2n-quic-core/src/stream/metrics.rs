@@ -0,0 +1,360 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-stream transfer counters
+//!
+//! Each stream records its own counters in a [`StreamMetrics`] with relaxed atomic increments,
+//! so recording a frame never contends with other streams or with readers of the counters.
+//! When a stream closes, its counters are drained into the connection's
+//! [`StreamMetricsCollector`], which is only ever modified behind the connection lock.
+
+use core::{
+    ops::AddAssign,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The transfer counters of a single stream
+#[derive(Debug, Default)]
+pub struct StreamMetrics {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    retransmitted_bytes: AtomicU64,
+    frames_sent: AtomicU64,
+    frame_bytes_sent: AtomicU64,
+}
+
+impl StreamMetrics {
+    /// Records a STREAM frame carrying `len` bytes of stream data, which took `frame_len` bytes
+    /// to encode
+    ///
+    /// Retransmitted bytes are counted in both `bytes_sent` and `retransmitted_bytes`.
+    #[inline]
+    pub fn on_frame_sent(&self, len: usize, frame_len: usize, is_retransmission: bool) {
+        let len = len as u64;
+        self.frames_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(len, Ordering::Relaxed);
+        self.frame_bytes_sent
+            .fetch_add(frame_len as u64, Ordering::Relaxed);
+        if is_retransmission {
+            self.retransmitted_bytes.fetch_add(len, Ordering::Relaxed);
+        }
+    }
+
+    /// Records `len` bytes of stream data received from the peer
+    #[inline]
+    pub fn on_bytes_received(&self, len: usize) {
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of the counters
+    ///
+    /// The counters are read individually, so a frame recorded concurrently may only be
+    /// reflected in some of them.
+    #[inline]
+    pub fn load(&self) -> StreamMetricsTotals {
+        StreamMetricsTotals {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            retransmitted_bytes: self.retransmitted_bytes.load(Ordering::Relaxed),
+            frames_sent: self.frames_sent.load(Ordering::Relaxed),
+            frame_bytes_sent: self.frame_bytes_sent.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the counters to zero and returns the values they held
+    ///
+    /// Each counter is swapped atomically, so every recorded value is returned by exactly one
+    /// call to `drain`.
+    #[inline]
+    pub fn drain(&self) -> StreamMetricsTotals {
+        StreamMetricsTotals {
+            bytes_sent: self.bytes_sent.swap(0, Ordering::Relaxed),
+            bytes_received: self.bytes_received.swap(0, Ordering::Relaxed),
+            retransmitted_bytes: self.retransmitted_bytes.swap(0, Ordering::Relaxed),
+            frames_sent: self.frames_sent.swap(0, Ordering::Relaxed),
+            frame_bytes_sent: self.frame_bytes_sent.swap(0, Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of stream transfer counters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamMetricsTotals {
+    /// The number of stream data bytes sent, including retransmissions
+    pub bytes_sent: u64,
+    /// The number of stream data bytes received
+    pub bytes_received: u64,
+    /// The number of stream data bytes sent more than once
+    pub retransmitted_bytes: u64,
+    /// The number of STREAM frames sent
+    pub frames_sent: u64,
+    /// The number of bytes of encoded STREAM frames sent, including retransmissions
+    pub frame_bytes_sent: u64,
+}
+
+impl StreamMetricsTotals {
+    /// Returns the number of STREAM frame bytes sent per byte of stream data
+    #[inline]
+    pub fn write_amplification(&self) -> WriteAmplification {
+        WriteAmplification {
+            payload_bytes: self.bytes_sent - self.retransmitted_bytes,
+            wire_bytes: self.frame_bytes_sent,
+        }
+    }
+}
+
+impl AddAssign for StreamMetricsTotals {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.bytes_sent += rhs.bytes_sent;
+        self.bytes_received += rhs.bytes_received;
+        self.retransmitted_bytes += rhs.retransmitted_bytes;
+        self.frames_sent += rhs.frames_sent;
+        self.frame_bytes_sent += rhs.frame_bytes_sent;
+    }
+}
+
+/// The number of bytes transmitted for the application data which was sent
+///
+/// Every byte of application data costs more than one byte to transmit: it is framed, put in
+/// packets with headers and authentication tags, and sent again if it was lost. The ratio of
+/// `wire_bytes` to `payload_bytes` is close to 1.0 on a lossless path with full packets, and
+/// increases with the rate of retransmissions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteAmplification {
+    /// The number of bytes of application data, counting each byte once
+    pub payload_bytes: u64,
+    /// The number of bytes transmitted to send the application data
+    pub wire_bytes: u64,
+}
+
+impl WriteAmplification {
+    /// Returns `wire_bytes / payload_bytes`, or `None` if no application data was sent
+    #[inline]
+    pub fn ratio(&self) -> Option<f64> {
+        if self.payload_bytes == 0 {
+            return None;
+        }
+
+        Some(self.wire_bytes as f64 / self.payload_bytes as f64)
+    }
+}
+
+/// The connection-level totals of all closed streams
+#[derive(Debug, Default)]
+pub struct StreamMetricsCollector {
+    totals: StreamMetricsTotals,
+}
+
+impl StreamMetricsCollector {
+    /// Drains the counters of a closed stream into the connection totals
+    #[inline]
+    pub fn commit(&mut self, metrics: &StreamMetrics) {
+        self.totals += metrics.drain();
+    }
+
+    /// Returns the totals of all of the committed streams
+    #[inline]
+    pub fn totals(&self) -> StreamMetricsTotals {
+        self.totals
+    }
+
+    /// Resets the totals to zero and returns the values they held
+    ///
+    /// Streams are only committed behind the connection lock, so every committed stream is
+    /// counted either before or after the reset.
+    #[inline]
+    pub fn reset(&mut self) -> StreamMetricsTotals {
+        core::mem::take(&mut self.totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    const FRAMES: usize = 10_000;
+
+    #[test]
+    fn commit_test() {
+        let metrics = StreamMetrics::default();
+        metrics.on_frame_sent(100, 104, false);
+        metrics.on_frame_sent(40, 44, true);
+        metrics.on_frame_sent(0, 4, false);
+        metrics.on_bytes_received(25);
+
+        let expected = StreamMetricsTotals {
+            bytes_sent: 140,
+            bytes_received: 25,
+            retransmitted_bytes: 40,
+            frames_sent: 3,
+            frame_bytes_sent: 152,
+        };
+        assert_eq!(metrics.load(), expected);
+
+        let mut collector = StreamMetricsCollector::default();
+        collector.commit(&metrics);
+        assert_eq!(collector.totals(), expected);
+        assert_eq!(metrics.load(), StreamMetricsTotals::default());
+
+        // committing a drained stream doesn't count anything twice
+        collector.commit(&metrics);
+        assert_eq!(collector.totals(), expected);
+
+        assert_eq!(collector.reset(), expected);
+        assert_eq!(collector.totals(), StreamMetricsTotals::default());
+
+        // streams committed after the reset are counted in the new totals
+        metrics.on_frame_sent(10, 14, false);
+        collector.commit(&metrics);
+        assert_eq!(collector.totals().bytes_sent, 10);
+    }
+
+    /// Sends and receives on a stream from separate threads while the connection commits it
+    #[test]
+    fn concurrent_commit_test() {
+        let metrics = Arc::new(StreamMetrics::default());
+        let collector = Arc::new(Mutex::new(StreamMetricsCollector::default()));
+
+        let sender = {
+            let metrics = metrics.clone();
+            thread::spawn(move || {
+                for i in 0..FRAMES {
+                    metrics.on_frame_sent(3, 5, i % 4 == 0);
+                }
+            })
+        };
+
+        let receiver = {
+            let metrics = metrics.clone();
+            thread::spawn(move || {
+                for _ in 0..FRAMES {
+                    metrics.on_bytes_received(5);
+                }
+            })
+        };
+
+        let committer = {
+            let metrics = metrics.clone();
+            let collector = collector.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    collector.lock().unwrap().commit(&metrics);
+                    thread::yield_now();
+                }
+            })
+        };
+
+        sender.join().unwrap();
+        receiver.join().unwrap();
+        committer.join().unwrap();
+
+        // commit whatever was recorded after the last concurrent commit
+        let mut collector = collector.lock().unwrap();
+        collector.commit(&metrics);
+
+        let frames = FRAMES as u64;
+        assert_eq!(
+            collector.totals(),
+            StreamMetricsTotals {
+                bytes_sent: frames * 3,
+                bytes_received: frames * 5,
+                retransmitted_bytes: frames / 4 * 3,
+                frames_sent: frames,
+                frame_bytes_sent: frames * 5,
+            }
+        );
+    }
+
+    /// Commits many streams of a connection from separate threads
+    #[test]
+    fn concurrent_streams_test() {
+        let collector = Arc::new(Mutex::new(StreamMetricsCollector::default()));
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let collector = collector.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let metrics = StreamMetrics::default();
+                        metrics.on_frame_sent(10, 12, false);
+                        metrics.on_bytes_received(20);
+                        collector.lock().unwrap().commit(&metrics);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let totals = collector.lock().unwrap().totals();
+        assert_eq!(totals.frames_sent, 800);
+        assert_eq!(totals.bytes_sent, 8000);
+        assert_eq!(totals.bytes_received, 16000);
+        assert_eq!(totals.retransmitted_bytes, 0);
+    }
+
+    /// Resets the connection totals while streams are committed from separate threads
+    #[test]
+    fn concurrent_reset_test() {
+        let collector = Arc::new(Mutex::new(StreamMetricsCollector::default()));
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let collector = collector.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let metrics = StreamMetrics::default();
+                        metrics.on_frame_sent(10, 12, false);
+                        collector.lock().unwrap().commit(&metrics);
+                    }
+                })
+            })
+            .collect();
+
+        let mut windows = StreamMetricsTotals::default();
+        for _ in 0..100 {
+            windows += collector.lock().unwrap().reset();
+            thread::yield_now();
+        }
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // every stream is counted in exactly one of the windows
+        windows += collector.lock().unwrap().reset();
+        assert_eq!(windows.frames_sent, 800);
+        assert_eq!(windows.bytes_sent, 8000);
+        assert_eq!(windows.frame_bytes_sent, 9600);
+    }
+
+    #[test]
+    fn write_amplification_test() {
+        assert_eq!(WriteAmplification::default().ratio(), None);
+
+        let metrics = StreamMetrics::default();
+        for _ in 0..10 {
+            metrics.on_frame_sent(1000, 1010, false);
+        }
+        let amplification = metrics.load().write_amplification();
+        assert_eq!(amplification.payload_bytes, 10_000);
+        assert_eq!(amplification.wire_bytes, 10_100);
+        assert_eq!(amplification.ratio(), Some(1.01));
+
+        // retransmissions don't add any payload
+        for _ in 0..5 {
+            metrics.on_frame_sent(1000, 1010, true);
+        }
+        let amplification = metrics.load().write_amplification();
+        assert_eq!(amplification.payload_bytes, 10_000);
+        assert_eq!(amplification.wire_bytes, 15_150);
+        assert_eq!(amplification.ratio(), Some(1.515));
+    }
+}
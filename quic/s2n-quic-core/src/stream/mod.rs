@@ -1,16 +1,23 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod checksum;
 mod error;
 mod id;
 pub mod iter;
 pub mod limits;
+pub mod metrics;
 pub mod ops;
+mod priority;
+#[cfg(feature = "alloc")]
+pub mod relay;
 mod type_;
 
 pub use error::*;
 pub use id::*;
 pub use limits::Limits;
+pub use metrics::{StreamMetrics, StreamMetricsCollector, StreamMetricsTotals, WriteAmplification};
+pub use priority::StreamPriority;
 pub use type_::*;
 
 #[cfg(any(test, feature = "testing"))]
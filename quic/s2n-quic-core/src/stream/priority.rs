@@ -0,0 +1,20 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+/// The priority of a stream relative to the other streams of a connection
+///
+/// Priorities are ordered from `Low` to `Critical`. Congestion controllers may reserve part of
+/// the sending rate for streams at or above a given priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StreamPriority {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Default for StreamPriority {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
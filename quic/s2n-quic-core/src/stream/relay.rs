@@ -0,0 +1,415 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Forwards STREAM frames from one connection to another
+//!
+//! A relay (or MASQUE proxy) terminates a connection on each side and passes stream data between
+//! them. Since each connection has its own stream ID space, a stream accepted from the upstream
+//! peer is generally known by a different ID on the downstream connection. The
+//! [`RelayForwarder`] keeps the mapping between the two and re-encodes received STREAM frames
+//! with the translated ID. The stream data is written straight from the received packet buffer
+//! into the outgoing one, without being copied into an intermediate buffer first.
+//!
+//! Forwarded frames always carry an explicit length, so several frames, possibly from different
+//! upstream connections, can be coalesced into the same outgoing packet payload.
+
+use crate::{
+    frame::{FrameMut, Stream},
+    stream::StreamId,
+};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use s2n_codec::{DecoderBufferMut, DecoderError, Encoder, EncoderValue};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The buffer did not start with a valid frame
+    Decode(DecoderError),
+    /// The frame was valid but not a STREAM frame
+    NotStreamFrame,
+    /// No route was registered for the stream
+    UnknownStream { stream_id: StreamId },
+    /// The upstream and downstream streams have different types
+    StreamTypeMismatch {
+        upstream: StreamId,
+        downstream: StreamId,
+    },
+    /// The translated frame does not fit in the remaining capacity of the encoder
+    InsufficientCapacity { required: usize, available: usize },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Decode(error) => write!(f, "could not decode frame: {}", error),
+            Self::NotStreamFrame => write!(f, "only STREAM frames can be forwarded"),
+            Self::UnknownStream { stream_id } => {
+                write!(f, "no route for stream {}", u64::from(*stream_id))
+            }
+            Self::StreamTypeMismatch {
+                upstream,
+                downstream,
+            } => write!(
+                f,
+                "cannot route {:?} stream {} to {:?} stream {}",
+                upstream.stream_type(),
+                u64::from(*upstream),
+                downstream.stream_type(),
+                u64::from(*downstream)
+            ),
+            Self::InsufficientCapacity {
+                required,
+                available,
+            } => write!(
+                f,
+                "frame requires {} bytes but only {} are available",
+                required, available
+            ),
+        }
+    }
+}
+
+impl From<DecoderError> for Error {
+    fn from(error: DecoderError) -> Self {
+        Self::Decode(error)
+    }
+}
+
+/// Translates the stream IDs of STREAM frames received on one connection into the IDs used on
+/// another connection
+#[derive(Clone, Debug, Default)]
+pub struct RelayForwarder {
+    routes: BTreeMap<StreamId, StreamId>,
+}
+
+impl RelayForwarder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forwards data received on the `upstream` stream to the `downstream` stream
+    ///
+    /// The streams must have the same type, since a unidirectional stream cannot carry the
+    /// response half of a bidirectional one. The initiators may differ. Returns the previous
+    /// downstream stream of the route, if any.
+    pub fn insert_route(
+        &mut self,
+        upstream: StreamId,
+        downstream: StreamId,
+    ) -> Result<Option<StreamId>, Error> {
+        if upstream.stream_type() != downstream.stream_type() {
+            return Err(Error::StreamTypeMismatch {
+                upstream,
+                downstream,
+            });
+        }
+
+        Ok(self.routes.insert(upstream, downstream))
+    }
+
+    /// Stops forwarding the `upstream` stream and returns the stream it was forwarded to
+    #[inline]
+    pub fn remove_route(&mut self, upstream: StreamId) -> Option<StreamId> {
+        self.routes.remove(&upstream)
+    }
+
+    /// Returns the stream the `upstream` stream is forwarded to
+    #[inline]
+    pub fn route(&self, upstream: StreamId) -> Option<StreamId> {
+        self.routes.get(&upstream).copied()
+    }
+
+    /// Returns the number of routed streams
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// Returns `true` if no streams are routed
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Replaces the stream ID of a decoded frame with its downstream ID
+    ///
+    /// The frame is marked as length-prefixed, so it can be followed by other frames.
+    pub fn translate<Data>(&self, frame: Stream<Data>) -> Result<Stream<Data>, Error> {
+        let upstream = StreamId::from_varint(frame.stream_id);
+        let downstream = self.route(upstream).ok_or(Error::UnknownStream {
+            stream_id: upstream,
+        })?;
+
+        Ok(Stream {
+            stream_id: downstream.as_varint(),
+            offset: frame.offset,
+            is_last_frame: false,
+            is_fin: frame.is_fin,
+            data: frame.data,
+        })
+    }
+
+    /// Decodes the STREAM frame at the start of `buffer` and encodes it with the translated
+    /// stream ID into `encoder`
+    ///
+    /// Returns the remainder of `buffer` following the frame. Nothing is written to the encoder
+    /// if an error is returned.
+    pub fn forward<'a, E: Encoder>(
+        &self,
+        buffer: DecoderBufferMut<'a>,
+        encoder: &mut E,
+    ) -> Result<DecoderBufferMut<'a>, Error> {
+        let (frame, remaining) = buffer.decode::<FrameMut>()?;
+
+        let frame = match frame {
+            FrameMut::Stream(frame) => frame,
+            _ => return Err(Error::NotStreamFrame),
+        };
+
+        let frame = self.translate(frame)?;
+
+        let required = frame.encoding_size();
+        let available = encoder.remaining_capacity();
+        if required > available {
+            return Err(Error::InsufficientCapacity {
+                required,
+                available,
+            });
+        }
+
+        encoder.encode(&frame);
+
+        Ok(remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint, stream::StreamType, varint::VarInt};
+    use s2n_codec::EncoderBuffer;
+
+    fn stream(initiator: endpoint::Type, stream_type: StreamType, n: u64) -> StreamId {
+        StreamId::nth(initiator, stream_type, n).unwrap()
+    }
+
+    /// Encodes `frames` into a single packet payload
+    fn encode(frames: &[Stream<&[u8]>], payload: &mut [u8]) -> usize {
+        let mut encoder = EncoderBuffer::new(payload);
+        for frame in frames {
+            encoder.encode(frame);
+        }
+        encoder.len()
+    }
+
+    /// Forwards every frame of `payload` and returns the length of the outgoing payload
+    fn forward_all(forwarder: &RelayForwarder, payload: &mut [u8], out: &mut [u8]) -> usize {
+        let mut encoder = EncoderBuffer::new(out);
+        let mut buffer = DecoderBufferMut::new(payload);
+        while !buffer.is_empty() {
+            buffer = forwarder.forward(buffer, &mut encoder).unwrap();
+        }
+        encoder.len()
+    }
+
+    /// Decodes the STREAM frames of `payload`, returning the data received on each stream
+    fn receive(payload: &mut [u8]) -> BTreeMap<StreamId, (Vec<u8>, bool)> {
+        let mut streams = BTreeMap::<StreamId, (Vec<u8>, bool)>::new();
+        let mut buffer = DecoderBufferMut::new(payload);
+
+        while !buffer.is_empty() {
+            let (frame, remaining) = buffer.decode::<FrameMut>().unwrap();
+            buffer = remaining;
+
+            let frame = match frame {
+                FrameMut::Stream(frame) => frame,
+                frame => panic!("unexpected frame {:?}", frame),
+            };
+
+            let (data, is_fin) = streams
+                .entry(StreamId::from_varint(frame.stream_id))
+                .or_default();
+            assert_eq!(data.len() as u64, frame.offset.as_u64());
+            data.extend_from_slice(frame.data.as_less_safe_slice());
+            *is_fin |= frame.is_fin;
+        }
+
+        streams
+    }
+
+    #[test]
+    fn two_hop_test() {
+        use endpoint::Type::{Client, Server};
+        use StreamType::{Bidirectional, Unidirectional};
+
+        // the client opens streams to the first relay
+        let client_bidi = stream(Client, Bidirectional, 0);
+        let client_uni = stream(Client, Unidirectional, 3);
+
+        // the first relay acts as a client towards the second relay, which already has another
+        // stream open to it
+        let mut first = RelayForwarder::new();
+        let first_bidi = stream(Client, Bidirectional, 1);
+        let first_uni = stream(Client, Unidirectional, 0);
+        first.insert_route(client_bidi, first_bidi).unwrap();
+        first.insert_route(client_uni, first_uni).unwrap();
+
+        // the second relay accepts the streams from the first one and forwards them to the
+        // origin, which it has opened a connection to as a server
+        let mut second = RelayForwarder::new();
+        let origin_bidi = stream(Server, Bidirectional, 7);
+        let origin_uni = stream(Server, Unidirectional, 2);
+        second.insert_route(first_bidi, origin_bidi).unwrap();
+        second.insert_route(first_uni, origin_uni).unwrap();
+        assert_eq!(second.len(), 2);
+
+        let mut bidi_data = [0; 300];
+        for (index, byte) in bidi_data.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+        let uni_data = b"hello from a unidirectional stream";
+
+        let frames = [
+            Stream {
+                stream_id: client_bidi.as_varint(),
+                offset: VarInt::from_u8(0),
+                is_last_frame: false,
+                is_fin: false,
+                data: &bidi_data[..100],
+            },
+            Stream {
+                stream_id: client_uni.as_varint(),
+                offset: VarInt::from_u8(0),
+                is_last_frame: false,
+                is_fin: true,
+                data: &uni_data[..],
+            },
+            // the last frame in the upstream packet has no length prefix
+            Stream {
+                stream_id: client_bidi.as_varint(),
+                offset: VarInt::from_u8(100),
+                is_last_frame: true,
+                is_fin: true,
+                data: &bidi_data[100..],
+            },
+        ];
+
+        let mut client_payload = [0; 1200];
+        let len = encode(&frames, &mut client_payload);
+
+        let mut first_payload = [0; 1200];
+        let len = forward_all(&first, &mut client_payload[..len], &mut first_payload);
+
+        let mut second_payload = [0; 1200];
+        let len = forward_all(&second, &mut first_payload[..len], &mut second_payload);
+
+        let streams = receive(&mut second_payload[..len]);
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[&origin_bidi], (bidi_data.to_vec(), true));
+        assert_eq!(streams[&origin_uni], (uni_data.to_vec(), true));
+    }
+
+    #[test]
+    fn coalesce_test() {
+        let upstream = stream(endpoint::Type::Client, StreamType::Bidirectional, 0);
+        let downstream = stream(endpoint::Type::Server, StreamType::Bidirectional, 0);
+        let mut forwarder = RelayForwarder::new();
+        forwarder.insert_route(upstream, downstream).unwrap();
+
+        // two packets which each end with a frame without a length prefix
+        let mut packets = [[0u8; 32]; 2];
+        let mut lens = [0; 2];
+        for (index, packet) in packets.iter_mut().enumerate() {
+            let data = [index as u8; 4];
+            let frame = Stream {
+                stream_id: upstream.as_varint(),
+                offset: VarInt::from_u8(index as u8 * 4),
+                is_last_frame: true,
+                is_fin: index == 1,
+                data: &data[..],
+            };
+            lens[index] = encode(&[frame], packet);
+        }
+
+        let mut out = [0; 64];
+        let mut encoder = EncoderBuffer::new(&mut out);
+        for (packet, len) in packets.iter_mut().zip(lens) {
+            let remaining = forwarder
+                .forward(DecoderBufferMut::new(&mut packet[..len]), &mut encoder)
+                .unwrap();
+            assert!(remaining.is_empty());
+        }
+        let len = encoder.len();
+
+        let streams = receive(&mut out[..len]);
+        assert_eq!(streams[&downstream], (vec![0, 0, 0, 0, 1, 1, 1, 1], true));
+    }
+
+    #[test]
+    fn error_test() {
+        let upstream = stream(endpoint::Type::Client, StreamType::Bidirectional, 0);
+        let unidirectional = stream(endpoint::Type::Server, StreamType::Unidirectional, 0);
+        let mut forwarder = RelayForwarder::new();
+
+        assert!(matches!(
+            forwarder.insert_route(upstream, unidirectional),
+            Err(Error::StreamTypeMismatch { .. })
+        ));
+        assert!(forwarder.is_empty());
+
+        let frame = Stream {
+            stream_id: upstream.as_varint(),
+            offset: VarInt::from_u8(0),
+            is_last_frame: true,
+            is_fin: false,
+            data: &[1u8; 100][..],
+        };
+        let mut payload = [0; 128];
+        let len = encode(&[frame], &mut payload);
+
+        let mut out = [0; 128];
+        let mut encoder = EncoderBuffer::new(&mut out);
+        assert!(matches!(
+            forwarder.forward(DecoderBufferMut::new(&mut payload[..len]), &mut encoder),
+            Err(Error::UnknownStream { stream_id }) if stream_id == upstream
+        ));
+
+        let downstream = stream(endpoint::Type::Server, StreamType::Bidirectional, 0);
+        assert_eq!(forwarder.insert_route(upstream, downstream).unwrap(), None);
+
+        // the translated frame gains a length prefix, so it no longer fits
+        let mut small = [0; 100];
+        let mut small_encoder = EncoderBuffer::new(&mut small);
+        assert!(matches!(
+            forwarder.forward(
+                DecoderBufferMut::new(&mut payload[..len]),
+                &mut small_encoder
+            ),
+            Err(Error::InsufficientCapacity { available: 100, .. })
+        ));
+        assert_eq!(small_encoder.len(), 0);
+
+        // PADDING frames are all zeros
+        let mut padding = [0; 4];
+        assert!(matches!(
+            forwarder.forward(DecoderBufferMut::new(&mut padding), &mut encoder),
+            Err(Error::NotStreamFrame)
+        ));
+
+        // a STREAM frame with a length of 5 but only a single byte of data
+        let mut truncated = [0x0a, 0x00, 0x05, 0x01];
+        assert!(matches!(
+            forwarder.forward(DecoderBufferMut::new(&mut truncated), &mut encoder),
+            Err(Error::Decode(_))
+        ));
+        assert_eq!(encoder.len(), 0);
+
+        assert_eq!(forwarder.remove_route(upstream), Some(downstream));
+        assert_eq!(forwarder.route(upstream), None);
+    }
+}
@@ -6,9 +6,11 @@ use core::ops::AddAssign;
 
 pub mod constraint;
 pub mod mode;
+pub mod shaping;
 
 pub use constraint::Constraint;
 pub use mode::Mode;
+pub use shaping::TrafficShapingConfig;
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Outcome {
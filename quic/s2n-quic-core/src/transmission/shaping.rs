@@ -0,0 +1,104 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Pads datagrams to fixed sizes to hide the size of the data being transmitted
+///
+/// Traffic shaping is disabled by default. When enabled, PADDING frames are added to each
+/// packet so the datagram carrying it reaches the configured size, which makes it more
+/// difficult for an on-path observer to infer application behavior from datagram sizes.
+/// Datagrams are never padded beyond the current path MTU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficShapingConfig {
+    /// Pads every datagram to the path MTU
+    pub pad_to_mtu: bool,
+    /// The minimum size of each datagram
+    pub min_packet_size: u16,
+    /// Rounds the size of each datagram up to the next multiple of this value
+    ///
+    /// A value of `0` disables quantization.
+    pub quantize_to: u16,
+}
+
+impl TrafficShapingConfig {
+    /// Returns `true` if the configuration requires any datagrams to be padded
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.pad_to_mtu || self.min_packet_size > 0 || self.quantize_to > 0
+    }
+
+    /// Returns the length that a datagram of `len` bytes should be padded to
+    ///
+    /// The returned value is never smaller than `len` nor larger than `capacity`, unless `len`
+    /// already exceeds `capacity`.
+    #[inline]
+    pub fn padded_len(&self, len: usize, capacity: usize) -> usize {
+        let target = if self.pad_to_mtu {
+            capacity
+        } else {
+            let target = len.max(self.min_packet_size as usize);
+
+            match self.quantize_to as usize {
+                0 => target,
+                quantum => (target + quantum - 1) / quantum * quantum,
+            }
+        };
+
+        target.min(capacity).max(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_test() {
+        let config = TrafficShapingConfig::default();
+        assert!(!config.is_enabled());
+        assert_eq!(config.padded_len(100, 1200), 100);
+    }
+
+    #[test]
+    fn pad_to_mtu_test() {
+        let config = TrafficShapingConfig {
+            pad_to_mtu: true,
+            ..Default::default()
+        };
+        assert!(config.is_enabled());
+        assert_eq!(config.padded_len(100, 1200), 1200);
+        assert_eq!(config.padded_len(1200, 1200), 1200);
+        assert_eq!(config.padded_len(100, 1472), 1472);
+    }
+
+    #[test]
+    fn min_packet_size_test() {
+        let config = TrafficShapingConfig {
+            min_packet_size: 500,
+            ..Default::default()
+        };
+        assert_eq!(config.padded_len(100, 1200), 500);
+        assert_eq!(config.padded_len(700, 1200), 700);
+        // the datagram is never padded beyond its capacity
+        assert_eq!(config.padded_len(100, 300), 300);
+    }
+
+    #[test]
+    fn quantize_test() {
+        let config = TrafficShapingConfig {
+            quantize_to: 256,
+            ..Default::default()
+        };
+        assert_eq!(config.padded_len(1, 1200), 256);
+        assert_eq!(config.padded_len(256, 1200), 256);
+        assert_eq!(config.padded_len(257, 1200), 512);
+        assert_eq!(config.padded_len(1100, 1200), 1200);
+        assert_eq!(config.padded_len(1300, 1200), 1300);
+
+        let config = TrafficShapingConfig {
+            min_packet_size: 300,
+            quantize_to: 256,
+            ..Default::default()
+        };
+        assert_eq!(config.padded_len(1, 1200), 512);
+    }
+}
@@ -4,6 +4,7 @@
 use crate::{
     ack, connection, endpoint, event,
     event::IntoEvent,
+    frame,
     inet::{SocketAddressV4, SocketAddressV6, Unspecified},
     stateless_reset,
     stream::{StreamId, StreamType},
@@ -214,6 +215,12 @@ where
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ValidationError(&'static str);
 
+impl ValidationError {
+    pub(crate) const fn new(reason: &'static str) -> Self {
+        Self(reason)
+    }
+}
+
 const MAX_ENCODABLE_VALUE: ValidationError =
     ValidationError("provided value exceeds maximum encodable value");
 
@@ -1082,6 +1089,193 @@ impl From<connection::id::LocalId> for InitialSourceConnectionId {
 connection_id_parameter!(RetrySourceConnectionId, LocalId, 0x10);
 optional_transport_parameter!(RetrySourceConnectionId);
 
+// version_information (0x11): Used by endpoints to authenticate the version negotiation
+//    process and to negotiate a compatible version during the handshake. The value
+//    contains the version chosen for the connection followed by the versions the
+//    endpoint supports.
+//
+// See https://www.rfc-editor.org/rfc/rfc9368#section-3
+//
+// Version Information {
+//   Chosen Version (32),
+//   Available Versions (32) ...,
+// }
+
+optional_transport_parameter!(VersionInformation);
+
+/// The maximum number of Available Versions stored from a version_information parameter
+///
+/// Any additional versions sent by the peer are ignored.
+pub const MAX_AVAILABLE_VERSIONS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionInformation {
+    chosen_version: u32,
+    available_versions: [u32; MAX_AVAILABLE_VERSIONS],
+    available_versions_len: u8,
+}
+
+impl VersionInformation {
+    /// Creates a new `VersionInformation` parameter
+    ///
+    /// Returns `None` if any of the versions are 0 or too many available versions are provided.
+    pub fn new(chosen_version: u32, available_versions: &[u32]) -> Option<Self> {
+        if available_versions.len() > MAX_AVAILABLE_VERSIONS {
+            return None;
+        }
+
+        let mut value = Self {
+            chosen_version,
+            available_versions: [0; MAX_AVAILABLE_VERSIONS],
+            available_versions_len: available_versions.len() as u8,
+        };
+        value.available_versions[..available_versions.len()].copy_from_slice(available_versions);
+
+        value.validate().ok()
+    }
+
+    /// Returns the version the endpoint chose for the connection
+    #[inline]
+    pub fn chosen_version(&self) -> u32 {
+        self.chosen_version
+    }
+
+    /// Returns the versions supported by the endpoint
+    #[inline]
+    pub fn available_versions(&self) -> &[u32] {
+        &self.available_versions[..self.available_versions_len as usize]
+    }
+}
+
+impl TransportParameter for VersionInformation {
+    type CodecValue = Self;
+
+    const ID: TransportParameterId = TransportParameterId::from_u8(0x11);
+
+    fn from_codec_value(value: Self) -> Self {
+        value
+    }
+
+    fn try_into_codec_value(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn default_value() -> Self {
+        unimplemented!(
+            "VersionInformation is an optional transport parameter, so the default is None"
+        )
+    }
+}
+
+impl TransportParameterValidator for VersionInformation {
+    fn validate(self) -> Result<Self, DecoderError> {
+        // Version 0 is reserved for Version Negotiation packets and can't be negotiated
+        // See https://www.rfc-editor.org/rfc/rfc9368#section-3
+        decoder_invariant!(self.chosen_version != 0, "chosen version cannot be 0");
+        decoder_invariant!(
+            !self.available_versions().contains(&0),
+            "available versions cannot contain 0"
+        );
+        Ok(self)
+    }
+}
+
+decoder_value!(
+    impl<'a> VersionInformation {
+        fn decode(buffer: Buffer) -> Result<Self> {
+            let (chosen_version, mut buffer) = buffer.decode::<u32>()?;
+
+            let mut value = Self {
+                chosen_version,
+                available_versions: [0; MAX_AVAILABLE_VERSIONS],
+                available_versions_len: 0,
+            };
+
+            while !buffer.is_empty() {
+                let (version, remaining) = buffer.decode::<u32>()?;
+                buffer = remaining;
+
+                let len = value.available_versions_len as usize;
+                if len < MAX_AVAILABLE_VERSIONS {
+                    value.available_versions[len] = version;
+                    value.available_versions_len += 1;
+                }
+            }
+
+            Ok((value, buffer))
+        }
+    }
+);
+
+impl EncoderValue for VersionInformation {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(&self.chosen_version);
+        for version in self.available_versions() {
+            buffer.encode(version);
+        }
+    }
+}
+
+/// Advertises support for checksums of STREAM frame payloads
+///
+/// The parameter is an extension with an ID from the private range and has an empty value. The
+/// checksums are only added once both endpoints sent the parameter.
+///
+/// See [`crate::stream::checksum`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StreamDataChecksum {
+    Enabled,
+    Disabled,
+}
+
+impl Default for StreamDataChecksum {
+    fn default() -> Self {
+        StreamDataChecksum::Disabled
+    }
+}
+
+impl StreamDataChecksum {
+    #[inline]
+    pub fn is_enabled(self) -> bool {
+        matches!(self, StreamDataChecksum::Enabled)
+    }
+}
+
+impl From<bool> for StreamDataChecksum {
+    #[inline]
+    fn from(enabled: bool) -> Self {
+        if enabled {
+            StreamDataChecksum::Enabled
+        } else {
+            StreamDataChecksum::Disabled
+        }
+    }
+}
+
+impl TransportParameter for StreamDataChecksum {
+    type CodecValue = ();
+
+    const ID: TransportParameterId = TransportParameterId::from_u32(0x7363_7263);
+
+    fn from_codec_value(_value: ()) -> Self {
+        StreamDataChecksum::Enabled
+    }
+
+    fn try_into_codec_value(&self) -> Option<&()> {
+        if let StreamDataChecksum::Enabled = self {
+            Some(&())
+        } else {
+            None
+        }
+    }
+
+    fn default_value() -> Self {
+        StreamDataChecksum::Disabled
+    }
+}
+
+impl TransportParameterValidator for StreamDataChecksum {}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
 //# If present, transport parameters that set initial per-stream flow
 //# control limits (initial_max_stream_data_bidi_local,
@@ -1187,7 +1381,16 @@ impl<
 
     // Calculates the maximum datagram payload size
     pub fn datagram_limits(&self) -> DatagramLimits {
-        let max_datagram_payload = self.max_datagram_frame_size.as_u64();
+        // The max_datagram_frame_size includes the frame type and length of the DATAGRAM frame.
+        // See https://www.rfc-editor.org/rfc/rfc9221#section-3
+        let max_datagram_frame_size = self.max_datagram_frame_size.as_u64();
+        let max_datagram_payload = max_datagram_frame_size
+            .saturating_sub(frame::datagram::DATAGRAM_TAG.encoding_size() as u64)
+            .saturating_sub(
+                VarInt::new(max_datagram_frame_size)
+                    .unwrap_or(VarInt::MAX)
+                    .encoding_size() as u64,
+            );
 
         // We factor in the received max_udp_payload_size since technically it
         // can be smaller than the received max_datagram_frame_size.
@@ -1438,6 +1641,8 @@ impl_transport_parameters!(
         preferred_address: PreferredAddress,
         initial_source_connection_id: Option<InitialSourceConnectionId>,
         retry_source_connection_id: RetrySourceConnectionId,
+        version_information: Option<VersionInformation>,
+        stream_data_checksum: StreamDataChecksum,
     }
 );
 
@@ -1484,6 +1689,7 @@ impl<
         load!(max_ack_delay, max_ack_delay);
         load!(max_active_connection_ids, active_connection_id_limit);
         load!(max_datagram_frame_size, max_datagram_frame_size);
+        self.stream_data_checksum = limits.stream_data_checksum.into();
     }
 }
 
@@ -1553,6 +1759,8 @@ mod snapshot_tests {
             }),
             initial_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
             retry_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
+            version_information: VersionInformation::new(1, &[1]),
+            stream_data_checksum: StreamDataChecksum::Disabled,
         }
     }
 
@@ -1590,6 +1798,8 @@ mod snapshot_tests {
             preferred_address: Default::default(),
             initial_source_connection_id: Some([1, 2, 3, 4][..].try_into().unwrap()),
             retry_source_connection_id: Default::default(),
+            version_information: VersionInformation::new(1, &[1]),
+            stream_data_checksum: StreamDataChecksum::Disabled,
         }
     }
 
@@ -1632,4 +1842,90 @@ mod snapshot_tests {
         assert_eq!(value, decoded_params);
         assert_eq!(0, remaining.len());
     }
+
+    #[test]
+    fn datagram_limits_test() {
+        let mut value = server_transport_parameters();
+
+        // the frame type and length are not part of the payload
+        value.max_datagram_frame_size = MaxDatagramFrameSize::new(1203u16).unwrap();
+        assert_eq!(value.datagram_limits().max_datagram_payload, 1200);
+        value.max_datagram_frame_size = MaxDatagramFrameSize::new(60u16).unwrap();
+        assert_eq!(value.datagram_limits().max_datagram_payload, 58);
+
+        // datagrams are disabled
+        value.max_datagram_frame_size = MaxDatagramFrameSize::new(0u16).unwrap();
+        assert_eq!(value.datagram_limits().max_datagram_payload, 0);
+
+        // the payload can't be larger than the max_udp_payload_size
+        value.max_datagram_frame_size = MaxDatagramFrameSize::new(u16::MAX).unwrap();
+        assert_eq!(value.datagram_limits().max_datagram_payload, 1500);
+    }
+
+    #[test]
+    fn version_information_test() {
+        use crate::crypto::{QUIC_VERSION_1, QUIC_VERSION_2};
+
+        let value =
+            VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_1, QUIC_VERSION_2]).unwrap();
+        assert_eq!(value.chosen_version(), QUIC_VERSION_1);
+        assert_eq!(
+            value.available_versions(),
+            &[QUIC_VERSION_1, QUIC_VERSION_2]
+        );
+        assert_codec_round_trip_value!(VersionInformation, value);
+
+        // version 0 can't be negotiated
+        assert!(VersionInformation::new(0, &[1]).is_none());
+        assert!(VersionInformation::new(1, &[0]).is_none());
+
+        assert!(VersionInformation::new(1, &[1; MAX_AVAILABLE_VERSIONS]).is_some());
+        assert!(VersionInformation::new(1, &[1; MAX_AVAILABLE_VERSIONS + 1]).is_none());
+    }
+
+    #[test]
+    fn version_information_decode_test() {
+        let versions: Vec<u8> = (1..=(MAX_AVAILABLE_VERSIONS as u32 + 2))
+            .flat_map(|version| version.to_be_bytes())
+            .collect();
+
+        // additional available versions are ignored
+        let (value, remaining) = DecoderBuffer::new(&versions)
+            .decode::<VersionInformation>()
+            .unwrap();
+        assert!(remaining.is_empty());
+        assert_eq!(value.chosen_version(), 1);
+        assert_eq!(
+            value.available_versions(),
+            &(2..=(MAX_AVAILABLE_VERSIONS as u32 + 1)).collect::<Vec<_>>()[..]
+        );
+
+        // the value must be a sequence of 32-bit versions
+        assert!(DecoderBuffer::new(&versions[..6])
+            .decode::<VersionInformation>()
+            .is_err());
+    }
+
+    #[test]
+    fn stream_data_checksum_test() {
+        let mut value = client_transport_parameters();
+        value.stream_data_checksum = StreamDataChecksum::Enabled;
+        let enabled = assert_codec_round_trip_value!(ClientTransportParameters, value);
+
+        value.stream_data_checksum = StreamDataChecksum::Disabled;
+        let disabled = assert_codec_round_trip_value!(ClientTransportParameters, value);
+
+        // the parameter has an empty value and is only sent when enabled
+        let id = StreamDataChecksum::ID;
+        assert_eq!(
+            enabled.len(),
+            disabled.len() + id.encoding_size() + VarInt::from_u8(0).encoding_size()
+        );
+
+        let limits = crate::connection::limits::Limits::new();
+        value.load_limits(&limits.with_stream_data_checksum(true).unwrap());
+        assert_eq!(value.stream_data_checksum, StreamDataChecksum::Enabled);
+        value.load_limits(&limits);
+        assert_eq!(value.stream_data_checksum, StreamDataChecksum::Disabled);
+    }
 }
@@ -1,10 +1,10 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{aead::Aead, header_key::HeaderKey, iv};
+use crate::{aead::Aead, header_key::HeaderKey, iv, key_derivation::Quicv2KeyDerivation};
 use ::ring::{aead, hkdf};
 use core::fmt;
-use s2n_quic_core::crypto::{label, CryptoError};
+use s2n_quic_core::crypto::CryptoError;
 use zeroize::{Zeroize, Zeroizing};
 
 mod negotiated;
@@ -26,10 +26,6 @@ macro_rules! impl_cipher_suite {
         $cipher:path,
         $cipher_key_len:expr,
         $header_protection:path,
-        $key_label:expr,
-        $iv_label:expr,
-        $hp_label:expr,
-        $key_update_label:expr,
         $confidentiality_limit:expr,
         $integrity_limit:expr,
         $test_name:ident
@@ -49,18 +45,29 @@ macro_rules! impl_cipher_suite {
                 secret: hkdf::Prk,
                 iv: iv::Iv,
                 key: Key,
+                key_derivation: Quicv2KeyDerivation,
             }
 
             impl $name {
-                pub fn new(secret: hkdf::Prk) -> (Self, HeaderKey) {
-                    let iv = Self::new_iv(&secret);
+                /// Creates the cipher_suite with the labels of the QUIC version for
+                /// `key_derivation`
+                pub fn new(
+                    secret: hkdf::Prk,
+                    key_derivation: Quicv2KeyDerivation,
+                ) -> (Self, HeaderKey) {
+                    let iv = Self::new_iv(&secret, key_derivation);
                     let key = {
-                        let secret = Self::new_key_secret(&secret);
+                        let secret = Self::new_key_secret(&secret, key_derivation);
                         Key::new(&*secret)
                     };
-                    let header_key = Self::new_header_key(&secret);
+                    let header_key = Self::new_header_key(&secret, key_derivation);
 
-                    let key = Self { secret, iv, key };
+                    let key = Self {
+                        secret,
+                        iv,
+                        key,
+                        key_derivation,
+                    };
 
                     (key, header_key)
                 }
@@ -69,35 +76,46 @@ macro_rules! impl_cipher_suite {
                 /// https://www.rfc-editor.org/rfc/rfc9001#section-6
                 #[inline]
                 pub fn update(&self) -> Self {
+                    let key_derivation = self.key_derivation;
+                    let secret_len = $digest.hmac_algorithm().digest_algorithm().output_len;
+                    let key_update_label = key_derivation.key_update_label(secret_len);
                     let secret: hkdf::Prk = self
                         .secret
-                        .expand(&[&$key_update_label], $digest)
+                        .expand(&[key_update_label], $digest)
                         .expect("label size verified")
                         .into();
 
-                    let iv = Self::new_iv(&secret);
+                    let iv = Self::new_iv(&secret, key_derivation);
                     let key = {
-                        let key = Self::new_key_secret(&secret);
+                        let key = Self::new_key_secret(&secret, key_derivation);
                         // ask the existing key to derive the next one so it can persist any
                         // configuration
                         self.key.update(&*key)
                     };
-                    Self { secret, iv, key }
+                    Self {
+                        secret,
+                        iv,
+                        key,
+                        key_derivation,
+                    }
                 }
 
                 #[inline]
                 pub fn update_pmtu(&mut self, mtu: u16) {
                     if self.key.should_update_pmtu(mtu) {
-                        let secret = Self::new_key_secret(&self.secret);
+                        let secret = Self::new_key_secret(&self.secret, self.key_derivation);
                         self.key.update_pmtu(&*secret, mtu);
                     }
                 }
 
-                fn new_key_secret(secret: &hkdf::Prk) -> Zeroizing<[u8; KEY_LEN]> {
+                fn new_key_secret(
+                    secret: &hkdf::Prk,
+                    key_derivation: Quicv2KeyDerivation,
+                ) -> Zeroizing<[u8; KEY_LEN]> {
                     let mut key = Zeroizing::new([0u8; KEY_LEN]);
 
                     secret
-                        .expand(&[&$key_label], &$cipher)
+                        .expand(&[key_derivation.key_label(KEY_LEN)], &$cipher)
                         .expect("label size verified")
                         .fill(&mut key.as_mut())
                         .expect("fill size verified");
@@ -105,12 +123,19 @@ macro_rules! impl_cipher_suite {
                     key
                 }
 
-                fn new_iv(secret: &hkdf::Prk) -> iv::Iv {
-                    iv::Iv::new(secret, &$iv_label)
+                fn new_iv(secret: &hkdf::Prk, key_derivation: Quicv2KeyDerivation) -> iv::Iv {
+                    iv::Iv::new(secret, key_derivation.iv_label())
                 }
 
-                fn new_header_key(secret: &hkdf::Prk) -> HeaderKey {
-                    HeaderKey::new::<{ KEY_LEN }>(secret, &$hp_label, &$header_protection)
+                fn new_header_key(
+                    secret: &hkdf::Prk,
+                    key_derivation: Quicv2KeyDerivation,
+                ) -> HeaderKey {
+                    HeaderKey::new::<{ KEY_LEN }>(
+                        secret,
+                        key_derivation.hp_label(KEY_LEN),
+                        &$header_protection,
+                    )
                 }
             }
 
@@ -219,9 +244,13 @@ macro_rules! impl_cipher_suite {
 
             #[test]
             fn $test_name() {
-                fn compute_vec_label(len: usize, label: &[u8]) -> Vec<u8> {
+                fn compute_vec_label(len: usize, prefix: &[u8], name: &[u8]) -> Vec<u8> {
                     let mut out = vec![];
-                    label::compute_label(len, label, &mut out);
+                    s2n_quic_core::crypto::label::compute_label(
+                        len,
+                        &[prefix, name].concat(),
+                        &mut out,
+                    );
                     out
                 }
 
@@ -236,32 +265,34 @@ macro_rules! impl_cipher_suite {
 
                 assert_eq!(KEY_LEN, $cipher.key_len(), "key len mismatch");
 
-                assert_eq!(
-                    compute_vec_label($cipher.key_len(), b"quic key"),
-                    $key_label,
-                    "key label mismatch"
-                );
-
-                assert_eq!(
-                    compute_vec_label(iv::NONCE_LEN, b"quic iv"),
-                    $iv_label,
-                    "iv label mismatch"
-                );
-
-                assert_eq!(
-                    compute_vec_label($header_protection.key_len(), b"quic hp"),
-                    $hp_label,
-                    "hp label mismatch"
-                );
-
-                assert_eq!(
-                    compute_vec_label(
-                        $digest.hmac_algorithm().digest_algorithm().output_len,
-                        b"quic ku"
-                    ),
-                    $key_update_label,
-                    "key update label mismatch"
-                );
+                for key_derivation in [Quicv2KeyDerivation::V1, Quicv2KeyDerivation::V2] {
+                    let prefix = key_derivation.label_prefix();
+
+                    assert_eq!(
+                        compute_vec_label($cipher.key_len(), prefix, b"key"),
+                        key_derivation.key_label(KEY_LEN),
+                        "key label mismatch"
+                    );
+
+                    assert_eq!(
+                        compute_vec_label(iv::NONCE_LEN, prefix, b"iv"),
+                        key_derivation.iv_label(),
+                        "iv label mismatch"
+                    );
+
+                    assert_eq!(
+                        compute_vec_label($header_protection.key_len(), prefix, b"hp"),
+                        key_derivation.hp_label(KEY_LEN),
+                        "hp label mismatch"
+                    );
+
+                    let secret_len = $digest.hmac_algorithm().digest_algorithm().output_len;
+                    assert_eq!(
+                        compute_vec_label(secret_len, prefix, b"ku"),
+                        key_derivation.key_update_label(secret_len),
+                        "key update label mismatch"
+                    );
+                }
             }
         }
 
@@ -279,10 +310,6 @@ impl_cipher_suite!(
     aead::AES_256_GCM,
     256 / 8, // 256-bit key
     aead::quic::AES_256,
-    label::QUIC_KEY_32,
-    label::QUIC_IV_12,
-    label::QUIC_HP_32,
-    label::QUIC_KU_48,
     u64::pow(2, 23), // Confidentiality limit
     u64::pow(2, 52), // Integrity limit
     tls_aes_256_gcm_sha384_test
@@ -299,10 +326,6 @@ impl_cipher_suite!(
     aead::CHACHA20_POLY1305,
     256 / 8, // 256-bit key
     aead::quic::CHACHA20,
-    label::QUIC_KEY_32,
-    label::QUIC_IV_12,
-    label::QUIC_HP_32,
-    label::QUIC_KU_32,
     u64::pow(2, 62), // Confidentiality limit even though specification notes it can be disregarded
     u64::pow(2, 36), // Integrity limit
     tls_chacha20_poly1305_sha256_test
@@ -316,10 +339,6 @@ impl_cipher_suite!(
     aead::AES_128_GCM,
     128 / 8, // 128-bit key
     aead::quic::AES_128,
-    label::QUIC_KEY_16,
-    label::QUIC_IV_12,
-    label::QUIC_HP_16,
-    label::QUIC_KU_32,
     u64::pow(2, 23), // Confidentiality limit
     u64::pow(2, 52), // Integrity limit
     tls_aes_128_gcm_sha256_test
@@ -4,6 +4,7 @@
 use crate::{
     cipher_suite::{TLS_AES_128_GCM_SHA256, TLS_AES_256_GCM_SHA384, TLS_CHACHA20_POLY1305_SHA256},
     header_key::HeaderKey,
+    key_derivation::Quicv2KeyDerivation,
 };
 use core::fmt;
 use ring::{aead, hkdf};
@@ -48,18 +49,27 @@ impl From<TLS_AES_128_GCM_SHA256> for NegotiatedCipherSuite {
 
 impl NegotiatedCipherSuite {
     /// Create a cipher_suite with a given negotiated algorithm and secret
-    pub fn new(algorithm: &aead::Algorithm, secret: hkdf::Prk) -> Option<(Self, HeaderKey)> {
+    ///
+    /// The keys are derived with the labels of the QUIC version for `key_derivation`.
+    pub fn new(
+        algorithm: &aead::Algorithm,
+        secret: hkdf::Prk,
+        key_derivation: Quicv2KeyDerivation,
+    ) -> Option<(Self, HeaderKey)> {
         Some(match algorithm {
             _ if algorithm == &aead::AES_256_GCM => {
-                let (cipher_suite, header_key) = TLS_AES_256_GCM_SHA384::new(secret);
+                let (cipher_suite, header_key) =
+                    TLS_AES_256_GCM_SHA384::new(secret, key_derivation);
                 (cipher_suite.into(), header_key)
             }
             _ if algorithm == &aead::CHACHA20_POLY1305 => {
-                let (cipher_suite, header_key) = TLS_CHACHA20_POLY1305_SHA256::new(secret);
+                let (cipher_suite, header_key) =
+                    TLS_CHACHA20_POLY1305_SHA256::new(secret, key_derivation);
                 (cipher_suite.into(), header_key)
             }
             _ if algorithm == &aead::AES_128_GCM => {
-                let (cipher_suite, header_key) = TLS_AES_128_GCM_SHA256::new(secret);
+                let (cipher_suite, header_key) =
+                    TLS_AES_128_GCM_SHA256::new(secret, key_derivation);
                 (cipher_suite.into(), header_key)
             }
             _ => return None,
@@ -1,13 +1,15 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{cipher_suite::TLS_AES_128_GCM_SHA256 as CipherSuite, header_key::HeaderKeyPair};
-use ring::hkdf;
+use crate::{
+    cipher_suite::TLS_AES_128_GCM_SHA256 as CipherSuite, header_key::HeaderKeyPair,
+    key_derivation::Quicv2KeyDerivation,
+};
 use s2n_quic_core::{
     crypto::{
         self,
         label::{CLIENT_IN, SERVER_IN},
-        CryptoError, Key, INITIAL_SALT,
+        CryptoError, Key,
     },
     endpoint,
 };
@@ -22,15 +24,19 @@ pub struct InitialKey {
     opener: CipherSuite,
 }
 
-lazy_static::lazy_static! {
-    /// Compute the Initial salt once, as the seed is constant
-    static ref INITIAL_SIGNING_KEY: hkdf::Salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &INITIAL_SALT);
-}
-
 impl InitialKey {
-    fn new(endpoint: endpoint::Type, connection_id: &[u8]) -> (Self, InitialHeaderKey) {
-        let initial_secret = INITIAL_SIGNING_KEY.extract(connection_id);
-        let digest = INITIAL_SIGNING_KEY.algorithm();
+    /// Creates the Initial keys of an endpoint for the QUIC version of `key_derivation`
+    ///
+    /// Both the Initial salt and the packet protection labels depend on the version, so the
+    /// version needs to be known before the first Initial packet is sent or received.
+    pub fn new_with_key_derivation(
+        endpoint: endpoint::Type,
+        connection_id: &[u8],
+        key_derivation: Quicv2KeyDerivation,
+    ) -> (Self, InitialHeaderKey) {
+        let salt = key_derivation.initial_signing_key();
+        let initial_secret = salt.extract(connection_id);
+        let digest = salt.algorithm();
 
         let client_secret = initial_secret
             .expand(&[&CLIENT_IN], digest)
@@ -44,12 +50,12 @@ impl InitialKey {
 
         let (sealer, opener) = match endpoint {
             endpoint::Type::Client => (
-                CipherSuite::new(client_secret),
-                CipherSuite::new(server_secret),
+                CipherSuite::new(client_secret, key_derivation),
+                CipherSuite::new(server_secret, key_derivation),
             ),
             endpoint::Type::Server => (
-                CipherSuite::new(server_secret),
-                CipherSuite::new(client_secret),
+                CipherSuite::new(server_secret, key_derivation),
+                CipherSuite::new(client_secret, key_derivation),
             ),
         };
 
@@ -72,11 +78,19 @@ impl crypto::InitialKey for InitialKey {
     type HeaderKey = InitialHeaderKey;
 
     fn new_server(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Server, connection_id)
+        Self::new_with_key_derivation(
+            endpoint::Type::Server,
+            connection_id,
+            Quicv2KeyDerivation::V1,
+        )
     }
 
     fn new_client(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Client, connection_id)
+        Self::new_with_key_derivation(
+            endpoint::Type::Client,
+            connection_id,
+            Quicv2KeyDerivation::V1,
+        )
     }
 }
 
@@ -160,6 +174,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn v2_round_trip_test() {
+        let new_key = |endpoint, key_derivation| {
+            InitialKey::new_with_key_derivation(endpoint, &EXAMPLE_DCID, key_derivation).0
+        };
+        let client = new_key(endpoint::Type::Client, Quicv2KeyDerivation::V2);
+        let server = new_key(endpoint::Type::Server, Quicv2KeyDerivation::V2);
+        let server_v1 = new_key(endpoint::Type::Server, Quicv2KeyDerivation::V1);
+
+        let header = [1, 2, 3];
+        let mut payload = vec![42; 32 + client.tag_len()];
+        client.encrypt(0, &header, &mut payload).unwrap();
+
+        // the keys of a version 1 endpoint can't open version 2 packets
+        assert!(server_v1.decrypt(0, &header, &mut payload.clone()).is_err());
+
+        server.decrypt(0, &header, &mut payload).unwrap();
+        assert_eq!(payload[..32], [42; 32]);
+    }
+
     fn test_round_trip(
         sealer: &(InitialKey, InitialHeaderKey),
         opener: &(InitialKey, InitialHeaderKey),
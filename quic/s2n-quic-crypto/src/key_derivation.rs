@@ -0,0 +1,270 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selects the Initial salt and packet protection labels for a QUIC version
+//!
+//! QUIC version 2 derives its keys the same way as version 1, except for the Initial salt and
+//! the "quicv2" prefix of the packet protection labels. All of the keys of a connection need to
+//! be derived with the labels of the version which was negotiated for it.
+//!
+//! See https://www.rfc-editor.org/rfc/rfc9369#section-3.3
+
+use ring::hkdf;
+use s2n_quic_core::crypto::{label, INITIAL_SALT, INITIAL_SALT_V2, QUIC_VERSION_1, QUIC_VERSION_2};
+
+lazy_static::lazy_static! {
+    /// Compute the Initial salt once, as the seed is constant
+    static ref INITIAL_SIGNING_KEY: hkdf::Salt =
+        hkdf::Salt::new(hkdf::HKDF_SHA256, Quicv2KeyDerivation::V1.initial_salt());
+
+    /// Compute the QUIC version 2 Initial salt once, as the seed is constant
+    static ref INITIAL_SIGNING_KEY_V2: hkdf::Salt =
+        hkdf::Salt::new(hkdf::HKDF_SHA256, Quicv2KeyDerivation::V2.initial_salt());
+}
+
+/// The key derivation of either QUIC version 1 or QUIC version 2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quicv2KeyDerivation {
+    V1,
+    V2,
+}
+
+impl Default for Quicv2KeyDerivation {
+    #[inline]
+    fn default() -> Self {
+        Self::V1
+    }
+}
+
+impl Quicv2KeyDerivation {
+    /// Returns the key derivation for the QUIC `version`
+    ///
+    /// Returns `None` if the version is not supported.
+    #[inline]
+    pub fn for_version(version: u32) -> Option<Self> {
+        match version {
+            QUIC_VERSION_1 => Some(Self::V1),
+            QUIC_VERSION_2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// Returns the QUIC version which uses the key derivation
+    #[inline]
+    pub fn version(self) -> u32 {
+        match self {
+            Self::V1 => QUIC_VERSION_1,
+            Self::V2 => QUIC_VERSION_2,
+        }
+    }
+
+    /// Returns the salt used to derive the Initial secrets
+    #[inline]
+    pub fn initial_salt(self) -> &'static [u8; 20] {
+        match self {
+            Self::V1 => &INITIAL_SALT,
+            Self::V2 => &INITIAL_SALT_V2,
+        }
+    }
+
+    /// Returns the HKDF salt used to extract the Initial secret
+    #[inline]
+    pub(crate) fn initial_signing_key(self) -> &'static hkdf::Salt {
+        match self {
+            Self::V1 => &INITIAL_SIGNING_KEY,
+            Self::V2 => &INITIAL_SIGNING_KEY_V2,
+        }
+    }
+
+    /// Returns the prefix of the packet protection labels
+    #[inline]
+    pub fn label_prefix(self) -> &'static [u8] {
+        match self {
+            Self::V1 => b"quic ",
+            Self::V2 => b"quicv2 ",
+        }
+    }
+
+    /// Returns the HkdfLabel used to derive a packet protection key of `key_len` bytes
+    #[inline]
+    pub(crate) fn key_label(self, key_len: usize) -> &'static [u8] {
+        match (self, key_len) {
+            (Self::V1, 16) => &label::QUIC_KEY_16,
+            (Self::V1, 32) => &label::QUIC_KEY_32,
+            (Self::V2, 16) => &label::QUICV2_KEY_16,
+            (Self::V2, 32) => &label::QUICV2_KEY_32,
+            _ => unreachable!("unsupported key length {}", key_len),
+        }
+    }
+
+    /// Returns the HkdfLabel used to derive the IV
+    #[inline]
+    pub(crate) fn iv_label(self) -> &'static [u8] {
+        match self {
+            Self::V1 => &label::QUIC_IV_12,
+            Self::V2 => &label::QUICV2_IV_12,
+        }
+    }
+
+    /// Returns the HkdfLabel used to derive a header protection key of `key_len` bytes
+    #[inline]
+    pub(crate) fn hp_label(self, key_len: usize) -> &'static [u8] {
+        match (self, key_len) {
+            (Self::V1, 16) => &label::QUIC_HP_16,
+            (Self::V1, 32) => &label::QUIC_HP_32,
+            (Self::V2, 16) => &label::QUICV2_HP_16,
+            (Self::V2, 32) => &label::QUICV2_HP_32,
+            _ => unreachable!("unsupported key length {}", key_len),
+        }
+    }
+
+    /// Returns the HkdfLabel used to derive the next secret in a key update
+    ///
+    /// The length of the secret is the output length of the hash function of the cipher suite.
+    #[inline]
+    pub(crate) fn key_update_label(self, secret_len: usize) -> &'static [u8] {
+        match (self, secret_len) {
+            (Self::V1, 32) => &label::QUIC_KU_32,
+            (Self::V1, 48) => &label::QUIC_KU_48,
+            (Self::V2, 32) => &label::QUICV2_KU_32,
+            (Self::V2, 48) => &label::QUICV2_KU_48,
+            _ => unreachable!("unsupported secret length {}", secret_len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use s2n_quic_core::crypto::{
+        initial::{
+            EXAMPLE_CLIENT_INITIAL_SECRET_V2, EXAMPLE_DCID, EXAMPLE_SERVER_INITIAL_SECRET_V2,
+        },
+        label::{CLIENT_IN, SERVER_IN},
+    };
+
+    struct Len(usize);
+
+    impl hkdf::KeyType for Len {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn expand(secret: &hkdf::Prk, label: &[u8], len: usize) -> Vec<u8> {
+        let mut out = vec![0; len];
+        secret
+            .expand(&[label], Len(len))
+            .unwrap()
+            .fill(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn version_test() {
+        for key_derivation in [Quicv2KeyDerivation::V1, Quicv2KeyDerivation::V2] {
+            assert_eq!(
+                Quicv2KeyDerivation::for_version(key_derivation.version()),
+                Some(key_derivation)
+            );
+        }
+        assert_eq!(Quicv2KeyDerivation::for_version(0xff00_001d), None);
+        assert_eq!(Quicv2KeyDerivation::default(), Quicv2KeyDerivation::V1);
+    }
+
+    #[test]
+    fn label_test() {
+        fn compute_vec_label(len: usize, prefix: &[u8], name: &[u8]) -> Vec<u8> {
+            let mut out = vec![];
+            label::compute_label(len, &[prefix, name].concat(), &mut out);
+            out
+        }
+
+        for key_derivation in [Quicv2KeyDerivation::V1, Quicv2KeyDerivation::V2] {
+            let prefix = key_derivation.label_prefix();
+
+            for len in [16, 32] {
+                assert_eq!(
+                    key_derivation.key_label(len),
+                    compute_vec_label(len, prefix, b"key")
+                );
+                assert_eq!(
+                    key_derivation.hp_label(len),
+                    compute_vec_label(len, prefix, b"hp")
+                );
+            }
+
+            assert_eq!(
+                key_derivation.iv_label(),
+                compute_vec_label(12, prefix, b"iv")
+            );
+
+            for len in [32, 48] {
+                assert_eq!(
+                    key_derivation.key_update_label(len),
+                    compute_vec_label(len, prefix, b"ku")
+                );
+            }
+        }
+    }
+
+    /// Checks the Initial keys against https://www.rfc-editor.org/rfc/rfc9369#appendix-A.1
+    #[test]
+    fn rfc9369_initial_keys_test() {
+        let key_derivation = Quicv2KeyDerivation::for_version(QUIC_VERSION_2).unwrap();
+        let initial_secret = key_derivation.initial_signing_key().extract(&EXAMPLE_DCID);
+
+        for (label, secret, key, iv, hp) in [
+            (
+                &CLIENT_IN,
+                EXAMPLE_CLIENT_INITIAL_SECRET_V2,
+                hex!("8b1a0bc121284290a29e0971b5cd045d"),
+                hex!("91f73e2351d8fa91660e909f"),
+                hex!("45b95e15235d6f45a6b19cbcb0294ba9"),
+            ),
+            (
+                &SERVER_IN,
+                EXAMPLE_SERVER_INITIAL_SECRET_V2,
+                hex!("82db637861d55e1d011f19ea71d5d2a7"),
+                hex!("dd13c276499c0249d3310652"),
+                hex!("edf6d05c83121201b436e16877593c3a"),
+            ),
+        ] {
+            assert_eq!(expand(&initial_secret, label, 32), secret);
+
+            let secret = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &secret);
+            assert_eq!(expand(&secret, key_derivation.key_label(16), 16), key);
+            assert_eq!(expand(&secret, key_derivation.iv_label(), 12), iv);
+            assert_eq!(expand(&secret, key_derivation.hp_label(16), 16), hp);
+        }
+    }
+
+    /// Checks the 1-RTT keys against https://www.rfc-editor.org/rfc/rfc9369#appendix-A.5
+    #[test]
+    fn rfc9369_chacha20_keys_test() {
+        let key_derivation = Quicv2KeyDerivation::V2;
+        let secret = hkdf::Prk::new_less_safe(
+            hkdf::HKDF_SHA256,
+            &hex!("9ac312a7f877468ebe69422748ad00a15443f18203a07d6060f688f30f21632b"),
+        );
+
+        assert_eq!(
+            expand(&secret, key_derivation.key_label(32), 32),
+            hex!("3bfcddd72bcf02541d7fa0dd1f5f9eeea817e09a6963a0e6c7df0f9a1bab90f2")
+        );
+        assert_eq!(
+            expand(&secret, key_derivation.iv_label(), 12),
+            hex!("a6b5bc6ab7dafce30ffff5dd")
+        );
+        assert_eq!(
+            expand(&secret, key_derivation.hp_label(32), 32),
+            hex!("d659760d2ba434a226fd37b35c69e2da8211d10c4f12538787d65645d5d1b8e2")
+        );
+        assert_eq!(
+            expand(&secret, key_derivation.key_update_label(32), 32),
+            hex!("c69374c49e3d2a9466fa689e49d476db5d0dfbc87d32ceeaa6343fd0ae4c7d88")
+        );
+    }
+}
@@ -49,6 +49,7 @@ pub struct SecretPair {
 
 pub mod handshake;
 pub mod initial;
+pub mod key_derivation;
 pub mod one_rtt;
 pub mod retry;
 pub mod zero_rtt;
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    cipher_suite::NegotiatedCipherSuite as CipherSuite, header_key::HeaderKeyPair, Algorithm,
-    SecretPair,
+    cipher_suite::NegotiatedCipherSuite as CipherSuite, header_key::HeaderKeyPair,
+    key_derivation::Quicv2KeyDerivation, Algorithm, SecretPair,
 };
 use s2n_quic_core::{
     crypto::{CryptoError, Key},
@@ -21,14 +21,15 @@ impl KeyPair {
         endpoint: endpoint::Type,
         algorithm: &Algorithm,
         secrets: SecretPair,
+        key_derivation: Quicv2KeyDerivation,
     ) -> Option<(Self, HeaderKeyPair)> {
         let (sealer_secret, opener_secret) = match endpoint {
             endpoint::Type::Client => (secrets.client, secrets.server),
             endpoint::Type::Server => (secrets.server, secrets.client),
         };
 
-        let (sealer, header_sealer) = CipherSuite::new(algorithm, sealer_secret)?;
-        let (opener, header_opener) = CipherSuite::new(algorithm, opener_secret)?;
+        let (sealer, header_sealer) = CipherSuite::new(algorithm, sealer_secret, key_derivation)?;
+        let (opener, header_opener) = CipherSuite::new(algorithm, opener_secret, key_derivation)?;
 
         let key = Self { sealer, opener };
         let header_key = HeaderKeyPair {
@@ -119,9 +120,25 @@ macro_rules! negotiated_crypto {
                 endpoint: s2n_quic_core::endpoint::Type,
                 algorithm: &$crate::Algorithm,
                 secrets: $crate::SecretPair,
+            ) -> Option<(Self, $header_key)> {
+                Self::new_with_key_derivation(
+                    endpoint,
+                    algorithm,
+                    secrets,
+                    $crate::key_derivation::Quicv2KeyDerivation::V1,
+                )
+            }
+
+            /// Create a cipher_suite for an endpoint type with the labels of the QUIC version for
+            /// `key_derivation`
+            pub fn new_with_key_derivation(
+                endpoint: s2n_quic_core::endpoint::Type,
+                algorithm: &$crate::Algorithm,
+                secrets: $crate::SecretPair,
+                key_derivation: $crate::key_derivation::Quicv2KeyDerivation,
             ) -> Option<(Self, $header_key)> {
                 let (key, header_key) =
-                    crate::negotiated::KeyPair::new(endpoint, algorithm, secrets)?;
+                    crate::negotiated::KeyPair::new(endpoint, algorithm, secrets, key_derivation)?;
 
                 let key = Self(key);
                 let header_key = $header_key::from(header_key);
@@ -28,7 +28,7 @@ impl crypto::OneRttHeaderKey for OneRttHeaderKey {}
 
 #[cfg(test)]
 mod tests {
-    use crate::cipher_suite::TLS_CHACHA20_POLY1305_SHA256;
+    use crate::{cipher_suite::TLS_CHACHA20_POLY1305_SHA256, key_derivation::Quicv2KeyDerivation};
     use hex_literal::hex;
     use ring::hkdf;
     use s2n_quic_core::crypto::Key;
@@ -62,6 +62,12 @@ mod tests {
     const KU_SECRET: [u8; 32] =
         hex!("1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9");
 
+    // The QUIC version 2 example uses the same secret, with the "quicv2 ku" label
+    //
+    // See https://www.rfc-editor.org/rfc/rfc9369#appendix-A.5
+    const KU_SECRET_V2: [u8; 32] =
+        hex!("c69374c49e3d2a9466fa689e49d476db5d0dfbc87d32ceeaa6343fd0ae4c7d88");
+
     // Prevent trivial success
     const INVALID_SECRET: [u8; 32] =
         hex!("0000000000000000000000000000000000000000000000000000000000000000");
@@ -72,24 +78,26 @@ mod tests {
     fn generate_ciphers(
         secret: &[u8],
         next_secret: &[u8],
+        key_derivation: Quicv2KeyDerivation,
     ) -> (TLS_CHACHA20_POLY1305_SHA256, TLS_CHACHA20_POLY1305_SHA256) {
         // Create a cipher based on the initial secret
         let key = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, secret);
-        let cipher = TLS_CHACHA20_POLY1305_SHA256::new(key);
+        let cipher = TLS_CHACHA20_POLY1305_SHA256::new(key, key_derivation);
 
         // Create the cipher after a Key Update has occurred
         let next_cipher = cipher.0.update();
 
         // Create a cipher based on the expected post-update secret
         let next_key = hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, next_secret);
-        let expected_next_cipher = TLS_CHACHA20_POLY1305_SHA256::new(next_key);
+        let expected_next_cipher = TLS_CHACHA20_POLY1305_SHA256::new(next_key, key_derivation);
 
         (next_cipher, expected_next_cipher.0)
     }
 
     #[test]
     fn test_key_update() {
-        let (next_cipher, expected_next_cipher) = generate_ciphers(&SECRET, &KU_SECRET);
+        let (next_cipher, expected_next_cipher) =
+            generate_ciphers(&SECRET, &KU_SECRET, Quicv2KeyDerivation::V1);
 
         // Encrypt two empty blocks to verify the ciphers are the same
         let mut next_cipher_output = [0; 32];
@@ -106,7 +114,8 @@ mod tests {
 
     #[test]
     fn test_key_update_failure() {
-        let (next_cipher, expected_next_cipher) = generate_ciphers(&INVALID_SECRET, &KU_SECRET);
+        let (next_cipher, expected_next_cipher) =
+            generate_ciphers(&INVALID_SECRET, &KU_SECRET, Quicv2KeyDerivation::V1);
 
         // Encrypt two empty blocks to verify the ciphers are the same
         let mut next_cipher_output = [0; 32];
@@ -120,4 +129,30 @@ mod tests {
 
         assert!(next_cipher_output != expected_cipher_output);
     }
+
+    #[test]
+    fn test_key_update_v2() {
+        let (next_cipher, expected_next_cipher) =
+            generate_ciphers(&SECRET, &KU_SECRET_V2, Quicv2KeyDerivation::V2);
+
+        let mut next_cipher_output = [0; 32];
+        let mut expected_cipher_output = [0; 32];
+        next_cipher
+            .encrypt(0, &[], &mut next_cipher_output[..])
+            .unwrap();
+        expected_next_cipher
+            .encrypt(0, &[], &mut expected_cipher_output[..])
+            .unwrap();
+
+        assert_eq!(next_cipher_output, expected_cipher_output);
+
+        // the version 1 labels derive a different key from the same secret
+        let (v1_cipher, _) = generate_ciphers(&SECRET, &KU_SECRET_V2, Quicv2KeyDerivation::V1);
+        let mut v1_cipher_output = [0; 32];
+        v1_cipher
+            .encrypt(0, &[], &mut v1_cipher_output[..])
+            .unwrap();
+
+        assert!(v1_cipher_output != expected_cipher_output);
+    }
 }
@@ -5,7 +5,7 @@ use core::convert::TryInto;
 use ring::aead;
 use s2n_quic_core::crypto::{
     self,
-    retry::{IntegrityTag, NONCE_BYTES, SECRET_KEY_BYTES},
+    retry::{IntegrityTag, NONCE_BYTES, NONCE_BYTES_V2, SECRET_KEY_BYTES, SECRET_KEY_BYTES_V2},
     CryptoError,
 };
 
@@ -14,6 +14,11 @@ lazy_static::lazy_static! {
     static ref SECRET_KEY: aead::LessSafeKey = aead::LessSafeKey::new(
         aead::UnboundKey::new(&aead::AES_128_GCM, &SECRET_KEY_BYTES).unwrap(),
     );
+
+    /// Compute the QUIC version 2 key once, as the seed is constant
+    static ref SECRET_KEY_V2: aead::LessSafeKey = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_128_GCM, &SECRET_KEY_BYTES_V2).unwrap(),
+    );
 }
 
 #[derive(Debug)]
@@ -21,24 +26,55 @@ pub struct RetryKey;
 
 impl crypto::RetryKey for RetryKey {
     fn generate_tag(pseudo_packet: &[u8]) -> IntegrityTag {
-        let nonce = aead::Nonce::assume_unique_for_key(NONCE_BYTES);
-        let tag = SECRET_KEY
-            .seal_in_place_separate_tag(nonce, aead::Aad::from(pseudo_packet), &mut [])
-            .expect("in_out len is 0 and should always be less than the nonce max bytes");
-
-        tag.as_ref()
-            .try_into()
-            .expect("AES_128_GCM tag len should always be 128 bits")
+        generate_tag(&SECRET_KEY, NONCE_BYTES, pseudo_packet)
     }
 
     fn validate(pseudo_packet: &[u8], tag: IntegrityTag) -> Result<(), CryptoError> {
-        let expected = Self::generate_tag(pseudo_packet);
+        validate::<Self>(pseudo_packet, tag)
+    }
+}
 
-        ring::constant_time::verify_slices_are_equal(&expected, &tag)
-            .map_err(|_| CryptoError::DECRYPT_ERROR)
+/// Computes the Retry Integrity Tag for QUIC version 2 packets
+#[derive(Debug)]
+pub struct RetryKeyV2;
+
+impl crypto::RetryKey for RetryKeyV2 {
+    fn generate_tag(pseudo_packet: &[u8]) -> IntegrityTag {
+        generate_tag(&SECRET_KEY_V2, NONCE_BYTES_V2, pseudo_packet)
+    }
+
+    fn validate(pseudo_packet: &[u8], tag: IntegrityTag) -> Result<(), CryptoError> {
+        validate::<Self>(pseudo_packet, tag)
     }
 }
 
+#[inline]
+fn generate_tag(
+    key: &aead::LessSafeKey,
+    nonce: [u8; aead::NONCE_LEN],
+    pseudo_packet: &[u8],
+) -> IntegrityTag {
+    let nonce = aead::Nonce::assume_unique_for_key(nonce);
+    let tag = key
+        .seal_in_place_separate_tag(nonce, aead::Aad::from(pseudo_packet), &mut [])
+        .expect("in_out len is 0 and should always be less than the nonce max bytes");
+
+    tag.as_ref()
+        .try_into()
+        .expect("AES_128_GCM tag len should always be 128 bits")
+}
+
+#[inline]
+fn validate<K: crypto::RetryKey>(
+    pseudo_packet: &[u8],
+    tag: IntegrityTag,
+) -> Result<(), CryptoError> {
+    let expected = K::generate_tag(pseudo_packet);
+
+    ring::constant_time::verify_slices_are_equal(&expected, &tag)
+        .map_err(|_| CryptoError::DECRYPT_ERROR)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,6 +101,61 @@ mod tests {
         assert!(RetryKey::validate(&retry::example::PSEUDO_PACKET, invalid_tag).is_err());
     }
 
+    #[test]
+    fn test_tag_validation_v2() {
+        assert!(RetryKeyV2::validate(
+            &retry::example::v2::PSEUDO_PACKET,
+            retry::example::v2::EXPECTED_TAG
+        )
+        .is_ok());
+
+        // version 2 packets are not protected with the version 1 key
+        assert!(RetryKey::validate(
+            &retry::example::v2::PSEUDO_PACKET,
+            retry::example::v2::EXPECTED_TAG
+        )
+        .is_err());
+        assert!(
+            RetryKeyV2::validate(&retry::example::PSEUDO_PACKET, retry::example::EXPECTED_TAG)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_packet_validate() {
+        let odcid = connection::InitialId::try_from_bytes(&retry::example::ODCID).unwrap();
+        let remote_address = inet::ip::SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+
+        let validate = |mut buf: [u8; retry::example::PACKET_LEN], odcid| {
+            let decoder = DecoderBufferMut::new(&mut buf);
+            match packet::ProtectedPacket::decode(decoder, &connection_info, &20).unwrap() {
+                (packet::ProtectedPacket::Retry(packet), _) => {
+                    packet.validate::<RetryKey, _, _>(odcid, |len| vec![0u8; len])
+                }
+                _ => panic!("expected retry packet type"),
+            }
+        };
+
+        assert!(validate(retry::example::PACKET, &odcid).is_ok());
+
+        // corrupt the integrity tag
+        let mut packet = retry::example::PACKET;
+        packet[retry::example::PACKET_LEN - 1] ^= 1;
+        assert!(validate(packet, &odcid).is_err());
+
+        // modify the retry token, as an attacker injecting a Retry packet would
+        let mut packet = retry::example::PACKET;
+        let token_offset =
+            retry::example::PACKET_LEN - retry::INTEGRITY_TAG_LEN - retry::example::TOKEN_LEN;
+        packet[token_offset] ^= 1;
+        assert!(validate(packet, &odcid).is_err());
+
+        // the tag is bound to the original destination connection ID
+        let other_odcid = connection::InitialId::try_from_bytes(&[0u8; 8]).unwrap();
+        assert!(validate(retry::example::PACKET, &other_odcid).is_err());
+    }
+
     fn pn(space: PacketNumberSpace) -> TruncatedPacketNumber {
         let pn = space.new_packet_number(VarInt::new(0x1).unwrap());
         pn.truncate(pn).unwrap()
@@ -114,6 +205,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_packet_encode_small_buffer() {
+        let remote_address = inet::ip::SocketAddress::default();
+        let mut token_format = token::testing::Format::new();
+        let packet = packet::initial::Initial {
+            version: 0x01,
+            destination_connection_id: &retry::example::ODCID[..],
+            source_connection_id: &retry::example::DCID[..],
+            token: &retry::example::TOKEN[..],
+            packet_number: pn(PacketNumberSpace::Initial),
+            payload: &[1u8, 2, 3, 4, 5][..],
+        };
+
+        let mut buf = vec![0u8; 1200];
+        let mut encoder = EncoderBuffer::new(&mut buf);
+        encoder.encode(&packet);
+        let len = encoder.len();
+        let decoder = DecoderBufferMut::new(&mut buf[..len]);
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let packet = match packet::ProtectedPacket::decode(decoder, &connection_info, &3).unwrap() {
+            (packet::ProtectedPacket::Initial(packet), _) => packet,
+            _ => panic!("expected initial packet type"),
+        };
+        let local_conn_id = connection::LocalId::try_from_bytes(&retry::example::SCID).unwrap();
+
+        // the buffer needs room for the original destination connection ID and its length
+        let required_len = retry::example::PACKET_LEN + 1 + retry::example::ODCID.len();
+        let mut output_buf = vec![0u8; required_len - 1];
+        assert!(packet::retry::Retry::encode_packet::<_, RetryKey, _>(
+            &remote_address,
+            &packet,
+            &local_conn_id,
+            &mut random::testing::Generator(5),
+            &mut token_format,
+            &mut output_buf,
+        )
+        .is_none());
+
+        let mut output_buf = vec![0u8; required_len];
+        let range = packet::retry::Retry::encode_packet::<_, RetryKey, _>(
+            &remote_address,
+            &packet,
+            &local_conn_id,
+            &mut random::testing::Generator(5),
+            &mut token_format,
+            &mut output_buf,
+        )
+        .unwrap();
+        assert_eq!(&output_buf[range], &retry::example::PACKET[..]);
+    }
+
     #[test]
     #[should_panic]
     fn test_odcid_different_from_local_cid() {
@@ -1,7 +1,10 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{cipher_suite::TLS_AES_128_GCM_SHA256 as CipherSuite, header_key::HeaderKey};
+use crate::{
+    cipher_suite::TLS_AES_128_GCM_SHA256 as CipherSuite, header_key::HeaderKey,
+    key_derivation::Quicv2KeyDerivation,
+};
 use s2n_quic_core::crypto::{self, CryptoError, HeaderProtectionMask, Key};
 
 #[derive(Debug)]
@@ -10,7 +13,15 @@ pub struct ZeroRttKey(CipherSuite);
 impl ZeroRttKey {
     /// Create a ZeroRTT cipher suite with a given secret
     pub fn new(secret: crate::Prk) -> (Self, ZeroRttHeaderKey) {
-        let (key, header_key) = CipherSuite::new(secret);
+        Self::new_with_key_derivation(secret, Quicv2KeyDerivation::V1)
+    }
+
+    /// Create a ZeroRTT cipher suite with the labels of the QUIC version for `key_derivation`
+    pub fn new_with_key_derivation(
+        secret: crate::Prk,
+        key_derivation: Quicv2KeyDerivation,
+    ) -> (Self, ZeroRttHeaderKey) {
+        let (key, header_key) = CipherSuite::new(secret, key_derivation);
         let key = Self(key);
         let header_key = ZeroRttHeaderKey(header_key);
         (key, header_key)
@@ -531,6 +531,76 @@ where
     }
 }
 
+/// A frame contained in a packet that was declared lost
+enum LostFrame {
+    /// A STREAM frame carrying application data
+    StreamData {
+        stream_id: u64,
+        offset: u64,
+        len: u16,
+        is_fin: bool,
+    },
+    /// A CRYPTO frame carrying handshake data
+    CryptoData {
+        level: KeySpace,
+        offset: u64,
+        len: u16,
+    },
+    /// An ACK frame
+    Ack { largest_acknowledged: u64 },
+    /// A DATAGRAM frame
+    Datagram { len: u16 },
+    /// Any other frame
+    Other { frame: Frame },
+}
+
+impl builder::LostFrame {
+    /// Summarizes a frame written to a packet in the given space so it can be reported if
+    /// the packet is lost
+    ///
+    /// Returns `None` for PADDING frames, since losing them has no effect on the connection.
+    pub fn new(
+        frame: &builder::Frame,
+        space: crate::packet::number::PacketNumberSpace,
+    ) -> Option<builder::LostFrame> {
+        use crate::packet::number::PacketNumberSpace;
+
+        Some(match frame {
+            builder::Frame::Padding => return None,
+            builder::Frame::Stream {
+                id,
+                offset,
+                len,
+                is_fin,
+            } => builder::LostFrame::StreamData {
+                stream_id: *id,
+                offset: *offset,
+                len: *len,
+                is_fin: *is_fin,
+            },
+            builder::Frame::Crypto { offset, len } => builder::LostFrame::CryptoData {
+                level: match space {
+                    PacketNumberSpace::Initial => builder::KeySpace::Initial,
+                    PacketNumberSpace::Handshake => builder::KeySpace::Handshake,
+                    PacketNumberSpace::ApplicationData => builder::KeySpace::OneRtt,
+                },
+                offset: *offset,
+                len: *len,
+            },
+            builder::Frame::Ack {
+                largest_acknowledged,
+                ..
+            } => builder::LostFrame::Ack {
+                largest_acknowledged: *largest_acknowledged,
+            },
+            builder::Frame::Datagram { len } => builder::LostFrame::Datagram { len: *len },
+            frame => builder::LostFrame::Other {
+                frame: frame.clone(),
+            },
+        })
+    }
+}
+
 enum StreamType {
     Bidirectional,
     Unidirectional,
@@ -670,6 +740,10 @@ enum DatagramDropReason {
     PathLimitExceeded,
     /// The peer initiated a connection migration without supplying enough connection IDs to use.
     InsufficientConnectionIds,
+    /// A DATAGRAM frame was dropped from the application's receive queue because it was full.
+    ///
+    /// The oldest queued DATAGRAM frame is dropped to make room for the newly received one.
+    QueueFull,
 }
 
 enum KeySpace {
@@ -836,6 +910,16 @@ enum SlowStartExitCause {
     Other,
 }
 
+/// The reason BBR estimated that it has fully utilized the available bandwidth
+enum FullPipeReason {
+    /// The bandwidth estimate stopped growing
+    BandwidthPlateau,
+    /// The loss rate exceeded the loss threshold
+    ExcessiveLoss,
+    /// The rate of ECN Congestion Experienced markings exceeded the ECN threshold
+    Ecn,
+}
+
 /// The reason the MTU was updated
 enum MtuUpdatedCause {
     /// The MTU was initialized with the default value
@@ -289,3 +289,119 @@ struct SlowStartExited<'a> {
     cause: SlowStartExitCause,
     congestion_window: u32,
 }
+
+#[event("recovery:startup_completed")]
+/// The startup phase of the congestion controller has completed
+///
+/// The event is emitted once, when BBR estimates it has filled the pipe and leaves Startup.
+struct StartupCompleted<'a> {
+    path: Path<'a>,
+    /// The time between the first packet sent in Startup and leaving Startup
+    duration: Duration,
+    reason: FullPipeReason,
+}
+
+#[event("recovery:spurious_loss_detected")]
+/// A lost packet was within the reordering observed on the path, so the loss may be spurious
+struct SpuriousLossDetected<'a> {
+    packet_header: PacketHeader,
+    path: Path<'a>,
+    bytes_lost: u16,
+}
+
+#[event("recovery:bandwidth_probe_measured")]
+/// The delivery rate of a path was measured during a bandwidth probe
+struct BandwidthProbeMeasured<'a> {
+    path: Path<'a>,
+    /// The delivery rate over the measurement interval, in bits per second
+    delivery_rate: u64,
+    bytes_acked: u64,
+    bytes_lost: u64,
+    interval: Duration,
+}
+
+#[event("recovery:frame_lost")]
+/// A frame contained in a lost packet
+///
+/// The event is only emitted for connections with a subscriber that returns `true` from
+/// `Subscriber::tracks_lost_frames`.
+struct FrameLost<'a> {
+    packet_header: PacketHeader,
+    path: Path<'a>,
+    frame: LostFrame,
+}
+
+#[event("transport:stream_blocked")]
+/// Sending data on a stream was blocked by the peer's stream flow control limit
+///
+/// The event is emitted once when the stream becomes blocked, before any STREAM_DATA_BLOCKED
+/// frame is sent. It is emitted again only after the peer raises the limit and the stream
+/// becomes blocked again.
+struct StreamBlocked {
+    stream_id: u64,
+    /// The stream flow control limit, in bytes
+    limit: u64,
+    /// The number of bytes enqueued by the application beyond the limit
+    pending_len: u64,
+}
+
+#[event("transport:stream_unblocked")]
+/// The peer raised the flow control limit of a stream which was reported as blocked
+///
+/// The event is emitted the next time the stream transmits after the limit is raised.
+struct StreamUnblocked {
+    stream_id: u64,
+    /// The new stream flow control limit, in bytes
+    limit: u64,
+    /// The time between reporting the stream as blocked and the stream resuming
+    blocked_duration: Duration,
+}
+
+#[event("transport:connection_blocked")]
+/// Sending stream data was blocked by the peer's connection flow control limit
+///
+/// The event is emitted once when the connection becomes blocked, before any DATA_BLOCKED
+/// frame is sent. It is emitted again only after the peer raises the limit and the connection
+/// becomes blocked again.
+struct ConnectionBlocked {
+    /// The connection flow control limit, in bytes
+    limit: u64,
+}
+
+#[event("connectivity:path_degraded")]
+/// The quality of the active path degraded
+///
+/// The event is emitted once when the averaged loss rate or RTT of the path exceeds its
+/// threshold. It is emitted again only after the path recovers and degrades again.
+struct PathDegraded<'a> {
+    current_path: Path<'a>,
+    /// The averaged ratio of lost packets to sent packets
+    loss_rate: f64,
+    /// The averaged RTT divided by the minimum RTT of the path
+    rtt_ratio: f64,
+}
+
+#[event("connectivity:migration_triggered")]
+/// The connection migrated away from a degraded path
+struct MigrationTriggered<'a> {
+    from_path: Path<'a>,
+    to_path: Path<'a>,
+}
+
+#[event("transport:potential_deadlock")]
+/// Streams may be deadlocked with the peer's streams
+///
+/// The event is emitted when sending stream data was blocked by the peer's flow control limits
+/// while received stream data was left unread for longer than the deadlock detection timeout.
+/// This happens when both applications wait for their writes to complete before reading. It is
+/// emitted again only after either condition clears and the stall recurs.
+struct PotentialDeadlock<'a> {
+    /// The IDs of the streams blocked by the peer's flow control limits
+    blocked_stream_ids: &'a [u64],
+    /// The IDs of the streams with received data the application has not read
+    unread_stream_ids: &'a [u64],
+    /// The number of unread bytes of each stream in `unread_stream_ids`
+    unread_lens: &'a [u64],
+    /// How long the streams have been stalled
+    duration: Duration,
+}
@@ -299,6 +299,15 @@ impl ToTokens for Output {
                         supervisor::Outcome::default()
                     }
 
+                    /// Returns `true` if the subscriber consumes `FrameLost` events
+                    ///
+                    /// Recording the frames contained in each sent packet has a cost, so frames are only
+                    /// tracked for connections where at least one `event::Subscriber` returns `true`.
+                    #[inline]
+                    fn tracks_lost_frames(&self) -> bool {
+                        false
+                    }
+
                     #subscriber
 
                     /// Called for each event that relates to the endpoint and all connections
@@ -365,6 +374,11 @@ impl ToTokens for Output {
                         }
                     }
 
+                    #[inline]
+                    fn tracks_lost_frames(&self) -> bool {
+                        self.0.tracks_lost_frames() || self.1.tracks_lost_frames()
+                    }
+
                     #tuple_subscriber
 
                     #[inline]
@@ -448,6 +462,9 @@ impl ToTokens for Output {
 
                     /// Returns the [`Subject`] for the current publisher
                     fn subject(&self) -> Subject;
+
+                    /// Returns `true` if `FrameLost` events should be published for the current connection
+                    fn tracks_lost_frames(&self) -> bool;
                 }
 
                 pub struct ConnectionPublisherSubscriber<'a, Sub: Subscriber> {
@@ -495,6 +512,11 @@ impl ToTokens for Output {
                     fn subject(&self) -> api::Subject {
                         self.meta.subject()
                     }
+
+                    #[inline]
+                    fn tracks_lost_frames(&self) -> bool {
+                        self.subscriber.tracks_lost_frames()
+                    }
                 }
             }
 
@@ -593,6 +615,10 @@ impl ToTokens for Output {
                     fn subject(&self) -> api::Subject {
                         api::Subject::Connection { id: 0 }
                     }
+
+                    fn tracks_lost_frames(&self) -> bool {
+                        false
+                    }
                 }
 
                 impl Drop for Publisher {
@@ -0,0 +1,412 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Migrates a session to an alternative service advertised by the origin
+//!
+//! An HTTP/3 server can redirect its clients to a different endpoint for the same origin
+//! with the `Alt-Svc` header, as described in
+//! [RFC 7838](https://www.rfc-editor.org/rfc/rfc7838).
+
+use core::{fmt, str::FromStr, time::Duration};
+use s2n_quic::{client::Connect, connection, Client, Connection};
+use std::net::{IpAddr, SocketAddr};
+
+/// The name of the `Alt-Svc` header
+pub const ALT_SVC: &str = "alt-svc";
+
+/// The protocol id of HTTP/3 alternatives
+const H3: &str = "h3";
+
+/// The application error code for closing the original connection after a migration
+///
+/// H3_NO_ERROR signals that the connection is closed without an error.
+pub const H3_NO_ERROR: u32 = 0x100;
+
+/// The freshness lifetime of an alternative without a `ma` parameter (24 hours)
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(86400);
+
+/// The value of an `Alt-Svc` header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AltSvc {
+    /// All alternatives of the origin are invalidated
+    Clear,
+    /// The alternatives of the origin, in order of the server's preference
+    Alternatives(Vec<Alternative>),
+}
+
+/// An alternative endpoint for an origin
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alternative {
+    /// The ALPN protocol id used by the alternative
+    pub protocol_id: String,
+    /// The host of the alternative, or `None` if it is the host of the origin
+    pub host: Option<String>,
+    /// The port of the alternative
+    pub port: u16,
+    /// The duration for which the alternative is considered fresh
+    pub max_age: Duration,
+    /// Whether the alternative should be kept after a network change
+    pub persist: bool,
+}
+
+impl Alternative {
+    /// Returns the address of the alternative for an origin at `origin`
+    ///
+    /// Returns `None` if the host of the alternative is a domain name, which needs to be
+    /// resolved by the caller.
+    pub fn address(&self, origin: SocketAddr) -> Option<SocketAddr> {
+        let ip = match self.host.as_deref() {
+            Some(host) => host.parse::<IpAddr>().ok()?,
+            None => origin.ip(),
+        };
+        Some((ip, self.port).into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError;
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Alt-Svc header value")
+    }
+}
+
+impl FromStr for AltSvc {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim_matches(is_whitespace) == "clear" {
+            return Ok(Self::Clear);
+        }
+
+        let mut parser = Parser { input: value };
+
+        let mut alternatives = vec![];
+
+        loop {
+            parser.skip_whitespace();
+
+            // the list syntax allows empty elements
+            if parser.eat(',') {
+                continue;
+            }
+
+            if parser.input.is_empty() {
+                break;
+            }
+
+            alternatives.push(parser.alternative()?);
+
+            parser.skip_whitespace();
+
+            if parser.input.is_empty() {
+                break;
+            }
+
+            parser.expect(',')?;
+        }
+
+        if alternatives.is_empty() {
+            return Err(ParseError);
+        }
+
+        Ok(Self::Alternatives(alternatives))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses an `alt-value`
+    ///
+    /// ```text
+    /// alt-value     = alternative *( OWS ";" OWS parameter )
+    /// alternative   = protocol-id "=" alt-authority
+    /// protocol-id   = token ; percent-encoded ALPN protocol name
+    /// alt-authority = quoted-string ; containing [ uri-host ] ":" port
+    /// parameter     = token "=" ( token / quoted-string )
+    /// ```
+    fn alternative(&mut self) -> Result<Alternative, ParseError> {
+        let protocol_id = percent_decode(self.token()?)?;
+        self.expect('=')?;
+
+        let authority = self.quoted_string()?;
+        let (host, port) = authority.rsplit_once(':').ok_or(ParseError)?;
+        let port = port.parse().map_err(|_| ParseError)?;
+        // IPv6 addresses are enclosed in brackets
+        let host = host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .unwrap_or(host);
+        let host = if host.is_empty() {
+            None
+        } else {
+            Some(host.to_owned())
+        };
+
+        let mut alternative = Alternative {
+            protocol_id,
+            host,
+            port,
+            max_age: DEFAULT_MAX_AGE,
+            persist: false,
+        };
+
+        loop {
+            self.skip_whitespace();
+
+            if !self.eat(';') {
+                break;
+            }
+
+            self.skip_whitespace();
+
+            let name = self.token()?;
+            self.expect('=')?;
+            let value = if self.input.starts_with('"') {
+                self.quoted_string()?
+            } else {
+                self.token()?.to_owned()
+            };
+
+            match name {
+                "ma" => {
+                    let max_age = value.parse().map_err(|_| ParseError)?;
+                    alternative.max_age = Duration::from_secs(max_age);
+                }
+                "persist" => {
+                    // values other than "1" are ignored
+                    alternative.persist = value == "1";
+                }
+                // unknown parameters are ignored
+                _ => {}
+            }
+        }
+
+        Ok(alternative)
+    }
+
+    fn token(&mut self) -> Result<&'a str, ParseError> {
+        let len = self
+            .input
+            .find(|c: char| !is_tchar(c))
+            .unwrap_or(self.input.len());
+
+        if len == 0 {
+            return Err(ParseError);
+        }
+
+        let (token, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(token)
+    }
+
+    fn quoted_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+
+        let mut value = String::new();
+        let mut chars = self.input.char_indices();
+
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.input = &self.input[index + 1..];
+                    return Ok(value);
+                }
+                '\\' => {
+                    let (_, c) = chars.next().ok_or(ParseError)?;
+                    value.push(c);
+                }
+                c => value.push(c),
+            }
+        }
+
+        // the string was not terminated
+        Err(ParseError)
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.input = self.input.trim_start_matches(is_whitespace);
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if let Some(rest) = self.input.strip_prefix(c) {
+            self.input = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(ParseError)
+        }
+    }
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+fn percent_decode(value: &str) -> Result<String, ParseError> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+
+        let hex = [
+            bytes.next().ok_or(ParseError)?,
+            bytes.next().ok_or(ParseError)?,
+        ];
+        let hex = core::str::from_utf8(&hex).map_err(|_| ParseError)?;
+        decoded.push(u8::from_str_radix(hex, 16).map_err(|_| ParseError)?);
+    }
+
+    String::from_utf8(decoded).map_err(|_| ParseError)
+}
+
+/// Moves a session from the origin to an HTTP/3 alternative advertised by the origin
+///
+/// Requests can't be moved between connections, so the original connection is left open for
+/// the requests which are still in flight. New requests should be sent on the connection
+/// returned by [`Self::migrate`]. Once the outstanding requests on the original connection
+/// have finished, the caller should close it with [`H3_NO_ERROR`].
+pub struct AltSvcMigration {
+    client: Client,
+    origin: Connect,
+}
+
+impl AltSvcMigration {
+    /// Creates a migration from the `origin`
+    pub fn new(client: Client, origin: Connect) -> Self {
+        Self { client, origin }
+    }
+
+    /// Connects to the first reachable HTTP/3 alternative in the `Alt-Svc` header `value`
+    ///
+    /// The new connection is returned once it is established. `None` is returned if the header
+    /// doesn't advertise a usable alternative.
+    pub async fn migrate(&self, value: &str) -> connection::Result<Option<Connection>> {
+        let alternatives = match value.parse() {
+            Ok(AltSvc::Alternatives(alternatives)) => alternatives,
+            // invalid headers are ignored rather than failing the session
+            Ok(AltSvc::Clear) | Err(_) => return Ok(None),
+        };
+
+        let origin: SocketAddr = self.origin.remote_address().into();
+        let mut result = Ok(None);
+
+        let addresses = alternatives
+            .iter()
+            .filter(|alternative| alternative.protocol_id == H3)
+            .filter_map(|alternative| alternative.address(origin))
+            .filter(|address| *address != origin);
+
+        for address in addresses {
+            // the alternative needs to present a certificate which is valid for the origin
+            let mut connect = Connect::new(address);
+            if let Some(server_name) = self.origin.server_name() {
+                connect = connect.with_server_name(server_name.clone());
+            }
+
+            match self.client.connect(connect).await {
+                Ok(connection) => return Ok(Some(connection)),
+                // try the next alternative
+                Err(error) => result = Err(error),
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alternative(host: Option<&str>, port: u16) -> Alternative {
+        Alternative {
+            protocol_id: H3.to_owned(),
+            host: host.map(String::from),
+            port,
+            max_age: DEFAULT_MAX_AGE,
+            persist: false,
+        }
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!("clear".parse(), Ok(AltSvc::Clear));
+        assert_eq!(
+            r#"h3=":4433""#.parse(),
+            Ok(AltSvc::Alternatives(vec![alternative(None, 4433)]))
+        );
+        assert_eq!(
+            r#"h3="alt.example.com:443"; ma=3600; persist=1, h3="[::1]:8443""#.parse(),
+            Ok(AltSvc::Alternatives(vec![
+                Alternative {
+                    max_age: Duration::from_secs(3600),
+                    persist: true,
+                    ..alternative(Some("alt.example.com"), 443)
+                },
+                alternative(Some("::1"), 8443),
+            ]))
+        );
+        assert_eq!(
+            r#"w%3D%3Dx="alt\.example.com:1"; foo="bar""#.parse(),
+            Ok(AltSvc::Alternatives(vec![Alternative {
+                protocol_id: "w==x".to_owned(),
+                ..alternative(Some("alt.example.com"), 1)
+            }]))
+        );
+    }
+
+    #[test]
+    fn parse_error_test() {
+        for value in [
+            "",
+            ",",
+            "h3",
+            "h3=:443",
+            r#"h3="example.com""#,
+            r#"h3=":443"#,
+            r#"h3=":65536""#,
+            r#"h3=":443"; ma=never"#,
+            r#"h3=":443" h2=":443""#,
+            r#"h3%2=":443""#,
+        ] {
+            assert_eq!(value.parse::<AltSvc>(), Err(ParseError), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn address_test() {
+        let origin: SocketAddr = "192.0.2.1:443".parse().unwrap();
+
+        assert_eq!(
+            alternative(None, 4433).address(origin),
+            Some("192.0.2.1:4433".parse().unwrap())
+        );
+        assert_eq!(
+            alternative(Some("::1"), 443).address(origin),
+            Some("[::1]:443".parse().unwrap())
+        );
+        assert_eq!(alternative(Some("example.com"), 443).address(origin), None);
+    }
+}
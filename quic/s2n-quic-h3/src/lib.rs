@@ -1,7 +1,11 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod alt_svc;
 mod s2n_quic;
 
 pub use self::s2n_quic::*;
 pub use h3;
+
+#[cfg(test)]
+mod tests;
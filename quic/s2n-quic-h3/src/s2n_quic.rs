@@ -45,7 +45,7 @@ impl fmt::Display for ConnectionError {
 
 impl Error for ConnectionError {
     fn is_timeout(&self) -> bool {
-        matches!(self.0, s2n_quic::connection::Error::IdleTimerExpired { .. })
+        matches!(self.0, s2n_quic::connection::Error::Timeout { .. })
     }
 
     fn err_code(&self) -> Option<u64> {
@@ -317,7 +317,7 @@ impl Error for ReadError {
         matches!(
             self.0,
             s2n_quic::stream::Error::ConnectionError {
-                error: s2n_quic::connection::Error::IdleTimerExpired { .. },
+                error: s2n_quic::connection::Error::Timeout { .. },
                 ..
             }
         )
@@ -443,7 +443,7 @@ impl Error for SendStreamError {
         matches!(
             self,
             Self::Write(s2n_quic::stream::Error::ConnectionError {
-                error: s2n_quic::connection::Error::IdleTimerExpired { .. },
+                error: s2n_quic::connection::Error::Timeout { .. },
                 ..
             })
         )
@@ -0,0 +1,146 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alt_svc::{AltSvcMigration, ALT_SVC, H3_NO_ERROR},
+    h3, Connection,
+};
+use ::s2n_quic::{
+    client::Connect,
+    provider::io::testing::{primary, spawn, test, Handle, Model, Result},
+    Client, Server,
+};
+use bytes::{Buf, Bytes};
+use s2n_quic_core::crypto::tls::testing::certificates;
+use std::net::SocketAddr;
+
+const RESPONSE_LEN: usize = 100_000;
+const CHUNK_LEN: usize = 1_000;
+
+/// Starts an HTTP/3 server at `address` which responds to each request with `RESPONSE_LEN` bytes
+///
+/// If `alt_svc` is set, the response advertises the alternative service.
+fn server(handle: &Handle, address: SocketAddr, alt_svc: Option<&'static str>) -> Result {
+    let io = handle.builder().with_address(address)?.build()?;
+    let mut server = Server::builder()
+        .with_io(io)?
+        .with_tls((certificates::CERT_PEM, certificates::KEY_PEM))?
+        .start()?;
+
+    spawn(async move {
+        while let Some(connection) = server.accept().await {
+            spawn(async move {
+                let mut connection = h3::server::Connection::new(Connection::new(connection))
+                    .await
+                    .unwrap();
+
+                while let Ok(Some((_request, stream))) = connection.accept().await {
+                    spawn(async move {
+                        let _ = respond(stream, alt_svc).await;
+                    });
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+async fn respond<S>(
+    mut stream: h3::server::RequestStream<S, Bytes>,
+    alt_svc: Option<&'static str>,
+) -> Result
+where
+    S: h3::quic::BidiStream<Bytes>,
+{
+    let mut response = http::Response::builder();
+    if let Some(alt_svc) = alt_svc {
+        response = response.header(ALT_SVC, alt_svc);
+    }
+    stream.send_response(response.body(())?).await?;
+
+    for _ in 0..RESPONSE_LEN / CHUNK_LEN {
+        stream
+            .send_data(Bytes::from_static(&[42; CHUNK_LEN]))
+            .await?;
+    }
+
+    stream.finish().await?;
+
+    Ok(())
+}
+
+fn request() -> http::Request<()> {
+    http::Request::builder()
+        .uri("https://localhost/")
+        .body(())
+        .unwrap()
+}
+
+/// Receives the body of a response, returning the number of bytes received
+async fn recv_body<S, B>(stream: &mut h3::client::RequestStream<S, B>) -> Result<usize>
+where
+    S: h3::quic::RecvStream,
+    B: Buf,
+{
+    let mut len = 0;
+    while let Some(chunk) = stream.recv_data().await? {
+        len += chunk.remaining();
+    }
+    Ok(len)
+}
+
+#[test]
+fn alt_svc_migration_test() {
+    let origin: SocketAddr = "127.0.0.1:443".parse().unwrap();
+    let alternative: SocketAddr = "127.0.0.1:4433".parse().unwrap();
+
+    test(Model::default(), |handle| {
+        server(handle, origin, Some(r#"h3=":4433"; ma=3600"#))?;
+        server(handle, alternative, None)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(origin).with_server_name("localhost");
+            let connection = client.connect(connect.clone()).await.unwrap();
+            let original = connection.handle();
+            let migration = AltSvcMigration::new(client.clone(), connect);
+
+            let (_driver, mut send_request) =
+                h3::client::new(Connection::new(connection)).await.unwrap();
+            let mut stream = send_request.send_request(request()).await.unwrap();
+            stream.finish().await.unwrap();
+
+            let response = stream.recv_response().await.unwrap();
+            let alt_svc = response.headers()[ALT_SVC].to_str().unwrap();
+
+            let connection = migration
+                .migrate(alt_svc)
+                .await
+                .unwrap()
+                .expect("the alternative should be reachable");
+            assert_eq!(connection.remote_addr().unwrap(), alternative);
+
+            // the request in flight on the original connection still completes
+            assert_eq!(recv_body(&mut stream).await.unwrap(), RESPONSE_LEN);
+            original.close(H3_NO_ERROR.into());
+
+            // new requests are sent on the alternative
+            let (_driver, mut send_request) =
+                h3::client::new(Connection::new(connection)).await.unwrap();
+            let mut stream = send_request.send_request(request()).await.unwrap();
+            stream.finish().await.unwrap();
+
+            let response = stream.recv_response().await.unwrap();
+            assert!(!response.headers().contains_key(ALT_SVC));
+            assert_eq!(recv_body(&mut stream).await.unwrap(), RESPONSE_LEN);
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
@@ -222,6 +222,13 @@ impl Handle {
             address: None,
         }
     }
+
+    /// Simulates a NAT rebinding for the endpoint bound to `address`
+    ///
+    /// Returns the new address observed by the endpoint's peers.
+    pub fn rebind<A: Into<SocketAddress>>(&self, address: A) -> SocketAddress {
+        self.buffers.rebind(address.into())
+    }
 }
 
 pub struct Builder {
@@ -230,6 +237,12 @@ pub struct Builder {
 }
 
 impl Builder {
+    /// Binds the endpoint to `address` rather than a generated address
+    pub fn with_address<A: Into<SocketAddress>>(mut self, address: A) -> Result<Self> {
+        self.address = Some(address.into());
+        Ok(self)
+    }
+
     pub fn build(self) -> Result<Io> {
         Ok(Io { builder: self })
     }
@@ -71,23 +71,38 @@ impl Buffers {
 
     pub fn rx<F: FnOnce(&mut Queue)>(&self, handle: SocketAddress, f: F) {
         let mut lock = self.inner.lock().unwrap();
-        if let Some(queue) = lock.rx.get_mut(&handle) {
+        let state = &mut *lock;
+
+        let handle = if let Some((internal, _)) =
+            state.nat.iter().find(|(_, external)| **external == handle)
+        {
+            // route packets sent to the external address to the rebound endpoint
+            *internal
+        } else if state.nat.contains_key(&handle) {
+            // the endpoint is no longer reachable on its original address
+            return;
+        } else {
+            handle
+        };
+
+        if let Some(queue) = state.rx.get_mut(&handle) {
             f(queue)
         }
     }
 
     pub fn pending_transmissions<F: FnMut(Packet) -> Result<(), ()>>(&self, mut f: F) {
         let mut lock = self.inner.lock().unwrap();
+        let state = &mut *lock;
 
         let mut queues = vec![];
 
         // find all of the queues with at least one packet to transmit
-        for queue in lock.tx.values_mut() {
+        for (handle, queue) in state.tx.iter_mut() {
             if queue.packets.is_empty() {
                 continue;
             }
 
-            queues.push(queue);
+            queues.push((queue, state.nat.get(handle).copied()));
         }
 
         // shuffle the queue so each endpoint has a fair chance of transmitting
@@ -95,10 +110,15 @@ impl Buffers {
 
         loop {
             let mut has_result = false;
-            for queue in &mut queues {
+            for (queue, external) in &mut queues {
                 // transmit a single packet at a time per queue so they are fairly
                 // transmitted
-                if let Some(packet) = queue.packets.pop_front() {
+                if let Some(mut packet) = queue.packets.pop_front() {
+                    // rewrite the source address if the endpoint has been rebound
+                    if let Some(external) = external {
+                        packet.path.local_address = (*external).into();
+                    }
+
                     let result = f(packet);
                     has_result = true;
 
@@ -143,6 +163,20 @@ impl Buffers {
         SocketAddress::IpV4(addr.into())
     }
 
+    /// Simulates a NAT rebinding for the endpoint registered at `handle`
+    ///
+    /// Packets sent by the endpoint appear to come from the returned address, which shares
+    /// the IP of `handle` with a new port. The endpoint is no longer reachable at `handle`.
+    pub fn rebind(&self, handle: SocketAddress) -> SocketAddress {
+        let mut external = handle;
+        external.set_port(self.next_port.fetch_add(1, Ordering::SeqCst));
+
+        let mut lock = self.inner.lock().unwrap();
+        lock.nat.insert(handle, external);
+
+        external
+    }
+
     /// Register an address on the network
     pub fn register(&self, handle: SocketAddress) {
         let mut lock = self.inner.lock().unwrap();
@@ -203,6 +237,8 @@ struct State {
     is_open: bool,
     tx: HashMap<SocketAddress, Queue>,
     rx: HashMap<SocketAddress, Queue>,
+    /// Maps the addresses of rebound endpoints to the address observed by their peers
+    nat: HashMap<SocketAddress, SocketAddress>,
 }
 
 impl Default for State {
@@ -211,6 +247,7 @@ impl Default for State {
             is_open: true,
             tx: Default::default(),
             rx: Default::default(),
+            nat: Default::default(),
         }
     }
 }
@@ -241,7 +278,10 @@ impl Queue {
         }
     }
 
-    pub fn receive(&mut self, packet: Packet) {
+    pub fn receive(&mut self, mut packet: Packet) {
+        // the packet may have been addressed to the external address of a rebound endpoint
+        packet.path.local_address = self.local_address;
+
         if self.packets.len() == self.capacity {
             // drop old packets if we're at capacity
             let _ = self.packets.pop_front();
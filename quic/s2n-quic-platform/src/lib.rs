@@ -15,5 +15,7 @@ pub mod buffer;
 pub mod features;
 pub mod io;
 pub mod message;
+#[cfg(feature = "std")]
+pub mod shard;
 pub mod socket;
 pub mod time;
@@ -0,0 +1,457 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Distributes received datagrams across shards by destination connection ID
+//!
+//! On servers with a large number of connections, a single thread processing every received
+//! datagram becomes the bottleneck. The server can instead be split into N shards, each
+//! with its own packet queue and connection state, running on a dedicated thread or process.
+//! All datagrams for a connection must reach the shard that owns it.
+//!
+//! Each shard issues structured connection IDs, as described in
+//! [`s2n_quic_core::connection::id::structured`], which carry the index of the shard as a
+//! 2-byte big-endian server ID. A front-end [`ShardRouter`] recovers the index and forwards
+//! each datagram to the shard that owns the connection. The destination connection ID of a
+//! client's first Initial packets is chosen by the client, so those are assigned to a shard
+//! with [`shard_index`]. Datagrams that cannot be attributed to a single shard are
+//! forwarded to all of them.
+
+use core::num::NonZeroUsize;
+use s2n_quic_core::connection::id::structured::{ConfigId, Decoder};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{self, TrySendError},
+    Arc,
+};
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-17.2
+//# Header Form:  The most significant bit (0x80) of byte 0 (the first
+//#    byte) is set to 1 for long headers.
+const LONG_HEADER_FORM: u8 = 0x80;
+
+/// The offset of the Destination Connection ID Length field in a long header
+///
+/// The field follows the first byte and the 4-byte Version field.
+const LONG_HEADER_DCID_LEN_OFFSET: usize = 5;
+
+/// The number of bytes of the connection ID used to derive the shard key
+const SHARD_KEY_LEN: usize = 4;
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-17.2
+//# Long Packet Type:  The next two bits (those with a mask of 0x30) of
+//#    byte 0 contain a packet type.
+const LONG_PACKET_TYPE_MASK: u8 = 0x30;
+
+/// The long packet types for which the client chooses the destination connection ID
+const INITIAL_PACKET_TYPE: u8 = 0x00;
+const ZERO_RTT_PACKET_TYPE: u8 = 0x10;
+
+/// The length of the server ID which encodes the index of a shard
+pub const SHARD_SERVER_ID_LEN: usize = 2;
+
+/// Returns the server ID a shard encodes in the connection IDs it issues
+#[inline]
+pub fn shard_server_id(index: u16) -> [u8; SHARD_SERVER_ID_LEN] {
+    index.to_be_bytes()
+}
+
+/// Returns the destination connection ID of the packet at the start of `payload`
+///
+/// Short header packets do not encode the length of the connection ID, so the rest of the
+/// datagram is returned for those. The length of structured connection IDs is recovered
+/// by the [`Decoder`].
+#[inline]
+fn destination_connection_id(payload: &[u8]) -> Option<&[u8]> {
+    let first = *payload.first()?;
+
+    if first & LONG_HEADER_FORM == LONG_HEADER_FORM {
+        let len = *payload.get(LONG_HEADER_DCID_LEN_OFFSET)? as usize;
+        let start = LONG_HEADER_DCID_LEN_OFFSET + 1;
+        payload.get(start..start + len)
+    } else {
+        Some(&payload[1..])
+    }
+}
+
+/// Derives the shard key for a connection ID
+///
+/// The first 4 bytes of the connection ID are XOR-folded into a `u16`. Connection IDs
+/// shorter than 4 bytes are padded with zeros.
+#[inline]
+pub fn shard_key(connection_id: &[u8]) -> u16 {
+    let mut bytes = [0u8; SHARD_KEY_LEN];
+    let len = connection_id.len().min(SHARD_KEY_LEN);
+    bytes[..len].copy_from_slice(&connection_id[..len]);
+
+    u16::from_be_bytes([bytes[0], bytes[1]]) ^ u16::from_be_bytes([bytes[2], bytes[3]])
+}
+
+/// Returns the index of the shard responsible for a connection ID chosen by the client
+#[inline]
+pub fn shard_index(connection_id: &[u8], shard_count: NonZeroUsize) -> usize {
+    shard_key(connection_id) as usize % shard_count.get()
+}
+
+/// Returns `true` if the packet at the start of `payload` is a long header packet with a
+/// destination connection ID chosen by the client
+#[inline]
+fn is_client_chosen_connection_id(payload: &[u8]) -> bool {
+    match payload.first() {
+        Some(first) if first & LONG_HEADER_FORM == LONG_HEADER_FORM => matches!(
+            first & LONG_PACKET_TYPE_MASK,
+            INITIAL_PACKET_TYPE | ZERO_RTT_PACKET_TYPE
+        ),
+        _ => false,
+    }
+}
+
+/// The shards a [`ShardRouter`] forwards a datagram to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    /// The datagram is forwarded to the shard at the given index
+    Shard(usize),
+    /// The datagram is forwarded to every shard
+    ///
+    /// This is used for datagrams with a zero-length destination connection ID, or a
+    /// connection ID that does not encode the index of a shard.
+    AllShards,
+}
+
+/// A datagram forwarded to a shard by a [`ShardRouter`]
+#[derive(Debug)]
+pub struct Routed<D> {
+    datagram: D,
+    /// The number of shards that have yet to reject the datagram, if it was forwarded to
+    /// more than one shard
+    pending: Option<Arc<AtomicUsize>>,
+}
+
+impl<D> Routed<D> {
+    /// Returns the forwarded datagram
+    #[inline]
+    pub fn datagram(&self) -> &D {
+        &self.datagram
+    }
+
+    /// Returns the forwarded datagram, consuming the `Routed` wrapper
+    ///
+    /// This should be called once a shard has found the connection for the datagram.
+    #[inline]
+    pub fn into_datagram(self) -> D {
+        self.datagram
+    }
+
+    /// Called by a shard that doesn't have a connection for the datagram
+    ///
+    /// Returns `true` once every shard the datagram was forwarded to has rejected it, in
+    /// which case the shard should respond with a stateless reset. At most one shard
+    /// observes `true` for each datagram.
+    #[inline]
+    pub fn reject(self) -> bool {
+        match self.pending {
+            Some(pending) => pending.fetch_sub(1, Ordering::AcqRel) == 1,
+            None => true,
+        }
+    }
+}
+
+/// Forwards received datagrams to the shard that issued their destination connection ID
+///
+/// Each shard is expected to issue structured connection IDs with the router's config
+/// rotation codepoint and its [`shard_server_id`] as the server ID. Datagrams are routed as
+/// follows:
+///
+/// * Connection IDs that encode the index of a shard are forwarded to that shard.
+/// * Connection IDs chosen by the client in Initial and 0-RTT packets are forwarded to the
+///   shard selected by [`shard_index`], so all of the packets for a new connection reach
+///   the same shard.
+/// * Zero-length and unknown connection IDs are forwarded to every shard. The shard which
+///   owns the connection processes the datagram and all others [`reject`](Routed::reject)
+///   it. If every shard rejects it, the last one to do so sends a stateless reset.
+pub struct ShardRouter<D> {
+    shards: Vec<mpsc::SyncSender<Routed<D>>>,
+    decoder: Decoder,
+}
+
+impl<D> core::fmt::Debug for ShardRouter<D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("ShardRouter")
+            .field("shards", &self.shards.len())
+            .field("decoder", &self.decoder)
+            .finish()
+    }
+}
+
+impl<D: AsRef<[u8]> + Clone> ShardRouter<D> {
+    /// Creates a router which forwards datagrams to the `shards`, in order of their index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is empty or contains more than 65536 shards.
+    pub fn new(shards: Vec<mpsc::SyncSender<Routed<D>>>, config_id: ConfigId) -> Self {
+        assert!(!shards.is_empty(), "at least one shard is required");
+        assert!(
+            shards.len() <= u16::MAX as usize + 1,
+            "the shard index must fit in the server ID"
+        );
+
+        let decoder =
+            Decoder::new(config_id, SHARD_SERVER_ID_LEN).expect("server ID length is valid");
+
+        Self { shards, decoder }
+    }
+
+    /// Returns the shards the datagram `payload` is forwarded to
+    pub fn route(&self, payload: &[u8]) -> Route {
+        let connection_id = match destination_connection_id(payload) {
+            Some(connection_id) => connection_id,
+            // datagrams which do not contain a valid packet header are sent to the first
+            // shard to be discarded
+            None => return Route::Shard(0),
+        };
+
+        if connection_id.is_empty() {
+            return Route::AllShards;
+        }
+
+        if let Some(server_id) = self.decoder.decode(connection_id) {
+            let index = u16::from_be_bytes([server_id[0], server_id[1]]) as usize;
+            if index < self.shards.len() {
+                return Route::Shard(index);
+            }
+        }
+
+        if is_client_chosen_connection_id(payload) {
+            return Route::Shard(shard_index(connection_id, self.shard_count()));
+        }
+
+        Route::AllShards
+    }
+
+    /// Forwards the datagram to the shards selected by [`route`](Self::route)
+    ///
+    /// The datagram is handed back if it could not be queued on any of the shards. A
+    /// datagram forwarded to all shards which could not be queued on some of them is never
+    /// reported as rejected by every shard, since one of those shards may own the connection.
+    pub fn dispatch(&self, datagram: D) -> Result<(), D> {
+        let index = match self.route(datagram.as_ref()) {
+            Route::Shard(index) => index,
+            Route::AllShards => return self.broadcast(datagram),
+        };
+
+        let routed = Routed {
+            datagram,
+            pending: None,
+        };
+
+        self.shards[index]
+            .try_send(routed)
+            .map_err(|err| match err {
+                TrySendError::Full(routed) | TrySendError::Disconnected(routed) => routed.datagram,
+            })
+    }
+
+    /// Returns the number of shards
+    #[inline]
+    pub fn shard_count(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.shards.len()).expect("at least one shard is required")
+    }
+
+    fn broadcast(&self, datagram: D) -> Result<(), D> {
+        let pending = Arc::new(AtomicUsize::new(self.shards.len()));
+        let mut queued = false;
+
+        for shard in &self.shards {
+            let routed = Routed {
+                datagram: datagram.clone(),
+                pending: Some(pending.clone()),
+            };
+            queued |= shard.try_send(routed).is_ok();
+        }
+
+        if queued {
+            Ok(())
+        } else {
+            Err(datagram)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL_CONNECTION_ID_LEN: usize = 8;
+
+    fn config_id() -> ConfigId {
+        ConfigId::new(1).unwrap()
+    }
+
+    fn short_packet(connection_id: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0x40];
+        packet.extend_from_slice(connection_id);
+        packet.extend_from_slice(&[0; 32]);
+        packet
+    }
+
+    fn long_packet(connection_id: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0xc0, 0, 0, 0, 1, connection_id.len() as u8];
+        packet.extend_from_slice(connection_id);
+        packet.extend_from_slice(&[0; 32]);
+        packet
+    }
+
+    fn handshake_packet(connection_id: &[u8]) -> Vec<u8> {
+        let mut packet = long_packet(connection_id);
+        packet[0] = 0xe0;
+        packet
+    }
+
+    /// Returns a structured connection ID issued by the shard at `index`
+    fn shard_connection_id(index: u16) -> [u8; LOCAL_CONNECTION_ID_LEN] {
+        let mut connection_id = [0xaa; LOCAL_CONNECTION_ID_LEN];
+        connection_id[0] = config_id().first_octet(LOCAL_CONNECTION_ID_LEN);
+        connection_id[1..=SHARD_SERVER_ID_LEN].copy_from_slice(&shard_server_id(index));
+        connection_id
+    }
+
+    fn router(shard_count: usize) -> (ShardRouter<Vec<u8>>, Vec<mpsc::Receiver<Routed<Vec<u8>>>>) {
+        let (shards, receivers) = (0..shard_count).map(|_| mpsc::sync_channel(16)).unzip();
+        (ShardRouter::new(shards, config_id()), receivers)
+    }
+
+    #[test]
+    fn shard_key_test() {
+        assert_eq!(shard_key(&[0x12, 0x34, 0x56, 0x78, 0xff]), 0x1234 ^ 0x5678);
+        assert_eq!(shard_key(&[0x12, 0x34, 0x56]), 0x1234 ^ 0x5600);
+        assert_eq!(shard_key(&[]), 0);
+    }
+
+    #[test]
+    fn destination_connection_id_test() {
+        let connection_id = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        // the length of short header connection IDs is not known
+        let packet = short_packet(&connection_id);
+        assert_eq!(destination_connection_id(&packet), Some(&packet[1..]));
+        assert_eq!(
+            destination_connection_id(&long_packet(&connection_id[..5])),
+            Some(&connection_id[..5])
+        );
+
+        // truncated packets
+        assert_eq!(destination_connection_id(&[]), None);
+        assert_eq!(destination_connection_id(&[0xc0, 0, 0, 0, 1, 8, 1]), None);
+    }
+
+    #[test]
+    fn route_test() {
+        let (router, _receivers) = router(4);
+
+        // connection IDs issued by a shard are routed to it
+        for index in 0..4 {
+            let connection_id = shard_connection_id(index);
+            let expected = Route::Shard(index as usize);
+            assert_eq!(router.route(&short_packet(&connection_id)), expected);
+            assert_eq!(router.route(&long_packet(&connection_id)), expected);
+            assert_eq!(router.route(&handshake_packet(&connection_id)), expected);
+        }
+
+        // connection IDs chosen by the client are routed to the same shard for every packet
+        let connection_id = shard_connection_id(0xff00);
+        let expected = Route::Shard(shard_index(&connection_id, router.shard_count()));
+        assert_eq!(router.route(&long_packet(&connection_id)), expected);
+        assert_eq!(router.route(&long_packet(&connection_id)), expected);
+
+        // unknown connection IDs are routed to all shards
+        assert_eq!(
+            router.route(&short_packet(&connection_id)),
+            Route::AllShards
+        );
+        assert_eq!(
+            router.route(&handshake_packet(&connection_id)),
+            Route::AllShards
+        );
+        assert_eq!(
+            router.route(&short_packet(&[0xff; LOCAL_CONNECTION_ID_LEN])),
+            Route::AllShards
+        );
+        assert_eq!(
+            router.route(&long_packet(&[1])),
+            Route::Shard(shard_index(&[1], router.shard_count()))
+        );
+        assert_eq!(router.route(&handshake_packet(&[1])), Route::AllShards);
+
+        // a connection ID with a different config rotation is not decoded
+        let mut connection_id = shard_connection_id(1);
+        connection_id[0] = ConfigId::new(2)
+            .unwrap()
+            .first_octet(LOCAL_CONNECTION_ID_LEN);
+        assert_eq!(
+            router.route(&short_packet(&connection_id)),
+            Route::AllShards
+        );
+
+        // zero-length connection IDs are routed to all shards
+        assert_eq!(router.route(&long_packet(&[])), Route::AllShards);
+        assert_eq!(router.route(&[0x40]), Route::AllShards);
+
+        // invalid packets are discarded by the first shard
+        assert_eq!(router.route(&[]), Route::Shard(0));
+    }
+
+    #[test]
+    fn router_dispatch_test() {
+        let (router, receivers) = router(4);
+
+        let packet = short_packet(&shard_connection_id(2));
+        router.dispatch(packet.clone()).unwrap();
+        for (index, receiver) in receivers.iter().enumerate() {
+            match receiver.try_recv() {
+                Ok(routed) => {
+                    assert_eq!(index, 2);
+                    assert_eq!(routed.into_datagram(), packet);
+                }
+                Err(_) => assert_ne!(index, 2),
+            }
+        }
+
+        // an unknown connection ID is sent to every shard and the last shard to reject it
+        // sends the stateless reset
+        let packet = short_packet(&shard_connection_id(0xffff));
+        router.dispatch(packet.clone()).unwrap();
+        let routed: Vec<_> = receivers.iter().map(|r| r.try_recv().unwrap()).collect();
+        assert!(routed.iter().all(|routed| *routed.datagram() == packet));
+        let resets: Vec<_> = routed.into_iter().map(Routed::reject).collect();
+        assert_eq!(resets, [false, false, false, true]);
+
+        // a shard which doesn't own a connection on its own route sends a stateless reset
+        router
+            .dispatch(short_packet(&shard_connection_id(1)))
+            .unwrap();
+        assert!(receivers[1].try_recv().unwrap().reject());
+    }
+
+    #[test]
+    fn router_broadcast_full_queue_test() {
+        let (shards, receivers): (Vec<_>, Vec<_>) = (0..2).map(|_| mpsc::sync_channel(1)).unzip();
+        let router = ShardRouter::new(shards, config_id());
+
+        // fill the queue of the second shard
+        router
+            .dispatch(short_packet(&shard_connection_id(1)))
+            .unwrap();
+
+        let packet = short_packet(&shard_connection_id(0xffff));
+        router.dispatch(packet.clone()).unwrap();
+
+        // the datagram never reached the second shard, which may own the connection
+        assert!(!receivers[0].try_recv().unwrap().reject());
+
+        // the datagram is handed back once no shard has room for it
+        router
+            .dispatch(short_packet(&shard_connection_id(0)))
+            .unwrap();
+        assert_eq!(router.dispatch(packet.clone()), Err(packet));
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Converts s2n-quic events into the data of the corresponding qlog events
+//!
+//! Field names and values follow the QUIC event definitions of
+//! [draft-ietf-quic-qlog-quic-events](https://datatracker.ietf.org/doc/draft-ietf-quic-qlog-quic-events/).
+
+use core::time::Duration;
+use s2n_quic_core::{
+    connection, endpoint,
+    event::{api, Timestamp},
+};
+use serde_json::{json, Map, Value};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+pub fn vantage_point(endpoint_type: &api::EndpointType) -> &'static str {
+    match endpoint_type {
+        api::EndpointType::Server { .. } => "server",
+        api::EndpointType::Client { .. } => "client",
+    }
+}
+
+/// Returns the time of an event in milliseconds
+pub fn time(timestamp: &Timestamp) -> f64 {
+    millis(timestamp.duration_since_start())
+}
+
+pub fn connection_started(event: &api::ConnectionStarted) -> Value {
+    let path = &event.path;
+
+    json!({
+        "ip_version": ip_version(&path.local_addr),
+        "src_ip": ip(&path.local_addr),
+        "dst_ip": ip(&path.remote_addr),
+        "src_port": path.local_addr.port(),
+        "dst_port": path.remote_addr.port(),
+        "src_cid": hex(path.local_cid.bytes),
+        "dst_cid": hex(path.remote_cid.bytes),
+    })
+}
+
+pub fn connection_closed(event: &api::ConnectionClosed) -> Value {
+    let error = event.error;
+    let mut data = Map::new();
+
+    let (owner, trigger) = match error {
+        connection::Error::Closed { initiator, .. } => (Some(initiator), "clean"),
+        connection::Error::Transport {
+            code, initiator, ..
+        } => {
+            data.insert("connection_code".into(), code.as_u64().into());
+            (Some(initiator), "error")
+        }
+        connection::Error::Application {
+            error, initiator, ..
+        } => {
+            data.insert("application_code".into(), u64::from(error).into());
+            (Some(initiator), "application")
+        }
+        connection::Error::StatelessReset { .. } => {
+            (Some(endpoint::Location::Remote), "stateless_reset")
+        }
+        connection::Error::Timeout { reason, .. } => {
+            let trigger = match reason {
+                connection::ConnectionTimeoutReason::IdleTimeout { .. } => "idle_timeout",
+                connection::ConnectionTimeoutReason::HandshakeTimeout { .. } => "handshake_timeout",
+                _ => "error",
+            };
+            (Some(endpoint::Location::Local), trigger)
+        }
+        _ => (None, "error"),
+    };
+
+    if let Some(owner) = owner {
+        data.insert("owner".into(), location(owner).into());
+    }
+    data.insert("trigger".into(), trigger.into());
+    data.insert("reason".into(), error.to_string().into());
+
+    data.into()
+}
+
+pub fn packet(header: &api::PacketHeader) -> Value {
+    json!({ "header": packet_header(header) })
+}
+
+pub fn recovery_metrics(event: &api::RecoveryMetrics) -> Value {
+    json!({
+        "min_rtt": millis(event.min_rtt),
+        "smoothed_rtt": millis(event.smoothed_rtt),
+        "latest_rtt": millis(event.latest_rtt),
+        "rtt_variance": millis(event.rtt_variance),
+        "pto_count": event.pto_count,
+        "congestion_window": event.congestion_window,
+        "bytes_in_flight": event.bytes_in_flight,
+    })
+}
+
+/// Returns the transport parameters the peer sent
+pub fn transport_parameters(params: &api::TransportParameters) -> Value {
+    let mut data = Map::new();
+    data.insert("owner".into(), "remote".into());
+
+    let connection_ids = [
+        (
+            "original_destination_connection_id",
+            &params.original_destination_connection_id,
+        ),
+        (
+            "initial_source_connection_id",
+            &params.initial_source_connection_id,
+        ),
+        (
+            "retry_source_connection_id",
+            &params.retry_source_connection_id,
+        ),
+    ];
+    for (name, id) in connection_ids {
+        if let Some(id) = id {
+            data.insert(name.into(), hex(id.bytes).into());
+        }
+    }
+
+    if let Some(token) = params.stateless_reset_token {
+        data.insert("stateless_reset_token".into(), hex(token).into());
+    }
+
+    if let Some(address) = &params.preferred_address {
+        data.insert("preferred_address".into(), preferred_address(address));
+    }
+
+    let values = json!({
+        "disable_active_migration": !params.migration_support,
+        "max_idle_timeout": params.max_idle_timeout.as_millis() as u64,
+        "max_udp_payload_size": params.max_udp_payload_size,
+        "ack_delay_exponent": params.ack_delay_exponent,
+        "max_ack_delay": params.max_ack_delay.as_millis() as u64,
+        "active_connection_id_limit": params.active_connection_id_limit,
+        "initial_max_stream_data_bidi_local": params.initial_max_stream_data_bidi_local,
+        "initial_max_stream_data_bidi_remote": params.initial_max_stream_data_bidi_remote,
+        "initial_max_stream_data_uni": params.initial_max_stream_data_uni,
+        "initial_max_streams_bidi": params.initial_max_streams_bidi,
+        "initial_max_streams_uni": params.initial_max_streams_uni,
+        "max_datagram_frame_size": params.max_datagram_frame_size,
+    });
+    if let Value::Object(values) = values {
+        data.extend(values);
+    }
+
+    data.into()
+}
+
+fn preferred_address(address: &api::PreferredAddress) -> Value {
+    let mut data = Map::new();
+
+    if let Some(ipv4) = &address.ipv4_address {
+        data.insert("ip_v4".into(), ip(ipv4).into());
+        data.insert("port_v4".into(), ipv4.port().into());
+    }
+    if let Some(ipv6) = &address.ipv6_address {
+        data.insert("ip_v6".into(), ip(ipv6).into());
+        data.insert("port_v6".into(), ipv6.port().into());
+    }
+    data.insert(
+        "connection_id".into(),
+        hex(address.connection_id.bytes).into(),
+    );
+    data.insert(
+        "stateless_reset_token".into(),
+        hex(address.stateless_reset_token).into(),
+    );
+
+    data.into()
+}
+
+fn packet_header(header: &api::PacketHeader) -> Value {
+    let (packet_type, packet_number) = match header {
+        api::PacketHeader::Initial { number, .. } => ("initial", Some(*number)),
+        api::PacketHeader::Handshake { number, .. } => ("handshake", Some(*number)),
+        api::PacketHeader::ZeroRtt { number, .. } => ("0RTT", Some(*number)),
+        api::PacketHeader::OneRtt { number, .. } => ("1RTT", Some(*number)),
+        api::PacketHeader::Retry { .. } => ("retry", None),
+        api::PacketHeader::VersionNegotiation { .. } => ("version_negotiation", None),
+        api::PacketHeader::StatelessReset { .. } => ("stateless_reset", None),
+        _ => ("unknown", None),
+    };
+
+    let mut header = Map::new();
+    header.insert("packet_type".into(), packet_type.into());
+    if let Some(packet_number) = packet_number {
+        header.insert("packet_number".into(), packet_number.into());
+    }
+
+    header.into()
+}
+
+fn ip_version(address: &api::SocketAddress) -> &'static str {
+    match address {
+        api::SocketAddress::IpV6 { .. } => "ipv6",
+        _ => "ipv4",
+    }
+}
+
+fn ip(address: &api::SocketAddress) -> String {
+    match address {
+        api::SocketAddress::IpV4 { ip, .. } => Ipv4Addr::from(**ip).to_string(),
+        api::SocketAddress::IpV6 { ip, .. } => Ipv6Addr::from(**ip).to_string(),
+        _ => String::new(),
+    }
+}
+
+fn location(location: endpoint::Location) -> &'static str {
+    match location {
+        endpoint::Location::Local => "local",
+        endpoint::Location::Remote => "remote",
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::event::{builder, IntoEvent};
+
+    #[test]
+    fn packet_header_test() {
+        let header: api::PacketHeader = builder::PacketHeader::OneRtt { number: 42 }.into_event();
+        assert_eq!(
+            packet(&header),
+            json!({ "header": { "packet_type": "1RTT", "packet_number": 42 } })
+        );
+
+        let header: api::PacketHeader = builder::PacketHeader::Retry { version: 1 }.into_event();
+        assert_eq!(
+            packet(&header),
+            json!({ "header": { "packet_type": "retry" } })
+        );
+    }
+
+    #[test]
+    fn connection_closed_test() {
+        let error = connection::Error::timeout(connection::ConnectionTimeoutReason::IdleTimeout {
+            idle_duration: Duration::from_secs(30),
+        });
+        let event: api::ConnectionClosed = builder::ConnectionClosed { error }.into_event();
+        let data = connection_closed(&event);
+
+        assert_eq!(data["owner"], "local");
+        assert_eq!(data["trigger"], "idle_timeout");
+        assert_eq!(data["reason"], error.to_string());
+    }
+
+    #[test]
+    fn hex_test() {
+        assert_eq!(hex(&[0x00, 0x0f, 0xa0, 0xff]), "000fa0ff");
+        assert_eq!(hex(&[]), "");
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writes s2n-quic events as a [qlog] trace
+//!
+//! [`QlogWriter`] is an event subscriber which serializes connection events in the `JSON-SEQ`
+//! format of the qlog main schema. Each record starts with an ASCII record separator and ends
+//! with a newline, so the trace can be streamed to a file while the endpoint is running and
+//! opened in tools such as [qvis] or Wireshark.
+//!
+//! ```rust,ignore
+//! let qlog = s2n_quic_qlog::QlogWriter::create("server.sqlog")?;
+//!
+//! let server = Server::builder()
+//!     .with_event(qlog)?
+//!     .with_tls((CERT_PEM, KEY_PEM))?
+//!     .with_io("127.0.0.1:4433")?
+//!     .start()?;
+//! ```
+//!
+//! The following events are written:
+//!
+//! * `connectivity:connection_started`
+//! * `connectivity:connection_closed`
+//! * `transport:packet_sent`
+//! * `transport:packet_received`
+//! * `transport:parameters_set`
+//! * `recovery:packet_lost`
+//! * `recovery:metrics_updated`
+//!
+//! [qlog]: https://datatracker.ietf.org/doc/draft-ietf-quic-qlog-main-schema/
+//! [qvis]: https://qvis.quictools.info
+
+use s2n_quic_core::event::{api, Subscriber};
+use serde_json::{json, Value};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+mod events;
+
+/// The version of the qlog main schema the trace follows
+pub const QLOG_VERSION: &str = "0.3";
+
+/// The ASCII record separator which starts every JSON-SEQ record
+const RECORD_SEPARATOR: u8 = 0x1e;
+
+/// Writes connection events in the qlog JSON-SEQ format
+///
+/// All of the connections of an endpoint are written to the same trace. Each event carries the
+/// internal id of its connection as the qlog `group_id`, which tools use to tell the
+/// connections apart. The vantage point of the trace is taken from the first event, so a writer
+/// should only be used by a single endpoint.
+///
+/// Clones of the writer share the same output, which allows the trace to be flushed or read
+/// back after the writer was handed to the endpoint.
+pub struct QlogWriter<W> {
+    output: Arc<Mutex<Output<W>>>,
+}
+
+impl<W> Clone for QlogWriter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            output: self.output.clone(),
+        }
+    }
+}
+
+impl QlogWriter<BufWriter<File>> {
+    /// Creates a writer which writes the trace to a new file at `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> QlogWriter<W> {
+    /// Creates a writer which writes the trace to `writer`
+    pub fn new(writer: W) -> Self {
+        Self::with_title(writer, "s2n-quic")
+    }
+
+    /// Creates a writer with the given trace `title`
+    pub fn with_title<T: Into<String>>(writer: W, title: T) -> Self {
+        let output = Output {
+            writer,
+            title: title.into(),
+            header_written: false,
+            error: None,
+        };
+
+        Self {
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    /// Calls `f` with the underlying writer
+    ///
+    /// This can be used to read the trace back from an in-memory buffer.
+    pub fn with_writer<F: FnOnce(&mut W) -> R, R>(&self, f: F) -> R {
+        f(&mut self.output.lock().unwrap().writer)
+    }
+
+    /// Flushes the underlying writer
+    ///
+    /// Returns the first error encountered while writing a record, if any. No records are
+    /// written after an error.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut output = self.output.lock().unwrap();
+
+        if let Some(error) = output.error.take() {
+            return Err(error);
+        }
+
+        output.writer.flush()
+    }
+
+    #[inline]
+    fn write_event(&self, meta: &api::ConnectionMeta, name: &str, data: Value) {
+        self.output.lock().unwrap().write_event(meta, name, data);
+    }
+}
+
+struct Output<W> {
+    writer: W,
+    title: String,
+    header_written: bool,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Output<W> {
+    fn write_event(&mut self, meta: &api::ConnectionMeta, name: &str, data: Value) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if !self.header_written {
+            self.header_written = true;
+
+            let header = json!({
+                "qlog_version": QLOG_VERSION,
+                "qlog_format": "JSON-SEQ",
+                "title": self.title,
+                "trace": {
+                    "vantage_point": {
+                        "name": "s2n-quic",
+                        "type": events::vantage_point(&meta.endpoint_type),
+                    },
+                    "common_fields": {
+                        "time_format": "relative",
+                    },
+                },
+            });
+            self.write_record(&header);
+        }
+
+        let record = json!({
+            "time": events::time(&meta.timestamp),
+            "name": name,
+            "group_id": meta.id.to_string(),
+            "data": data,
+        });
+        self.write_record(&record);
+    }
+
+    fn write_record(&mut self, record: &Value) {
+        let writer = &mut self.writer;
+        let result = writer
+            .write_all(&[RECORD_SEPARATOR])
+            .and_then(|_| serde_json::to_writer(&mut *writer, record).map_err(io::Error::from))
+            .and_then(|_| writer.write_all(b"\n"));
+
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<W: 'static + Write + Send> Subscriber for QlogWriter<W> {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &api::ConnectionMeta,
+        _info: &api::ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_connection_started(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::ConnectionStarted,
+    ) {
+        self.write_event(
+            meta,
+            "connectivity:connection_started",
+            events::connection_started(event),
+        );
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::ConnectionClosed,
+    ) {
+        self.write_event(
+            meta,
+            "connectivity:connection_closed",
+            events::connection_closed(event),
+        );
+    }
+
+    fn on_packet_sent(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::PacketSent,
+    ) {
+        self.write_event(
+            meta,
+            "transport:packet_sent",
+            events::packet(&event.packet_header),
+        );
+    }
+
+    fn on_packet_received(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::PacketReceived,
+    ) {
+        self.write_event(
+            meta,
+            "transport:packet_received",
+            events::packet(&event.packet_header),
+        );
+    }
+
+    fn on_packet_lost(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::PacketLost,
+    ) {
+        self.write_event(
+            meta,
+            "recovery:packet_lost",
+            events::packet(&event.packet_header),
+        );
+    }
+
+    fn on_recovery_metrics(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::RecoveryMetrics,
+    ) {
+        self.write_event(
+            meta,
+            "recovery:metrics_updated",
+            events::recovery_metrics(event),
+        );
+    }
+
+    fn on_transport_parameters_received(
+        &mut self,
+        _context: &mut Self::ConnectionContext,
+        meta: &api::ConnectionMeta,
+        event: &api::TransportParametersReceived,
+    ) {
+        self.write_event(
+            meta,
+            "transport:parameters_set",
+            events::transport_parameters(&event.transport_parameters),
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use jsonschema::JSONSchema;
+use s2n_quic::{
+    client::Connect,
+    provider::io::testing::{primary, test, Model},
+    Client, Server,
+};
+use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+use s2n_quic_qlog::QlogWriter;
+use serde_json::Value;
+use std::{collections::HashSet, path::Path};
+
+static SCHEMA: &str = include_str!("../schema/qlog.schema.json");
+
+/// Runs a connection which transfers some data on a stream before the server closes it
+fn run(qlog: QlogWriter<impl 'static + std::io::Write + Send>) {
+    test(Model::default(), |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls((certificates::CERT_PEM, certificates::KEY_PEM))?
+            .with_event(qlog)?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                stream.send(chunk).await.unwrap();
+            }
+
+            connection.close(123u8.into());
+        });
+
+        let client = Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+            let mut data = Data::new(100_000);
+            while let Some(chunk) = data.send_one(usize::MAX) {
+                stream.send(chunk).await.unwrap();
+            }
+            stream.finish().unwrap();
+
+            // read the echoed data until the server closes the connection
+            while let Ok(Some(_)) = stream.receive().await {}
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Splits a JSON-SEQ trace into its records
+fn records(trace: &[u8]) -> Vec<Value> {
+    assert_eq!(
+        trace.first(),
+        Some(&0x1e),
+        "records start with a record separator"
+    );
+
+    trace
+        .split(|byte| *byte == 0x1e)
+        .skip(1)
+        .map(|record| {
+            assert_eq!(record.last(), Some(&b'\n'), "records end with a newline");
+            serde_json::from_slice(record).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn server_trace_test() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("s2n-quic-qlog-server.sqlog");
+    let qlog = QlogWriter::create(&path).unwrap();
+
+    run(qlog.clone());
+    qlog.flush().unwrap();
+
+    let trace = std::fs::read(&path).unwrap();
+    let records = records(&trace);
+
+    let schema = serde_json::from_str(SCHEMA).unwrap();
+    let schema = JSONSchema::compile(&schema).unwrap();
+
+    for record in &records {
+        if let Err(errors) = schema.validate(record) {
+            let errors: Vec<_> = errors.map(|error| error.to_string()).collect();
+            panic!("invalid record {}: {:?}", record, errors);
+        }
+    }
+
+    let header = &records[0];
+    assert_eq!(header["qlog_format"], "JSON-SEQ");
+    assert_eq!(header["trace"]["vantage_point"]["type"], "server");
+
+    let names: HashSet<_> = records[1..]
+        .iter()
+        .map(|record| record["name"].as_str().unwrap())
+        .collect();
+
+    // packet_lost is not expected since the network doesn't drop any packets
+    for name in [
+        "connectivity:connection_started",
+        "connectivity:connection_closed",
+        "transport:packet_sent",
+        "transport:packet_received",
+        "transport:parameters_set",
+        "recovery:metrics_updated",
+    ] {
+        assert!(names.contains(name), "missing {}", name);
+    }
+
+    let closed = records
+        .iter()
+        .find(|record| record["name"] == "connectivity:connection_closed")
+        .unwrap();
+    assert_eq!(closed["data"]["owner"], "local");
+    assert_eq!(closed["data"]["trigger"], "application");
+    assert_eq!(closed["data"]["application_code"], 123);
+
+    // the events are written in the order they occurred
+    let times: Vec<_> = records[1..]
+        .iter()
+        .map(|record| record["time"].as_f64().unwrap())
+        .collect();
+    assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn buffer_test() {
+    let qlog = QlogWriter::new(Vec::new());
+
+    run(qlog.clone());
+
+    let records = qlog.with_writer(|trace| records(trace));
+    assert!(records.len() > 1);
+    assert_eq!(records[0]["title"], "s2n-quic");
+
+    // all of the events belong to the same connection
+    let group_id = &records[1]["group_id"];
+    assert!(records[1..]
+        .iter()
+        .all(|record| &record["group_id"] == group_id));
+}
@@ -1,7 +1,11 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{certificate, encode_transport_parameters, session::Session};
+use crate::{
+    certificate, encode_transport_parameters,
+    session::Session,
+    validator::{CertificateValidator, Verifier},
+};
 use core::convert::TryFrom;
 use rustls::{quic, ClientConfig};
 use s2n_codec::EncoderValue;
@@ -83,6 +87,8 @@ pub struct Builder {
     cert_store: rustls::RootCertStore,
     application_protocols: Vec<Vec<u8>>,
     key_log: Option<Arc<dyn rustls::KeyLog>>,
+    certificate_validator: Option<Arc<dyn CertificateValidator>>,
+    client_identity: Option<(certificate::Certificate, certificate::PrivateKey)>,
 }
 
 impl Default for Builder {
@@ -97,6 +103,8 @@ impl Builder {
             cert_store: rustls::RootCertStore::empty(),
             application_protocols: vec![b"h3".to_vec()],
             key_log: None,
+            certificate_validator: None,
+            client_identity: None,
         }
     }
 
@@ -133,6 +141,31 @@ impl Builder {
         Ok(self)
     }
 
+    /// Sets a validator which is called after the server's certificate chain has been
+    /// validated against the trusted root certificates
+    pub fn with_certificate_validator<V: CertificateValidator>(
+        mut self,
+        validator: V,
+    ) -> Result<Self, rustls::Error> {
+        self.certificate_validator = Some(Arc::new(validator));
+        Ok(self)
+    }
+
+    /// Sets the certificate the client authenticates with when the server requires mutual TLS
+    pub fn with_client_identity<
+        C: certificate::IntoCertificate,
+        PK: certificate::IntoPrivateKey,
+    >(
+        mut self,
+        certificate: C,
+        private_key: PK,
+    ) -> Result<Self, rustls::Error> {
+        let certificate = certificate.into_certificate()?;
+        let private_key = private_key.into_private_key()?;
+        self.client_identity = Some((certificate, private_key));
+        Ok(self)
+    }
+
     pub fn build(self) -> Result<Client, rustls::Error> {
         // TODO load system root store?
         if self.cert_store.is_empty() {
@@ -143,12 +176,25 @@ impl Builder {
             ));
         }
 
-        let mut config = ClientConfig::builder()
+        let config = ClientConfig::builder()
             .with_cipher_suites(crate::cipher_suite::DEFAULT_CIPHERSUITES)
             .with_safe_default_kx_groups()
-            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?
-            .with_root_certificates(self.cert_store)
-            .with_no_client_auth();
+            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?;
+
+        let config = if let Some(validator) = self.certificate_validator {
+            config.with_custom_certificate_verifier(Arc::new(Verifier::new(
+                self.cert_store,
+                validator,
+            )))
+        } else {
+            config.with_root_certificates(self.cert_store)
+        };
+
+        let mut config = if let Some((certificate, private_key)) = self.client_identity {
+            config.with_single_cert(certificate.0, private_key.0)?
+        } else {
+            config.with_no_client_auth()
+        };
 
         config.max_fragment_size = None;
         config.alpn_protocols = self.application_protocols;
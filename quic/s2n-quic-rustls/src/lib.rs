@@ -8,6 +8,7 @@ pub use rustls::{self, Certificate, PrivateKey};
 mod cipher_suite;
 mod error;
 mod session;
+mod validator;
 
 pub mod certificate;
 pub mod client;
@@ -15,6 +16,7 @@ pub mod server;
 
 pub use client::Client;
 pub use server::Server;
+pub use validator::{CertificateValidator, ClientCertificateValidator, ValidationError};
 
 //= https://www.rfc-editor.org/rfc/rfc9001#section-4.2
 //# Clients MUST NOT offer TLS versions older than 1.3.
@@ -1,7 +1,10 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{certificate, encode_transport_parameters, session::Session};
+use crate::{
+    certificate, encode_transport_parameters, session::Session,
+    validator::ClientCertificateValidator,
+};
 use rustls::{quic, ServerConfig};
 use s2n_codec::EncoderValue;
 use s2n_quic_core::{application::ServerName, crypto::tls};
@@ -9,12 +12,14 @@ use std::sync::Arc;
 
 pub struct Server {
     config: Arc<ServerConfig>,
+    client_certificate_validator: Option<Arc<dyn ClientCertificateValidator>>,
 }
 
 impl Server {
     pub fn new(config: ServerConfig) -> Self {
         Self {
             config: Arc::new(config),
+            client_certificate_validator: None,
         }
     }
 
@@ -58,6 +63,7 @@ impl tls::Endpoint for Server {
         .expect("could not create rustls server session");
 
         Session::new(session.into(), None)
+            .with_client_certificate_validator(self.client_certificate_validator.clone())
     }
 
     fn new_client_session<Params: EncoderValue>(
@@ -77,6 +83,8 @@ pub struct Builder {
     cert_resolver: Option<Arc<dyn rustls::server::ResolvesServerCert>>,
     application_protocols: Vec<Vec<u8>>,
     key_log: Option<Arc<dyn rustls::KeyLog>>,
+    client_cert_store: Option<rustls::RootCertStore>,
+    client_certificate_validator: Option<Arc<dyn ClientCertificateValidator>>,
 }
 
 impl Default for Builder {
@@ -91,6 +99,8 @@ impl Builder {
             cert_resolver: None,
             application_protocols: vec![b"h3".to_vec()],
             key_log: None,
+            client_cert_store: None,
+            client_certificate_validator: None,
         }
     }
 
@@ -127,12 +137,54 @@ impl Builder {
         Ok(self)
     }
 
+    /// Requires clients to authenticate with a certificate issued by the trusted `certificate`
+    /// (mutual TLS)
+    pub fn with_client_authentication<C: certificate::IntoCertificate>(
+        mut self,
+        certificate: C,
+    ) -> Result<Self, rustls::Error> {
+        let certificates = certificate.into_certificate()?;
+        let cert_store = self
+            .client_cert_store
+            .get_or_insert_with(rustls::RootCertStore::empty);
+        for certificate in certificates.0.iter() {
+            cert_store
+                .add(certificate)
+                .map_err(|err| rustls::Error::General(err.to_string()))?;
+        }
+        Ok(self)
+    }
+
+    /// Sets a validator which is called with the client's certificate chain after the
+    /// TLS handshake has completed
+    ///
+    /// Connections with a rejected chain are closed with a QUIC `APPLICATION_ERROR`. Client
+    /// authentication must be enabled with [`Self::with_client_authentication`].
+    pub fn with_client_certificate_validator<V: ClientCertificateValidator>(
+        mut self,
+        validator: V,
+    ) -> Result<Self, rustls::Error> {
+        self.client_certificate_validator = Some(Arc::new(validator));
+        Ok(self)
+    }
+
     pub fn build(self) -> Result<Server, rustls::Error> {
         let builder = ServerConfig::builder()
             .with_cipher_suites(crate::cipher_suite::DEFAULT_CIPHERSUITES)
             .with_safe_default_kx_groups()
-            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?
-            .with_no_client_auth();
+            .with_protocol_versions(crate::PROTOCOL_VERSIONS)?;
+
+        let builder = if let Some(cert_store) = self.client_cert_store {
+            builder.with_client_cert_verifier(rustls::server::AllowAnyAuthenticatedClient::new(
+                cert_store,
+            ))
+        } else if self.client_certificate_validator.is_some() {
+            return Err(rustls::Error::General(
+                "Client certificate validator requires client authentication".to_string(),
+            ));
+        } else {
+            builder.with_no_client_auth()
+        };
 
         let mut config = if let Some(cert_resolver) = self.cert_resolver {
             builder.with_cert_resolver(cert_resolver)
@@ -150,7 +202,9 @@ impl Builder {
             config.key_log = key_log;
         }
 
-        Ok(Server::new(config))
+        let mut server = Server::new(config);
+        server.client_certificate_validator = self.client_certificate_validator;
+        Ok(server)
     }
 }
 
@@ -1,8 +1,9 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::cipher_suite::{
-    HeaderProtectionKey, HeaderProtectionKeys, OneRttKey, PacketKey, PacketKeys,
+use crate::{
+    cipher_suite::{HeaderProtectionKey, HeaderProtectionKeys, OneRttKey, PacketKey, PacketKeys},
+    validator::ClientCertificateValidator,
 };
 use bytes::Bytes;
 use core::{fmt, fmt::Debug, task::Poll};
@@ -15,6 +16,7 @@ use s2n_quic_core::{
     crypto::{self, tls, CryptoError},
     transport,
 };
+use std::sync::Arc;
 
 pub struct Session {
     connection: Connection,
@@ -25,6 +27,7 @@ pub struct Session {
     emitted_server_name: bool,
     emitted_application_protocol: bool,
     server_name: Option<ServerName>,
+    client_certificate_validator: Option<Arc<dyn ClientCertificateValidator>>,
 }
 
 impl fmt::Debug for Session {
@@ -47,9 +50,35 @@ impl Session {
             emitted_server_name: false,
             emitted_application_protocol: false,
             server_name,
+            client_certificate_validator: None,
         }
     }
 
+    /// Sets the validator which is applied to the client's certificate chain once the
+    /// handshake has completed
+    pub fn with_client_certificate_validator(
+        mut self,
+        validator: Option<Arc<dyn ClientCertificateValidator>>,
+    ) -> Self {
+        self.client_certificate_validator = validator;
+        self
+    }
+
+    /// Applies the application's policy to the certificate chain of the client
+    fn validate_client_certificate(&self) -> Result<(), transport::Error> {
+        if let Some(validator) = self.client_certificate_validator.as_ref() {
+            let chain = self.connection.peer_certificates().unwrap_or_default();
+
+            // the handshake itself succeeded so the rejection is reported as an application
+            // error instead of a TLS alert
+            validator.validate(chain).map_err(|_| {
+                transport::Error::APPLICATION_ERROR.with_reason("client certificate was rejected")
+            })?;
+        }
+
+        Ok(())
+    }
+
     fn receive(&mut self, crypto_data: &[u8]) -> Result<(), transport::Error> {
         self.connection
             .read_hs(crypto_data)
@@ -148,6 +177,7 @@ impl Session {
 
             // the handshake is complete!
             if !self.emitted_handshake_complete {
+                self.validate_client_certificate()?;
                 self.rx_phase.transition();
                 context.on_handshake_complete()?;
             }
@@ -0,0 +1,289 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks for applying custom validation to the certificate chain presented by the peer
+//!
+//! The [`CertificateValidator`] is called after rustls has validated the chain against
+//! the configured root certificates, so it can only further restrict which chains are
+//! accepted. Common uses include pinning the leaf certificate or its public key, or
+//! consulting an external trust store.
+//!
+//! The [`ClientCertificateValidator`] is called by the server once the TLS handshake with a
+//! client using mutual TLS has succeeded. Rejecting the certificate closes the connection with
+//! a QUIC `APPLICATION_ERROR` rather than a TLS alert, which lets applications apply their own
+//! policy, such as an allow list, on top of the TLS level authentication.
+
+use rustls::{
+    client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier, WebPkiVerifier},
+    Certificate, DigitallySignedStruct, ServerName,
+};
+use std::{fmt, sync::Arc, time::SystemTime};
+
+/// Performs additional validation of the certificate chain presented by the server
+pub trait CertificateValidator: 'static + Send + Sync {
+    /// Validates the certificate `chain` for the given `server_name`
+    ///
+    /// The first certificate in the chain is the end-entity certificate, followed by
+    /// any intermediate certificates sent by the server. The chain has already been
+    /// validated against the configured root certificates.
+    fn validate(&self, chain: &[Certificate], server_name: &str) -> Result<(), ValidationError>;
+}
+
+/// Performs application level validation of the certificate chain presented by a client
+pub trait ClientCertificateValidator: 'static + Send + Sync {
+    /// Validates the certificate `chain` of a client which completed the TLS handshake
+    ///
+    /// The first certificate in the chain is the end-entity certificate, followed by
+    /// any intermediate certificates sent by the client. The chain has already been
+    /// validated against the trusted client root certificates.
+    fn validate(&self, chain: &[Certificate]) -> Result<(), ValidationError>;
+}
+
+/// The error returned by a [`CertificateValidator`] when rejecting a certificate chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    reason: String,
+}
+
+impl ValidationError {
+    /// Creates a new `ValidationError` with the given reason
+    pub fn new<R: Into<String>>(reason: R) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the reason the chain was rejected
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "certificate validation failed: {}", self.reason)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A `ServerCertVerifier` which applies a [`CertificateValidator`] after the default
+/// webpki validation
+pub(crate) struct Verifier {
+    inner: WebPkiVerifier,
+    validator: Arc<dyn CertificateValidator>,
+}
+
+impl Verifier {
+    pub fn new(roots: rustls::RootCertStore, validator: Arc<dyn CertificateValidator>) -> Self {
+        Self {
+            inner: WebPkiVerifier::new(roots, None),
+            validator,
+        }
+    }
+}
+
+impl ServerCertVerifier for Verifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )?;
+
+        let mut chain = Vec::with_capacity(intermediates.len() + 1);
+        chain.push(end_entity.clone());
+        chain.extend_from_slice(intermediates);
+
+        let server_name = match server_name {
+            ServerName::DnsName(name) => name.as_ref(),
+            _ => "",
+        };
+
+        self.validator
+            .validate(&chain, server_name)
+            .map_err(|err| rustls::Error::InvalidCertificateData(err.to_string()))?;
+
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client, server};
+    use s2n_quic_core::crypto::tls::{self, testing::certificates::*};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<(usize, String)>>,
+        reject: bool,
+    }
+
+    impl CertificateValidator for Arc<Recorder> {
+        fn validate(
+            &self,
+            chain: &[Certificate],
+            server_name: &str,
+        ) -> Result<(), ValidationError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((chain.len(), server_name.to_string()));
+
+            if self.reject {
+                return Err(ValidationError::new("rejected by test"));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn handshake(validator: Arc<Recorder>) -> Result<(), s2n_quic_core::transport::Error> {
+        let mut client = client::Builder::new()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_certificate_validator(validator)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut server = server::Builder::new()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut pair = tls::testing::Pair::new(&mut server, &mut client, "localhost".into());
+
+        while pair.is_handshaking() {
+            pair.poll(None)?;
+        }
+
+        pair.finish();
+
+        Ok(())
+    }
+
+    #[test]
+    fn validator_accept_test() {
+        let validator = Arc::new(Recorder::default());
+
+        handshake(validator.clone()).unwrap();
+
+        let calls = validator.calls.lock().unwrap();
+        assert_eq!(*calls, vec![(1, "localhost".to_string())]);
+    }
+
+    #[test]
+    fn validator_reject_test() {
+        let validator = Arc::new(Recorder {
+            reject: true,
+            ..Default::default()
+        });
+
+        assert!(handshake(validator.clone()).is_err());
+        assert_eq!(validator.calls.lock().unwrap().len(), 1);
+    }
+
+    /// Only accepts clients presenting one of the allowed certificates
+    struct AllowList(Vec<Certificate>);
+
+    impl ClientCertificateValidator for AllowList {
+        fn validate(&self, chain: &[Certificate]) -> Result<(), ValidationError> {
+            match chain.first() {
+                Some(certificate) if self.0.contains(certificate) => Ok(()),
+                _ => Err(ValidationError::new("client is not in the allow list")),
+            }
+        }
+    }
+
+    fn mutual_handshake(allowed: &str) -> Result<(), s2n_quic_core::transport::Error> {
+        use crate::certificate::IntoCertificate;
+
+        let mut client = client::Builder::new()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_client_identity(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let allowed = allowed.into_certificate().unwrap().0;
+        let mut server = server::Builder::new()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_client_authentication(CERT_PEM)
+            .unwrap()
+            .with_client_certificate_validator(AllowList(allowed))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut pair = tls::testing::Pair::new(&mut server, &mut client, "localhost".into());
+
+        while pair.is_handshaking() {
+            pair.poll(None)?;
+        }
+
+        pair.finish();
+
+        Ok(())
+    }
+
+    #[test]
+    fn client_validator_accept_test() {
+        mutual_handshake(CERT_PEM).unwrap();
+    }
+
+    /// The client's certificate is trusted by TLS but rejected by the application's policy
+    #[test]
+    fn client_validator_reject_test() {
+        let error = mutual_handshake(UNTRUSTED_CERT_PEM).unwrap_err();
+        assert_eq!(
+            error.code,
+            s2n_quic_core::transport::Error::APPLICATION_ERROR.code
+        );
+    }
+
+    #[test]
+    fn client_validator_requires_client_authentication_test() {
+        assert!(server::Builder::new()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_client_certificate_validator(AllowList(vec![]))
+            .unwrap()
+            .build()
+            .is_err());
+    }
+}
@@ -255,10 +255,14 @@ impl event::Subscriber for Events {
                     context.application_error = Some(error);
                 }
             }
-            connection::Error::IdleTimerExpired { .. } => context.idle_timer_error = true,
-            connection::Error::MaxHandshakeDurationExceeded { .. } => {
-                context.handshake_duration_exceeded_error = true
-            }
+            connection::Error::Timeout {
+                reason: connection::ConnectionTimeoutReason::IdleTimeout { .. },
+                ..
+            } => context.idle_timer_error = true,
+            connection::Error::Timeout {
+                reason: connection::ConnectionTimeoutReason::HandshakeTimeout { .. },
+                ..
+            } => context.handshake_duration_exceeded_error = true,
             _ => context.unspecified_error = true,
         }
     }
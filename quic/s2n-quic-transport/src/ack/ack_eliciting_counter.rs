@@ -0,0 +1,92 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_quic_core::counter::{Counter, Saturating};
+
+/// The maximum number of consecutive non-ack-eliciting packets sent before a PING
+/// frame is bundled into the next packet, regardless of the configured
+/// `ack_elicitation_interval`
+pub const MAX_NON_ACK_ELICITING_COUNT: u8 = 10;
+
+/// Tracks the number of consecutive non-ack-eliciting packets sent
+///
+/// An endpoint that only sends ACK frames never has ack-eliciting packets in flight, so
+/// the peer has no reason to acknowledge them and the loss detection timer is never armed.
+/// The counter is used to periodically bundle a PING frame to elicit an acknowledgement.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AckElicitingCounter {
+    count: Counter<u8, Saturating>,
+}
+
+impl AckElicitingCounter {
+    /// Returns true if the next packet should be made ack-eliciting
+    ///
+    /// The `interval` is capped at `MAX_NON_ACK_ELICITING_COUNT`.
+    #[inline]
+    pub fn should_elicit(&self, interval: u8) -> bool {
+        self.count >= interval.min(MAX_NON_ACK_ELICITING_COUNT)
+    }
+
+    /// Called after a packet has been sent
+    #[inline]
+    pub fn on_transmit(&mut self, is_ack_eliciting: bool) {
+        if is_ack_eliciting {
+            self.count = Counter::new(0);
+        } else {
+            self.count += 1;
+        }
+    }
+
+    /// Returns the number of consecutive non-ack-eliciting packets sent
+    #[cfg(test)]
+    pub fn count(&self) -> u8 {
+        *self.count
+    }
+
+    #[cfg(test)]
+    pub fn new(count: u8) -> Self {
+        Self {
+            count: Counter::new(count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_elicit_test() {
+        let mut counter = AckElicitingCounter::default();
+
+        for _ in 0..4 {
+            assert!(!counter.should_elicit(4));
+            counter.on_transmit(false);
+        }
+        assert!(counter.should_elicit(4));
+
+        counter.on_transmit(true);
+        assert_eq!(0, counter.count());
+        assert!(!counter.should_elicit(4));
+    }
+
+    #[test]
+    fn max_non_ack_eliciting_count_test() {
+        let mut counter = AckElicitingCounter::default();
+
+        for _ in 0..MAX_NON_ACK_ELICITING_COUNT {
+            assert!(!counter.should_elicit(u8::MAX));
+            counter.on_transmit(false);
+        }
+
+        // the configured interval is capped
+        assert!(counter.should_elicit(u8::MAX));
+    }
+
+    #[test]
+    fn saturating_test() {
+        let mut counter = AckElicitingCounter::new(u8::MAX);
+        counter.on_transmit(false);
+        assert_eq!(u8::MAX, counter.count());
+    }
+}
@@ -3,6 +3,7 @@
 
 use crate::{
     ack::{
+        ack_eliciting_counter::AckElicitingCounter,
         ack_eliciting_transmission::{AckElicitingTransmission, AckElicitingTransmissionSet},
         ack_ranges::{AckRanges, AckRangesError},
         ack_transmission_state::AckTransmissionState,
@@ -11,6 +12,7 @@ use crate::{
     processed_packet::ProcessedPacket,
     transmission,
 };
+use s2n_codec::EncoderValue as _;
 use s2n_quic_core::{
     ack,
     counter::{Counter, Saturating},
@@ -19,7 +21,10 @@ use s2n_quic_core::{
         builder::{AckAction, AckProcessed},
         IntoEvent as _,
     },
-    frame::{ack::EcnCounts, Ack, Ping},
+    frame::{
+        ack::{AckRanges as _, EcnCounts},
+        Ack, Ping,
+    },
     packet::number::{PacketNumber, PacketNumberSpace},
     time::{timer, Timer, Timestamp},
     varint::VarInt,
@@ -65,7 +70,7 @@ pub struct AckManager {
     processed_packets_since_transmission: Counter<u8, Saturating>,
 
     /// The number of transmissions since the last ACK-eliciting packet was sent
-    transmissions_since_elicitation: Counter<u8, Saturating>,
+    ack_eliciting_counter: AckElicitingCounter,
 
     /// Used to transition through transmission/retransmission states
     transmission_state: AckTransmissionState,
@@ -85,7 +90,7 @@ impl AckManager {
                 .new_packet_number(VarInt::from_u8(0)),
             largest_received_packet_number_at: None,
             processed_packets_since_transmission: Counter::new(0),
-            transmissions_since_elicitation: Counter::new(0),
+            ack_eliciting_counter: AckElicitingCounter::default(),
             transmission_state: AckTransmissionState::default(),
             ecn_counts: EcnCounts::default(),
         }
@@ -110,13 +115,68 @@ impl AckManager {
         //# Even if an endpoint does not set an ECT field on packets it sends,
         //# the endpoint MUST provide feedback about ECN markings it receives, if
         //# these are accessible.
-        context
-            .write_frame(&Ack {
-                ack_delay,
-                ack_ranges: &self.ack_ranges,
-                ecn_counts: self.ecn_counts.as_option(),
-            })
-            .is_some()
+        let mut frame = Ack {
+            ack_delay,
+            ack_ranges: self.ack_ranges.truncated(1),
+            ecn_counts: self.ecn_counts.as_option(),
+        };
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.3
+        //# A receiver can discard unacknowledged ACK
+        //# Ranges to limit ACK frame size, at the cost of increased
+        //# retransmissions from the sender.  This is necessary if an ACK frame
+        //# would be too large to fit in a packet.  Receivers MAY also limit ACK
+        //# frame size further to preserve space for other frames or to limit the
+        //# capacity that acknowledgments consume.
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.3
+        //# A receiver SHOULD include an ACK Range containing the largest
+        //# received packet number in every ACK frame.
+
+        // Leave out the oldest ranges if the frame would exceed the configured size. The range
+        // with the largest packet number is always kept.
+        //
+        // The omitted ranges are not sent again: they are discarded along with the rest of the
+        // ranges once the peer acknowledges a packet carrying the frame.
+        let ack_range_count = self.ack_range_count(frame.encoding_size());
+        frame.ack_ranges = self.ack_ranges.truncated(ack_range_count);
+
+        context.write_frame(&frame).is_some()
+    }
+
+    /// Returns the number of ranges, starting with the largest packet numbers, that fit in an
+    /// ACK frame of `max_ack_frame_size`
+    ///
+    /// `frame_size` is the encoded size of the frame with only the first range.
+    fn ack_range_count(&self, mut frame_size: usize) -> usize {
+        let max_ack_frame_size = self.ack_settings.max_ack_frame_size as usize;
+        let mut ranges = (&self.ack_ranges).ack_ranges();
+        let mut smallest = match ranges.next() {
+            Some(range) => *range.start(),
+            None => return 0,
+        };
+        let mut count = 1;
+
+        for range in ranges {
+            let (start, end) = range.into_inner();
+            let gap = smallest - end - 2;
+            let ack_range = end - start;
+
+            // the ACK Range Count field may need more bytes to encode the additional range
+            let prev_count = VarInt::try_from(count - 1).expect("count fits in a VarInt");
+            let next_count = VarInt::try_from(count).expect("count fits in a VarInt");
+            frame_size += next_count.encoding_size() - prev_count.encoding_size();
+            frame_size += gap.encoding_size() + ack_range.encoding_size();
+
+            if frame_size > max_ack_frame_size {
+                break;
+            }
+
+            smallest = start;
+            count += 1;
+        }
+
+        count
     }
 
     /// Called after an outgoing packet is assembled and `on_transmit` returned `true`
@@ -138,25 +198,23 @@ impl AckManager {
             // retransmission that is not ack eliciting will not help us recover faster.
             if (context.transmission_constraint().can_transmit()
                 || context.transmission_constraint().can_retransmit())
-                && self.transmissions_since_elicitation
-                    >= self.ack_settings.ack_elicitation_interval
+                && self
+                    .ack_eliciting_counter
+                    .should_elicit(self.ack_settings.ack_elicitation_interval)
                 && context.write_frame(&Ping).is_some()
             {
                 is_ack_eliciting = true;
-            } else {
-                self.transmissions_since_elicitation += 1;
             }
         }
 
+        self.ack_eliciting_counter.on_transmit(is_ack_eliciting);
+
         self.largest_received_packet_number_acked = self
             .ack_ranges
             .max_value()
             .expect("transmission_state should be Disabled while ack_ranges is empty");
 
         if is_ack_eliciting {
-            // reset the counter
-            self.transmissions_since_elicitation = Counter::new(0);
-
             //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.4
             //# When a packet containing an ACK frame is sent, the Largest
             //# Acknowledged field in that frame can be saved.
@@ -398,6 +456,7 @@ impl transmission::interest::Provider for AckManager {
 mod tests {
     use super::{super::tests::*, *};
     use crate::{
+        ack::ack_eliciting_counter::MAX_NON_ACK_ELICITING_COUNT,
         contexts::testing::{MockWriteContext, OutgoingFrameBuffer},
         path::{path_event, testing::helper_path_server},
     };
@@ -554,8 +613,8 @@ mod tests {
             )
             .is_ok());
         manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
-        manager.transmissions_since_elicitation =
-            Counter::new(ack::Settings::EARLY.ack_elicitation_interval);
+        manager.ack_eliciting_counter =
+            AckElicitingCounter::new(ack::Settings::EARLY.ack_elicitation_interval);
 
         manager.on_transmit_complete(&mut write_context);
 
@@ -570,8 +629,8 @@ mod tests {
         );
 
         manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
-        manager.transmissions_since_elicitation =
-            Counter::new(ack::Settings::EARLY.ack_elicitation_interval);
+        manager.ack_eliciting_counter =
+            AckElicitingCounter::new(ack::Settings::EARLY.ack_elicitation_interval);
         write_context.frame_buffer.clear();
         write_context.transmission_constraint = transmission::Constraint::CongestionLimited;
 
@@ -582,8 +641,8 @@ mod tests {
         );
 
         manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
-        manager.transmissions_since_elicitation =
-            Counter::new(ack::Settings::EARLY.ack_elicitation_interval);
+        manager.ack_eliciting_counter =
+            AckElicitingCounter::new(ack::Settings::EARLY.ack_elicitation_interval);
         write_context.frame_buffer.clear();
         write_context.transmission_constraint = transmission::Constraint::RetransmissionOnly;
 
@@ -621,14 +680,128 @@ mod tests {
             )
             .is_ok());
         manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
-        manager.transmissions_since_elicitation = Counter::new(u8::max_value());
+        manager.ack_eliciting_counter = AckElicitingCounter::new(u8::max_value());
 
         manager.on_transmit_complete(&mut write_context);
 
-        assert_eq!(
-            manager.transmissions_since_elicitation,
-            Counter::new(u8::max_value())
+        assert_eq!(manager.ack_eliciting_counter.count(), u8::max_value());
+    }
+
+    /// A connection that only sends ACK frames bundles a PING at least every
+    /// `MAX_NON_ACK_ELICITING_COUNT` packets, even if the configured interval is larger
+    #[test]
+    fn ack_only_transmissions_elicit_acks() {
+        let ack_settings = ack::Settings {
+            ack_elicitation_interval: u8::MAX,
+            ..Default::default()
+        };
+        let mut manager = AckManager::new(PacketNumberSpace::ApplicationData, ack_settings);
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            s2n_quic_platform::time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
         );
+
+        manager.ack_ranges = AckRanges::default();
+        assert!(manager
+            .ack_ranges
+            .insert_packet_number(
+                PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(1)),
+            )
+            .is_ok());
+
+        let mut pings = 0;
+        let mut non_ack_eliciting = 0;
+
+        for _ in 0..100 {
+            manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
+            write_context.frame_buffer.clear();
+
+            manager.on_transmit_complete(&mut write_context);
+
+            if write_context.frame_buffer.is_empty() {
+                non_ack_eliciting += 1;
+                assert!(non_ack_eliciting <= MAX_NON_ACK_ELICITING_COUNT);
+            } else {
+                assert_eq!(
+                    write_context
+                        .frame_buffer
+                        .pop_front()
+                        .expect("Frame is written")
+                        .as_frame(),
+                    Frame::Ping(ping::Ping),
+                );
+                assert_eq!(non_ack_eliciting, MAX_NON_ACK_ELICITING_COUNT);
+                non_ack_eliciting = 0;
+                pings += 1;
+            }
+        }
+
+        assert_eq!(pings, 100 / (MAX_NON_ACK_ELICITING_COUNT as usize + 1));
+    }
+
+    /// ACK frames that would exceed `max_ack_frame_size` leave out the ranges with the
+    /// smallest packet numbers
+    #[test]
+    fn max_ack_frame_size_test() {
+        const MAX_ACK_FRAME_SIZE: u16 = 32;
+
+        let space = PacketNumberSpace::ApplicationData;
+        let ack_settings = ack::Settings {
+            ack_ranges_limit: 100,
+            max_ack_frame_size: MAX_ACK_FRAME_SIZE,
+            ..Default::default()
+        };
+        let mut manager = AckManager::new(space, ack_settings);
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            s2n_quic_platform::time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
+        );
+
+        // every other packet is received, which results in 50 ranges
+        for packet_number in (0..100u8).step_by(2) {
+            assert!(manager
+                .ack_ranges
+                .insert_packet_number(space.new_packet_number(VarInt::from_u8(packet_number)))
+                .is_ok());
+        }
+        assert_eq!(50, manager.ack_ranges.interval_len());
+        manager.transmission_state = AckTransmissionState::Active { retransmissions: 0 };
+
+        assert!(manager.on_transmit(&mut write_context));
+
+        let mut written = write_context
+            .frame_buffer
+            .pop_front()
+            .expect("Frame is written");
+        assert!(written.data.len() <= MAX_ACK_FRAME_SIZE as usize);
+
+        if let Frame::Ack(frame) = written.as_frame() {
+            assert_eq!(VarInt::from_u8(98), frame.largest_acknowledged());
+
+            let ranges: Vec<_> = frame.ack_ranges().collect();
+            assert!(ranges.len() > 1 && ranges.len() < 50);
+
+            // the most recent ranges are kept
+            let expected = (0..100u8).step_by(2).rev().map(|packet_number| {
+                VarInt::from_u8(packet_number)..=VarInt::from_u8(packet_number)
+            });
+            assert!(ranges
+                .into_iter()
+                .eq(expected.take(frame.ack_ranges().len())));
+        } else {
+            panic!("expected an ACK frame");
+        }
+
+        // the stored ranges are kept until a packet carrying the frame is acknowledged
+        assert_eq!(50, manager.ack_ranges.interval_len());
     }
 
     #[test]
@@ -104,6 +104,12 @@ impl AckRanges {
             _ => 0,
         }
     }
+
+    /// Returns a view of the `len` ranges with the largest packet numbers
+    #[inline]
+    pub fn truncated(&self, len: usize) -> TruncatedAckRanges<'_> {
+        TruncatedAckRanges { ranges: self, len }
+    }
 }
 
 type AckRangesIter<'a> = core::iter::Map<
@@ -122,6 +128,23 @@ impl<'a> ack::AckRanges for &'a AckRanges {
     }
 }
 
+/// The ranges with the largest packet numbers of an `AckRanges`
+///
+/// This is used to reduce the size of an ACK frame by leaving out the oldest ranges.
+#[derive(Clone, Copy, Debug)]
+pub struct TruncatedAckRanges<'a> {
+    ranges: &'a AckRanges,
+    len: usize,
+}
+
+impl<'a> ack::AckRanges for TruncatedAckRanges<'a> {
+    type Iter = core::iter::Take<AckRangesIter<'a>>;
+
+    fn ack_ranges(&self) -> Self::Iter {
+        ack::AckRanges::ack_ranges(&self.ranges).take(self.len)
+    }
+}
+
 impl Deref for AckRanges {
     type Target = IntervalSet<PacketNumber>;
 
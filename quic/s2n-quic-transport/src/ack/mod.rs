@@ -4,6 +4,7 @@
 pub use ack_manager::*;
 pub use s2n_quic_core::ack::*;
 
+mod ack_eliciting_counter;
 mod ack_eliciting_transmission;
 mod ack_manager;
 pub(crate) mod ack_ranges;
@@ -4,21 +4,29 @@
 //! Defines the public QUIC connection API
 
 use crate::{
-    connection::{self, ConnectionApi, OpenToken},
+    connection::{self, ConnectionApi, ConnectionApiProvider, OpenToken},
     stream::{ops, Stream, StreamError, StreamId},
 };
+use alloc::sync::{Arc, Weak};
 use bytes::Bytes;
 use core::{
     fmt,
     sync::atomic::{self, Ordering},
     task::{Context, Poll},
+    time::Duration,
 };
 use s2n_quic_core::{
     application,
     application::ServerName,
+    connection::Histogram,
     event::query::{Query, QueryMut},
     inet::SocketAddress,
-    stream::StreamType,
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+    },
+    stream::{StreamMetricsTotals, StreamPriority, StreamType, WriteAmplification},
+    transport,
 };
 
 /// A QUIC connection
@@ -157,6 +165,16 @@ impl Connection {
         self.api.poll_request(stream_id, request, context)
     }
 
+    #[inline]
+    pub fn donate_credits(
+        &self,
+        from: StreamId,
+        to: StreamId,
+        amount: u64,
+    ) -> Result<(), StreamError> {
+        self.api.donate_credits(from, to, amount)
+    }
+
     /// Closes the Connection with the provided error code
     ///
     /// This will immediately terminate all outstanding streams.
@@ -165,6 +183,24 @@ impl Connection {
         self.api.close_connection(Some(error_code));
     }
 
+    /// Closes the Connection with the provided transport error
+    ///
+    /// This is used for errors which the application detects on behalf of the transport, such
+    /// as a negotiated application protocol which it doesn't support.
+    #[inline]
+    #[doc(hidden)]
+    pub fn close_with_transport_error(&self, error: transport::Error) {
+        self.api.close_connection_with_transport_error(error);
+    }
+
+    /// Gracefully closes the Connection and polls for it to finish closing
+    ///
+    /// Outstanding streams are flushed before the CONNECTION_CLOSE frame is sent.
+    #[inline]
+    pub fn poll_close_and_flush(&self, context: &Context) -> Poll<()> {
+        self.api.poll_close_and_flush(context)
+    }
+
     #[inline]
     pub fn server_name(&self) -> Result<Option<ServerName>, connection::Error> {
         self.api.server_name()
@@ -189,6 +225,95 @@ impl Connection {
         self.api.keep_alive(enabled)
     }
 
+    #[inline]
+    pub fn probe_bandwidth(&self, duration: Duration) -> Result<(), connection::Error> {
+        self.api.probe_bandwidth(duration)
+    }
+
+    #[inline]
+    pub fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error> {
+        self.api.bandwidth_estimate()
+    }
+
+    #[inline]
+    pub fn set_send_bandwidth(&self, bandwidth: Bandwidth) -> Result<(), connection::Error> {
+        self.api.set_send_bandwidth(bandwidth)
+    }
+
+    #[inline]
+    pub fn loss_rates(&self) -> LossRates {
+        self.api.loss_rates()
+    }
+
+    #[inline]
+    pub fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        self.api.rtt_histogram()
+    }
+
+    #[inline]
+    pub fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        self.api.throughput_histogram()
+    }
+
+    #[inline]
+    pub fn reset_histograms(&self) -> Result<(), connection::Error> {
+        self.api.reset_histograms()
+    }
+
+    #[inline]
+    pub fn recv_budget_remaining(&self) -> Result<u64, connection::Error> {
+        self.api.recv_budget_remaining()
+    }
+
+    #[inline]
+    pub fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error> {
+        self.api.stream_metrics()
+    }
+
+    #[inline]
+    pub fn write_amplification(&self) -> Result<WriteAmplification, connection::Error> {
+        self.api.write_amplification()
+    }
+
+    #[inline]
+    pub fn reset_stats(&self) -> Result<(), connection::Error> {
+        self.api.reset_stats()
+    }
+
+    #[inline]
+    pub fn stream_write_amplification(
+        &self,
+        stream_id: StreamId,
+    ) -> Result<WriteAmplification, StreamError> {
+        self.api.stream_write_amplification(stream_id)
+    }
+
+    #[inline]
+    pub fn set_stream_priority(
+        &self,
+        stream_id: StreamId,
+        priority: StreamPriority,
+    ) -> Result<(), StreamError> {
+        self.api.set_stream_priority(stream_id, priority)
+    }
+
+    #[inline]
+    pub fn active_stream_count(&self) -> Result<usize, connection::Error> {
+        self.api.active_stream_count()
+    }
+
+    /// Returns the number of application handles to the connection, including this one
+    #[inline]
+    pub fn handle_count(&self) -> usize {
+        self.api.application_handle_count().load(Ordering::Acquire)
+    }
+
+    /// Returns a reference to the connection which doesn't keep it open
+    #[inline]
+    pub fn downgrade(&self) -> WeakConnection {
+        WeakConnection(Arc::downgrade(&self.api))
+    }
+
     #[inline]
     pub fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api.local_address()
@@ -217,3 +342,40 @@ impl Connection {
         self.api.datagram_mut(query)
     }
 }
+
+/// A reference to a connection which doesn't keep it open
+///
+/// The connection is closed once all of its [`Connection`] handles are dropped, regardless of
+/// any outstanding weak references.
+#[derive(Clone)]
+pub struct WeakConnection(Weak<dyn ConnectionApiProvider>);
+
+impl fmt::Debug for WeakConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakConnection").finish()
+    }
+}
+
+impl WeakConnection {
+    /// Returns a new handle to the connection, or `None` if all of its handles were dropped
+    pub fn upgrade(&self) -> Option<Connection> {
+        let api = self.0.upgrade()?;
+
+        // Only add a handle while another one is still alive. Once the count drops to zero, the
+        // connection is closed and can't be handed out again.
+        api.application_handle_count()
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |count| {
+                if count == 0 {
+                    None
+                } else {
+                    Some(count + 1)
+                }
+            })
+            .ok()?;
+
+        Some(Connection {
+            api,
+            open_token: OpenToken::new(),
+        })
+    }
+}
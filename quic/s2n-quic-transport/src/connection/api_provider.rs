@@ -13,13 +13,20 @@ use bytes::Bytes;
 use core::{
     sync::atomic::AtomicUsize,
     task::{Context, Poll},
+    time::Duration,
 };
 use s2n_quic_core::{
     application,
     application::ServerName,
+    connection::Histogram,
     event::query::{Query, QueryMut},
     inet::SocketAddress,
-    stream::{ops, StreamId, StreamType},
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+    },
+    stream::{ops, StreamId, StreamMetricsTotals, StreamPriority, StreamType, WriteAmplification},
+    transport,
 };
 
 /// A dynamically dispatched connection API
@@ -37,6 +44,8 @@ pub(crate) trait ConnectionApiProvider: Sync + Send {
         context: Option<&Context>,
     ) -> Result<ops::Response, StreamError>;
 
+    fn donate_credits(&self, from: StreamId, to: StreamId, amount: u64) -> Result<(), StreamError>;
+
     fn poll_accept(
         &self,
         arc_self: &Arc<dyn ConnectionApiProvider>,
@@ -54,6 +63,10 @@ pub(crate) trait ConnectionApiProvider: Sync + Send {
 
     fn close_connection(&self, code: Option<application::Error>);
 
+    fn close_connection_with_transport_error(&self, error: transport::Error);
+
+    fn poll_close_and_flush(&self, context: &Context) -> Poll<()>;
+
     fn server_name(&self) -> Result<Option<ServerName>, connection::Error>;
 
     fn application_protocol(&self) -> Result<Bytes, connection::Error>;
@@ -64,6 +77,42 @@ pub(crate) trait ConnectionApiProvider: Sync + Send {
 
     fn keep_alive(&self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn probe_bandwidth(&self, duration: Duration) -> Result<(), connection::Error>;
+
+    fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error>;
+
+    fn set_send_bandwidth(&self, bandwidth: Bandwidth) -> Result<(), connection::Error>;
+
+    /// Returns the packet loss rates without acquiring the connection lock
+    fn loss_rates(&self) -> LossRates;
+
+    fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error>;
+
+    fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error>;
+
+    fn reset_histograms(&self) -> Result<(), connection::Error>;
+
+    fn recv_budget_remaining(&self) -> Result<u64, connection::Error>;
+
+    fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error>;
+
+    fn write_amplification(&self) -> Result<WriteAmplification, connection::Error>;
+
+    fn reset_stats(&self) -> Result<(), connection::Error>;
+
+    fn stream_write_amplification(
+        &self,
+        stream_id: StreamId,
+    ) -> Result<WriteAmplification, StreamError>;
+
+    fn set_stream_priority(
+        &self,
+        stream_id: StreamId,
+        priority: StreamPriority,
+    ) -> Result<(), StreamError>;
+
+    fn active_stream_count(&self) -> Result<usize, connection::Error>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
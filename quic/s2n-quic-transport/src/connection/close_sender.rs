@@ -263,6 +263,14 @@ enum TransmissionState {
     Transmitting,
 }
 
+/// The number of datagrams which need to be received before the first response
+///
+/// The CONNECTION_CLOSE packet is sent once when the connection is closed, so the peer only
+/// needs another copy if that one was lost. Using the same value as `kPacketThreshold` means
+/// the closing connection responds to no more than one in three datagrams, which prevents it
+/// from being used to reflect traffic.
+const INITIAL_RESPONSE_FACTOR: u8 = 3;
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.1
 //# An endpoint SHOULD limit the rate at which it generates packets in
 //# the closing state.  For instance, an endpoint could wait for a
@@ -278,7 +286,7 @@ struct Limiter {
 impl Default for Limiter {
     fn default() -> Self {
         Self {
-            factor: Counter::new(1),
+            factor: Counter::new(INITIAL_RESPONSE_FACTOR),
             received: Counter::new(0),
             debounce: Timer::default(),
         }
@@ -415,7 +423,7 @@ mod tests {
         let mut clock = Clock::default();
         let rtt = Duration::from_millis(250);
 
-        for count in (0..10).map(|v| 2usize.pow(v)) {
+        for count in (0..10).map(|v| INITIAL_RESPONSE_FACTOR as usize * 2usize.pow(v)) {
             for _ in 0..(count - 1) {
                 limiter.on_datagram_received(rtt, clock.get_time());
             }
@@ -431,4 +439,52 @@ mod tests {
             assert!(limiter.on_timeout(clock.get_time()).is_ready());
         }
     }
+
+    #[test]
+    fn response_rate_test() {
+        let mut sender = CloseSender::default();
+        let mut clock = Clock::default();
+        let mut path = helper_path_server();
+        let mut buffer = [0; MINIMUM_MTU as usize];
+        let mut publisher = Publisher::no_snapshot();
+        let rtt = Duration::from_millis(10);
+
+        path.on_handshake_packet();
+        path.on_closing();
+        sender.close(PACKET.clone(), Duration::from_secs(10), clock.get_time());
+
+        // the CONNECTION_CLOSE is sent immediately
+        assert!(sender.can_transmit(path.transmission_constraint()));
+        let _ = sender
+            .transmission(&mut path, clock.get_time(), &mut publisher)
+            .write_payload(tx::PayloadBuffer::new(&mut buffer), 0);
+
+        let mut received = 0;
+        let mut transmission_count = 0;
+
+        // the peer keeps sending packets which don't get through to it
+        for _ in 0..500 {
+            clock.inc_by(rtt);
+            assert!(sender.on_timeout(clock.get_time()).is_pending());
+
+            sender.on_datagram_received(rtt, clock.get_time());
+            received += 1;
+
+            if sender.can_transmit(path.transmission_constraint()) {
+                let _ = sender
+                    .transmission(&mut path, clock.get_time(), &mut publisher)
+                    .write_payload(tx::PayloadBuffer::new(&mut buffer), 0);
+                transmission_count += 1;
+            }
+
+            assert!(
+                transmission_count * INITIAL_RESPONSE_FACTOR as usize <= received,
+                "{} responses to {} datagrams",
+                transmission_count,
+                received
+            );
+        }
+
+        assert!(transmission_count > 0);
+    }
 }
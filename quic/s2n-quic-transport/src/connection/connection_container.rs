@@ -24,8 +24,9 @@ use core::{
     marker::PhantomData,
     ops::Deref,
     pin::Pin,
-    sync::atomic::AtomicUsize,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
     task::{Context, Poll},
+    time::Duration,
 };
 use intrusive_collections::{
     intrusive_adapter, KeyAdapter, LinkedList, LinkedListLink, RBTree, RBTreeLink,
@@ -33,12 +34,18 @@ use intrusive_collections::{
 use s2n_quic_core::{
     application,
     application::ServerName,
+    connection::Histogram,
     event::{
         query::{Query, QueryMut},
         supervisor,
     },
     inet::SocketAddress,
-    recovery::K_GRANULARITY,
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+        K_GRANULARITY,
+    },
+    stream::{StreamMetricsTotals, StreamPriority, WriteAmplification},
     time::Timestamp,
     transport,
 };
@@ -93,6 +100,9 @@ struct ConnectionNode<C: connection::Trait, L: connection::Lock<C>> {
     timeout: Cell<Option<Timestamp>>,
     /// The count of outstanding application handles
     application_handle_count: AtomicUsize,
+    /// The loss rates of the connection, cached after each interaction so they can be read
+    /// without acquiring the lock
+    loss_rates: SharedLossRates,
     /// The inner connection type
     _connection: PhantomData<C>,
 }
@@ -113,10 +123,17 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionNode<C, L> {
             waiting_for_timeout_link: RBTreeLink::new(),
             timeout: Cell::new(None),
             application_handle_count: AtomicUsize::new(0),
+            loss_rates: SharedLossRates::default(),
             _connection: PhantomData,
         }
     }
 
+    /// Caches the current loss rates of the connection
+    #[inline]
+    fn update_loss_rates(&self, connection: &C) {
+        self.loss_rates.store(connection.loss_rates());
+    }
+
     /// Obtains a `Arc<ConnectionNode>` from a `&ConnectionNode`.
     ///
     /// This method is only safe to be called if the `ConnectionNode` is known to be
@@ -234,6 +251,31 @@ impl<'a, C: connection::Trait, L: connection::Lock<C>> KeyAdapter<'a>
     }
 }
 
+/// Loss rates which are shared with the application without a lock
+#[derive(Debug, Default)]
+struct SharedLossRates {
+    packet_loss_rate: AtomicU64,
+    burst_loss_rate: AtomicU64,
+}
+
+impl SharedLossRates {
+    #[inline]
+    fn load(&self) -> LossRates {
+        LossRates {
+            packet_loss_rate: f64::from_bits(self.packet_loss_rate.load(Ordering::Relaxed)),
+            burst_loss_rate: f64::from_bits(self.burst_loss_rate.load(Ordering::Relaxed)),
+        }
+    }
+
+    #[inline]
+    fn store(&self, rates: LossRates) {
+        self.packet_loss_rate
+            .store(rates.packet_loss_rate.to_bits(), Ordering::Relaxed);
+        self.burst_loss_rate
+            .store(rates.burst_loss_rate.to_bits(), Ordering::Relaxed);
+    }
+}
+
 /// Safety: ConnectionNode uses connection::Lock to ensure all cross-thread access is synchronized
 unsafe impl<C: connection::Trait, L: connection::Lock<C>> Sync for ConnectionNode<C, L> {}
 
@@ -251,6 +293,15 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_write_call(|conn| conn.poll_stream_request(stream_id, request, context))
     }
 
+    fn donate_credits(
+        &self,
+        from: stream::StreamId,
+        to: stream::StreamId,
+        amount: u64,
+    ) -> Result<(), stream::StreamError> {
+        self.api_write_call(|conn| conn.donate_stream_credits(from, to, amount))
+    }
+
     fn poll_accept(
         &self,
         arc_self: &ConnectionApi,
@@ -303,6 +354,22 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         });
     }
 
+    fn close_connection_with_transport_error(&self, error: transport::Error) {
+        let _: Result<(), connection::Error> = self.api_write_call(|conn| {
+            conn.transport_close(error);
+            Ok(())
+        });
+    }
+
+    fn poll_close_and_flush(&self, context: &Context) -> Poll<()> {
+        // If the connection is no longer available it has already been closed
+        self.api_poll_call(|conn| {
+            conn.poll_close_and_flush(context)
+                .map(Ok::<_, connection::Error>)
+        })
+        .map(|_| ())
+    }
+
     fn server_name(&self) -> Result<Option<ServerName>, connection::Error> {
         self.api_read_call(|conn| Ok(conn.server_name()))
     }
@@ -323,6 +390,69 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_write_call(|conn| conn.keep_alive(enabled))
     }
 
+    fn probe_bandwidth(&self, duration: Duration) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.probe_bandwidth(duration))
+    }
+
+    fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error> {
+        self.api_read_call(|conn| conn.bandwidth_estimate())
+    }
+
+    fn set_send_bandwidth(&self, bandwidth: Bandwidth) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.set_send_bandwidth(bandwidth))
+    }
+
+    fn loss_rates(&self) -> LossRates {
+        self.loss_rates.load()
+    }
+
+    fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        self.api_read_call(|conn| conn.rtt_histogram())
+    }
+
+    fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        self.api_read_call(|conn| conn.throughput_histogram())
+    }
+
+    fn reset_histograms(&self) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.reset_histograms())
+    }
+
+    fn recv_budget_remaining(&self) -> Result<u64, connection::Error> {
+        self.api_read_call(|conn| conn.recv_budget_remaining())
+    }
+
+    fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error> {
+        self.api_read_call(|conn| conn.stream_metrics())
+    }
+
+    fn write_amplification(&self) -> Result<WriteAmplification, connection::Error> {
+        self.api_read_call(|conn| conn.write_amplification())
+    }
+
+    fn reset_stats(&self) -> Result<(), connection::Error> {
+        self.api_write_call(|conn| conn.reset_stats())
+    }
+
+    fn stream_write_amplification(
+        &self,
+        stream_id: stream::StreamId,
+    ) -> Result<WriteAmplification, stream::StreamError> {
+        self.api_write_call(|conn| conn.stream_write_amplification(stream_id))
+    }
+
+    fn set_stream_priority(
+        &self,
+        stream_id: stream::StreamId,
+        priority: StreamPriority,
+    ) -> Result<(), stream::StreamError> {
+        self.api_write_call(|conn| conn.set_stream_priority(stream_id, priority))
+    }
+
+    fn active_stream_count(&self) -> Result<usize, connection::Error> {
+        self.api_read_call(|conn| conn.active_stream_count())
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api_read_call(|conn| conn.local_address())
     }
@@ -627,6 +757,7 @@ macro_rules! iterate_interruptible {
             let (result, interests) = match connection.inner.write(|conn| {
                 let result = $func(conn);
                 let interests = conn.interests();
+                connection.update_loss_rates(conn);
                 (result, interests)
             }) {
                 Ok(result) => result,
@@ -840,6 +971,7 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
         let (result, interests) = match node.inner.write(|conn| {
             let result = func(conn);
             let interests = conn.interests();
+            node.update_loss_rates(conn);
             (result, interests)
         }) {
             Ok(result) => result,
@@ -996,6 +1128,7 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
                     conn.is_handshaking(),
                 );
                 func(conn, &context);
+                connection.update_loss_rates(conn);
                 conn.interests()
             }) {
                 Ok(result) => result,
@@ -17,7 +17,9 @@ use core::{
     time::Duration,
 };
 use s2n_quic_core::{
-    application, event,
+    application,
+    connection::Histogram,
+    event,
     event::builder::DatagramDropReason,
     inet::{DatagramInfo, SocketAddress},
     io::tx,
@@ -30,7 +32,13 @@ use s2n_quic_core::{
         zero_rtt::ProtectedZeroRtt,
     },
     path::MaxMtu,
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+    },
+    stream::{StreamMetricsTotals, StreamPriority, WriteAmplification},
     time::{Timer, Timestamp},
+    transport,
 };
 use std::sync::Mutex;
 
@@ -256,6 +264,15 @@ impl connection::Trait for TestConnection {
         todo!()
     }
 
+    fn donate_stream_credits(
+        &mut self,
+        _from: stream::StreamId,
+        _to: stream::StreamId,
+        _amount: u64,
+    ) -> Result<(), stream::StreamError> {
+        todo!()
+    }
+
     fn poll_accept_stream(
         &mut self,
         _stream_type: Option<stream::StreamType>,
@@ -277,6 +294,14 @@ impl connection::Trait for TestConnection {
         // no-op
     }
 
+    fn transport_close(&mut self, _error: transport::Error) {
+        // no-op
+    }
+
+    fn poll_close_and_flush(&mut self, _context: &Context) -> Poll<()> {
+        todo!()
+    }
+
     fn server_name(&self) -> Option<ServerName> {
         todo!()
     }
@@ -293,6 +318,69 @@ impl connection::Trait for TestConnection {
         todo!()
     }
 
+    fn probe_bandwidth(&mut self, _duration: Duration) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error> {
+        todo!()
+    }
+
+    fn set_send_bandwidth(&mut self, _bandwidth: Bandwidth) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn loss_rates(&self) -> LossRates {
+        LossRates::default()
+    }
+
+    fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        todo!()
+    }
+
+    fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        todo!()
+    }
+
+    fn reset_histograms(&mut self) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn recv_budget_remaining(&self) -> Result<u64, connection::Error> {
+        todo!()
+    }
+
+    fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error> {
+        todo!()
+    }
+
+    fn write_amplification(&self) -> Result<WriteAmplification, connection::Error> {
+        todo!()
+    }
+
+    fn reset_stats(&mut self) -> Result<(), connection::Error> {
+        todo!()
+    }
+
+    fn stream_write_amplification(
+        &mut self,
+        _stream_id: stream::StreamId,
+    ) -> Result<WriteAmplification, stream::StreamError> {
+        todo!()
+    }
+
+    fn set_stream_priority(
+        &mut self,
+        _stream_id: stream::StreamId,
+        _priority: StreamPriority,
+    ) -> Result<(), stream::StreamError> {
+        todo!()
+    }
+
+    fn active_stream_count(&self) -> Result<usize, connection::Error> {
+        todo!()
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         todo!()
     }
@@ -21,7 +21,7 @@ use crate::{
     recovery::{recovery_event, RttEstimator},
     space::{PacketSpace, PacketSpaceManager},
     stream, transmission,
-    transmission::interest::Provider as _,
+    transmission::{application::HandshakeProbeSuppressor, interest::Provider as _},
     wakeup_queue::WakeupHandle,
 };
 use alloc::sync::Arc;
@@ -34,7 +34,7 @@ use core::{
 use s2n_quic_core::{
     application,
     application::ServerName,
-    connection::{id::Generator as _, InitialId, PeerId},
+    connection::{id::Generator as _, Histogram, InitialId, PeerId},
     crypto::{tls, CryptoSuite},
     datagram::{Receiver, Sender},
     event::{
@@ -54,8 +54,13 @@ use s2n_quic_core::{
         zero_rtt::ProtectedZeroRtt,
     },
     path::{Handle as _, MaxMtu},
-    recovery::CongestionController,
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+        CongestionController,
+    },
     stateless_reset::token::Generator as _,
+    stream::{StreamMetricsTotals, StreamPriority, WriteAmplification},
     time::{timer, Timestamp},
     transport,
 };
@@ -100,11 +105,17 @@ enum ConnectionState {
 impl From<connection::Error> for ConnectionState {
     fn from(error: connection::Error) -> Self {
         match error {
-            connection::Error::IdleTimerExpired { .. } => {
+            connection::Error::Timeout {
+                reason: connection::ConnectionTimeoutReason::IdleTimeout { .. },
+                ..
+            } => {
                 // If the idle timer expired we directly move into the final state
                 ConnectionState::Finished
             }
-            connection::Error::NoValidPath { .. } => {
+            connection::Error::Timeout {
+                reason: connection::ConnectionTimeoutReason::MigrationTimeout,
+                ..
+            } => {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-9
                 //# When an endpoint has no validated path on which to send packets, it
                 //# MAY discard connection state.
@@ -175,6 +186,8 @@ pub struct ConnectionImpl<Config: endpoint::Config> {
     wakeup_handle: Arc<WakeupHandle<InternalConnectionId>>,
     /// A Waker to the connection.
     waker: Waker,
+    /// The waker of the application task waiting for the connection to finish closing
+    close_waker: Option<Waker>,
     event_context: EventContext<Config>,
 }
 
@@ -254,6 +267,7 @@ macro_rules! transmission_context {
             outcome: $outcome,
             ecn,
             min_packet_len: None,
+            traffic_shaping: transmission::Shaping::new($self.limits.traffic_shaping()),
             transmission_mode: $transmission_mode,
             publisher: &mut $self.event_context.publisher($timestamp, $subscriber),
             packet_interceptor: $packet_interceptor,
@@ -364,8 +378,7 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
         //# received and processed successfully.
         if let Some(duration) = self.get_idle_timer_duration() {
             self.timers
-                .peer_idle_timer
-                .set(packet.datagram.timestamp + duration);
+                .restart_peer_idle_timer(packet.datagram.timestamp, duration);
             self.timers.reset_peer_idle_timer_on_send = true;
         }
 
@@ -397,7 +410,7 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
         // reset the value back to `false` after reading it
         if core::mem::take(&mut self.timers.reset_peer_idle_timer_on_send) {
             if let Some(duration) = self.get_idle_timer_duration() {
-                self.timers.peer_idle_timer.set(timestamp + duration);
+                self.timers.restart_peer_idle_timer(timestamp, duration);
             }
         }
     }
@@ -449,6 +462,7 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
                         local_id_registry: &mut self.local_id_registry,
                         outcome,
                         min_packet_len: None,
+                        traffic_shaping: transmission::Shaping::new(self.limits.traffic_shaping()),
                         ecn,
                         transmission_mode,
                         publisher: &mut self.event_context.publisher(timestamp, subscriber),
@@ -543,6 +557,19 @@ impl<Config: endpoint::Config> ConnectionImpl<Config> {
 
         Poll::Pending
     }
+
+    /// Records an error raised through the application API
+    ///
+    /// The endpoint closes the connection with the error on the next poll.
+    fn close_streams(&mut self, error: connection::Error) {
+        self.error = Err(error);
+
+        // Reset all of the streams right away so tasks blocked on a stream observe the error
+        // without waiting for the endpoint to close the connection
+        if let Some((space, _)) = self.space_manager.application_mut() {
+            space.stream_manager.close(error);
+        }
+    }
 }
 
 impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
@@ -567,7 +594,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         // Assume clients validate the server's address implicitly.
         let peer_validated = Self::Config::ENDPOINT_TYPE.is_server();
 
-        let initial_path = path::Path::new(
+        let mut initial_path = path::Path::new(
             parameters.path_handle,
             parameters.peer_connection_id,
             parameters.local_connection_id,
@@ -576,6 +603,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             peer_validated,
             parameters.max_mtu,
         );
+        initial_path.set_amplification_factor(parameters.limits.amplification_factor());
 
         let path_manager = path::Manager::new(initial_path, parameters.peer_id_registry);
 
@@ -613,6 +641,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             space_manager: parameters.space_manager,
             wakeup_handle,
             waker,
+            close_waker: None,
             event_context,
         };
 
@@ -658,6 +687,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .timers
             .max_handshake_duration_timer
             .set(parameters.timestamp + connection.limits.max_handshake_duration());
+        connection.timers.handshake_start = Some(parameters.timestamp);
 
         Ok(connection)
     }
@@ -708,7 +738,8 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         //# connection error MUST use a CONNECTION_CLOSE frame if it is able.
 
         let remote_address = self.path_manager.active_path().remote_address();
-        let close_context = s2n_quic_core::connection::close::Context::new(&remote_address);
+        let close_context = s2n_quic_core::connection::close::Context::new(&remote_address)
+            .with_endpoint_type(Config::ENDPOINT_TYPE);
         let active_path_id = self.path_manager.active_path_id();
 
         if let Some((early_connection_close, connection_close)) =
@@ -769,6 +800,11 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             space.datagram_manager.receiver.on_connection_error(error);
         }
 
+        // Notify the application that the connection has finished closing
+        if let Some(waker) = self.close_waker.take() {
+            waker.wake();
+        }
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.1
         //# In the closing state, an endpoint retains only enough information to
         //# generate a packet containing a CONNECTION_CLOSE frame and to identify
@@ -839,10 +875,12 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
                 let mut outcome = transmission::Outcome::default();
                 let path_id = self.path_manager.active_path_id();
 
-                // Send an MTU probe if necessary and the handshake has completed
+                // Send an MTU probe if necessary and the handshake has been confirmed
                 // MTU probes are prioritized over other data so they are not blocked by the
                 // congestion controller, as they are critical to achieving maximum throughput.
                 if self.state == ConnectionState::Active
+                    && !HandshakeProbeSuppressor::new(self.space_manager.is_handshake_confirmed())
+                        .is_suppressed()
                     && self.path_manager.active_path().can_transmit(timestamp)
                     && self
                         .path_manager
@@ -1023,8 +1061,11 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .is_ready()
         {
             debug_assert_eq!(ConnectionState::Handshaking, self.state);
-            return Err(connection::Error::max_handshake_duration_exceeded(
-                self.limits.max_handshake_duration(),
+            let elapsed = self.timers.handshake_start.map_or(Duration::ZERO, |start| {
+                timestamp.saturating_duration_since(start)
+            });
+            return Err(connection::Error::timeout(
+                connection::ConnectionTimeoutReason::HandshakeTimeout { elapsed },
             ));
         }
 
@@ -1034,7 +1075,15 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .poll_expiration(timestamp)
             .is_ready()
         {
-            return Err(connection::Error::idle_timer_expired());
+            let idle_duration = self
+                .timers
+                .peer_idle_timer_restarted
+                .map_or(Duration::ZERO, |restarted| {
+                    timestamp.saturating_duration_since(restarted)
+                });
+            return Err(connection::Error::timeout(
+                connection::ConnectionTimeoutReason::IdleTimeout { idle_duration },
+            ));
         }
 
         if self
@@ -1730,6 +1779,26 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             .poll_request(stream_id, &mut api_context, request, context)
     }
 
+    fn donate_stream_credits(
+        &mut self,
+        from: stream::StreamId,
+        to: stream::StreamId,
+        amount: u64,
+    ) -> Result<(), stream::StreamError> {
+        self.error?;
+
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        let mut api_context = ConnectionApiCallContext::from_wakeup_handle(&self.wakeup_handle);
+
+        space
+            .stream_manager
+            .donate_credits(from, to, amount, &mut api_context)
+    }
+
     fn poll_accept_stream(
         &mut self,
         stream_type: Option<stream::StreamType>,
@@ -1769,7 +1838,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         }
 
         if let Some(error) = error {
-            self.error = Err(connection::Error::application(error));
+            self.close_streams(connection::Error::application(error));
         } else {
             // give the connection some time to flush all outstanding streams
             self.state = ConnectionState::Flushing;
@@ -1780,6 +1849,31 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         self.wakeup_handle.wakeup();
     }
 
+    fn transport_close(&mut self, error: transport::Error) {
+        if self.error.is_err() {
+            return;
+        }
+
+        self.close_streams(error.into());
+        self.wakeup_handle.wakeup();
+    }
+
+    fn poll_close_and_flush(&mut self, context: &Context) -> Poll<()> {
+        if matches!(
+            self.state,
+            ConnectionState::Closing | ConnectionState::Draining | ConnectionState::Finished
+        ) {
+            return Poll::Ready(());
+        }
+
+        // Start flushing the connection if the application hasn't closed it already
+        self.application_close(None);
+
+        self.close_waker = Some(context.waker().clone());
+
+        Poll::Pending
+    }
+
     fn server_name(&self) -> Option<ServerName> {
         self.space_manager.server_name.clone()
     }
@@ -1824,6 +1918,147 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         Ok(())
     }
 
+    fn probe_bandwidth(&mut self, duration: Duration) -> Result<(), connection::Error> {
+        self.error?;
+
+        self.path_manager
+            .active_path_mut()
+            .bandwidth_probe
+            .request(duration);
+
+        self.wakeup_handle.wakeup();
+
+        Ok(())
+    }
+
+    fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error> {
+        Ok(self.path_manager.active_path().bandwidth_probe.estimate())
+    }
+
+    fn set_send_bandwidth(&mut self, bandwidth: Bandwidth) -> Result<(), connection::Error> {
+        self.error?;
+
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        space.stream_manager.set_send_bandwidth(bandwidth);
+
+        // streams may be able to send at the new rate right away
+        self.wakeup_handle.wakeup();
+
+        Ok(())
+    }
+
+    fn loss_rates(&self) -> LossRates {
+        self.space_manager
+            .application()
+            .map_or_else(LossRates::default, |space| space.loss_rate.rates())
+    }
+
+    fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        Ok(self
+            .space_manager
+            .application()
+            .and_then(|space| space.histograms.as_ref())
+            .map(|histograms| histograms.rtt().clone()))
+    }
+
+    fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error> {
+        Ok(self
+            .space_manager
+            .application()
+            .and_then(|space| space.histograms.as_ref())
+            .map(|histograms| histograms.throughput().clone()))
+    }
+
+    fn reset_histograms(&mut self) -> Result<(), connection::Error> {
+        if let Some(histograms) = self
+            .space_manager
+            .application_mut()
+            .and_then(|(space, _)| space.histograms.as_mut())
+        {
+            histograms.reset();
+        }
+
+        Ok(())
+    }
+
+    fn recv_budget_remaining(&self) -> Result<u64, connection::Error> {
+        Ok(self.space_manager.application().map_or(u64::MAX, |space| {
+            space.stream_manager.recv_budget_remaining()
+        }))
+    }
+
+    fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error> {
+        Ok(self
+            .space_manager
+            .application()
+            .map_or_else(StreamMetricsTotals::default, |space| {
+                space.stream_manager.stream_metrics()
+            }))
+    }
+
+    fn write_amplification(&self) -> Result<WriteAmplification, connection::Error> {
+        Ok(self
+            .space_manager
+            .application()
+            .map_or_else(WriteAmplification::default, |space| {
+                space.write_amplification()
+            }))
+    }
+
+    fn reset_stats(&mut self) -> Result<(), connection::Error> {
+        if let Some((space, _)) = self.space_manager.application_mut() {
+            space.reset_stats();
+        }
+
+        Ok(())
+    }
+
+    fn stream_write_amplification(
+        &mut self,
+        stream_id: stream::StreamId,
+    ) -> Result<WriteAmplification, stream::StreamError> {
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        let mut api_context = ConnectionApiCallContext::from_wakeup_handle(&self.wakeup_handle);
+
+        space
+            .stream_manager
+            .stream_write_amplification(stream_id, &mut api_context)
+    }
+
+    fn set_stream_priority(
+        &mut self,
+        stream_id: stream::StreamId,
+        priority: StreamPriority,
+    ) -> Result<(), stream::StreamError> {
+        let (space, _) = self
+            .space_manager
+            .application_mut()
+            .ok_or_else(connection::Error::unspecified)?;
+
+        let mut api_context = ConnectionApiCallContext::from_wakeup_handle(&self.wakeup_handle);
+
+        space
+            .stream_manager
+            .set_stream_priority(stream_id, priority, &mut api_context)
+    }
+
+    fn active_stream_count(&self) -> Result<usize, connection::Error> {
+        self.error?;
+
+        Ok(self
+            .space_manager
+            .application()
+            .map_or(0, |space| space.stream_manager.active_stream_count()))
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         Ok(*self.path_manager.active_path().handle.local_address())
     }
@@ -1912,10 +2147,15 @@ impl<Config: endpoint::Config> transmission::interest::Provider for ConnectionIm
         self.space_manager.transmission_interest(query)?;
 
         self.local_id_registry.transmission_interest(query)?;
-        self.path_manager
-            .active_path()
-            .mtu_controller
-            .transmission_interest(query)?;
+
+        if !HandshakeProbeSuppressor::new(self.space_manager.is_handshake_confirmed())
+            .is_suppressed()
+        {
+            self.path_manager
+                .active_path()
+                .mtu_controller
+                .transmission_interest(query)?;
+        }
 
         Ok(())
     }
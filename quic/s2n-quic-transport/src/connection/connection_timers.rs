@@ -3,13 +3,16 @@
 
 //! Manages all timers inside a Connection
 
-use s2n_quic_core::time::{timer, Timer};
+use core::time::Duration;
+use s2n_quic_core::time::{timer, Timer, Timestamp};
 
 /// Stores connection-level timer state
 #[derive(Debug, Default)]
 pub struct ConnectionTimers {
     /// The timer which is used to check peer idle times
     pub peer_idle_timer: Timer,
+    /// The last time the peer idle timer was restarted
+    pub peer_idle_timer_restarted: Option<Timestamp>,
     /// Stores if sending an ack-eliciting packet will rearm the idle timer
     //= https://www.rfc-editor.org/rfc/rfc9000#section-10.1
     //# An endpoint also restarts its
@@ -26,11 +29,20 @@ pub struct ConnectionTimers {
     pub pacing_timer: Timer,
     /// The timer for closing the connection if the handshake is still in progress
     pub max_handshake_duration_timer: Timer,
+    /// The time the handshake started
+    pub handshake_start: Option<Timestamp>,
     /// The timer for calling the connection supervisor
     pub supervisor_timer: Timer,
 }
 
 impl ConnectionTimers {
+    /// Restarts the peer idle timer to expire `duration` after `timestamp`
+    #[inline]
+    pub fn restart_peer_idle_timer(&mut self, timestamp: Timestamp, duration: Duration) {
+        self.peer_idle_timer.set(timestamp + duration);
+        self.peer_idle_timer_restarted = Some(timestamp);
+    }
+
     pub fn cancel(&mut self) {
         self.peer_idle_timer.cancel();
         self.local_idle_timer.cancel();
@@ -15,11 +15,15 @@ use crate::{
     stream,
 };
 use bytes::Bytes;
-use core::task::{Context, Poll};
+use core::{
+    task::{Context, Poll},
+    time::Duration,
+};
 use s2n_codec::DecoderBufferMut;
 use s2n_quic_core::{
     application,
     application::ServerName,
+    connection::Histogram,
     event::{self, builder::DatagramDropReason, supervisor, ConnectionPublisher, IntoEvent},
     inet::{DatagramInfo, SocketAddress},
     io::tx,
@@ -33,7 +37,13 @@ use s2n_quic_core::{
         ProtectedPacket,
     },
     path::{Handle as _, MaxMtu},
+    recovery::{
+        bandwidth::{Bandwidth, BandwidthEstimate},
+        loss_rate::LossRates,
+    },
+    stream::{StreamMetricsTotals, StreamPriority, WriteAmplification},
     time::Timestamp,
+    transport,
 };
 
 /// A trait which represents an internally used `Connection`
@@ -414,6 +424,13 @@ pub trait ConnectionTrait: 'static + Send + Sized {
         context: Option<&Context>,
     ) -> Result<stream::ops::Response, stream::StreamError>;
 
+    fn donate_stream_credits(
+        &mut self,
+        from: stream::StreamId,
+        to: stream::StreamId,
+        amount: u64,
+    ) -> Result<(), stream::StreamError>;
+
     fn poll_accept_stream(
         &mut self,
         stream_type: Option<stream::StreamType>,
@@ -429,6 +446,14 @@ pub trait ConnectionTrait: 'static + Send + Sized {
 
     fn application_close(&mut self, error: Option<application::Error>);
 
+    /// Closes the connection with a transport error on behalf of the application
+    fn transport_close(&mut self, error: transport::Error);
+
+    /// Gracefully closes the connection and polls for the outstanding streams to be flushed
+    ///
+    /// Returns `Poll::Ready` once the connection has entered the closing or draining state.
+    fn poll_close_and_flush(&mut self, context: &Context) -> Poll<()>;
+
     fn server_name(&self) -> Option<ServerName>;
 
     fn application_protocol(&self) -> Bytes;
@@ -437,6 +462,57 @@ pub trait ConnectionTrait: 'static + Send + Sized {
 
     fn keep_alive(&mut self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn probe_bandwidth(&mut self, duration: Duration) -> Result<(), connection::Error>;
+
+    fn bandwidth_estimate(&self) -> Result<Option<BandwidthEstimate>, connection::Error>;
+
+    fn set_send_bandwidth(&mut self, bandwidth: Bandwidth) -> Result<(), connection::Error>;
+
+    /// Returns the packet loss rates as of the end of the last round trip
+    fn loss_rates(&self) -> LossRates;
+
+    /// Returns the RTT samples of the connection, in microseconds
+    ///
+    /// `None` is returned if the histograms are not enabled.
+    fn rtt_histogram(&self) -> Result<Option<Histogram>, connection::Error>;
+
+    /// Returns the number of bytes the connection sent per second
+    ///
+    /// `None` is returned if the histograms are not enabled.
+    fn throughput_histogram(&self) -> Result<Option<Histogram>, connection::Error>;
+
+    fn reset_histograms(&mut self) -> Result<(), connection::Error>;
+
+    /// Returns the number of bytes which can be received before the receive budget is used up
+    fn recv_budget_remaining(&self) -> Result<u64, connection::Error>;
+
+    /// Returns the transfer counters of all of the streams which have been closed
+    fn stream_metrics(&self) -> Result<StreamMetricsTotals, connection::Error>;
+
+    /// Returns the number of STREAM frame bytes sent per byte of new stream data
+    fn write_amplification(&self) -> Result<WriteAmplification, connection::Error>;
+
+    /// Resets the cumulative statistics of the connection to zero
+    fn reset_stats(&mut self) -> Result<(), connection::Error>;
+
+    /// Returns the write amplification of a single stream
+    fn stream_write_amplification(
+        &mut self,
+        stream_id: stream::StreamId,
+    ) -> Result<WriteAmplification, stream::StreamError>;
+
+    /// Changes the priority of a single stream when sharing reserved bandwidth
+    fn set_stream_priority(
+        &mut self,
+        stream_id: stream::StreamId,
+        priority: StreamPriority,
+    ) -> Result<(), stream::StreamError>;
+
+    /// Returns the number of streams which are currently open
+    ///
+    /// An error is returned if the connection is closed.
+    fn active_stream_count(&self) -> Result<usize, connection::Error>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
@@ -903,3 +903,93 @@ fn retire_handshake_connection_id() {
         }
     }
 }
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.1
+//= type=test
+//# An endpoint MUST NOT
+//# provide more connection IDs than the peer's limit.
+#[test]
+fn connection_id_rotation_within_peer_limit() {
+    const LIFETIME: Duration = Duration::from_secs(60);
+    const RTT: Duration = Duration::from_millis(100);
+
+    for limit in 2..=MAX_ACTIVE_CONNECTION_ID_LIMIT {
+        let mut now = s2n_quic_platform::time::now();
+        let mut next_id = 1u8;
+
+        let (_, mut reg1) = mapper(id(&[0; 4]), None, stateless_reset::Token::from([0xff; 16]));
+        reg1.set_active_connection_id_limit(limit);
+        reg1.retire_handshake_connection_id();
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        // The connection IDs the peer has received and not yet retired, by sequence number
+        let mut peer_ids: Vec<(u32, connection::LocalId)> = vec![(0, id(&[0; 4]))];
+        let mut peer_retire_prior_to = 0;
+        let mut issued_ids = 0;
+
+        for _ in 0..LIFETIME.as_secs() * 10 {
+            reg1.on_timeout(now);
+
+            if let connection::id::Interest::New(count) = reg1.connection_id_interest() {
+                for _ in 0..count {
+                    let local_id = id(&[next_id; 4]);
+                    let token = stateless_reset::Token::from([next_id; 16]);
+                    next_id += 1;
+                    assert!(reg1
+                        .register_connection_id(&local_id, Some(now + LIFETIME), token)
+                        .is_ok());
+                }
+            }
+
+            let mut write_context = MockWriteContext::new(
+                now,
+                &mut frame_buffer,
+                transmission::Constraint::None,
+                transmission::Mode::Normal,
+                endpoint::Type::Server,
+            );
+            reg1.on_transmit(&mut write_context);
+
+            while let Some(mut written) = write_context.frame_buffer.pop_front() {
+                let packet_nr = written.packet_nr;
+                let frame = match written.as_frame() {
+                    Frame::NewConnectionId(frame) => frame,
+                    frame => panic!("unexpected frame: {:?}", frame),
+                };
+                let sequence_number = frame.sequence_number.as_u64() as u32;
+                let retire_prior_to = frame.retire_prior_to.as_u64() as u32;
+
+                // The peer stores the new ID and retires everything below `retire_prior_to`
+                peer_ids.push((sequence_number, id(frame.connection_id)));
+                peer_retire_prior_to = peer_retire_prior_to.max(retire_prior_to);
+                let (retired, active): (Vec<_>, Vec<_>) = peer_ids
+                    .into_iter()
+                    .partition(|(sequence_number, _)| *sequence_number < peer_retire_prior_to);
+                peer_ids = active;
+
+                assert!(
+                    peer_ids.len() as u64 <= limit,
+                    "limit {}: the peer holds {} connection IDs",
+                    limit,
+                    peer_ids.len()
+                );
+
+                issued_ids += 1;
+                reg1.on_packet_ack(&PacketNumberRange::new(packet_nr, packet_nr));
+
+                let (_, destination_id) = peer_ids.last().unwrap();
+                for (sequence_number, _) in retired {
+                    assert!(reg1
+                        .on_retire_connection_id(sequence_number, destination_id, RTT, now)
+                        .is_ok());
+                }
+            }
+
+            now += Duration::from_secs(1);
+        }
+
+        // The connection IDs were rotated several times
+        assert!(issued_ids > 10, "limit {}: {} issued", limit, issued_ids);
+        assert!(reg1.registered_ids.len() as u64 <= limit * 2);
+    }
+}
@@ -37,7 +37,7 @@ pub(crate) use local_id_registry::LocalIdRegistry;
 pub(crate) use peer_id_registry::PeerIdRegistry;
 pub(crate) use transmission::{ConnectionTransmission, ConnectionTransmissionContext};
 
-pub use api::Connection;
+pub use api::{Connection, WeakConnection};
 pub use connection_impl::ConnectionImpl as Implementation;
 pub use connection_trait::Lock;
 pub use open_token::Pair as OpenToken;
@@ -525,6 +525,21 @@ impl PeerIdRegistry {
         self.consume_new_id_inner()
     }
 
+    /// Retires the given peer_id if it is currently active
+    ///
+    /// A RETIRE_CONNECTION_ID frame will be transmitted for the ID. Returns `true` if
+    /// the ID was retired.
+    pub fn retire_id(&mut self, peer_id: &connection::PeerId) -> bool {
+        for id_info in self.registered_ids.iter_mut() {
+            if id_info.id == *peer_id && id_info.status.is_active() {
+                id_info.status = PendingRetirement;
+                return true;
+            }
+        }
+
+        false
+    }
+
     // Validate that the ACTIVE_CONNECTION_ID_LIMIT has not been exceeded
     fn check_active_connection_id_limit(
         &self,
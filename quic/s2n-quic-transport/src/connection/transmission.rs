@@ -27,6 +27,7 @@ pub struct ConnectionTransmissionContext<'a, 'sub, Config: endpoint::Config> {
     pub outcome: &'a mut transmission::Outcome,
     pub ecn: ExplicitCongestionNotification,
     pub min_packet_len: Option<usize>,
+    pub traffic_shaping: Option<transmission::Shaping>,
     pub transmission_mode: transmission::Mode,
     pub publisher: &'a mut event::ConnectionPublisherSubscriber<'sub, Config::EventSubscriber>,
     pub packet_interceptor: &'a mut Config::PacketInterceptor,
@@ -183,14 +184,29 @@ impl<'a, 'sub, Config: endpoint::Config> tx::Message for ConnectionTransmission<
                 }
             };
 
+            let is_mtu_probing = self.context.transmission_mode.is_mtu_probing();
+
+            // only the last packet in the datagram is padded so the earlier packets can still be
+            // coalesced with it
+            if let Some(shaping) = self.context.traffic_shaping.as_mut() {
+                shaping.datagram_capacity = initial_capacity;
+                shaping.last_space = if is_mtu_probing
+                    || has_transmission(space_manager.application(), transmission_constraint)
+                {
+                    PacketNumberSpace::ApplicationData
+                } else if has_transmission(space_manager.handshake(), transmission_constraint) {
+                    PacketNumberSpace::Handshake
+                } else {
+                    PacketNumberSpace::Initial
+                };
+            }
+
             //= https://www.rfc-editor.org/rfc/rfc9001#section-4
             //# When packets of different types need to be sent,
             //# endpoints SHOULD use coalesced packets to send them in the same UDP
             //# datagram.
             // here we query all of the spaces to try and fill the current datagram
 
-            let is_mtu_probing = self.context.transmission_mode.is_mtu_probing();
-
             let encoder = if let Some((space, handshake_status)) = space_manager
                 .initial_mut()
                 // MTU probes are only sent in the Application Space
@@ -5,13 +5,16 @@
 //! within the connection in order to collect data
 
 use crate::{connection::InternalConnectionId, transmission, wakeup_queue::WakeupHandle};
+use core::time::Duration;
 use s2n_codec::encoder::EncoderValue;
 use s2n_quic_core::{
     endpoint,
     event::{self, IntoEvent},
     frame::{ack_elicitation::AckElicitation, FrameTrait},
     packet::number::PacketNumber,
+    stream::StreamId,
     time::Timestamp,
+    varint::VarInt,
 };
 
 /// Context information that is passed to `on_transmit` calls on Streams
@@ -68,6 +71,27 @@ pub trait WriteContext {
 
     /// Returns the length of the authentication tag in bytes
     fn tag_len(&self) -> usize;
+
+    /// Called when sending data on `stream_id` becomes blocked by the peer's stream flow
+    /// control `limit` with `pending_len` bytes enqueued beyond the limit
+    fn on_stream_blocked(&mut self, stream_id: StreamId, limit: VarInt, pending_len: VarInt);
+
+    /// Called when the peer raises the stream flow control limit of a blocked `stream_id` to
+    /// `limit` after `blocked_duration`
+    fn on_stream_unblocked(
+        &mut self,
+        stream_id: StreamId,
+        limit: VarInt,
+        blocked_duration: Duration,
+    );
+
+    /// Called when sending stream data becomes blocked by the peer's connection flow control
+    /// `limit`
+    fn on_connection_blocked(&mut self, limit: VarInt);
+
+    /// Called when the current packet retransmits data which is still in flight in
+    /// `original_packet_number`
+    fn on_retransmission(&mut self, original_packet_number: PacketNumber);
 }
 
 /// Enumerates error values for `on_transmit` calls
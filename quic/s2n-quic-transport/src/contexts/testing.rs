@@ -3,6 +3,7 @@
 
 use crate::contexts::WriteContext;
 use alloc::collections::VecDeque;
+use core::time::Duration;
 use s2n_codec::{
     encoder::{EncoderBuffer, EncoderValue},
     DecoderBufferMut,
@@ -15,6 +16,7 @@ use s2n_quic_core::{
         FrameMut, FrameTrait,
     },
     packet::number::{PacketNumber, PacketNumberSpace},
+    stream::StreamId,
     time::Timestamp,
     transmission,
     transmission::{Constraint, Mode},
@@ -58,6 +60,14 @@ pub struct OutgoingFrameBuffer {
     /// permitted before errors are returned on write. This can be used to simulate
     /// failing write calls.
     error_after_frames: Option<usize>,
+    /// The stream flow control stalls which have been reported, along with the pending length
+    pub stream_blocked: Vec<(StreamId, VarInt, VarInt)>,
+    /// The stream flow control stalls which have been reported as resolved
+    pub stream_unblocked: Vec<(StreamId, VarInt, Duration)>,
+    /// The connection flow control stalls which have been reported
+    pub connection_blocked: Vec<VarInt>,
+    /// The packets which retransmitted in-flight data, along with the original packets
+    pub retransmissions: Vec<(PacketNumber, PacketNumber)>,
 }
 
 impl Default for OutgoingFrameBuffer {
@@ -70,6 +80,10 @@ impl Default for OutgoingFrameBuffer {
             max_buffer_size: None,
             remaining_packet_space: 0,
             error_after_frames: None,
+            stream_blocked: Vec::new(),
+            stream_unblocked: Vec::new(),
+            connection_blocked: Vec::new(),
+            retransmissions: Vec::new(),
         }
     }
 }
@@ -314,4 +328,32 @@ impl<'a> WriteContext for MockWriteContext<'a> {
     fn tag_len(&self) -> usize {
         0
     }
+
+    fn on_stream_blocked(&mut self, stream_id: StreamId, limit: VarInt, pending_len: VarInt) {
+        self.frame_buffer
+            .stream_blocked
+            .push((stream_id, limit, pending_len));
+    }
+
+    fn on_stream_unblocked(
+        &mut self,
+        stream_id: StreamId,
+        limit: VarInt,
+        blocked_duration: Duration,
+    ) {
+        self.frame_buffer
+            .stream_unblocked
+            .push((stream_id, limit, blocked_duration));
+    }
+
+    fn on_connection_blocked(&mut self, limit: VarInt) {
+        self.frame_buffer.connection_blocked.push(limit);
+    }
+
+    fn on_retransmission(&mut self, original_packet_number: PacketNumber) {
+        let packet_number = self.packet_number();
+        self.frame_buffer
+            .retransmissions
+            .push((packet_number, original_packet_number));
+    }
 }
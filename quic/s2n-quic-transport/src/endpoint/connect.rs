@@ -60,6 +60,18 @@ impl Connect {
             ..self
         }
     }
+
+    /// Returns the address of the remote endpoint
+    #[inline]
+    pub fn remote_address(&self) -> SocketAddress {
+        *self.remote_address
+    }
+
+    /// Returns the server name to use for the connection, if any
+    #[inline]
+    pub fn server_name(&self) -> Option<&ServerName> {
+        self.server_name.as_ref()
+    }
 }
 
 /// Make it easy for applications to create a connection attempt without importing the `Connect` struct
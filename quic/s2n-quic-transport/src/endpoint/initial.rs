@@ -33,6 +33,7 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
         packet: ProtectedInitial,
         remaining: DecoderBufferMut,
         retry_token_dcid: Option<connection::InitialId>,
+        admission: endpoint::Admission,
     ) -> Result<(), connection::Error> {
         debug_assert!(
             Config::ENDPOINT_TYPE.is_server(),
@@ -113,6 +114,28 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
         let packet = packet.unprotect(&initial_header_key, largest_packet_number)?;
         let packet = packet.decrypt(&initial_key)?;
 
+        if admission == endpoint::Admission::Refuse {
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-5.2.2
+            //# If a server refuses to accept a new connection, it SHOULD send an
+            //# Initial packet containing a CONNECTION_CLOSE frame with error code
+            //# CONNECTION_REFUSED.
+
+            // The packet is only sent once the Initial packet is authenticated, so nothing is
+            // sent in response to forged or corrupted datagrams. No connection or TLS state is
+            // created for the attempt.
+            let error = transport::Error::CONNECTION_REFUSED;
+            self.refuse_dispatch.queue(
+                header.path,
+                &packet,
+                initial_connection_id.as_bytes(),
+                &initial_key,
+                &initial_header_key,
+                error,
+            );
+
+            return Err(error.into());
+        }
+
         // TODO handle token with stateless retry
 
         let internal_connection_id = self.connection_id_generator.generate_id();
@@ -51,6 +51,7 @@ pub mod connect;
 pub mod handle;
 mod initial;
 mod packet_buffer;
+mod refuse;
 mod retry;
 mod stateless_reset;
 mod version;
@@ -62,6 +63,16 @@ pub use s2n_quic_core::endpoint::*;
 
 const DEFAULT_MAX_PEERS: usize = 1024;
 
+/// How the endpoint proceeds with a connection attempt permitted by the endpoint limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Admission {
+    /// The handshake proceeds as usual
+    Accept,
+    /// The connection attempt is refused with CONNECTION_REFUSED before any connection state
+    /// is created
+    Refuse,
+}
+
 /// A QUIC `Endpoint`
 pub struct Endpoint<Cfg: Config> {
     /// Configuration parameters for the endpoint
@@ -83,6 +94,7 @@ pub struct Endpoint<Cfg: Config> {
     dequeued_wakeups: VecDeque<InternalConnectionId>,
     version_negotiator: version::Negotiator<Cfg>,
     retry_dispatch: retry::Dispatch<Cfg::PathHandle>,
+    refuse_dispatch: refuse::Dispatch<Cfg::PathHandle>,
     stateless_reset_dispatch: stateless_reset::Dispatch<Cfg::PathHandle>,
     close_packet_buffer: packet_buffer::Buffer,
     /// The largest maximum transmission unit (MTU) that can be sent on a path
@@ -163,6 +175,7 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
             );
             self.version_negotiator.on_transmit(queue, &mut publisher);
             self.retry_dispatch.on_transmit(queue, &mut publisher);
+            self.refuse_dispatch.on_transmit(queue, &mut publisher);
             self.stateless_reset_dispatch
                 .on_transmit(queue, &mut publisher);
         }
@@ -311,6 +324,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
             dequeued_wakeups: VecDeque::new(),
             version_negotiator: version::Negotiator::default(),
             retry_dispatch: retry::Dispatch::default(),
+            refuse_dispatch: refuse::Dispatch::default(),
             stateless_reset_dispatch: stateless_reset::Dispatch::default(),
             close_packet_buffer: Default::default(),
             max_mtu: Default::default(),
@@ -320,13 +334,15 @@ impl<Cfg: Config> Endpoint<Cfg> {
     }
 
     /// Determine the next step when a peer attempts a connection
+    ///
+    /// Returns `None` if the connection attempt should not be processed any further.
     fn connection_allowed(
         &mut self,
         header: &datagram::Header<Cfg::PathHandle>,
         packet: &ProtectedInitial,
         payload_len: usize,
         timestamp: Timestamp,
-    ) -> Option<()> {
+    ) -> Option<Admission> {
         if !self.connections.can_accept() {
             return None;
         }
@@ -352,7 +368,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
         );
 
         match outcome {
-            Outcome::Allow { .. } => Some(()),
+            Outcome::Allow { .. } => Some(Admission::Accept),
             Outcome::Retry { .. } => {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
                 //# A server can also use a Retry packet to defer the state and
@@ -381,21 +397,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
 
                 None
             }
-            Outcome::Close { .. } => {
-                //= https://www.rfc-editor.org/rfc/rfc9000#section-5.2.2
-                //= type=TODO
-                //= tracking-issue=270
-                //# If a server refuses to accept a new connection, it SHOULD send an
-                //# Initial packet containing a CONNECTION_CLOSE frame with error code
-                //# CONNECTION_REFUSED.
-
-                publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
-                    len: payload_len as u16,
-                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt,
-                });
-
-                None
-            }
+            Outcome::Close { .. } => Some(Admission::Refuse),
             Outcome::Drop { .. } => {
                 publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
                     len: payload_len as u16,
@@ -704,7 +706,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
                 //# In response to processing an Initial packet containing a token that
                 //# was provided in a Retry packet, a server cannot send another Retry
                 //# packet; it can only refuse the connection or permit it to proceed.
-                let retry_token_dcid = if !packet.token().is_empty() {
+                let (retry_token_dcid, admission) = if !packet.token().is_empty() {
                     let mut context = token::Context::new(
                         &remote_address,
                         &source_connection_id,
@@ -745,23 +747,24 @@ impl<Cfg: Config> Endpoint<Cfg> {
                     //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
                     //# If the validation succeeds, the server SHOULD then allow
                     //# the handshake to proceed.
-                    outcome
+                    (outcome, Admission::Accept)
                 } else {
                     //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
                     //# Upon receiving the client's Initial packet, the server can request
                     //# address validation by sending a Retry packet (Section 17.2.5)
                     //# containing a token.
-                    if self
-                        .connection_allowed(header, &packet, payload_len, timestamp)
-                        .is_none()
+                    let admission = if let Some(admission) =
+                        self.connection_allowed(header, &packet, payload_len, timestamp)
                     {
+                        admission
+                    } else {
                         //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.1
                         //# A server MUST NOT send more than one Retry
                         //# packet in response to a single UDP datagram.
                         return;
-                    }
+                    };
 
-                    None
+                    (None, admission)
                 };
 
                 if let Err(err) = self.handle_initial_packet(
@@ -770,6 +773,7 @@ impl<Cfg: Config> Endpoint<Cfg> {
                     packet,
                     remaining,
                     retry_token_dcid,
+                    admission,
                 ) {
                     // TODO send a minimal connection close frame
                     let mut publisher = event::EndpointPublisherSubscriber::new(
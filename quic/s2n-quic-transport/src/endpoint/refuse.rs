@@ -0,0 +1,206 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::endpoint;
+use alloc::collections::VecDeque;
+use s2n_codec::{Encoder, EncoderBuffer, EncoderValue};
+use s2n_quic_core::{
+    crypto::{InitialHeaderKey, InitialKey},
+    event,
+    frame::{ConnectionClose, Padding},
+    inet::ExplicitCongestionNotification,
+    io::tx,
+    packet::{self, encoding::PacketEncoder, number::PacketNumberSpace},
+    path::{self, MINIMUM_MTU},
+    time, transport,
+    varint::VarInt,
+};
+
+/// Refuses connection attempts without creating any connection state
+///
+/// Each refused attempt is answered with an Initial packet carrying a CONNECTION_CLOSE frame.
+#[derive(Debug)]
+pub struct Dispatch<Path: path::Handle> {
+    transmissions: VecDeque<Transmission<Path>>,
+}
+
+impl<Path: path::Handle> Default for Dispatch<Path> {
+    fn default() -> Self {
+        Self::new(endpoint::DEFAULT_MAX_PEERS)
+    }
+}
+
+impl<Path: path::Handle> Dispatch<Path> {
+    pub fn new(max_peers: usize) -> Self {
+        Self {
+            transmissions: VecDeque::with_capacity(max_peers),
+        }
+    }
+
+    pub fn queue<K: InitialKey, H: InitialHeaderKey>(
+        &mut self,
+        path: Path,
+        packet: &packet::initial::CleartextInitial,
+        local_connection_id: &[u8],
+        key: &K,
+        header_key: &H,
+        error: transport::Error,
+    ) {
+        if let Some(transmission) =
+            Transmission::new(path, packet, local_connection_id, key, header_key, error)
+        {
+            self.transmissions.push_back(transmission);
+        }
+    }
+
+    pub fn on_transmit<Tx: tx::Queue<Handle = Path>, Pub: event::EndpointPublisher>(
+        &mut self,
+        queue: &mut Tx,
+        publisher: &mut Pub,
+    ) {
+        while let Some(transmission) = self.transmissions.pop_front() {
+            match queue.push(&transmission) {
+                Ok(tx::Outcome { len, .. }) => {
+                    publisher.on_endpoint_packet_sent(event::builder::EndpointPacketSent {
+                        packet_header: event::builder::PacketHeader::Initial {
+                            number: 0,
+                            version: transmission.version,
+                        },
+                    });
+
+                    publisher.on_endpoint_datagram_sent(event::builder::EndpointDatagramSent {
+                        len: len as u16,
+                        gso_offset: 0,
+                    });
+                }
+                Err(_) => {
+                    self.transmissions.push_front(transmission);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+pub struct Transmission<Path: path::Handle> {
+    path: Path,
+    packet: [u8; MINIMUM_MTU as usize],
+    packet_len: usize,
+    version: u32,
+}
+
+impl<Path: path::Handle> core::fmt::Debug for Transmission<Path> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Transmission")
+            .field("remote_address", &self.path.remote_address())
+            .field("local_address", &self.path.local_address())
+            .field("packet", &&self.packet[..self.packet_len])
+            .finish()
+    }
+}
+
+impl<Path: path::Handle> Transmission<Path> {
+    pub fn new<K: InitialKey, H: InitialHeaderKey>(
+        path: Path,
+        packet: &packet::initial::CleartextInitial,
+        local_connection_id: &[u8],
+        key: &K,
+        header_key: &H,
+        error: transport::Error,
+    ) -> Option<Self> {
+        let packet_number = PacketNumberSpace::Initial.new_packet_number(VarInt::from_u8(0));
+
+        let token: &[u8] = &[];
+        let initial = packet::initial::Initial {
+            version: packet.version,
+            destination_connection_id: packet.source_connection_id(),
+            source_connection_id: local_connection_id,
+            token,
+            packet_number,
+            payload: Payload(error.into()),
+        };
+
+        let mut packet_buf = [0u8; MINIMUM_MTU as usize];
+        let buffer = EncoderBuffer::new(&mut packet_buf);
+        let (_protected_packet, remaining) = initial
+            .encode_packet(key, header_key, packet_number, None, buffer)
+            .ok()?;
+        let packet_len = MINIMUM_MTU as usize - remaining.capacity();
+
+        Some(Self {
+            path,
+            packet: packet_buf,
+            packet_len,
+            version: packet.version,
+        })
+    }
+}
+
+/// A CONNECTION_CLOSE frame, padded to the minimum length of the packet payload
+struct Payload<'a>(ConnectionClose<'a>);
+
+impl<'a> packet::encoding::PacketPayloadEncoder for Payload<'a> {
+    fn encoding_size_hint<E: Encoder>(&mut self, _encoder: &E, minimum_len: usize) -> usize {
+        self.0.encoding_size().max(minimum_len)
+    }
+
+    fn encode(
+        &mut self,
+        buffer: &mut EncoderBuffer,
+        minimum_len: usize,
+        _header_len: usize,
+        _tag_len: usize,
+    ) {
+        let len = self.0.encoding_size();
+        if len < minimum_len {
+            buffer.encode(&Padding {
+                length: minimum_len - len,
+            });
+        }
+        buffer.encode(&self.0);
+    }
+}
+
+impl<Path: path::Handle> AsRef<[u8]> for Transmission<Path> {
+    fn as_ref(&self) -> &[u8] {
+        &self.packet[..self.packet_len]
+    }
+}
+
+impl<Path: path::Handle> tx::Message for &Transmission<Path> {
+    type Handle = Path;
+
+    #[inline]
+    fn path_handle(&self) -> &Self::Handle {
+        &self.path
+    }
+
+    #[inline]
+    fn ecn(&mut self) -> ExplicitCongestionNotification {
+        Default::default()
+    }
+
+    #[inline]
+    fn delay(&mut self) -> time::Duration {
+        Default::default()
+    }
+
+    #[inline]
+    fn ipv6_flow_label(&mut self) -> u32 {
+        0
+    }
+
+    #[inline]
+    fn can_gso(&self, segment_len: usize, _segment_count: usize) -> bool {
+        segment_len >= self.as_ref().len()
+    }
+
+    #[inline]
+    fn write_payload(
+        &mut self,
+        mut buffer: tx::PayloadBuffer,
+        _gso_offset: usize,
+    ) -> Result<usize, tx::Error> {
+        buffer.write(self.as_ref())
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{contexts::WriteContext, transmission};
+use core::time::Duration;
+use s2n_quic_core::{
+    event, frame,
+    packet::number::PacketNumber,
+    recovery::bandwidth::{Bandwidth, BandwidthEstimate},
+    time::{timer, Timer, Timestamp},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// No probe has been requested
+    Idle,
+    /// A probe of the given duration has been requested, but no probe packet has been sent yet
+    Requested(Duration),
+    /// The probe is filling packets with PADDING and measuring the delivery rate
+    Probing,
+    /// The probe has finished and the estimate is available
+    Complete,
+}
+
+/// The bytes acknowledged and lost since the start of the current measurement interval
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Interval {
+    start: Timestamp,
+    bytes_acked: u64,
+    bytes_lost: u64,
+}
+
+impl Interval {
+    fn new(start: Timestamp) -> Self {
+        Self {
+            start,
+            bytes_acked: 0,
+            bytes_lost: 0,
+        }
+    }
+}
+
+/// Probes the bandwidth available on a path
+///
+/// While the probe is running, the capacity left in each packet after application data
+/// has been written is filled with PING and PADDING frames. The frames do not carry any
+/// stream data, so they do not consume flow control credits and are not retransmitted
+/// if lost. The congestion controller paces the probe packets, so the send rate increases
+/// as the congestion window grows.
+///
+/// The delivery rate is measured once per smoothed RTT. When the probe ends, the measured
+/// rates are summarized in a `BandwidthEstimate`.
+#[derive(Clone, Debug)]
+pub struct Controller {
+    state: State,
+    /// Expires when the probe should end
+    end_timer: Timer,
+    /// The current measurement interval
+    interval: Option<Interval>,
+    /// The delivery rates measured so far
+    samples: Vec<Bandwidth>,
+    /// The result of the last completed probe
+    estimate: Option<BandwidthEstimate>,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self {
+            state: State::Idle,
+            end_timer: Timer::default(),
+            interval: None,
+            samples: Vec::new(),
+            estimate: None,
+        }
+    }
+}
+
+impl Controller {
+    /// Requests a probe of the given `duration`
+    ///
+    /// The probe starts with the next transmission on the path. Any probe that is currently
+    /// running is restarted.
+    pub fn request(&mut self, duration: Duration) {
+        self.state = State::Requested(duration);
+        self.end_timer.cancel();
+        self.interval = None;
+        self.samples.clear();
+        self.estimate = None;
+    }
+
+    #[inline]
+    fn start(&mut self, duration: Duration, now: Timestamp) {
+        self.state = State::Probing;
+        self.end_timer.set(now + duration);
+        self.interval = Some(Interval::new(now));
+    }
+
+    /// Returns true if the probe is currently running
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.state == State::Probing
+    }
+
+    /// Returns the estimate from the last completed probe
+    #[inline]
+    pub fn estimate(&self) -> Option<BandwidthEstimate> {
+        self.estimate
+    }
+
+    /// Fills the remaining capacity of the packet with PING and PADDING frames
+    #[inline]
+    pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) {
+        if !context.transmission_mode().is_normal()
+            || !context.transmission_constraint().can_transmit()
+        {
+            return;
+        }
+
+        match self.state {
+            State::Requested(duration) => self.start(duration, context.current_time()),
+            State::Probing => {}
+            State::Idle | State::Complete => return,
+        }
+
+        // Bundle a PING so the probe packet is acknowledged and its delivery can be measured
+        if context.write_frame(&frame::Ping).is_none() {
+            return;
+        }
+
+        let length = context.remaining_capacity();
+        if length > 0 {
+            context.write_frame(&frame::Padding { length });
+        }
+    }
+
+    /// Called when a packet sent on the path is acknowledged
+    #[inline]
+    pub fn on_packet_ack<Pub: event::ConnectionPublisher>(
+        &mut self,
+        packet_number: PacketNumber,
+        sent_bytes: u16,
+        now: Timestamp,
+        smoothed_rtt: Duration,
+        path: event::builder::Path,
+        publisher: &mut Pub,
+    ) {
+        if !self.is_active() || !packet_number.space().is_application_data() {
+            return;
+        }
+
+        if let Some(interval) = self.interval.as_mut() {
+            interval.bytes_acked += sent_bytes as u64;
+        }
+
+        self.measure(now, smoothed_rtt, path, publisher);
+    }
+
+    /// Called when a packet sent on the path is declared lost
+    #[inline]
+    pub fn on_packet_loss(&mut self, packet_number: PacketNumber, lost_bytes: u16) {
+        if !self.is_active() || !packet_number.space().is_application_data() {
+            return;
+        }
+
+        if let Some(interval) = self.interval.as_mut() {
+            interval.bytes_lost += lost_bytes as u64;
+        }
+    }
+
+    /// Ends the probe once the requested duration has elapsed
+    #[inline]
+    pub fn on_timeout(&mut self, now: Timestamp) {
+        if self.end_timer.poll_expiration(now).is_ready() {
+            self.finish();
+        }
+    }
+
+    /// Records a delivery rate sample if at least one smoothed RTT has elapsed since the
+    /// start of the current interval
+    fn measure<Pub: event::ConnectionPublisher>(
+        &mut self,
+        now: Timestamp,
+        smoothed_rtt: Duration,
+        path: event::builder::Path,
+        publisher: &mut Pub,
+    ) {
+        let interval = if let Some(interval) = self.interval {
+            interval
+        } else {
+            return;
+        };
+
+        let elapsed = now.saturating_duration_since(interval.start);
+        if elapsed.is_zero() || elapsed < smoothed_rtt {
+            return;
+        }
+
+        let delivery_rate = Bandwidth::new(interval.bytes_acked, elapsed);
+        self.samples.push(delivery_rate);
+
+        publisher.on_bandwidth_probe_measured(event::builder::BandwidthProbeMeasured {
+            path,
+            delivery_rate: delivery_rate.as_bits_per_second(),
+            bytes_acked: interval.bytes_acked,
+            bytes_lost: interval.bytes_lost,
+            interval: elapsed,
+        });
+
+        self.interval = Some(Interval::new(now));
+    }
+
+    fn finish(&mut self) {
+        self.state = State::Complete;
+        self.interval = None;
+        self.estimate = BandwidthEstimate::from_samples(&mut self.samples);
+        self.samples = Vec::new();
+    }
+}
+
+impl timer::Provider for Controller {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.end_timer.timers(query)?;
+
+        Ok(())
+    }
+}
+
+impl transmission::interest::Provider for Controller {
+    #[inline]
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        if matches!(self.state, State::Requested(_) | State::Probing) {
+            query.on_new_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contexts::testing::{MockWriteContext, OutgoingFrameBuffer};
+    use s2n_quic_core::{
+        endpoint,
+        event::{builder::Path, testing::Publisher},
+        frame::Frame,
+        packet::number::PacketNumberSpace,
+        varint::VarInt,
+    };
+    use s2n_quic_platform::time::now;
+
+    /// Creates an application space packet number with the given value
+    fn pn(nr: usize) -> PacketNumber {
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::new(nr as u64).unwrap())
+    }
+
+    #[test]
+    fn on_transmit_test() {
+        let mut controller = Controller::default();
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        frame_buffer.set_max_packet_size(Some(1200));
+        let mut write_context = MockWriteContext::new(
+            now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Client,
+        );
+
+        // nothing is written until the probe is started
+        controller.on_transmit(&mut write_context);
+        assert!(write_context.frame_buffer.is_empty());
+
+        controller.request(Duration::from_secs(1));
+        assert!(!controller.is_active());
+        controller.on_transmit(&mut write_context);
+        assert!(controller.is_active());
+        assert_eq!(0, write_context.remaining_capacity());
+        assert_eq!(
+            Frame::Ping(frame::Ping),
+            write_context.frame_buffer.pop_front().unwrap().as_frame()
+        );
+        assert_eq!(
+            Frame::Padding(frame::Padding { length: 1199 }),
+            write_context.frame_buffer.pop_front().unwrap().as_frame()
+        );
+    }
+
+    #[test]
+    fn on_transmit_not_normal_test() {
+        let mut controller = Controller::default();
+        controller.request(Duration::from_secs(1));
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut write_context = MockWriteContext::new(
+            now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::MtuProbing,
+            endpoint::Type::Client,
+        );
+
+        controller.on_transmit(&mut write_context);
+        assert!(write_context.frame_buffer.is_empty());
+        assert!(
+            !controller.is_active(),
+            "the probe starts with a normal transmission"
+        );
+    }
+
+    #[test]
+    fn measurement_test() {
+        let mut controller = Controller::default();
+        let mut publisher = Publisher::no_snapshot();
+        let rtt = Duration::from_millis(100);
+        let start = now();
+
+        controller.start(Duration::from_secs(1), start);
+        assert!(controller.is_active());
+
+        // the first interval delivers 10_000 bytes and loses 1_000 bytes
+        for nr in 0..10 {
+            controller.on_packet_ack(
+                pn(nr),
+                1_000,
+                start + Duration::from_millis(50),
+                rtt,
+                Path::test(),
+                &mut publisher,
+            );
+        }
+        controller.on_packet_loss(pn(10), 1_000);
+        assert!(controller.samples.is_empty());
+
+        controller.on_packet_ack(pn(11), 0, start + rtt, rtt, Path::test(), &mut publisher);
+        assert_eq!(
+            vec![Bandwidth::new(10_000, rtt)],
+            controller.samples,
+            "a sample is taken once every smoothed RTT"
+        );
+        assert_eq!(Some(Interval::new(start + rtt)), controller.interval);
+
+        // the second interval delivers twice as much
+        controller.on_packet_ack(
+            pn(12),
+            20_000,
+            start + rtt * 2,
+            rtt,
+            Path::test(),
+            &mut publisher,
+        );
+        assert_eq!(2, controller.samples.len());
+
+        controller.on_timeout(start + Duration::from_secs(1));
+        assert!(!controller.is_active());
+        assert_eq!(
+            Some(BandwidthEstimate {
+                min: Bandwidth::new(10_000, rtt),
+                max: Bandwidth::new(20_000, rtt),
+                p50: Bandwidth::new(10_000, rtt),
+                p95: Bandwidth::new(20_000, rtt),
+            }),
+            controller.estimate()
+        );
+
+        // packets acknowledged after the probe are ignored
+        controller.on_packet_ack(
+            pn(13),
+            1_000,
+            start + rtt * 20,
+            rtt,
+            Path::test(),
+            &mut publisher,
+        );
+        assert!(controller.samples.is_empty());
+    }
+
+    #[test]
+    fn restart_test() {
+        let mut controller = Controller::default();
+        let start = now();
+
+        controller.start(Duration::from_secs(1), start);
+        controller.on_timeout(start + Duration::from_secs(1));
+        assert!(!controller.is_active());
+        assert_eq!(None, controller.estimate(), "no samples were measured");
+
+        // the end timer is not armed until the probe starts transmitting
+        controller.request(Duration::from_secs(1));
+        assert!(!controller.is_active());
+        assert!(!controller.end_timer.is_armed());
+
+        controller.start(Duration::from_secs(1), start + Duration::from_secs(2));
+        assert!(controller.is_active());
+        controller.on_timeout(start + Duration::from_secs(2));
+        assert!(controller.is_active());
+    }
+}
@@ -96,6 +96,20 @@ impl Controller {
         self.black_hole_counter = Default::default();
     }
 
+    /// Called when the connection migrates to the path associated with this controller
+    ///
+    /// The new path may traverse different network elements than the previous path, so
+    /// any ECN state that was learned previously, including a failed validation, is
+    /// discarded and the testing sequence is restarted.
+    pub fn on_path_migration<Pub: event::ConnectionPublisher>(
+        &mut self,
+        path: event::builder::Path,
+        publisher: &mut Pub,
+    ) {
+        self.restart(path, publisher);
+        self.last_acked_ecn_packet_timestamp = None;
+    }
+
     /// Called when the connection timer expires
     pub fn on_timeout<Rnd: random::Generator, Pub: event::ConnectionPublisher>(
         &mut self,
@@ -55,6 +55,79 @@ fn restart_already_in_testing_0() {
     assert_eq!(0, *controller.black_hole_counter.deref());
 }
 
+#[test]
+fn on_path_migration_after_failure() {
+    let mut publisher = Publisher::no_snapshot();
+    let mut controller = Controller::default();
+    let now = s2n_quic_platform::time::now();
+    let rtt = Duration::from_millis(50);
+
+    // a middlebox on the old path strips the ECN markings
+    for _ in 0..TESTING_PACKET_THRESHOLD {
+        controller.on_packet_sent(
+            ExplicitCongestionNotification::Ect0,
+            Path::test(),
+            &mut publisher,
+        );
+    }
+    assert_eq!(State::Unknown, controller.state);
+    controller.on_packet_ack(now, ExplicitCongestionNotification::Ect0);
+    let outcome = controller.validate(
+        helper_ecn_counts(TESTING_PACKET_THRESHOLD, 0, 0),
+        helper_ecn_counts(TESTING_PACKET_THRESHOLD, 0, 0),
+        EcnCounts::default(),
+        None,
+        now,
+        rtt,
+        Path::test(),
+        &mut publisher,
+    );
+    assert_eq!(ValidationOutcome::Failed, outcome);
+    assert_eq!(
+        ExplicitCongestionNotification::NotEct,
+        controller.ecn(transmission::Mode::Normal, now)
+    );
+
+    // the connection migrates to a new path
+    controller.black_hole_counter += 1;
+    controller.on_path_migration(Path::test(), &mut publisher);
+
+    assert_eq!(State::Testing(0), controller.state);
+    assert_eq!(0, *controller.black_hole_counter.deref());
+    assert_eq!(None, controller.last_acked_ecn_packet_timestamp);
+    assert_eq!(
+        ExplicitCongestionNotification::Ect0,
+        controller.ecn(transmission::Mode::Normal, now)
+    );
+
+    // the probing sequence is run again on the new path, which preserves the markings
+    for _ in 0..TESTING_PACKET_THRESHOLD {
+        controller.on_packet_sent(
+            ExplicitCongestionNotification::Ect0,
+            Path::test(),
+            &mut publisher,
+        );
+    }
+    assert_eq!(State::Unknown, controller.state);
+    let outcome = controller.validate(
+        helper_ecn_counts(TESTING_PACKET_THRESHOLD, 0, 0),
+        helper_ecn_counts(TESTING_PACKET_THRESHOLD, 0, 0),
+        EcnCounts::default(),
+        Some(helper_ecn_counts(TESTING_PACKET_THRESHOLD, 0, 0)),
+        now,
+        rtt,
+        Path::test(),
+        &mut publisher,
+    );
+
+    assert_eq!(ValidationOutcome::Passed, outcome);
+    assert!(controller.is_capable());
+    assert_eq!(
+        ExplicitCongestionNotification::Ect0,
+        controller.ecn(transmission::Mode::Normal, now)
+    );
+}
+
 #[test]
 fn on_timeout_failed() {
     let mut publisher = Publisher::snapshot();
@@ -132,10 +132,12 @@ impl<Config: endpoint::Config> Manager<Config> {
 
         self.activate_path(publisher, prev_path_id, new_path_id);
 
+        MigrationCidRotator::on_path_migration(self, prev_path_id, new_path_id, publisher);
+
         // Restart ECN validation to check that the path still supports ECN
         let path = self.active_path_mut();
         path.ecn_controller
-            .restart(path_event!(path, new_path_id), publisher);
+            .on_path_migration(path_event!(path, new_path_id), publisher);
         Ok(())
     }
 
@@ -432,6 +434,7 @@ impl<Config: endpoint::Config> Manager<Config> {
             true,
             max_mtu,
         );
+        path.set_amplification_factor(self.active_path().amplification_factor);
 
         let unblocked = path.on_bytes_received(datagram.payload_len);
 
@@ -463,8 +466,11 @@ impl<Config: endpoint::Config> Manager<Config> {
         //# The endpoint MUST use unpredictable data in every PATH_CHALLENGE
         //# frame so that it can associate the peer's response with the
         //# corresponding PATH_CHALLENGE.
+        //
+        // The data is drawn from private randomness since the public generator's output may be
+        // observable by the peer or an attacker in other fields, such as connection IDs.
         let mut data: challenge::Data = [0; 8];
-        random_generator.public_random_fill(&mut data);
+        random_generator.private_random_fill(&mut data);
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.2.4
         //# Endpoints SHOULD abandon path validation based on a timer.
@@ -639,6 +645,7 @@ impl<Config: endpoint::Config> Manager<Config> {
             // attacker could block all path validation attempts simply by forwarding packets.
             if self.active_path().is_validated() {
                 self.abandon_all_path_challenges(publisher);
+                MigrationCidRotator::retire_unused(self);
             } else if !self.active_path().is_challenge_pending() {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-9.3
                 //# If the recipient permits the migration, it MUST send subsequent
@@ -648,9 +655,72 @@ impl<Config: endpoint::Config> Manager<Config> {
                 self.set_challenge(self.active_path_id(), random_generator);
             }
         }
+
+        self.check_path_quality(random_generator, publisher);
+
         Ok(())
     }
 
+    /// Migrates away from the active path if its quality degraded
+    ///
+    /// Degradation is reported by both endpoints, but only clients migrate. The connection only
+    /// moves to a path that was previously active and has been validated, since those are the
+    /// only addresses the peer is known to be reachable at. If no such path exists, or no
+    /// connection id is available for it, the connection stays on the degraded path.
+    pub fn check_path_quality<Pub: event::ConnectionPublisher>(
+        &mut self,
+        random_generator: &mut Config::RandomGenerator,
+        publisher: &mut Pub,
+    ) {
+        let degradation =
+            if let Some(degradation) = self.active_path_mut().quality.poll_degradation() {
+                degradation
+            } else {
+                return;
+            };
+
+        let active_path_id = self.active_path_id();
+        let active_path = self.active_path();
+        publisher.on_path_degraded(event::builder::PathDegraded {
+            current_path: path_event!(active_path, active_path_id),
+            loss_rate: degradation.loss_rate,
+            rtt_ratio: degradation.rtt_ratio,
+        });
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# Clients are responsible for initiating all migrations.
+        if Config::ENDPOINT_TYPE.is_server() {
+            return;
+        }
+
+        let candidate = self.paths.iter().position(|path| {
+            path.is_activated()
+                && path.is_validated()
+                && !path.is_active()
+                && !path.quality.is_degraded()
+        });
+
+        let new_path_id = if let Some(idx) = candidate {
+            path_id(idx as u8)
+        } else {
+            return;
+        };
+
+        if self
+            .update_active_path(new_path_id, random_generator, publisher)
+            .is_err()
+        {
+            return;
+        }
+
+        let from_path = &self[active_path_id];
+        let to_path = &self[new_path_id];
+        publisher.on_migration_triggered(event::builder::MigrationTriggered {
+            from_path: path_event!(from_path, active_path_id),
+            to_path: path_event!(to_path, new_path_id),
+        });
+    }
+
     #[inline]
     fn abandon_all_path_challenges<Pub: event::ConnectionPublisher>(
         &mut self,
@@ -754,11 +824,15 @@ impl<Config: endpoint::Config> Manager<Config> {
                     //= https://www.rfc-editor.org/rfc/rfc9000#section-10
                     //# An endpoint MAY discard connection state if it does not have a
                     //# validated path on which it can send packets; see Section 8.2
-                    return Err(connection::Error::no_valid_path());
+                    return Err(connection::Error::timeout(
+                        connection::ConnectionTimeoutReason::MigrationTimeout,
+                    ));
                 }
             }
         }
 
+        self.check_path_quality(random_generator, publisher);
+
         Ok(())
     }
 
@@ -811,6 +885,90 @@ impl<Config: endpoint::Config> Manager<Config> {
     }
 }
 
+/// Rotates the peer connection IDs used by the connection when it migrates to a new path
+///
+/// Using the same connection ID on more than one path allows on-path observers to link the
+/// migrated flow to the original one. When the connection migrates, the new active path is
+/// assigned a connection ID from the pool issued by the peer via NEW_CONNECTION_ID frames and
+/// the connection IDs of previous paths are retired once those paths are no longer in use.
+///
+/// Only the destination connection ID needs to be rotated here: short header packets do not
+/// carry a source connection ID and the peer chooses which of our connection IDs it uses.
+struct MigrationCidRotator;
+
+impl MigrationCidRotator {
+    /// Called after the active path changed from `prev_path_id` to `new_path_id`
+    ///
+    /// A connection ID which was retired while the path was unused has already been replaced
+    /// by `update_active_path`, so only connection IDs shared with the previous path are
+    /// rotated here.
+    #[inline]
+    fn on_path_migration<Config: endpoint::Config, Pub: event::ConnectionPublisher>(
+        manager: &mut Manager<Config>,
+        prev_path_id: Id,
+        new_path_id: Id,
+        publisher: &mut Pub,
+    ) {
+        let peer_id = manager[new_path_id].peer_connection_id;
+        if peer_id != manager[prev_path_id].peer_connection_id {
+            return;
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+        //# An endpoint MUST NOT reuse a connection ID when sending to
+        //# more than one destination address.
+        //
+        // If the peer has not issued any spare connection IDs the current one continues to be
+        // used, which is permitted for migrations caused by NAT rebinding.
+        if let Some(new_id) = manager.peer_id_registry.consume_new_id_for_existing_path(
+            new_path_id,
+            peer_id,
+            publisher,
+        ) {
+            manager[new_path_id].peer_connection_id = new_id;
+        }
+    }
+
+    /// Retires the connection IDs which are no longer used by any path
+    ///
+    /// This is called once the migration has been confirmed by validating the new active path,
+    /// at which point the previous paths are no longer needed as a fallback. A path still uses
+    /// its connection ID while it is active, has a challenge pending or has not been
+    /// authenticated yet.
+    #[inline]
+    fn retire_unused<Config: endpoint::Config>(manager: &mut Manager<Config>) {
+        let pending_packet_authentication = manager.pending_packet_authentication;
+        let is_in_use = |idx: usize, path: &Path<Config>| {
+            path.is_active()
+                || path.is_challenge_pending()
+                || pending_packet_authentication == Some(idx as u8)
+        };
+
+        let Manager {
+            paths,
+            peer_id_registry,
+            ..
+        } = manager;
+
+        for (idx, path) in paths.iter().enumerate() {
+            if is_in_use(idx, path) {
+                continue;
+            }
+
+            let peer_id = path.peer_connection_id;
+
+            // the connection ID may be shared with a path that is still in use
+            let is_shared = paths.iter().enumerate().any(|(other_idx, other)| {
+                other.peer_connection_id == peer_id && is_in_use(other_idx, other)
+            });
+
+            if !is_shared {
+                peer_id_registry.retire_id(&peer_id);
+            }
+        }
+    }
+}
+
 #[inline]
 fn path_id(id: u8) -> path::Id {
     // Safety: The path::Manager is responsible for managing path ID and is thus
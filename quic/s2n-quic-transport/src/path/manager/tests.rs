@@ -21,6 +21,7 @@ use s2n_quic_core::{
     recovery::RttEstimator,
     stateless_reset::token::testing::*,
     time::{Clock, NoopClock},
+    varint::VarInt,
 };
 use std::net::SocketAddr;
 
@@ -475,7 +476,7 @@ fn initiate_path_challenge_if_new_path_is_not_validated() {
 //# validated path on which it can send packets; see Section 8.2
 //
 // If there is no last_known_active_validated_path after a on_timeout then return a
-// NoValidPath error
+// MigrationTimeout error
 fn silently_return_when_there_is_no_valid_path() {
     // Setup:
     let mut publisher = Publisher::snapshot();
@@ -520,7 +521,10 @@ fn silently_return_when_there_is_no_valid_path() {
     assert!(!manager[first_path_id].is_challenge_pending());
     assert!(matches!(
         res.unwrap_err(),
-        connection::Error::NoValidPath { .. }
+        connection::Error::Timeout {
+            reason: connection::ConnectionTimeoutReason::MigrationTimeout,
+            ..
+        }
     ));
 }
 
@@ -1016,11 +1020,10 @@ fn connection_migration_challenge_behavior() {
     //# The endpoint MUST use unpredictable data in every PATH_CHALLENGE
     //# frame so that it can associate the peer's response with the
     //# corresponding PATH_CHALLENGE.
-    // Verify that the data stored in the challenge is taken from the random generator
-    // TODO does the below actually work?? investigate
+    // Verify that the data stored in the challenge is taken from the private random generator
     let mut test_rnd_generator = random::testing::Generator(123);
     let mut expected_data: [u8; 8] = [0; 8];
-    test_rnd_generator.public_random_fill(&mut expected_data);
+    test_rnd_generator.private_random_fill(&mut expected_data);
 
     //= https://www.rfc-editor.org/rfc/rfc9000#section-9
     //= type=test
@@ -1265,6 +1268,143 @@ fn stop_using_a_retired_connection_id() {
     assert_eq!(id_2, manager.paths[0].peer_connection_id);
 }
 
+//= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+//= type=test
+//# An endpoint MUST NOT reuse a connection ID when sending to
+//# more than one destination address.
+#[test]
+fn rotate_connection_id_on_migration() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let zero_conn_id = connection::PeerId::try_from_bytes(&[0]).unwrap();
+    let first_conn_id = connection::PeerId::try_from_bytes(&[1]).unwrap();
+    let mut zero_path = helper_path(zero_conn_id);
+    zero_path.on_handshake_packet();
+
+    let mut random_generator = random::testing::Generator(123);
+    let mut peer_id_registry =
+        ConnectionIdMapper::new(&mut random_generator, endpoint::Type::Server)
+            .create_server_peer_id_registry(
+                InternalConnectionIdGenerator::new().generate_id(),
+                zero_path.peer_connection_id,
+            );
+    assert!(peer_id_registry
+        .on_new_connection_id(&first_conn_id, 1, 0, &TEST_TOKEN_1)
+        .is_ok());
+    let mut manager = Manager::new(zero_path, peer_id_registry);
+
+    // the peer migrates without changing the destination connection ID
+    let new_addr: SocketAddr = "127.0.0.2:8001".parse().unwrap();
+    let new_addr = RemoteAddress::from(SocketAddress::from(new_addr));
+    let datagram = DatagramInfo {
+        timestamp: NoopClock {}.get_time(),
+        payload_len: 0,
+        ecn: ExplicitCongestionNotification::default(),
+        destination_connection_id: connection::LocalId::TEST_ID,
+        source_connection_id: None,
+    };
+    let (new_path_id, _unblocked) = manager
+        .handle_connection_migration(
+            &new_addr,
+            &datagram,
+            &mut Default::default(),
+            &mut migration::default::Validator::default(),
+            DEFAULT_MAX_MTU,
+            &mut publisher,
+        )
+        .unwrap();
+    assert_eq!(manager[new_path_id].peer_connection_id, zero_conn_id);
+
+    // simulate the new path being validated
+    manager[new_path_id].on_handshake_packet();
+
+    // Trigger:
+    manager
+        .on_processed_packet(
+            new_path_id,
+            None,
+            path_validation::Probe::NonProbing,
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+
+    // Expectation:
+    assert_eq!(manager.active_path_id(), new_path_id);
+    // the new path uses a connection ID issued by the peer that was not used on the old path
+    assert_eq!(manager.active_path().peer_connection_id, first_conn_id);
+    assert!(manager.peer_id_registry.is_active(&first_conn_id));
+    // the old connection ID is retired since the old path is no longer in use
+    assert!(!manager.peer_id_registry.is_active(&zero_conn_id));
+
+    // a RETIRE_CONNECTION_ID frame is sent for the old connection ID
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let mut context = MockWriteContext::new(
+        NoopClock {}.get_time(),
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+    manager.on_transmit(&mut context);
+
+    let expected_frame = frame::Frame::RetireConnectionId(frame::RetireConnectionId {
+        sequence_number: VarInt::from_u32(0),
+    });
+    assert_eq!(
+        expected_frame,
+        context.frame_buffer.pop_front().unwrap().as_frame()
+    );
+}
+
+#[test]
+fn keep_connection_id_on_migration_if_none_available() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let zero_conn_id = connection::PeerId::try_from_bytes(&[0]).unwrap();
+    let mut zero_path = helper_path(zero_conn_id);
+    zero_path.on_handshake_packet();
+    let mut manager = manager_server(zero_path);
+
+    let new_addr: SocketAddr = "127.0.0.2:8001".parse().unwrap();
+    let new_addr = RemoteAddress::from(SocketAddress::from(new_addr));
+    let datagram = DatagramInfo {
+        timestamp: NoopClock {}.get_time(),
+        payload_len: 0,
+        ecn: ExplicitCongestionNotification::default(),
+        destination_connection_id: connection::LocalId::TEST_ID,
+        source_connection_id: None,
+    };
+    let (new_path_id, _unblocked) = manager
+        .handle_connection_migration(
+            &new_addr,
+            &datagram,
+            &mut Default::default(),
+            &mut migration::default::Validator::default(),
+            DEFAULT_MAX_MTU,
+            &mut publisher,
+        )
+        .unwrap();
+    manager[new_path_id].on_handshake_packet();
+
+    // Trigger:
+    manager
+        .on_processed_packet(
+            new_path_id,
+            None,
+            path_validation::Probe::NonProbing,
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+
+    // Expectation:
+    // the connection ID is still used by the active path so it cannot be retired
+    assert_eq!(manager.active_path_id(), new_path_id);
+    assert_eq!(manager.active_path().peer_connection_id, zero_conn_id);
+    assert!(manager.peer_id_registry.is_active(&zero_conn_id));
+}
+
 #[test]
 fn amplification_limited_true_if_all_paths_amplificaiton_limited() {
     // Setup:
@@ -1677,6 +1817,140 @@ fn last_known_validated_path_should_update_on_path_response() {
     assert_eq!(manager.last_known_active_validated_path, None);
 }
 
+// loses one in every `loss_interval` packets sent on the path over 10 seconds
+fn degrade_path<Config: endpoint::Config>(
+    path: &mut super::Path<Config>,
+    now: Timestamp,
+    loss_interval: u32,
+) {
+    for packet in 0..1000u32 {
+        let now = now + Duration::from_millis(packet as u64 * 10);
+        if packet % loss_interval == 0 {
+            path.quality.on_packet_lost(now);
+        } else {
+            path.quality.on_packet_ack(now);
+        }
+    }
+}
+
+// creates a client manager with an active path and a validated path that was active before
+fn client_manager_with_previous_path(publisher: &mut Publisher) -> ClientManager {
+    let zero_conn_id = connection::PeerId::try_from_bytes(&[0]).unwrap();
+    let first_conn_id = connection::PeerId::try_from_bytes(&[1]).unwrap();
+    let mut manager = manager_client(client_path("127.0.0.1:443", zero_conn_id));
+    manager
+        .peer_id_registry
+        .register_initial_connection_id(zero_conn_id);
+    assert!(manager
+        .on_new_connection_id(&first_conn_id, 1, 0, &TEST_TOKEN_1, publisher)
+        .is_ok());
+
+    let mut previous_path = client_path("127.0.0.2:443", first_conn_id);
+    previous_path.activated = true;
+    manager.paths.push(previous_path);
+
+    manager
+}
+
+#[test]
+fn migrate_away_from_degraded_path() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = client_manager_with_previous_path(&mut publisher);
+    let now = NoopClock {}.get_time();
+    assert_eq!(manager.active_path_id(), path_id(0));
+
+    // Trigger 1:
+    // - a healthy path is not migrated away from
+    manager.check_path_quality(&mut random::testing::Generator(123), &mut publisher);
+
+    // Expectation 1:
+    assert_eq!(manager.active_path_id(), path_id(0));
+    assert_eq!(publisher.path_degraded, 0);
+
+    // Trigger 2:
+    // - 20% of the packets on the active path are lost
+    degrade_path(&mut manager[path_id(0)], now, 5);
+    manager.check_path_quality(&mut random::testing::Generator(123), &mut publisher);
+
+    // Expectation 2:
+    // - the client migrates to the previously active and validated path
+    assert_eq!(manager.active_path_id(), path_id(1));
+    assert!(manager
+        .peer_id_registry
+        .is_active(&manager.active_path().peer_connection_id));
+    assert!(manager[path_id(0)].quality.is_degraded());
+    assert_eq!(publisher.path_degraded, 1);
+    assert_eq!(publisher.migration_triggered, 1);
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-9
+//= type=test
+//# Clients are responsible for initiating all migrations.
+#[test]
+fn server_does_not_migrate_away_from_degraded_path() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut helper = helper_manager_with_paths(&mut publisher);
+    let third_conn_id = connection::PeerId::try_from_bytes(&[3]).unwrap();
+    assert!(helper
+        .manager
+        .on_new_connection_id(&third_conn_id, 3, 1, &TEST_TOKEN_3, &mut publisher)
+        .is_ok());
+
+    // Trigger:
+    // - 20% of the packets on the active path are lost
+    degrade_path(&mut helper.manager[helper.first_path_id], helper.now, 5);
+    helper
+        .manager
+        .check_path_quality(&mut random::testing::Generator(123), &mut publisher);
+
+    // Expectation:
+    // - the degradation is reported, but the server stays on the active path
+    assert_eq!(helper.manager.active_path_id(), helper.first_path_id);
+    assert_eq!(publisher.path_degraded, 1);
+    assert_eq!(publisher.migration_triggered, 0);
+}
+
+#[test]
+fn stay_on_degraded_path_without_alternative() {
+    // Setup:
+    let mut publisher = Publisher::no_snapshot();
+    let mut manager = client_manager_with_previous_path(&mut publisher);
+    let now = NoopClock {}.get_time();
+
+    // the only other validated path is also degraded
+    degrade_path(&mut manager[path_id(1)], now, 5);
+    assert!(manager[path_id(1)].quality.poll_degradation().is_some());
+
+    // Trigger:
+    degrade_path(&mut manager[path_id(0)], now, 5);
+    manager.check_path_quality(&mut random::testing::Generator(123), &mut publisher);
+    manager.check_path_quality(&mut random::testing::Generator(123), &mut publisher);
+
+    // Expectation:
+    // - degradation is only reported once and the connection stays on the active path
+    assert_eq!(manager.active_path_id(), path_id(0));
+    assert_eq!(publisher.path_degraded, 1);
+    assert_eq!(publisher.migration_triggered, 0);
+}
+
+fn client_path(addr: &str, peer_id: connection::PeerId) -> ClientPath {
+    let addr: SocketAddr = addr.parse().unwrap();
+    let mut path = ClientPath::new(
+        SocketAddress::from(addr).into(),
+        peer_id,
+        connection::LocalId::TEST_ID,
+        RttEstimator::new(Duration::from_millis(30)),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+    // simulate a successful path validation
+    path.on_handshake_packet();
+    path
+}
+
 // creates a test path_manager. also check out `helper_manager_with_paths`
 // which calls this helper with preset options
 pub fn helper_manager_with_paths_base(
@@ -8,7 +8,9 @@ use crate::{
     contexts::WriteContext,
     endpoint,
     endpoint::Type,
-    recovery::{congestion_controller, CongestionController, RttEstimator},
+    recovery::{
+        congestion_controller, CongestionController, HandshakeCongestionIsolation, RttEstimator,
+    },
     transmission::{self, Mode},
 };
 use s2n_quic_core::{
@@ -18,6 +20,7 @@ use s2n_quic_core::{
     time::{timer, Timestamp},
 };
 
+pub(crate) mod bandwidth_probe;
 mod challenge;
 pub(crate) mod ecn;
 mod manager;
@@ -25,6 +28,7 @@ pub(crate) mod mtu;
 
 pub use challenge::*;
 pub use manager::*;
+pub use mtu::PmtudState;
 
 /// re-export core
 pub use s2n_quic_core::path::*;
@@ -52,14 +56,22 @@ pub struct Path<Config: endpoint::Config> {
     pub rtt_estimator: RttEstimator,
     /// The congestion controller for the path
     pub congestion_controller: <Config::CongestionControllerEndpoint as congestion_controller::Endpoint>::CongestionController,
+    /// Keeps the ECN congestion signals of the handshake away from the congestion controller
+    pub handshake_congestion_isolation: HandshakeCongestionIsolation,
     /// Probe timeout backoff multiplier
     pub pto_backoff: u32,
     /// Tracks whether this path has passed Address or Path validation
     state: State,
+    /// The number of bytes which can be sent for each byte received before validation
+    amplification_factor: u32,
     /// Controller for determining the maximum transmission unit of the path
     pub mtu_controller: mtu::Controller,
     /// Controller for determining the ECN capability of the path
     pub ecn_controller: ecn::Controller,
+    /// Controller for probing the bandwidth available on the path
+    pub bandwidth_probe: bandwidth_probe::Controller,
+    /// Monitors the loss rate and RTT of the path for degradation
+    pub quality: quality::PathQualityMonitor,
 
     /// True if the path has been validated by the peer
     peer_validated: bool,
@@ -90,10 +102,14 @@ impl<Config: endpoint::Config> Clone for Path<Config> {
             local_connection_id: self.local_connection_id,
             rtt_estimator: self.rtt_estimator,
             congestion_controller: self.congestion_controller.clone(),
+            handshake_congestion_isolation: self.handshake_congestion_isolation,
             pto_backoff: self.pto_backoff,
             state: self.state,
+            amplification_factor: self.amplification_factor,
             mtu_controller: self.mtu_controller.clone(),
             ecn_controller: self.ecn_controller.clone(),
+            bandwidth_probe: self.bandwidth_probe.clone(),
+            quality: self.quality.clone(),
             peer_validated: self.peer_validated,
             challenge: self.challenge.clone(),
             response_data: self.response_data,
@@ -137,10 +153,14 @@ impl<Config: endpoint::Config> Path<Config> {
             local_connection_id,
             rtt_estimator,
             congestion_controller,
+            handshake_congestion_isolation: HandshakeCongestionIsolation::default(),
             pto_backoff: INITIAL_PTO_BACKOFF,
             state,
+            amplification_factor: MAX_AMPLIFICATION_FACTOR,
             mtu_controller: mtu::Controller::new(max_mtu, &peer_socket_address),
             ecn_controller: ecn::Controller::default(),
+            bandwidth_probe: bandwidth_probe::Controller::default(),
+            quality: quality::PathQualityMonitor::default(),
             peer_validated,
             challenge: Challenge::disabled(),
             response_data: None,
@@ -149,6 +169,14 @@ impl<Config: endpoint::Config> Path<Config> {
         }
     }
 
+    /// Sets the number of bytes which can be sent for each byte received before the path is
+    /// validated
+    #[inline]
+    pub fn set_amplification_factor(&mut self, factor: u32) {
+        debug_assert!((1..=MAX_AMPLIFICATION_FACTOR).contains(&factor));
+        self.amplification_factor = factor;
+    }
+
     #[inline]
     pub fn remote_address(&self) -> RemoteAddress {
         self.handle.remote_address()
@@ -217,7 +245,7 @@ impl<Config: endpoint::Config> Path<Config> {
         //# received.
         //
         if let State::AmplificationLimited { tx_allowance } = &mut self.state {
-            *tx_allowance += bytes.saturating_mul(3) as u32;
+            *tx_allowance += bytes.saturating_mul(self.amplification_factor as usize) as u32;
         }
 
         was_at_amplification_limit && !self.at_amplification_limit()
@@ -241,6 +269,7 @@ impl<Config: endpoint::Config> Path<Config> {
             self.rtt_estimator.smoothed_rtt(),
             publisher,
         );
+        self.bandwidth_probe.on_timeout(timestamp);
     }
 
     /// Returns true if this path is able to transmit packets at the given timestamp
@@ -448,8 +477,13 @@ impl<Config: endpoint::Config> Path<Config> {
             // - Expanding to the full MTU allows for MTU validation during connection migration.
             // - Networking infrastructure cares more about number of packets than bytes for
             // anti-amplification.
+            //
+            // A server configured with a lower factor opted into a stricter limit, so the
+            // datagrams are clamped to the remaining allowance.
             State::AmplificationLimited { tx_allowance } => {
-                if tx_allowance > 0 {
+                if self.amplification_factor < MAX_AMPLIFICATION_FACTOR {
+                    requested_size.min(mtu).min(*tx_allowance as usize)
+                } else if tx_allowance > 0 {
                     requested_size.min(mtu)
                 } else {
                     0
@@ -538,6 +572,15 @@ impl<Config: endpoint::Config> Path<Config> {
         self.mtu_controller.max_mtu()
     }
 
+    /// Returns the current state of path MTU discovery on this path
+    ///
+    /// Each path discovers its MTU independently, so a path that was migrated to searches
+    /// from the BASE_PLPMTU once it has been validated.
+    #[inline]
+    pub fn pmtud_state(&self) -> PmtudState {
+        self.mtu_controller.pmtud_state()
+    }
+
     /// Returns `true` if the congestion window does not have sufficient space for a packet of
     /// size `mtu` considering the current bytes in flight and the additional `bytes_sent` provided
     #[inline]
@@ -574,6 +617,7 @@ impl<Config: endpoint::Config> timer::Provider for Path<Config> {
         self.challenge.timers(query)?;
         self.mtu_controller.timers(query)?;
         self.ecn_controller.timers(query)?;
+        self.bandwidth_probe.timers(query)?;
 
         Ok(())
     }
@@ -581,7 +625,7 @@ impl<Config: endpoint::Config> timer::Provider for Path<Config> {
 
 impl<Config: endpoint::Config> transmission::interest::Provider for Path<Config> {
     /// Indicate if the path is interested in transmitting PATH_CHALLENGE or
-    /// PATH_RESPONSE frames.
+    /// PATH_RESPONSE frames, or bandwidth probe packets.
     #[inline]
     fn transmission_interest<Q: transmission::interest::Query>(
         &self,
@@ -596,6 +640,7 @@ impl<Config: endpoint::Config> transmission::interest::Provider for Path<Config>
         }
 
         self.challenge.transmission_interest(query)?;
+        self.bandwidth_probe.transmission_interest(query)?;
 
         Ok(())
     }
@@ -977,6 +1022,32 @@ mod tests {
         assert!(path.is_validated());
     }
 
+    #[test]
+    fn amplification_factor_test() {
+        let mut path = testing::helper_path_server();
+        path.set_amplification_factor(1);
+
+        // the server can respond to the client's Initial with a datagram of the same size
+        assert!(path.on_bytes_received(1200));
+        assert_eq!(path.clamp_mtu(1500, Mode::Normal), 1200);
+        path.on_bytes_transmitted(1200);
+        assert!(path.at_amplification_limit());
+
+        // a partial allowance doesn't allow sending a full datagram
+        assert!(!path.on_bytes_received(600));
+        assert_eq!(path.clamp_mtu(1500, Mode::Normal), 600);
+        assert!(path.at_amplification_limit());
+
+        // the next datagram from the client unblocks the server
+        assert!(path.on_bytes_received(600));
+        assert_eq!(path.clamp_mtu(1500, Mode::Normal), 1200);
+
+        // validating the path lifts the limit
+        path.on_validated();
+        path.on_bytes_transmitted(1200);
+        assert!(!path.at_amplification_limit());
+    }
+
     #[test]
     fn amplification_limited_mtu_test() {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1
@@ -37,6 +37,18 @@ enum State {
     SearchComplete,
 }
 
+/// The progress of path MTU discovery on a path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PmtudState {
+    /// The path is searching for a larger MTU, or will once it has been validated
+    Probing { current_probe_size: u16 },
+    /// The search has completed after confirming an MTU larger than the BASE_PLPMTU
+    Complete { pmtu: u16 },
+    /// The search has completed without confirming an MTU larger than the BASE_PLPMTU,
+    /// or a black hole caused the MTU to be reset to the BASE_PLPMTU
+    Failed,
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-14.3
 //# Endpoints SHOULD set the initial value of BASE_PLPMTU (Section 5.1 of
 //# [DPLPMTUD]) to be consistent with QUIC's smallest allowed maximum
@@ -348,6 +360,25 @@ impl Controller {
         self.probed_size as usize
     }
 
+    /// Returns the current state of path MTU discovery
+    pub fn pmtud_state(&self) -> PmtudState {
+        match self.state {
+            State::Disabled | State::SearchRequested | State::Searching(_, _) => {
+                PmtudState::Probing {
+                    current_probe_size: self.probed_size,
+                }
+            }
+            // the path could never support a larger MTU, so there was nothing to discover
+            State::SearchComplete
+                if self.max_udp_payload.saturating_sub(BASE_PLPMTU) < PROBE_THRESHOLD =>
+            {
+                PmtudState::Complete { pmtu: self.plpmtu }
+            }
+            State::SearchComplete if self.plpmtu == BASE_PLPMTU => PmtudState::Failed,
+            State::SearchComplete => PmtudState::Complete { pmtu: self.plpmtu },
+        }
+    }
+
     /// Sets `probed_size` to the next MTU size to probe for based on a binary search
     fn update_probed_size(&mut self) {
         //= https://www.rfc-editor.org/rfc/rfc8899#section-5.3.2
@@ -545,6 +576,97 @@ mod test {
         );
     }
 
+    /// Acknowledges or loses probes until the search completes
+    fn complete_search(controller: &mut Controller, path_max_udp_payload: u16) {
+        let mut cc = CongestionController::default();
+        let mut publisher = Publisher::no_snapshot();
+        let now = now();
+        let mut packet_number = 0;
+
+        controller.enable();
+
+        while let PmtudState::Probing { current_probe_size } = controller.pmtud_state() {
+            assert_eq!(current_probe_size, controller.probed_size);
+            let pn = pn(packet_number);
+            packet_number += 1;
+            controller.probe_count += 1;
+            controller.state = State::Searching(pn, now);
+
+            if current_probe_size <= path_max_udp_payload {
+                controller.on_packet_ack(
+                    pn,
+                    current_probe_size,
+                    &mut cc,
+                    path::Id::test_id(),
+                    &mut publisher,
+                );
+            } else {
+                controller.on_packet_loss(
+                    pn,
+                    current_probe_size,
+                    now,
+                    &mut cc,
+                    path::Id::test_id(),
+                    &mut publisher,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pmtud_state() {
+        let mut controller = new_controller(1500);
+        assert_eq!(
+            PmtudState::Probing {
+                current_probe_size: 1472
+            },
+            controller.pmtud_state()
+        );
+
+        // A 1500 MTU Ethernet path confirms the initial probe
+        complete_search(&mut controller, 1472);
+        assert_eq!(
+            PmtudState::Complete { pmtu: 1472 },
+            controller.pmtud_state()
+        );
+
+        // A path that can't support anything larger than the BASE_PLPMTU
+        let mut controller = new_controller(1500);
+        complete_search(&mut controller, BASE_PLPMTU);
+        assert_eq!(PmtudState::Failed, controller.pmtud_state());
+
+        // A path with a max MTU that doesn't allow probing has nothing to discover
+        let mut controller = new_controller(BASE_PLPMTU + UDP_HEADER_LEN + IPV4_MIN_HEADER_LEN);
+        complete_search(&mut controller, BASE_PLPMTU);
+        assert_eq!(
+            PmtudState::Complete { pmtu: BASE_PLPMTU },
+            controller.pmtud_state()
+        );
+    }
+
+    /// A path migrating from a 1500 MTU Ethernet path to a 1280 MTU IPv6 tunnel gets a new
+    /// controller that searches from the BASE_PLPMTU
+    #[test]
+    fn pmtud_state_ipv6_tunnel() {
+        let addr: SocketAddr = "[2001:0db8:85a3:0001:0002:8a2e:0370:7334]:9000"
+            .parse()
+            .unwrap();
+        let mut controller = Controller::new(1500.try_into().unwrap(), &addr.into());
+        assert_eq!(BASE_PLPMTU as usize, controller.mtu());
+
+        // 1280 byte IPv6 MTU minus the IPv6 and UDP headers
+        let tunnel_max_udp_payload = 1280 - IPV6_MIN_HEADER_LEN - UDP_HEADER_LEN;
+        complete_search(&mut controller, tunnel_max_udp_payload);
+
+        match controller.pmtud_state() {
+            PmtudState::Complete { pmtu } => {
+                assert!(pmtu <= tunnel_max_udp_payload);
+                assert!(tunnel_max_udp_payload - pmtu < PROBE_THRESHOLD);
+            }
+            state => panic!("unexpected state {:?}", state),
+        }
+    }
+
     #[test]
     fn enable_already_enabled() {
         let mut controller = new_controller(1500);
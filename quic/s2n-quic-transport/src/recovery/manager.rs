@@ -6,7 +6,12 @@ use crate::{
     endpoint,
     path::{self, ecn::ValidationOutcome, path_event, Path},
     recovery::{
-        manager::persistent_congestion::PersistentCongestionCalculator, SentPacketInfo, SentPackets,
+        manager::{
+            non_congestion_loss::NonCongestionLossDetector,
+            persistent_congestion::PersistentCongestionCalculator, rack::Rack,
+            retransmission::RetransmissionDeduplicator,
+        },
+        SentFrames, SentPacketInfo, SentPackets,
     },
     transmission,
 };
@@ -20,7 +25,7 @@ use s2n_quic_core::{
     frame,
     frame::ack::EcnCounts,
     inet::ExplicitCongestionNotification,
-    packet::number::{PacketNumber, PacketNumberRange, PacketNumberSpace},
+    packet::number::{self, PacketNumber, PacketNumberRange, PacketNumberSpace},
     recovery::{congestion_controller, CongestionController, RttEstimator, K_GRANULARITY},
     time::{timer, Timer, Timestamp},
     transport,
@@ -43,6 +48,10 @@ pub struct Manager<Config: endpoint::Config> {
     //  These are packets that are pending acknowledgement.
     sent_packets: SentPackets<<<Config::CongestionControllerEndpoint as congestion_controller::Endpoint>::CongestionController as congestion_controller::CongestionController>::PacketInfo>,
 
+    // The frames contained in packets pending acknowledgement. Frames are only recorded
+    // when the event subscriber tracks lost frames.
+    sent_frames: number::Map<SentFrames>,
+
     // Timer set when packets may be declared lost at a time in the future
     loss_timer: Timer,
 
@@ -64,6 +73,18 @@ pub struct Manager<Config: endpoint::Config> {
 
     // The total ecn counts for outstanding (unacknowledged) packets
     sent_packet_ecn_counts: EcnCounts,
+
+    // Distinguishes packets lost due to reordering from packets lost due to congestion, for
+    // reporting through events
+    non_congestion_loss_detector: NonCongestionLossDetector,
+
+    // Prevents data acknowledged in both the original packet and its retransmission from
+    // being credited to the delivery rate twice
+    retransmissions: RetransmissionDeduplicator,
+
+    // Detects lost packets based on the send time of the most recently delivered packet,
+    // instead of the time and packet thresholds. `None` if RACK is disabled.
+    rack: Option<Rack>,
 }
 
 //= https://www.rfc-editor.org/rfc/rfc9002#section-6.1.1
@@ -123,11 +144,22 @@ impl<Config: endpoint::Config> Manager<Config> {
             space,
             largest_acked_packet: None,
             sent_packets: SentPackets::default(),
+            sent_frames: number::Map::default(),
             loss_timer: Timer::default(),
             pto: Pto::default(),
             time_of_last_ack_eliciting_packet: None,
             baseline_ecn_counts: EcnCounts::default(),
             sent_packet_ecn_counts: EcnCounts::default(),
+            non_congestion_loss_detector: NonCongestionLossDetector::default(),
+            retransmissions: RetransmissionDeduplicator::default(),
+            rack: None,
+        }
+    }
+
+    /// Detects lost packets with RACK (RFC 8985) rather than the packet and time thresholds
+    pub fn enable_rack(&mut self) {
+        if self.rack.is_none() {
+            self.rack = Some(Rack::default());
         }
     }
 
@@ -221,6 +253,10 @@ impl<Config: endpoint::Config> Manager<Config> {
             0
         };
 
+        if let Some(rack) = self.rack.as_mut() {
+            rack.on_packet_sent(packet_number);
+        }
+
         let path_id = context.path_id();
         let path = context.path_mut();
         let cc_packet_info = path.congestion_controller.on_packet_sent(
@@ -260,6 +296,22 @@ impl<Config: endpoint::Config> Manager<Config> {
         }
     }
 
+    /// Records the frames contained in a sent packet so they can be reported if the packet is lost
+    ///
+    /// If the packet retransmits data which is still in flight, the bytes are only credited to
+    /// the delivery rate once if both packets are acknowledged.
+    #[inline]
+    pub fn on_frames_sent(&mut self, packet_number: PacketNumber, sent_frames: SentFrames) {
+        if let Some(original_packet_number) = sent_frames.retransmission_of() {
+            self.retransmissions
+                .on_retransmission_sent(original_packet_number, packet_number);
+        }
+
+        if !sent_frames.is_empty() {
+            self.sent_frames.insert(packet_number, sent_frames);
+        }
+    }
+
     /// Updates the PTO timer
     pub fn update_pto_timer(
         &mut self,
@@ -463,12 +515,27 @@ impl<Config: endpoint::Config> Manager<Config> {
             context.validate_packet_ack(timestamp, &pn_range)?;
             // notify components of packets acked
             context.on_packet_ack(timestamp, &pn_range);
+            // packets that were previously declared lost may be acknowledged if reordered
+            if self.non_congestion_loss_detector.on_packet_ack(&pn_range) {
+                if let Some(rack) = self.rack.as_mut() {
+                    rack.on_spurious_loss();
+                }
+                context.path_mut().congestion_controller.on_spurious_loss();
+            }
 
             let mut newly_acked_range: Option<(PacketNumber, PacketNumber)> = None;
 
+            // The frames in acknowledged packets are no longer needed. Dropping the iterator
+            // removes the entries in the range.
+            let _ = self.sent_frames.remove_range(pn_range);
+
             for (packet_number, acked_packet_info) in self.sent_packets.remove_range(pn_range) {
                 newly_acked_packets.push(acked_packet_info);
 
+                if let Some(rack) = self.rack.as_mut() {
+                    rack.on_packet_ack(packet_number, acked_packet_info.time_sent, timestamp);
+                }
+
                 if largest_newly_acked.map_or(true, |(pn, _)| packet_number > pn) {
                     largest_newly_acked = Some((packet_number, acked_packet_info));
                 }
@@ -495,6 +562,25 @@ impl<Config: endpoint::Config> Manager<Config> {
                 );
                 path.ecn_controller
                     .on_packet_ack(acked_packet_info.time_sent, acked_packet_info.ecn);
+                if acked_packet_info.sent_bytes > 0
+                    && !acked_packet_info.transmission_mode.is_mtu_probing()
+                {
+                    path.quality.on_packet_ack(timestamp);
+                }
+                let path_id = acked_packet_info.path_id;
+                let credited_bytes = if self.retransmissions.on_packet_ack(packet_number) {
+                    acked_packet_info.sent_bytes
+                } else {
+                    0
+                };
+                path.bandwidth_probe.on_packet_ack(
+                    packet_number,
+                    credited_bytes,
+                    timestamp,
+                    path.rtt_estimator.smoothed_rtt(),
+                    path_event!(path, path_id),
+                    publisher,
+                );
             }
 
             if let Some((start, end)) = newly_acked_range {
@@ -547,6 +633,11 @@ impl<Config: endpoint::Config> Manager<Config> {
                 is_handshake_confirmed,
                 largest_acked_packet_number.space(),
             );
+            path.quality.on_rtt_sample(
+                path.rtt_estimator.latest_rtt(),
+                path.rtt_estimator.min_rtt(),
+                timestamp,
+            );
 
             let slow_start = path.congestion_controller.is_slow_start();
             let congestion_window = path.congestion_controller.congestion_window();
@@ -621,6 +712,7 @@ impl<Config: endpoint::Config> Manager<Config> {
                         cause: SlowStartExitCause::Other,
                         congestion_window,
                     });
+                    Self::publish_startup_completed(path, path_id, publisher);
                 }
             }
 
@@ -667,6 +759,7 @@ impl<Config: endpoint::Config> Manager<Config> {
         let path = context.path_mut();
 
         if current_path_acked_bytes > 0 {
+            let slow_start = path.congestion_controller.is_slow_start();
             path.congestion_controller.on_ack(
                 largest_newly_acked.time_sent,
                 current_path_acked_bytes,
@@ -675,8 +768,31 @@ impl<Config: endpoint::Config> Manager<Config> {
                 random_generator,
                 timestamp,
             );
+            if slow_start && !path.congestion_controller.is_slow_start() {
+                Self::publish_startup_completed(path, current_path_id, publisher);
+            }
 
             self.update_pto_timer(path, timestamp, is_handshake_confirmed);
+
+            // Notify components the congestion controller was updated
+            context.on_congestion_controller_update();
+        }
+    }
+
+    /// Publishes a `StartupCompleted` event if the congestion controller of the path has just
+    /// completed its startup phase
+    #[inline]
+    fn publish_startup_completed<Pub: event::ConnectionPublisher>(
+        path: &Path<Config>,
+        path_id: path::Id,
+        publisher: &mut Pub,
+    ) {
+        if let Some(startup) = path.congestion_controller.startup_completed() {
+            publisher.on_startup_completed(event::builder::StartupCompleted {
+                path: path_event!(path, path_id),
+                duration: startup.duration,
+                reason: startup.reason.into_event(),
+            });
         }
     }
 
@@ -710,23 +826,32 @@ impl<Config: endpoint::Config> Manager<Config> {
             //# Notification (ECN) [RFC3168] [RFC8311], QUIC treats a Congestion
             //# Experienced (CE) codepoint in the IP header as a signal of
             //# congestion.
-            context
-                .path_mut()
-                .congestion_controller
-                .on_explicit_congestion(ce_count.as_u64(), timestamp);
-            if slow_start && !context.path().congestion_controller.is_slow_start() {
+            //
+            // CE marks on Initial and Handshake packets are counted separately so the congestion
+            // signals of the handshake don't end slow start or reduce the congestion window
+            // the application data is sent with.
+            let path = context.path_mut();
+            let is_congestion_event = path.handshake_congestion_isolation.on_explicit_congestion(
+                self.space,
+                ce_count.as_u64(),
+                timestamp,
+                &mut path.congestion_controller,
+            );
+            if is_congestion_event {
+                if slow_start && !context.path().congestion_controller.is_slow_start() {
+                    let path = context.path();
+                    publisher.on_slow_start_exited(event::builder::SlowStartExited {
+                        path: path_event!(path, path_id),
+                        cause: SlowStartExitCause::Ecn,
+                        congestion_window,
+                    });
+                }
                 let path = context.path();
-                publisher.on_slow_start_exited(event::builder::SlowStartExited {
+                publisher.on_congestion(event::builder::Congestion {
                     path: path_event!(path, path_id),
-                    cause: SlowStartExitCause::Ecn,
-                    congestion_window,
-                });
+                    source: CongestionSource::Ecn,
+                })
             }
-            let path = context.path();
-            publisher.on_congestion(event::builder::Congestion {
-                path: path_event!(path, path_id),
-                source: CongestionSource::Ecn,
-            })
         }
 
         self.baseline_ecn_counts = ack_frame_ecn_counts.unwrap_or_default();
@@ -783,6 +908,10 @@ impl<Config: endpoint::Config> Manager<Config> {
         // older than the largest acked packet, but not old enough to be considered lost yet
         self.loss_timer.cancel();
 
+        let time_threshold = Self::calculate_loss_time_threshold(&context.path().rtt_estimator);
+        self.non_congestion_loss_detector
+            .on_timeout(now, time_threshold);
+
         let (persistent_congestion_duration, sent_packets_to_remove) =
             self.detect_lost_packets(now, context, publisher);
 
@@ -824,18 +953,36 @@ impl<Config: endpoint::Config> Manager<Config> {
             let path = &context.path_by_id(unacked_path_id);
             // Calculate how long we wait until a packet is declared lost
             let time_threshold = Self::calculate_loss_time_threshold(&path.rtt_estimator);
+
+            let mut packet_number_threshold_exceeded = largest_acked_packet
+                .checked_distance(unacked_packet_number)
+                .expect("largest_acked_packet >= unacked_packet_number")
+                >= K_PACKET_THRESHOLD;
+
             // Calculate at what time this particular packet is considered lost based on the
             // current path `time_threshold`
-            let packet_lost_time = unacked_sent_info.time_sent + time_threshold;
+            let packet_lost_time = if let Some(rack) = self.rack.as_ref() {
+                let rack_lost_time = rack.loss_time(
+                    unacked_packet_number,
+                    unacked_sent_info.time_sent,
+                    packet_number_threshold_exceeded,
+                    &path.rtt_estimator,
+                );
+
+                // RACK replaces the packet threshold, and can only declare packets lost which
+                // were sent before the most recently delivered packet
+                packet_number_threshold_exceeded = false;
+                match rack_lost_time {
+                    Some(rack_lost_time) => rack_lost_time,
+                    None => break,
+                }
+            } else {
+                unacked_sent_info.time_sent + time_threshold
+            };
 
             // If the `packet_lost_time` exceeds the current time, it's lost
             let time_threshold_exceeded = packet_lost_time.has_elapsed(now);
 
-            let packet_number_threshold_exceeded = largest_acked_packet
-                .checked_distance(unacked_packet_number)
-                .expect("largest_acked_packet >= unacked_packet_number")
-                >= K_PACKET_THRESHOLD;
-
             //= https://www.rfc-editor.org/rfc/rfc9002#section-6.1
             //# A packet is declared lost if it meets all of the following
             //# conditions:
@@ -891,6 +1038,35 @@ impl<Config: endpoint::Config> Manager<Config> {
         )
     }
 
+    /// Returns true if the lost packet was most likely reordered rather than lost due to
+    /// congestion
+    fn is_reordered(
+        &mut self,
+        packet_number: PacketNumber,
+        time_sent: Timestamp,
+        rtt_estimator: &RttEstimator,
+        now: Timestamp,
+    ) -> bool {
+        let largest_acked_packet = if let Some(largest_acked_packet) = self.largest_acked_packet {
+            largest_acked_packet
+        } else {
+            return false;
+        };
+
+        let time_threshold_exceeded =
+            (time_sent + Self::calculate_loss_time_threshold(rtt_estimator)).has_elapsed(now);
+
+        !self
+            .non_congestion_loss_detector
+            .on_packet_lost(
+                packet_number,
+                largest_acked_packet,
+                time_threshold_exceeded,
+                now,
+            )
+            .is_congestion()
+    }
+
     fn remove_lost_packets<Ctx: Context<Config>, Pub: event::ConnectionPublisher>(
         &mut self,
         now: Timestamp,
@@ -937,6 +1113,22 @@ impl<Config: endpoint::Config> Manager<Config> {
                 path.congestion_controller
                     .on_packet_discarded(sent_info.sent_bytes as usize);
             } else if sent_info.sent_bytes > 0 {
+                // A reordered packet can't be told apart from a lost one until it is
+                // acknowledged, so the classification is only reported. The congestion
+                // controller responds to the loss either way, and is notified if the loss
+                // turns out to be spurious.
+                if self.is_reordered(packet_number, sent_info.time_sent, &path.rtt_estimator, now) {
+                    let path_id = sent_info.path_id;
+                    publisher.on_spurious_loss_detected(event::builder::SpuriousLossDetected {
+                        packet_header: event::builder::PacketHeader::new(
+                            packet_number,
+                            publisher.quic_version(),
+                        ),
+                        path: path_event!(path, path_id),
+                        bytes_lost: sent_info.sent_bytes,
+                    });
+                }
+
                 let slow_start = path.congestion_controller.is_slow_start();
                 let congestion_window = path.congestion_controller.congestion_window();
                 path.congestion_controller.on_packet_lost(
@@ -947,6 +1139,7 @@ impl<Config: endpoint::Config> Manager<Config> {
                     random_generator,
                     now,
                 );
+                path.quality.on_packet_lost(now);
                 if slow_start && !path.congestion_controller.is_slow_start() {
                     let path_id = sent_info.path_id;
                     publisher.on_slow_start_exited(event::builder::SlowStartExited {
@@ -968,6 +1161,22 @@ impl<Config: endpoint::Config> Manager<Config> {
                 is_mtu_probe: sent_info.transmission_mode.is_mtu_probing(),
             });
 
+            self.retransmissions.on_packet_lost(packet_number);
+
+            if let Some(sent_frames) = self.sent_frames.remove(packet_number) {
+                let path_id = sent_info.path_id;
+                for frame in sent_frames {
+                    publisher.on_frame_lost(event::builder::FrameLost {
+                        packet_header: event::builder::PacketHeader::new(
+                            packet_number,
+                            publisher.quic_version(),
+                        ),
+                        path: path_event!(path, path_id),
+                        frame,
+                    });
+                }
+            }
+
             // Notify the MTU controller of packet loss even if it wasn't a probe since it uses
             // that information for blackhole detection.
             path.mtu_controller.on_packet_loss(
@@ -979,6 +1188,9 @@ impl<Config: endpoint::Config> Manager<Config> {
                 publisher,
             );
 
+            path.bandwidth_probe
+                .on_packet_loss(packet_number, sent_info.sent_bytes);
+
             let path_id = sent_info.path_id;
 
             // Notify the ECN controller of packet loss for blackhole detection.
@@ -1082,6 +1294,7 @@ pub trait Context<Config: endpoint::Config> {
         publisher: &mut Pub,
     );
     fn on_rtt_update(&mut self);
+    fn on_congestion_controller_update(&mut self);
 }
 
 impl<Config: endpoint::Config> transmission::interest::Provider for Manager<Config> {
@@ -1233,6 +1446,9 @@ impl transmission::interest::Provider for Pto {
     }
 }
 
+mod non_congestion_loss;
 mod persistent_congestion;
+mod rack;
+mod retransmission;
 #[cfg(test)]
 mod tests;
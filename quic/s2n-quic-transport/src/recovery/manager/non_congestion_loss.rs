@@ -0,0 +1,240 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::collections::VecDeque;
+use core::time::Duration;
+use s2n_quic_core::{
+    packet::number::{PacketNumber, PacketNumberRange},
+    time::Timestamp,
+};
+
+/// The maximum number of lost packets tracked while waiting for a late acknowledgement
+const MAX_TRACKED_LOSSES: usize = 32;
+
+/// The reason a packet was declared lost
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LossClassification {
+    /// The packet was lost due to congestion
+    ///
+    /// Correlated losses, such as a burst of packets dropped at the tail of a full queue,
+    /// are a genuine congestion signal and fall into this category.
+    Congestion,
+    /// The packet was most likely reordered rather than lost
+    Reordering,
+}
+
+impl LossClassification {
+    #[inline]
+    pub fn is_congestion(self) -> bool {
+        matches!(self, Self::Congestion)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LostPacket {
+    packet_number: PacketNumber,
+    /// The distance between the largest acknowledged packet and the lost packet at the
+    /// time the packet was declared lost
+    reordering_distance: u64,
+    time_lost: Timestamp,
+    classification: LossClassification,
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9002#section-6.1
+//# Spuriously declaring packets as lost leads to unnecessary
+//# retransmissions and may result in degraded performance due to the
+//# actions of the congestion controller upon detecting loss.
+/// Distinguishes packets declared lost due to reordering from packets lost due to congestion
+///
+/// Packets declared lost by the packet threshold which are later acknowledged were
+/// reordered by the network rather than lost. The detector records the largest reordering
+/// distance observed this way and classifies subsequent packet threshold losses within that
+/// distance as reordering. Packets declared lost by the time threshold are always classified
+/// as congestion.
+///
+/// The classification is a prediction, so it is only reported through events. It never
+/// prevents the congestion controller from responding to a loss.
+///
+/// If a packet classified as reordering is not acknowledged within the loss time threshold,
+/// the reordering estimate was wrong and is reset.
+#[derive(Debug, Default)]
+pub(crate) struct NonCongestionLossDetector {
+    /// The largest reordering distance observed on a packet that was declared lost
+    reordering_distance: u64,
+    /// Recently lost packets which may still be acknowledged
+    lost_packets: VecDeque<LostPacket>,
+}
+
+impl NonCongestionLossDetector {
+    /// Called for each packet declared lost
+    ///
+    /// Returns the classification of the loss.
+    pub fn on_packet_lost(
+        &mut self,
+        packet_number: PacketNumber,
+        largest_acked_packet: PacketNumber,
+        time_threshold_exceeded: bool,
+        now: Timestamp,
+    ) -> LossClassification {
+        let reordering_distance = largest_acked_packet
+            .checked_distance(packet_number)
+            .unwrap_or_default();
+
+        let classification =
+            if !time_threshold_exceeded && reordering_distance <= self.reordering_distance {
+                LossClassification::Reordering
+            } else {
+                LossClassification::Congestion
+            };
+
+        if self.lost_packets.len() == MAX_TRACKED_LOSSES {
+            if let Some(evicted) = self.lost_packets.pop_front() {
+                self.on_unacknowledged(evicted);
+            }
+        }
+
+        self.lost_packets.push_back(LostPacket {
+            packet_number,
+            reordering_distance,
+            time_lost: now,
+            classification,
+        });
+
+        classification
+    }
+
+    /// Called for each range of packets acknowledged by the peer
+    ///
+    /// Returns true if any of the acknowledged packets had previously been declared lost.
+    pub fn on_packet_ack(&mut self, acked_packets: &PacketNumberRange) -> bool {
+        let len = self.lost_packets.len();
+        let mut reordering_distance = self.reordering_distance;
+
+        self.lost_packets.retain(|lost_packet| {
+            if acked_packets.contains(lost_packet.packet_number) {
+                reordering_distance = reordering_distance.max(lost_packet.reordering_distance);
+                false
+            } else {
+                true
+            }
+        });
+
+        self.reordering_distance = reordering_distance;
+        len != self.lost_packets.len()
+    }
+
+    /// Discards lost packets which were declared lost more than `time_threshold` ago
+    pub fn on_timeout(&mut self, now: Timestamp, time_threshold: Duration) {
+        while let Some(lost_packet) = self.lost_packets.front().copied() {
+            if !(lost_packet.time_lost + time_threshold).has_elapsed(now) {
+                break;
+            }
+
+            self.lost_packets.pop_front();
+            self.on_unacknowledged(lost_packet);
+        }
+    }
+
+    /// Returns the largest reordering distance observed
+    #[cfg(test)]
+    pub fn reordering_distance(&self) -> u64 {
+        self.reordering_distance
+    }
+
+    #[inline]
+    fn on_unacknowledged(&mut self, lost_packet: LostPacket) {
+        // The packet was actually lost, so the reordering estimate can't be trusted
+        if lost_packet.classification == LossClassification::Reordering {
+            self.reordering_distance = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        packet::number::PacketNumberSpace,
+        time::{Clock, NoopClock},
+        varint::VarInt,
+    };
+
+    const TIME_THRESHOLD: Duration = Duration::from_millis(100);
+
+    fn pn(value: u8) -> PacketNumber {
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(value))
+    }
+
+    fn range(start: u8, end: u8) -> PacketNumberRange {
+        PacketNumberRange::new(pn(start), pn(end))
+    }
+
+    #[test]
+    fn reordering_test() {
+        let mut detector = NonCongestionLossDetector::default();
+        let now = NoopClock.get_time();
+
+        // nothing has been learned about reordering yet
+        assert_eq!(
+            detector.on_packet_lost(pn(1), pn(4), false, now),
+            LossClassification::Congestion
+        );
+
+        // the lost packet arrives late
+        assert!(detector.on_packet_ack(&range(1, 1)));
+        assert_eq!(detector.reordering_distance(), 3);
+
+        // later losses within the reordering distance are classified as reordering
+        assert_eq!(
+            detector.on_packet_lost(pn(11), pn(14), false, now),
+            LossClassification::Reordering
+        );
+
+        // losses beyond the reordering distance are congestion
+        assert_eq!(
+            detector.on_packet_lost(pn(20), pn(30), false, now),
+            LossClassification::Congestion
+        );
+
+        // losses detected by the time threshold are always congestion
+        assert_eq!(
+            detector.on_packet_lost(pn(31), pn(32), true, now),
+            LossClassification::Congestion
+        );
+    }
+
+    #[test]
+    fn reset_reordering_distance_test() {
+        let mut detector = NonCongestionLossDetector::default();
+        let now = NoopClock.get_time();
+
+        detector.on_packet_lost(pn(1), pn(4), false, now);
+        detector.on_packet_ack(&range(1, 1));
+
+        assert_eq!(
+            detector.on_packet_lost(pn(11), pn(14), false, now),
+            LossClassification::Reordering
+        );
+
+        // the packet is not acknowledged within the time threshold
+        detector.on_timeout(now + TIME_THRESHOLD / 2, TIME_THRESHOLD);
+        assert_eq!(detector.reordering_distance(), 3);
+        detector.on_timeout(now + TIME_THRESHOLD * 2, TIME_THRESHOLD);
+        assert_eq!(detector.reordering_distance(), 0);
+
+        assert_eq!(
+            detector.on_packet_lost(pn(21), pn(24), false, now + TIME_THRESHOLD * 2),
+            LossClassification::Congestion
+        );
+    }
+
+    #[test]
+    fn ack_unrelated_packets_test() {
+        let mut detector = NonCongestionLossDetector::default();
+        let now = NoopClock.get_time();
+
+        detector.on_packet_lost(pn(1), pn(4), false, now);
+        assert!(!detector.on_packet_ack(&range(5, 10)));
+        assert_eq!(detector.reordering_distance(), 0);
+    }
+}
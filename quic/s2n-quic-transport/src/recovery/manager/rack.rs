@@ -0,0 +1,288 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::{cmp::min, time::Duration};
+use s2n_quic_core::{packet::number::PacketNumber, recovery::RttEstimator, time::Timestamp};
+
+/// The number of rounds without spurious losses before the reordering window multiplier is
+/// reset
+const REO_WND_PERSIST: u8 = 16;
+
+/// Detects lost packets with the Recent ACKnowledgment (RACK) algorithm
+///
+/// Instead of counting how many packets were acknowledged after a packet, RACK compares the send
+/// time of each unacknowledged packet with the send time of the most recently sent packet that
+/// was delivered. A packet is lost if it was sent before that packet, and more than the RTT of that
+/// packet plus a reordering window has passed since it was sent.
+///
+/// The reordering window starts at a quarter of the min RTT once the peer has delivered packets
+/// out of order. Each round which found a spurious loss widens the window by another quarter of
+/// the min RTT, up to the smoothed RTT. After 16 rounds without spurious losses, the window
+/// shrinks back to a quarter of the min RTT.
+///
+/// See https://www.rfc-editor.org/rfc/rfc8985
+#[derive(Clone, Debug)]
+pub(crate) struct Rack {
+    /// The send time of the most recently sent packet that was delivered (RACK.xmit_ts)
+    xmit_ts: Option<Timestamp>,
+    /// The packet number of the most recently sent packet that was delivered (RACK.end_seq)
+    end_seq: Option<PacketNumber>,
+    /// The RTT of the most recently sent packet that was delivered (RACK.rtt)
+    rtt: Duration,
+    /// Set once a packet is delivered after a packet sent later (RACK.reordering_seen)
+    reordering_seen: bool,
+    /// The multiplier of the reordering window (RACK.reo_wnd_mult)
+    reo_wnd_mult: u32,
+    /// The rounds left until the multiplier is reset (RACK.reo_wnd_persist)
+    reo_wnd_persist: u8,
+    /// The largest packet number sent so far
+    largest_sent: Option<PacketNumber>,
+    /// The round ends once this packet, or a later one, is acknowledged
+    round_end: Option<PacketNumber>,
+    /// Set if a packet declared lost in the current round was acknowledged
+    spurious_loss_in_round: bool,
+}
+
+impl Default for Rack {
+    fn default() -> Self {
+        Self {
+            xmit_ts: None,
+            end_seq: None,
+            rtt: Duration::ZERO,
+            reordering_seen: false,
+            reo_wnd_mult: 1,
+            reo_wnd_persist: 0,
+            largest_sent: None,
+            round_end: None,
+            spurious_loss_in_round: false,
+        }
+    }
+}
+
+impl Rack {
+    /// Called for each packet sent
+    #[inline]
+    pub fn on_packet_sent(&mut self, packet_number: PacketNumber) {
+        self.largest_sent = Some(packet_number);
+    }
+
+    /// Called for each newly acknowledged packet, in ascending packet number order
+    #[inline]
+    pub fn on_packet_ack(
+        &mut self,
+        packet_number: PacketNumber,
+        time_sent: Timestamp,
+        now: Timestamp,
+    ) {
+        // A packet delivered after a packet which was sent later was reordered by the network
+        //
+        // See https://www.rfc-editor.org/rfc/rfc8985#section-6.2 step 3
+        if self
+            .end_seq
+            .map_or(false, |end_seq| packet_number < end_seq)
+        {
+            self.reordering_seen = true;
+        }
+
+        // See https://www.rfc-editor.org/rfc/rfc8985#section-6.2 step 2
+        if !self.is_sent_before_rack(packet_number, time_sent) {
+            self.xmit_ts = Some(time_sent);
+            self.end_seq = Some(packet_number);
+            self.rtt = now.saturating_duration_since(time_sent);
+        }
+
+        match self.round_end {
+            Some(round_end) if packet_number >= round_end => self.on_round_end(),
+            None => self.round_end = self.largest_sent,
+            _ => {}
+        }
+    }
+
+    /// Called when a packet which was declared lost is acknowledged
+    #[inline]
+    pub fn on_spurious_loss(&mut self) {
+        self.reordering_seen = true;
+        self.spurious_loss_in_round = true;
+    }
+
+    /// Returns the time at which a packet is considered lost
+    ///
+    /// Returns `None` if the packet was not sent before the most recently delivered packet, in
+    /// which case RACK can't declare it lost yet.
+    ///
+    /// `packet_threshold_exceeded` indicates that enough later packets were acknowledged for the
+    /// packet threshold to declare the packet lost. As long as no reordering was observed,
+    /// those packets are declared lost without waiting for the reordering window.
+    #[inline]
+    pub fn loss_time(
+        &self,
+        packet_number: PacketNumber,
+        time_sent: Timestamp,
+        packet_threshold_exceeded: bool,
+        rtt_estimator: &RttEstimator,
+    ) -> Option<Timestamp> {
+        if !self.is_sent_before_rack(packet_number, time_sent) {
+            return None;
+        }
+
+        let reo_wnd = self.reo_wnd(packet_threshold_exceeded, rtt_estimator);
+        Some(time_sent + self.rtt + reo_wnd)
+    }
+
+    /// Returns the reordering window (RACK.reo_wnd)
+    #[inline]
+    fn reo_wnd(&self, packet_threshold_exceeded: bool, rtt_estimator: &RttEstimator) -> Duration {
+        if !self.reordering_seen && packet_threshold_exceeded {
+            return Duration::ZERO;
+        }
+
+        min(
+            rtt_estimator.min_rtt() * self.reo_wnd_mult / 4,
+            rtt_estimator.smoothed_rtt(),
+        )
+    }
+
+    /// Returns true if the packet was sent before the most recently delivered packet
+    #[inline]
+    fn is_sent_before_rack(&self, packet_number: PacketNumber, time_sent: Timestamp) -> bool {
+        match (self.xmit_ts, self.end_seq) {
+            (Some(xmit_ts), Some(end_seq)) => {
+                time_sent < xmit_ts || (time_sent == xmit_ts && packet_number < end_seq)
+            }
+            _ => false,
+        }
+    }
+
+    /// Adjusts the reordering window to the spurious losses found in the round which ended
+    ///
+    /// See https://www.rfc-editor.org/rfc/rfc8985#section-6.2 step 4
+    #[inline]
+    fn on_round_end(&mut self) {
+        if core::mem::take(&mut self.spurious_loss_in_round) {
+            self.reo_wnd_mult = self.reo_wnd_mult.saturating_add(1);
+            self.reo_wnd_persist = REO_WND_PERSIST;
+        } else if self.reo_wnd_persist > 0 {
+            self.reo_wnd_persist -= 1;
+            if self.reo_wnd_persist == 0 {
+                self.reo_wnd_mult = 1;
+            }
+        }
+
+        self.round_end = self.largest_sent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        packet::number::PacketNumberSpace,
+        time::{Clock, NoopClock},
+        varint::VarInt,
+    };
+
+    fn pn(value: u8) -> PacketNumber {
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(value))
+    }
+
+    fn rtt_estimator(min_rtt: Duration) -> RttEstimator {
+        let mut rtt_estimator = RttEstimator::default();
+        rtt_estimator.update_rtt(
+            Duration::ZERO,
+            min_rtt,
+            NoopClock.get_time(),
+            true,
+            PacketNumberSpace::ApplicationData,
+        );
+        rtt_estimator
+    }
+
+    #[test]
+    fn loss_time_test() {
+        let now = NoopClock.get_time();
+        let rtt = Duration::from_millis(100);
+        let rtt_estimator = rtt_estimator(rtt);
+        let mut rack = Rack::default();
+
+        for i in 0..=3 {
+            rack.on_packet_sent(pn(i));
+        }
+
+        // nothing can be declared lost before a packet is delivered
+        assert_eq!(rack.loss_time(pn(0), now, true, &rtt_estimator), None);
+
+        // packet 1 is delivered before packet 0
+        let time_sent_1 = now + Duration::from_millis(1);
+        rack.on_packet_ack(pn(1), time_sent_1, time_sent_1 + rtt);
+
+        // packets sent after the delivered packet aren't judged yet
+        let time_sent_2 = now + Duration::from_millis(2);
+        assert_eq!(
+            rack.loss_time(pn(2), time_sent_2, false, &rtt_estimator),
+            None
+        );
+
+        // without any observed reordering, the packet threshold declares losses immediately
+        assert_eq!(
+            rack.loss_time(pn(0), now, true, &rtt_estimator),
+            Some(now + rtt)
+        );
+
+        // otherwise the packet is given a quarter of the min RTT to arrive
+        assert_eq!(
+            rack.loss_time(pn(0), now, false, &rtt_estimator),
+            Some(now + rtt + rtt / 4)
+        );
+
+        // packet 0 is delivered late, so reordering was seen
+        rack.on_packet_ack(pn(0), now, time_sent_1 + rtt);
+        assert!(rack.reordering_seen);
+        assert_eq!(
+            rack.loss_time(pn(0), now, true, &rtt_estimator),
+            Some(now + rtt + rtt / 4)
+        );
+    }
+
+    #[test]
+    fn reo_wnd_test() {
+        let now = NoopClock.get_time();
+        let rtt = Duration::from_millis(100);
+        let rtt_estimator = rtt_estimator(rtt);
+        let mut rack = Rack::default();
+
+        let mut next_packet = 0;
+        let mut run_round = |rack: &mut Rack, spurious_loss: bool| {
+            let packet_number = pn(next_packet);
+            next_packet += 1;
+            rack.on_packet_sent(packet_number);
+            if spurious_loss {
+                rack.on_spurious_loss();
+            }
+            rack.on_packet_ack(packet_number, now, now + rtt);
+        };
+
+        // the first acknowledgement starts the first round
+        run_round(&mut rack, false);
+        assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt / 4);
+
+        // each round with a spurious loss widens the window
+        run_round(&mut rack, true);
+        assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt / 2);
+        run_round(&mut rack, true);
+        assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt * 3 / 4);
+
+        // the window never exceeds the smoothed RTT
+        for _ in 0..10 {
+            run_round(&mut rack, true);
+        }
+        assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt);
+
+        // the window is kept for 16 rounds without spurious losses
+        for _ in 0..(REO_WND_PERSIST - 1) {
+            run_round(&mut rack, false);
+            assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt);
+        }
+        run_round(&mut rack, false);
+        assert_eq!(rack.reo_wnd(false, &rtt_estimator), rtt / 4);
+    }
+}
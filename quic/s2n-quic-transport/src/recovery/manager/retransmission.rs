@@ -0,0 +1,219 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::collections::VecDeque;
+use s2n_quic_core::packet::number::PacketNumber;
+
+/// The maximum number of retransmissions tracked while waiting for an acknowledgement
+const MAX_TRACKED_RETRANSMISSIONS: usize = 32;
+
+#[derive(Clone, Copy, Debug)]
+struct Retransmission {
+    /// The packet carrying the original transmission of the data, which serves as the
+    /// retransmit token shared by all retransmissions of the same data
+    original: PacketNumber,
+    /// The packet carrying the retransmitted data
+    retransmission: PacketNumber,
+    /// Set once either packet carrying the data has been acknowledged
+    is_credited: bool,
+    /// Set once the original packet has been acknowledged or declared lost
+    is_original_resolved: bool,
+    /// Set once the retransmission has been acknowledged or declared lost
+    is_retransmission_resolved: bool,
+}
+
+impl Retransmission {
+    #[inline]
+    fn is_resolved(&self) -> bool {
+        self.is_original_resolved && self.is_retransmission_resolved
+    }
+}
+
+/// Prevents data retransmitted after a probe timeout from being credited twice
+///
+/// When a PTO fires, the data in the outstanding packets may be retransmitted before the
+/// original packets are declared lost. If the original packets were merely delayed, the peer
+/// acknowledges both the original and the retransmission. Only the first acknowledgement
+/// of the data is credited to the delivery rate, otherwise the rate would be overestimated.
+///
+/// If the original packet was genuinely lost, only the retransmission is acknowledged and
+/// it is credited as usual.
+#[derive(Debug, Default)]
+pub(crate) struct RetransmissionDeduplicator {
+    retransmissions: VecDeque<Retransmission>,
+}
+
+impl RetransmissionDeduplicator {
+    /// Called when `retransmission` is sent carrying data from the `original` packet
+    pub fn on_retransmission_sent(&mut self, original: PacketNumber, retransmission: PacketNumber) {
+        debug_assert!(original < retransmission);
+
+        // inherit the state of previous retransmissions of the same data
+        let mut is_credited = false;
+        let mut is_original_resolved = false;
+        for entry in self
+            .retransmissions
+            .iter()
+            .filter(|e| e.original == original)
+        {
+            is_credited |= entry.is_credited;
+            is_original_resolved |= entry.is_original_resolved;
+        }
+
+        if self.retransmissions.len() == MAX_TRACKED_RETRANSMISSIONS {
+            self.retransmissions.pop_front();
+        }
+
+        self.retransmissions.push_back(Retransmission {
+            original,
+            retransmission,
+            is_credited,
+            is_original_resolved,
+            is_retransmission_resolved: false,
+        });
+    }
+
+    /// Called for each packet acknowledged by the peer
+    ///
+    /// Returns true if the bytes in the packet should be credited to the delivery rate.
+    pub fn on_packet_ack(&mut self, packet_number: PacketNumber) -> bool {
+        let original = match self.original(packet_number) {
+            Some(original) => original,
+            // the packet doesn't carry any retransmitted data
+            None => return true,
+        };
+
+        let mut is_credited = false;
+        for entry in self.retransmissions.iter_mut() {
+            if entry.original != original {
+                continue;
+            }
+
+            is_credited |= entry.is_credited;
+            entry.is_credited = true;
+            entry.is_original_resolved |= entry.original == packet_number;
+            entry.is_retransmission_resolved |= entry.retransmission == packet_number;
+        }
+
+        self.retransmissions.retain(|entry| !entry.is_resolved());
+
+        !is_credited
+    }
+
+    /// Called for each packet declared lost
+    pub fn on_packet_lost(&mut self, packet_number: PacketNumber) {
+        for entry in self.retransmissions.iter_mut() {
+            entry.is_original_resolved |= entry.original == packet_number;
+            entry.is_retransmission_resolved |= entry.retransmission == packet_number;
+        }
+
+        self.retransmissions.retain(|entry| !entry.is_resolved());
+    }
+
+    /// Returns the number of retransmissions being tracked
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.retransmissions.len()
+    }
+
+    /// Returns the retransmit token for the data carried in `packet_number`
+    #[inline]
+    fn original(&self, packet_number: PacketNumber) -> Option<PacketNumber> {
+        self.retransmissions
+            .iter()
+            .find(|entry| entry.original == packet_number || entry.retransmission == packet_number)
+            .map(|entry| entry.original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{packet::number::PacketNumberSpace, varint::VarInt};
+
+    fn pn(value: u8) -> PacketNumber {
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(value))
+    }
+
+    #[test]
+    fn untracked_packet_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+
+        assert!(deduplicator.on_packet_ack(pn(1)));
+        deduplicator.on_retransmission_sent(pn(2), pn(3));
+        assert!(deduplicator.on_packet_ack(pn(4)));
+    }
+
+    #[test]
+    fn original_and_retransmission_acked_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+        deduplicator.on_retransmission_sent(pn(1), pn(5));
+
+        assert!(deduplicator.on_packet_ack(pn(1)));
+        assert_eq!(deduplicator.len(), 1);
+        assert!(!deduplicator.on_packet_ack(pn(5)));
+        assert_eq!(deduplicator.len(), 0);
+
+        // the retransmission may also be acknowledged before the original
+        deduplicator.on_retransmission_sent(pn(6), pn(7));
+        assert!(deduplicator.on_packet_ack(pn(7)));
+        assert!(!deduplicator.on_packet_ack(pn(6)));
+        assert_eq!(deduplicator.len(), 0);
+    }
+
+    #[test]
+    fn original_lost_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+        deduplicator.on_retransmission_sent(pn(1), pn(5));
+
+        deduplicator.on_packet_lost(pn(1));
+        assert!(deduplicator.on_packet_ack(pn(5)));
+        assert_eq!(deduplicator.len(), 0);
+    }
+
+    #[test]
+    fn multiple_retransmissions_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+        deduplicator.on_retransmission_sent(pn(1), pn(5));
+        deduplicator.on_retransmission_sent(pn(1), pn(6));
+
+        assert!(deduplicator.on_packet_ack(pn(6)));
+        assert!(!deduplicator.on_packet_ack(pn(1)));
+        assert_eq!(deduplicator.len(), 1);
+        assert!(!deduplicator.on_packet_ack(pn(5)));
+        assert_eq!(deduplicator.len(), 0);
+
+        // a retransmission sent after the data was credited is not credited again
+        deduplicator.on_retransmission_sent(pn(7), pn(8));
+        assert!(deduplicator.on_packet_ack(pn(7)));
+        deduplicator.on_retransmission_sent(pn(7), pn(9));
+        assert!(!deduplicator.on_packet_ack(pn(8)));
+        assert!(!deduplicator.on_packet_ack(pn(9)));
+        assert_eq!(deduplicator.len(), 0);
+    }
+
+    #[test]
+    fn all_lost_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+        deduplicator.on_retransmission_sent(pn(1), pn(5));
+
+        deduplicator.on_packet_lost(pn(5));
+        assert_eq!(deduplicator.len(), 1);
+        deduplicator.on_packet_lost(pn(1));
+        assert_eq!(deduplicator.len(), 0);
+    }
+
+    #[test]
+    fn eviction_test() {
+        let mut deduplicator = RetransmissionDeduplicator::default();
+
+        for original in 0..=MAX_TRACKED_RETRANSMISSIONS as u8 {
+            deduplicator.on_retransmission_sent(pn(original), pn(original + 100));
+        }
+
+        assert_eq!(deduplicator.len(), MAX_TRACKED_RETRANSMISSIONS);
+        // the oldest retransmission is no longer tracked
+        assert!(deduplicator.on_packet_ack(pn(0)));
+        assert!(deduplicator.on_packet_ack(pn(100)));
+    }
+}
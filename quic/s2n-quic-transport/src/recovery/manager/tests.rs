@@ -3,14 +3,16 @@
 
 use super::*;
 use crate::{
-    ack::ack_ranges::AckRanges,
+    ack::{self, ack_ranges::AckRanges, AckManager},
     connection::{ConnectionIdMapper, InternalConnectionIdGenerator},
     contexts::testing::{MockWriteContext, OutgoingFrameBuffer},
     endpoint::{self, testing::Server as Config},
     path::MINIMUM_MTU,
     recovery,
     recovery::manager::PtoState::RequiresTransmission,
+    space::CryptoStream,
 };
+use bytes::Bytes;
 use core::{ops::RangeInclusive, time::Duration};
 use s2n_quic_core::{
     connection,
@@ -394,6 +396,7 @@ fn on_ack_frame() {
         Duration::from_millis(500)
     );
     assert_eq!(1, context.on_rtt_update_count);
+    assert_eq!(1, context.on_congestion_controller_update_count);
 
     // Reset the pto backoff to 2 so we can tell if it was reset
     context.path_mut().pto_backoff = 2;
@@ -427,6 +430,7 @@ fn on_ack_frame() {
         Duration::from_millis(500)
     );
     assert_eq!(1, context.on_rtt_update_count);
+    assert_eq!(1, context.on_congestion_controller_update_count);
 
     // Ack packets 7 to 9 (4 - 6 will be considered lost)
     let ack_receive_time = ack_receive_time + Duration::from_secs(1);
@@ -453,6 +457,7 @@ fn on_ack_frame() {
         Duration::from_millis(2500)
     );
     assert_eq!(2, context.on_rtt_update_count);
+    assert_eq!(2, context.on_congestion_controller_update_count);
 
     // Ack packet 10, but with a path that is not peer validated
     context.path_manager[unsafe { path::Id::new(0) }] = Path::new(
@@ -485,6 +490,7 @@ fn on_ack_frame() {
         Duration::from_millis(3000)
     );
     assert_eq!(3, context.on_rtt_update_count);
+    assert_eq!(3, context.on_congestion_controller_update_count);
 
     // Send and ack a non ack eliciting packet
     manager.on_packet_sent(
@@ -523,6 +529,7 @@ fn on_ack_frame() {
         Duration::from_millis(3000)
     );
     assert_eq!(3, context.on_rtt_update_count);
+    assert_eq!(4, context.on_congestion_controller_update_count);
 }
 
 #[test]
@@ -1000,6 +1007,63 @@ fn process_new_acked_packets_process_ecn() {
     assert!(context.path().ecn_controller.is_capable());
 }
 
+#[test]
+fn process_new_acked_packets_process_ecn_handshake() {
+    // Setup:
+    let space = PacketNumberSpace::Handshake;
+    let mut manager = Manager::new(space);
+    let packet_bytes = 128;
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let mut context = MockContext::new(&mut path_manager);
+    let time_sent = s2n_quic_platform::time::now() + Duration::from_secs(10);
+    let mut publisher = Publisher::snapshot();
+
+    // Send 10 ECT0 marked packets
+    for i in 1..=10 {
+        manager.on_packet_sent(
+            space.new_packet_number(VarInt::from_u8(i)),
+            transmission::Outcome {
+                ack_elicitation: AckElicitation::Eliciting,
+                is_congestion_controlled: true,
+                bytes_sent: packet_bytes,
+                bytes_progressed: 0,
+            },
+            time_sent,
+            ExplicitCongestionNotification::Ect0,
+            transmission::Mode::Normal,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+    }
+
+    // Trigger:
+    // Ack packets 1-10, with 4 of them marked CE
+    let ack_receive_time = time_sent + Duration::from_millis(500);
+    let ack_ecn_counts = EcnCounts {
+        ect_0_count: VarInt::from_u8(6),
+        ect_1_count: Default::default(),
+        ce_count: VarInt::from_u8(4),
+    };
+    ack_packets(
+        1..=10,
+        ack_receive_time,
+        &mut context,
+        &mut manager,
+        Some(ack_ecn_counts),
+        &mut publisher,
+    );
+
+    // Expectation:
+    // The CE marks are counted separately and don't reach the congestion controller
+    let path = context.path();
+    assert_eq!(ack_ecn_counts, manager.baseline_ecn_counts);
+    assert!(path.ecn_controller.is_capable());
+    assert_eq!(0, path.congestion_controller.congestion_events);
+    assert_eq!(4, path.handshake_congestion_isolation.handshake_ce_count());
+    assert_eq!(0, path.handshake_congestion_isolation.initial_ce_count());
+}
+
 #[test]
 // Increase in ECN CE count should not cause congestion event if ECN validation fails
 //
@@ -1881,6 +1945,330 @@ fn detect_and_remove_lost_packets_mtu_probe() {
     assert_eq!(context.path().congestion_controller.bytes_in_flight, 0);
 }
 
+#[test]
+fn detect_and_remove_lost_packets_frames() {
+    let space = PacketNumberSpace::ApplicationData;
+    let mut manager = Manager::new(space);
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let ecn = ExplicitCongestionNotification::default();
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::no_snapshot();
+
+    let time_sent = s2n_quic_platform::time::now();
+    let outcome = transmission::Outcome {
+        ack_elicitation: AckElicitation::Eliciting,
+        is_congestion_controlled: true,
+        bytes_sent: 100,
+        bytes_progressed: 0,
+    };
+
+    for i in 1..=6 {
+        let packet_number = space.new_packet_number(VarInt::from_u8(i));
+        manager.on_packet_sent(
+            packet_number,
+            outcome,
+            time_sent,
+            ecn,
+            transmission::Mode::Normal,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+
+        let mut sent_frames = recovery::SentFrames::default();
+        sent_frames.on_frame_sent(
+            &event::builder::Frame::Stream {
+                id: 0,
+                offset: i as u64 * 100,
+                len: 100,
+                is_fin: false,
+            },
+            space,
+        );
+        sent_frames.on_frame_sent(&event::builder::Frame::Padding, space);
+        manager.on_frames_sent(packet_number, sent_frames);
+    }
+
+    // Acknowledging packet 6 declares packets 1-3 lost by the packet threshold
+    ack_packets(
+        6..=6,
+        time_sent + Duration::from_millis(10),
+        &mut context,
+        &mut manager,
+        None,
+        &mut publisher,
+    );
+
+    // One event is published for each recorded frame in the lost packets
+    assert_eq!(3, publisher.frame_lost);
+    for i in 1..=6 {
+        let packet_number = space.new_packet_number(VarInt::from_u8(i));
+        assert_eq!(
+            (4..=5).contains(&i),
+            manager.sent_frames.get(packet_number).is_some(),
+            "only frames for packets pending acknowledgement are retained"
+        );
+    }
+}
+
+#[test]
+// An ACK frame that exceeds the max ACK frame size leaves out the ranges with the smallest
+// packet numbers. Packets in the gaps between the remaining ranges are still detected as lost.
+// Packets in the left out ranges are declared lost as well, which is the cost of the smaller
+// frame.
+fn truncated_ack_frame_detects_lost_packets() {
+    let space = PacketNumberSpace::ApplicationData;
+    let mut manager = Manager::new(space);
+    let ecn = ExplicitCongestionNotification::default();
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::no_snapshot();
+    let random = &mut random::testing::Generator::default();
+
+    let time_sent = s2n_quic_platform::time::now() + Duration::from_secs(10);
+
+    // Send packets 0 to 20
+    for i in 0..=20 {
+        manager.on_packet_sent(
+            space.new_packet_number(VarInt::from_u8(i)),
+            transmission::Outcome {
+                ack_elicitation: AckElicitation::Eliciting,
+                is_congestion_controlled: true,
+                bytes_sent: 1,
+                bytes_progressed: 0,
+            },
+            time_sent,
+            ecn,
+            transmission::Mode::Normal,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+    }
+
+    // The peer received every other packet, but only the 3 most recent ranges fit in the frame
+    let mut ack_ranges = AckRanges::new(11);
+    for i in (0..=20).step_by(2) {
+        assert!(ack_ranges
+            .insert_packet_number(space.new_packet_number(VarInt::from_u8(i)))
+            .is_ok());
+    }
+    let frame = frame::Ack {
+        ack_delay: VarInt::from_u8(10),
+        ack_ranges: ack_ranges.truncated(3),
+        ecn_counts: None,
+    };
+
+    let ack_receive_time = time_sent + Duration::from_millis(500);
+    let _ = manager.on_ack_frame(
+        ack_receive_time,
+        frame,
+        space.new_packet_number(VarInt::from_u8(1)),
+        random,
+        &mut context,
+        &mut publisher,
+    );
+
+    assert_eq!(
+        manager.largest_acked_packet,
+        Some(space.new_packet_number(VarInt::from_u8(20)))
+    );
+
+    for i in 0..=20 {
+        let packet_number = space.new_packet_number(VarInt::from_u8(i));
+        match i {
+            // acknowledged by the ranges in the frame
+            16 | 18 | 20 => {
+                assert!(!context.lost_packets.contains(&packet_number));
+                assert!(manager.sent_packets.get(packet_number).is_none());
+            }
+            // less than K_PACKET_THRESHOLD from the largest acknowledged packet
+            19 => {
+                assert!(!context.lost_packets.contains(&packet_number));
+                assert!(manager.sent_packets.get(packet_number).is_some());
+            }
+            // in a gap or in one of the ranges that were left out
+            _ => {
+                assert!(context.lost_packets.contains(&packet_number));
+                assert!(manager.sent_packets.get(packet_number).is_none());
+            }
+        }
+    }
+
+    assert_eq!(context.path().congestion_controller.lost_bytes, 17);
+}
+
+#[test]
+fn reordered_packets_are_reported_as_spurious_losses() {
+    let space = PacketNumberSpace::ApplicationData;
+    let mut manager = Manager::new(space);
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let ecn = ExplicitCongestionNotification::default();
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::no_snapshot();
+    let rtt = Duration::from_millis(100);
+
+    let outcome = transmission::Outcome {
+        ack_elicitation: AckElicitation::Eliciting,
+        is_congestion_controlled: true,
+        bytes_sent: 100,
+        bytes_progressed: 0,
+    };
+
+    let mut time_sent = s2n_quic_platform::time::now();
+
+    // Send packets 0-9 in a single burst
+    for i in 0..=9 {
+        manager.on_packet_sent(
+            space.new_packet_number(VarInt::from_u8(i)),
+            outcome,
+            time_sent,
+            ecn,
+            transmission::Mode::Normal,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+    }
+
+    // Packets 4-9 are acknowledged first, so 0-3 are declared lost by the packet threshold.
+    // Nothing is known about reordering yet, so the loss is treated as congestion.
+    ack_packets(
+        4..=9,
+        time_sent + rtt,
+        &mut context,
+        &mut manager,
+        None,
+        &mut publisher,
+    );
+    assert_eq!(context.lost_packets.len(), 4);
+    assert_eq!(context.path().congestion_controller.lost_bytes, 400);
+    assert_eq!(publisher.spurious_loss_detected, 0);
+
+    // Packets 0-3 arrive late, indicating they were reordered rather than lost
+    ack_packets(
+        0..=3,
+        time_sent + rtt,
+        &mut context,
+        &mut manager,
+        None,
+        &mut publisher,
+    );
+    // The congestion controller is told the loss was spurious
+    assert_eq!(context.path().congestion_controller.on_spurious_loss, 1);
+
+    let lost_bytes = context.path().congestion_controller.lost_bytes;
+    let on_packets_lost = context.path().congestion_controller.on_packets_lost;
+
+    // Send packets 10-19 in a single burst and reorder them the same way
+    time_sent += rtt;
+    for i in 10..=19 {
+        manager.on_packet_sent(
+            space.new_packet_number(VarInt::from_u8(i)),
+            outcome,
+            time_sent,
+            ecn,
+            transmission::Mode::Normal,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+    }
+
+    ack_packets(
+        14..=19,
+        time_sent + rtt,
+        &mut context,
+        &mut manager,
+        None,
+        &mut publisher,
+    );
+
+    // Packets 10-13 are reported as likely spurious losses, but the congestion controller
+    // still responds to them
+    assert_eq!(context.lost_packets.len(), 8);
+    assert_eq!(
+        context.path().congestion_controller.lost_bytes,
+        lost_bytes + 400
+    );
+    assert_eq!(
+        context.path().congestion_controller.on_packets_lost,
+        on_packets_lost + 4
+    );
+    assert_eq!(publisher.spurious_loss_detected, 4);
+}
+
+/// Sends 10 rounds of 10 packets, where the first packet of each round is delivered after the
+/// next 4 packets, and returns the number of packets which were declared lost
+fn reordered_rounds_spurious_losses(rack: bool) -> usize {
+    let space = PacketNumberSpace::ApplicationData;
+    let mut manager = Manager::new(space);
+    if rack {
+        manager.enable_rack();
+    }
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let ecn = ExplicitCongestionNotification::default();
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::no_snapshot();
+    let rtt = Duration::from_millis(100);
+
+    let outcome = transmission::Outcome {
+        ack_elicitation: AckElicitation::Eliciting,
+        is_congestion_controlled: true,
+        bytes_sent: 100,
+        bytes_progressed: 0,
+    };
+
+    let start = s2n_quic_platform::time::now();
+
+    for round in 0..10u8 {
+        let first = round * 10;
+        let time_sent = start + rtt * 2 * round as u32;
+
+        for i in 0..10 {
+            manager.on_packet_sent(
+                space.new_packet_number(VarInt::from_u8(first + i)),
+                outcome,
+                time_sent + Duration::from_millis(i as u64),
+                ecn,
+                transmission::Mode::Normal,
+                None,
+                &mut context,
+                &mut publisher,
+            );
+        }
+
+        let ack_time = time_sent + rtt;
+        for (range, delay) in [
+            (first + 1..=first + 4, 4),
+            (first..=first, 5),
+            (first + 5..=first + 9, 9),
+        ] {
+            ack_packets(
+                range,
+                ack_time + Duration::from_millis(delay),
+                &mut context,
+                &mut manager,
+                None,
+                &mut publisher,
+            );
+        }
+    }
+
+    // every packet was eventually acknowledged, so all of the losses were spurious
+    context.lost_packets.len()
+}
+
+#[test]
+fn rack_reduces_spurious_losses() {
+    // The packet threshold declares the reordered packet lost in every round
+    assert_eq!(reordered_rounds_spurious_losses(false), 10);
+
+    // RACK declares the packet lost in the first round, before any reordering was observed.
+    // Afterwards, the reordering window gives the packet enough time to be delivered.
+    assert_eq!(reordered_rounds_spurious_losses(true), 1);
+}
+
 #[test]
 fn persistent_congestion() {
     //= https://www.rfc-editor.org/rfc/rfc9002#section-7.6.2
@@ -2952,6 +3340,110 @@ fn ack_packets(
     )
 }
 
+//= https://www.rfc-editor.org/rfc/rfc9002#section-6.2.4
+//= type=test
+//# An endpoint SHOULD include new data in packets that are sent on PTO
+//# expiration.  Previously sent data MAY be sent if no new data can be
+//# sent.
+#[test]
+fn pto_probe_retransmits_crypto_data() {
+    let space = PacketNumberSpace::Initial;
+    let mut manager = Manager::new(space);
+    let mut ack_manager = AckManager::new(space, ack::Settings::default());
+    let mut crypto_stream = CryptoStream::new();
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let now = s2n_quic_platform::time::now();
+    let ecn = ExplicitCongestionNotification::default();
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::snapshot();
+    let random = &mut random::testing::Generator::default();
+
+    // Receive a datagram from the client so the path isn't at the anti-amplification limit
+    context.path_mut().on_bytes_received(1200);
+
+    crypto_stream.tx.push(Bytes::from_static(&[1; 100]));
+    let frames = helper_transmit_initial(
+        &mut manager,
+        &mut ack_manager,
+        &mut crypto_stream,
+        &mut frame_buffer,
+        transmission::Mode::Normal,
+    );
+    assert_eq!(frames, vec![(0, 100)]);
+
+    manager.on_packet_sent(
+        space.new_packet_number(VarInt::from_u8(0)),
+        transmission::Outcome {
+            ack_elicitation: AckElicitation::Eliciting,
+            is_congestion_controlled: true,
+            bytes_sent: 100,
+            bytes_progressed: 100,
+        },
+        now,
+        ecn,
+        transmission::Mode::Normal,
+        None,
+        &mut context,
+        &mut publisher,
+    );
+
+    // The packet is never acknowledged, so the PTO timer expires
+    let pto_expiration = manager
+        .next_expiration()
+        .expect("the PTO timer should be armed");
+    manager.on_timeout(pto_expiration, random, &mut context, &mut publisher);
+    assert!(manager.requires_probe());
+
+    // The first probe retransmits the unacknowledged CRYPTO data, so no PING is needed
+    let frames = helper_transmit_initial(
+        &mut manager,
+        &mut ack_manager,
+        &mut crypto_stream,
+        &mut frame_buffer,
+        transmission::Mode::LossRecoveryProbing,
+    );
+    assert_eq!(frames, vec![(0, 100)]);
+    assert_eq!(manager.pto.state, RequiresTransmission(1));
+}
+
+// Helper function that writes an Initial packet payload and returns the offset and length of
+// each CRYPTO frame in it
+fn helper_transmit_initial(
+    manager: &mut Manager,
+    ack_manager: &mut AckManager,
+    crypto_stream: &mut CryptoStream,
+    frame_buffer: &mut OutgoingFrameBuffer,
+    mode: transmission::Mode,
+) -> Vec<(u64, usize)> {
+    frame_buffer.clear();
+
+    let mut context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        frame_buffer,
+        transmission::Constraint::None,
+        mode,
+        endpoint::Type::Server,
+    );
+    let mut payload = transmission::early::Payload {
+        ack_manager,
+        crypto_stream,
+        packet_number_space: PacketNumberSpace::Initial,
+        recovery_manager: manager,
+    };
+    transmission::Payload::on_transmit(&mut payload, &mut context);
+    frame_buffer.flush();
+
+    frame_buffer
+        .frames
+        .iter_mut()
+        .map(|frame| match frame.as_frame() {
+            frame::Frame::Crypto(frame) => (frame.offset.as_u64(), frame.data.len()),
+            frame => panic!("unexpected frame: {:?}", frame),
+        })
+        .collect()
+}
+
 #[test]
 fn requires_probe() {
     let space = PacketNumberSpace::ApplicationData;
@@ -3000,6 +3492,61 @@ fn probe_packets_count_towards_bytes_in_flight() {
     assert_eq!(context.path().congestion_controller.bytes_in_flight, 100);
 }
 
+#[test]
+fn on_frames_sent_tracks_retransmissions() {
+    let space = PacketNumberSpace::ApplicationData;
+    let mut manager = Manager::new(space);
+    let ecn = ExplicitCongestionNotification::default();
+    let mut path_manager = helper_generate_path_manager(Duration::from_millis(10));
+    let mut context = MockContext::new(&mut path_manager);
+    let mut publisher = Publisher::snapshot();
+    let time_sent = s2n_quic_platform::time::now();
+    let outcome = transmission::Outcome {
+        ack_elicitation: AckElicitation::Eliciting,
+        is_congestion_controlled: true,
+        bytes_sent: 100,
+        bytes_progressed: 0,
+    };
+
+    let original = space.new_packet_number(VarInt::from_u8(1));
+    let retransmission = space.new_packet_number(VarInt::from_u8(2));
+
+    for (packet_number, mode) in [
+        (original, transmission::Mode::Normal),
+        (retransmission, transmission::Mode::LossRecoveryProbing),
+    ] {
+        manager.on_packet_sent(
+            packet_number,
+            outcome,
+            time_sent,
+            ecn,
+            mode,
+            None,
+            &mut context,
+            &mut publisher,
+        );
+    }
+
+    manager.on_frames_sent(original, SentFrames::default());
+    assert_eq!(manager.retransmissions.len(), 0);
+
+    let mut sent_frames = SentFrames::default();
+    sent_frames.on_retransmission(original);
+    manager.on_frames_sent(retransmission, sent_frames);
+    assert_eq!(manager.retransmissions.len(), 1);
+
+    // the retransmission is no longer tracked once both packets are acknowledged
+    ack_packets(
+        1..=2,
+        time_sent + Duration::from_millis(10),
+        &mut context,
+        &mut manager,
+        None,
+        &mut publisher,
+    );
+    assert_eq!(manager.retransmissions.len(), 0);
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9002#section-6.1.1
 //= type=test
 //# The RECOMMENDED initial value for the packet reordering threshold
@@ -3225,6 +3772,7 @@ struct MockContext<'a> {
     on_packet_ack_count: u8,
     on_packet_loss_count: u8,
     on_rtt_update_count: u8,
+    on_congestion_controller_update_count: u8,
     path_id: path::Id,
     lost_packets: HashSet<PacketNumber>,
     path_manager: &'a mut path::Manager<Config>,
@@ -3238,6 +3786,7 @@ impl<'a> MockContext<'a> {
             on_packet_ack_count: 0,
             on_packet_loss_count: 0,
             on_rtt_update_count: 0,
+            on_congestion_controller_update_count: 0,
             path_id: path_manager.active_path_id(),
             lost_packets: HashSet::default(),
             path_manager,
@@ -3309,4 +3858,8 @@ impl<'a> recovery::Context<Config> for MockContext<'a> {
     fn on_rtt_update(&mut self) {
         self.on_rtt_update_count += 1;
     }
+
+    fn on_congestion_controller_update(&mut self) {
+        self.on_congestion_controller_update_count += 1;
+    }
 }
@@ -4,5 +4,7 @@
 pub use manager::*;
 /// re-export core
 pub use s2n_quic_core::recovery::*;
+pub use sent_frames::SentFrames;
 
 mod manager;
+mod sent_frames;
@@ -0,0 +1,120 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_quic_core::{
+    event::builder::{Frame, LostFrame},
+    packet::number::{PacketNumber, PacketNumberSpace},
+};
+use smallvec::SmallVec;
+
+/// The number of frames that can be recorded for a packet before allocating
+const INLINE_FRAMES: usize = 4;
+
+/// The frames written to a single packet
+///
+/// Frames are only recorded for connections with an event subscriber that tracks lost frames.
+/// The recovery manager holds onto the frames until the packet is either acknowledged or
+/// declared lost, at which point a `FrameLost` event is published for each frame.
+///
+/// Regardless of the subscriber, the packet which originally carried retransmitted data is
+/// recorded, so the data isn't credited to the delivery rate twice.
+#[derive(Clone, Debug, Default)]
+pub struct SentFrames {
+    frames: SmallVec<[LostFrame; INLINE_FRAMES]>,
+    retransmission_of: Option<PacketNumber>,
+}
+
+impl SentFrames {
+    /// Records a frame written to a packet in the given `space`
+    #[inline]
+    pub fn on_frame_sent(&mut self, frame: &Frame, space: PacketNumberSpace) {
+        if let Some(frame) = LostFrame::new(frame, space) {
+            self.frames.push(frame);
+        }
+    }
+
+    /// Records that the packet retransmits data which is still in flight in `original`
+    ///
+    /// Only the first original packet is recorded if the packet retransmits data from several.
+    #[inline]
+    pub fn on_retransmission(&mut self, original: PacketNumber) {
+        if self.retransmission_of.is_none() {
+            self.retransmission_of = Some(original);
+        }
+    }
+
+    /// Returns the packet which originally carried the retransmitted data in the packet
+    #[inline]
+    pub fn retransmission_of(&self) -> Option<PacketNumber> {
+        self.retransmission_of
+    }
+
+    /// Returns true if no frames were recorded
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl IntoIterator for SentFrames {
+    type Item = LostFrame;
+    type IntoIter = smallvec::IntoIter<[LostFrame; INLINE_FRAMES]>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.frames.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::event::builder::KeySpace;
+
+    #[test]
+    fn on_frame_sent_test() {
+        let mut sent_frames = SentFrames::default();
+
+        sent_frames.on_frame_sent(&Frame::Padding, PacketNumberSpace::Initial);
+        assert!(sent_frames.is_empty(), "PADDING frames are not recorded");
+
+        sent_frames.on_frame_sent(
+            &Frame::Crypto {
+                offset: 100,
+                len: 200,
+            },
+            PacketNumberSpace::Handshake,
+        );
+        sent_frames.on_frame_sent(
+            &Frame::Stream {
+                id: 4,
+                offset: 10,
+                len: 20,
+                is_fin: true,
+            },
+            PacketNumberSpace::ApplicationData,
+        );
+        sent_frames.on_frame_sent(&Frame::Ping, PacketNumberSpace::ApplicationData);
+
+        let frames: Vec<_> = sent_frames.into_iter().collect();
+        assert_eq!(3, frames.len());
+        assert!(matches!(
+            frames[0],
+            LostFrame::CryptoData {
+                level: KeySpace::Handshake,
+                offset: 100,
+                len: 200,
+            }
+        ));
+        assert!(matches!(
+            frames[1],
+            LostFrame::StreamData {
+                stream_id: 4,
+                offset: 10,
+                len: 20,
+                is_fin: true,
+            }
+        ));
+        assert!(matches!(frames[2], LostFrame::Other { frame: Frame::Ping }));
+    }
+}
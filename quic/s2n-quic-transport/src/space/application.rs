@@ -18,6 +18,7 @@ use core::{convert::TryInto, fmt, marker::PhantomData};
 use once_cell::sync::OnceCell;
 use s2n_codec::EncoderBuffer;
 use s2n_quic_core::{
+    connection::ConnectionHistograms,
     crypto::{application::KeySet, limited, tls, CryptoSuite},
     event::{self, ConnectionPublisher as _, IntoEvent},
     frame::{
@@ -33,7 +34,9 @@ use s2n_quic_core::{
         short::{CleartextShort, ProtectedShort, Short, SpinBit},
     },
     path::MaxMtu,
-    time::{timer, Timestamp},
+    recovery::{loss_rate, CongestionController as _},
+    stream::WriteAmplification,
+    time::{timer, Duration, Timestamp},
     transport,
 };
 
@@ -64,6 +67,15 @@ pub struct ApplicationSpace<Config: endpoint::Config> {
     processed_packet_numbers: SlidingWindow,
     recovery_manager: recovery::Manager<Config>,
     pub datagram_manager: datagram::Manager<Config>,
+    /// Tracks the packet loss rates of the connection
+    pub loss_rate: loss_rate::Estimator,
+    /// The RTT and throughput histograms, if enabled in the connection limits
+    pub histograms: Option<Box<ConnectionHistograms>>,
+    /// The number of outgoing stream bytes which were progressed when the statistics were last
+    /// reset
+    pub payload_bytes_reset: u64,
+    /// The number of STREAM frame bytes which were sent when the statistics were last reset
+    pub wire_bytes_reset: u64,
 }
 
 impl<Config: endpoint::Config> fmt::Debug for ApplicationSpace<Config> {
@@ -75,6 +87,10 @@ impl<Config: endpoint::Config> fmt::Debug for ApplicationSpace<Config> {
             .field("recovery_manager", &self.recovery_manager)
             .field("stream_manager", &self.stream_manager)
             .field("tx_packet_numbers", &self.tx_packet_numbers)
+            .field("loss_rate", &self.loss_rate)
+            .field("histograms", &self.histograms)
+            .field("payload_bytes_reset", &self.payload_bytes_reset)
+            .field("wire_bytes_reset", &self.wire_bytes_reset)
             .finish()
     }
 }
@@ -90,8 +106,17 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         keep_alive: KeepAlive,
         max_mtu: MaxMtu,
         datagram_manager: datagram::Manager<Config>,
+        loss_rate: loss_rate::Estimator,
+        histograms: Option<Box<ConnectionHistograms>>,
+        rack_loss_detection: bool,
+        min_key_update_interval: Duration,
     ) -> Self {
-        let key_set = KeySet::new(key, Self::key_limits(max_mtu));
+        let key_set = KeySet::new(key, Self::key_limits(max_mtu, min_key_update_interval));
+
+        let mut recovery_manager = recovery::Manager::new(PacketNumberSpace::ApplicationData);
+        if rack_loss_detection {
+            recovery_manager.enable_rack();
+        }
 
         Self {
             tx_packet_numbers: TxPacketNumbers::new(PacketNumberSpace::ApplicationData, now),
@@ -103,11 +128,41 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             ping: flag::Ping::default(),
             keep_alive,
             processed_packet_numbers: SlidingWindow::default(),
-            recovery_manager: recovery::Manager::new(PacketNumberSpace::ApplicationData),
+            recovery_manager,
             datagram_manager,
+            loss_rate,
+            histograms,
+            payload_bytes_reset: 0,
+            wire_bytes_reset: 0,
+        }
+    }
+
+    /// Returns the number of STREAM frame bytes sent per byte of new stream data since the
+    /// statistics were last reset
+    pub fn write_amplification(&self) -> WriteAmplification {
+        let payload_bytes = self.stream_manager.outgoing_bytes_progressed().as_u64();
+        let wire_bytes = self.stream_manager.outgoing_frame_bytes_sent();
+
+        WriteAmplification {
+            payload_bytes: payload_bytes - self.payload_bytes_reset,
+            wire_bytes: wire_bytes - self.wire_bytes_reset,
         }
     }
 
+    /// Resets the cumulative statistics of the connection to zero
+    ///
+    /// Gauges, such as the loss rates and the congestion window, are not affected.
+    pub fn reset_stats(&mut self) {
+        self.stream_manager.reset_stream_metrics();
+
+        if let Some(histograms) = self.histograms.as_mut() {
+            histograms.reset();
+        }
+
+        self.payload_bytes_reset = self.stream_manager.outgoing_bytes_progressed().as_u64();
+        self.wire_bytes_reset = self.stream_manager.outgoing_frame_bytes_sent();
+    }
+
     /// Returns true if the packet number has already been processed
     pub fn is_duplicate<Pub: event::ConnectionPublisher>(
         &self,
@@ -155,6 +210,7 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         let packet_number_encoder = self.packet_number_encoder();
 
         let mut outcome = transmission::Outcome::default();
+        let mut sent_frames = recovery::SentFrames::default();
 
         let destination_connection_id = context.path().peer_connection_id;
         let timestamp = context.timestamp;
@@ -185,6 +241,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             path_id: context.path_id,
             publisher: context.publisher,
             packet_interceptor: context.packet_interceptor,
+            sent_frames: &mut sent_frames,
+            traffic_shaping: context.traffic_shaping,
         };
 
         let spin_bit = self.spin_bit;
@@ -229,6 +287,16 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             &mut recovery_context,
             context.publisher,
         );
+        recovery_manager.on_frames_sent(packet_number, sent_frames);
+
+        // only congestion controlled packets are declared lost so the rest are not counted
+        if outcome.is_congestion_controlled {
+            self.loss_rate.on_packet_sent(packet_number);
+        }
+
+        if let Some(histograms) = self.histograms.as_mut() {
+            histograms.on_bytes_sent(outcome.bytes_sent, timestamp);
+        }
 
         // reset the keep alive timer after sending an ack-eliciting packet
         if outcome.ack_elicitation.is_ack_eliciting() {
@@ -258,6 +326,7 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         let packet_number_encoder = self.packet_number_encoder();
 
         let mut outcome = transmission::Outcome::default();
+        let mut sent_frames = recovery::SentFrames::default();
         let destination_connection_id = context.path().peer_connection_id;
 
         let payload = transmission::Transmission {
@@ -275,6 +344,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             path_id: context.path_id,
             publisher: context.publisher,
             packet_interceptor: context.packet_interceptor,
+            sent_frames: &mut sent_frames,
+            traffic_shaping: context.traffic_shaping,
         };
 
         let spin_bit = self.spin_bit;
@@ -384,6 +455,15 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
 
         self.stream_manager.on_timeout(timestamp);
 
+        if let Some(deadlock) = self.stream_manager.poll_potential_deadlock() {
+            publisher.on_potential_deadlock(event::builder::PotentialDeadlock {
+                blocked_stream_ids: &deadlock.blocked_stream_ids,
+                unread_stream_ids: &deadlock.unread_stream_ids,
+                unread_lens: &deadlock.unread_lens,
+                duration: deadlock.duration,
+            });
+        }
+
         if self.keep_alive.on_timeout(timestamp).is_ready() {
             publisher.on_keep_alive_timer_expired(event::builder::KeepAliveTimerExpired {
                 timeout: self.keep_alive.period(),
@@ -434,6 +514,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
                 path_id,
                 path_manager,
                 tx_packet_numbers: &mut self.tx_packet_numbers,
+                loss_rate: &mut self.loss_rate,
+                histograms: self.histograms.as_deref_mut(),
             },
         )
     }
@@ -443,7 +525,9 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
     /// Sending is app limited if the application is not fully utilizing the available
     /// congestion window currently and there is no more application data remaining to send.
     fn is_app_limited(&self, path: &Path<Config>, bytes_sent: usize) -> bool {
-        !path.is_congestion_limited(bytes_sent) && !self.has_transmission_interest()
+        !path.is_congestion_limited(bytes_sent)
+            && !self.has_transmission_interest()
+            && !path.bandwidth_probe.is_active()
     }
 
     /// Validate packets in the Application packet space
@@ -485,10 +569,9 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             //# (PTO; see [QUIC-RECOVERY]) after promoting the next set of receive
             //# keys to be current before it creates the subsequent set of packet
             //# protection keys.
-            datagram.timestamp
-                + path
-                    .rtt_estimator
-                    .pto_period(1, PacketNumberSpace::ApplicationData),
+            datagram.timestamp,
+            path.rtt_estimator
+                .pto_period(1, PacketNumberSpace::ApplicationData),
         );
         match decrypted {
             Ok((_, Some(generation))) => {
@@ -527,10 +610,11 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         decrypted.map(|x| x.0)
     }
 
-    fn key_limits(max_mtu: MaxMtu) -> limited::Limits {
+    fn key_limits(max_mtu: MaxMtu, min_key_update_interval: Duration) -> limited::Limits {
         let mut limits = limited::Limits::default();
 
         limits.max_mtu = max_mtu;
+        limits.min_key_update_interval = min_key_update_interval;
 
         // AEAD optimizations are currently in the testing phase so make them opt-in at runtime
         limits.sealer_optimization_threshold = {
@@ -602,6 +686,8 @@ struct RecoveryContext<'a, Config: endpoint::Config> {
     path_id: path::Id,
     path_manager: &'a mut path::Manager<Config>,
     tx_packet_numbers: &'a mut TxPacketNumbers,
+    loss_rate: &'a mut loss_rate::Estimator,
+    histograms: Option<&'a mut ConnectionHistograms>,
 }
 
 impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext<'a, Config> {
@@ -651,6 +737,7 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
         self.stream_manager.on_packet_ack(packet_number_range);
         self.local_id_registry.on_packet_ack(packet_number_range);
         self.path_manager.on_packet_ack(packet_number_range);
+        self.loss_rate.on_packet_ack(packet_number_range.end());
     }
 
     fn on_packet_ack(&mut self, timestamp: Timestamp, packet_number_range: &PacketNumberRange) {
@@ -670,13 +757,33 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
         self.stream_manager.on_packet_loss(packet_number_range);
         self.local_id_registry.on_packet_loss(packet_number_range);
         self.path_manager.on_packet_loss(packet_number_range);
+        for packet_number in *packet_number_range {
+            self.loss_rate.on_packet_lost(packet_number);
+        }
     }
 
     fn on_rtt_update(&mut self) {
         // Update the stream manager if this RTT update was for the active path
         if self.path_manager.active_path_id() == self.path_id {
+            let rtt_estimator = &self.path_manager.active_path().rtt_estimator;
+            self.stream_manager.on_rtt_update(rtt_estimator);
+
+            if let Some(histograms) = self.histograms.as_mut() {
+                histograms.on_rtt_sample(rtt_estimator.latest_rtt());
+            }
+        }
+    }
+
+    fn on_congestion_controller_update(&mut self) {
+        // Update the stream manager if this update was for the active path
+        if self.path_manager.active_path_id() == self.path_id {
+            let bandwidth_estimate = self
+                .path_manager
+                .active_path()
+                .congestion_controller
+                .bandwidth_estimate();
             self.stream_manager
-                .on_rtt_update(&self.path_manager.active_path().rtt_estimator)
+                .on_bandwidth_estimate(bandwidth_estimate);
         }
     }
 }
@@ -752,8 +859,12 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         Ok(())
     }
 
-    fn handle_datagram_frame(&mut self, frame: DatagramRef) -> Result<(), transport::Error> {
-        self.datagram_manager.on_datagram_frame(frame);
+    fn handle_datagram_frame<Pub: event::ConnectionPublisher>(
+        &mut self,
+        frame: DatagramRef,
+        publisher: &mut Pub,
+    ) -> Result<(), transport::Error> {
+        self.datagram_manager.on_datagram_frame(frame, publisher);
         Ok(())
     }
 
@@ -137,3 +137,135 @@ impl transmission::interest::Provider for CryptoStream {
         self.tx.transmission_interest(query)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contexts::testing::{MockWriteContext, OutgoingFrameBuffer};
+    use bytes::Bytes;
+    use s2n_quic_core::{
+        endpoint,
+        frame::Frame,
+        packet::number::PacketNumber,
+        time::{Clock, NoopClock},
+    };
+
+    const MAX_PACKET_SIZE: usize = 1200;
+
+    /// Transmits a single packet and returns the packet number along with the
+    /// CRYPTO ranges it contains
+    fn transmit(
+        stream: &mut CryptoStream,
+        frame_buffer: &mut OutgoingFrameBuffer,
+        mode: transmission::Mode,
+    ) -> Option<(PacketNumber, Vec<(u64, u64)>)> {
+        let prev_len = frame_buffer.len();
+        let mut context = MockWriteContext::new(
+            NoopClock.get_time(),
+            frame_buffer,
+            transmission::Constraint::None,
+            mode,
+            endpoint::Type::Client,
+        );
+        stream.tx.on_transmit((), &mut context).unwrap();
+        frame_buffer.flush();
+
+        let mut packet_nr = None;
+        let ranges = frame_buffer
+            .frames
+            .iter_mut()
+            .skip(prev_len)
+            .map(|frame| {
+                packet_nr = Some(frame.packet_nr);
+                match frame.as_frame() {
+                    Frame::Crypto(frame) => {
+                        let offset = frame.offset.as_u64();
+                        (offset, offset + frame.data.len() as u64)
+                    }
+                    _ => panic!("only CRYPTO frames should be written"),
+                }
+            })
+            .collect();
+
+        Some((packet_nr?, ranges))
+    }
+
+    /// Sends a large ClientHello over 3 packets and acknowledges the first and last
+    fn setup() -> (CryptoStream, OutgoingFrameBuffer, [PacketNumber; 3]) {
+        let mut stream = CryptoStream::new();
+        stream.tx.push(Bytes::from(vec![0u8; 3000]));
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        frame_buffer.set_max_packet_size(Some(MAX_PACKET_SIZE));
+
+        let mut packets = [PacketNumber::default(); 3];
+        for packet in packets.iter_mut() {
+            *packet = transmit(&mut stream, &mut frame_buffer, transmission::Mode::Normal)
+                .expect("data should be transmitted")
+                .0;
+        }
+        assert!(transmit(&mut stream, &mut frame_buffer, transmission::Mode::Normal).is_none());
+
+        stream.on_packet_ack(&packets[0]);
+        stream.on_packet_ack(&packets[2]);
+
+        (stream, frame_buffer, packets)
+    }
+
+    fn sent_ranges(
+        frame_buffer: &mut OutgoingFrameBuffer,
+        packet: PacketNumber,
+    ) -> Vec<(u64, u64)> {
+        frame_buffer
+            .frames
+            .iter_mut()
+            .filter(|frame| frame.packet_nr == packet)
+            .filter_map(|frame| match frame.as_frame() {
+                Frame::Crypto(frame) => {
+                    let offset = frame.offset.as_u64();
+                    Some((offset, offset + frame.data.len() as u64))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loss_retransmits_missing_range_test() {
+        let (mut stream, mut frame_buffer, packets) = setup();
+        let lost_ranges = sent_ranges(&mut frame_buffer, packets[1]);
+
+        stream.on_packet_loss(&packets[1]);
+
+        let (_, ranges) =
+            transmit(&mut stream, &mut frame_buffer, transmission::Mode::Normal).unwrap();
+        assert_eq!(ranges, lost_ranges);
+        assert!(transmit(&mut stream, &mut frame_buffer, transmission::Mode::Normal).is_none());
+
+        // the lost packet is no longer in flight, so it can't be acknowledged along with the
+        // retransmission
+        assert!(frame_buffer.retransmissions.is_empty());
+    }
+
+    //= https://www.rfc-editor.org/rfc/rfc9002#section-6.2.4
+    //= type=test
+    //# An endpoint SHOULD include new data in packets that are sent on PTO
+    //# expiration.  Previously sent data MAY be sent if no new data can be
+    //# sent.
+    #[test]
+    fn probe_retransmits_unacked_range_test() {
+        let (mut stream, mut frame_buffer, packets) = setup();
+        let unacked_ranges = sent_ranges(&mut frame_buffer, packets[1]);
+
+        let (probe, ranges) = transmit(
+            &mut stream,
+            &mut frame_buffer,
+            transmission::Mode::LossRecoveryProbing,
+        )
+        .unwrap();
+        assert_eq!(ranges, unacked_ranges);
+
+        // the probe is registered as a retransmission of the packet which is still in flight
+        assert_eq!(frame_buffer.retransmissions, vec![(probe, packets[1])]);
+    }
+}
@@ -47,7 +47,15 @@ impl<Config: endpoint::Config> Manager<Config> {
         context: &mut W,
         stream_manager: &mut AbstractStreamManager<S>,
         datagrams_prioritized: bool,
+        mtu: usize,
     ) {
+        // The peer's limit may be larger than what fits in a packet of the current path MTU
+        let max_packet_payload = mtu
+            .saturating_sub(context.header_len())
+            .saturating_sub(context.tag_len());
+        self.sender
+            .on_max_datagram_payload_update(max_datagram_payload(max_packet_payload) as u64);
+
         let mut packet = Packet {
             context,
             has_pending_streams: stream_manager.has_pending_streams(),
@@ -59,8 +67,19 @@ impl<Config: endpoint::Config> Manager<Config> {
 
     // A callback that allows users to access datagrams directly after they are
     // received.
-    pub fn on_datagram_frame(&mut self, datagram: DatagramRef) {
+    pub fn on_datagram_frame<Pub: event::ConnectionPublisher>(
+        &mut self,
+        datagram: DatagramRef,
+        publisher: &mut Pub,
+    ) {
         self.receiver.on_datagram(datagram.data);
+
+        while let Some(len) = self.receiver.take_dropped_datagram() {
+            publisher.on_datagram_dropped(event::builder::DatagramDropped {
+                len: len.try_into().unwrap_or(u16::MAX),
+                reason: event::builder::DatagramDropReason::QueueFull,
+            });
+        }
     }
 
     pub fn datagram_mut(&mut self, query: &mut dyn event::query::QueryMut) -> Poll<()> {
@@ -90,6 +109,19 @@ impl<Config: endpoint::Config> interest::Provider for Manager<Config> {
     }
 }
 
+/// Returns the largest datagram which fits in a DATAGRAM frame taking up `space` bytes
+#[inline]
+fn max_datagram_payload(space: usize) -> usize {
+    // Remove the frame type length and the maximum length value
+    space
+        .saturating_sub(frame::datagram::DATAGRAM_TAG.encoding_size())
+        .saturating_sub(
+            VarInt::new(space as u64)
+                .unwrap_or(VarInt::MAX)
+                .encoding_size(),
+        )
+}
+
 struct Packet<'a, C: WriteContext> {
     context: &'a mut C,
     has_pending_streams: bool,
@@ -100,15 +132,7 @@ struct Packet<'a, C: WriteContext> {
 impl<'a, C: WriteContext> s2n_quic_core::datagram::Packet for Packet<'a, C> {
     /// Returns the remaining space in the packet
     fn remaining_capacity(&self) -> usize {
-        let space = self.context.remaining_capacity();
-        // Remove the frame type length and the maximum length value
-        space
-            .saturating_sub(frame::datagram::DATAGRAM_TAG.encoding_size())
-            .saturating_sub(
-                VarInt::new(space as u64)
-                    .unwrap_or(VarInt::MAX)
-                    .encoding_size(),
-            )
+        max_datagram_payload(self.context.remaining_capacity())
     }
 
     /// Writes a single datagram to a packet
@@ -141,3 +165,18 @@ impl<'a, C: WriteContext> s2n_quic_core::datagram::Packet for Packet<'a, C> {
         self.datagrams_prioritized
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_datagram_payload_test() {
+        // 1 byte of frame type and 2 bytes of length
+        assert_eq!(max_datagram_payload(1200), 1197);
+        // 1 byte of frame type and 1 byte of length
+        assert_eq!(max_datagram_payload(63), 61);
+        assert_eq!(max_datagram_payload(1), 0);
+        assert_eq!(max_datagram_payload(0), 0);
+    }
+}
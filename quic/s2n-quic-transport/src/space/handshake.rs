@@ -120,6 +120,7 @@ impl<Config: endpoint::Config> HandshakeSpace<Config> {
 
         let packet_number_encoder = self.packet_number_encoder();
         let mut outcome = transmission::Outcome::default();
+        let mut sent_frames = recovery::SentFrames::default();
 
         let destination_connection_id = context.path().peer_connection_id;
         let payload = transmission::Transmission {
@@ -139,6 +140,8 @@ impl<Config: endpoint::Config> HandshakeSpace<Config> {
             path_id: context.path_id,
             publisher: context.publisher,
             packet_interceptor: context.packet_interceptor,
+            sent_frames: &mut sent_frames,
+            traffic_shaping: context.traffic_shaping,
         };
 
         let packet = Handshake {
@@ -171,6 +174,7 @@ impl<Config: endpoint::Config> HandshakeSpace<Config> {
             &mut recovery_context,
             context.publisher,
         );
+        recovery_manager.on_frames_sent(packet_number, sent_frames);
 
         context
             .publisher
@@ -194,6 +198,7 @@ impl<Config: endpoint::Config> HandshakeSpace<Config> {
 
         let packet_number_encoder = self.packet_number_encoder();
         let mut outcome = transmission::Outcome::default();
+        let mut sent_frames = recovery::SentFrames::default();
 
         let destination_connection_id = context.path().peer_connection_id;
         let payload = transmission::Transmission {
@@ -211,6 +216,8 @@ impl<Config: endpoint::Config> HandshakeSpace<Config> {
             path_id: context.path_id,
             publisher: context.publisher,
             packet_interceptor: context.packet_interceptor,
+            sent_frames: &mut sent_frames,
+            traffic_shaping: context.traffic_shaping,
         };
 
         let packet = Handshake {
@@ -471,6 +478,8 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
     }
 
     fn on_rtt_update(&mut self) {}
+
+    fn on_congestion_controller_update(&mut self) {}
 }
 
 //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.4
@@ -374,6 +374,18 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
         }
 
         packet_buffer.write(|buffer| {
+            if let Some(shaping) = context.traffic_shaping.as_mut() {
+                shaping.datagram_capacity = buffer.capacity();
+                // only the last packet in the datagram is padded
+                shaping.last_space = if can_send_application {
+                    PacketNumberSpace::ApplicationData
+                } else if can_send_handshake {
+                    PacketNumberSpace::Handshake
+                } else {
+                    PacketNumberSpace::Initial
+                };
+            }
+
             macro_rules! write_packet {
                 ($buffer:expr, $space:ident, $check:expr, $frame:expr) => {
                     if let Some((space, _handshake_status)) = self.$space().filter(|_| $check) {
@@ -623,7 +635,11 @@ pub trait PacketSpace<Config: endpoint::Config> {
             .with_frame_type(frame.tag().into()))
     }
 
-    fn handle_datagram_frame(&mut self, frame: DatagramRef) -> Result<(), transport::Error> {
+    fn handle_datagram_frame<Pub: event::ConnectionPublisher>(
+        &mut self,
+        frame: DatagramRef,
+        _publisher: &mut Pub,
+    ) -> Result<(), transport::Error> {
         Err(transport::Error::PROTOCOL_VIOLATION
             .with_reason(Self::INVALID_FRAME_ERROR)
             .with_frame_type(frame.tag().into()))
@@ -772,7 +788,8 @@ pub trait PacketSpace<Config: endpoint::Config> {
                 }
                 Frame::Datagram(frame) => {
                     let on_error = on_frame_processed!(frame);
-                    self.handle_datagram_frame(frame.into()).map_err(on_error)?;
+                    self.handle_datagram_frame(frame.into(), publisher)
+                        .map_err(on_error)?;
                 }
                 Frame::DataBlocked(frame) => {
                     let on_error = on_frame_processed!(frame);
@@ -17,7 +17,7 @@ use s2n_codec::{DecoderBuffer, DecoderValue};
 use s2n_quic_core::{
     ack,
     application::ServerName,
-    connection::{InitialId, PeerId},
+    connection::{ConnectionHistograms, InitialId, PeerId},
     crypto,
     crypto::{tls, CryptoSuite, Key},
     ct::ConstantTimeEq,
@@ -25,6 +25,7 @@ use s2n_quic_core::{
     event,
     event::IntoEvent,
     packet::number::PacketNumberSpace,
+    recovery::loss_rate,
     time::Timestamp,
     transport::{
         self,
@@ -423,6 +424,12 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             keep_alive,
             max_mtu,
             datagram_manager,
+            loss_rate::Estimator::new(self.limits.loss_window()),
+            self.limits
+                .histograms()
+                .then(|| Box::new(ConnectionHistograms::default())),
+            self.limits.rack_loss_detection(),
+            self.limits.min_key_update_interval(),
         )));
         self.publisher.on_key_update(event::builder::KeyUpdate {
             key_type: event::builder::KeyType::OneRtt { generation: 0 },
@@ -13,7 +13,7 @@ use core::{
 };
 pub use s2n_quic_core::{
     application,
-    stream::{ops, StreamError, StreamId, StreamType},
+    stream::{ops, StreamError, StreamId, StreamPriority, StreamType, WriteAmplification},
 };
 
 #[derive(Clone)]
@@ -182,6 +182,28 @@ macro_rules! tx_stream_apis {
             }
         }
 
+        /// Enqueues a slice of chunks of data for sending it towards the peer without blocking.
+        ///
+        /// The chunks are not copied; only their reference counts are incremented. Chunks which
+        /// are enqueued together are coalesced into as few `STREAM` frames as the path MTU
+        /// permits.
+        ///
+        /// The method will return:
+        /// - `Ok(len)` with the number of bytes that were enqueued. This will be less than the
+        ///   total length of `bufs` if the send buffer capacity was exhausted.
+        /// - `Err(stream_error)` if the data could not be sent, because the stream
+        ///   had previously entered an error state.
+        pub fn write_vectored(&mut self, bufs: &[Bytes]) -> Result<usize, StreamError> {
+            if bufs.is_empty() {
+                return Ok(0);
+            }
+
+            let mut chunks = bufs.to_vec();
+            let response = self.tx_request()?.send(&mut chunks).poll(None)?;
+
+            Ok(response.tx().expect("invalid response").bytes.consumed)
+        }
+
         /// Flushes the send buffer and waits for acknowledgement from the peer.
         ///
         /// The method will return:
@@ -224,6 +246,27 @@ macro_rules! tx_stream_apis {
             self.tx_request()?.reset(error_code).poll(None)?;
             Ok(())
         }
+
+        /// Returns the number of bytes written to STREAM frames per byte of stream data
+        ///
+        /// Retransmitted data and the STREAM frame headers are counted in the wire bytes, so the
+        /// ratio grows above 1 when packets carrying stream data are lost.
+        pub fn write_amplification(&mut self) -> Result<WriteAmplification, StreamError> {
+            self.0
+                .connection
+                .stream_write_amplification(self.0.stream_id)
+        }
+
+        /// Changes the priority of the stream when sharing bandwidth reserved by the congestion
+        /// controller
+        ///
+        /// Streams below the reserved priority only share the unreserved part of the sending
+        /// rate. Streams default to [`StreamPriority::Normal`].
+        pub fn set_priority(&mut self, priority: StreamPriority) -> Result<(), StreamError> {
+            self.0
+                .connection
+                .set_stream_priority(self.0.stream_id, priority)
+        }
     };
 }
 
@@ -302,6 +345,24 @@ macro_rules! rx_stream_apis {
             self.rx_request()?.stop_sending(error_code).poll(None)?;
             Ok(())
         }
+
+        /// Donates `bytes` of unused flow control credits to the stream `to_stream`.
+        ///
+        /// The credits must have already been granted to the peer on this `Stream`, without
+        /// the peer having used them yet. The window this `Stream` maintains towards the peer
+        /// shrinks by `bytes`, while the window of `to_stream` grows by `bytes` and is sent to
+        /// the peer in a `MAX_STREAM_DATA` frame.
+        ///
+        /// This can be used to move credits from a `Stream` that is not expected to receive
+        /// much more data to a `Stream` on which the peer is blocked.
+        pub fn donate_credits(
+            &mut self,
+            to_stream: StreamId,
+            bytes: u64,
+        ) -> Result<(), StreamError> {
+            let id = self.0.stream_id;
+            self.0.connection.donate_credits(id, to_stream, bytes)
+        }
     };
 }
 
@@ -0,0 +1,349 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proactively scales the connection-level `MAX_DATA` window based on the observed throughput
+//!
+//! Issuing `MAX_DATA` credits only after the window has been mostly consumed can cause the
+//! peer to stall when the application is reading at a high rate. `AutoScaleMaxData` tracks the
+//! rate at which the application consumes data and computes a window which keeps the peer at
+//! least [`RTT_MULTIPLIER`] round trips ahead of the current send rate, up to a configured
+//! maximum.
+
+use core::time::Duration;
+use s2n_quic_core::{recovery::bandwidth::Bandwidth, time::Timestamp};
+
+/// The number of round trips worth of data the window should cover
+pub const RTT_MULTIPLIER: u32 = 2;
+
+/// The minimum amount of time between two throughput samples
+///
+/// Sampling more frequently than this results in very noisy rate estimates, since the
+/// application usually consumes data in bursts.
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The weight given to the newest sample in the throughput EWMA (1/8, same as SRTT)
+const EWMA_NEW_SAMPLE_WEIGHT: u64 = 1;
+const EWMA_WEIGHT_DENOMINATOR: u64 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sample {
+    /// The time the sample was taken
+    timestamp: Timestamp,
+    /// The total amount of consumed bytes at the time of the sample
+    consumed_bytes: u64,
+}
+
+/// Estimates the connection receive window required to avoid sender stalls
+#[derive(Clone, Debug)]
+pub struct AutoScaleMaxData {
+    /// The largest window which is returned
+    max_window: u64,
+    /// The smoothed round trip time of the active path
+    smoothed_rtt: Option<Duration>,
+    /// The bandwidth estimate provided by the congestion controller, if available
+    bandwidth_estimate: Option<Bandwidth>,
+    /// The EWMA of the application consumption rate in bytes per second
+    consumption_rate: Option<u64>,
+    /// The previous throughput sample
+    last_sample: Option<Sample>,
+}
+
+impl AutoScaleMaxData {
+    pub fn new(max_window: u64) -> Self {
+        Self {
+            max_window,
+            smoothed_rtt: None,
+            bandwidth_estimate: None,
+            consumption_rate: None,
+            last_sample: None,
+        }
+    }
+
+    /// Called when the RTT estimate for the active path is updated
+    #[inline]
+    pub fn on_rtt_update(&mut self, smoothed_rtt: Duration) {
+        self.smoothed_rtt = Some(smoothed_rtt);
+    }
+
+    /// Called when the congestion controller produces a new bandwidth estimate
+    ///
+    /// When available, the bandwidth estimate is preferred over the measured consumption rate.
+    #[inline]
+    pub fn on_bandwidth_estimate(&mut self, bandwidth_estimate: Option<Bandwidth>) {
+        self.bandwidth_estimate = bandwidth_estimate.filter(|bw| *bw > Bandwidth::ZERO);
+    }
+
+    /// Records the total number of bytes consumed by the application at the given time
+    #[inline]
+    pub fn on_consumed(&mut self, now: Timestamp, consumed_bytes: u64) {
+        let sample = Sample {
+            timestamp: now,
+            consumed_bytes,
+        };
+
+        let last_sample = if let Some(last_sample) = self.last_sample {
+            last_sample
+        } else {
+            self.last_sample = Some(sample);
+            return;
+        };
+
+        let elapsed = now.saturating_duration_since(last_sample.timestamp);
+        if elapsed < MIN_SAMPLE_INTERVAL {
+            return;
+        }
+
+        let bytes = consumed_bytes.saturating_sub(last_sample.consumed_bytes);
+        let rate = bytes.saturating_mul(1_000_000) / (elapsed.as_micros() as u64).max(1);
+
+        self.consumption_rate = Some(match self.consumption_rate {
+            Some(prev) => {
+                (prev.saturating_mul(EWMA_WEIGHT_DENOMINATOR - EWMA_NEW_SAMPLE_WEIGHT)
+                    + rate.saturating_mul(EWMA_NEW_SAMPLE_WEIGHT))
+                    / EWMA_WEIGHT_DENOMINATOR
+            }
+            None => rate,
+        });
+        self.last_sample = Some(sample);
+    }
+
+    /// Returns the rate used for scaling the window
+    ///
+    /// The congestion controller bandwidth estimate is used if available, otherwise
+    /// the measured consumption rate is used.
+    #[inline]
+    pub fn rate(&self) -> Option<Bandwidth> {
+        self.bandwidth_estimate.or_else(|| {
+            self.consumption_rate
+                .map(|rate| Bandwidth::new(rate, Duration::from_secs(1)))
+        })
+    }
+
+    /// Returns the window required to keep the peer [`RTT_MULTIPLIER`] round trips ahead
+    ///
+    /// The window is clamped to the configured maximum. `None` is returned if there is not
+    /// enough information to compute a window.
+    #[inline]
+    pub fn target_window(&self) -> Option<u64> {
+        let rtt = self.smoothed_rtt?;
+        let rate = self.rate()?;
+        let window = rate * (rtt * RTT_MULTIPLIER);
+        Some(window.min(self.max_window))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ops::Range;
+    use s2n_quic_core::time::{testing::Clock, Clock as _};
+
+    const RTT: Duration = Duration::from_millis(20);
+    const TICK: Duration = Duration::from_millis(1);
+    /// 1 Gbps
+    const LINK_RATE: u64 = 1_000_000_000 / 8;
+
+    #[test]
+    fn no_estimate_without_rtt() {
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        auto_scale.on_bandwidth_estimate(Some(Bandwidth::new(LINK_RATE, Duration::from_secs(1))));
+        assert_eq!(auto_scale.target_window(), None);
+
+        auto_scale.on_rtt_update(RTT);
+        assert_eq!(auto_scale.target_window(), Some(2 * LINK_RATE * 20 / 1000));
+    }
+
+    #[test]
+    fn window_clamped_to_max() {
+        let max_window = LINK_RATE * 10 / 1000;
+        let mut auto_scale = AutoScaleMaxData::new(max_window);
+        auto_scale.on_rtt_update(RTT);
+        auto_scale.on_bandwidth_estimate(Some(Bandwidth::new(LINK_RATE, Duration::from_secs(1))));
+        assert_eq!(auto_scale.target_window(), Some(max_window));
+
+        // smaller windows aren't affected
+        auto_scale.on_rtt_update(RTT / 8);
+        assert_eq!(
+            auto_scale.target_window(),
+            Some(2 * LINK_RATE * 20 / 8 / 1000)
+        );
+    }
+
+    #[test]
+    fn bandwidth_estimate_preferred() {
+        let mut clock = Clock::default();
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        auto_scale.on_rtt_update(RTT);
+
+        auto_scale.on_consumed(clock.get_time(), 0);
+        clock.inc_by(Duration::from_secs(1));
+        auto_scale.on_consumed(clock.get_time(), 1000);
+        assert_eq!(
+            auto_scale.rate(),
+            Some(Bandwidth::new(1000, Duration::from_secs(1)))
+        );
+
+        let bw = Bandwidth::new(LINK_RATE, Duration::from_secs(1));
+        auto_scale.on_bandwidth_estimate(Some(bw));
+        assert_eq!(auto_scale.rate(), Some(bw));
+
+        // fall back to the measured rate when the estimate goes away
+        auto_scale.on_bandwidth_estimate(None);
+        assert_eq!(
+            auto_scale.rate(),
+            Some(Bandwidth::new(1000, Duration::from_secs(1)))
+        );
+    }
+
+    #[test]
+    fn consumption_rate_ewma() {
+        let mut clock = Clock::default();
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        let mut consumed = 0;
+
+        auto_scale.on_consumed(clock.get_time(), consumed);
+        for _ in 0..1000 {
+            clock.inc_by(TICK);
+            consumed += LINK_RATE / 1000;
+            auto_scale.on_consumed(clock.get_time(), consumed);
+        }
+
+        assert_eq!(
+            auto_scale.rate(),
+            Some(Bandwidth::new(LINK_RATE, Duration::from_secs(1)))
+        );
+
+        // samples closer than the minimum interval are ignored
+        auto_scale.on_consumed(clock.get_time() + Duration::from_micros(10), consumed + 1);
+        assert_eq!(
+            auto_scale.rate(),
+            Some(Bandwidth::new(LINK_RATE, Duration::from_secs(1)))
+        );
+    }
+
+    /// Simulates a 1 Gbps path with a 20ms RTT and returns the number of ticks the sender
+    /// was blocked on connection flow control
+    fn simulate(auto_scale: Option<&mut AutoScaleMaxData>, initial_window: u64) -> usize {
+        simulate_with_pause(auto_scale, initial_window, 0..0)
+    }
+
+    /// Simulates a 1 Gbps path with a 20ms RTT where the application stops reading during the
+    /// `pause` ticks, and returns the number of ticks the sender was blocked on connection
+    /// flow control
+    ///
+    /// The sender is expected to be blocked while the application isn't reading and until the
+    /// first `MAX_DATA` frame sent after the pause arrives, so those ticks aren't counted.
+    fn simulate_with_pause(
+        auto_scale: Option<&mut AutoScaleMaxData>,
+        initial_window: u64,
+        pause: Range<usize>,
+    ) -> usize {
+        let mut clock = Clock::default();
+        let one_way_ticks = (RTT / 2).as_millis() as usize;
+        let per_tick = LINK_RATE / 1000;
+
+        let mut auto_scale = auto_scale;
+        let mut sent = 0u64;
+        let mut max_data = initial_window;
+        // MAX_DATA values which are in flight to the sender
+        let mut in_flight_max_data = vec![None; one_way_ticks];
+        // data which is in flight to the receiver
+        let mut in_flight_data = vec![0u64; one_way_ticks];
+        // data which was received but not consumed by the application yet
+        let mut buffered = 0u64;
+        let mut consumed = 0u64;
+        let mut advertised = initial_window;
+        let mut stalls = 0;
+
+        for tick in 0..2000 {
+            let slot = tick % one_way_ticks;
+
+            // deliver the MAX_DATA frames sent one-way delay ago
+            if let Some(value) = in_flight_max_data[slot].take() {
+                max_data = max_data.max(value);
+            }
+
+            // deliver the data sent one-way delay ago and let the application consume it
+            buffered += core::mem::take(&mut in_flight_data[slot]);
+            if !pause.contains(&tick) {
+                consumed += core::mem::take(&mut buffered);
+            }
+
+            // send as much as the path and flow control permit
+            let available = max_data - sent;
+            let is_blocked_by_pause =
+                !pause.is_empty() && (pause.start..pause.end + one_way_ticks).contains(&tick);
+            if available < per_tick && !is_blocked_by_pause {
+                stalls += 1;
+            }
+            let len = available.min(per_tick);
+            sent += len;
+            in_flight_data[slot] = len;
+
+            // compute the new receive window
+            let window = if let Some(auto_scale) = auto_scale.as_mut() {
+                auto_scale.on_consumed(clock.get_time(), consumed);
+                auto_scale.target_window().unwrap_or(0).max(initial_window)
+            } else {
+                initial_window
+            };
+
+            let target = consumed + window;
+            // reactive mode only updates when less than half of the window remains
+            let should_update = if auto_scale.is_some() {
+                target > advertised
+            } else {
+                advertised - consumed < initial_window / 2
+            };
+
+            if should_update {
+                advertised = target;
+                in_flight_max_data[slot] = Some(target);
+            }
+
+            clock.inc_by(TICK);
+        }
+
+        stalls
+    }
+
+    #[test]
+    fn reactive_window_stalls() {
+        // 1.5 BDP
+        let initial_window = LINK_RATE * 30 / 1000;
+        assert!(simulate(None, initial_window) > 0);
+    }
+
+    #[test]
+    fn zero_stalls_with_bandwidth_estimate() {
+        let initial_window = LINK_RATE * 30 / 1000;
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        auto_scale.on_rtt_update(RTT);
+        auto_scale.on_bandwidth_estimate(Some(Bandwidth::new(LINK_RATE, Duration::from_secs(1))));
+
+        assert_eq!(simulate(Some(&mut auto_scale), initial_window), 0);
+    }
+
+    #[test]
+    fn zero_stalls_with_measured_throughput() {
+        let initial_window = LINK_RATE * 30 / 1000;
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        auto_scale.on_rtt_update(RTT);
+
+        assert_eq!(simulate(Some(&mut auto_scale), initial_window), 0);
+    }
+
+    #[test]
+    fn zero_stalls_after_application_pause() {
+        let initial_window = LINK_RATE * 30 / 1000;
+        let mut auto_scale = AutoScaleMaxData::new(u64::MAX);
+        auto_scale.on_rtt_update(RTT);
+
+        // the application stops reading for 500ms and then consumes the buffered data at once
+        let pause = 500..1000;
+        assert_eq!(
+            simulate_with_pause(Some(&mut auto_scale), initial_window, pause),
+            0
+        );
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::time::Duration;
+use s2n_quic_core::{time::Timestamp, varint::VarInt};
+
+/// A change of the flow control state which should be reported to the application
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Transition {
+    /// The sender became blocked on `limit`
+    Blocked { limit: VarInt },
+    /// The peer raised the limit of a reported stall to `limit`
+    Unblocked {
+        limit: VarInt,
+        blocked_duration: Duration,
+    },
+}
+
+/// Tracks when a flow control stall should be reported to the application
+///
+/// A stall is reported once, when it begins. It is not reported again until the peer has
+/// raised the limit and the sender becomes blocked again. Raising the limit of a stall that
+/// was reported is reported as well, along with the duration of the stall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum BlockedEvent {
+    /// The sender is not blocked
+    Unblocked,
+    /// The sender is blocked on `limit` and the stall has not been reported yet
+    Pending { limit: VarInt },
+    /// The stall has been reported at `since`
+    Reported { since: Timestamp },
+    /// The peer raised the limit of the stall reported at `since` to `limit`
+    ///
+    /// The sender may already be blocked on the new limit, in which case the new stall is
+    /// reported after the resolution of the previous one.
+    Resolved {
+        since: Timestamp,
+        limit: VarInt,
+        blocked_again: Option<VarInt>,
+    },
+}
+
+impl Default for BlockedEvent {
+    fn default() -> Self {
+        Self::Unblocked
+    }
+}
+
+impl BlockedEvent {
+    /// Called when the sender is blocked by the peer's `limit`
+    #[inline]
+    pub fn on_blocked(&mut self, limit: VarInt) {
+        match self {
+            Self::Unblocked => *self = Self::Pending { limit },
+            Self::Resolved { blocked_again, .. } => {
+                blocked_again.get_or_insert(limit);
+            }
+            Self::Pending { .. } | Self::Reported { .. } => {}
+        }
+    }
+
+    /// Called when the peer raises the limit to `limit`
+    #[inline]
+    pub fn on_unblocked(&mut self, limit: VarInt) {
+        match *self {
+            Self::Pending { .. } => *self = Self::Unblocked,
+            Self::Reported { since } => {
+                *self = Self::Resolved {
+                    since,
+                    limit,
+                    blocked_again: None,
+                }
+            }
+            Self::Resolved { since, .. } => {
+                *self = Self::Resolved {
+                    since,
+                    limit,
+                    blocked_again: None,
+                }
+            }
+            Self::Unblocked => {}
+        }
+    }
+
+    /// Returns the next transition which has not been reported yet
+    ///
+    /// This should be called until it returns `None`.
+    #[inline]
+    pub fn poll(&mut self, now: Timestamp) -> Option<Transition> {
+        match *self {
+            Self::Pending { limit } => {
+                *self = Self::Reported { since: now };
+                Some(Transition::Blocked { limit })
+            }
+            Self::Resolved {
+                since,
+                limit,
+                blocked_again,
+            } => {
+                *self = match blocked_again {
+                    Some(limit) => Self::Pending { limit },
+                    None => Self::Unblocked,
+                };
+                Some(Transition::Unblocked {
+                    limit,
+                    blocked_duration: now.saturating_duration_since(since),
+                })
+            }
+            Self::Unblocked | Self::Reported { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::time::{testing::Clock as MockClock, Clock};
+
+    #[test]
+    fn report_once_test() {
+        let now = MockClock::default().get_time();
+        let mut event = BlockedEvent::default();
+        assert_eq!(event.poll(now), None);
+
+        event.on_blocked(VarInt::from_u8(10));
+        event.on_blocked(VarInt::from_u8(10));
+        assert_eq!(
+            event.poll(now),
+            Some(Transition::Blocked {
+                limit: VarInt::from_u8(10)
+            })
+        );
+        assert_eq!(event.poll(now), None);
+
+        // the stall continues
+        event.on_blocked(VarInt::from_u8(10));
+        assert_eq!(event.poll(now), None);
+
+        // the stall is resolved and triggered again
+        event.on_unblocked(VarInt::from_u8(20));
+        event.on_blocked(VarInt::from_u8(20));
+        assert_eq!(
+            event.poll(now + Duration::from_millis(5)),
+            Some(Transition::Unblocked {
+                limit: VarInt::from_u8(20),
+                blocked_duration: Duration::from_millis(5),
+            })
+        );
+        assert_eq!(
+            event.poll(now),
+            Some(Transition::Blocked {
+                limit: VarInt::from_u8(20)
+            })
+        );
+        assert_eq!(event.poll(now), None);
+    }
+
+    #[test]
+    fn unreported_stall_test() {
+        let now = MockClock::default().get_time();
+        let mut event = BlockedEvent::default();
+
+        // a stall which is resolved before it is reported is never reported
+        event.on_blocked(VarInt::from_u8(10));
+        event.on_unblocked(VarInt::from_u8(20));
+        assert_eq!(event.poll(now), None);
+    }
+
+    #[test]
+    fn resolved_stall_test() {
+        let now = MockClock::default().get_time();
+        let mut event = BlockedEvent::default();
+
+        event.on_blocked(VarInt::from_u8(10));
+        assert!(event.poll(now).is_some());
+
+        // the limit is raised multiple times before the resolution is reported
+        event.on_unblocked(VarInt::from_u8(20));
+        event.on_unblocked(VarInt::from_u8(30));
+        assert_eq!(
+            event.poll(now + Duration::from_secs(1)),
+            Some(Transition::Unblocked {
+                limit: VarInt::from_u8(30),
+                blocked_duration: Duration::from_secs(1),
+            })
+        );
+        assert_eq!(event.poll(now), None);
+    }
+}
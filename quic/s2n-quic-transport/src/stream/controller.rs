@@ -356,6 +356,96 @@ mod tests {
             }
         }
     }
+
+    fn controller(max_remote_bidi: u32, max_remote_uni: u32) -> Controller {
+        let initial_local_limits = InitialFlowControlLimits {
+            max_open_remote_bidirectional_streams: VarInt::from_u32(max_remote_bidi),
+            max_open_remote_unidirectional_streams: VarInt::from_u32(max_remote_uni),
+            ..Default::default()
+        };
+
+        Controller::new(
+            endpoint::Type::Server,
+            InitialFlowControlLimits::default(),
+            initial_local_limits,
+            stream::Limits::default(),
+        )
+    }
+
+    fn open_remote_stream(
+        controller: &mut Controller,
+        stream_type: StreamType,
+        nth: u64,
+    ) -> Result<StreamId, transport::Error> {
+        let stream_id = StreamId::nth(endpoint::Type::Client, stream_type, nth).unwrap();
+        controller.on_open_remote_stream(StreamIter::new(stream_id, stream_id))?;
+        Ok(stream_id)
+    }
+
+    #[test]
+    fn remote_stream_limits_are_independent_test() {
+        for (stream_type, other_type) in [
+            (StreamType::Unidirectional, StreamType::Bidirectional),
+            (StreamType::Bidirectional, StreamType::Unidirectional),
+        ] {
+            let mut controller = controller(4, 4);
+
+            for nth in 0..4 {
+                assert!(open_remote_stream(&mut controller, stream_type, nth).is_ok());
+            }
+
+            assert_eq!(
+                open_remote_stream(&mut controller, stream_type, 4),
+                Err(transport::Error::STREAM_LIMIT_ERROR)
+            );
+
+            // exhausting one type of stream doesn't affect the other
+            assert_eq!(
+                controller.available_remote_initiated_stream_capacity(other_type),
+                VarInt::from_u8(4)
+            );
+            for nth in 0..4 {
+                assert!(open_remote_stream(&mut controller, other_type, nth).is_ok());
+            }
+            assert_eq!(
+                open_remote_stream(&mut controller, other_type, 4),
+                Err(transport::Error::STREAM_LIMIT_ERROR)
+            );
+        }
+    }
+
+    #[test]
+    fn proactive_max_streams_test() {
+        let mut controller = controller(100, 100);
+        let stream_type = StreamType::Unidirectional;
+
+        // the peer has more than half of its credit left, so closing a stream waits for the
+        // threshold
+        open_remote_stream(&mut controller, stream_type, 39).unwrap();
+        controller.on_close_stream(StreamId::nth(endpoint::Type::Client, stream_type, 0).unwrap());
+        assert_eq!(
+            controller.get_transmission_interest(),
+            transmission::Interest::None
+        );
+
+        // the peer has less than half of its credit left, so MAX_STREAMS is sent immediately
+        open_remote_stream(&mut controller, stream_type, 59).unwrap();
+        controller.on_close_stream(StreamId::nth(endpoint::Type::Client, stream_type, 1).unwrap());
+        assert_eq!(
+            controller.get_transmission_interest(),
+            transmission::Interest::NewData
+        );
+        assert_eq!(
+            controller.remote_initiated_max_streams_latest_value(stream_type),
+            VarInt::from_u8(102)
+        );
+
+        // the limit for the other type of stream isn't updated
+        assert_eq!(
+            controller.remote_initiated_max_streams_latest_value(StreamType::Bidirectional),
+            VarInt::from_u8(100)
+        );
+    }
 }
 
 #[cfg(test)]
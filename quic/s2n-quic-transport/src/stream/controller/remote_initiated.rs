@@ -24,6 +24,9 @@ pub const MAX_STREAMS_SYNC_FRACTION: VarInt = VarInt::from_u8(10);
 //# corresponding type that can be opened over the lifetime of the
 //# connection.  This value cannot exceed 2^60, as it is not possible
 //# to encode stream IDs larger than 2^62-1.
+// Send a MAX_STREAMS frame immediately whenever the peer can open fewer than 1/2 of the window
+// with the limit it was sent
+pub const MAX_STREAMS_PROACTIVE_FRACTION: VarInt = VarInt::from_u8(2);
 // Safety: 2^60 is less than MAX_VARINT_VALUE
 const MAX_STREAMS_MAX_VALUE: VarInt = unsafe { VarInt::new_unchecked(1 << 60) };
 
@@ -99,7 +102,15 @@ impl RemoteInitiated {
             .closed_streams
             .saturating_add(self.max_local_limit)
             .min(MAX_STREAMS_MAX_VALUE);
-        self.max_streams_sync.update_latest_value(max_streams);
+
+        // A peer which keeps most of the window open could run out of credit before enough
+        // streams are closed to reach the threshold, so the increase is sent right away
+        if self.available_credit() < self.max_local_limit / MAX_STREAMS_PROACTIVE_FRACTION {
+            self.max_streams_sync
+                .update_latest_value_immediately(max_streams);
+        } else {
+            self.max_streams_sync.update_latest_value(max_streams);
+        }
 
         self.check_integrity();
     }
@@ -110,6 +121,14 @@ impl RemoteInitiated {
         self.opened_streams - self.closed_streams
     }
 
+    /// Returns the number of streams the peer can still open with the limit it was sent
+    #[inline]
+    fn available_credit(&self) -> VarInt {
+        self.max_streams_sync
+            .advertised_value()
+            .saturating_sub(self.opened_streams)
+    }
+
     #[inline]
     pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
         self.max_streams_sync.on_packet_ack(ack_set)
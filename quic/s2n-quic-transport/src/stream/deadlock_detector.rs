@@ -0,0 +1,222 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::vec::Vec;
+use core::time::Duration;
+use s2n_quic_core::time::{timer, Timer, Timestamp};
+
+/// The number of times the streams are sampled within each timeout while a stream is blocked
+const SAMPLES_PER_TIMEOUT: u32 = 5;
+
+/// The state of the streams observed when sampling for a potential deadlock
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PotentialDeadlock {
+    /// The IDs of the streams blocked by the peer's flow control limits
+    pub blocked_stream_ids: Vec<u64>,
+    /// The IDs of the streams with received data the application has not read
+    pub unread_stream_ids: Vec<u64>,
+    /// The number of unread bytes of each stream in `unread_stream_ids`
+    pub unread_lens: Vec<u64>,
+    /// How long streams were both blocked and left unread
+    pub duration: Duration,
+}
+
+/// Detects both endpoints waiting for the other to read
+///
+/// If neither application reads from its streams until its writes complete, each endpoint
+/// eventually exhausts the flow control credits of the other while holding unread data of its
+/// own. Neither side can make progress, but the connection stays alive since both endpoints keep
+/// sending blocked frames.
+///
+/// Once a stream is blocked by the peer's flow control limits, the streams are sampled
+/// periodically. A potential deadlock is reported once a stream has been blocked and received
+/// data has been left unread at every sample for the whole timeout. It is not reported again
+/// until either condition clears.
+#[derive(Debug)]
+pub(super) struct DeadlockDetector {
+    /// How long both conditions need to persist before a potential deadlock is reported
+    ///
+    /// The detection is disabled if the timeout is zero.
+    timeout: Duration,
+    /// The time of the first sample of the current stall
+    since: Option<Timestamp>,
+    /// Set once the current stall has been reported
+    reported: bool,
+    /// Armed while a stream is blocked
+    sample_timer: Timer,
+    /// A potential deadlock which has not been published yet
+    pending: Option<PotentialDeadlock>,
+}
+
+impl DeadlockDetector {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            since: None,
+            reported: false,
+            sample_timer: Timer::default(),
+            pending: None,
+        }
+    }
+
+    /// Called when a stream is blocked by the peer's flow control limits
+    #[inline]
+    pub fn on_blocked(&mut self, now: Timestamp) {
+        if self.timeout == Duration::ZERO || self.sample_timer.is_armed() {
+            return;
+        }
+
+        self.sample_timer.set(now + self.sample_period());
+    }
+
+    /// Returns `true` if the streams should be sampled
+    #[inline]
+    pub fn on_timeout(&mut self, now: Timestamp) -> bool {
+        self.sample_timer.poll_expiration(now).is_ready()
+    }
+
+    /// Called with the state of the streams after `on_timeout` returned `true`
+    pub fn on_sample(&mut self, now: Timestamp, mut sample: PotentialDeadlock) {
+        if sample.blocked_stream_ids.is_empty() {
+            // nothing is blocked anymore so sampling stops until a stream is blocked again
+            self.since = None;
+            self.reported = false;
+            return;
+        }
+
+        self.sample_timer.set(now + self.sample_period());
+
+        if sample.unread_stream_ids.is_empty() {
+            self.since = None;
+            self.reported = false;
+            return;
+        }
+
+        let since = *self.since.get_or_insert(now);
+        sample.duration = now.saturating_duration_since(since);
+
+        if !self.reported && sample.duration >= self.timeout {
+            self.reported = true;
+            self.pending = Some(sample);
+        }
+    }
+
+    /// Returns the potential deadlock which should be published, if any
+    #[inline]
+    pub fn poll(&mut self) -> Option<PotentialDeadlock> {
+        self.pending.take()
+    }
+
+    #[inline]
+    fn sample_period(&self) -> Duration {
+        self.timeout / SAMPLES_PER_TIMEOUT
+    }
+}
+
+impl timer::Provider for DeadlockDetector {
+    #[inline]
+    fn timers<Q: timer::Query>(&self, query: &mut Q) -> timer::Result {
+        self.sample_timer.timers(query)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use s2n_quic_core::time::{testing::Clock as MockClock, timer::Provider as _, Clock};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn sample(blocked: &[u64], unread: &[(u64, u64)]) -> PotentialDeadlock {
+        PotentialDeadlock {
+            blocked_stream_ids: blocked.to_vec(),
+            unread_stream_ids: unread.iter().map(|(id, _)| *id).collect(),
+            unread_lens: unread.iter().map(|(_, len)| *len).collect(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    /// Samples the detector until it stops sampling or `end` is reached
+    fn run(
+        detector: &mut DeadlockDetector,
+        mut now: Timestamp,
+        end: Timestamp,
+        state: &PotentialDeadlock,
+    ) -> Timestamp {
+        while let Some(expiration) = detector.next_expiration() {
+            if expiration > end {
+                break;
+            }
+            now = expiration;
+            assert!(detector.on_timeout(now));
+            detector.on_sample(now, state.clone());
+        }
+        now
+    }
+
+    #[test]
+    fn report_once_test() {
+        let start = MockClock::default().get_time();
+        let mut detector = DeadlockDetector::new(TIMEOUT);
+        let deadlocked = sample(&[0], &[(1, 100), (5, 20)]);
+
+        // nothing is sampled until a stream is blocked
+        assert!(!detector.sample_timer.is_armed());
+        detector.on_blocked(start);
+
+        // the stall hasn't persisted for the whole timeout yet
+        let now = run(&mut detector, start, start + TIMEOUT, &deadlocked);
+        assert_eq!(detector.poll(), None);
+
+        // the stall is reported a timeout after the first sample
+        let now = run(&mut detector, now, start + TIMEOUT * 2, &deadlocked);
+        assert_eq!(
+            detector.poll(),
+            Some(PotentialDeadlock {
+                blocked_stream_ids: vec![0],
+                unread_stream_ids: vec![1, 5],
+                unread_lens: vec![100, 20],
+                duration: TIMEOUT,
+            })
+        );
+
+        // the stall is only reported once
+        let now = run(&mut detector, now, now + TIMEOUT * 2, &deadlocked);
+        assert_eq!(detector.poll(), None);
+
+        // the application reads the data, after which the stall is reported again
+        let now = run(&mut detector, now, now + TIMEOUT, &sample(&[0], &[]));
+        run(&mut detector, now, now + TIMEOUT * 2, &deadlocked);
+        assert!(detector.poll().is_some());
+    }
+
+    #[test]
+    fn unblocked_test() {
+        let now = MockClock::default().get_time();
+        let mut detector = DeadlockDetector::new(TIMEOUT);
+        detector.on_blocked(now);
+
+        // the peer raises its limits, so the sampling stops
+        let now = run(&mut detector, now, now + TIMEOUT, &sample(&[], &[(1, 100)]));
+        assert!(!detector.sample_timer.is_armed());
+        assert_eq!(detector.poll(), None);
+
+        // a stall which clears in between samples restarts the timeout
+        detector.on_blocked(now);
+        let deadlocked = sample(&[0], &[(1, 100)]);
+        let now = run(&mut detector, now, now + TIMEOUT / 2, &deadlocked);
+        let now = run(&mut detector, now, now + TIMEOUT / 2, &sample(&[0], &[]));
+        run(&mut detector, now, now + TIMEOUT - TIMEOUT / 5, &deadlocked);
+        assert_eq!(detector.poll(), None);
+    }
+
+    #[test]
+    fn disabled_test() {
+        let now = MockClock::default().get_time();
+        let mut detector = DeadlockDetector::new(Duration::ZERO);
+        detector.on_blocked(now);
+        assert!(!detector.sample_timer.is_armed());
+    }
+}
@@ -5,14 +5,15 @@
 
 use crate::{
     contexts::{OnTransmitError, WriteContext},
+    stream::{auto_scale_max_data::AutoScaleMaxData, receive_budget::ReceiveByteBudget},
     sync::{IncrementalValueSync, ValueToFrameWriter},
     transmission,
 };
 use alloc::rc::Rc;
-use core::cell::RefCell;
+use core::{cell::RefCell, time::Duration};
 use s2n_quic_core::{
-    ack, frame::max_data::MaxData, packet::number::PacketNumber, stream::StreamId, transport,
-    varint::VarInt,
+    ack, frame::max_data::MaxData, packet::number::PacketNumber, recovery::bandwidth::Bandwidth,
+    stream::StreamId, time::Timestamp, transport, varint::VarInt,
 };
 
 /// Writes `MAX_DATA` frames based on the connections flow control window.
@@ -46,10 +47,19 @@ struct IncomingConnectionFlowControllerImpl {
     /// The amount of flow control credits which had been acquired and where the
     /// data had already been consumed by the application
     pub(super) consumed_window: VarInt,
+    /// Scales the window based on the observed throughput
+    pub(super) auto_scale: AutoScaleMaxData,
+    /// Caps the number of unread bytes, if configured
+    pub(super) receive_budget: Option<ReceiveByteBudget>,
 }
 
 impl IncomingConnectionFlowControllerImpl {
-    pub fn new(initial_window_size: VarInt, desired_flow_control_window: u32) -> Self {
+    pub fn new(
+        initial_window_size: VarInt,
+        desired_flow_control_window: u32,
+        max_buffered_recv_bytes: Option<u64>,
+        max_data_window: u64,
+    ) -> Self {
         Self {
             read_window_sync: IncrementalValueSync::new(
                 VarInt::from_u32(desired_flow_control_window),
@@ -59,9 +69,21 @@ impl IncomingConnectionFlowControllerImpl {
             desired_flow_control_window,
             acquired_window: VarInt::from_u32(0),
             consumed_window: VarInt::from_u32(0),
+            auto_scale: AutoScaleMaxData::new(max_data_window),
+            receive_budget: max_buffered_recv_bytes.map(ReceiveByteBudget::new),
         }
     }
 
+    /// The number of received bytes which have not been consumed by the application
+    fn buffered(&self) -> u64 {
+        (self.acquired_window - self.consumed_window).as_u64()
+    }
+
+    pub fn recv_budget_remaining(&self) -> u64 {
+        self.receive_budget
+            .map_or(u64::MAX, |budget| budget.remaining(self.buffered()))
+    }
+
     pub fn remaining_window(&self) -> VarInt {
         self.read_window_sync.latest_value() - self.acquired_window
     }
@@ -78,10 +100,58 @@ impl IncomingConnectionFlowControllerImpl {
             "Can not consume more window than previously acquired"
         );
 
-        self.read_window_sync.update_latest_value(
-            self.consumed_window
-                .saturating_add(VarInt::from_u32(self.desired_flow_control_window)),
-        );
+        self.update_read_window();
+    }
+
+    /// The window which is currently maintained towards the peer
+    ///
+    /// This is the larger of the configured window and the window required to keep the peer
+    /// from stalling at the observed throughput.
+    fn flow_control_window(&self) -> VarInt {
+        let desired = VarInt::from_u32(self.desired_flow_control_window);
+        self.auto_scale
+            .target_window()
+            .and_then(|window| VarInt::new(window).ok())
+            .map_or(desired, |window| window.max(desired))
+    }
+
+    fn update_read_window(&mut self) {
+        let mut value = self
+            .consumed_window
+            .saturating_add(self.flow_control_window());
+
+        let buffered = self.buffered();
+        if let Some(budget) = self.receive_budget.as_mut() {
+            budget.on_buffered(buffered);
+
+            // The limit isn't raised until the application catches up on its reads
+            let max_data = match budget.max_data(self.consumed_window.as_u64()) {
+                Some(max_data) => VarInt::new(max_data).unwrap_or(VarInt::MAX),
+                None => return,
+            };
+            value = value.min(max_data);
+        }
+
+        // The window may shrink if the RTT or throughput decreases, but credits which
+        // were already issued can never be revoked
+        let value = value.max(self.read_window_sync.latest_value());
+        self.read_window_sync.update_latest_value(value);
+    }
+
+    pub fn on_rtt_update(&mut self, smoothed_rtt: Duration) {
+        self.auto_scale.on_rtt_update(smoothed_rtt);
+        self.update_read_window();
+    }
+
+    pub fn on_bandwidth_estimate(&mut self, bandwidth_estimate: Option<Bandwidth>) {
+        self.auto_scale.on_bandwidth_estimate(bandwidth_estimate);
+        self.update_read_window();
+    }
+
+    fn on_consumption_sample(&mut self, now: Timestamp) {
+        self.auto_scale
+            .on_consumed(now, self.consumed_window.as_u64());
+        self.update_read_window();
     }
 
     pub fn acquire_window(&mut self, desired: VarInt) -> Result<(), transport::Error> {
@@ -94,6 +164,12 @@ impl IncomingConnectionFlowControllerImpl {
         }
 
         self.acquired_window += desired;
+
+        let buffered = self.buffered();
+        if let Some(budget) = self.receive_budget.as_mut() {
+            budget.on_buffered(buffered);
+        }
+
         Ok(())
     }
 
@@ -107,6 +183,10 @@ impl IncomingConnectionFlowControllerImpl {
 
     #[inline]
     pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) -> Result<(), OnTransmitError> {
+        // Sample the consumption rate before writing so any window increase is sent
+        // proactively rather than waiting for the window to be consumed
+        self.on_consumption_sample(context.current_time());
+
         // Stream ID does not matter here, since it does not get transmitted
         self.read_window_sync
             .on_transmit(StreamId::from_varint(VarInt::from_u32(0)), context)
@@ -134,11 +214,24 @@ impl IncomingConnectionFlowController {
     /// `desired_flow_control_window`. This means if the window which is indicated
     /// to the peer is lower than this value the new value will be communicated
     /// to the peer.
-    pub fn new(initial_window_size: VarInt, desired_flow_control_window: u32) -> Self {
+    ///
+    /// If `max_buffered_recv_bytes` is set, the window never extends more than that many bytes
+    /// past the data which was consumed by the application.
+    ///
+    /// The window is scaled to the throughput of the connection, but never past
+    /// `max_data_window` unless `desired_flow_control_window` is larger.
+    pub fn new(
+        initial_window_size: VarInt,
+        desired_flow_control_window: u32,
+        max_buffered_recv_bytes: Option<u64>,
+        max_data_window: u64,
+    ) -> Self {
         Self {
             inner: Rc::new(RefCell::new(IncomingConnectionFlowControllerImpl::new(
                 initial_window_size,
                 desired_flow_control_window,
+                max_buffered_recv_bytes,
+                max_data_window,
             ))),
         }
     }
@@ -157,6 +250,20 @@ impl IncomingConnectionFlowController {
         self.inner.borrow_mut().release_window(amount)
     }
 
+    /// This method gets called when the RTT estimate of the active path is updated
+    pub fn on_rtt_update(&mut self, smoothed_rtt: Duration) {
+        self.inner.borrow_mut().on_rtt_update(smoothed_rtt)
+    }
+
+    /// This method gets called when the congestion controller provides a bandwidth estimate
+    ///
+    /// The estimate is preferred over the measured consumption rate when scaling the window.
+    pub fn on_bandwidth_estimate(&mut self, bandwidth_estimate: Option<Bandwidth>) {
+        self.inner
+            .borrow_mut()
+            .on_bandwidth_estimate(bandwidth_estimate)
+    }
+
     /// This method gets called when a packet delivery got acknowledged
     pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
         self.inner.borrow_mut().on_packet_ack(ack_set)
@@ -177,6 +284,13 @@ impl IncomingConnectionFlowController {
         self.inner.borrow().acquired_window
     }
 
+    /// Returns the number of bytes which can be received before the receive budget is used up
+    ///
+    /// Returns `u64::MAX` if no budget is configured.
+    pub fn recv_budget_remaining(&self) -> u64 {
+        self.inner.borrow().recv_budget_remaining()
+    }
+
     #[cfg(test)]
     pub fn remaining_window(&self) -> VarInt {
         self.inner.borrow_mut().remaining_window()
@@ -9,6 +9,7 @@ use crate::{
     recovery::RttEstimator,
     stream::{
         self,
+        deadlock_detector::{DeadlockDetector, PotentialDeadlock},
         incoming_connection_flow_controller::IncomingConnectionFlowController,
         outgoing_connection_flow_controller::OutgoingConnectionFlowController,
         stream_container::{StreamContainer, StreamContainerIterationResult},
@@ -24,13 +25,19 @@ use core::{
 };
 use futures_core::ready;
 use s2n_quic_core::{
-    ack, endpoint,
+    ack,
+    connection::{BiDirectionalFairnessController, ConnectionTokenBucket},
+    endpoint,
     frame::{
         stream::StreamRef, DataBlocked, MaxData, MaxStreamData, MaxStreams, ResetStream,
         StopSending, StreamDataBlocked, StreamsBlocked,
     },
     packet::number::PacketNumberSpace,
-    stream::{iter::StreamIter, ops, StreamId, StreamType},
+    recovery::{bandwidth::Bandwidth, congestion_controller::ReservedBandwidth},
+    stream::{
+        iter::StreamIter, ops, StreamId, StreamMetricsTotals, StreamPriority, StreamType,
+        WriteAmplification,
+    },
     time::{timer, Timestamp},
     transport::{self, parameters::InitialFlowControlLimits},
     varint::VarInt,
@@ -163,6 +170,13 @@ pub struct StreamManagerState<S> {
     pub(super) incoming_connection_flow_controller: IncomingConnectionFlowController,
     /// Flow control credit manager for sending data
     pub(super) outgoing_connection_flow_controller: OutgoingConnectionFlowController,
+    /// Limits the rate at which all streams combined send new data, if configured
+    send_token_bucket: Option<ConnectionTokenBucket>,
+    /// Limits the rate at which streams below the given priority send new data, while the
+    /// congestion controller reserves bandwidth for higher priority streams
+    unreserved_token_bucket: Option<(StreamPriority, ConnectionTokenBucket)>,
+    /// Holds back new data while the connection sends much more than it receives, if enabled
+    fairness_controller: Option<BiDirectionalFairnessController>,
     /// Controller for managing streams concurrency limits
     stream_controller: stream::Controller,
     /// A container which contains all Streams
@@ -186,6 +200,10 @@ pub struct StreamManagerState<S> {
     /// Limits for the Stream manager. Since only Stream limits are utilized at
     /// the moment we only store those
     stream_limits: stream::Limits,
+    /// Whether both endpoints negotiated checksums of the STREAM frame data
+    stream_data_checksum: bool,
+    /// Detects streams in both directions waiting on each other
+    deadlock_detector: DeadlockDetector,
 }
 
 impl<S: StreamTrait> StreamManagerState<S> {
@@ -239,6 +257,7 @@ impl<S: StreamTrait> StreamManagerState<S> {
             desired_flow_control_window: initial_receive_window.as_u64() as u32,
             initial_send_window,
             max_send_buffer_size: self.stream_limits.max_send_buffer_size.as_u32(),
+            stream_data_checksum: self.stream_data_checksum,
         }));
     }
 
@@ -393,6 +412,32 @@ impl<S: StreamTrait> StreamManagerState<S> {
         self.stream_controller.close();
     }
 
+    /// Collects the streams blocked by the peer's flow control limits and the streams with
+    /// unread data
+    fn sample_potential_deadlock(&mut self) -> PotentialDeadlock {
+        let mut sample = PotentialDeadlock::default();
+
+        self.streams
+            .iterate_streams(&mut self.stream_controller, |stream| {
+                let stream_id = stream.stream_id().as_varint().as_u64();
+
+                let interests = stream.get_stream_interests();
+                if interests.stream_flow_control_credits
+                    || interests.connection_flow_control_credits
+                {
+                    sample.blocked_stream_ids.push(stream_id);
+                }
+
+                let unread_len = stream.unread_len();
+                if unread_len > 0 {
+                    sample.unread_stream_ids.push(stream_id);
+                    sample.unread_lens.push(unread_len);
+                }
+            });
+
+        sample
+    }
+
     fn flush(&mut self, error: connection::Error) -> Poll<()> {
         self.close(error, true);
 
@@ -435,10 +480,17 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 incoming_connection_flow_controller: IncomingConnectionFlowController::new(
                     initial_local_limits.max_data,
                     initial_local_limits.max_data.as_u64() as u32,
+                    connection_limits.max_buffered_recv_bytes(),
+                    connection_limits.max_data_window(),
                 ),
                 outgoing_connection_flow_controller: OutgoingConnectionFlowController::new(
                     initial_peer_limits.max_data,
                 ),
+                send_token_bucket: None,
+                unreserved_token_bucket: None,
+                fairness_controller: BiDirectionalFairnessController::new(
+                    connection_limits.fairness_ratio(),
+                ),
                 stream_controller: stream::Controller::new(
                     local_endpoint_type,
                     initial_peer_limits,
@@ -453,6 +505,10 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 close_reason: None,
                 accept_state: AcceptState::new(local_endpoint_type),
                 stream_limits: connection_limits.stream_limits(),
+                stream_data_checksum: connection_limits.stream_data_checksum(),
+                deadlock_detector: DeadlockDetector::new(
+                    connection_limits.deadlock_detection_timeout(),
+                ),
             },
         }
     }
@@ -471,6 +527,14 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
             .acquired_window()
     }
 
+    /// The number of bytes of STREAM frames the local endpoint has written on outgoing streams,
+    /// including retransmissions
+    pub fn outgoing_frame_bytes_sent(&self) -> u64 {
+        self.inner
+            .outgoing_connection_flow_controller
+            .frame_bytes_sent()
+    }
+
     /// Accepts the next incoming stream of a given type
     pub fn poll_accept(
         &mut self,
@@ -519,7 +583,10 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 return Ok(None).into()
             }
             // Translate idle timer expiration to end of stream
-            Some(connection::Error::IdleTimerExpired { .. }) => return Ok(None).into(),
+            Some(connection::Error::Timeout {
+                reason: connection::ConnectionTimeoutReason::IdleTimeout { .. },
+                ..
+            }) => return Ok(None).into(),
             Some(reason) => return Err(reason).into(),
             None => {}
         }
@@ -624,9 +691,23 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
         );
     }
 
+    /// This method gets called when the congestion controller of the active path updates its
+    /// bandwidth estimate
+    pub fn on_bandwidth_estimate(&mut self, bandwidth_estimate: Option<Bandwidth>) {
+        self.inner
+            .incoming_connection_flow_controller
+            .on_bandwidth_estimate(bandwidth_estimate);
+    }
+
     /// This method gets called when the RTT estimate is updated for the active path
     pub fn on_rtt_update(&mut self, rtt_estimator: &RttEstimator) {
         let blocked_sync_period = self.blocked_sync_period(rtt_estimator);
+        self.inner
+            .incoming_connection_flow_controller
+            .on_rtt_update(rtt_estimator.smoothed_rtt());
+        if let Some(fairness_controller) = self.inner.fairness_controller.as_mut() {
+            fairness_controller.on_rtt_update(rtt_estimator.smoothed_rtt());
+        }
         self.inner
             .stream_controller
             .update_blocked_sync_period(blocked_sync_period);
@@ -645,6 +726,15 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
     /// Called when the connection timer expires
     pub fn on_timeout(&mut self, now: Timestamp) {
         self.inner.stream_controller.on_timeout(now);
+        if let Some(send_token_bucket) = self.inner.send_token_bucket.as_mut() {
+            send_token_bucket.on_timeout(now);
+        }
+        if let Some((_, unreserved_token_bucket)) = self.inner.unreserved_token_bucket.as_mut() {
+            unreserved_token_bucket.on_timeout(now);
+        }
+        if let Some(fairness_controller) = self.inner.fairness_controller.as_mut() {
+            fairness_controller.on_timeout(now);
+        }
         self.inner
             .outgoing_connection_flow_controller
             .on_timeout(now);
@@ -655,6 +745,16 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 StreamContainerIterationResult::Continue
             },
         );
+
+        if self.inner.deadlock_detector.on_timeout(now) {
+            let sample = self.inner.sample_potential_deadlock();
+            self.inner.deadlock_detector.on_sample(now, sample);
+        }
+    }
+
+    /// Returns a potential deadlock of the streams which should be reported, if any
+    pub fn poll_potential_deadlock(&mut self) -> Option<PotentialDeadlock> {
+        self.inner.deadlock_detector.poll()
     }
 
     /// Closes the [`AbstractStreamManager`] and resets all streams with the
@@ -705,34 +805,132 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
                 transmission::context::RetransmissionContext::new(context);
 
             // Prioritize retransmitting lost data
-            self.inner.streams.iterate_retransmission_list(
-                &mut self.inner.stream_controller,
-                |stream: &mut S| {
-                    transmit_result = stream.on_transmit(&mut retransmission_context);
-                    if transmit_result.is_err() {
-                        StreamContainerIterationResult::BreakAndInsertAtBack
-                    } else {
-                        StreamContainerIterationResult::Continue
-                    }
-                },
-            );
+            //
+            // Streams with a lost FIN are retransmitted in a first pass, since the peer can't
+            // complete those streams until the FIN arrives. The streams skipped in the first
+            // pass keep their relative order for the second pass.
+            for lost_fin_only in [true, false] {
+                self.inner.streams.iterate_retransmission_list(
+                    &mut self.inner.stream_controller,
+                    |stream: &mut S| {
+                        if lost_fin_only && !stream.has_lost_fin() {
+                            return StreamContainerIterationResult::Continue;
+                        }
+
+                        transmit_result = stream.on_transmit(&mut retransmission_context);
+                        if transmit_result.is_err() {
+                            StreamContainerIterationResult::BreakAndInsertAtBack
+                        } else {
+                            StreamContainerIterationResult::Continue
+                        }
+                    },
+                );
 
-            // return if there were any errors
-            transmit_result?;
+                // return if there were any errors
+                transmit_result?;
+            }
         }
 
         if context.transmission_constraint().can_transmit() {
-            self.inner.streams.iterate_transmission_list(
-                &mut self.inner.stream_controller,
-                |stream: &mut S| {
-                    transmit_result = stream.on_transmit(context);
-                    if transmit_result.is_err() {
-                        StreamContainerIterationResult::BreakAndInsertAtBack
+            let initial_capacity = context.remaining_capacity();
+
+            // New data written by all of the streams draws from the shared send token bucket
+            let send_limit = match self.inner.send_token_bucket.as_mut() {
+                Some(send_token_bucket) => {
+                    send_token_bucket.refill(context.current_time());
+                    if send_token_bucket.can_send() {
+                        send_token_bucket.tokens().try_into().unwrap_or(usize::MAX)
                     } else {
-                        StreamContainerIterationResult::Continue
+                        0
                     }
-                },
-            );
+                }
+                None => usize::MAX,
+            };
+
+            // New data is held back while the peer is owed its share of the connection
+            let send_limit = match self.inner.fairness_controller.as_mut() {
+                Some(fairness_controller) => {
+                    fairness_controller.update_window(context.current_time());
+                    if fairness_controller.can_send() {
+                        send_limit
+                    } else {
+                        0
+                    }
+                }
+                None => send_limit,
+            };
+
+            if send_limit > 0 {
+                let mut rate_limited_context =
+                    transmission::context::RateLimitedContext::new(context, send_limit);
+
+                // Streams below the reserved priority only draw from the unreserved bandwidth
+                let mut unreserved_token_bucket = self.inner.unreserved_token_bucket.as_mut();
+                if let Some((_, unreserved_token_bucket)) = unreserved_token_bucket.as_mut() {
+                    unreserved_token_bucket.refill(rate_limited_context.current_time());
+                }
+
+                // Each stream writes as much as its flow control limits allow, after which the
+                // next stream continues filling the same packet. This batches frames from many
+                // small streams into a single packet rather than sending one packet per stream.
+                self.inner.streams.iterate_transmission_list(
+                    &mut self.inner.stream_controller,
+                    |stream: &mut S| {
+                        match unreserved_token_bucket.as_mut() {
+                            Some((for_priority, unreserved_token_bucket))
+                                if stream.priority() < *for_priority =>
+                            {
+                                // the stream stays in the transmission list until the bucket
+                                // is refilled
+                                if !unreserved_token_bucket.can_send() {
+                                    return StreamContainerIterationResult::Continue;
+                                }
+
+                                let limit = unreserved_token_bucket
+                                    .tokens()
+                                    .try_into()
+                                    .unwrap_or(usize::MAX);
+                                let initial_capacity = rate_limited_context.remaining_capacity();
+                                transmit_result = stream.on_transmit(
+                                    &mut transmission::context::RateLimitedContext::new(
+                                        &mut rate_limited_context,
+                                        limit,
+                                    ),
+                                );
+                                let written = initial_capacity
+                                    .saturating_sub(rate_limited_context.remaining_capacity());
+                                unreserved_token_bucket.on_send(written as u64);
+                            }
+                            _ => {
+                                transmit_result = stream.on_transmit(&mut rate_limited_context);
+                            }
+                        }
+
+                        if transmit_result.is_err() {
+                            StreamContainerIterationResult::BreakAndInsertAtBack
+                        } else {
+                            StreamContainerIterationResult::Continue
+                        }
+                    },
+                );
+            }
+
+            if let Some(send_token_bucket) = self.inner.send_token_bucket.as_mut() {
+                let written = initial_capacity.saturating_sub(context.remaining_capacity());
+                send_token_bucket.on_send(written as u64);
+            }
+
+            if let Some(fairness_controller) = self.inner.fairness_controller.as_mut() {
+                let written = initial_capacity.saturating_sub(context.remaining_capacity());
+                fairness_controller.on_send(context.current_time(), written as u64);
+            }
+        }
+
+        // Start sampling the streams for a potential deadlock once a stream is blocked
+        if self.inner.streams.has_flow_control_blocked_streams() {
+            self.inner
+                .deadlock_detector
+                .on_blocked(context.current_time());
         }
 
         // There is no `finalize_done_streams` here, since we do not expect to
@@ -808,7 +1006,13 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
     /// a stream
     pub fn on_data(&mut self, frame: &StreamRef) -> Result<(), transport::Error> {
         let stream_id = StreamId::from_varint(frame.stream_id);
-        self.handle_stream_frame(stream_id, |stream, events| stream.on_data(frame, events))
+        self.handle_stream_frame(stream_id, |stream, events| stream.on_data(frame, events))?;
+
+        if let Some(fairness_controller) = self.inner.fairness_controller.as_mut() {
+            fairness_controller.on_receive(frame.data.len() as u64);
+        }
+
+        Ok(())
     }
 
     /// This is called when a `DATA_BLOCKED` frame had been received
@@ -964,10 +1168,156 @@ impl<S: StreamTrait> AbstractStreamManager<S> {
         )
     }
 
+    /// Transfers `amount` of unused receive credits from the `from` stream to the `to` stream
+    ///
+    /// The window which `from` maintains towards the peer shrinks by `amount`, while the
+    /// window of `to` grows by `amount`. The new window of `to` is sent to the peer in a
+    /// `MAX_STREAM_DATA` frame.
+    pub fn donate_credits(
+        &mut self,
+        from: StreamId,
+        to: StreamId,
+        amount: u64,
+        api_call_context: &mut ConnectionApiCallContext,
+    ) -> Result<(), StreamError> {
+        if from == to {
+            return Err(StreamError::invalid_stream());
+        }
+
+        let amount = u32::try_from(amount).map_err(|_| StreamError::insufficient_credits())?;
+
+        // Make sure the recipient can use the credits before taking them from the donor
+        let can_accept = self.perform_api_call(
+            to,
+            Err(StreamError::invalid_stream()),
+            api_call_context,
+            |stream| Ok(stream.can_accept_receive_credits()),
+        )?;
+
+        if !can_accept {
+            return Err(StreamError::non_readable());
+        }
+
+        self.perform_api_call(
+            from,
+            Err(StreamError::invalid_stream()),
+            api_call_context,
+            |stream| stream.withdraw_receive_credits(amount),
+        )?;
+
+        self.perform_api_call(
+            to,
+            Err(StreamError::invalid_stream()),
+            api_call_context,
+            |stream| {
+                stream.deposit_receive_credits(amount);
+                Ok(())
+            },
+        )
+    }
+
+    /// Limits the rate at which all streams on the connection combined send new data
+    ///
+    /// Passing [`Bandwidth::MAX`] removes the limit.
+    pub fn set_send_bandwidth(&mut self, bandwidth: Bandwidth) {
+        if bandwidth == Bandwidth::MAX {
+            self.inner.send_token_bucket = None;
+        } else if let Some(send_token_bucket) = self.inner.send_token_bucket.as_mut() {
+            send_token_bucket.set_rate(bandwidth);
+        } else {
+            self.inner.send_token_bucket = Some(ConnectionTokenBucket::new(bandwidth));
+        }
+    }
+
+    /// Updates the bandwidth reserved by the congestion controller for higher priority streams
+    ///
+    /// Streams below the reserved priority share the unreserved bandwidth, while streams at or
+    /// above it may use the full sending rate of the connection. Passing `None` removes the limit.
+    pub fn on_reserved_bandwidth(&mut self, reserved_bandwidth: Option<ReservedBandwidth>) {
+        let reserved_bandwidth = if let Some(reserved_bandwidth) = reserved_bandwidth {
+            reserved_bandwidth
+        } else {
+            self.inner.unreserved_token_bucket = None;
+            return;
+        };
+
+        if let Some((for_priority, unreserved_token_bucket)) =
+            self.inner.unreserved_token_bucket.as_mut()
+        {
+            *for_priority = reserved_bandwidth.for_priority;
+            unreserved_token_bucket.set_rate(reserved_bandwidth.unreserved);
+        } else {
+            self.inner.unreserved_token_bucket = Some((
+                reserved_bandwidth.for_priority,
+                ConnectionTokenBucket::new(reserved_bandwidth.unreserved),
+            ));
+        }
+    }
+
+    /// Changes the priority of the stream with the given ID
+    pub fn set_stream_priority(
+        &mut self,
+        stream_id: StreamId,
+        priority: StreamPriority,
+        api_call_context: &mut ConnectionApiCallContext,
+    ) -> Result<(), StreamError> {
+        self.perform_api_call(
+            stream_id,
+            Err(StreamError::invalid_stream()),
+            api_call_context,
+            |stream| {
+                stream.set_priority(priority);
+                Ok(())
+            },
+        )
+    }
+
     /// Returns whether or not streams have data to send
     pub fn has_pending_streams(&self) -> bool {
         self.inner.streams.has_pending_streams()
     }
+
+    /// Returns the number of bytes which can be received before the receive budget is used up
+    pub fn recv_budget_remaining(&self) -> u64 {
+        self.inner
+            .incoming_connection_flow_controller
+            .recv_budget_remaining()
+    }
+
+    /// Returns the transfer counters of all of the streams which have been closed
+    pub fn stream_metrics(&self) -> StreamMetricsTotals {
+        self.inner.streams.metrics()
+    }
+
+    /// Resets the transfer counters of all of the streams which have been closed
+    ///
+    /// Streams which are still open keep their counters, which are added to the new totals
+    /// once they close.
+    pub fn reset_stream_metrics(&mut self) {
+        self.inner.streams.reset_metrics()
+    }
+
+    /// Returns the write amplification of the stream with the given ID
+    ///
+    /// Only the STREAM frames of the stream are counted as transmitted bytes, since the packet
+    /// headers are shared with the other frames in each packet.
+    pub fn stream_write_amplification(
+        &mut self,
+        stream_id: StreamId,
+        api_call_context: &mut ConnectionApiCallContext,
+    ) -> Result<WriteAmplification, StreamError> {
+        self.perform_api_call(
+            stream_id,
+            Err(StreamError::invalid_stream()),
+            api_call_context,
+            |stream| Ok(stream.metrics().load().write_amplification()),
+        )
+    }
+
+    /// Returns the number of streams which have not been finalized yet
+    pub fn active_stream_count(&self) -> usize {
+        self.inner.streams.nr_active_streams()
+    }
 }
 
 impl<S: StreamTrait> timer::Provider for AbstractStreamManager<S> {
@@ -977,7 +1327,17 @@ impl<S: StreamTrait> timer::Provider for AbstractStreamManager<S> {
         self.inner
             .outgoing_connection_flow_controller
             .timers(query)?;
+        if let Some(send_token_bucket) = self.inner.send_token_bucket.as_ref() {
+            send_token_bucket.timers(query)?;
+        }
+        if let Some((_, unreserved_token_bucket)) = self.inner.unreserved_token_bucket.as_ref() {
+            unreserved_token_bucket.timers(query)?;
+        }
+        if let Some(fairness_controller) = self.inner.fairness_controller.as_ref() {
+            fairness_controller.timers(query)?;
+        }
         self.inner.streams.timers(query)?;
+        self.inner.deadlock_detector.timers(query)?;
         Ok(())
     }
 }
@@ -988,7 +1348,35 @@ impl<S: StreamTrait> transmission::interest::Provider for AbstractStreamManager<
         &self,
         query: &mut Q,
     ) -> transmission::interest::Result {
-        self.inner.streams.transmission_interest(query)?;
+        // Only lost data can be sent while the send token bucket is empty or the fairness
+        // controller is holding back new data
+        let can_send = self
+            .inner
+            .send_token_bucket
+            .as_ref()
+            .map_or(true, |send_token_bucket| send_token_bucket.can_send())
+            && self
+                .inner
+                .fairness_controller
+                .as_ref()
+                .map_or(true, |fairness_controller| fairness_controller.can_send());
+
+        if can_send {
+            match self.inner.unreserved_token_bucket.as_ref() {
+                // Only streams which may use the reserved bandwidth can send new data while the
+                // unreserved bandwidth is used up
+                Some((for_priority, unreserved_token_bucket))
+                    if !unreserved_token_bucket.can_send() =>
+                {
+                    self.inner
+                        .streams
+                        .priority_transmission_interest(*for_priority, query)?;
+                }
+                _ => self.inner.streams.transmission_interest(query)?,
+            }
+        } else {
+            self.inner.streams.retransmission_interest(query)?;
+        }
         self.inner.stream_controller.transmission_interest(query)?;
         self.inner
             .incoming_connection_flow_controller
@@ -39,7 +39,7 @@ use s2n_quic_core::{
         StopSending, Stream as StreamFrame, StreamDataBlocked, StreamsBlocked,
     },
     packet::number::{PacketNumberRange, PacketNumberSpace},
-    stream::{ops, StreamId, StreamType},
+    stream::{ops, StreamId, StreamMetrics, StreamMetricsTotals, StreamPriority, StreamType},
     time::{
         timer::{self, Provider as _},
         Timestamp,
@@ -71,12 +71,14 @@ struct MockStream {
     on_transmit_try_write_frames: usize,
     on_transmit_count: usize,
     on_transmit_limit: Option<usize>,
+    on_transmit_stream_data_len: Option<usize>,
     on_data_count: usize,
     on_reset_count: usize,
     on_stream_data_blocked_count: usize,
     on_stop_sending_count: usize,
     on_max_stream_data_count: usize,
     lost_data: bool,
+    lost_fin: bool,
     set_finalize_on_internal_reset: bool,
     next_packet_error: Option<TransportError>,
     next_api_error: Option<StreamError>,
@@ -86,6 +88,11 @@ struct MockStream {
     poll_push_count: usize,
     poll_finish_count: usize,
     reset_count: usize,
+    receive_credits: u32,
+    accepts_receive_credits: bool,
+    unread_len: u64,
+    metrics: StreamMetrics,
+    priority: StreamPriority,
 }
 
 impl MockStream {
@@ -147,7 +154,9 @@ impl StreamTrait for MockStream {
             on_transmit_count: 0,
             on_transmit_try_write_frames: 0,
             on_transmit_limit: None,
+            on_transmit_stream_data_len: None,
             lost_data: false,
+            lost_fin: false,
             set_finalize_on_internal_reset: false,
             next_packet_error: None,
             next_api_error: None,
@@ -159,6 +168,11 @@ impl StreamTrait for MockStream {
             poll_push_count: 0,
             poll_finish_count: 0,
             reset_count: 0,
+            receive_credits: 0,
+            accepts_receive_credits: true,
+            unread_len: 0,
+            metrics: StreamMetrics::default(),
+            priority: StreamPriority::default(),
         }
     }
 
@@ -177,6 +191,7 @@ impl StreamTrait for MockStream {
         if let Some(err) = self.next_packet_error {
             return Err(err);
         };
+        self.metrics.on_bytes_received(frame.data.len());
         Ok(())
     }
 
@@ -277,19 +292,33 @@ impl StreamTrait for MockStream {
             .on_transmit_try_write_frames
             .min(self.on_transmit_limit.unwrap_or(usize::MAX));
         for _ in 0..count {
-            // We write simple frames here, since the tests do not care about
-            // the content but only the number of succeeded write calls.
-            let _pn = context
-                .write_frame(&MaxData {
+            let _pn = if let Some(len) = self.on_transmit_stream_data_len {
+                let data = vec![0u8; len];
+                context.write_frame(&StreamRef {
+                    stream_id: self.config.stream_id.into(),
+                    offset: VarInt::from_u8(0),
+                    is_last_frame: false,
+                    is_fin: false,
+                    data: &data[..],
+                })
+            } else {
+                // We write simple frames here, since most tests do not care about
+                // the content but only the number of succeeded write calls.
+                context.write_frame(&MaxData {
                     maximum_data: VarInt::from_u32(0),
                 })
-                .ok_or(OnTransmitError::CouldNotWriteFrame)?;
+            }
+            .ok_or(OnTransmitError::CouldNotWriteFrame)?;
             self.on_transmit_try_write_frames -= 1;
         }
 
         Ok(())
     }
 
+    fn has_lost_fin(&self) -> bool {
+        self.lost_fin
+    }
+
     fn on_connection_window_available(&mut self) {
         self.on_connection_window_available_count += 1;
         let acquired_window = self
@@ -346,6 +375,39 @@ impl StreamTrait for MockStream {
 
         Ok(response)
     }
+
+    fn can_accept_receive_credits(&self) -> bool {
+        self.accepts_receive_credits
+    }
+
+    fn withdraw_receive_credits(&mut self, amount: u32) -> Result<(), StreamError> {
+        self.receive_credits = self
+            .receive_credits
+            .checked_sub(amount)
+            .ok_or_else(StreamError::insufficient_credits)?;
+        Ok(())
+    }
+
+    fn deposit_receive_credits(&mut self, amount: u32) {
+        self.receive_credits += amount;
+        self.on_transmit_try_write_frames = 1;
+    }
+
+    fn metrics(&self) -> &StreamMetrics {
+        &self.metrics
+    }
+
+    fn priority(&self) -> StreamPriority {
+        self.priority
+    }
+
+    fn set_priority(&mut self, priority: StreamPriority) {
+        self.priority = priority;
+    }
+
+    fn unread_len(&self) -> u64 {
+        self.unread_len
+    }
 }
 
 impl timer::Provider for MockStream {
@@ -585,6 +647,56 @@ fn returns_finalization_interest_after_last_stream_is_drained() {
     assert!(manager.finalization_status().is_final());
 }
 
+#[test]
+fn stream_metrics_are_committed_when_streams_are_finalized() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    let stream_1 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_2 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    for stream_id in [stream_1, stream_2] {
+        assert_eq!(
+            Ok(()),
+            manager.on_data(&stream_data(
+                stream_id,
+                VarInt::from_u32(0),
+                &[1u8; 10][..],
+                false
+            ))
+        );
+        manager.with_asserted_stream(stream_id, |stream| {
+            stream.metrics.on_frame_sent(100, 105, false);
+            stream.metrics.on_frame_sent(50, 55, true);
+        });
+    }
+
+    // streams are only counted once they are closed
+    assert_eq!(StreamMetricsTotals::default(), manager.stream_metrics());
+
+    manager.with_asserted_stream(stream_1, |stream| {
+        stream.interests.retained = false;
+    });
+    assert_eq!(
+        StreamMetricsTotals {
+            bytes_sent: 150,
+            bytes_received: 10,
+            retransmitted_bytes: 50,
+            frames_sent: 2,
+            frame_bytes_sent: 160,
+        },
+        manager.stream_metrics()
+    );
+
+    manager.with_asserted_stream(stream_2, |stream| {
+        stream.interests.retained = false;
+    });
+    let totals = manager.stream_metrics();
+    assert_eq!(300, totals.bytes_sent);
+    assert_eq!(20, totals.bytes_received);
+    assert_eq!(100, totals.retransmitted_bytes);
+    assert_eq!(4, totals.frames_sent);
+}
+
 #[test]
 fn remote_messages_which_target_locally_initiated_unopened_streams_error() {
     for initiator_type in &[endpoint::Type::Server, endpoint::Type::Client] {
@@ -1811,6 +1923,59 @@ fn add_and_remove_streams_from_on_connection_window_lists() {
         .is_empty());
 }
 
+#[test]
+fn potential_deadlock_is_reported_once() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+    let timeout = ConnectionLimits::default().deadlock_detection_timeout();
+
+    // the application writes to one stream until it's blocked by the peer, while it never reads
+    // the data the peer sent on another stream
+    let blocked_stream = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let unread_stream = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    manager.with_asserted_stream(blocked_stream, |stream| {
+        stream.interests.stream_flow_control_credits = true;
+    });
+    manager.with_asserted_stream(unread_stream, |stream| {
+        stream.unread_len = 100;
+    });
+
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    let mut write_context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+    assert!(manager.on_transmit(&mut write_context).is_ok());
+    let start = write_context.current_time;
+
+    fn run_until(manager: &mut AbstractStreamManager<MockStream>, end: Timestamp) {
+        while let Some(expiration) = manager.next_expiration().filter(|time| *time <= end) {
+            manager.on_timeout(expiration);
+        }
+    }
+
+    run_until(&mut manager, start + timeout);
+    assert_eq!(None, manager.poll_potential_deadlock());
+
+    run_until(&mut manager, start + timeout * 2);
+    let deadlock = manager.poll_potential_deadlock().unwrap();
+    assert_eq!(
+        vec![blocked_stream.as_varint().as_u64()],
+        deadlock.blocked_stream_ids
+    );
+    assert_eq!(
+        vec![unread_stream.as_varint().as_u64()],
+        deadlock.unread_stream_ids
+    );
+    assert_eq!(vec![100], deadlock.unread_lens);
+    assert!(deadlock.duration >= timeout);
+
+    run_until(&mut manager, start + timeout * 4);
+    assert_eq!(None, manager.poll_potential_deadlock());
+}
+
 #[test]
 fn max_data_causes_on_connection_window_available_to_be_called_on_streams() {
     fn assert_connection_window_state(
@@ -2201,6 +2366,50 @@ fn add_and_remove_streams_from_transmission_lists() {
     assert!(manager.streams_waiting_for_transmission().is_empty());
 }
 
+#[test]
+fn transmission_list_is_ordered_by_priority() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    let stream_1 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_2 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let stream_3 = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    for stream_id in &[stream_1, stream_2, stream_3] {
+        manager.with_asserted_stream(*stream_id, |stream| {
+            stream.on_transmit_try_write_frames = 1;
+        });
+    }
+
+    // streams with a higher priority are polled first
+    manager.with_asserted_stream(stream_3, |stream| {
+        stream.set_priority(StreamPriority::Critical);
+    });
+    manager.with_asserted_stream(stream_1, |stream| {
+        stream.set_priority(StreamPriority::Low);
+    });
+    assert_eq!(
+        [stream_3, stream_2, stream_1],
+        *manager.streams_waiting_for_transmission()
+    );
+
+    // a stream which changes its priority moves to the back of the streams with that priority
+    manager.with_asserted_stream(stream_1, |stream| {
+        stream.set_priority(StreamPriority::Critical);
+    });
+    assert_eq!(
+        [stream_3, stream_1, stream_2],
+        *manager.streams_waiting_for_transmission()
+    );
+
+    manager.with_asserted_stream(stream_3, |stream| {
+        stream.on_transmit_try_write_frames = 0;
+    });
+    assert_eq!(
+        [stream_1, stream_2],
+        *manager.streams_waiting_for_transmission()
+    );
+}
+
 #[test]
 fn add_and_remove_streams_from_retransmission_lists() {
     let mut manager = create_stream_manager(endpoint::Type::Server);
@@ -2594,6 +2803,44 @@ fn forwards_on_rtt_update() {
     });
 }
 
+#[test]
+fn forwards_on_bandwidth_estimate() {
+    let initial_local_limits = create_default_initial_flow_control_limits();
+    let initial_window = initial_local_limits.max_data.as_u64();
+    let max_data_window = initial_window * 4;
+    let limits = ConnectionLimits::default()
+        .with_max_data_window(max_data_window)
+        .unwrap();
+    let mut manager = AbstractStreamManager::<MockStream>::new(
+        &limits,
+        endpoint::Type::Server,
+        initial_local_limits,
+        create_default_initial_flow_control_limits(),
+    );
+
+    let receive_window = |manager: &AbstractStreamManager<MockStream>| {
+        manager
+            .inner
+            .incoming_connection_flow_controller
+            .current_receive_window()
+            .as_u64()
+    };
+    assert_eq!(receive_window(&manager), initial_window);
+
+    manager.on_rtt_update(&RttEstimator::new(Duration::from_millis(100)));
+    assert_eq!(receive_window(&manager), initial_window);
+
+    // the window covering 2 RTTs at 1 MB/s is larger than the initial window
+    let bandwidth = Bandwidth::new(1_000_000, Duration::from_secs(1));
+    manager.on_bandwidth_estimate(Some(bandwidth));
+    assert_eq!(receive_window(&manager), 200_000);
+
+    // the window is clamped to the configured maximum
+    let bandwidth = Bandwidth::new(1_000_000_000, Duration::from_secs(1));
+    manager.on_bandwidth_estimate(Some(bandwidth));
+    assert_eq!(receive_window(&manager), max_data_window);
+}
+
 #[test]
 fn forwards_on_max_stream_data() {
     let mut manager = create_stream_manager(endpoint::Type::Server);
@@ -2810,6 +3057,103 @@ fn forwards_poll_pop() {
     );
 }
 
+#[test]
+fn donate_credits() {
+    let (mut wakeup_queue, wakeup_handle) = create_wakeup_queue_and_handle();
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    let donor = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+    let recipient = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    manager.with_asserted_stream(donor, |stream| {
+        stream.receive_credits = 1000;
+    });
+
+    assert!(manager.get_transmission_interest().is_none());
+    assert!(manager
+        .donate_credits(
+            donor,
+            recipient,
+            400,
+            &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+        )
+        .is_ok());
+
+    manager.with_asserted_stream(donor, |stream| {
+        assert_eq!(600, stream.receive_credits);
+    });
+    manager.with_asserted_stream(recipient, |stream| {
+        assert_eq!(400, stream.receive_credits);
+    });
+
+    // The recipient needs to synchronize its new window with the peer
+    assert_eq!(
+        transmission::Interest::NewData,
+        manager.get_transmission_interest()
+    );
+    assert_wakeups(&mut wakeup_queue, 1);
+
+    // The donor does not have enough unused credits
+    assert_matches!(
+        manager.donate_credits(
+            donor,
+            recipient,
+            601,
+            &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+        ),
+        Err(StreamError::InsufficientCredits { .. }),
+    );
+    assert_matches!(
+        manager.donate_credits(
+            donor,
+            recipient,
+            u64::MAX,
+            &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+        ),
+        Err(StreamError::InsufficientCredits { .. }),
+    );
+
+    // Credits are not withdrawn if the recipient is not able to use them
+    manager.with_asserted_stream(recipient, |stream| {
+        stream.accepts_receive_credits = false;
+    });
+    assert_matches!(
+        manager.donate_credits(
+            donor,
+            recipient,
+            100,
+            &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+        ),
+        Err(StreamError::NonReadable { .. }),
+    );
+    manager.with_asserted_stream(donor, |stream| {
+        assert_eq!(600, stream.receive_credits);
+    });
+    manager.with_asserted_stream(recipient, |stream| {
+        stream.accepts_receive_credits = true;
+    });
+
+    // Check invalid stream IDs
+    for (from, to) in [
+        (donor, invalid_stream_id(endpoint::Type::Server)),
+        (invalid_stream_id(endpoint::Type::Server), recipient),
+        (donor, donor),
+    ] {
+        assert_matches!(
+            manager.donate_credits(
+                from,
+                to,
+                100,
+                &mut ConnectionApiCallContext::from_wakeup_handle(&wakeup_handle),
+            ),
+            Err(StreamError::InvalidStream { .. }),
+        );
+    }
+    manager.with_asserted_stream(donor, |stream| {
+        assert_eq!(600, stream.receive_credits);
+    });
+}
+
 #[test]
 fn forwards_stop_sending() {
     let (mut wakeup_queue, wakeup_handle) = create_wakeup_queue_and_handle();
@@ -3025,6 +3369,52 @@ fn forwards_reset() {
     );
 }
 
+#[test]
+fn lost_fin_is_retransmitted_first() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    let streams: Vec<_> = (0..3)
+        .map(|_| try_open(&mut manager, StreamType::Bidirectional).unwrap())
+        .collect();
+
+    for stream_id in &streams {
+        manager.with_asserted_stream(*stream_id, |stream| {
+            stream.on_transmit_try_write_frames = 1;
+            stream.lost_data = true;
+        });
+    }
+    manager.with_asserted_stream(streams[2], |stream| {
+        stream.lost_fin = true;
+    });
+    assert_eq!(streams, manager.streams_waiting_for_retransmission());
+
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    frame_buffer.set_error_write_after_n_frames(1);
+    let mut write_context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        &mut frame_buffer,
+        transmission::Constraint::RetransmissionOnly,
+        transmission::Mode::LossRecoveryProbing,
+        endpoint::Type::Server,
+    );
+
+    // only a single frame fits, which goes to the stream with the lost FIN
+    assert_eq!(
+        Err(OnTransmitError::CouldNotWriteFrame),
+        manager.on_transmit(&mut write_context)
+    );
+    manager.with_asserted_stream(streams[2], |stream| {
+        assert_eq!(1, stream.on_transmit_count);
+        assert_eq!(0, stream.on_transmit_try_write_frames);
+    });
+
+    // the remaining streams keep their order
+    assert_eq!(
+        [streams[0], streams[1]],
+        *manager.streams_waiting_for_retransmission()
+    );
+}
+
 #[test]
 fn stream_transmission_fairness_test() {
     for concurrent_streams in 2..=5 {
@@ -3072,3 +3462,185 @@ fn stream_transmission_fairness_test() {
         }
     }
 }
+
+#[test]
+fn on_transmit_fills_packet_with_frames_from_multiple_streams() {
+    let mut manager = create_stream_manager(endpoint::Type::Server);
+
+    // Create 10 streams which each have a small amount of data to send
+    let streams: Vec<_> = (0..10)
+        .map(|_| try_open(&mut manager, StreamType::Bidirectional).unwrap())
+        .collect();
+
+    for stream_id in &streams {
+        manager.with_asserted_stream(*stream_id, |stream| {
+            stream.on_transmit_try_write_frames = 1;
+            stream.on_transmit_stream_data_len = Some(100);
+        });
+    }
+
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    frame_buffer.set_max_packet_size(Some(1500));
+    let mut write_context = MockWriteContext::new(
+        s2n_quic_platform::time::now(),
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+
+    assert!(manager.on_transmit(&mut write_context).is_ok());
+    assert!(manager.streams_waiting_for_transmission().is_empty());
+
+    // All of the streams are written to the same packet
+    assert_eq!(streams.len(), write_context.frame_buffer.len());
+    let packet_number = write_context.frame_buffer.frames[0].packet_nr;
+    for stream_id in &streams {
+        let mut frame = write_context.frame_buffer.pop_front().unwrap();
+        assert_eq!(packet_number, frame.packet_nr);
+        match frame.as_frame() {
+            Frame::Stream(frame) => assert_eq!(VarInt::from(*stream_id), frame.stream_id),
+            frame => panic!("unexpected frame {:?}", frame),
+        }
+    }
+}
+
+#[test]
+fn reserved_bandwidth_test() {
+    const FRAME_LEN: usize = 1000;
+    const PACKETS_PER_MS: usize = 4;
+    const CRITICAL_PACKETS_PER_MS: usize = 2;
+    const DURATION_MS: u64 = 100;
+
+    let initial_local_limits = create_default_initial_flow_control_limits();
+    let initial_peer_limits = create_default_initial_flow_control_limits();
+    let limits = ConnectionLimits::default();
+    let mut manager = AbstractStreamManager::<MockStream>::new(
+        &limits,
+        endpoint::Type::Server,
+        initial_local_limits,
+        initial_peer_limits,
+    );
+
+    // two lower priority streams are opened before the critical stream
+    let normal_streams = [
+        try_open(&mut manager, StreamType::Bidirectional).unwrap(),
+        try_open(&mut manager, StreamType::Bidirectional).unwrap(),
+    ];
+    let critical_stream = try_open(&mut manager, StreamType::Bidirectional).unwrap();
+
+    for stream_id in normal_streams.iter().chain(Some(&critical_stream)) {
+        manager.with_asserted_stream(*stream_id, |stream| {
+            stream.on_transmit_try_write_frames = usize::MAX;
+            stream.on_transmit_stream_data_len = Some(FRAME_LEN);
+            stream.on_transmit_limit = Some(1);
+        });
+    }
+    manager.with_asserted_stream(critical_stream, |stream| {
+        stream.set_priority(StreamPriority::Critical);
+    });
+
+    // the congestion controller leaves 1.2MB/s for the lower priority streams
+    let unreserved = Bandwidth::new(1_200_000, Duration::from_secs(1));
+    manager.on_reserved_bandwidth(Some(ReservedBandwidth {
+        for_priority: StreamPriority::Critical,
+        unreserved,
+    }));
+
+    let mut frame_buffer = OutgoingFrameBuffer::new();
+    frame_buffer.set_max_packet_size(Some(1500));
+    let start = s2n_quic_platform::time::now();
+    let mut write_context = MockWriteContext::new(
+        start,
+        &mut frame_buffer,
+        transmission::Constraint::None,
+        transmission::Mode::Normal,
+        endpoint::Type::Server,
+    );
+
+    let mut normal_bytes = 0;
+    let mut critical_bytes = 0;
+
+    for ms in 0..DURATION_MS {
+        write_context.current_time = start + Duration::from_millis(ms);
+        manager.on_timeout(write_context.current_time);
+
+        // the critical stream only needs part of the link, which leaves the rest for the lower
+        // priority streams
+        manager.with_asserted_stream(critical_stream, |stream| {
+            stream.on_transmit_try_write_frames = CRITICAL_PACKETS_PER_MS;
+        });
+
+        // the link is saturated by sending a packet each time the streams are polled
+        for _ in 0..PACKETS_PER_MS {
+            // the stream after the one filling the packet fails to write its frame
+            let _ = manager.on_transmit(&mut write_context);
+            write_context.frame_buffer.flush();
+        }
+
+        while let Some(mut frame) = write_context.frame_buffer.pop_front() {
+            match frame.as_frame() {
+                Frame::Stream(frame) if frame.stream_id == VarInt::from(critical_stream) => {
+                    critical_bytes += frame.data.len();
+                }
+                Frame::Stream(frame) => {
+                    assert!(normal_streams
+                        .iter()
+                        .any(|stream_id| frame.stream_id == VarInt::from(*stream_id)));
+                    normal_bytes += frame.data.len();
+                }
+                frame => panic!("unexpected frame {:?}", frame),
+            }
+        }
+    }
+
+    let elapsed = Duration::from_millis(DURATION_MS);
+
+    // the lower priority streams are limited to the unreserved bandwidth, plus the initial burst
+    // of the token bucket
+    let burst = (unreserved * s2n_quic_core::connection::token_bucket::MAX_BURST_DURATION) as usize;
+    let max_normal_bytes = (unreserved * elapsed) as usize + burst + FRAME_LEN;
+    assert!(normal_bytes > 0);
+    assert!(
+        normal_bytes <= max_normal_bytes,
+        "{} > {}",
+        normal_bytes,
+        max_normal_bytes
+    );
+
+    // the critical stream is polled before the lower priority streams, so it always gets to send
+    // its data
+    let critical_packets = CRITICAL_PACKETS_PER_MS * DURATION_MS as usize;
+    assert_eq!(FRAME_LEN * critical_packets, critical_bytes);
+
+    // only the critical stream is interested in sending new data while the bucket is empty
+    manager.on_reserved_bandwidth(Some(ReservedBandwidth {
+        for_priority: StreamPriority::Critical,
+        unreserved: Bandwidth::ZERO,
+    }));
+    assert_eq!(
+        transmission::Interest::None,
+        manager.get_transmission_interest()
+    );
+    manager.with_asserted_stream(critical_stream, |stream| {
+        stream.on_transmit_try_write_frames = 1;
+    });
+    assert_eq!(
+        transmission::Interest::NewData,
+        manager.get_transmission_interest()
+    );
+    manager.with_asserted_stream(critical_stream, |stream| {
+        stream.on_transmit_try_write_frames = 0;
+    });
+    assert_eq!(
+        transmission::Interest::None,
+        manager.get_transmission_interest()
+    );
+
+    // lower priority streams share the full sending rate once nothing is reserved
+    manager.on_reserved_bandwidth(None);
+    assert_eq!(
+        transmission::Interest::NewData,
+        manager.get_transmission_interest()
+    );
+}
@@ -4,10 +4,14 @@
 //! This module contains the implementation of QUIC `Streams` and their management
 
 mod api;
+mod auto_scale_max_data;
+mod blocked_event;
 mod controller;
+mod deadlock_detector;
 mod incoming_connection_flow_controller;
 mod manager;
 mod outgoing_connection_flow_controller;
+mod receive_budget;
 mod receive_stream;
 mod send_stream;
 mod stream_container;
@@ -20,6 +24,7 @@ pub(crate) mod contract;
 
 pub use api::*;
 pub use controller::Controller;
+pub use deadlock_detector::PotentialDeadlock;
 pub use manager::AbstractStreamManager;
 pub use s2n_quic_core::stream::limits::Limits;
 pub use stream_events::StreamEvents;
@@ -3,6 +3,7 @@
 
 use crate::{
     contexts::{OnTransmitError, WriteContext},
+    stream::blocked_event::{BlockedEvent, Transition},
     sync::{PeriodicSync, ValueToFrameWriter},
     transmission,
 };
@@ -29,6 +30,10 @@ struct OutgoingConnectionFlowControllerImpl {
     available_window: VarInt,
     /// For periodically sending `DATA_BLOCKED` frames when blocked by peer limits
     data_blocked_sync: PeriodicSync<VarInt, DataBlockedToFrameWriter>,
+    /// For notifying the application when the connection becomes blocked by peer limits
+    blocked_event: BlockedEvent,
+    /// The number of bytes of STREAM frames which were written, including retransmissions
+    frame_bytes_sent: u64,
 }
 
 impl OutgoingConnectionFlowControllerImpl {
@@ -37,6 +42,8 @@ impl OutgoingConnectionFlowControllerImpl {
             total_available_window: initial_window_size,
             available_window: initial_window_size,
             data_blocked_sync: PeriodicSync::new(),
+            blocked_event: BlockedEvent::default(),
+            frame_bytes_sent: 0,
         }
     }
 
@@ -62,6 +69,7 @@ impl OutgoingConnectionFlowControllerImpl {
             //# control; see Section 4.
             self.data_blocked_sync
                 .request_delivery(self.total_available_window);
+            self.blocked_event.on_blocked(self.total_available_window);
         }
 
         result
@@ -81,6 +89,7 @@ impl OutgoingConnectionFlowControllerImpl {
 
         // We now have more capacity from the peer so stop sending DATA_BLOCKED frames
         self.data_blocked_sync.stop_sync();
+        self.blocked_event.on_unblocked(self.total_available_window);
     }
 }
 
@@ -135,6 +144,17 @@ impl OutgoingConnectionFlowController {
         inner.total_available_window - inner.available_window
     }
 
+    /// Returns the number of bytes of STREAM frames which were written on all
+    /// streams, including retransmissions
+    pub fn frame_bytes_sent(&self) -> u64 {
+        self.inner.borrow().frame_bytes_sent
+    }
+
+    /// This method is called when a STREAM frame of `frame_len` bytes was written
+    pub fn on_frame_sent(&mut self, frame_len: usize) {
+        self.inner.borrow_mut().frame_bytes_sent += frame_len as u64;
+    }
+
     /// Acquires a part of the window from the `ConnectionFlowController` in
     /// order to be able to use it for sending data. `desired` is the window
     /// size that is intended to be borrowed. The returned window size might
@@ -186,7 +206,17 @@ impl OutgoingConnectionFlowController {
         //# connection from closing, a sender that is flow control limited SHOULD
         //# periodically send a STREAM_DATA_BLOCKED or DATA_BLOCKED frame when it
         //# has no ack-eliciting packets in flight.
-        let data_blocked_sync = &mut self.inner.borrow_mut().data_blocked_sync;
+        let inner = &mut *self.inner.borrow_mut();
+
+        // notify the application of the stall before any DATA_BLOCKED frame is sent
+        while let Some(transition) = inner.blocked_event.poll(context.current_time()) {
+            // only stream stalls are reported when they are resolved
+            if let Transition::Blocked { limit } = transition {
+                context.on_connection_blocked(limit);
+            }
+        }
+
+        let data_blocked_sync = &mut inner.data_blocked_sync;
 
         if context.ack_elicitation().is_ack_eliciting() && data_blocked_sync.has_delivered() {
             // We are already sending an ack-eliciting packet, so no need to send another DATA_BLOCKED
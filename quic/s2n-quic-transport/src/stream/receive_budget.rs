@@ -0,0 +1,91 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+/// Caps the number of received bytes which the application has not read across all streams
+///
+/// The connection flow control window normally moves forward as the application reads, so the
+/// peer can always send a full window ahead of the application. With a budget, the window never
+/// extends more than `max_buffered` bytes past the data which has been read.
+///
+/// Once the buffered bytes reach the budget, the window is held until the application has read
+/// enough to bring them back down to the low-water mark of half the budget. This avoids
+/// sending a `MAX_DATA` frame for every small read of an application which is falling behind.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ReceiveByteBudget {
+    /// The maximum number of unread bytes
+    max_buffered: u64,
+    /// Set once the budget is used up, until the buffered bytes drop below the low-water mark
+    exhausted: bool,
+}
+
+impl ReceiveByteBudget {
+    pub fn new(max_buffered: u64) -> Self {
+        Self {
+            max_buffered,
+            exhausted: false,
+        }
+    }
+
+    /// Called with the number of bytes which were received but not read by the application
+    #[inline]
+    pub fn on_buffered(&mut self, buffered: u64) {
+        if buffered >= self.max_buffered {
+            self.exhausted = true;
+        } else if buffered <= self.low_water_mark() {
+            self.exhausted = false;
+        }
+    }
+
+    /// Returns the largest connection limit which can be issued to the peer
+    ///
+    /// Returns `None` while the budget is exhausted, in which case the limit should not be
+    /// raised.
+    #[inline]
+    pub fn max_data(&self, consumed: u64) -> Option<u64> {
+        if self.exhausted {
+            return None;
+        }
+
+        Some(consumed.saturating_add(self.max_buffered))
+    }
+
+    /// Returns the number of bytes which can still be buffered
+    #[inline]
+    pub fn remaining(&self, buffered: u64) -> u64 {
+        self.max_buffered.saturating_sub(buffered)
+    }
+
+    #[inline]
+    fn low_water_mark(&self) -> u64 {
+        self.max_buffered / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_water_mark_test() {
+        let mut budget = ReceiveByteBudget::new(1000);
+
+        budget.on_buffered(999);
+        assert_eq!(budget.max_data(0), Some(1000));
+        assert_eq!(budget.remaining(999), 1);
+
+        // the budget is used up so the limit is held
+        budget.on_buffered(1000);
+        assert_eq!(budget.max_data(0), None);
+        assert_eq!(budget.remaining(1000), 0);
+
+        // reading some of the data isn't enough to release the limit
+        budget.on_buffered(501);
+        assert_eq!(budget.max_data(499), None);
+
+        // once the buffered bytes reach the low-water mark, the freed amount is issued
+        budget.on_buffered(500);
+        assert_eq!(budget.max_data(500), Some(1500));
+        budget.on_buffered(700);
+        assert_eq!(budget.max_data(500), Some(1500));
+    }
+}
@@ -181,6 +181,8 @@ pub(super) struct ReceiveStreamFlowController {
     pub(super) acquired_connection_window: VarInt,
     /// The amount of credits which had been released in total
     pub(super) released_connection_window: VarInt,
+    /// The amount of granted credits which had been donated to other streams in total
+    pub(super) donated_window: VarInt,
 }
 
 impl ReceiveStreamFlowController {
@@ -198,6 +200,7 @@ impl ReceiveStreamFlowController {
             ),
             acquired_connection_window: VarInt::from_u32(0),
             released_connection_window: VarInt::from_u32(0),
+            donated_window: VarInt::from_u32(0),
             desired_flow_control_window,
         }
     }
@@ -297,6 +300,48 @@ impl ReceiveStreamFlowController {
         self.release_window(unreleased);
     }
 
+    /// Returns the credits which had been granted to the peer, but which the peer
+    /// has not used yet and which had not been donated to another stream
+    fn unused_window(&self) -> VarInt {
+        self.read_window_sync
+            .latest_value()
+            .saturating_sub(self.acquired_connection_window)
+            .saturating_sub(self.donated_window)
+    }
+
+    /// Withdraws `amount` of unused credits from the window so they can be donated to
+    /// another stream
+    ///
+    /// Credits which had already been granted to the peer can not be revoked. Instead
+    /// the window which is maintained towards the peer shrinks by `amount`, which reduces
+    /// future window updates.
+    fn withdraw_window(&mut self, amount: u32) -> Result<(), StreamError> {
+        let amount_varint = VarInt::from_u32(amount);
+
+        if amount_varint > self.unused_window() || amount > self.desired_flow_control_window {
+            return Err(StreamError::insufficient_credits());
+        }
+
+        // The withdrawn credits can't be donated again
+        self.donated_window += amount_varint;
+        self.desired_flow_control_window -= amount;
+
+        Ok(())
+    }
+
+    /// Grants `amount` of credits which had been donated by another stream to the peer
+    fn deposit_window(&mut self, amount: u32) {
+        self.desired_flow_control_window = self.desired_flow_control_window.saturating_add(amount);
+
+        // Donations are synchronized immediately, since the peer is likely blocked
+        // on the stream
+        self.read_window_sync.update_latest_value_immediately(
+            self.read_window_sync
+                .latest_value()
+                .saturating_add(VarInt::from_u32(amount)),
+        );
+    }
+
     /// Stop to synchronize the Streams flow control window to the peer
     fn stop_sync(&mut self) {
         self.read_window_sync.stop_sync();
@@ -733,8 +778,38 @@ impl ReceiveStream {
         }
     }
 
+    /// Returns the number of received bytes which are ready to be read by the application
+    pub fn unread_len(&self) -> u64 {
+        self.receive_buffer.len() as u64
+    }
+
     // These functions are called from the client API
 
+    /// Returns true if the stream is able to make use of credits donated by another stream
+    ///
+    /// Once the final size of the stream is known, additional credits have no effect.
+    pub fn can_accept_credits(&self) -> bool {
+        matches!(self.state, ReceiveStreamState::Receiving(None))
+            && !self.flow_controller.read_window_sync.is_cancelled()
+    }
+
+    /// Withdraws `amount` of unused flow control credits, so they can be donated to
+    /// another stream
+    pub fn withdraw_credits(&mut self, amount: u32) -> Result<(), StreamError> {
+        if !matches!(self.state, ReceiveStreamState::Receiving(_)) {
+            return Err(StreamError::non_readable());
+        }
+
+        self.flow_controller.withdraw_window(amount)
+    }
+
+    /// Grants `amount` of flow control credits which had been donated by another stream
+    pub fn deposit_credits(&mut self, amount: u32) {
+        debug_assert!(self.can_accept_credits());
+
+        self.flow_controller.deposit_window(amount);
+    }
+
     pub fn poll_request(
         &mut self,
         request: &mut ops::rx::Request,
@@ -982,5 +1057,7 @@ impl StreamInterestProvider for ReceiveStream {
     }
 }
 
+#[cfg(test)]
+mod fuzz_target;
 #[cfg(test)]
 mod tests;
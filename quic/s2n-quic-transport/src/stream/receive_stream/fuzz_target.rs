@@ -0,0 +1,100 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::stream::{testing::*, StreamEvents, StreamTrait};
+use bolero::{check, generator::*};
+use s2n_quic_core::{
+    frame::{ResetStream, StopSending},
+    varint::VarInt,
+};
+
+/// The frames the peer may send for a single bidirectional stream, interleaved with the
+/// operations of the local application
+#[derive(Clone, Copy, Debug, TypeGenerator)]
+enum Operation {
+    Stream {
+        // stay within the stream's flow control window
+        #[generator(0..2048)]
+        offset: u16,
+        #[generator(0..256)]
+        len: u16,
+        is_fin: bool,
+    },
+    ResetStream {
+        #[generator(0..2304)]
+        final_size: u16,
+    },
+    /// Retransmits the first RESET_STREAM frame which was accepted
+    RepeatResetStream,
+    StopSending,
+    Receive,
+    Transmit,
+}
+
+#[test]
+fn frame_ordering_test() {
+    check!()
+        .with_type::<Vec<Operation>>()
+        .for_each(|operations| {
+            let mut test_env = setup_stream_test_env();
+            let stream_id = test_env.stream.stream_id;
+            let mut accepted_reset = None;
+
+            for operation in operations.iter().copied() {
+                let mut events = StreamEvents::new();
+
+                let result = match operation {
+                    Operation::Stream {
+                        offset,
+                        len,
+                        is_fin,
+                    } => {
+                        let data = vec![0u8; len as usize];
+                        let frame = stream_data(stream_id, offset.into(), &data[..], is_fin);
+                        test_env.stream.on_data(&frame, &mut events)
+                    }
+                    Operation::ResetStream { final_size } => {
+                        let frame = ResetStream {
+                            stream_id: stream_id.into(),
+                            application_error_code: VarInt::from_u8(1),
+                            final_size: final_size.into(),
+                        };
+                        let result = test_env.stream.on_reset(&frame, &mut events);
+                        if result.is_ok() && accepted_reset.is_none() {
+                            accepted_reset = Some(frame);
+                        }
+                        result
+                    }
+                    Operation::RepeatResetStream => {
+                        if let Some(frame) = accepted_reset {
+                            // a retransmitted reset is always ignored
+                            assert_eq!(Ok(()), test_env.stream.on_reset(&frame, &mut events));
+                        }
+                        Ok(())
+                    }
+                    Operation::StopSending => {
+                        let frame = StopSending {
+                            stream_id: stream_id.into(),
+                            application_error_code: VarInt::from_u8(2),
+                        };
+                        test_env.stream.on_stop_sending(&frame, &mut events)
+                    }
+                    Operation::Receive => {
+                        let _ = test_env.poll_pop();
+                        Ok(())
+                    }
+                    Operation::Transmit => {
+                        test_env.transmit();
+                        Ok(())
+                    }
+                };
+
+                events.wake_all();
+
+                // the connection is closed on a transport error, so no more frames are processed
+                if result.is_err() {
+                    return;
+                }
+            }
+        });
+}
@@ -559,7 +559,9 @@ fn reset_errors_if_final_size_contradicts_fin_size() {
 
 #[test]
 fn reset_has_no_impact_if_all_data_had_been_received() {
-    for data_is_consumed in &[false, true] {
+    for (data_is_consumed, is_internal_reset) in
+        [(false, false), (true, false), (false, true), (true, true)]
+    {
         let mut test_env = setup_receive_only_test_env();
 
         let mut events = StreamEvents::new();
@@ -576,7 +578,7 @@ fn reset_has_no_impact_if_all_data_had_been_received() {
             )
             .is_ok());
 
-        if *data_is_consumed {
+        if data_is_consumed {
             test_env.assert_receive_data(&[0, 1, 2, 3]);
             test_env.assert_end_of_stream();
             assert_eq!(
@@ -585,18 +587,24 @@ fn reset_has_no_impact_if_all_data_had_been_received() {
             );
         }
 
-        let reset_frame = ResetStream {
-            stream_id: test_env.stream.stream_id.into(),
-            application_error_code: VarInt::from_u8(0),
-            final_size: VarInt::new(test_env.stream.receive_stream.receive_buffer.len() as u64)
-                .unwrap(),
-        };
         let mut events = StreamEvents::new();
-        assert!(test_env.stream.on_reset(&reset_frame, &mut events).is_ok());
+        if is_internal_reset {
+            // The connection was closed by the application
+            let error = connection::Error::application(ApplicationErrorCode::new(1).unwrap());
+            test_env.stream.on_internal_reset(error.into(), &mut events);
+        } else {
+            let reset_frame = ResetStream {
+                stream_id: test_env.stream.stream_id.into(),
+                application_error_code: VarInt::from_u8(0),
+                final_size: VarInt::new(test_env.stream.receive_stream.receive_buffer.len() as u64)
+                    .unwrap(),
+            };
+            assert!(test_env.stream.on_reset(&reset_frame, &mut events).is_ok());
+        }
 
         // If the data hasn't been consumed yet, it should still be available
         // after the reset.
-        if !*data_is_consumed {
+        if !data_is_consumed {
             test_env.assert_receive_data(&[0, 1, 2, 3]);
             assert_eq!(
                 stream_interests(&["fin"]),
@@ -2548,3 +2556,126 @@ fn receiving_into_non_empty_buffers_returns_an_error() {
         "data should not be lost when returning an error"
     );
 }
+
+#[test]
+fn donated_credits_are_synchronized_to_the_peer() {
+    let mut donor = setup_receive_only_test_env();
+    let mut recipient = setup_receive_only_test_env();
+
+    let initial_window = TestEnvironment::DEFAULT_INITIAL_RECEIVE_WINDOW;
+    // Donate less than the sync threshold to make sure the update is sent immediately
+    let donation = 100;
+
+    // The peer has used part of the donor window
+    donor.feed_data(VarInt::from_u32(0), 1000);
+
+    // Only credits the peer has not used yet can be donated
+    assert_matches!(
+        donor
+            .stream
+            .receive_stream
+            .withdraw_credits((initial_window - 1000 + 1) as u32),
+        Err(StreamError::InsufficientCredits { .. }),
+    );
+    assert!(donor
+        .stream
+        .receive_stream
+        .withdraw_credits(donation)
+        .is_ok());
+
+    // Credits which had already been granted to the peer are not revoked
+    assert_eq!(
+        VarInt::new(initial_window).unwrap(),
+        donor
+            .stream
+            .receive_stream
+            .flow_controller
+            .current_stream_receive_window()
+    );
+
+    assert!(recipient.stream.receive_stream.can_accept_credits());
+    recipient.stream.receive_stream.deposit_credits(donation);
+    assert_eq!(
+        stream_interests(&["tx"]),
+        recipient.stream.get_stream_interests()
+    );
+
+    let expected_window = initial_window + donation as u64;
+    recipient.assert_write_frames(1);
+    let sent_frame = recipient.sent_frames.pop_front().expect("Frame is written");
+    assert_eq!(
+        Frame::MaxStreamData(MaxStreamData {
+            stream_id: recipient.stream.stream_id.into(),
+            maximum_stream_data: VarInt::new(expected_window).unwrap(),
+        }),
+        sent_frame.as_frame()
+    );
+
+    // The peer can now send up to the new window on the recipient
+    recipient.feed_data(VarInt::from_u32(0), expected_window as usize);
+
+    // The donor window grows by less after the donation
+    assert_eq!(1000, donor.consume_all_data());
+    let expected_window = 1000 + initial_window - donation as u64;
+    donor.assert_write_frames(1);
+    let sent_frame = donor.sent_frames.pop_front().expect("Frame is written");
+    assert_eq!(
+        Frame::MaxStreamData(MaxStreamData {
+            stream_id: donor.stream.stream_id.into(),
+            maximum_stream_data: VarInt::new(expected_window).unwrap(),
+        }),
+        sent_frame.as_frame()
+    );
+}
+
+#[test]
+fn credits_can_only_be_donated_once() {
+    let initial_window = TestEnvironment::DEFAULT_INITIAL_RECEIVE_WINDOW as u32;
+
+    // The desired window is larger than the granted window, so it doesn't limit the donations
+    let mut config = TestEnvironmentConfig::new(endpoint::Type::Server);
+    config.stream_id = StreamId::initial(
+        config.local_endpoint_type.peer_type(),
+        StreamType::Unidirectional,
+    );
+    config.desired_flow_control_window = initial_window * 4;
+    let mut donor = setup_stream_test_env_with_config(config);
+
+    // Donating all of the unused credits in two steps succeeds
+    let half = initial_window / 2;
+    assert!(donor.stream.receive_stream.withdraw_credits(half).is_ok());
+    assert!(donor
+        .stream
+        .receive_stream
+        .withdraw_credits(initial_window - half)
+        .is_ok());
+
+    // The same credits can't be donated again
+    assert_matches!(
+        donor.stream.receive_stream.withdraw_credits(1),
+        Err(StreamError::InsufficientCredits { .. }),
+    );
+    assert_matches!(
+        donor.stream.receive_stream.withdraw_credits(initial_window),
+        Err(StreamError::InsufficientCredits { .. }),
+    );
+}
+
+#[test]
+fn credits_can_not_be_donated_to_finished_streams() {
+    let mut test_env = setup_receive_only_test_env();
+
+    test_env.feed_data(VarInt::from_u32(0), 16);
+    assert!(test_env.stream.receive_stream.can_accept_credits());
+
+    // Once the final size is known additional credits have no effect
+    let mut events = StreamEvents::new();
+    assert!(test_env
+        .stream
+        .on_data(
+            &stream_data(test_env.stream.stream_id, VarInt::from_u8(16), &[], true),
+            &mut events
+        )
+        .is_ok());
+    assert!(!test_env.stream.receive_stream.can_accept_credits());
+}
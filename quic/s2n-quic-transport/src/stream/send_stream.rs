@@ -4,6 +4,7 @@
 use crate::{
     contexts::{OnTransmitError, WriteContext},
     stream::{
+        blocked_event::{BlockedEvent, Transition},
         outgoing_connection_flow_controller::OutgoingConnectionFlowController,
         stream_events::StreamEvents,
         stream_interests::{StreamInterestProvider, StreamInterests},
@@ -197,6 +198,8 @@ pub(super) struct StreamFlowController {
     state: StreamFlowControllerState,
     /// For periodically sending `STREAM_DATA_BLOCKED` frames when blocked by peer limits
     stream_data_blocked_sync: PeriodicSync<VarInt, StreamDataBlockedToFrameWriter>,
+    /// For notifying the application when the stream becomes blocked by peer limits
+    blocked_event: BlockedEvent,
 }
 
 impl StreamFlowController {
@@ -212,6 +215,7 @@ impl StreamFlowController {
             max_stream_data: initial_window,
             state: StreamFlowControllerState::Ready,
             stream_data_blocked_sync: PeriodicSync::new(),
+            blocked_event: BlockedEvent::default(),
         }
     }
 
@@ -225,6 +229,7 @@ impl StreamFlowController {
         }
 
         self.max_stream_data = max_stream_data;
+        self.blocked_event.on_unblocked(max_stream_data);
         if self.state == StreamFlowControllerState::BlockedOnStreamWindow {
             self.state = StreamFlowControllerState::Ready;
             // We now have more capacity from the peer so stop sending STREAM_DATA_BLOCKED frames
@@ -298,9 +303,12 @@ impl StreamFlowController {
     }
 
     /// Queries the component for any outgoing frames that need to get sent
+    ///
+    /// `enqueued_len` is the total number of bytes the application has enqueued on the stream.
     pub fn on_transmit<W: WriteContext>(
         &mut self,
         stream_id: StreamId,
+        enqueued_len: VarInt,
         context: &mut W,
     ) -> Result<(), OnTransmitError> {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-4.1
@@ -308,6 +316,21 @@ impl StreamFlowController {
         //# connection from closing, a sender that is flow control limited SHOULD
         //# periodically send a STREAM_DATA_BLOCKED or DATA_BLOCKED frame when it
         //# has no ack-eliciting packets in flight.
+
+        // notify the application of the stall before any STREAM_DATA_BLOCKED frame is sent
+        while let Some(transition) = self.blocked_event.poll(context.current_time()) {
+            match transition {
+                Transition::Blocked { limit } => {
+                    let pending_len = enqueued_len.saturating_sub(limit);
+                    context.on_stream_blocked(stream_id, limit, pending_len);
+                }
+                Transition::Unblocked {
+                    limit,
+                    blocked_duration,
+                } => context.on_stream_unblocked(stream_id, limit, blocked_duration),
+            }
+        }
+
         if context.ack_elicitation().is_ack_eliciting()
             && self.stream_data_blocked_sync.has_delivered()
         {
@@ -379,6 +402,7 @@ impl OutgoingDataFlowController for StreamFlowController {
             self.state = StreamFlowControllerState::BlockedOnStreamWindow;
             self.stream_data_blocked_sync
                 .request_delivery(self.max_stream_data);
+            self.blocked_event.on_blocked(self.max_stream_data);
         }
 
         self.highest_requested_connection_flow_control_window = core::cmp::max(
@@ -418,6 +442,10 @@ impl OutgoingDataFlowController for StreamFlowController {
         //# any terminal state -- that is, after sending a RESET_STREAM frame.
         self.stream_data_blocked_sync.stop_sync();
     }
+
+    fn on_frame_sent(&mut self, frame_len: usize) {
+        self.connection_flow_controller.on_frame_sent(frame_len);
+    }
 }
 
 /// Writes the `STREAM_DATA_BLOCKED` frames.
@@ -654,9 +682,18 @@ impl SendStream {
     ) -> Result<(), OnTransmitError> {
         self.reset_sync.on_transmit(stream_id, context)?;
         self.data_sender.on_transmit(stream_id.into(), context)?;
+        let enqueued_len = self.data_sender.total_enqueued_len();
         self.data_sender
             .flow_controller_mut()
-            .on_transmit(stream_id, context)
+            .on_transmit(stream_id, enqueued_len, context)
+    }
+
+    /// Returns `true` if the FIN bit was declared lost and still needs to be retransmitted
+    pub fn has_lost_fin(&self) -> bool {
+        matches!(
+            self.data_sender.state(),
+            data_sender::State::Finishing(data_sender::FinState::Lost)
+        )
     }
 
     /// Updates the period at which `STREAM_DATA_BLOCKED` frames are sent to the peer
@@ -2,10 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::stream::{
-    stream_interests::{StreamInterestProvider, StreamInterests},
-    testing::*,
-    StreamError, StreamEvents, StreamTrait,
+use crate::{
+    contexts::testing::MockWriteContext,
+    stream::{
+        stream_interests::{StreamInterestProvider, StreamInterests},
+        testing::*,
+        StreamError, StreamEvents, StreamTrait,
+    },
 };
 use bytes::Bytes;
 use core::task::Poll;
@@ -15,8 +18,8 @@ use s2n_quic_core::{
     connection, endpoint,
     frame::{Frame, MaxData, MaxStreamData, StopSending},
     packet::number::PacketNumber,
-    stream::{ops, StreamType},
-    transmission,
+    stream::{checksum, ops, StreamType},
+    transmission, transport,
     varint::{VarInt, MAX_VARINT_VALUE},
 };
 
@@ -1152,6 +1155,141 @@ fn blocked_on_connection_flow_control_does_not_prevent_retransmissions() {
     }
 }
 
+#[test]
+fn stream_blocked_event_is_reported_once_per_stall() {
+    const WINDOW_SIZE: usize = 2000;
+
+    let test_env_config = TestEnvironmentConfig {
+        max_send_buffer_size: 1500,
+        initial_send_window: WINDOW_SIZE as u64,
+        ..Default::default()
+    };
+    let mut test_env = setup_stream_test_env_with_config(test_env_config);
+    test_env.sent_frames.set_max_packet_size(Some(1000));
+    let stream_id = test_env.stream.stream_id;
+
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::EnqueueData(VarInt::from_u32(0), 2500, true),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 998, false, true, pn(0)),
+            Instruction::CheckDataTx(VarInt::from_u32(998), 996, false, true, pn(1)),
+            Instruction::CheckDataTx(VarInt::from_u32(1994), 6, false, false, pn(2)),
+            Instruction::CheckStreamDataBlockedTx(VarInt::from_u32(2000), pn(2)),
+        ],
+    );
+    // 500 of the enqueued bytes are beyond the limit
+    assert_eq!(
+        test_env.sent_frames.stream_blocked,
+        vec![(stream_id, VarInt::from_u32(2000), VarInt::from_u32(500))]
+    );
+
+    // Retransmitting the STREAM_DATA_BLOCKED frame does not report the stall again
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::NackPacket(pn(2)),
+            Instruction::CheckDataTx(VarInt::from_u32(1994), 6, false, false, pn(3)),
+            Instruction::CheckStreamDataBlockedTx(VarInt::from_u32(2000), pn(3)),
+            Instruction::CheckNoTx,
+        ],
+    );
+    assert_eq!(test_env.sent_frames.stream_blocked.len(), 1);
+    assert!(test_env.sent_frames.stream_unblocked.is_empty());
+
+    // Raising the limit resolves the stall; hitting the new limit reports it again
+    test_env.current_time += Duration::from_millis(100);
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::SetMaxStreamData(VarInt::from_u32(2100), ExpectWakeup(Some(false))),
+            Instruction::CheckDataTx(VarInt::from_u32(2000), 100, false, false, pn(4)),
+            Instruction::CheckStreamDataBlockedTx(VarInt::from_u32(2100), pn(4)),
+            Instruction::CheckNoTx,
+        ],
+    );
+    assert_eq!(
+        test_env.sent_frames.stream_blocked,
+        vec![
+            (stream_id, VarInt::from_u32(2000), VarInt::from_u32(500)),
+            (stream_id, VarInt::from_u32(2100), VarInt::from_u32(400))
+        ]
+    );
+    assert_eq!(
+        test_env.sent_frames.stream_unblocked,
+        vec![(
+            stream_id,
+            VarInt::from_u32(2100),
+            Duration::from_millis(100)
+        )]
+    );
+    assert!(test_env.sent_frames.connection_blocked.is_empty());
+}
+
+#[test]
+fn connection_blocked_event_is_reported_once_per_stall() {
+    const CONN_WINDOW_SIZE: usize = 2000;
+
+    let test_env_config = TestEnvironmentConfig {
+        max_send_buffer_size: 1500,
+        initial_send_window: 100 * 1024,
+        initial_connection_send_window_size: CONN_WINDOW_SIZE as u64,
+        ..Default::default()
+    };
+    let mut test_env = setup_stream_test_env_with_config(test_env_config);
+    test_env.sent_frames.set_max_packet_size(Some(1000));
+
+    fn transmit_connection_frames(test_env: &mut TestEnvironment) {
+        let mut write_ctx = MockWriteContext::new(
+            test_env.current_time,
+            &mut test_env.sent_frames,
+            test_env.transmission_constraint,
+            transmission::Mode::Normal,
+            test_env.endpoint,
+        );
+        assert!(test_env
+            .tx_connection_flow_controller
+            .on_transmit(&mut write_ctx)
+            .is_ok());
+        test_env.sent_frames.flush();
+        test_env.sent_frames.clear();
+    }
+
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::EnqueueData(VarInt::from_u32(0), 2500, true),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 998, false, true, pn(0)),
+            Instruction::CheckDataTx(VarInt::from_u32(998), 996, false, true, pn(1)),
+            Instruction::CheckDataTx(VarInt::from_u32(1994), 6, false, false, pn(2)),
+            Instruction::CheckInterests(stream_interests(&["ack", "cf"])),
+        ],
+    );
+
+    transmit_connection_frames(&mut test_env);
+    transmit_connection_frames(&mut test_env);
+    assert_eq!(
+        test_env.sent_frames.connection_blocked,
+        vec![VarInt::from_u32(2000)]
+    );
+
+    // Raising the limit resolves the stall; hitting the new limit reports it again
+    execute_instructions(
+        &mut test_env,
+        &[Instruction::SetMaxData(VarInt::from_u32(2100))],
+    );
+    test_env.assert_write_frames(1);
+    test_env.sent_frames.clear();
+
+    transmit_connection_frames(&mut test_env);
+    transmit_connection_frames(&mut test_env);
+    assert_eq!(
+        test_env.sent_frames.connection_blocked,
+        vec![VarInt::from_u32(2000), VarInt::from_u32(2100)]
+    );
+    assert!(test_env.sent_frames.stream_blocked.is_empty());
+}
+
 #[test]
 fn can_write_up_to_max_stream_size() {
     // This is a bit hacky. We can obviously not write 2**62 bytes of data in
@@ -1442,6 +1580,84 @@ fn adds_finish_packet_in_enqueued_packet() {
     }
 }
 
+#[test]
+fn write_and_finish_in_same_request_coalesces_fin() {
+    for size in [1, 10, 500] {
+        let mut test_env = setup_send_only_test_env();
+
+        let mut chunks = gen_pattern_test_chunks(VarInt::from_u8(0), &[size]);
+        let mut request = ops::Request::default();
+        request.send(&mut chunks).finish();
+
+        let response = test_env.run_request(&mut request, true).unwrap();
+        assert_eq!(response.tx().unwrap().status, ops::Status::Finishing);
+
+        // the data and the FIN should be written in a single STREAM frame
+        let mut sent_frame = test_env.transmit().expect("no frame was written");
+        if let Frame::Stream(stream_frame) = sent_frame.as_frame() {
+            assert_eq!(stream_frame.offset, VarInt::from_u8(0));
+            assert_eq!(stream_frame.data.len(), size);
+            assert!(stream_frame.is_fin);
+        } else {
+            panic!("Expected a STREAM frame");
+        }
+
+        // no standalone FIN frame should follow
+        assert!(test_env.transmit().is_none());
+        execute_instructions(
+            &mut test_env,
+            &[
+                Instruction::CheckInterests(stream_interests(&["ack"])),
+                Instruction::AckPacket(pn(0), ExpectWakeup(None)),
+                Instruction::Finish(true),
+            ],
+        );
+    }
+}
+
+#[test]
+fn vectored_write_coalesces_chunks() {
+    const CHUNKS: usize = 10;
+    const CHUNK_SIZE: usize = 50;
+
+    // write each chunk individually and transmit in between
+    let mut test_env = setup_send_only_test_env();
+    let mut individual_frames = 0;
+    for idx in 0..CHUNKS {
+        let offset = VarInt::from_u32((idx * CHUNK_SIZE) as u32);
+        let mut chunks = gen_pattern_test_chunks(offset, &[CHUNK_SIZE]);
+        let mut request = ops::Request::default();
+        request.send(&mut chunks);
+        test_env.run_request(&mut request, false).unwrap();
+
+        while test_env.transmit().is_some() {
+            individual_frames += 1;
+        }
+    }
+
+    // write all of the chunks at once
+    let mut test_env = setup_send_only_test_env();
+    let mut chunks = gen_pattern_test_chunks(VarInt::from_u8(0), &[CHUNK_SIZE; CHUNKS]);
+    let mut request = ops::Request::default();
+    request.send(&mut chunks);
+    let response = test_env.run_request(&mut request, false).unwrap();
+    let response = response.tx().unwrap();
+    assert_eq!(response.chunks.consumed, CHUNKS);
+    assert_eq!(response.bytes.consumed, CHUNKS * CHUNK_SIZE);
+
+    // all of the chunks should be written in a single STREAM frame
+    let mut sent_frame = test_env.transmit().expect("no frame was written");
+    if let Frame::Stream(stream_frame) = sent_frame.as_frame() {
+        assert_eq!(stream_frame.offset, VarInt::from_u8(0));
+        assert_eq!(stream_frame.data.len(), CHUNKS * CHUNK_SIZE);
+    } else {
+        panic!("Expected a STREAM frame");
+    }
+    assert!(test_env.transmit().is_none());
+
+    assert_eq!(individual_frames, CHUNKS);
+}
+
 #[test]
 fn finish_without_data() {
     let test_configs = &[&[
@@ -1987,6 +2203,63 @@ fn stop_sending_while_sending_data_leads_to_a_reset() {
     }
 }
 
+#[test]
+fn stop_sending_prevents_further_stream_frames() {
+    let test_env_config = TestEnvironmentConfig {
+        max_send_buffer_size: 1000,
+        stream_id: StreamId::initial(endpoint::Type::Server, StreamType::Unidirectional),
+        local_endpoint_type: endpoint::Type::Server,
+        ..Default::default()
+    };
+    let mut test_env = setup_stream_test_env_with_config(test_env_config);
+
+    // Transmit some data and block the application on the next write
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::EnqueueData(VarInt::from_u32(0), 500, true),
+            Instruction::CheckDataTx(VarInt::from_u32(0), 500, false, false, pn(0)),
+            Instruction::EnqueueData(VarInt::from_u32(500), 2000, true),
+            Instruction::EnqueueData(VarInt::from_u32(2500), 2000, false),
+        ],
+    );
+
+    let error_code = ApplicationErrorCode::new(0x1234_5678).unwrap();
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-3.5
+    //= type=test
+    //# An endpoint that receives a STOP_SENDING frame
+    //# MUST send a RESET_STREAM frame if the stream is in the "Ready" or
+    //# "Send" state.
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::StopSending(error_code, ExpectWakeup(Some(true))),
+            // Losing the outstanding data must not lead to a retransmission
+            Instruction::NackPacket(pn(0)),
+            Instruction::CheckResetTx(error_code, pn(1), VarInt::from_u32(500)),
+            Instruction::CheckNoTx,
+        ],
+    );
+
+    // The blocked writer is notified about the reset and no further data is accepted
+    assert_matches!(
+        test_env.poll_push(Bytes::from_static(b"data")),
+        Poll::Ready(Err(StreamError::StreamReset { .. })),
+    );
+    execute_instructions(&mut test_env, &[Instruction::CheckNoTx]);
+
+    // Losing the RESET_STREAM frame only retransmits the reset
+    execute_instructions(
+        &mut test_env,
+        &[
+            Instruction::NackPacket(pn(1)),
+            Instruction::CheckResetTx(error_code, pn(2), VarInt::from_u32(500)),
+            Instruction::CheckNoTx,
+        ],
+    );
+}
+
 #[test]
 fn stop_sending_does_not_cause_an_action_if_stream_is_already_reset() {
     for reset_is_acknowledged in &[true, false] {
@@ -2854,3 +3127,75 @@ fn can_query_stream_readiness() {
         }
     }
 }
+
+#[test]
+fn stream_data_checksum_test() {
+    const LEN: usize = 1000;
+
+    let stream_id = StreamId::initial(endpoint::Type::Server, StreamType::Unidirectional);
+    let mut sender = setup_stream_test_env_with_config(TestEnvironmentConfig {
+        stream_id,
+        max_packet_size: Some(100),
+        stream_data_checksum: true,
+        ..Default::default()
+    });
+    let mut receiver = setup_stream_test_env_with_config(TestEnvironmentConfig {
+        stream_id,
+        stream_data_checksum: true,
+        ..TestEnvironmentConfig::new(endpoint::Type::Client)
+    });
+
+    let mut chunks = gen_pattern_test_chunks(VarInt::from_u8(0), &[LEN]);
+    let mut request = ops::Request::default();
+    request.send(&mut chunks).finish();
+    sender.run_request(&mut request, false).unwrap();
+
+    let mut frames = vec![];
+    while let Some(mut sent_frame) = sender.transmit() {
+        if let Frame::Stream(stream_frame) = sent_frame.as_frame() {
+            let data = stream_frame.data.into_less_safe_slice().to_vec();
+            frames.push((stream_frame.offset, data, stream_frame.is_fin));
+        } else {
+            panic!("Expected a STREAM frame");
+        }
+    }
+    assert!(frames.len() > 1);
+
+    let mut expected_offset = VarInt::from_u8(0);
+    for (offset, data, is_fin) in &frames {
+        // the offsets only count the stream data following the checksum
+        assert_eq!(*offset, expected_offset);
+        let payload = checksum::verify(data).expect("invalid checksum");
+        assert_eq!(gen_pattern_test_data(*offset, payload.len()), payload);
+        expected_offset += VarInt::from_u32(payload.len() as u32);
+
+        let mut events = StreamEvents::new();
+        receiver
+            .stream
+            .on_data(
+                &stream_data(stream_id, *offset, &data[..], *is_fin),
+                &mut events,
+            )
+            .unwrap();
+    }
+    assert_eq!(expected_offset, VarInt::from_u32(LEN as u32));
+    assert_eq!(receiver.consume_all_data(), LEN);
+    receiver.assert_end_of_stream();
+
+    // a corrupted frame closes the connection
+    let mut receiver = setup_stream_test_env_with_config(TestEnvironmentConfig {
+        stream_id,
+        stream_data_checksum: true,
+        ..TestEnvironmentConfig::new(endpoint::Type::Client)
+    });
+    let (offset, mut data, is_fin) = frames.pop().unwrap();
+    *data.last_mut().unwrap() ^= 1;
+    let mut events = StreamEvents::new();
+    assert_is_transport_error(
+        receiver.stream.on_data(
+            &stream_data(stream_id, offset, &data[..], is_fin),
+            &mut events,
+        ),
+        transport::Error::INTERNAL_ERROR,
+    );
+}
@@ -13,11 +13,17 @@ use crate::{
     transmission,
 };
 use alloc::rc::Rc;
-use core::{cell::RefCell, ops::Deref};
+use core::{
+    cell::{Cell, RefCell},
+    ops::Deref,
+};
 use intrusive_collections::{
     intrusive_adapter, KeyAdapter, LinkedList, LinkedListLink, RBTree, RBTreeLink,
 };
-use s2n_quic_core::{stream::StreamId, time::timer};
+use s2n_quic_core::{
+    stream::{StreamId, StreamMetricsCollector, StreamMetricsTotals, StreamPriority},
+    time::timer,
+};
 
 // Intrusive list adapter for managing the list of `done` streams
 intrusive_adapter!(DoneStreamsAdapter<S> = Rc<StreamNode<S>>: StreamNode<S> {
@@ -71,8 +77,10 @@ struct StreamNode<S> {
     done_streams_link: LinkedListLink,
     /// Allows the Stream to be part of the `waiting_for_frame_delivery` collection
     waiting_for_frame_delivery_link: LinkedListLink,
-    /// Allows the Stream to be part of the `waiting_for_transmission` collection
+    /// Allows the Stream to be part of one of the `waiting_for_transmission` collections
     waiting_for_transmission_link: LinkedListLink,
+    /// The priority of the `waiting_for_transmission` collection the Stream is part of
+    transmission_priority: Cell<StreamPriority>,
     /// Allows the Stream to be part of the `waiting_for_transmission` collection
     waiting_for_retransmission_link: LinkedListLink,
     /// Allows the Stream to be part of the `waiting_for_connection_flow_control_credits` collection
@@ -90,6 +98,7 @@ impl<S> StreamNode<S> {
             done_streams_link: LinkedListLink::new(),
             waiting_for_frame_delivery_link: LinkedListLink::new(),
             waiting_for_transmission_link: LinkedListLink::new(),
+            transmission_priority: Cell::new(StreamPriority::default()),
             waiting_for_retransmission_link: LinkedListLink::new(),
             waiting_for_connection_flow_control_credits_link: LinkedListLink::new(),
             waiting_for_stream_flow_control_credits_link: LinkedListLink::new(),
@@ -123,6 +132,19 @@ unsafe fn stream_node_rc_from_ref<S>(stream_node: &StreamNode<S>) -> Rc<StreamNo
     temp_node_ptr.deref().clone()
 }
 
+/// The number of stream priorities, each of which has its own transmission list
+const PRIORITY_LEVELS: usize = 3;
+
+/// Returns the index of the transmission list for Streams of the given priority
+#[inline]
+fn priority_index(priority: StreamPriority) -> usize {
+    match priority {
+        StreamPriority::Low => 0,
+        StreamPriority::Normal => 1,
+        StreamPriority::Critical => 2,
+    }
+}
+
 /// Contains all secondary lists of Streams.
 ///
 /// A Stream can be a member in any of those, in addition to being a member of
@@ -133,8 +155,8 @@ struct InterestLists<S> {
     /// Streams which are waiting for packet acknowledgements and
     /// packet loss notifications
     waiting_for_frame_delivery: LinkedList<WaitingForFrameDeliveryAdapter<S>>,
-    /// Streams which need to transmit data
-    waiting_for_transmission: LinkedList<WaitingForTransmissionAdapter<S>>,
+    /// Streams which need to transmit data, indexed by their priority
+    waiting_for_transmission: [LinkedList<WaitingForTransmissionAdapter<S>>; PRIORITY_LEVELS],
     /// Streams which need to transmit data
     waiting_for_retransmission: LinkedList<WaitingForRetransmissionAdapter<S>>,
     /// Streams which are blocked on transmission due to waiting on the
@@ -152,7 +174,11 @@ impl<S: StreamTrait> InterestLists<S> {
         Self {
             done_streams: LinkedList::new(DoneStreamsAdapter::new()),
             waiting_for_frame_delivery: LinkedList::new(WaitingForFrameDeliveryAdapter::new()),
-            waiting_for_transmission: LinkedList::new(WaitingForTransmissionAdapter::new()),
+            waiting_for_transmission: [
+                LinkedList::new(WaitingForTransmissionAdapter::new()),
+                LinkedList::new(WaitingForTransmissionAdapter::new()),
+                LinkedList::new(WaitingForTransmissionAdapter::new()),
+            ],
             waiting_for_retransmission: LinkedList::new(WaitingForRetransmissionAdapter::new()),
             waiting_for_connection_flow_control_credits: LinkedList::new(
                 WaitingForConnectionFlowControlCreditsAdapter::new(),
@@ -168,6 +194,7 @@ impl<S: StreamTrait> InterestLists<S> {
         &mut self,
         node: &Rc<StreamNode<S>>,
         interests: StreamInterests,
+        priority: StreamPriority,
         result: StreamContainerIterationResult,
     ) -> bool {
         // Note that all comparisons start by checking whether the stream is
@@ -206,11 +233,32 @@ impl<S: StreamTrait> InterestLists<S> {
             waiting_for_frame_delivery_link,
             waiting_for_frame_delivery
         );
-        sync_interests!(
-            matches!(interests.transmission, transmission::Interest::NewData),
-            waiting_for_transmission_link,
-            waiting_for_transmission
-        );
+        // Streams waiting for transmission are placed in the list for their priority. A Stream
+        // which changed its priority moves to the back of the list for the new priority.
+        let interest = matches!(interests.transmission, transmission::Interest::NewData);
+        if node.waiting_for_transmission_link.is_linked()
+            && (!interest || node.transmission_priority.get() != priority)
+        {
+            let index = priority_index(node.transmission_priority.get());
+            // Safety: We know that the node is only ever part of the list for the priority
+            // it was inserted with.
+            let mut cursor = unsafe {
+                self.waiting_for_transmission[index]
+                    .cursor_mut_from_ptr(node.deref() as *const StreamNode<S>)
+            };
+            cursor.remove();
+        }
+        if interest && !node.waiting_for_transmission_link.is_linked() {
+            node.transmission_priority.set(priority);
+            let list = &mut self.waiting_for_transmission[priority_index(priority)];
+            if matches!(result, StreamContainerIterationResult::Continue) {
+                list.push_back(node.clone());
+            } else {
+                list.push_front(node.clone());
+            }
+        }
+        debug_assert_eq!(interest, node.waiting_for_transmission_link.is_linked());
+
         sync_interests!(
             matches!(interests.transmission, transmission::Interest::LostData),
             waiting_for_retransmission_link,
@@ -259,12 +307,15 @@ pub struct StreamContainer<S> {
     nr_active_streams: usize,
     /// Additional interest lists in which Streams will be placed dynamically
     interest_lists: InterestLists<S>,
+    /// The transfer counters of all finalized Streams
+    metrics: StreamMetricsCollector,
 }
 
 impl<S> core::fmt::Debug for StreamContainer<S> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         f.debug_struct("StreamContainer")
             .field("nr_active_streams", &self.nr_active_streams)
+            .field("metrics", &self.metrics)
             .finish()
     }
 }
@@ -274,15 +325,16 @@ macro_rules! iterate_uninterruptible {
         for stream in $sel.interest_lists.$list_name.take() {
             debug_assert!(!stream.$link_name.is_linked());
 
-            let interests = {
+            let (interests, priority) = {
                 let mut mut_stream = stream.inner.borrow_mut();
                 $func(&mut *mut_stream);
-                mut_stream.get_stream_interests()
+                (mut_stream.get_stream_interests(), mut_stream.priority())
             };
 
             $sel.interest_lists.update_interests(
                 &stream,
                 interests,
+                priority,
                 StreamContainerIterationResult::Continue,
             );
         }
@@ -308,8 +360,9 @@ macro_rules! iterate_interruptible {
 
             // Update the interests after the interaction
             let interests = mut_stream.get_stream_interests();
+            let priority = mut_stream.priority();
             $sel.interest_lists
-                .update_interests(&stream, interests, result);
+                .update_interests(&stream, interests, priority, result);
 
             match result {
                 StreamContainerIterationResult::BreakAndInsertAtBack => {
@@ -336,20 +389,33 @@ impl<S: StreamTrait> StreamContainer<S> {
             stream_map: RBTree::new(StreamTreeAdapter::new()),
             nr_active_streams: 0,
             interest_lists: InterestLists::new(),
+            metrics: StreamMetricsCollector::default(),
         }
     }
 
+    /// Returns the transfer counters of all finalized Streams
+    pub fn metrics(&self) -> StreamMetricsTotals {
+        self.metrics.totals()
+    }
+
+    /// Resets the transfer counters of all finalized Streams to zero
+    pub fn reset_metrics(&mut self) {
+        self.metrics.reset();
+    }
+
     /// Insert a new Stream into the container
     pub fn insert_stream(&mut self, stream: S) {
         // Even though it likely might have none, it seems like it
         // would be better to avoid future bugs
         let interests = stream.get_stream_interests();
+        let priority = stream.priority();
 
         let new_stream = Rc::new(StreamNode::new(stream));
 
         self.interest_lists.update_interests(
             &new_stream,
             interests,
+            priority,
             StreamContainerIterationResult::Continue,
         );
 
@@ -391,6 +457,7 @@ impl<S: StreamTrait> StreamContainer<S> {
         let node_ptr: Rc<StreamNode<S>>;
         let result: R;
         let interests;
+        let priority;
 
         // This block is required since we mutably borrow `self` inside the
         // block in order to obtain a Stream reference and to executing the
@@ -410,6 +477,7 @@ impl<S: StreamTrait> StreamContainer<S> {
             let stream: &mut S = &mut *node.inner.borrow_mut();
             result = func(stream);
             interests = stream.get_stream_interests();
+            priority = stream.priority();
         }
 
         // Update the interest lists after the interactions and then remove
@@ -417,6 +485,7 @@ impl<S: StreamTrait> StreamContainer<S> {
         if self.interest_lists.update_interests(
             &node_ptr,
             interests,
+            priority,
             StreamContainerIterationResult::Continue,
         ) {
             self.finalize_done_streams(controller);
@@ -459,7 +528,17 @@ impl<S: StreamTrait> StreamContainer<S> {
             }
 
             remove_stream_from_list!(waiting_for_frame_delivery, waiting_for_frame_delivery_link);
-            remove_stream_from_list!(waiting_for_transmission, waiting_for_transmission_link);
+            if stream.waiting_for_transmission_link.is_linked() {
+                let index = priority_index(stream.transmission_priority.get());
+                // Safety: We know that the Stream is part of the list for the priority it
+                // was inserted with, because it is linked.
+                let mut cursor = unsafe {
+                    self.interest_lists.waiting_for_transmission[index]
+                        .cursor_mut_from_ptr(stream_ptr)
+                };
+                let remove_result = cursor.remove();
+                debug_assert!(remove_result.is_some());
+            }
             remove_stream_from_list!(waiting_for_retransmission, waiting_for_retransmission_link);
             remove_stream_from_list!(
                 waiting_for_connection_flow_control_credits,
@@ -470,7 +549,9 @@ impl<S: StreamTrait> StreamContainer<S> {
                 waiting_for_stream_flow_control_credits_link
             );
 
-            controller.on_close_stream(stream.inner.borrow().stream_id());
+            let inner = stream.inner.borrow();
+            self.metrics.commit(inner.metrics());
+            controller.on_close_stream(inner.stream_id());
         }
     }
 
@@ -540,19 +621,48 @@ impl<S: StreamTrait> StreamContainer<S> {
     /// Iterates over all `Stream`s which are waiting for transmission,
     /// and executes the given function on each `Stream`
     ///
+    /// `Stream`s with a higher priority are visited first.
+    ///
     /// The `stream::Controller` will be notified of streams that have been
     /// closed to allow for further streams to be opened.
     pub fn iterate_transmission_list<F>(&mut self, controller: &mut stream::Controller, mut func: F)
     where
         F: FnMut(&mut S) -> StreamContainerIterationResult,
     {
-        iterate_interruptible!(
-            self,
-            waiting_for_transmission,
-            waiting_for_transmission_link,
-            controller,
-            func
-        );
+        'priorities: for index in (0..PRIORITY_LEVELS).rev() {
+            let mut extracted_list = self.interest_lists.waiting_for_transmission[index].take();
+            let mut cursor = extracted_list.front_mut();
+
+            while let Some(stream) = cursor.remove() {
+                // Note that while we iterate over the intrusive lists here
+                // `stream` is part of no list anymore, since it also got dropped
+                // from list that is described by the `cursor`.
+                debug_assert!(!stream.waiting_for_transmission_link.is_linked());
+                let mut mut_stream = stream.inner.borrow_mut();
+                let result = func(&mut *mut_stream);
+
+                // Update the interests after the interaction
+                let interests = mut_stream.get_stream_interests();
+                let priority = mut_stream.priority();
+                self.interest_lists
+                    .update_interests(&stream, interests, priority, result);
+
+                match result {
+                    StreamContainerIterationResult::BreakAndInsertAtBack => {
+                        self.interest_lists.waiting_for_transmission[index]
+                            .front_mut()
+                            .splice_after(extracted_list);
+                        // Lower priority streams don't get to transmit either
+                        break 'priorities;
+                    }
+                    StreamContainerIterationResult::Continue => {}
+                }
+            }
+        }
+
+        if !self.interest_lists.done_streams.is_empty() {
+            self.finalize_done_streams(controller);
+        }
     }
 
     /// Iterates over all `Stream`s which are waiting for retransmission,
@@ -601,9 +711,11 @@ impl<S: StreamTrait> StreamContainer<S> {
             // Safety: The stream reference is obtained from the RBTree, which
             // stores it's nodes as `Rc`
             let stream_node_rc = unsafe { stream_node_rc_from_ref(stream) };
+            let priority = mut_stream.priority();
             self.interest_lists.update_interests(
                 &stream_node_rc,
                 interests,
+                priority,
                 StreamContainerIterationResult::Continue,
             );
         }
@@ -615,11 +727,61 @@ impl<S: StreamTrait> StreamContainer<S> {
         }
     }
 
+    /// Returns whether or not streams are blocked by the peer's flow control limits
+    pub fn has_flow_control_blocked_streams(&self) -> bool {
+        !self
+            .interest_lists
+            .waiting_for_connection_flow_control_credits
+            .is_empty()
+            || !self
+                .interest_lists
+                .waiting_for_stream_flow_control_credits
+                .is_empty()
+    }
+
     /// Returns whether or not streams have data to send
     pub fn has_pending_streams(&self) -> bool {
-        !self.interest_lists.waiting_for_transmission.is_empty()
+        self.has_transmitting_streams(StreamPriority::Low)
             || !self.interest_lists.waiting_for_retransmission.is_empty()
     }
+
+    /// Returns whether or not streams at or above the given `priority` have new data to send
+    #[inline]
+    fn has_transmitting_streams(&self, priority: StreamPriority) -> bool {
+        self.interest_lists.waiting_for_transmission[priority_index(priority)..]
+            .iter()
+            .any(|list| !list.is_empty())
+    }
+
+    /// Queries the container for interest in retransmitting lost data, ignoring new data
+    #[inline]
+    pub fn retransmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        if !self.interest_lists.waiting_for_retransmission.is_empty() {
+            query.on_lost_data()?;
+        }
+
+        Ok(())
+    }
+
+    /// Queries the container for interest in transmitting, ignoring new data of streams below
+    /// the given `priority`
+    #[inline]
+    pub fn priority_transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        priority: StreamPriority,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        if !self.interest_lists.waiting_for_retransmission.is_empty() {
+            query.on_lost_data()?;
+        } else if self.has_transmitting_streams(priority) {
+            query.on_new_data()?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<S: StreamTrait> timer::Provider for StreamContainer<S> {
@@ -645,7 +807,7 @@ impl<S: StreamTrait> transmission::interest::Provider for StreamContainer<S> {
     ) -> transmission::interest::Result {
         if !self.interest_lists.waiting_for_retransmission.is_empty() {
             query.on_lost_data()?;
-        } else if !self.interest_lists.waiting_for_transmission.is_empty() {
+        } else if self.has_transmitting_streams(StreamPriority::Low) {
             query.on_new_data()?;
         }
 
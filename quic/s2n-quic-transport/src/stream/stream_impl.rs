@@ -12,12 +12,14 @@ use crate::{
         stream_interests::{StreamInterestProvider, StreamInterests},
         StreamError,
     },
+    sync::data_sender,
 };
+use alloc::sync::Arc;
 use core::{task::Context, time::Duration};
 use s2n_quic_core::{
     ack, endpoint,
     frame::{stream::StreamRef, MaxStreamData, ResetStream, StopSending, StreamDataBlocked},
-    stream::{ops, StreamId},
+    stream::{checksum, ops, StreamId, StreamMetrics, StreamPriority},
     time::{timer, Timestamp},
     transport,
     varint::VarInt,
@@ -42,6 +44,8 @@ pub struct StreamConfig {
     pub initial_send_window: VarInt,
     /// The maximum buffered amount of data on the sending side
     pub max_send_buffer_size: u32,
+    /// Whether the data of STREAM frames is prefixed with a checksum in both directions
+    pub stream_data_checksum: bool,
 }
 
 /// A trait which represents an internally used `Stream`
@@ -119,6 +123,15 @@ pub trait StreamTrait: StreamInterestProvider + timer::Provider + core::fmt::Deb
     /// Queries the component for any outgoing frames that need to get sent
     fn on_transmit<W: WriteContext>(&mut self, context: &mut W) -> Result<(), OnTransmitError>;
 
+    /// Returns `true` if the stream is waiting to retransmit a lost FIN
+    ///
+    /// These streams are retransmitted ahead of other lost data, since the peer can't complete
+    /// the stream until the FIN is received.
+    fn has_lost_fin(&self) -> bool;
+
+    /// Returns the number of received bytes which are ready to be read by the application
+    fn unread_len(&self) -> u64;
+
     /// This method is called when a connection window is available
     fn on_connection_window_available(&mut self);
 
@@ -129,6 +142,26 @@ pub trait StreamTrait: StreamInterestProvider + timer::Provider + core::fmt::Deb
         request: &mut ops::Request,
         context: Option<&Context>,
     ) -> Result<ops::Response, StreamError>;
+
+    /// Returns true if the stream is able to make use of receive credits donated by
+    /// another stream
+    fn can_accept_receive_credits(&self) -> bool;
+
+    /// Withdraws `amount` of unused receive credits from the stream, so they can be
+    /// donated to another stream
+    fn withdraw_receive_credits(&mut self, amount: u32) -> Result<(), StreamError>;
+
+    /// Grants `amount` of receive credits which had been donated by another stream
+    fn deposit_receive_credits(&mut self, amount: u32);
+
+    /// Returns the transfer counters of the stream
+    fn metrics(&self) -> &StreamMetrics;
+
+    /// Returns the priority of the stream when sharing reserved bandwidth
+    fn priority(&self) -> StreamPriority;
+
+    /// Changes the priority of the stream when sharing reserved bandwidth
+    fn set_priority(&mut self, priority: StreamPriority);
 }
 
 /// The implementation of a `Stream`.
@@ -143,6 +176,12 @@ pub struct StreamImpl {
     has_send: bool,
     /// Manages the sending side of the stream
     pub(super) send_stream: SendStream,
+    /// The transfer counters of the stream, which are shared with the data sender
+    metrics: Arc<StreamMetrics>,
+    /// The priority of the stream when sharing reserved bandwidth
+    priority: StreamPriority,
+    /// Whether the data of received STREAM frames is prefixed with a checksum
+    stream_data_checksum: bool,
 }
 
 impl StreamImpl {
@@ -197,6 +236,21 @@ impl StreamTrait for StreamImpl {
         let send_is_closed = config.stream_id.stream_type().is_unidirectional()
             && config.stream_id.initiator() != config.local_endpoint_type;
 
+        let metrics = Arc::new(StreamMetrics::default());
+
+        let mut send_stream = SendStream::new(
+            config.outgoing_connection_flow_controller,
+            send_is_closed,
+            config.initial_send_window,
+            config.max_send_buffer_size,
+        );
+        send_stream.data_sender.set_metrics(metrics.clone());
+        if config.stream_data_checksum {
+            send_stream
+                .data_sender
+                .set_writer(data_sender::writer::Stream::with_checksum());
+        }
+
         StreamImpl {
             stream_id: config.stream_id,
             receive_stream: ReceiveStream::new(
@@ -206,12 +260,10 @@ impl StreamTrait for StreamImpl {
                 config.desired_flow_control_window,
             ),
             has_send: !send_is_closed,
-            send_stream: SendStream::new(
-                config.outgoing_connection_flow_controller,
-                send_is_closed,
-                config.initial_send_window,
-                config.max_send_buffer_size,
-            ),
+            send_stream,
+            metrics,
+            priority: StreamPriority::default(),
+            stream_data_checksum: config.stream_data_checksum,
         }
     }
 
@@ -228,7 +280,28 @@ impl StreamTrait for StreamImpl {
         frame: &StreamRef,
         events: &mut StreamEvents,
     ) -> Result<(), transport::Error> {
-        self.receive_stream.on_data(frame, events)
+        // Frames without any data, like a FIN-only frame, don't carry a checksum
+        if self.stream_data_checksum && !frame.data.is_empty() {
+            let data = checksum::verify(frame.data).ok_or_else(|| {
+                transport::Error::INTERNAL_ERROR.with_reason("stream data checksum mismatch")
+            })?;
+
+            let frame = StreamRef {
+                stream_id: frame.stream_id,
+                offset: frame.offset,
+                is_last_frame: frame.is_last_frame,
+                is_fin: frame.is_fin,
+                data,
+            };
+
+            self.receive_stream.on_data(&frame, events)?;
+            self.metrics.on_bytes_received(frame.data.len());
+            return Ok(());
+        }
+
+        self.receive_stream.on_data(frame, events)?;
+        self.metrics.on_bytes_received(frame.data.len());
+        Ok(())
     }
 
     #[inline]
@@ -322,6 +395,16 @@ impl StreamTrait for StreamImpl {
         self.send_stream.on_transmit(self.stream_id, context)
     }
 
+    #[inline]
+    fn has_lost_fin(&self) -> bool {
+        self.send_stream.has_lost_fin()
+    }
+
+    #[inline]
+    fn unread_len(&self) -> u64 {
+        self.receive_stream.unread_len()
+    }
+
     #[inline]
     fn on_connection_window_available(&mut self) {
         self.send_stream.on_connection_window_available()
@@ -344,6 +427,36 @@ impl StreamTrait for StreamImpl {
 
         result
     }
+
+    #[inline]
+    fn can_accept_receive_credits(&self) -> bool {
+        self.receive_stream.can_accept_credits()
+    }
+
+    #[inline]
+    fn withdraw_receive_credits(&mut self, amount: u32) -> Result<(), StreamError> {
+        self.receive_stream.withdraw_credits(amount)
+    }
+
+    #[inline]
+    fn deposit_receive_credits(&mut self, amount: u32) {
+        self.receive_stream.deposit_credits(amount)
+    }
+
+    #[inline]
+    fn metrics(&self) -> &StreamMetrics {
+        &self.metrics
+    }
+
+    #[inline]
+    fn priority(&self) -> StreamPriority {
+        self.priority
+    }
+
+    #[inline]
+    fn set_priority(&mut self, priority: StreamPriority) {
+        self.priority = priority;
+    }
 }
 
 impl timer::Provider for StreamImpl {
@@ -487,6 +487,7 @@ pub struct TestEnvironmentConfig {
     pub transmission_constraint: transmission::Constraint,
     pub local_endpoint_type: endpoint::Type,
     pub max_packet_size: Option<usize>,
+    pub stream_data_checksum: bool,
 }
 
 impl Default for TestEnvironmentConfig {
@@ -515,6 +516,7 @@ impl TestEnvironmentConfig {
             max_send_buffer_size: TestEnvironment::DEFAULT_MAX_SEND_BUFFER_SIZE,
             transmission_constraint: transmission::Constraint::None,
             max_packet_size: None,
+            stream_data_checksum: false,
         }
     }
 }
@@ -530,6 +532,8 @@ pub fn setup_stream_test_env_with_config(config: TestEnvironmentConfig) -> TestE
     let rx_connection_flow_controller = IncomingConnectionFlowController::new(
         VarInt::new(config.initial_connection_receive_window_size).unwrap(),
         config.desired_connection_flow_control_window,
+        None,
+        u64::MAX,
     );
 
     let tx_connection_flow_controller = OutgoingConnectionFlowController::new(
@@ -545,6 +549,7 @@ pub fn setup_stream_test_env_with_config(config: TestEnvironmentConfig) -> TestE
         desired_flow_control_window: config.desired_flow_control_window,
         initial_send_window: VarInt::new(config.initial_send_window).unwrap(),
         max_send_buffer_size: config.max_send_buffer_size as u32,
+        stream_data_checksum: config.stream_data_checksum,
     });
 
     let (waker, wake_counter) = new_count_waker();
@@ -6,9 +6,10 @@ use crate::{
     interval_set::IntervalSet,
     transmission,
 };
+use alloc::sync::Arc;
 use bytes::Bytes;
 use core::convert::TryInto;
-use s2n_quic_core::{ack, packet::number::PacketNumber, varint::VarInt};
+use s2n_quic_core::{ack, packet::number::PacketNumber, stream::StreamMetrics, varint::VarInt};
 
 mod buffer;
 mod traits;
@@ -148,6 +149,18 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         }
     }
 
+    /// Records the frames written by the `DataSender` in `metrics`
+    pub fn set_metrics(&mut self, metrics: Arc<StreamMetrics>) {
+        self.transmissions.set_metrics(metrics);
+    }
+
+    /// Replaces the writer which serializes the data into frames
+    ///
+    /// This should be called before any data is transmitted.
+    pub fn set_writer(&mut self, writer: Writer) {
+        self.transmissions.set_writer(writer);
+    }
+
     /// Declares all inflight packets as lost.
     pub fn on_all_lost(&mut self) {
         self.on_packet_loss(&self.transmissions.get_inflight_range());
@@ -410,6 +423,7 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
                 .transmit_interval(
                     &mut viewer,
                     (self.transmission_offset..total_len).into(),
+                    false,
                     &mut self.state,
                     writer_context,
                     context,
@@ -451,6 +465,7 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
                 self.transmissions.transmit_interval(
                     &mut viewer,
                     (interval.start..interval_end).into(),
+                    true,
                     &mut self.state,
                     writer_context,
                     context,
@@ -750,6 +765,58 @@ mod tests {
         frame_buffer
     }
 
+    #[test]
+    fn packet_packing_test() {
+        // 1500 byte MTU - 20 byte IPv4 header - 8 byte UDP header - 1 byte short header
+        // - 8 byte destination connection ID - 2 byte packet number - 16 byte AEAD tag
+        const PACKET_CAPACITY: usize = 1445;
+        const LEN: u64 = 1024 * 1024;
+
+        let flow_controller = TestFlowController {
+            max_offset: VarInt::MAX,
+            is_blocked: false,
+        };
+        let mut sender: DataSender<_, writer::Stream> = DataSender::new(flow_controller, u32::MAX);
+        let mut send_data = stream::Data::new(LEN);
+        while let Some(chunk) = send_data.send_one(usize::MAX) {
+            sender.push(chunk);
+        }
+        sender.finish();
+
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        frame_buffer.set_max_packet_size(Some(PACKET_CAPACITY));
+        let mut context = MockWriteContext {
+            current_time: s2n_quic_platform::time::now(),
+            frame_buffer: &mut frame_buffer,
+            transmission_constraint: transmission::Constraint::None,
+            transmission_mode: transmission::Mode::Normal,
+            endpoint: endpoint::Type::Server,
+        };
+
+        let mut packet_lens = vec![];
+        while sender.has_transmission_interest() {
+            let prev_len = context.frame_buffer.len();
+            sender
+                .on_transmit(VarInt::from_u8(0), &mut context)
+                .unwrap();
+            context.frame_buffer.flush();
+
+            let frames = context.frame_buffer.frames.iter().skip(prev_len);
+            packet_lens.push(frames.map(|frame| frame.data.len()).sum::<usize>());
+        }
+
+        // every packet but the last one is filled with stream data
+        let (_last, filled) = packet_lens.split_last().unwrap();
+        assert!(filled.iter().all(|len| *len == PACKET_CAPACITY));
+
+        let max_packets = (LEN as usize + 1439) / 1440;
+        assert!(
+            packet_lens.len() <= max_packets,
+            "{} packets were sent",
+            packet_lens.len()
+        );
+    }
+
     #[test]
     fn model() {
         check!()
@@ -27,6 +27,11 @@ pub trait OutgoingDataFlowController {
     /// Signals the flow controller that no further data will be submitted on
     /// the stream and therefore no further flow control window will be requested.
     fn finish(&mut self);
+
+    /// Called when a frame of `frame_len` bytes was written, including
+    /// frames which retransmit data and frames which only carry the FIN.
+    #[inline]
+    fn on_frame_sent(&mut self, _frame_len: usize) {}
 }
 
 /// Writes chunks of data into frames.
@@ -9,10 +9,12 @@ use crate::{
     contexts::{OnTransmitError, WriteContext},
     interval_set::{Interval, IntervalSet},
 };
-use core::{convert::TryInto, num::NonZeroU16};
+use alloc::sync::Arc;
+use core::{convert::TryInto, num::NonZeroU16, ops::RangeBounds};
 use s2n_quic_core::{
     ack,
     packet::number::{Map as PacketNumberMap, PacketNumber, PacketNumberRange},
+    stream::StreamMetrics,
     varint::VarInt,
 };
 
@@ -29,6 +31,8 @@ pub struct Transmissions<FlowController, Writer> {
     pub flow_controller: FlowController,
     /// Serializes chunks into frames and writes the frames
     writer: Writer,
+    /// Records the transmitted frames, if the sender belongs to an application stream
+    metrics: Option<Arc<StreamMetrics>>,
 }
 
 impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
@@ -40,9 +44,20 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
             in_flight: Default::default(),
             flow_controller,
             writer: Default::default(),
+            metrics: None,
         }
     }
 
+    #[inline]
+    pub fn set_metrics(&mut self, metrics: Arc<StreamMetrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    #[inline]
+    pub fn set_writer(&mut self, writer: Writer) {
+        self.writer = writer;
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.in_flight.is_empty()
@@ -84,7 +99,14 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
 
         let mut has_transmitted = false;
         while let Some(mut interval) = set.pop_min() {
-            match self.transmit_interval(&mut viewer, interval, state, writer_context, context) {
+            match self.transmit_interval(
+                &mut viewer,
+                interval,
+                true,
+                state,
+                writer_context,
+                context,
+            ) {
                 Ok(transmitted) => {
                     has_transmitted = true;
                     let len = transmitted.len();
@@ -112,6 +134,7 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         &mut self,
         viewer: &mut Viewer,
         mut interval: Interval<VarInt>,
+        is_retransmission: bool,
         state: &mut State,
         writer_context: Writer::Context,
         context: &mut W,
@@ -160,12 +183,24 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         }
 
         let packet_number = context.packet_number();
+
+        if is_retransmission {
+            // Data which is still in flight is retransmitted by loss recovery probes
+            if let Some(original) = self.in_flight.packet_containing(interval.start) {
+                context.on_retransmission(original);
+            }
+        }
+
         let mut view = viewer.next_view(interval, matches!(state, State::Finishing(_)));
+        let capacity_before = context.remaining_capacity();
 
         self.writer
             .write_chunk(interval.start, &mut view, writer_context, context)
             .map_err(|_| OnTransmitError::CouldNotAcquireEnoughSpace)?;
 
+        let frame_len = capacity_before.saturating_sub(context.remaining_capacity());
+        self.flow_controller.on_frame_sent(frame_len);
+
         let len = view.len();
         debug_assert_ne!(len, 0u64, "cannot transmit an empty payload");
 
@@ -175,6 +210,10 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
 
         self.in_flight.insert(packet_number, interval.start, len);
 
+        if let Some(metrics) = &self.metrics {
+            metrics.on_frame_sent(interval.len(), frame_len, is_retransmission);
+        }
+
         // Piggyback a fin transmission if we can
         if Writer::WRITES_FIN && view.is_fin() {
             if let Some(state) = state.fin_state_mut() {
@@ -201,11 +240,19 @@ impl<FlowController: OutgoingDataFlowController, Writer: FrameWriter>
         if let Some(state) = state.fin_state_mut() {
             if matches!(state, FinState::Pending | FinState::Lost) {
                 let packet_number = context.packet_number();
+                let capacity_before = context.remaining_capacity();
 
                 self.writer
                     .write_fin(buffer.total_len(), writer_context, context)
                     .map_err(|_| OnTransmitError::CouldNotAcquireEnoughSpace)?;
 
+                let frame_len = capacity_before.saturating_sub(context.remaining_capacity());
+                self.flow_controller.on_frame_sent(frame_len);
+
+                if let Some(metrics) = &self.metrics {
+                    metrics.on_frame_sent(0, frame_len, matches!(state, FinState::Lost));
+                }
+
                 state.on_transmit(packet_number);
             }
         }
@@ -289,6 +336,25 @@ impl Set {
             });
     }
 
+    /// Returns the earliest packet in flight which carries the byte at `offset`
+    #[inline]
+    pub fn packet_containing(&self, offset: VarInt) -> Option<PacketNumber> {
+        self.packets
+            .iter()
+            .find_map(|(packet_number, transmission)| {
+                let mut transmission = Some(transmission);
+
+                while let Some(current) = transmission {
+                    if current.range().contains(&offset) {
+                        return Some(packet_number);
+                    }
+                    transmission = current.next.map(|idx| &self.overflow.get(idx).transmission);
+                }
+
+                None
+            })
+    }
+
     #[inline]
     pub fn remove_range(&mut self, range: PacketNumberRange) -> SetRemoveIter {
         SetRemoveIter {
@@ -428,6 +494,16 @@ impl TransmissionSlab {
         self.get_mut(next).transmission.next = next_entry;
     }
 
+    #[inline]
+    fn get(&self, idx: TransmissionId) -> &TransmissionSlabEntry {
+        let index = (idx.0.get() - 1) as usize;
+
+        #[cfg(debug_assertions)]
+        assert!(self.entries[index].occupied);
+
+        &self.entries[index]
+    }
+
     #[inline]
     fn get_mut(&mut self, idx: TransmissionId) -> &mut TransmissionSlabEntry {
         let index = (idx.0.get() - 1) as usize;
@@ -2,11 +2,84 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use s2n_codec::EncoderValue;
-use s2n_quic_core::frame::{self, FitError};
+use s2n_codec::{Encoder, EncoderValue};
+use s2n_quic_core::{
+    frame::{self, FitError},
+    stream::checksum,
+};
 
 #[derive(Clone, Copy, Debug, Default)]
-pub struct Stream;
+pub struct Stream {
+    /// Prefixes the data of each frame with its checksum
+    checksum: bool,
+}
+
+impl Stream {
+    /// Creates a writer which prefixes the data of each frame with its checksum
+    ///
+    /// See [`s2n_quic_core::stream::checksum`]
+    pub fn with_checksum() -> Self {
+        Self { checksum: true }
+    }
+
+    #[inline]
+    fn write_checksummed_chunk<W: WriteContext>(
+        &self,
+        offset: VarInt,
+        data: &mut View,
+        stream_id: VarInt,
+        context: &mut W,
+    ) -> Result<(), FitError> {
+        let remaining_capacity = context.remaining_capacity();
+
+        let mut frame = frame::Stream {
+            stream_id,
+            offset,
+            is_last_frame: false,
+            is_fin: false,
+            data: Checksummed { checksum: 0, data },
+        };
+
+        let len = frame.try_fit(remaining_capacity)?;
+        // make sure there's room for at least one byte of data after the checksum
+        if len <= checksum::LEN {
+            return Err(FitError);
+        }
+
+        frame.data.data.trim_off(frame.data.encoding_size() - len)?;
+        frame.is_fin = frame.data.data.is_fin();
+
+        // only compute the checksum once the data has been trimmed to fit
+        let mut crc = checksum::Crc32::new();
+        for chunk in frame.data.data.iter::<&[u8]>() {
+            crc.update(chunk);
+        }
+        frame.data.checksum = crc.finish();
+
+        context.write_fitted_frame(&frame);
+
+        Ok(())
+    }
+}
+
+/// Stream data prefixed with its checksum
+struct Checksummed<'a, 'b> {
+    checksum: u32,
+    data: &'a mut View<'b>,
+}
+
+impl<'a, 'b> EncoderValue for Checksummed<'a, 'b> {
+    #[inline]
+    fn encode<E: Encoder>(&self, encoder: &mut E) {
+        encoder.encode(&self.checksum);
+        encoder.encode(&self.data);
+    }
+
+    #[inline]
+    fn encoding_size_for_encoder<E: Encoder>(&self, encoder: &E) -> usize {
+        checksum::LEN + self.data.encoding_size_for_encoder(encoder)
+    }
+}
 
 impl FrameWriter for Stream {
     type Context = VarInt;
@@ -26,6 +99,10 @@ impl FrameWriter for Stream {
             "the data sender should not pass a payload that exceeds the current capacity"
         );
 
+        if self.checksum {
+            return self.write_checksummed_chunk(offset, data, stream_id, context);
+        }
+
         let mut frame = frame::Stream {
             stream_id,
             offset,
@@ -53,6 +53,17 @@ impl<
         self.latest_value
     }
 
+    /// Returns the value the peer has been sent, or is about to be sent
+    ///
+    /// This is the value which was last acknowledged if no update has been requested since.
+    pub fn advertised_value(&self) -> T {
+        match self.delivery {
+            DeliveryState::Requested(_) | DeliveryState::Lost(_) => self.latest_value,
+            DeliveryState::InFlight(in_flight) => in_flight.value,
+            _ => self.value_ackd_up_to,
+        }
+    }
+
     /// Returns `true` if the synchronization has been cancelled
     pub fn is_cancelled(&self) -> bool {
         self.delivery.is_cancelled()
@@ -73,6 +84,17 @@ impl<
         self.request_delivery_if_necessary();
     }
 
+    /// Sets the new value that needs to get synchronized to the peer and
+    /// requests its delivery, even if the increase is below the configured `threshold`.
+    pub fn update_latest_value_immediately(&mut self, value: T) {
+        debug_assert!(value >= self.latest_value);
+        self.latest_value = value;
+
+        if !self.delivery.is_cancelled() && self.latest_value != self.value_ackd_up_to {
+            self.delivery = DeliveryState::Requested(self.latest_value);
+        }
+    }
+
     /// Stop to synchronize the value to the peer
     pub fn stop_sync(&mut self) {
         self.delivery.cancel();
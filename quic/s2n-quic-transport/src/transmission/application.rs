@@ -14,7 +14,7 @@ use crate::{
     transmission::{self, Mode},
 };
 use core::ops::RangeInclusive;
-use s2n_quic_core::packet::number::PacketNumberSpace;
+use s2n_quic_core::{packet::number::PacketNumberSpace, recovery::CongestionController as _};
 
 pub enum Payload<'a, Config: endpoint::Config> {
     Normal(Normal<'a, Config::Stream, Config>),
@@ -130,6 +130,7 @@ impl<'a, S: Stream, Config: endpoint::Config> Normal<'a, S, Config> {
                 context,
                 self.stream_manager,
                 self.prioritize_datagrams,
+                self.path_manager.active_path().mtu_controller.mtu(),
             );
         }
         let did_send_ack = self.ack_manager.on_transmit(context);
@@ -145,18 +146,35 @@ impl<'a, S: Stream, Config: endpoint::Config> Normal<'a, S, Config> {
                     context,
                     self.stream_manager,
                     self.prioritize_datagrams,
+                    self.path_manager.active_path().mtu_controller.mtu(),
                 );
             }
 
+            // Streams below the priority the congestion controller reserves bandwidth for
+            // only share the unreserved part of the sending rate
+            self.stream_manager.on_reserved_bandwidth(
+                self.path_manager
+                    .active_path()
+                    .congestion_controller
+                    .reserved_bandwidth(),
+            );
+
             // The default sending behavior is to alternate between sending datagrams
             // and sending stream data. This can be configured by implementing a
             // custom datagram sender and choosing when to cede packet space for stream data.
             let _ = self.stream_manager.on_transmit(context);
 
+            // fill any remaining capacity with bandwidth probe padding after application data
+            self.path_manager
+                .active_path_mut()
+                .bandwidth_probe
+                .on_transmit(context);
+
             // send PINGs last, since they might not actually be needed if there's an ack-eliciting
             // frame already present in the payload
             self.recovery_manager.on_transmit(context);
-            let _ = self.ping.on_transmit(context);
+            HandshakeProbeSuppressor::new(self.handshake_status.is_confirmed())
+                .on_transmit_ping(self.ping, context);
         }
 
         if did_send_ack {
@@ -203,11 +221,58 @@ impl<'a, S: Stream, Config: endpoint::Config> transmission::interest::Provider
         self.path_manager
             .active_path()
             .transmission_interest(query)?;
-        self.ping.transmission_interest(query)?;
+        HandshakeProbeSuppressor::new(self.handshake_status.is_confirmed())
+            .ping_interest(self.ping, query)?;
         Ok(())
     }
 }
 
+/// Holds back probes which aren't needed to complete the handshake until it is confirmed
+///
+/// PINGs sent for keep-alive or on behalf of the application, as well as MTU probes, compete
+/// with the handshake messages on slow paths without helping to complete the handshake. PTO
+/// probes are not affected, since they are required to recover lost handshake packets.
+#[derive(Clone, Copy, Debug)]
+pub struct HandshakeProbeSuppressor {
+    is_handshake_confirmed: bool,
+}
+
+impl HandshakeProbeSuppressor {
+    #[inline]
+    pub fn new(is_handshake_confirmed: bool) -> Self {
+        Self {
+            is_handshake_confirmed,
+        }
+    }
+
+    /// Returns `true` if probes should be held back
+    #[inline]
+    pub fn is_suppressed(&self) -> bool {
+        !self.is_handshake_confirmed
+    }
+
+    /// Transmits a pending PING frame, unless it is held back
+    #[inline]
+    pub fn on_transmit_ping<W: WriteContext>(&self, ping: &mut Ping, context: &mut W) {
+        if !self.is_suppressed() {
+            let _ = ping.on_transmit(context);
+        }
+    }
+
+    /// Expresses interest in transmitting a pending PING frame, unless it is held back
+    #[inline]
+    pub fn ping_interest<Q: transmission::interest::Query>(
+        &self,
+        ping: &Ping,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        if self.is_suppressed() {
+            return Ok(());
+        }
+        ping.transmission_interest(query)
+    }
+}
+
 pub struct MtuProbe<'a> {
     mtu_controller: &'a mut mtu::Controller,
 }
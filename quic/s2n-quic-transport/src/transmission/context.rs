@@ -1,14 +1,18 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{contexts::WriteContext, endpoint, path, transmission, transmission::Mode};
-use core::marker::PhantomData;
+use crate::{
+    contexts::WriteContext, endpoint, path, recovery::SentFrames, transmission, transmission::Mode,
+};
+use core::{marker::PhantomData, time::Duration};
 use s2n_codec::{Encoder, EncoderBuffer, EncoderValue};
 use s2n_quic_core::{
     event::{self, ConnectionPublisher as _, IntoEvent},
     frame::{ack_elicitation::AckElicitation, FrameTrait},
     packet::number::PacketNumber,
+    stream::StreamId,
     time::Timestamp,
+    varint::VarInt,
 };
 
 pub struct Context<'a, 'b, 'sub, Config: endpoint::Config> {
@@ -26,9 +30,27 @@ pub struct Context<'a, 'b, 'sub, Config: endpoint::Config> {
         'sub,
         <Config as endpoint::Config>::EventSubscriber,
     >,
+    pub sent_frames: &'a mut SentFrames,
 }
 
 impl<'a, 'b, 'sub, Config: endpoint::Config> Context<'a, 'b, 'sub, Config> {
+    #[inline]
+    fn on_frame_sent(&mut self, frame: event::builder::Frame) {
+        if self.publisher.tracks_lost_frames() {
+            self.sent_frames
+                .on_frame_sent(&frame, self.packet_number.space());
+        }
+
+        self.publisher.on_frame_sent(event::builder::FrameSent {
+            packet_header: event::builder::PacketHeader::new(
+                self.packet_number,
+                self.publisher.quic_version(),
+            ),
+            path_id: self.path_id.into_event(),
+            frame,
+        });
+    }
+
     #[inline]
     fn check_frame_constraint<Frame: FrameTrait>(&self, frame: &Frame) {
         // only apply checks with debug_assertions enabled
@@ -104,14 +126,7 @@ impl<'a, 'b, 'sub, Config: endpoint::Config> WriteContext for Context<'a, 'b, 's
         self.outcome.ack_elicitation |= frame.ack_elicitation();
         self.outcome.is_congestion_controlled |= frame.is_congestion_controlled();
 
-        self.publisher.on_frame_sent(event::builder::FrameSent {
-            packet_header: event::builder::PacketHeader::new(
-                self.packet_number,
-                self.publisher.quic_version(),
-            ),
-            path_id: self.path_id.into_event(),
-            frame: frame.into_event(),
-        });
+        self.on_frame_sent(frame.into_event());
         self.packet_number
     }
 
@@ -128,14 +143,7 @@ impl<'a, 'b, 'sub, Config: endpoint::Config> WriteContext for Context<'a, 'b, 's
         self.outcome.ack_elicitation |= frame.ack_elicitation();
         self.outcome.is_congestion_controlled |= frame.is_congestion_controlled();
 
-        self.publisher.on_frame_sent(event::builder::FrameSent {
-            packet_header: event::builder::PacketHeader::new(
-                self.packet_number,
-                self.publisher.quic_version(),
-            ),
-            path_id: self.path_id.into_event(),
-            frame: frame.into_event(),
-        });
+        self.on_frame_sent(frame.into_event());
         Some(self.packet_number)
     }
 
@@ -163,6 +171,44 @@ impl<'a, 'b, 'sub, Config: endpoint::Config> WriteContext for Context<'a, 'b, 's
     fn tag_len(&self) -> usize {
         self.tag_len
     }
+
+    #[inline]
+    fn on_stream_blocked(&mut self, stream_id: StreamId, limit: VarInt, pending_len: VarInt) {
+        self.publisher
+            .on_stream_blocked(event::builder::StreamBlocked {
+                stream_id: stream_id.as_varint().as_u64(),
+                limit: limit.as_u64(),
+                pending_len: pending_len.as_u64(),
+            });
+    }
+
+    #[inline]
+    fn on_stream_unblocked(
+        &mut self,
+        stream_id: StreamId,
+        limit: VarInt,
+        blocked_duration: Duration,
+    ) {
+        self.publisher
+            .on_stream_unblocked(event::builder::StreamUnblocked {
+                stream_id: stream_id.as_varint().as_u64(),
+                limit: limit.as_u64(),
+                blocked_duration,
+            });
+    }
+
+    #[inline]
+    fn on_connection_blocked(&mut self, limit: VarInt) {
+        self.publisher
+            .on_connection_blocked(event::builder::ConnectionBlocked {
+                limit: limit.as_u64(),
+            });
+    }
+
+    #[inline]
+    fn on_retransmission(&mut self, original_packet_number: PacketNumber) {
+        self.sent_frames.on_retransmission(original_packet_number);
+    }
 }
 
 // Overrides a context's transmission constraint to allow only retransmissions to be written to
@@ -253,4 +299,156 @@ impl<'a, C: WriteContext> WriteContext for RetransmissionContext<'a, C> {
     fn tag_len(&self) -> usize {
         self.context.tag_len()
     }
+
+    #[inline]
+    fn on_stream_blocked(&mut self, stream_id: StreamId, limit: VarInt, pending_len: VarInt) {
+        self.context
+            .on_stream_blocked(stream_id, limit, pending_len)
+    }
+
+    #[inline]
+    fn on_stream_unblocked(
+        &mut self,
+        stream_id: StreamId,
+        limit: VarInt,
+        blocked_duration: Duration,
+    ) {
+        self.context
+            .on_stream_unblocked(stream_id, limit, blocked_duration)
+    }
+
+    #[inline]
+    fn on_connection_blocked(&mut self, limit: VarInt) {
+        self.context.on_connection_blocked(limit)
+    }
+
+    #[inline]
+    fn on_retransmission(&mut self, original_packet_number: PacketNumber) {
+        self.context.on_retransmission(original_packet_number)
+    }
+}
+
+// Limits the number of bytes that can be written to a context's packet
+pub struct RateLimitedContext<'a, C: WriteContext> {
+    context: &'a mut C,
+    /// The capacity of the underlying context that may not be written to
+    reserved_capacity: usize,
+}
+
+impl<'a, C: WriteContext> RateLimitedContext<'a, C> {
+    pub fn new(context: &'a mut C, limit: usize) -> Self {
+        let reserved_capacity = context.remaining_capacity().saturating_sub(limit);
+        Self {
+            context,
+            reserved_capacity,
+        }
+    }
+}
+
+impl<'a, C: WriteContext> WriteContext for RateLimitedContext<'a, C> {
+    #[inline]
+    fn current_time(&self) -> Timestamp {
+        self.context.current_time()
+    }
+
+    #[inline]
+    fn transmission_constraint(&self) -> transmission::Constraint {
+        self.context.transmission_constraint()
+    }
+
+    #[inline]
+    fn transmission_mode(&self) -> Mode {
+        self.context.transmission_mode()
+    }
+
+    #[inline]
+    fn remaining_capacity(&self) -> usize {
+        self.context
+            .remaining_capacity()
+            .saturating_sub(self.reserved_capacity)
+    }
+
+    #[inline]
+    fn write_frame<Frame>(&mut self, frame: &Frame) -> Option<PacketNumber>
+    where
+        Frame: EncoderValue + FrameTrait,
+        for<'frame> &'frame Frame: IntoEvent<event::builder::Frame>,
+    {
+        if frame.encoding_size() > self.remaining_capacity() {
+            return None;
+        }
+
+        self.context.write_frame(frame)
+    }
+
+    #[inline]
+    fn write_fitted_frame<Frame>(&mut self, frame: &Frame) -> PacketNumber
+    where
+        Frame: EncoderValue + FrameTrait,
+        for<'frame> &'frame Frame: IntoEvent<event::builder::Frame>,
+    {
+        debug_assert!(frame.encoding_size() <= self.remaining_capacity());
+
+        self.context.write_fitted_frame(frame)
+    }
+
+    fn write_frame_forced<Frame>(&mut self, frame: &Frame) -> Option<PacketNumber>
+    where
+        Frame: EncoderValue + FrameTrait,
+        for<'frame> &'frame Frame: IntoEvent<event::builder::Frame>,
+    {
+        self.context.write_frame_forced(frame)
+    }
+
+    #[inline]
+    fn ack_elicitation(&self) -> AckElicitation {
+        self.context.ack_elicitation()
+    }
+
+    #[inline]
+    fn packet_number(&self) -> PacketNumber {
+        self.context.packet_number()
+    }
+
+    #[inline]
+    fn local_endpoint_type(&self) -> endpoint::Type {
+        self.context.local_endpoint_type()
+    }
+
+    #[inline]
+    fn header_len(&self) -> usize {
+        self.context.header_len()
+    }
+
+    #[inline]
+    fn tag_len(&self) -> usize {
+        self.context.tag_len()
+    }
+
+    #[inline]
+    fn on_stream_blocked(&mut self, stream_id: StreamId, limit: VarInt, pending_len: VarInt) {
+        self.context
+            .on_stream_blocked(stream_id, limit, pending_len)
+    }
+
+    #[inline]
+    fn on_stream_unblocked(
+        &mut self,
+        stream_id: StreamId,
+        limit: VarInt,
+        blocked_duration: Duration,
+    ) {
+        self.context
+            .on_stream_unblocked(stream_id, limit, blocked_duration)
+    }
+
+    #[inline]
+    fn on_connection_blocked(&mut self, limit: VarInt) {
+        self.context.on_connection_blocked(limit)
+    }
+
+    #[inline]
+    fn on_retransmission(&mut self, original_packet_number: PacketNumber) {
+        self.context.on_retransmission(original_packet_number)
+    }
 }
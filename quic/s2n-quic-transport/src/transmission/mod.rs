@@ -16,7 +16,7 @@ pub use interest::Interest;
 pub use s2n_quic_core::transmission::*;
 
 use crate::{
-    endpoint, path,
+    endpoint, path, recovery,
     space::TxPacketNumbers,
     transmission::{self, interest::Provider as _},
 };
@@ -54,6 +54,36 @@ pub struct Transmission<'a, 'sub, Config: endpoint::Config, P: Payload> {
         <Config as endpoint::Config>::EventSubscriber,
     >,
     pub packet_interceptor: &'a mut <Config as endpoint::Config>::PacketInterceptor,
+    pub sent_frames: &'a mut recovery::SentFrames,
+    pub traffic_shaping: Option<Shaping>,
+}
+
+/// Pads packets so the datagram carrying them matches the connection's traffic shaping
+/// configuration
+#[derive(Clone, Copy, Debug)]
+pub struct Shaping {
+    pub config: TrafficShapingConfig,
+    /// The number of bytes available for the entire datagram
+    pub datagram_capacity: usize,
+    /// The packet number space of the last packet in the datagram
+    ///
+    /// Only the last packet is padded, so the packets before it leave room to be coalesced.
+    pub last_space: PacketNumberSpace,
+}
+
+impl Shaping {
+    /// Returns `None` if the configuration doesn't require any padding
+    pub fn new(config: TrafficShapingConfig) -> Option<Self> {
+        if !config.is_enabled() {
+            return None;
+        }
+
+        Some(Self {
+            config,
+            datagram_capacity: 0,
+            last_space: PacketNumberSpace::ApplicationData,
+        })
+    }
 }
 
 impl<'a, 'sub, Config: endpoint::Config, P: Payload> PacketPayloadEncoder
@@ -91,6 +121,7 @@ impl<'a, 'sub, Config: endpoint::Config, P: Payload> PacketPayloadEncoder
             config: Default::default(),
             path_id: self.path_id,
             publisher: self.publisher,
+            sent_frames: self.sent_frames,
         };
 
         self.payload.on_transmit(&mut context);
@@ -106,6 +137,21 @@ impl<'a, 'sub, Config: endpoint::Config, P: Payload> PacketPayloadEncoder
                 length = remaining_capacity;
             }
 
+            let packet_number_space = self.payload.packet_number_space();
+            if let Some(shaping) = self
+                .traffic_shaping
+                .filter(|shaping| shaping.last_space == packet_number_space)
+            {
+                // The payload buffer ends where the crypto tag begins, so the datagram ends at
+                // the capacity minus whatever remains in the payload buffer after padding.
+                let remaining_capacity = remaining_capacity.saturating_sub(length);
+                let datagram_len = shaping.datagram_capacity.saturating_sub(remaining_capacity);
+                let padded_len = shaping
+                    .config
+                    .padded_len(datagram_len, shaping.datagram_capacity);
+                length += (padded_len - datagram_len).min(remaining_capacity);
+            }
+
             if length > 0 {
                 // Use `write_frame_forced` to bypass congestion controller checks
                 // since we still want to send this packet despite Padding being
@@ -12,8 +12,10 @@ use core::{
     task::{Context, Poll},
 };
 use s2n_quic_transport::endpoint::{connect, handle::Connector};
+use std::sync::Arc;
 
 mod builder;
+mod pool;
 mod providers;
 
 pub use builder::*;
@@ -25,6 +27,7 @@ pub use providers::*;
 pub struct Client {
     connector: Connector,
     local_addr: s2n_quic_core::inet::SocketAddress,
+    pool: Option<Arc<pool::ConnectionPool>>,
 }
 
 impl fmt::Debug for Client {
@@ -86,6 +89,10 @@ impl Client {
 
     /// Establishes a connection to the specified endpoint
     ///
+    /// If the client was built with
+    /// [`Builder::coalesce_connections`](crate::client::Builder::coalesce_connections), the
+    /// returned connection may be shared with other callers.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -106,8 +113,13 @@ impl Client {
     /// # }
     /// ```
     pub fn connect(&self, connect: Connect) -> ConnectionAttempt {
+        if let Some(pool) = self.pool.as_ref() {
+            let attempt = pool.connect(&self.connector, connect);
+            return ConnectionAttempt(AttemptState::Pooled(attempt));
+        }
+
         let attempt = self.connector.connect(connect);
-        ConnectionAttempt(attempt)
+        ConnectionAttempt(AttemptState::Connect(attempt))
     }
 
     /// Wait for the client endpoint to finish handling all outstanding connections
@@ -169,13 +181,25 @@ impl Client {
 }
 
 #[must_use = "futures do nothing unless you `.await` or poll them"]
-pub struct ConnectionAttempt(connect::Attempt);
+pub struct ConnectionAttempt(AttemptState);
+
+enum AttemptState {
+    /// The attempt opens a new connection
+    Connect(connect::Attempt),
+    /// The attempt may be shared with other callers
+    Pooled(pool::Attempt),
+}
 
 impl Future for ConnectionAttempt {
     type Output = Result<Connection, connection::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match Pin::new(&mut self.0).poll(cx) {
+        let result = match &mut self.0 {
+            AttemptState::Connect(attempt) => Pin::new(attempt).poll(cx),
+            AttemptState::Pooled(attempt) => Pin::new(attempt).poll(cx),
+        };
+
+        match result {
             Poll::Ready(Ok(conn)) => Poll::Ready(Ok(Connection::new(conn))),
             Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
             Poll::Pending => Poll::Pending,
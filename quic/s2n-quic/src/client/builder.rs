@@ -2,17 +2,41 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    client::{Client, ClientProviders, DefaultProviders},
+    client::{pool, Client, ClientProviders, DefaultProviders},
     provider::*,
 };
+use std::sync::Arc;
 
 /// A builder for configuring [`Client`] providers
 #[derive(Debug)]
-pub struct Builder<Providers>(pub(crate) Providers);
+pub struct Builder<Providers>(pub(crate) Providers, pub(crate) Options);
 
 impl Default for Builder<DefaultProviders> {
     fn default() -> Self {
-        Self(Default::default())
+        Self(Default::default(), Default::default())
+    }
+}
+
+/// Client options which aren't configured by a provider
+#[derive(Debug)]
+pub(crate) struct Options {
+    coalesce_connections: bool,
+    max_streams_per_connection: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            coalesce_connections: false,
+            max_streams_per_connection: pool::DEFAULT_MAX_STREAMS_PER_CONNECTION,
+        }
+    }
+}
+
+impl<Providers> Builder<Providers> {
+    #[inline]
+    pub(crate) fn map_providers<F: FnOnce(Providers) -> P, P>(self, f: F) -> Builder<P> {
+        Builder(f(self.0), self.1)
     }
 }
 
@@ -272,6 +296,52 @@ impl<Providers: ClientProviders> Builder<Providers> {
         ClientProviders
     );
 
+    /// Shares connections to the same server between calls to [`Client::connect`]
+    ///
+    /// When enabled, connection attempts with the same remote address and server name return a
+    /// handle to an existing connection instead of performing another handshake, as long as that
+    /// connection has fewer open streams and fewer handles than the
+    /// [`max_streams_per_connection`](Self::with_max_streams_per_connection) limit. Each caller
+    /// opens its own streams on the shared connection. Streams opened by the peer are delivered
+    /// to whichever handle accepts them first.
+    ///
+    /// Shared connections are closed once all of their handles are dropped, the same as
+    /// connections which aren't shared.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::{error::Error, path::Path};
+    /// # use s2n_quic::Client;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let client = Client::builder()
+    ///     .coalesce_connections(true)
+    ///     .with_tls(Path::new("./certs/cert.pem"))?
+    ///     .with_io("0.0.0.0:0")?
+    ///     .start()?;
+    /// #
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn coalesce_connections(mut self, enabled: bool) -> Self {
+        self.1.coalesce_connections = enabled;
+        self
+    }
+
+    /// Sets the number of open streams at which a shared connection is no longer reused
+    ///
+    /// A shared connection is considered to have at least as many streams as it has handles,
+    /// counting callers which are still waiting for the handshake. Once all of the connections to
+    /// a server have reached the limit, the next call to [`Client::connect`] opens another
+    /// connection. This only applies if
+    /// [`coalesce_connections`](Self::coalesce_connections) is enabled. Defaults to 100.
+    pub fn with_max_streams_per_connection(mut self, limit: usize) -> Self {
+        self.1.max_streams_per_connection = limit;
+        self
+    }
+
     /// Starts the [`Client`] with the configured providers
     ///
     /// # Examples
@@ -291,6 +361,14 @@ impl<Providers: ClientProviders> Builder<Providers> {
     /// # }
     /// ```
     pub fn start(self) -> Result<Client, StartError> {
-        self.0.build().start()
+        let Self(providers, options) = self;
+        let mut client = providers.build().start()?;
+
+        if options.coalesce_connections {
+            let pool = pool::ConnectionPool::new(options.max_streams_per_connection);
+            client.pool = Some(Arc::new(pool));
+        }
+
+        Ok(client)
     }
 }
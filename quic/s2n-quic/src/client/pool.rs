@@ -0,0 +1,228 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shares connections to the same server between connection attempts
+//!
+//! Connections are keyed by the remote address and server name of the attempt. The client
+//! offers the same ALPN protocols on every connection, so connections to the same address and
+//! name always negotiate the same protocol.
+
+use crate::connection;
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use s2n_quic_core::inet::SocketAddress;
+use s2n_quic_transport::{
+    connection::{Connection as Inner, WeakConnection},
+    endpoint::{connect::Connect, handle::Connector},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The default maximum number of open streams on a connection before another one is opened
+///
+/// This matches the default number of concurrent bidirectional streams an s2n-quic server
+/// allows its peer to open.
+pub const DEFAULT_MAX_STREAMS_PER_CONNECTION: usize = 100;
+
+type SharedAttempt = Shared<BoxFuture<'static, Result<Inner, connection::Error>>>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    remote_address: SocketAddress,
+    server_name: Option<String>,
+}
+
+impl Key {
+    fn new(connect: &Connect) -> Self {
+        Self {
+            remote_address: connect.remote_address(),
+            server_name: connect.server_name().map(|name| name.to_string()),
+        }
+    }
+}
+
+enum Entry {
+    /// The handshake is still in progress
+    Connecting {
+        attempt: SharedAttempt,
+        /// The number of callers waiting on the attempt
+        callers: usize,
+    },
+    /// The connection was established
+    ///
+    /// The pool doesn't keep the connection open, so it's closed as soon as the last
+    /// application handle is dropped.
+    Established(WeakConnection),
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connecting { callers, .. } => f
+                .debug_struct("Connecting")
+                .field("callers", callers)
+                .finish(),
+            Self::Established(connection) => {
+                f.debug_tuple("Established").field(connection).finish()
+            }
+        }
+    }
+}
+
+impl Entry {
+    /// Returns the number of streams the connection is considered to carry, or `None` if it
+    /// can no longer be used
+    fn load(&mut self) -> Option<usize> {
+        if let Self::Connecting { attempt, callers } = self {
+            let connection = match attempt.peek() {
+                None => return Some(*callers),
+                Some(Ok(connection)) => connection.downgrade(),
+                Some(Err(_)) => return None,
+            };
+            *self = Self::Established(connection);
+        }
+
+        if let Self::Established(connection) = self {
+            let connection = connection.upgrade()?;
+            let streams = connection.active_stream_count().ok()?;
+            // don't count the handle which was just upgraded
+            let handles = connection.handle_count() - 1;
+            return Some(streams.max(handles));
+        }
+
+        None
+    }
+}
+
+type Connections = Arc<Mutex<HashMap<Key, Vec<Entry>>>>;
+
+/// The connections which are shared by a client
+///
+/// The pool only holds weak references to its connections, so they are closed once the
+/// applications drop all of their handles.
+#[derive(Debug)]
+pub(crate) struct ConnectionPool {
+    max_streams_per_connection: usize,
+    connections: Connections,
+}
+
+impl ConnectionPool {
+    pub fn new(max_streams_per_connection: usize) -> Self {
+        Self {
+            max_streams_per_connection,
+            connections: Default::default(),
+        }
+    }
+
+    /// Returns an attempt for a connection to the server in `connect`
+    ///
+    /// An existing connection is reused while both its number of open streams and its number of
+    /// application handles are below `max_streams_per_connection`. Attempts which are still in
+    /// progress are reused as well, up to the same number of callers, so concurrent callers wait
+    /// for the same handshake instead of each starting their own.
+    pub fn connect(&self, connector: &Connector, connect: Connect) -> Attempt {
+        let key = Key::new(&connect);
+        let mut connections = self.connections.lock().unwrap();
+        let entries = connections.entry(key.clone()).or_default();
+
+        let mut reusable = None;
+        let mut index = 0;
+        while index < entries.len() {
+            match entries[index].load() {
+                Some(load) => {
+                    if reusable.is_none() && load < self.max_streams_per_connection {
+                        reusable = Some(index);
+                    }
+                    index += 1;
+                }
+                // the connection was closed
+                None => {
+                    entries.remove(index);
+                }
+            }
+        }
+
+        let state = reusable.and_then(|index| match &mut entries[index] {
+            Entry::Connecting { attempt, callers } => {
+                *callers += 1;
+                Some(AttemptState::Connecting(attempt.clone()))
+            }
+            // the last handle may have been dropped since the entry was checked
+            Entry::Established(connection) => connection.upgrade().map(AttemptState::Ready),
+        });
+
+        let state = state.unwrap_or_else(|| {
+            let attempt = connector.connect(connect).boxed().shared();
+            entries.push(Entry::Connecting {
+                attempt: attempt.clone(),
+                callers: 1,
+            });
+            AttemptState::Connecting(attempt)
+        });
+
+        Attempt {
+            state,
+            key,
+            connections: self.connections.clone(),
+        }
+    }
+}
+
+/// An attempt for a connection which may be shared with other callers
+pub(crate) struct Attempt {
+    state: AttemptState,
+    key: Key,
+    connections: Connections,
+}
+
+enum AttemptState {
+    Connecting(SharedAttempt),
+    Ready(Inner),
+}
+
+impl Attempt {
+    /// Replaces the pool's reference to the attempt with a weak reference to the connection
+    ///
+    /// Otherwise the result of the attempt would keep the connection open until the pool is
+    /// used again.
+    fn on_established(&self, attempt: &SharedAttempt, connection: &Inner) {
+        let mut connections = self.connections.lock().unwrap();
+        let entries = match connections.get_mut(&self.key) {
+            Some(entries) => entries,
+            None => return,
+        };
+
+        for entry in entries.iter_mut() {
+            if let Entry::Connecting { attempt: other, .. } = entry {
+                if other.ptr_eq(attempt) {
+                    *entry = Entry::Established(connection.downgrade());
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Future for Attempt {
+    type Output = Result<Inner, connection::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = match &mut self.state {
+            AttemptState::Connecting(attempt) => futures::ready!(Pin::new(attempt).poll(cx)),
+            AttemptState::Ready(connection) => return Poll::Ready(Ok(connection.clone())),
+        };
+
+        if let (Ok(connection), AttemptState::Connecting(attempt)) = (&result, &self.state) {
+            self.on_established(attempt, connection);
+        }
+
+        Poll::Ready(result)
+    }
+}
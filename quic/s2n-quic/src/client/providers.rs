@@ -127,6 +127,7 @@ impl<
         Ok(Client {
             connector,
             local_addr,
+            pool: None,
         })
     }
 }
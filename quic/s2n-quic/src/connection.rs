@@ -11,7 +11,7 @@ mod handle;
 
 pub use acceptor::*;
 pub use handle::*;
-pub use s2n_quic_core::connection::Error;
+pub use s2n_quic_core::connection::{ConnectionTimeoutReason, Error};
 
 pub mod error {
     pub use s2n_quic_core::transport::error::Code;
@@ -36,6 +36,12 @@ impl Connection {
     impl_accept_api!();
     impl_handle_api!(|handle, call| call!(handle));
 
+    /// Closes the connection with a transport error
+    #[inline]
+    pub(crate) fn close_with_transport_error(&self, error: s2n_quic_core::transport::Error) {
+        self.0.close_with_transport_error(error)
+    }
+
     /// Returns a cloneable handle to the connection
     ///
     /// # Examples
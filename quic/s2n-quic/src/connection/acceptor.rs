@@ -65,6 +65,67 @@ macro_rules! impl_accept_api {
             .into()
         }
 
+        /// Accepts an incoming [`PeerStream`](`crate::stream::PeerStream`), waiting until
+        /// `timeout` completes for a stream to arrive
+        ///
+        /// `timeout` is a timer future from the runtime the endpoint runs on, such as
+        /// `tokio::time::sleep` for the default IO provider, so the timeout follows the same
+        /// clock as the connection.
+        ///
+        /// The method will return
+        /// - `Ok(stream)` if a [`PeerStream`](`crate::stream::PeerStream`) was accepted
+        /// - `Err(AcceptError::Timeout)` if no stream was accepted before the timeout elapsed
+        /// - `Err(AcceptError::ConnectionClosed(_))` if the connection was closed
+        ///
+        /// Timing out does not affect streams opened by the peer. A stream which arrives after
+        /// the timeout elapsed remains queued and is returned by the next call to accept.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # use std::time::Duration;
+        /// # async fn test() {
+        /// #   let mut acceptor: s2n_quic::connection::StreamAcceptor = todo!();
+        /// #
+        /// let timeout = tokio::time::sleep(Duration::from_secs(5));
+        /// match acceptor.accept_stream_timeout(timeout).await {
+        ///     Ok(stream) => println!("Stream opened from {:?}", stream.connection().remote_addr()),
+        ///     Err(error) => println!("no stream was accepted: {}", error),
+        /// }
+        /// #
+        /// # }
+        /// ```
+        #[inline]
+        pub async fn accept_stream_timeout<Timeout>(
+            &mut self,
+            timeout: Timeout,
+        ) -> Result<$crate::stream::PeerStream, $crate::connection::AcceptError>
+        where
+            Timeout: core::future::Future<Output = ()>,
+        {
+            use core::{future::Future, task::Poll};
+            use $crate::connection::AcceptError;
+
+            ::futures::pin_mut!(timeout);
+
+            ::futures::future::poll_fn(|cx| {
+                // `poll_accept` only removes a stream from the accept queue when returning it,
+                // so giving up after the timeout elapsed can't lose a stream.
+                match self.poll_accept(cx) {
+                    Poll::Ready(Ok(Some(stream))) => Poll::Ready(Ok(stream)),
+                    Poll::Ready(Ok(None)) => Poll::Ready(Err(AcceptError::ConnectionClosed(None))),
+                    Poll::Ready(Err(error)) => {
+                        Poll::Ready(Err(AcceptError::ConnectionClosed(Some(error))))
+                    }
+                    Poll::Pending => timeout
+                        .as_mut()
+                        .poll(cx)
+                        .map(|()| Err(AcceptError::Timeout)),
+                }
+            })
+            .await
+        }
+
         impl_accept_bidirectional_api!();
         impl_accept_receive_api!();
     };
@@ -179,6 +240,31 @@ macro_rules! impl_accept_receive_api {
     };
 }
 
+/// The error returned by `accept_stream_timeout`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AcceptError {
+    /// No stream was accepted before the timeout elapsed
+    Timeout,
+    /// The connection was closed
+    ///
+    /// Contains the error which closed the connection, or `None` if the connection was
+    /// closed without an error.
+    ConnectionClosed(Option<connection::Error>),
+}
+
+impl core::fmt::Display for AcceptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "timed out while waiting for a stream"),
+            Self::ConnectionClosed(Some(error)) => write!(f, "{}", error),
+            Self::ConnectionClosed(None) => write!(f, "the connection was closed"),
+        }
+    }
+}
+
+impl std::error::Error for AcceptError {}
+
 #[derive(Debug)]
 pub struct StreamAcceptor(pub(crate) s2n_quic_transport::connection::Connection);
 
@@ -186,6 +186,166 @@ macro_rules! impl_handle_api {
             self.0.keep_alive(enabled)
         }
 
+        /// Starts probing the bandwidth available on the active path for the given `duration`
+        ///
+        /// While the probe is running, the capacity left in each packet after application data
+        /// is filled with PADDING. The padding does not consume any stream flow control credits.
+        /// The delivery rate is measured once per round trip and, once the probe completes, the
+        /// results are available from [`Self::bandwidth_estimate`].
+        ///
+        /// A typical probe runs for
+        /// [`DEFAULT_BANDWIDTH_PROBE_DURATION`](s2n_quic_core::recovery::bandwidth::DEFAULT_BANDWIDTH_PROBE_DURATION).
+        #[inline]
+        pub fn probe_bandwidth(
+            &mut self,
+            duration: core::time::Duration,
+        ) -> $crate::connection::Result<()> {
+            self.0.probe_bandwidth(duration)
+        }
+
+        /// Returns the summary of the delivery rates measured by the last completed bandwidth probe
+        ///
+        /// `None` is returned if no probe has completed on the active path.
+        #[inline]
+        pub fn bandwidth_estimate(
+            &self,
+        ) -> $crate::connection::Result<
+            Option<s2n_quic_core::recovery::bandwidth::BandwidthEstimate>,
+        > {
+            self.0.bandwidth_estimate()
+        }
+
+        /// Limits the rate at which all of the streams on the connection combined send data
+        ///
+        /// The connection maintains a token bucket that is filled at `bandwidth`. Every stream
+        /// draws from the bucket when it writes data to a packet, and while the bucket is empty
+        /// no stream is able to send new data, even if its own flow control limits allow it.
+        /// Retransmissions of lost data are not limited.
+        ///
+        /// Passing [`Bandwidth::MAX`](s2n_quic_core::recovery::bandwidth::Bandwidth::MAX)
+        /// removes the limit.
+        #[inline]
+        pub fn set_send_bandwidth(
+            &mut self,
+            bandwidth: s2n_quic_core::recovery::bandwidth::Bandwidth,
+        ) -> $crate::connection::Result<()> {
+            self.0.set_send_bandwidth(bandwidth)
+        }
+
+        /// Returns the fraction of packets that were lost
+        ///
+        /// The rate is averaged over the most recent round trips, as configured by
+        /// [`Limits::with_loss_window`](s2n_quic_core::connection::limits::Limits::with_loss_window),
+        /// and is updated at the end of each round trip. Reading it does not acquire the
+        /// connection lock.
+        #[inline]
+        pub fn packet_loss_rate(&self) -> f64 {
+            self.0.loss_rates().packet_loss_rate
+        }
+
+        /// Returns the number of loss bursts divided by the number of sent packets
+        ///
+        /// A loss burst is a run of one or more consecutively numbered lost packets. Comparing this
+        /// to [`Self::packet_loss_rate`] indicates whether packets are lost individually or in
+        /// bursts. Like the packet loss rate, it is updated at the end of each round trip.
+        #[inline]
+        pub fn burst_loss_rate(&self) -> f64 {
+            self.0.loss_rates().burst_loss_rate
+        }
+
+        /// Returns a snapshot of the histogram of RTT samples, in microseconds
+        ///
+        /// The histograms are only maintained if enabled with
+        /// [`Limits::with_histograms`](s2n_quic_core::connection::limits::Limits::with_histograms).
+        /// `None` is returned otherwise, or if the handshake has not completed yet.
+        #[inline]
+        pub fn rtt_histogram(
+            &self,
+        ) -> $crate::connection::Result<Option<s2n_quic_core::connection::Histogram>> {
+            self.0.rtt_histogram()
+        }
+
+        /// Returns a snapshot of the histogram of the number of bytes sent per second
+        ///
+        /// Seconds in which the connection didn't send anything are not recorded. Like
+        /// [`Self::rtt_histogram`], `None` is returned if the histograms are not enabled.
+        #[inline]
+        pub fn throughput_histogram(
+            &self,
+        ) -> $crate::connection::Result<Option<s2n_quic_core::connection::Histogram>> {
+            self.0.throughput_histogram()
+        }
+
+        /// Removes all of the values recorded in the RTT and throughput histograms
+        ///
+        /// Calling [`Histogram::reset`](s2n_quic_core::connection::Histogram::reset) on a
+        /// snapshot only clears the snapshot.
+        #[inline]
+        pub fn reset_histograms(&mut self) -> $crate::connection::Result<()> {
+            self.0.reset_histograms()
+        }
+
+        /// Returns the number of bytes which can be received before the receive budget is used up
+        ///
+        /// The budget counts the received bytes which the application has not read yet across
+        /// all streams, and is configured with
+        /// [`Limits::with_max_buffered_recv_bytes`](s2n_quic_core::connection::limits::Limits::with_max_buffered_recv_bytes).
+        /// `u64::MAX` is returned if no budget is configured.
+        #[inline]
+        pub fn recv_budget_remaining(&self) -> $crate::connection::Result<u64> {
+            self.0.recv_budget_remaining()
+        }
+
+        /// Returns the transfer counters of all of the streams which have been closed
+        ///
+        /// Each stream keeps its own counters while it is open. They are added to the
+        /// connection totals once the stream is closed and all of its data has been delivered, so
+        /// streams which are still open are not included.
+        #[inline]
+        pub fn stream_metrics(&self) -> $crate::connection::Result<$crate::stream::MetricsTotals> {
+            self.0.stream_metrics()
+        }
+
+        /// Returns the number of STREAM frame bytes sent per byte of new stream data
+        ///
+        /// The wire bytes include the frame headers and retransmissions of all streams, so the
+        /// ratio grows as packets are lost. Packets which only carry acknowledgements or other
+        /// control frames are not counted.
+        #[inline]
+        pub fn write_amplification(
+            &self,
+        ) -> $crate::connection::Result<$crate::stream::WriteAmplification> {
+            self.0.write_amplification()
+        }
+
+        /// Resets the cumulative statistics of the connection to zero
+        ///
+        /// This clears the [`Self::stream_metrics`] totals, the [`Self::write_amplification`]
+        /// counters and the histograms, so applications can measure the statistics over a
+        /// window of their choosing. Gauges, such as the loss rates, are not affected.
+        ///
+        /// The statistics are updated behind the connection lock, so each update is counted
+        /// either before or after the reset. Streams which are still open keep their own
+        /// counters and are added to the new totals once they close.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// let totals = connection.stream_metrics()?;
+        /// println!("bytes sent in the last window: {}", totals.bytes_sent);
+        /// connection.reset_stats()?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn reset_stats(&mut self) -> $crate::connection::Result<()> {
+            self.0.reset_stats()
+        }
+
         /// Closes the Connection with the provided error code
         ///
         /// This will immediately terminate all outstanding streams.
@@ -207,6 +367,39 @@ macro_rules! impl_handle_api {
             self.0.close(error_code)
         }
 
+        /// Gracefully closes the Connection and waits for it to finish closing
+        ///
+        /// All outstanding streams are flushed before the peer is notified with a
+        /// CONNECTION_CLOSE frame carrying the `NO_ERROR` code. Dropping the last handle of a
+        /// Connection closes it in the same way, but does not allow the application to wait
+        /// for it.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> s2n_quic::connection::Result<()> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// connection.close_and_flush().await;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub async fn close_and_flush(&self) {
+            futures::future::poll_fn(|cx| self.poll_close_and_flush(cx)).await
+        }
+
+        /// Polls gracefully closing the Connection
+        ///
+        /// The method will return
+        /// - `Poll::Ready(())` if the connection has finished flushing and is closing
+        /// - `Poll::Pending` if the connection is still flushing its outstanding streams
+        #[inline]
+        pub fn poll_close_and_flush(&self, cx: &mut core::task::Context) -> core::task::Poll<()> {
+            self.0.poll_close_and_flush(cx)
+        }
+
         /// API for querying the connection's
         /// [`Subscriber::ConnectionContext`](crate::provider::event::Subscriber::ConnectionContext).
         ///
@@ -20,16 +20,35 @@ impl_provider_utils!();
 
 pub mod cubic {
     use s2n_quic_core::recovery::cubic::Endpoint;
+    pub use s2n_quic_core::recovery::delay_gradient::DEFAULT_THRESHOLD;
 
     #[derive(Debug, Default)]
-    pub struct Provider(());
+    pub struct Provider {
+        endpoint: Endpoint,
+    }
+
+    impl Provider {
+        /// Reduces the congestion window by 10% when the round-trip time increases steeply
+        ///
+        /// The delay gradient is the increase in RTT divided by the time over which it increased,
+        /// measured over roughly one round trip. When it exceeds `threshold`, the bottleneck
+        /// queue is filling up and packets are likely to be lost soon, so the window is reduced
+        /// before that happens. The reduction is smaller than the one in response to a loss and
+        /// the window grows back more quickly.
+        ///
+        /// [`DEFAULT_THRESHOLD`] is a threshold of 0.2ms of RTT increase per 1ms.
+        pub fn with_delay_gradient_threshold(mut self, threshold: f32) -> Self {
+            self.endpoint = self.endpoint.with_delay_gradient_threshold(threshold);
+            self
+        }
+    }
 
     impl super::Provider for Provider {
         type Endpoint = Endpoint;
         type Error = core::convert::Infallible;
 
         fn start(self) -> Result<Self::Endpoint, Self::Error> {
-            Ok(Endpoint::default())
+            Ok(self.endpoint)
         }
     }
 }
@@ -206,3 +206,283 @@ pub mod default {
         }
     }
 }
+
+pub mod structured {
+    //! Connection IDs which encode a server ID
+    //!
+    //! The format is described in [`s2n_quic_core::connection::id::structured`]. Load
+    //! balancers recover the server ID with a [`Decoder`] to route packets to the server that
+    //! issued the connection ID.
+    //!
+    //! Servers which are split into shards behind a
+    //! [`ShardRouter`](s2n_quic_platform::shard::ShardRouter) use the index of each shard,
+    //! encoded as 2 big-endian bytes, as its server ID.
+
+    use core::{convert::TryInto, time::Duration};
+    use rand::prelude::*;
+    use s2n_quic_core::connection::{
+        self,
+        id::{
+            structured::{MAX_NONCE_LEN, MAX_SERVER_ID_LEN, MIN_NONCE_LEN, MIN_SERVER_ID_LEN},
+            ConnectionInfo, Generator, Validator,
+        },
+    };
+
+    pub use s2n_quic_core::connection::id::structured::{ConfigId, Decoder};
+
+    const DEFAULT_NONCE_LEN: usize = 8;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    struct ServerId {
+        bytes: [u8; MAX_SERVER_ID_LEN],
+        len: u8,
+    }
+
+    impl ServerId {
+        #[inline]
+        fn as_bytes(&self) -> &[u8] {
+            &self.bytes[..self.len as usize]
+        }
+    }
+
+    /// Connection ID format which encodes a server ID followed by a random nonce
+    #[derive(Debug)]
+    pub struct Format {
+        config_id: ConfigId,
+        server_id: ServerId,
+        nonce_len: usize,
+        lifetime: Option<Duration>,
+    }
+
+    impl Format {
+        /// Creates a builder for the format
+        pub fn builder() -> Builder {
+            Builder::default()
+        }
+
+        /// Returns a decoder for the connection IDs generated by the format
+        pub fn decoder(&self) -> Decoder {
+            Decoder::new(self.config_id, self.server_id.len as usize)
+                .expect("server ID length already checked")
+        }
+
+        #[inline]
+        fn len(&self) -> usize {
+            1 + self.server_id.len as usize + self.nonce_len
+        }
+    }
+
+    /// A builder for [`Format`] providers
+    #[derive(Debug)]
+    pub struct Builder {
+        config_id: ConfigId,
+        server_id: ServerId,
+        nonce_len: usize,
+        lifetime: Option<Duration>,
+    }
+
+    impl Default for Builder {
+        fn default() -> Self {
+            Self {
+                config_id: ConfigId::default(),
+                server_id: ServerId::default(),
+                nonce_len: DEFAULT_NONCE_LEN,
+                lifetime: None,
+            }
+        }
+    }
+
+    impl Builder {
+        /// Sets the config rotation codepoint encoded in the first 3 bits of each connection ID
+        pub fn with_config_id(mut self, config_id: ConfigId) -> Self {
+            self.config_id = config_id;
+            self
+        }
+
+        /// Sets the opaque server ID encoded in each connection ID
+        ///
+        /// The server ID must be between 1 and 15 bytes.
+        pub fn with_server_id(mut self, server_id: &[u8]) -> Result<Self, connection::id::Error> {
+            if !(MIN_SERVER_ID_LEN..=MAX_SERVER_ID_LEN).contains(&server_id.len()) {
+                return Err(connection::id::Error::InvalidLength);
+            }
+            self.server_id = ServerId::default();
+            self.server_id.bytes[..server_id.len()].copy_from_slice(server_id);
+            self.server_id.len = server_id.len() as u8;
+            Ok(self)
+        }
+
+        /// Sets the number of random bytes following the server ID
+        ///
+        /// The nonce must be between 4 and 18 bytes.
+        pub fn with_nonce_len(mut self, nonce_len: usize) -> Result<Self, connection::id::Error> {
+            if !(MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&nonce_len) {
+                return Err(connection::id::Error::InvalidLength);
+            }
+            self.nonce_len = nonce_len;
+            Ok(self)
+        }
+
+        /// Sets the lifetime of each generated connection Id
+        pub fn with_lifetime(mut self, lifetime: Duration) -> Result<Self, connection::id::Error> {
+            if !(connection::id::MIN_LIFETIME..=connection::id::MAX_LIFETIME).contains(&lifetime) {
+                return Err(connection::id::Error::InvalidLifetime);
+            }
+            self.lifetime = Some(lifetime);
+            Ok(self)
+        }
+
+        /// Builds the [`Format`] into a provider
+        ///
+        /// Returns an error if a server ID was not set or if the connection ID would
+        /// exceed the maximum length.
+        pub fn build(self) -> Result<Format, connection::id::Error> {
+            let format = Format {
+                config_id: self.config_id,
+                server_id: self.server_id,
+                nonce_len: self.nonce_len,
+                lifetime: self.lifetime,
+            };
+
+            if format.server_id.len == 0 || format.len() > connection::id::MAX_LEN {
+                return Err(connection::id::Error::InvalidLength);
+            }
+
+            Ok(format)
+        }
+    }
+
+    impl Generator for Format {
+        fn generate(&mut self, _connection_info: &ConnectionInfo) -> connection::LocalId {
+            let len = self.len();
+            let server_id = self.server_id.as_bytes();
+
+            let mut id = [0u8; connection::id::MAX_LEN];
+            let id = &mut id[..len];
+            id[0] = self.config_id.first_octet(len);
+            id[1..=server_id.len()].copy_from_slice(server_id);
+            rand::thread_rng().fill_bytes(&mut id[1 + server_id.len()..]);
+            (&*id).try_into().expect("length already checked")
+        }
+
+        fn lifetime(&self) -> Option<Duration> {
+            self.lifetime
+        }
+    }
+
+    impl Validator for Format {
+        fn validate(&self, _connection_info: &ConnectionInfo, buffer: &[u8]) -> Option<usize> {
+            let len = self.decoder().len(buffer)?;
+
+            if len == self.len() {
+                Some(len)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn generator_test() {
+            let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+            let connection_info = ConnectionInfo::new(remote_address);
+            let server_id = [1, 2, 3];
+
+            for config_id in (0..=u8::MAX).filter_map(ConfigId::new) {
+                for nonce_len in MIN_NONCE_LEN..=MAX_NONCE_LEN - server_id.len() + 1 {
+                    let mut format = Format::builder()
+                        .with_config_id(config_id)
+                        .with_server_id(&server_id)
+                        .unwrap()
+                        .with_nonce_len(nonce_len)
+                        .unwrap()
+                        .build()
+                        .unwrap();
+
+                    let id = format.generate(&connection_info);
+                    let len = 1 + server_id.len() + nonce_len;
+                    assert_eq!(id.len(), len);
+                    assert_eq!(format.validate(&connection_info, id.as_ref()), Some(len));
+
+                    // the server ID can be recovered with a decoder that only knows the config
+                    let decoder = Decoder::new(config_id, server_id.len()).unwrap();
+                    assert_eq!(decoder, format.decoder());
+                    assert_eq!(decoder.decode(id.as_ref()), Some(&server_id[..]));
+
+                    // trailing packet data does not affect decoding
+                    let mut buffer = id.as_ref().to_vec();
+                    buffer.extend_from_slice(&[0xff; 8]);
+                    assert_eq!(format.validate(&connection_info, &buffer), Some(len));
+                    assert_eq!(decoder.decode(&buffer), Some(&server_id[..]));
+
+                    // a different config rotation is rejected
+                    let other = ConfigId::new((u8::from(config_id) + 1) % 7).unwrap();
+                    let other = Decoder::new(other, server_id.len()).unwrap();
+                    assert_eq!(other.decode(id.as_ref()), None);
+
+                    // a truncated connection ID is rejected
+                    assert_eq!(
+                        format.validate(&connection_info, &id.as_ref()[..len - 1]),
+                        None
+                    );
+                    assert_eq!(decoder.decode(&id.as_ref()[..len - 1]), None);
+                }
+            }
+        }
+
+        #[test]
+        fn nonce_test() {
+            let remote_address = &s2n_quic_core::inet::SocketAddress::default();
+            let connection_info = ConnectionInfo::new(remote_address);
+            let mut format = Format::builder()
+                .with_server_id(&[42])
+                .unwrap()
+                .build()
+                .unwrap();
+
+            let a = format.generate(&connection_info);
+            let b = format.generate(&connection_info);
+            assert_eq!(a.as_ref()[..2], b.as_ref()[..2]);
+            assert_ne!(a, b, "the nonce should be randomly generated");
+        }
+
+        #[test]
+        fn builder_test() {
+            assert_eq!(
+                Some(connection::id::Error::InvalidLength),
+                Format::builder().build().err(),
+                "a server ID is required"
+            );
+
+            for len in [0, MAX_SERVER_ID_LEN + 1] {
+                assert_eq!(
+                    Some(connection::id::Error::InvalidLength),
+                    Format::builder().with_server_id(&vec![1; len]).err()
+                );
+            }
+
+            for len in [MIN_NONCE_LEN - 1, MAX_NONCE_LEN + 1] {
+                assert_eq!(
+                    Some(connection::id::Error::InvalidLength),
+                    Format::builder().with_nonce_len(len).err()
+                );
+            }
+
+            // the connection ID may not exceed the maximum length
+            assert_eq!(
+                Some(connection::id::Error::InvalidLength),
+                Format::builder()
+                    .with_server_id(&[1; MAX_SERVER_ID_LEN])
+                    .unwrap()
+                    .with_nonce_len(MAX_NONCE_LEN)
+                    .unwrap()
+                    .build()
+                    .err()
+            );
+        }
+    }
+}
@@ -20,6 +20,9 @@ pub trait Provider: 'static {
 use core::time::Duration;
 pub use default::Limits as Default;
 
+pub mod admission_controller;
+pub mod connection_limiter;
+
 impl_provider_utils!();
 
 impl<T: 'static + Limiter> Provider for T {
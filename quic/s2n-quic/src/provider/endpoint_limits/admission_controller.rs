@@ -0,0 +1,438 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Refuses new connections while the server is overloaded
+//!
+//! An [`AdmissionLimiter`] samples the load of the server and asks an [`AdmissionController`]
+//! whether each connection attempt should be admitted. Attempts which are denied are answered
+//! with a `CONNECTION_REFUSED` error, which is the error QUIC defines for a server that is unable
+//! to accept a new connection. The error is sent before any connection or TLS state is created,
+//! so an overloaded server doesn't spend any handshake work on the attempts it refuses.
+//!
+//! ```rust,no_run
+//! use s2n_quic::{
+//!     provider::endpoint_limits::admission_controller::{
+//!         AdmissionLimiter, ResourceBasedAdmissionController, ResourceThresholds,
+//!     },
+//!     Server,
+//! };
+//! # use std::error::Error;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn Error>> {
+//! let controller = ResourceBasedAdmissionController::new(ResourceThresholds {
+//!     max_cpu_usage: 0.8,
+//!     min_available_memory: 512 * 1024 * 1024,
+//! });
+//!
+//! let server = Server::builder()
+//!     .with_endpoint_limits(AdmissionLimiter::new(controller))?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use super::{default, ConnectionAttempt, Limiter, Outcome};
+use core::time::Duration;
+use s2n_quic_core::event::Timestamp;
+use std::fs;
+
+/// The default interval between samples of the system resources
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The load of the server at the time of a connection attempt
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ServerLoad {
+    /// The fraction of CPU time, between 0.0 and 1.0, spent busy over the last sample interval
+    ///
+    /// This is `None` until two samples have been taken or if the CPU usage can't be read on
+    /// this platform.
+    pub cpu_usage: Option<f64>,
+    /// The number of bytes of memory available for new allocations without swapping
+    ///
+    /// This is `None` if the available memory can't be read on this platform.
+    pub available_memory: Option<u64>,
+    /// The number of handshakes which have begun but not completed
+    pub inflight_handshakes: usize,
+    /// The number of open connections
+    pub connection_count: usize,
+}
+
+/// Decides whether the server has the capacity to accept a new connection
+pub trait AdmissionController: 'static + Send {
+    /// Returns `true` if a new connection should be accepted under `server_load`
+    fn should_accept(&mut self, server_load: &ServerLoad) -> bool;
+}
+
+/// Configures the thresholds enforced by a [`ResourceBasedAdmissionController`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResourceThresholds {
+    /// The CPU usage, between 0.0 and 1.0, at or above which new connections are refused
+    pub max_cpu_usage: f64,
+    /// The number of bytes of available memory below which new connections are refused
+    pub min_available_memory: u64,
+}
+
+impl Default for ResourceThresholds {
+    fn default() -> Self {
+        Self {
+            max_cpu_usage: 0.9,
+            min_available_memory: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Refuses new connections while the CPU usage or the available memory cross their thresholds
+///
+/// Resources which can't be measured never cause a connection to be refused.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResourceBasedAdmissionController {
+    thresholds: ResourceThresholds,
+}
+
+impl ResourceBasedAdmissionController {
+    /// Creates a new controller with the provided thresholds
+    pub fn new(thresholds: ResourceThresholds) -> Self {
+        Self { thresholds }
+    }
+}
+
+impl AdmissionController for ResourceBasedAdmissionController {
+    fn should_accept(&mut self, server_load: &ServerLoad) -> bool {
+        let thresholds = &self.thresholds;
+
+        if let Some(cpu_usage) = server_load.cpu_usage {
+            if cpu_usage >= thresholds.max_cpu_usage {
+                return false;
+            }
+        }
+
+        if let Some(available_memory) = server_load.available_memory {
+            if available_memory < thresholds.min_available_memory {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Refuses connection attempts which are denied by an [`AdmissionController`]
+///
+/// The CPU usage and available memory are read from `/proc/stat` and `/proc/meminfo` at most
+/// once per sample interval, so a flood of connection attempts doesn't result in a flood of
+/// file reads. On platforms without these files the resource usage is reported as unknown.
+///
+/// Attempts which are admitted are passed through to the
+/// [default limits](super::default::Limits).
+#[derive(Debug)]
+pub struct AdmissionLimiter<C> {
+    controller: C,
+    monitor: Monitor,
+    limits: default::Limits,
+}
+
+impl<C: AdmissionController> AdmissionLimiter<C> {
+    /// Creates a new limiter which admits connections with `controller`
+    pub fn new(controller: C) -> Self {
+        Self {
+            controller,
+            monitor: Monitor::new(DEFAULT_SAMPLE_INTERVAL),
+            limits: default::Limits::default(),
+        }
+    }
+
+    /// Sets the interval between samples of the system resources
+    ///
+    /// The CPU usage is averaged over this interval.
+    pub fn with_sample_interval(mut self, interval: Duration) -> Self {
+        self.monitor.interval = interval;
+        self
+    }
+}
+
+impl<C: AdmissionController> Limiter for AdmissionLimiter<C> {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        let outcome = self.limits.on_connection_attempt(info);
+
+        if outcome != Outcome::allow() {
+            return outcome;
+        }
+
+        self.monitor.update(info.timestamp);
+
+        let server_load = ServerLoad {
+            cpu_usage: self.monitor.cpu_usage,
+            available_memory: self.monitor.available_memory,
+            inflight_handshakes: info.inflight_handshakes,
+            connection_count: info.connection_count,
+        };
+
+        if !self.controller.should_accept(&server_load) {
+            return Outcome::close();
+        }
+
+        outcome
+    }
+}
+
+/// Samples the system resources from procfs
+#[derive(Debug)]
+struct Monitor {
+    interval: Duration,
+    last_sample: Option<Timestamp>,
+    cpu_times: Option<CpuTimes>,
+    cpu_usage: Option<f64>,
+    available_memory: Option<u64>,
+}
+
+impl Monitor {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sample: None,
+            cpu_times: None,
+            cpu_usage: None,
+            available_memory: None,
+        }
+    }
+
+    /// Takes a new sample if the sample interval has elapsed since the previous one
+    fn update(&mut self, now: Timestamp) {
+        if let Some(last_sample) = self.last_sample {
+            if now.saturating_duration_since(last_sample) < self.interval {
+                return;
+            }
+        }
+        self.last_sample = Some(now);
+
+        let cpu_times = fs::read_to_string("/proc/stat")
+            .ok()
+            .and_then(|stat| CpuTimes::parse(&stat));
+        let available_memory = fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|meminfo| parse_available_memory(&meminfo));
+
+        self.on_sample(cpu_times, available_memory);
+    }
+
+    fn on_sample(&mut self, cpu_times: Option<CpuTimes>, available_memory: Option<u64>) {
+        self.cpu_usage = match (self.cpu_times, cpu_times) {
+            (Some(earlier), Some(cpu_times)) => cpu_times.usage_since(&earlier),
+            _ => None,
+        };
+        self.cpu_times = cpu_times;
+        self.available_memory = available_memory;
+    }
+}
+
+/// The cumulative time spent by all of the CPUs since boot, in clock ticks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CpuTimes {
+    busy: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Parses the aggregate `cpu` line of `/proc/stat`
+    fn parse(stat: &str) -> Option<Self> {
+        let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+
+        let mut idle = 0u64;
+        let mut total = 0u64;
+
+        // user, nice, system, idle, iowait, irq, softirq, steal
+        //
+        // The guest times which follow are already included in the user and nice times.
+        for (index, value) in line.split_whitespace().skip(1).take(8).enumerate() {
+            let value: u64 = value.parse().ok()?;
+
+            if index == 3 || index == 4 {
+                idle += value;
+            }
+            total += value;
+        }
+
+        Some(Self {
+            busy: total - idle,
+            total,
+        })
+    }
+
+    /// Returns the fraction of CPU time spent busy between `earlier` and `self`
+    fn usage_since(&self, earlier: &Self) -> Option<f64> {
+        let total = self.total.checked_sub(earlier.total)?;
+
+        // the clock didn't tick between the samples
+        if total == 0 {
+            return None;
+        }
+
+        let busy = self.busy.saturating_sub(earlier.busy).min(total);
+        Some(busy as f64 / total as f64)
+    }
+}
+
+/// Parses the `MemAvailable` field of `/proc/meminfo` into a number of bytes
+fn parse_available_memory(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let mut fields = line.split_whitespace().skip(1);
+    let value: u64 = fields.next()?.parse().ok()?;
+
+    match fields.next() {
+        Some("kB") => value.checked_mul(1024),
+        None => Some(value),
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        event::IntoEvent,
+        inet::SocketAddress,
+        time::{testing::Clock as MockClock, Clock},
+    };
+    use std::net;
+
+    const STAT: &str = "\
+cpu  1000 50 500 8000 400 20 30 0 100 0
+cpu0 500 25 250 4000 200 10 15 0 50 0
+intr 12345 0 0
+";
+
+    const MEMINFO: &str = "\
+MemTotal:       16384000 kB
+MemFree:          512000 kB
+MemAvailable:    8192000 kB
+Buffers:          100000 kB
+";
+
+    /// Admits a fixed number of open connections
+    struct Capacity(usize);
+
+    impl AdmissionController for Capacity {
+        fn should_accept(&mut self, server_load: &ServerLoad) -> bool {
+            server_load.connection_count < self.0
+        }
+    }
+
+    fn attempt<C: AdmissionController>(
+        limiter: &mut AdmissionLimiter<C>,
+        port: u16,
+        connection_count: usize,
+    ) -> Outcome {
+        let address: SocketAddress = net::SocketAddr::from(([10, 0, 0, 1], port)).into();
+        let timestamp = MockClock::default().get_time().into_event();
+        let info = ConnectionAttempt::new(0, connection_count, &address, timestamp);
+        limiter.on_connection_attempt(&info)
+    }
+
+    #[test]
+    fn parse_stat_test() {
+        assert_eq!(
+            CpuTimes::parse(STAT),
+            Some(CpuTimes {
+                busy: 1600,
+                total: 10000,
+            })
+        );
+
+        assert_eq!(CpuTimes::parse("cpu0 1 2 3 4\n"), None);
+        assert_eq!(CpuTimes::parse("cpu  1 2 x 4\n"), None);
+        assert_eq!(CpuTimes::parse(""), None);
+    }
+
+    #[test]
+    fn cpu_usage_test() {
+        let earlier = CpuTimes {
+            busy: 1600,
+            total: 10000,
+        };
+
+        let later = CpuTimes {
+            busy: 1900,
+            total: 10400,
+        };
+        assert_eq!(later.usage_since(&earlier), Some(0.75));
+
+        assert_eq!(earlier.usage_since(&earlier), None);
+        assert_eq!(earlier.usage_since(&later), None);
+    }
+
+    #[test]
+    fn parse_meminfo_test() {
+        assert_eq!(parse_available_memory(MEMINFO), Some(8192000 * 1024));
+        assert_eq!(parse_available_memory("MemFree: 100 kB\n"), None);
+        assert_eq!(parse_available_memory("MemAvailable: 100 MB\n"), None);
+    }
+
+    #[test]
+    fn monitor_test() {
+        let mut monitor = Monitor::new(DEFAULT_SAMPLE_INTERVAL);
+
+        // the usage isn't known until there are two samples to compare
+        monitor.on_sample(CpuTimes::parse(STAT), Some(1024));
+        assert_eq!(monitor.cpu_usage, None);
+        assert_eq!(monitor.available_memory, Some(1024));
+
+        monitor.on_sample(
+            Some(CpuTimes {
+                busy: 1700,
+                total: 10400,
+            }),
+            None,
+        );
+        assert_eq!(monitor.cpu_usage, Some(0.25));
+        assert_eq!(monitor.available_memory, None);
+
+        // an unreadable sample resets the usage
+        monitor.on_sample(None, None);
+        assert_eq!(monitor.cpu_usage, None);
+    }
+
+    #[test]
+    fn resource_thresholds_test() {
+        let mut controller = ResourceBasedAdmissionController::new(ResourceThresholds {
+            max_cpu_usage: 0.8,
+            min_available_memory: 1000,
+        });
+
+        let load = |cpu_usage, available_memory| ServerLoad {
+            cpu_usage,
+            available_memory,
+            ..Default::default()
+        };
+
+        assert!(controller.should_accept(&load(Some(0.5), Some(2000))));
+        assert!(!controller.should_accept(&load(Some(0.8), Some(2000))));
+        assert!(!controller.should_accept(&load(Some(0.5), Some(999))));
+
+        // unknown resources don't refuse any connections
+        assert!(controller.should_accept(&load(None, None)));
+        assert!(!controller.should_accept(&load(None, Some(0))));
+        assert!(!controller.should_accept(&load(Some(1.0), None)));
+    }
+
+    #[test]
+    fn limiter_test() {
+        let mut limiter = AdmissionLimiter::new(Capacity(2));
+
+        assert_eq!(attempt(&mut limiter, 443, 0), Outcome::allow());
+        assert_eq!(attempt(&mut limiter, 443, 1), Outcome::allow());
+        assert_eq!(attempt(&mut limiter, 443, 2), Outcome::close());
+        assert_eq!(attempt(&mut limiter, 443, 20), Outcome::close());
+    }
+
+    #[test]
+    fn default_limits_test() {
+        let mut limiter = AdmissionLimiter::new(Capacity(usize::MAX));
+
+        // blocked ports are still dropped
+        assert_eq!(attempt(&mut limiter, 0, 0), Outcome::drop());
+    }
+}
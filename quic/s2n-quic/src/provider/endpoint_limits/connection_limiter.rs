@@ -0,0 +1,542 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits the number of open connections, both globally and per source address prefix
+//!
+//! The limiter needs to observe both the connection attempts and the lifetime of the accepted
+//! connections so it is registered as the endpoint limits provider as well as an event
+//! subscriber. Both registrations must use clones of the same limiter.
+//!
+//! ```rust,no_run
+//! use s2n_quic::{
+//!     provider::endpoint_limits::connection_limiter::{ConnectionLimiter, ConnectionLimiterConfig},
+//!     Server,
+//! };
+//! # use std::error::Error;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn Error>> {
+//! let limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+//!     max_connections_global: 10_000,
+//!     max_connections_per_prefix: 100,
+//!     ..Default::default()
+//! });
+//!
+//! let server = Server::builder()
+//!     .with_endpoint_limits(limiter.clone())?
+//!     .with_event(limiter)?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use super::{default, ConnectionAttempt, Limiter, Outcome};
+use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+use std::{
+    cell::RefCell,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// The number of counters used to track the connections for each prefix
+const PREFIX_COUNTERS: usize = 1 << 16;
+
+thread_local! {
+    /// The slot reserved by the last connection attempt allowed on this thread
+    ///
+    /// The endpoint creates the connection for an allowed attempt on the same thread, before it
+    /// processes another attempt, so the connection claims this slot when it starts. If the
+    /// attempt fails before a connection is started, the slot is released by the next attempt.
+    static RESERVED: RefCell<Option<Guard>> = RefCell::new(None);
+}
+
+/// Configures the limits enforced by a [`ConnectionLimiter`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnectionLimiterConfig {
+    /// The maximum number of connections open on the endpoint
+    pub max_connections_global: usize,
+    /// The maximum number of connections open from a single address prefix
+    pub max_connections_per_prefix: usize,
+    /// The number of leading bits of an IPv4 address that make up its prefix
+    pub prefix_ipv4: u8,
+    /// The number of leading bits of an IPv6 address that make up its prefix
+    pub prefix_ipv6: u8,
+}
+
+impl Default for ConnectionLimiterConfig {
+    fn default() -> Self {
+        Self {
+            max_connections_global: usize::MAX,
+            max_connections_per_prefix: usize::MAX,
+            prefix_ipv4: 24,
+            prefix_ipv6: 48,
+        }
+    }
+}
+
+/// Refuses connection attempts once the global or per-prefix connection limit is reached
+///
+/// Connection attempts exceeding either limit are closed immediately with a
+/// `CONNECTION_REFUSED` error. All other attempts are passed through to the
+/// [default limits](super::default::Limits).
+///
+/// An allowed attempt reserves its slot with an atomic check-and-increment of the counters, so
+/// the limits hold even when the limiter is shared between endpoints, without introducing any
+/// locking on the connection attempt path. Connections are
+/// tracked in a fixed number of counters, selected by a randomly-keyed hash of the prefix. In
+/// the unlikely event that two prefixes map to the same counter they share a single limit.
+#[derive(Clone, Debug)]
+pub struct ConnectionLimiter {
+    state: Arc<State>,
+    limits: default::Limits,
+}
+
+impl ConnectionLimiter {
+    /// Creates a new limiter with the provided configuration
+    pub fn new(config: ConnectionLimiterConfig) -> Self {
+        let prefixes = (0..PREFIX_COUNTERS).map(|_| AtomicUsize::new(0)).collect();
+        let state = State {
+            config,
+            global: AtomicUsize::new(0),
+            prefixes,
+            hasher: RandomState::new(),
+        };
+
+        Self {
+            state: Arc::new(state),
+            limits: default::Limits::default(),
+        }
+    }
+
+    /// Returns the number of open connections tracked by the limiter
+    pub fn connection_count(&self) -> usize {
+        self.state.global.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of open connections from the prefix of `remote_address`
+    pub fn prefix_connection_count(&self, remote_address: &events::SocketAddress) -> usize {
+        self.state.counter(remote_address).map_or(0, |index| {
+            self.state.prefixes[index].load(Ordering::Relaxed)
+        })
+    }
+
+    /// Returns the slot for a connection that started from `remote_address`
+    ///
+    /// The slot reserved by the connection attempt is claimed if there is one. Otherwise, as for
+    /// connections opened by a client, a new slot is taken regardless of the limits.
+    fn start(&self, remote_address: &events::SocketAddress) -> Guard {
+        let counter = self.state.counter(remote_address);
+        let reserved = RESERVED.with(|reserved| {
+            let mut reserved = reserved.borrow_mut();
+            let is_match = reserved.as_ref().map_or(false, |guard| {
+                Arc::ptr_eq(&guard.state, &self.state) && guard.counter == counter
+            });
+            if is_match {
+                reserved.take()
+            } else {
+                None
+            }
+        });
+
+        reserved.unwrap_or_else(|| Guard::new(&self.state, remote_address))
+    }
+}
+
+impl Limiter for ConnectionLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        let outcome = self.limits.on_connection_attempt(info);
+
+        if outcome != Outcome::allow() {
+            return outcome;
+        }
+
+        // release a slot reserved by an earlier attempt that never started a connection
+        let previous = RESERVED.with(|reserved| reserved.borrow_mut().take());
+        drop(previous);
+
+        let guard = if let Some(guard) = Guard::try_new(&self.state, &info.remote_address) {
+            guard
+        } else {
+            return Outcome::close();
+        };
+
+        RESERVED.with(|reserved| *reserved.borrow_mut() = Some(guard));
+
+        outcome
+    }
+}
+
+impl Subscriber for ConnectionLimiter {
+    type ConnectionContext = Option<Guard>;
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        None
+    }
+
+    fn on_connection_started(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::ConnectionStarted,
+    ) {
+        *context = Some(self.start(&event.path.remote_addr));
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        _event: &events::ConnectionClosed,
+    ) {
+        // release the connection as soon as it closes rather than waiting for it to drain
+        *context = None;
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    config: ConnectionLimiterConfig,
+    global: AtomicUsize,
+    prefixes: Box<[AtomicUsize]>,
+    hasher: RandomState,
+}
+
+impl State {
+    /// Returns the index of the counter tracking the prefix of `remote_address`
+    fn counter(&self, remote_address: &events::SocketAddress) -> Option<usize> {
+        let prefix = Prefix::new(remote_address, &self.config)?;
+        let mut hasher = self.hasher.build_hasher();
+        prefix.hash(&mut hasher);
+        Some(hasher.finish() as usize % self.prefixes.len())
+    }
+}
+
+/// Holds a connection slot in the limiter until it is dropped
+#[derive(Debug)]
+pub struct Guard {
+    state: Arc<State>,
+    counter: Option<usize>,
+}
+
+impl Guard {
+    fn new(state: &Arc<State>, remote_address: &events::SocketAddress) -> Self {
+        let counter = state.counter(remote_address);
+
+        state.global.fetch_add(1, Ordering::Relaxed);
+        if let Some(index) = counter {
+            state.prefixes[index].fetch_add(1, Ordering::Relaxed);
+        }
+
+        Self {
+            state: state.clone(),
+            counter,
+        }
+    }
+
+    /// Takes a slot only if it is within both the global and the prefix limits
+    fn try_new(state: &Arc<State>, remote_address: &events::SocketAddress) -> Option<Self> {
+        let config = &state.config;
+        let counter = state.counter(remote_address);
+
+        if !try_increment(&state.global, config.max_connections_global) {
+            return None;
+        }
+
+        if let Some(index) = counter {
+            if !try_increment(&state.prefixes[index], config.max_connections_per_prefix) {
+                state.global.fetch_sub(1, Ordering::Relaxed);
+                return None;
+            }
+        }
+
+        Some(Self {
+            state: state.clone(),
+            counter,
+        })
+    }
+}
+
+/// Increments `count` if it is below `limit`, returning `true` if it was incremented
+///
+/// The comparison and the increment are a single atomic operation, so concurrent attempts
+/// can't both take the last slot.
+fn try_increment(count: &AtomicUsize, limit: usize) -> bool {
+    count
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            if count < limit {
+                Some(count + 1)
+            } else {
+                None
+            }
+        })
+        .is_ok()
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.state.global.fetch_sub(1, Ordering::Relaxed);
+        if let Some(index) = self.counter {
+            self.state.prefixes[index].fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Prefix {
+    IpV4([u8; 4]),
+    IpV6([u8; 16]),
+}
+
+impl Prefix {
+    fn new(
+        remote_address: &events::SocketAddress,
+        config: &ConnectionLimiterConfig,
+    ) -> Option<Self> {
+        match remote_address {
+            events::SocketAddress::IpV4 { ip, .. } => {
+                let mut ip = **ip;
+                mask(&mut ip, config.prefix_ipv4);
+                Some(Self::IpV4(ip))
+            }
+            events::SocketAddress::IpV6 { ip, .. } => {
+                let mut ip = **ip;
+                mask(&mut ip, config.prefix_ipv6);
+                Some(Self::IpV6(ip))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Clears all of the bits in `bytes` after the first `len` bits
+fn mask(bytes: &mut [u8], len: u8) {
+    let len = len as usize;
+
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        let start = index * 8;
+        if start >= len {
+            *byte = 0;
+        } else if len - start < 8 {
+            *byte &= !(0xff >> (len - start));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        event::IntoEvent,
+        inet::SocketAddress,
+        time::{testing::Clock as MockClock, Clock},
+    };
+    use std::{collections::HashSet, net};
+
+    fn v4(ip: [u8; 4]) -> SocketAddress {
+        net::SocketAddr::from((ip, 443)).into()
+    }
+
+    fn attempt(limiter: &mut ConnectionLimiter, address: &SocketAddress) -> Outcome {
+        let timestamp = MockClock::default().get_time().into_event();
+        let info = ConnectionAttempt::new(0, 0, address, timestamp);
+        limiter.on_connection_attempt(&info)
+    }
+
+    fn open(limiter: &ConnectionLimiter, address: &SocketAddress) -> Guard {
+        limiter.start(&address.into_event())
+    }
+
+    #[test]
+    fn mask_test() {
+        let mut ip = [192, 168, 255, 255];
+        mask(&mut ip, 24);
+        assert_eq!(ip, [192, 168, 255, 0]);
+
+        let mut ip = [192, 168, 255, 255];
+        mask(&mut ip, 20);
+        assert_eq!(ip, [192, 168, 240, 0]);
+
+        let mut ip = [192, 168, 255, 255];
+        mask(&mut ip, 0);
+        assert_eq!(ip, [0; 4]);
+
+        let mut ip = [192, 168, 255, 255];
+        mask(&mut ip, 64);
+        assert_eq!(ip, [192, 168, 255, 255]);
+
+        let mut ip = [0xff; 16];
+        mask(&mut ip, 48);
+        assert_eq!(&ip[..6], &[0xff; 6]);
+        assert_eq!(&ip[6..], &[0; 10]);
+    }
+
+    #[test]
+    fn prefix_test() {
+        let config = ConnectionLimiterConfig::default();
+        let prefix = |address: SocketAddress| Prefix::new(&address.into_event(), &config).unwrap();
+
+        assert_eq!(prefix(v4([10, 0, 0, 1])), prefix(v4([10, 0, 0, 200])));
+        assert_ne!(prefix(v4([10, 0, 0, 1])), prefix(v4([10, 0, 1, 1])));
+
+        let v6 = |last: u8, third: u8| -> SocketAddress {
+            let mut ip = [0x20; 16];
+            ip[2] = third;
+            ip[15] = last;
+            net::SocketAddr::from((ip, 443)).into()
+        };
+        assert_eq!(prefix(v6(1, 0)), prefix(v6(2, 0)));
+        assert_ne!(prefix(v6(1, 0)), prefix(v6(1, 1)));
+    }
+
+    #[test]
+    fn per_prefix_limit_test() {
+        let mut limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+            max_connections_per_prefix: 2,
+            ..Default::default()
+        });
+
+        let a = v4([10, 0, 0, 1]);
+        let b = v4([10, 0, 0, 2]);
+        let other = v4([10, 0, 1, 1]);
+
+        assert_eq!(attempt(&mut limiter, &a), Outcome::allow());
+        let first = open(&limiter, &a);
+        assert_eq!(attempt(&mut limiter, &b), Outcome::allow());
+        let _second = open(&limiter, &b);
+
+        // both addresses share a /24 prefix
+        assert_eq!(attempt(&mut limiter, &a), Outcome::close());
+        assert_eq!(attempt(&mut limiter, &b), Outcome::close());
+        assert_eq!(attempt(&mut limiter, &other), Outcome::allow());
+
+        drop(first);
+        assert_eq!(limiter.prefix_connection_count(&a.into_event()), 1);
+        assert_eq!(attempt(&mut limiter, &a), Outcome::allow());
+    }
+
+    #[test]
+    fn global_limit_test() {
+        let mut limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+            max_connections_global: 2,
+            ..Default::default()
+        });
+
+        let _first = open(&limiter, &v4([10, 0, 0, 1]));
+        let second = open(&limiter, &v4([10, 0, 1, 1]));
+        assert_eq!(limiter.connection_count(), 2);
+        assert_eq!(attempt(&mut limiter, &v4([10, 0, 2, 1])), Outcome::close());
+
+        drop(second);
+        assert_eq!(limiter.connection_count(), 1);
+        assert_eq!(attempt(&mut limiter, &v4([10, 0, 2, 1])), Outcome::allow());
+    }
+
+    #[test]
+    fn reservation_test() {
+        let mut limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+            max_connections_global: 1,
+            ..Default::default()
+        });
+
+        let a = v4([10, 0, 0, 1]);
+        let b = v4([10, 0, 1, 1]);
+
+        // an allowed attempt holds its slot before the connection starts
+        assert_eq!(attempt(&mut limiter, &a), Outcome::allow());
+        let mut other = limiter.clone();
+        let outcome = std::thread::spawn(move || attempt(&mut other, &b))
+            .join()
+            .unwrap();
+        assert_eq!(outcome, Outcome::close());
+
+        // the connection claims the reserved slot rather than taking another one
+        let guard = open(&limiter, &a);
+        assert_eq!(limiter.connection_count(), 1);
+        drop(guard);
+        assert_eq!(limiter.connection_count(), 0);
+
+        // a slot reserved by an attempt which never started a connection is released by the
+        // next attempt
+        assert_eq!(attempt(&mut limiter, &a), Outcome::allow());
+        assert_eq!(attempt(&mut limiter, &b), Outcome::allow());
+        assert_eq!(limiter.connection_count(), 1);
+    }
+
+    #[test]
+    fn default_limits_test() {
+        let mut limiter = ConnectionLimiter::new(Default::default());
+
+        // blocked ports are still dropped
+        let address: SocketAddress = net::SocketAddr::from(([10, 0, 0, 1], 0)).into();
+        assert_eq!(attempt(&mut limiter, &address), Outcome::drop());
+    }
+
+    /// Opens 10k connections concurrently from 100 different addresses
+    #[test]
+    fn load_test() {
+        const ADDRESSES: usize = 100;
+        const CONNECTIONS_PER_ADDRESS: usize = 100;
+
+        let limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+            max_connections_global: ADDRESSES * CONNECTIONS_PER_ADDRESS,
+            max_connections_per_prefix: CONNECTIONS_PER_ADDRESS,
+            ..Default::default()
+        });
+
+        // pick prefixes that are tracked by distinct counters so they don't share a limit
+        let mut counters = HashSet::new();
+        let addresses: Vec<_> = (0..=u16::MAX)
+            .map(|index| v4([10, (index >> 8) as u8, index as u8, 1]))
+            .filter(|address| counters.insert(limiter.state.counter(&address.into_event())))
+            .take(ADDRESSES)
+            .collect();
+
+        let threads: Vec<_> = addresses
+            .iter()
+            .map(|address| {
+                let mut limiter = limiter.clone();
+                let address = *address;
+                std::thread::spawn(move || {
+                    let mut guards = vec![];
+                    for _ in 0..CONNECTIONS_PER_ADDRESS {
+                        assert_eq!(attempt(&mut limiter, &address), Outcome::allow());
+                        guards.push(open(&limiter, &address));
+                    }
+                    // the prefix is now at its limit
+                    assert_eq!(attempt(&mut limiter, &address), Outcome::close());
+                    guards
+                })
+            })
+            .collect();
+
+        let guards: Vec<_> = threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect();
+
+        let mut limiter = limiter;
+        let address = v4([192, 168, 0, 1]);
+        assert_eq!(
+            limiter.connection_count(),
+            ADDRESSES * CONNECTIONS_PER_ADDRESS
+        );
+        // the global limit has been reached
+        assert_eq!(attempt(&mut limiter, &address), Outcome::close());
+
+        drop(guards);
+        assert_eq!(limiter.connection_count(), 0);
+        for address in &addresses {
+            assert_eq!(limiter.prefix_connection_count(&address.into_event()), 0);
+        }
+        assert_eq!(attempt(&mut limiter, &address), Outcome::allow());
+    }
+}
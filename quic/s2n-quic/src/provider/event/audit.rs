@@ -0,0 +1,910 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Records security-relevant connection events for auditing
+//!
+//! The [`AuditLogger`] is an event subscriber which turns connection events into [`Record`]s.
+//! Each record contains the wall-clock time of the event, the ID of the connection and the
+//! address of the peer. The records are written to one or more [`Target`]s, such as a file or
+//! the local syslog daemon.
+//!
+//! The targets are written by a background thread so the endpoint never waits on them. Records
+//! are handed to the thread through a bounded queue. If the targets fall behind and the queue
+//! fills up, new records are dropped rather than delaying the endpoint, and counted in
+//! [`AuditLogger::dropped_records`].
+//!
+//! ```rust,no_run
+//! use s2n_quic::{
+//!     provider::event::audit::{self, AuditLogger},
+//!     Server,
+//! };
+//! # use std::error::Error;
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn Error>> {
+//! let logger = AuditLogger::builder()
+//!     .with_target(audit::Writer::file("/var/log/quic-audit.log")?)
+//!     .with_target(audit::Syslog::connect()?)
+//!     .build()?;
+//!
+//! let server = Server::builder()
+//!     .with_event(logger.clone())?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+use s2n_quic_core::connection;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The number of records which can be queued for the targets by default
+pub const DEFAULT_QUEUE_CAPACITY: usize = 4096;
+
+/// A security-relevant event of a connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// The wall-clock time at which the event occurred
+    pub time: SystemTime,
+    /// The ID of the connection, as reported in the [`ConnectionMeta`] of its events
+    ///
+    /// The ID is assigned by the endpoint and stays the same for the lifetime of the connection,
+    /// unlike the QUIC connection IDs which are rotated.
+    pub connection_id: u64,
+    /// The address of the peer at the time of the event
+    pub peer_address: SocketAddr,
+    /// The event which occurred
+    pub kind: RecordKind,
+}
+
+/// The events which are recorded
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RecordKind {
+    /// The handshake completed
+    ///
+    /// `server_name` is the name requested by the client, if any. The TLS providers don't
+    /// expose the peer's certificate, so its subject isn't included.
+    ConnectionEstablished { server_name: Option<String> },
+    /// The connection was closed
+    ConnectionClosed { reason: String },
+    /// The first frame of a stream was sent or received
+    StreamOpened { stream_id: u64 },
+    /// Each direction of the stream was either finished or reset
+    StreamClosed { stream_id: u64 },
+    /// A new generation of 1-RTT keys was installed
+    KeyUpdate {
+        generation: u16,
+        cipher_suite: &'static str,
+    },
+    /// The peer moved to a new address, which is the `peer_address` of the record
+    Migration { previous_address: SocketAddr },
+    /// The peer attempted to move to a new address, which was refused
+    MigrationDenied { reason: String },
+    /// The connection was closed because the cryptographic handshake failed
+    AuthenticationFailure { reason: String },
+}
+
+impl RecordKind {
+    /// Returns the name of the event
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConnectionEstablished { .. } => "connection_established",
+            Self::ConnectionClosed { .. } => "connection_closed",
+            Self::StreamOpened { .. } => "stream_opened",
+            Self::StreamClosed { .. } => "stream_closed",
+            Self::KeyUpdate { .. } => "key_update",
+            Self::Migration { .. } => "migration",
+            Self::MigrationDenied { .. } => "migration_denied",
+            Self::AuthenticationFailure { .. } => "authentication_failure",
+        }
+    }
+
+    /// Returns `true` if the event indicates a potential attack on the endpoint
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            Self::MigrationDenied { .. } | Self::AuthenticationFailure { .. }
+        )
+    }
+}
+
+/// Formats the record as a line of space-separated `key=value` pairs
+///
+/// The time is written as seconds since the Unix epoch, with microsecond precision. Text values
+/// are quoted and escaped.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "time={}.{:06} connection_id={} peer_address={} event={}",
+            time.as_secs(),
+            time.subsec_micros(),
+            self.connection_id,
+            self.peer_address,
+            self.kind.name()
+        )?;
+
+        match &self.kind {
+            RecordKind::ConnectionEstablished { server_name } => match server_name {
+                Some(server_name) => write!(f, " server_name={:?}", server_name),
+                None => write!(f, " server_name=-"),
+            },
+            RecordKind::ConnectionClosed { reason }
+            | RecordKind::MigrationDenied { reason }
+            | RecordKind::AuthenticationFailure { reason } => write!(f, " reason={:?}", reason),
+            RecordKind::StreamOpened { stream_id } | RecordKind::StreamClosed { stream_id } => {
+                write!(f, " stream_id={}", stream_id)
+            }
+            RecordKind::KeyUpdate {
+                generation,
+                cipher_suite,
+            } => write!(
+                f,
+                " generation={} cipher_suite={}",
+                generation, cipher_suite
+            ),
+            RecordKind::Migration { previous_address } => {
+                write!(f, " previous_address={}", previous_address)
+            }
+        }
+    }
+}
+
+/// A destination for the records
+///
+/// The targets are only called from the background thread of the [`AuditLogger`], so they
+/// may block.
+pub trait Target: 'static + Send {
+    fn write(&mut self, record: &Record) -> io::Result<()>;
+}
+
+/// Writes each record as a line of text
+#[derive(Debug)]
+pub struct Writer<W>(W);
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self(writer)
+    }
+}
+
+impl Writer<io::BufWriter<fs::File>> {
+    /// Appends the records to the file at `path`, which is created if it doesn't exist
+    pub fn file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self(io::BufWriter::new(file)))
+    }
+}
+
+impl<W: 'static + Write + Send> Target for Writer<W> {
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        writeln!(self.0, "{}", record)?;
+        // each record is flushed so it isn't lost if the process exits
+        self.0.flush()
+    }
+}
+
+/// Sends the records to the local syslog daemon
+///
+/// The records are logged with the `authpriv` facility. Records for which
+/// [`RecordKind::is_warning`] returns `true` are logged with the `warning` severity, all others
+/// with `info`.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Syslog {
+    socket: std::os::unix::net::UnixDatagram,
+    pid: u32,
+}
+
+#[cfg(unix)]
+impl Syslog {
+    /// The facility for security and authorization messages
+    const FACILITY_AUTHPRIV: u8 = 10;
+    const SEVERITY_WARNING: u8 = 4;
+    const SEVERITY_INFO: u8 = 6;
+
+    /// Connects to the syslog daemon at `/dev/log`
+    pub fn connect() -> io::Result<Self> {
+        Self::connect_to("/dev/log")
+    }
+
+    /// Connects to the syslog daemon listening on the Unix socket at `path`
+    pub fn connect_to<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Self {
+            socket,
+            pid: std::process::id(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Target for Syslog {
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        let severity = if record.kind.is_warning() {
+            Self::SEVERITY_WARNING
+        } else {
+            Self::SEVERITY_INFO
+        };
+        let priority = Self::FACILITY_AUTHPRIV * 8 + severity;
+
+        // The daemon adds the timestamp and hostname to messages received on a local socket
+        //
+        // See https://www.rfc-editor.org/rfc/rfc3164#section-4.1
+        let message = format!("<{}>s2n-quic[{}]: {}", priority, self.pid, record);
+        self.socket.send(message.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Configures an [`AuditLogger`]
+pub struct Builder {
+    targets: Vec<Box<dyn Target>>,
+    queue_capacity: usize,
+}
+
+impl Builder {
+    /// Adds a target which all records are written to
+    pub fn with_target<T: Target>(mut self, target: T) -> Self {
+        self.targets.push(Box::new(target));
+        self
+    }
+
+    /// Sets the number of records which can be queued before new records are dropped
+    ///
+    /// The default is [`DEFAULT_QUEUE_CAPACITY`].
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// Spawns the thread which writes the records to the targets
+    pub fn build(self) -> io::Result<AuditLogger> {
+        let Self {
+            mut targets,
+            queue_capacity,
+        } = self;
+        let (sender, records) = mpsc::sync_channel::<Record>(queue_capacity);
+        let failed_writes = Arc::new(AtomicU64::new(0));
+        let state = Arc::new(State {
+            sender: Mutex::new(sender),
+            dropped_records: AtomicU64::new(0),
+            failed_writes: failed_writes.clone(),
+        });
+
+        // The thread only holds the receiving end of the queue, so the loop exits and the
+        // targets are dropped once every clone of the logger has been dropped
+        thread::Builder::new()
+            .name("s2n-quic-audit".to_string())
+            .spawn(move || {
+                for record in records {
+                    for target in targets.iter_mut() {
+                        if target.write(&record).is_err() {
+                            failed_writes.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            })?;
+
+        Ok(AuditLogger { state })
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    // `SyncSender` is only `Sync` on recent compilers, so it is wrapped in a `Mutex`. Each
+    // endpoint only takes the lock when queueing a record, so it is rarely contended.
+    sender: Mutex<mpsc::SyncSender<Record>>,
+    dropped_records: AtomicU64,
+    /// Shared with the thread writing the targets
+    failed_writes: Arc<AtomicU64>,
+}
+
+/// Writes an audit record for each security-relevant event of the connections
+///
+/// The logger can be cloned to register it with several endpoints, in which case all of the
+/// clones share the same queue and targets.
+#[derive(Clone, Debug)]
+pub struct AuditLogger {
+    state: Arc<State>,
+}
+
+impl AuditLogger {
+    pub fn builder() -> Builder {
+        Builder {
+            targets: Vec::new(),
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+        }
+    }
+
+    /// Returns the number of records which were dropped because the queue was full
+    pub fn dropped_records(&self) -> u64 {
+        self.state.dropped_records.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of times a target failed to write a record
+    pub fn failed_writes(&self) -> u64 {
+        self.state.failed_writes.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, context: &ConnectionContext, meta: &ConnectionMeta, kind: RecordKind) {
+        let record = Record {
+            time: SystemTime::now(),
+            connection_id: meta.id,
+            peer_address: context.peer_address,
+            kind,
+        };
+
+        // never wait for the targets to catch up
+        if self.state.sender.lock().unwrap().try_send(record).is_err() {
+            self.state.dropped_records.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Tracks whether each direction of a stream is done
+#[derive(Clone, Copy, Debug, Default)]
+struct StreamState {
+    send_done: bool,
+    receive_done: bool,
+}
+
+/// The state of a connection which is included in its records
+#[derive(Debug)]
+pub struct ConnectionContext {
+    is_server: bool,
+    peer_address: SocketAddr,
+    server_name: Option<String>,
+    /// The streams which are open
+    streams: HashMap<u64, StreamState>,
+    /// The highest ID of a closed stream, for each of the four stream types
+    highest_closed: [Option<u64>; 4],
+}
+
+impl ConnectionContext {
+    fn new(is_server: bool, peer_address: SocketAddr) -> Self {
+        Self {
+            is_server,
+            peer_address,
+            server_name: None,
+            streams: HashMap::new(),
+            highest_closed: [None; 4],
+        }
+    }
+
+    /// Returns `true` if the stream was already closed
+    ///
+    /// Streams of each type are opened in order, so a stream which isn't open and has an ID at
+    /// or below the highest closed ID of its type is considered closed. This avoids keeping
+    /// an entry for every stream of a long-lived connection.
+    fn is_closed(&self, stream_id: u64) -> bool {
+        if self.streams.contains_key(&stream_id) {
+            return false;
+        }
+
+        self.highest_closed[Self::stream_type(stream_id)]
+            .map_or(false, |highest_closed| stream_id <= highest_closed)
+    }
+
+    fn on_stream_closed(&mut self, stream_id: u64) {
+        self.streams.remove(&stream_id);
+
+        let highest_closed = &mut self.highest_closed[Self::stream_type(stream_id)];
+        *highest_closed = Some(highest_closed.map_or(stream_id, |id| id.max(stream_id)));
+    }
+
+    /// Returns the index of the stream type, which is encoded in the two lowest bits of the ID
+    fn stream_type(stream_id: u64) -> usize {
+        (stream_id & 0x3) as usize
+    }
+
+    /// Returns the state of the stream, along with `true` if it was seen for the first time
+    fn stream(&mut self, stream_id: u64) -> (&mut StreamState, bool) {
+        let is_server = self.is_server;
+        let mut is_new = false;
+        let state = self.streams.entry(stream_id).or_insert_with(|| {
+            is_new = true;
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-2.1
+            //# The least significant bit (0x01) of the stream ID identifies the
+            //# initiator of the stream.
+            let is_local = (stream_id & 0x1 == 1) == is_server;
+
+            //# The second least significant bit (0x02) of the stream ID
+            //# distinguishes between bidirectional streams (with the bit set to 0)
+            //# and unidirectional streams (with the bit set to 1).
+            let is_unidirectional = stream_id & 0x2 == 2;
+
+            // unidirectional streams only have a single direction
+            StreamState {
+                send_done: is_unidirectional && !is_local,
+                receive_done: is_unidirectional && is_local,
+            }
+        });
+        (state, is_new)
+    }
+}
+
+impl AuditLogger {
+    fn on_stream_frame(
+        &self,
+        context: &mut ConnectionContext,
+        meta: &ConnectionMeta,
+        frame: &events::Frame,
+        is_sent: bool,
+    ) {
+        let (stream_id, is_done) = match frame {
+            events::Frame::Stream { id, is_fin, .. } => (*id, *is_fin),
+            events::Frame::ResetStream { id, .. } => (*id, true),
+            _ => return,
+        };
+
+        if context.is_closed(stream_id) {
+            // the frame is retransmitted after the stream was closed
+            return;
+        }
+
+        let (state, is_new) = context.stream(stream_id);
+
+        if is_done {
+            if is_sent {
+                state.send_done = true;
+            } else {
+                state.receive_done = true;
+            }
+        }
+
+        let is_closed = state.send_done && state.receive_done;
+
+        if is_new {
+            self.record(context, meta, RecordKind::StreamOpened { stream_id });
+        }
+
+        if is_closed {
+            context.on_stream_closed(stream_id);
+            self.record(context, meta, RecordKind::StreamClosed { stream_id });
+        }
+    }
+}
+
+impl Subscriber for AuditLogger {
+    type ConnectionContext = ConnectionContext;
+
+    fn create_connection_context(
+        &mut self,
+        meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        ConnectionContext::new(
+            matches!(meta.endpoint_type, events::EndpointType::Server { .. }),
+            // the address is known once the connection has started, before any other events
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        )
+    }
+
+    fn on_connection_started(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::ConnectionStarted,
+    ) {
+        context.peer_address = socket_addr(&event.path.remote_addr);
+    }
+
+    fn on_server_name_information(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &ConnectionMeta,
+        event: &events::ServerNameInformation,
+    ) {
+        context.server_name = Some(event.chosen_server_name.to_string());
+    }
+
+    fn on_handshake_status_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::HandshakeStatusUpdated,
+    ) {
+        if let events::HandshakeStatus::Complete { .. } = event.status {
+            let server_name = context.server_name.clone();
+            self.record(
+                context,
+                meta,
+                RecordKind::ConnectionEstablished { server_name },
+            );
+        }
+    }
+
+    fn on_key_update(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::KeyUpdate,
+    ) {
+        if let events::KeyType::OneRtt { generation, .. } = event.key_type {
+            let cipher_suite = event.cipher_suite.as_str();
+            self.record(
+                context,
+                meta,
+                RecordKind::KeyUpdate {
+                    generation,
+                    cipher_suite,
+                },
+            );
+        }
+    }
+
+    fn on_active_path_updated(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ActivePathUpdated,
+    ) {
+        let previous_address = socket_addr(&event.previous.remote_addr);
+        let active_address = socket_addr(&event.active.remote_addr);
+
+        // only the local address or the connection ID may have changed
+        if previous_address == active_address {
+            return;
+        }
+
+        context.peer_address = active_address;
+        self.record(context, meta, RecordKind::Migration { previous_address });
+    }
+
+    fn on_connection_migration_denied(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionMigrationDenied,
+    ) {
+        let reason = format!("{:?}", event.reason);
+        self.record(context, meta, RecordKind::MigrationDenied { reason });
+    }
+
+    fn on_frame_sent(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::FrameSent,
+    ) {
+        self.on_stream_frame(context, meta, &event.frame, true);
+    }
+
+    fn on_frame_received(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::FrameReceived,
+    ) {
+        self.on_stream_frame(context, meta, &event.frame, false);
+    }
+
+    fn on_connection_closed(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        meta: &ConnectionMeta,
+        event: &events::ConnectionClosed,
+    ) {
+        let reason = event.error.to_string();
+
+        if is_authentication_failure(&event.error) {
+            let reason = reason.clone();
+            self.record(context, meta, RecordKind::AuthenticationFailure { reason });
+        }
+
+        // closing the connection closes all of the streams which are still open
+        let mut open_streams: Vec<u64> = context.streams.keys().copied().collect();
+        open_streams.sort_unstable();
+        for stream_id in open_streams {
+            self.record(context, meta, RecordKind::StreamClosed { stream_id });
+        }
+        context.streams.clear();
+
+        self.record(context, meta, RecordKind::ConnectionClosed { reason });
+    }
+}
+
+/// Returns `true` if the connection was closed with a TLS alert
+fn is_authentication_failure(error: &connection::Error) -> bool {
+    match error {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-20.1
+        //# CRYPTO_ERROR (0x0100-0x01ff):  The cryptographic handshake failed.
+        connection::Error::Transport { code, .. } => (0x100..=0x1ff).contains(&code.as_u64()),
+        _ => false,
+    }
+}
+
+fn socket_addr(address: &events::SocketAddress) -> SocketAddr {
+    match address {
+        events::SocketAddress::IpV4 { ip, port, .. } => SocketAddr::new((**ip).into(), *port),
+        events::SocketAddress::IpV6 { ip, port, .. } => SocketAddr::new((**ip).into(), *port),
+        _ => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{
+        crypto::CryptoError,
+        endpoint,
+        event::{builder, IntoEvent},
+        time::{Clock, NoopClock},
+        transport,
+    };
+    use std::time::Duration;
+
+    /// Forwards the records to the test
+    struct Channel(mpsc::Sender<Record>);
+
+    impl Target for Channel {
+        fn write(&mut self, record: &Record) -> io::Result<()> {
+            let _ = self.0.send(record.clone());
+            Ok(())
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    /// Parses the `key=value` pairs of a formatted record
+    fn fields(line: &str) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+        let mut chars = line.chars().peekable();
+
+        while chars.peek().is_some() {
+            let key: String = chars.by_ref().take_while(|c| *c != '=').collect();
+            let mut value = String::new();
+
+            if chars.peek() == Some(&'"') {
+                // quoted values may contain spaces and escaped quotes
+                value.push(chars.next().unwrap());
+                while let Some(c) = chars.next() {
+                    value.push(c);
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                assert!(matches!(chars.next(), None | Some(' ')), "{}", line);
+            } else {
+                value = chars.by_ref().take_while(|c| *c != ' ').collect();
+            }
+
+            assert!(!value.is_empty(), "{} has no value in {:?}", key, line);
+            assert!(fields.insert(key, value).is_none(), "{}", line);
+        }
+
+        fields
+    }
+
+    #[test]
+    fn compliance_test() {
+        assert_send_sync::<AuditLogger>();
+
+        let peer_address: SocketAddr = "192.0.2.1:4433".parse().unwrap();
+        let kinds = [
+            (
+                RecordKind::ConnectionEstablished {
+                    server_name: Some("localhost".to_string()),
+                },
+                &["server_name"][..],
+            ),
+            (
+                RecordKind::ConnectionEstablished { server_name: None },
+                &["server_name"][..],
+            ),
+            (
+                RecordKind::ConnectionClosed {
+                    reason: "closed by \"peer\"".to_string(),
+                },
+                &["reason"][..],
+            ),
+            (
+                RecordKind::StreamOpened { stream_id: 4 },
+                &["stream_id"][..],
+            ),
+            (
+                RecordKind::StreamClosed { stream_id: 4 },
+                &["stream_id"][..],
+            ),
+            (
+                RecordKind::KeyUpdate {
+                    generation: 1,
+                    cipher_suite: "TLS_AES_128_GCM_SHA256",
+                },
+                &["generation", "cipher_suite"][..],
+            ),
+            (
+                RecordKind::Migration {
+                    previous_address: "192.0.2.1:4432".parse().unwrap(),
+                },
+                &["previous_address"][..],
+            ),
+            (
+                RecordKind::MigrationDenied {
+                    reason: "BlockedPort".to_string(),
+                },
+                &["reason"][..],
+            ),
+            (
+                RecordKind::AuthenticationFailure {
+                    reason: "bad certificate".to_string(),
+                },
+                &["reason"][..],
+            ),
+        ];
+
+        for (kind, kind_fields) in kinds.iter() {
+            let record = Record {
+                time: UNIX_EPOCH + Duration::from_micros(1_650_000_000_123_456),
+                connection_id: 7,
+                peer_address,
+                kind: kind.clone(),
+            };
+            let line = record.to_string();
+            let fields = fields(&line);
+
+            // the fields required of every record
+            assert_eq!(fields["time"], "1650000000.123456", "{}", line);
+            assert_eq!(fields["connection_id"], "7", "{}", line);
+            assert_eq!(fields["peer_address"], "192.0.2.1:4433", "{}", line);
+            assert_eq!(fields["event"], kind.name(), "{}", line);
+
+            for field in kind_fields.iter() {
+                assert!(fields.contains_key(*field), "{} is missing {}", line, field);
+            }
+            assert_eq!(fields.len(), 4 + kind_fields.len(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn queue_test() {
+        let (sender, records) = mpsc::channel();
+        let logger = AuditLogger::builder()
+            .with_target(Channel(sender))
+            .build()
+            .unwrap();
+
+        let meta: ConnectionMeta = builder::ConnectionMeta {
+            endpoint_type: endpoint::Type::Server,
+            id: 7,
+            timestamp: NoopClock.get_time(),
+        }
+        .into_event();
+        let context = ConnectionContext::new(true, "192.0.2.1:4433".parse().unwrap());
+        logger.record(&context, &meta, RecordKind::StreamOpened { stream_id: 0 });
+
+        let record = records.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(record.connection_id, 7);
+        assert_eq!(record.peer_address, context.peer_address);
+        assert_eq!(record.kind, RecordKind::StreamOpened { stream_id: 0 });
+        assert_eq!(logger.dropped_records(), 0);
+    }
+
+    /// Signals the test when the target is dropped
+    struct DropSignal(mpsc::Sender<()>);
+
+    impl Target for DropSignal {
+        fn write(&mut self, _record: &Record) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for DropSignal {
+        fn drop(&mut self) {
+            let _ = self.0.send(());
+        }
+    }
+
+    #[test]
+    fn worker_exit_test() {
+        let (sender, dropped) = mpsc::channel();
+        let logger = AuditLogger::builder()
+            .with_target(DropSignal(sender))
+            .build()
+            .unwrap();
+        let clone = logger.clone();
+
+        drop(logger);
+        assert!(dropped.recv_timeout(Duration::from_millis(100)).is_err());
+
+        // the thread exits and releases the targets once the last clone is dropped
+        drop(clone);
+        dropped.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn stream_state_test() {
+        let (sender, records) = mpsc::channel();
+        let logger = AuditLogger::builder()
+            .with_target(Channel(sender))
+            .build()
+            .unwrap();
+
+        let meta: ConnectionMeta = builder::ConnectionMeta {
+            endpoint_type: endpoint::Type::Server,
+            id: 7,
+            timestamp: NoopClock.get_time(),
+        }
+        .into_event();
+        let mut context = ConnectionContext::new(true, "192.0.2.1:4433".parse().unwrap());
+
+        let stream = |id: u64, is_fin: bool| -> events::Frame {
+            builder::Frame::Stream {
+                id,
+                offset: 0,
+                len: 1,
+                is_fin,
+            }
+            .into_event()
+        };
+
+        // client-initiated bidirectional streams
+        for id in [0, 4, 8] {
+            logger.on_stream_frame(&mut context, &meta, &stream(id, false), false);
+        }
+        assert_eq!(context.streams.len(), 3);
+
+        // stream 4 finishes in both directions before the others
+        logger.on_stream_frame(&mut context, &meta, &stream(4, true), false);
+        logger.on_stream_frame(&mut context, &meta, &stream(4, true), true);
+        assert_eq!(context.streams.len(), 2);
+
+        // retransmissions for the closed stream are ignored
+        logger.on_stream_frame(&mut context, &meta, &stream(4, true), true);
+        assert_eq!(context.streams.len(), 2);
+
+        // streams opened before the closed stream are still tracked
+        logger.on_stream_frame(&mut context, &meta, &stream(0, true), false);
+        logger.on_stream_frame(&mut context, &meta, &stream(0, true), true);
+        assert!(!context.streams.contains_key(&0));
+        assert_eq!(context.streams.len(), 1);
+
+        let expected = [
+            RecordKind::StreamOpened { stream_id: 0 },
+            RecordKind::StreamOpened { stream_id: 4 },
+            RecordKind::StreamOpened { stream_id: 8 },
+            RecordKind::StreamClosed { stream_id: 4 },
+            RecordKind::StreamClosed { stream_id: 0 },
+        ];
+        for kind in expected {
+            let record = records.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(record.kind, kind);
+        }
+        assert!(records.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn authentication_failure_test() {
+        let alert = CryptoError::new(42);
+        assert!(is_authentication_failure(&alert.into()));
+        assert!(!is_authentication_failure(
+            &transport::Error::PROTOCOL_VIOLATION.into()
+        ));
+        assert!(!is_authentication_failure(&connection::Error::closed(
+            endpoint::Location::Remote
+        )));
+    }
+}
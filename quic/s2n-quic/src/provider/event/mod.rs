@@ -24,6 +24,9 @@ pub trait Provider {
 /// Provides an implementation to disable all events
 pub mod disabled;
 
+/// Records security-relevant connection events for auditing
+pub mod audit;
+
 /// This module contains event integration with [`tracing`](https://docs.rs/tracing)
 #[cfg(any(feature = "provider-event-tracing", test))]
 pub mod tracing;
@@ -153,15 +153,17 @@ macro_rules! impl_providers_state {
             type Output = Builder<Providers<$(Provider::$prev_ty, )* New $(, Provider::$rest_ty)*>>;
 
             fn with(self, $field: New) -> Self::Output {
-                let providers = self.0.build();
-                Builder(Providers {
-                    $field,
-                    $(
-                        $prev: providers.$prev,
-                    )*
-                    $(
-                        $rest: providers.$rest,
-                    )*
+                self.map_providers(|providers| {
+                    let providers = providers.build();
+                    Providers {
+                        $field,
+                        $(
+                            $prev: providers.$prev,
+                        )*
+                        $(
+                            $rest: providers.$rest,
+                        )*
+                    }
                 })
             }
         }
@@ -16,6 +16,9 @@ pub use random::Provider as Default;
 
 impl_provider_utils!();
 
+#[cfg(feature = "ring")]
+pub mod hmac;
+
 mod random {
     use core::convert::Infallible;
     use rand::prelude::*;
@@ -0,0 +1,96 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derives stateless reset tokens from the connection ID with a static key
+//!
+//! Each connection ID issued to the peer gets its own token, computed as
+//! `HMAC-SHA256(key, local_connection_id)` truncated to the token length. Since the token only
+//! depends on the key and the connection ID, an endpoint which lost the state of a connection
+//! can still send a stateless reset the peer recognizes, as long as it is started with the same
+//! key. Tokens of retired connection IDs are forgotten by the peer along with the IDs, so
+//! rotating connection IDs rotates the tokens as well.
+//!
+//! See https://www.rfc-editor.org/rfc/rfc9000#section-10.3.2
+
+use core::convert::Infallible;
+use ring::hmac;
+use s2n_quic_core::{frame::new_connection_id::STATELESS_RESET_TOKEN_LEN, stateless_reset};
+
+#[derive(Debug)]
+pub struct Provider(Generator);
+
+impl super::Provider for Provider {
+    type Generator = Generator;
+    type Error = Infallible;
+
+    fn start(self) -> Result<Self::Generator, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+impl super::TryInto for Generator {
+    type Provider = Provider;
+    type Error = Infallible;
+
+    fn try_into(self) -> Result<Self::Provider, Self::Error> {
+        Ok(Provider(self))
+    }
+}
+
+/// Generates stateless reset tokens with an HMAC of the local connection ID
+#[derive(Debug)]
+pub struct Generator {
+    key: hmac::Key,
+}
+
+impl Generator {
+    /// Creates a generator with the provided key material
+    ///
+    /// The key must be kept secret, and should be the same across restarts of the endpoint so
+    /// the tokens of connections established before a restart remain valid.
+    pub fn new(key_material: &[u8]) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, key_material),
+        }
+    }
+}
+
+impl stateless_reset::token::Generator for Generator {
+    const ENABLED: bool = true;
+
+    fn generate(&mut self, local_connection_id: &[u8]) -> stateless_reset::Token {
+        let tag = hmac::sign(&self.key, local_connection_id);
+        let mut token = [0u8; STATELESS_RESET_TOKEN_LEN];
+        token.copy_from_slice(&tag.as_ref()[..STATELESS_RESET_TOKEN_LEN]);
+        token.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::{connection, stateless_reset::token::Generator as _};
+
+    #[test]
+    fn stateless_reset_token_test() {
+        let mut generator = Generator::new(b"key");
+        let id_1 = connection::LocalId::try_from_bytes(b"id01").unwrap();
+        let id_2 = connection::LocalId::try_from_bytes(b"id02").unwrap();
+
+        // the same connection ID always gets the same token
+        let token_1 = generator.generate(id_1.as_bytes());
+        assert_eq!(token_1, generator.generate(id_1.as_bytes()));
+
+        // a new connection ID gets a new token
+        let token_2 = generator.generate(id_2.as_bytes());
+        assert_ne!(token_1, token_2);
+
+        // an endpoint restarted with the same key generates the same tokens
+        let mut restarted = Generator::new(b"key");
+        assert_eq!(token_1, restarted.generate(id_1.as_bytes()));
+
+        // the tokens can't be derived without the key
+        let mut other = Generator::new(b"other key");
+        assert_ne!(token_1, other.generate(id_1.as_bytes()));
+    }
+}
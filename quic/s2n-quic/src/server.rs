@@ -8,9 +8,11 @@ use core::{
 };
 use s2n_quic_transport::endpoint::handle::Acceptor;
 
+mod alpn;
 mod builder;
 mod providers;
 
+pub use alpn::{AlpnDispatcher, ConnectionHandler};
 pub use builder::*;
 pub use providers::*;
 pub use s2n_quic_core::application::ServerName as Name;
@@ -0,0 +1,217 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routes accepted connections to a handler based on the negotiated application protocol
+//!
+//! When a server supports multiple application protocols, the [`AlpnDispatcher`] maps each
+//! protocol to a [`ConnectionHandler`]. The protocols registered with the dispatcher should
+//! be used to configure the TLS provider so only those protocols are negotiated during the
+//! handshake. If none of the protocols offered by the client are registered, the handshake
+//! fails and the connection is closed with a `no_application_protocol` TLS alert (QUIC error
+//! code 0x178).
+//!
+//! Handlers can be added and removed while the server is running. A dispatcher is cheap to
+//! clone and all clones share the same handlers, so a clone can be used to update the handlers
+//! from another task while connections are being dispatched. New connections are routed with
+//! the updated handlers while connections that were already dispatched are unaffected. Note
+//! that the TLS provider is configured when the server is started, so a protocol added later
+//! is only negotiated if it was included in the TLS configuration.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # use std::{error::Error, path::Path};
+//! # use s2n_quic::{provider::tls, server::AlpnDispatcher, Connection, Server};
+//! #
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn Error>> {
+//! let dispatcher = AlpnDispatcher::new()
+//!     .with_handler("h3", |connection: Connection| {
+//!         // handle HTTP/3 connections
+//!         # let _ = connection;
+//!     })
+//!     .with_handler("my-protocol", |connection: Connection| {
+//!         // handle connections for a custom protocol
+//!         # let _ = connection;
+//!     });
+//!
+//! let tls = tls::default::Server::builder()
+//!     .with_certificate(Path::new("./certs/cert.pem"), Path::new("./certs/key.pem"))?
+//!     .with_application_protocols(dispatcher.application_protocols())?
+//!     .build()?;
+//!
+//! let mut server = Server::builder()
+//!     .with_tls(tls)?
+//!     .with_io("127.0.0.1:443")?
+//!     .start()?;
+//!
+//! while let Some(connection) = server.accept().await {
+//!     dispatcher.dispatch(connection)?;
+//! }
+//! #
+//! #    Ok(())
+//! # }
+//! ```
+
+use crate::connection::{self, Connection};
+use bytes::Bytes;
+use core::fmt;
+use s2n_quic_core::{crypto::CryptoError, transport};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// Handles connections which negotiated a particular application protocol
+pub trait ConnectionHandler: 'static + Send {
+    /// Called with each accepted connection which negotiated the handler's protocol
+    fn handle(&mut self, connection: Connection);
+}
+
+impl<F: FnMut(Connection) + Send + 'static> ConnectionHandler for F {
+    #[inline]
+    fn handle(&mut self, connection: Connection) {
+        (self)(connection)
+    }
+}
+
+type Handler = Arc<Mutex<Box<dyn ConnectionHandler>>>;
+
+/// The registered handlers, in order of preference
+type Handlers = Vec<(Bytes, Handler)>;
+
+/// Dispatches accepted connections to the [`ConnectionHandler`] registered for the negotiated
+/// application protocol
+#[derive(Clone, Default)]
+pub struct AlpnDispatcher {
+    /// The registered handlers, shared by all clones of the dispatcher
+    ///
+    /// Updates replace the entire list, so dispatching a connection only holds the lock long
+    /// enough to clone the current list.
+    handlers: Arc<RwLock<Arc<Handlers>>>,
+}
+
+impl fmt::Debug for AlpnDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AlpnDispatcher")
+            .field(
+                "protocols",
+                &self.snapshot().iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl AlpnDispatcher {
+    /// Creates a dispatcher without any handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a handler for the given application protocol
+    ///
+    /// Protocols are preferred in the order they are registered. Registering a protocol
+    /// more than once replaces the previous handler.
+    pub fn with_handler<P: AsRef<[u8]>, H: ConnectionHandler>(
+        self,
+        protocol: P,
+        handler: H,
+    ) -> Self {
+        self.add_handler(protocol, handler);
+        self
+    }
+
+    /// Registers a handler for the given application protocol on a running dispatcher
+    ///
+    /// Connections dispatched after this call returns are routed to the new handler.
+    /// Registering a protocol more than once replaces the previous handler, though
+    /// connections already passed to the previous handler are unaffected.
+    pub fn add_handler<P: AsRef<[u8]>, H: ConnectionHandler>(&self, protocol: P, handler: H) {
+        let protocol = Bytes::copy_from_slice(protocol.as_ref());
+        let handler: Handler = Arc::new(Mutex::new(Box::new(handler)));
+
+        self.update(|handlers| {
+            if let Some(entry) = handlers.iter_mut().find(|(p, _)| *p == protocol) {
+                entry.1 = handler;
+            } else {
+                handlers.push((protocol, handler));
+            }
+        });
+    }
+
+    /// Removes the handler for the given application protocol
+    ///
+    /// Connections dispatched after this call returns which negotiated the protocol are
+    /// closed. Connections already passed to the handler are unaffected. Returns `false` if
+    /// no handler was registered for the protocol.
+    pub fn remove_handler<P: AsRef<[u8]>>(&self, protocol: P) -> bool {
+        let protocol = protocol.as_ref();
+        let mut removed = false;
+
+        self.update(|handlers| {
+            let len = handlers.len();
+            handlers.retain(|(p, _)| p != protocol);
+            removed = handlers.len() != len;
+        });
+
+        removed
+    }
+
+    /// Returns the registered application protocols, in order of preference
+    ///
+    /// This should be passed to the TLS provider so only protocols with a
+    /// registered handler are negotiated.
+    pub fn application_protocols(&self) -> impl Iterator<Item = Bytes> {
+        self.snapshot()
+            .iter()
+            .map(|(protocol, _)| protocol.clone())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Routes the connection to the handler for its negotiated application protocol
+    ///
+    /// If the TLS provider negotiated a protocol without a registered handler, the connection
+    /// is closed with a `no_application_protocol` TLS alert (QUIC error code 0x178) and an
+    /// [`Error::Transport`](connection::Error::Transport) is returned.
+    pub fn dispatch(&self, connection: Connection) -> connection::Result<()> {
+        let protocol = connection.application_protocol()?;
+        let handlers = self.snapshot();
+
+        if let Some((_, handler)) = handlers.iter().find(|(p, _)| *p == protocol) {
+            handler
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .handle(connection);
+            return Ok(());
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-8.1
+        //# When using ALPN, endpoints MUST immediately close a connection (see
+        //# Section 10.2 of [QUIC-TRANSPORT]) with a no_application_protocol TLS
+        //# alert (QUIC error code 0x0178; see Section 4.8) if an application
+        //# protocol is not negotiated.
+        let error = transport::Error::from(CryptoError::NO_APPLICATION_PROTOCOL)
+            .with_reason("no handler for the negotiated application protocol");
+        connection.close_with_transport_error(error);
+        Err(error.into())
+    }
+
+    /// Returns the currently registered handlers
+    #[inline]
+    fn snapshot(&self) -> Arc<Handlers> {
+        self.handlers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the registered handlers with a copy modified by `f`
+    #[inline]
+    fn update<F: FnOnce(&mut Handlers)>(&self, f: F) {
+        let mut handlers = self
+            .handlers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut updated = Handlers::clone(&handlers);
+        f(&mut updated);
+        *handlers = Arc::new(updated);
+    }
+}
@@ -16,6 +16,13 @@ impl Default for Builder<DefaultProviders> {
     }
 }
 
+impl<Providers> Builder<Providers> {
+    #[inline]
+    pub(crate) fn map_providers<F: FnOnce(Providers) -> P, P>(self, f: F) -> Builder<P> {
+        Builder(f(self.0))
+    }
+}
+
 impl<Providers: ServerProviders> Builder<Providers> {
     impl_provider_method!(
         /// Sets the connection ID provider for the [`Server`]
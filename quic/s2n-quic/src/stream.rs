@@ -15,7 +15,10 @@ mod bidirectional;
 mod local;
 mod peer;
 
-pub use s2n_quic_core::stream::{StreamError as Error, StreamType as Type};
+pub use s2n_quic_core::stream::{
+    StreamError as Error, StreamMetricsTotals as MetricsTotals, StreamPriority as Priority,
+    StreamType as Type, WriteAmplification,
+};
 
 pub use bidirectional::*;
 pub use local::*;
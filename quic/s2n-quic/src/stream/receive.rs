@@ -204,6 +204,40 @@ macro_rules! impl_receive_stream_api {
             $dispatch_body
         }
 
+        /// Donates unused flow control credits to another stream on the same connection
+        ///
+        /// The credits must have already been granted to the peer on this stream without the
+        /// peer having used them yet. The window this stream maintains towards the peer shrinks by
+        /// `bytes`, while the window of the stream with the id `to_stream` grows by `bytes`.
+        /// The new window is sent to the peer immediately.
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        ///
+        /// - `Ok(())` if the credits were donated.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        ///   An error is returned if this stream does not have `bytes` of unused credits or if
+        ///   `to_stream` is not able to receive data.
+        #[inline]
+        pub fn donate_credits(&mut self, to_stream: u64, bytes: u64) -> $crate::stream::Result<()> {
+            let to_stream = s2n_quic_core::varint::VarInt::new(to_stream)
+                .map(s2n_quic_core::stream::StreamId::from_varint)
+                .map_err(|_| $crate::stream::Error::invalid_stream())?;
+
+            macro_rules! $dispatch {
+                () => {
+                    Err($crate::stream::Error::non_readable())
+                };
+                ($variant: expr) => {
+                    $variant.donate_credits(to_stream.into(), bytes)
+                };
+            }
+
+            let $stream = self;
+            $dispatch_body
+        }
+
         /// Create a batch request for receiving data
         #[inline]
         pub(crate) fn rx_request(
@@ -199,6 +199,124 @@ macro_rules! impl_send_stream_api {
             $dispatch_body
         }
 
+        /// Enqueues a slice of chunks of data for sending without blocking the task.
+        ///
+        /// The chunks are not copied; only their reference counts are incremented. Chunks which
+        /// are enqueued together are coalesced into as few `STREAM` frames as the path MTU
+        /// permits, which avoids the overhead of sending each chunk in its own frame.
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        /// - `Ok(len)` with the number of bytes that were enqueued. This will be less than the
+        ///   total length of `bufs` if the stream's send buffer capacity was exhausted.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # fn test() -> s2n_quic::stream::Result<()> {
+        /// #   let mut stream: s2n_quic::stream::SendStream = todo!();
+        /// #
+        /// let headers = bytes::Bytes::from_static(&[1, 2, 3]);
+        /// let body = bytes::Bytes::from_static(&[4, 5, 6]);
+        /// let len = stream.write_vectored(&[headers, body])?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub fn write_vectored(&mut self, bufs: &[bytes::Bytes]) -> $crate::stream::Result<usize> {
+            macro_rules! $dispatch {
+                () => {
+                    Err($crate::stream::Error::non_writable())
+                };
+                ($variant: expr) => {
+                    $variant.write_vectored(bufs)
+                };
+            }
+
+            let $stream = self;
+            $dispatch_body
+        }
+
+        /// Enqueues as much of `data` for sending as the stream accepts before `deadline`.
+        ///
+        /// `deadline` is a timer future from the runtime the endpoint runs on, such as
+        /// `tokio::time::sleep_until` for the default IO provider, so the deadline follows the
+        /// same clock as the connection.
+        ///
+        /// This behaves like a blocking socket write with `SO_SNDTIMEO` set: if the stream runs
+        /// out of send capacity, the call waits for more capacity until the deadline elapses and
+        /// then returns the number of bytes which were enqueued so far. Any capacity which is
+        /// available when the call is made is always used, even if the deadline already elapsed.
+        ///
+        /// Data is only ever enqueued in whole chunks, so the stream contains exactly the first
+        /// `len` bytes of `data` when the call returns `Ok(len)`. Writing the remaining
+        /// `&data[len..]` in a later call continues the stream without gaps or duplicates.
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        /// - `Ok(len)` with the number of bytes that were enqueued. This will be less than
+        ///   `data.len()` if the deadline elapsed before all of `data` was enqueued.
+        /// - `Err(DeadlineExceeded)` if the deadline elapsed before any of `data` was enqueued.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # use std::time::Duration;
+        /// # async fn test() -> s2n_quic::stream::Result<()> {
+        /// #   let mut stream: s2n_quic::stream::SendStream = todo!();
+        /// #
+        /// let data = [1, 2, 3, 4];
+        /// let deadline = tokio::time::sleep(Duration::from_millis(100));
+        /// let len = stream.write_with_deadline(&data, deadline).await?;
+        /// if len < data.len() {
+        ///     println!("the peer is reading too slowly; only sent {} bytes", len);
+        /// }
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        pub async fn write_with_deadline<Deadline>(
+            &mut self,
+            data: &[u8],
+            deadline: Deadline,
+        ) -> $crate::stream::Result<usize>
+        where
+            Deadline: core::future::Future<Output = ()>,
+        {
+            use core::{future::Future, task::Poll};
+
+            ::futures::pin_mut!(deadline);
+            let mut written = 0;
+
+            ::futures::future::poll_fn(|cx| {
+                while written < data.len() {
+                    let available = match self.poll_send_ready(cx) {
+                        Poll::Ready(available) => available?,
+                        Poll::Pending if deadline.as_mut().poll(cx).is_ready() => {
+                            if written == 0 {
+                                return Err($crate::stream::Error::deadline_exceeded()).into();
+                            }
+                            return Ok(written).into();
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    };
+
+                    let len = available.min(data.len() - written);
+                    let chunk = bytes::Bytes::copy_from_slice(&data[written..written + len]);
+                    self.send_data(chunk)?;
+                    written += len;
+                }
+
+                Ok(written).into()
+            })
+            .await
+        }
+
         /// Flushes the stream and waits for the peer to receive all outstanding data.
         ///
         /// # Return value
@@ -376,6 +494,62 @@ macro_rules! impl_send_stream_api {
             let $stream = self;
             $dispatch_body
         }
+
+        /// Returns the number of bytes written to STREAM frames per byte of stream data
+        ///
+        /// The wire bytes include the STREAM frame headers and any retransmitted data, so the
+        /// ratio grows as packets carrying the stream's data are lost.
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        /// - `Ok(amplification)` with the payload and wire bytes of the stream.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        #[inline]
+        pub fn write_amplification(
+            &mut self,
+        ) -> $crate::stream::Result<$crate::stream::WriteAmplification> {
+            macro_rules! $dispatch {
+                () => {
+                    Err($crate::stream::Error::non_writable())
+                };
+                ($variant: expr) => {
+                    $variant.write_amplification()
+                };
+            }
+
+            let $stream = self;
+            $dispatch_body
+        }
+
+        /// Changes the priority of the stream when sharing the sending rate of the connection
+        ///
+        /// Congestion controllers may reserve part of the sending rate for streams at or above
+        /// a given priority, in which case lower priority streams only share the rest. Streams
+        /// default to [`Priority::Normal`](crate::stream::Priority::Normal).
+        ///
+        /// # Return value
+        ///
+        /// The function returns:
+        /// - `Ok(())` if the priority was changed.
+        /// - `Err(e)` if the stream encountered a [`stream::Error`](crate::stream::Error).
+        #[inline]
+        pub fn set_priority(
+            &mut self,
+            priority: $crate::stream::Priority,
+        ) -> $crate::stream::Result<()> {
+            macro_rules! $dispatch {
+                () => {
+                    Err($crate::stream::Error::non_writable())
+                };
+                ($variant: expr) => {
+                    $variant.set_priority(priority)
+                };
+            }
+
+            let $stream = self;
+            $dispatch_body
+        }
     };
 }
 
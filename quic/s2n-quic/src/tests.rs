@@ -172,3 +172,3212 @@ fn stream_reset_test() {
     })
     .unwrap();
 }
+
+/// Opens a bidirectional stream while the peer does the same and exchanges a message on each
+async fn exchange_bidirectional_streams(
+    connection: crate::Connection,
+    local_initiator: u64,
+    message: &'static [u8],
+    peer_message: &'static [u8],
+) {
+    let (mut handle, mut acceptor) = connection.split();
+
+    let send = async move {
+        let mut stream = handle.open_bidirectional_stream().await.unwrap();
+        // the low bit of a stream ID identifies the endpoint which initiated it
+        assert_eq!(stream.id() & 0x1, local_initiator);
+        // the second bit is not set for bidirectional streams
+        assert_eq!(stream.id() & 0x2, 0);
+
+        stream.send(Bytes::from_static(message)).await.unwrap();
+        stream.close().await.unwrap();
+        handle
+    };
+
+    let receive = async move {
+        let mut stream = acceptor
+            .accept_bidirectional_stream()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stream.id() & 0x1, local_initiator ^ 0x1);
+        assert_eq!(stream.id() & 0x2, 0);
+
+        let mut received = vec![];
+        while let Some(chunk) = stream.receive().await.unwrap() {
+            received.extend_from_slice(&chunk);
+        }
+        assert_eq!(received, peer_message);
+        acceptor
+    };
+
+    // keep the connection open until both sides are done
+    let (_handle, _acceptor) = futures::join!(send, receive);
+    delay(Duration::from_secs(1)).await;
+}
+
+#[test]
+fn simultaneous_bidirectional_streams_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let connection = server.accept().await.unwrap();
+            exchange_bidirectional_streams(connection, 0x1, b"server", b"client").await;
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let connection = client.connect(connect).await.unwrap();
+            exchange_bidirectional_streams(connection, 0x0, b"client", b"server").await;
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Ensures streams blocked on `receive` observe the error as soon as the connection is closed
+#[test]
+fn close_resets_pending_streams_test() {
+    use core::task::Poll;
+    use futures::future::poll_fn;
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+
+            stream.receive().await.unwrap().unwrap();
+
+            // the client doesn't send any more data so the stream is blocked
+            let poll = poll_fn(|cx| Poll::Ready(stream.poll_receive(cx))).await;
+            assert!(poll.is_pending());
+
+            connection.close(123u8.into());
+
+            // the stream is reset on the next poll
+            let poll = poll_fn(|cx| Poll::Ready(stream.poll_receive(cx))).await;
+            match poll {
+                Poll::Ready(Err(crate::stream::Error::ConnectionError {
+                    error: crate::connection::Error::Application { error, .. },
+                    ..
+                })) => {
+                    assert_eq!(error, crate::application::Error::from(123u8));
+                }
+                other => panic!("expected a connection error, got {:?}", other),
+            }
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+            stream.send(Bytes::from_static(&[42])).await.unwrap();
+
+            // the stream is closed once the server closes the connection
+            assert!(stream.receive().await.is_err());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn close_keeps_finished_streams_readable_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut finished = connection.accept_receive_stream().await.unwrap().unwrap();
+            let mut active = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+
+            // the client finished the first stream before opening the second one
+            active.receive().await.unwrap().unwrap();
+
+            connection.close(123u8.into());
+
+            // all of the data of the finished stream was received so it can still be read
+            let chunk = finished.receive().await.unwrap().unwrap();
+            assert_eq!(chunk, Bytes::from_static(b"finished"));
+            assert!(finished.receive().await.unwrap().is_none());
+
+            // the stream which was still transferring data observes the error code
+            match active.receive().await {
+                Err(crate::stream::Error::ConnectionError {
+                    error: crate::connection::Error::Application { error, .. },
+                    ..
+                }) => {
+                    assert_eq!(error, crate::application::Error::from(123u8));
+                }
+                other => panic!("expected a connection error, got {:?}", other),
+            }
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            let mut finished = connection.open_send_stream().await.unwrap();
+            finished
+                .send(Bytes::from_static(b"finished"))
+                .await
+                .unwrap();
+            // wait for the server to acknowledge the data and the FIN
+            finished.close().await.unwrap();
+
+            let mut active = connection.open_bidirectional_stream().await.unwrap();
+            active.send(Bytes::from_static(&[42])).await.unwrap();
+
+            // the stream is closed once the server closes the connection
+            assert!(active.receive().await.is_err());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Returns an `AlpnDispatcher` handler which responds to each stream with `name`
+fn alpn_handler(name: &'static str) -> impl FnMut(crate::Connection) + Send + 'static {
+    move |mut connection: crate::Connection| {
+        spawn(async move {
+            // respond to each stream with the name of the handler
+            while let Ok(Some(mut stream)) = connection.accept_bidirectional_stream().await {
+                spawn(async move {
+                    while stream.receive().await.unwrap().is_some() {}
+                    let _ = stream.send(Bytes::from_static(name.as_bytes())).await;
+                    let _ = stream.finish();
+                });
+            }
+        });
+    }
+}
+
+/// Ensures connections are routed to the handler for the negotiated application protocol
+#[test]
+fn alpn_dispatch_test() {
+    use crate::{provider::tls, server::AlpnDispatcher};
+    use s2n_quic_core::crypto::tls::testing::certificates;
+
+    const PROTOCOLS: [&str; 2] = ["h3", "custom"];
+
+    let model = Model::default();
+    test(model, |handle| {
+        let dispatcher = AlpnDispatcher::new()
+            .with_handler(PROTOCOLS[0], alpn_handler(PROTOCOLS[0]))
+            .with_handler(PROTOCOLS[1], alpn_handler(PROTOCOLS[1]));
+
+        let server_tls = tls::default::Server::builder()
+            .with_certificate(certificates::CERT_PEM, certificates::KEY_PEM)?
+            .with_application_protocols(dispatcher.application_protocols())?
+            .build()?;
+
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(server_tls)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            while let Some(connection) = server.accept().await {
+                dispatcher.dispatch(connection).unwrap();
+            }
+        });
+
+        for protocol in [PROTOCOLS[0], PROTOCOLS[1], "unknown"] {
+            let client_tls = tls::default::Client::builder()
+                .with_certificate(certificates::CERT_PEM)?
+                .with_application_protocols([protocol].iter())?
+                .build()?;
+
+            let client = crate::Client::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(client_tls)?
+                .with_event(events())?
+                .start()?;
+
+            primary::spawn(async move {
+                let connect = Connect::new(server_addr).with_server_name("localhost");
+                let connection = client.connect(connect).await;
+
+                if !PROTOCOLS.contains(&protocol) {
+                    // the handshake fails if no handler is registered for any offered protocol
+                    assert!(connection.is_err());
+                    return;
+                }
+
+                let mut connection = connection.unwrap();
+                assert_eq!(
+                    connection.application_protocol().unwrap(),
+                    protocol.as_bytes()
+                );
+
+                let mut stream = connection.open_bidirectional_stream().await.unwrap();
+                stream.finish().unwrap();
+
+                let response = stream.receive().await.unwrap().unwrap();
+                assert_eq!(response, protocol.as_bytes());
+            });
+        }
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Ensures handlers can be added and removed while connections are being dispatched
+#[test]
+fn alpn_dispatch_hot_reload_test() {
+    use crate::{
+        provider::{
+            event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+            tls,
+        },
+        server::AlpnDispatcher,
+        Client, Connection,
+    };
+    use s2n_quic_core::{
+        crypto::{tls::testing::certificates, CryptoError},
+        transport,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    const PROTOCOLS: [&str; 2] = ["a", "b"];
+
+    /// Sends a request on a new stream and returns the name of the handler which responded
+    async fn request(connection: &mut Connection) -> Option<Bytes> {
+        let mut stream = connection.open_bidirectional_stream().await.ok()?;
+        stream.finish().ok()?;
+        stream.receive().await.ok()?
+    }
+
+    /// Connects to the server with the given client and sends a single request
+    async fn connect_and_request(
+        client: &Client,
+        server_addr: std::net::SocketAddr,
+    ) -> Option<Bytes> {
+        let connect = Connect::new(server_addr).with_server_name("localhost");
+        let mut connection = client.connect(connect).await.ok()?;
+        request(&mut connection).await
+    }
+
+    /// Records the errors of closed connections
+    #[derive(Clone, Default)]
+    struct ClosedConnections(Arc<Mutex<Vec<crate::connection::Error>>>);
+
+    impl Subscriber for ClosedConnections {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_closed(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::ConnectionClosed,
+        ) {
+            self.0.lock().unwrap().push(event.error);
+        }
+    }
+
+    let successes = [Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0))];
+    let closed_connections = ClosedConnections::default();
+
+    let model = Model::default();
+    test(model, |handle| {
+        let dispatcher =
+            AlpnDispatcher::new().with_handler(PROTOCOLS[0], alpn_handler(PROTOCOLS[0]));
+
+        // the TLS provider negotiates both protocols, even though "b" doesn't have a handler yet
+        let server_tls = tls::default::Server::builder()
+            .with_certificate(certificates::CERT_PEM, certificates::KEY_PEM)?
+            .with_application_protocols(PROTOCOLS.iter())?
+            .build()?;
+
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(server_tls)?
+            .with_event((closed_connections.clone(), events()))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        let accept_dispatcher = dispatcher.clone();
+        spawn(async move {
+            while let Some(connection) = server.accept().await {
+                // connections without a handler are closed by the dispatcher
+                let _ = accept_dispatcher.dispatch(connection);
+            }
+        });
+
+        let mut clients = vec![];
+        for protocol in PROTOCOLS {
+            let client_tls = tls::default::Client::builder()
+                .with_certificate(certificates::CERT_PEM)?
+                .with_application_protocols([protocol].iter())?
+                .build()?;
+
+            clients.push(
+                Client::builder()
+                    .with_io(handle.builder().build()?)?
+                    .with_tls(client_tls)?
+                    .with_event(events())?
+                    .start()?,
+            );
+        }
+
+        // continuously open connections for both protocols while the handlers are updated
+        let load = PROTOCOLS.into_iter().zip(&clients).zip(&successes);
+        for ((protocol, client), successes) in load {
+            let client = client.clone();
+            let successes = successes.clone();
+            spawn(async move {
+                loop {
+                    if let Some(response) = connect_and_request(&client, server_addr).await {
+                        // connections are never routed to the wrong handler
+                        assert_eq!(response, protocol.as_bytes());
+                        successes.fetch_add(1, Ordering::Relaxed);
+                    }
+                    delay(Duration::from_millis(10)).await;
+                }
+            });
+        }
+
+        primary::spawn(async move {
+            let (client_a, client_b) = (&clients[0], &clients[1]);
+
+            // a connection which outlives the removal of its handler
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut existing = client_a.connect(connect).await.unwrap();
+            assert_eq!(request(&mut existing).await.unwrap(), PROTOCOLS[0]);
+
+            // "b" doesn't have a handler yet
+            assert!(connect_and_request(client_b, server_addr).await.is_none());
+
+            delay(Duration::from_secs(1)).await;
+            dispatcher.add_handler(PROTOCOLS[1], alpn_handler(PROTOCOLS[1]));
+            assert_eq!(
+                connect_and_request(client_b, server_addr).await.unwrap(),
+                PROTOCOLS[1]
+            );
+
+            delay(Duration::from_secs(1)).await;
+            assert!(dispatcher.remove_handler(PROTOCOLS[0]));
+            assert!(!dispatcher.remove_handler(PROTOCOLS[0]));
+            assert!(connect_and_request(client_a, server_addr).await.is_none());
+
+            // the existing connection continues to be served by its original handler
+            assert_eq!(request(&mut existing).await.unwrap(), PROTOCOLS[0]);
+
+            assert_eq!(
+                dispatcher.application_protocols().collect::<Vec<_>>(),
+                [PROTOCOLS[1]]
+            );
+
+            delay(Duration::from_secs(1)).await;
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    // both protocols were served while the handlers were being updated
+    for successes in successes.iter() {
+        assert!(successes.load(Ordering::Relaxed) > 0);
+    }
+
+    // connections without a handler are closed with a no_application_protocol alert
+    let no_application_protocol = transport::Error::from(CryptoError::NO_APPLICATION_PROTOCOL);
+    let closed_connections = closed_connections.0.lock().unwrap();
+    assert!(closed_connections.iter().any(|error| matches!(
+        error,
+        crate::connection::Error::Transport { code, .. } if *code == no_application_protocol.code
+    )));
+}
+
+#[test]
+fn accept_stream_timeout_test() {
+    use crate::{connection::AcceptError, stream::PeerStream};
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+
+            // the client waits before opening a stream
+            assert_eq!(
+                connection
+                    .accept_stream_timeout(delay(Duration::from_millis(100)))
+                    .await
+                    .unwrap_err(),
+                AcceptError::Timeout
+            );
+
+            // the stream arrives while no accept is pending and should remain queued
+            delay(Duration::from_secs(1)).await;
+
+            let stream = connection
+                .accept_stream_timeout(delay(Duration::from_secs(5)))
+                .await
+                .unwrap();
+            let mut stream = match stream {
+                PeerStream::Bidirectional(stream) => stream,
+                PeerStream::Receive(_) => panic!("expected a bidirectional stream"),
+            };
+            assert_eq!(
+                stream.receive().await.unwrap().unwrap(),
+                Bytes::from_static(b"hello")
+            );
+
+            assert!(matches!(
+                connection
+                    .accept_stream_timeout(delay(Duration::from_secs(5)))
+                    .await
+                    .unwrap_err(),
+                AcceptError::ConnectionClosed(Some(_))
+            ));
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            // only open the stream once the server's accept has timed out
+            delay(Duration::from_millis(500)).await;
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+            stream.send(Bytes::from_static(b"hello")).await.unwrap();
+
+            delay(Duration::from_secs(2)).await;
+            connection.close(123u8.into());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn write_with_deadline_test() {
+    use crate::{provider::limits::Limits, stream::Error};
+
+    fn data() -> Vec<u8> {
+        (0..2_000_000u32).map(|i| i as u8).collect()
+    }
+
+    let model = Model::default();
+    test(model, |handle| {
+        // keep the stream's flow control window small so the client's writes block quickly
+        let limits = Limits::new()
+            .with_bidirectional_remote_data_window(64 * 1024)
+            .unwrap();
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .with_limits(limits)?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+
+            // only read once the client's writes have timed out
+            delay(Duration::from_secs(1)).await;
+
+            let mut received = vec![];
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                received.extend_from_slice(&chunk);
+            }
+            assert!(received == data(), "the peer received different data");
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+            let data = data();
+
+            // the peer isn't reading, so only part of the data fits in the window and send buffer
+            let written = stream
+                .write_with_deadline(&data, delay(Duration::from_millis(200)))
+                .await
+                .unwrap();
+            assert!(written > 0);
+            assert!(written < data.len());
+
+            // nothing else can be enqueued until the peer reads
+            assert!(matches!(
+                stream
+                    .write_with_deadline(&data[written..], delay(Duration::from_millis(100)))
+                    .await,
+                Err(Error::DeadlineExceeded { .. })
+            ));
+
+            // continuing after the partial write sends the rest of the data without any gaps
+            let rest = stream
+                .write_with_deadline(&data[written..], delay(Duration::from_secs(10)))
+                .await
+                .unwrap();
+            assert_eq!(written + rest, data.len());
+
+            // keep the connection open until the peer has received all of the data
+            stream.close().await.unwrap();
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Ensures a server releases a connection which never receives the client's Finished message
+#[test]
+fn handshake_timeout_test() {
+    use crate::provider::{
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        packet_interceptor::PacketInterceptor,
+    };
+    use s2n_codec::DecoderBufferMut;
+    use s2n_quic_core::{
+        connection::{ConnectionTimeoutReason, Limits},
+        event::api::Subject,
+        packet::interceptor::Packet,
+        transport,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Drops the frames in all received Handshake packets
+    struct DropHandshake;
+
+    impl PacketInterceptor for DropHandshake {
+        fn intercept_rx_payload<'a>(
+            &mut self,
+            _subject: &Subject,
+            packet: &Packet,
+            payload: DecoderBufferMut<'a>,
+        ) -> DecoderBufferMut<'a> {
+            if packet.number.space().is_handshake() {
+                DecoderBufferMut::new(&mut [])
+            } else {
+                payload
+            }
+        }
+    }
+
+    /// Records the errors of closed connections
+    #[derive(Clone, Default)]
+    struct ClosedConnections(Arc<Mutex<Vec<crate::connection::Error>>>);
+
+    impl Subscriber for ClosedConnections {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_closed(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::ConnectionClosed,
+        ) {
+            self.0.lock().unwrap().push(event.error);
+        }
+    }
+
+    let closed_connections = ClosedConnections::default();
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(closed_connections.clone())?
+            .with_packet_interceptor(DropHandshake)?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            // the handshake never completes so the connection is never accepted
+            assert!(server.accept().await.is_none());
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let error = match client.connect(connect).await {
+                // the handshake is complete from the client's perspective
+                Ok(mut connection) => connection.accept_bidirectional_stream().await.unwrap_err(),
+                Err(error) => error,
+            };
+
+            // the server notifies the client that the connection was refused
+            match error {
+                crate::connection::Error::Transport { code, .. } => {
+                    assert_eq!(code, transport::Error::CONNECTION_REFUSED.code);
+                }
+                other => panic!("expected a transport error, got {:?}", other),
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let closed_connections = closed_connections.0.lock().unwrap();
+    assert_eq!(closed_connections.len(), 1);
+    match closed_connections[0] {
+        crate::connection::Error::Timeout {
+            reason: ConnectionTimeoutReason::HandshakeTimeout { elapsed },
+            ..
+        } => {
+            let max_handshake_duration = Limits::default().max_handshake_duration();
+            assert!(elapsed >= max_handshake_duration, "{:?}", elapsed);
+        }
+        other => panic!("expected a handshake timeout, got {:?}", other),
+    }
+}
+
+/// Ensures a connection reports how long the peer was silent when the idle timer expires
+#[test]
+fn idle_timeout_test() {
+    use crate::provider::{
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        limits::Limits,
+    };
+    use s2n_quic_core::{connection::ConnectionTimeoutReason, crypto::tls::testing::certificates};
+    use std::sync::{Arc, Mutex};
+
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Records the errors of closed connections
+    #[derive(Clone, Default)]
+    struct ClosedConnections(Arc<Mutex<Vec<crate::connection::Error>>>);
+
+    impl Subscriber for ClosedConnections {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_closed(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::ConnectionClosed,
+        ) {
+            self.0.lock().unwrap().push(event.error);
+        }
+    }
+
+    let closed_connections = ClosedConnections::default();
+
+    let model = Model::default();
+    test(model.clone(), |handle| {
+        let server_addr = server(handle)?;
+
+        let limits = Limits::new().with_max_idle_timeout(IDLE_TIMEOUT)?;
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_limits(limits)?
+            .with_event(closed_connections.clone())?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+            stream.send(Bytes::from_static(b"ping")).await.unwrap();
+            stream.receive().await.unwrap().unwrap();
+
+            // the peer goes silent
+            model.set_drop_rate(1.0);
+
+            // the idle timeout is reported to the application as the end of the stream
+            while let Ok(Some(_)) = stream.receive().await {}
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let closed_connections = closed_connections.0.lock().unwrap();
+    assert_eq!(closed_connections.len(), 1);
+    match closed_connections[0] {
+        crate::connection::Error::Timeout {
+            reason: ConnectionTimeoutReason::IdleTimeout { idle_duration },
+            ..
+        } => {
+            assert!(idle_duration >= IDLE_TIMEOUT, "{:?}", idle_duration);
+        }
+        other => panic!("expected an idle timeout, got {:?}", other),
+    }
+}
+
+fn graceful_close(close_and_flush: bool) {
+    use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+    use s2n_quic_core::endpoint;
+    use std::sync::{Arc, Mutex};
+
+    const CHUNK_LEN: usize = 10_000;
+    const CHUNK_COUNT: usize = 100;
+
+    /// Records the errors of closed connections
+    #[derive(Clone, Default)]
+    struct ClosedConnections(Arc<Mutex<Vec<crate::connection::Error>>>);
+
+    impl Subscriber for ClosedConnections {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_closed(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::ConnectionClosed,
+        ) {
+            self.0.lock().unwrap().push(event.error);
+        }
+    }
+
+    let closed_connections = ClosedConnections::default();
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(closed_connections.clone())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+
+            let mut received = 0;
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                received += chunk.len();
+            }
+            assert_eq!(received, CHUNK_LEN * CHUNK_COUNT);
+
+            // wait for the client to close the connection
+            while let Ok(Some(_)) = connection.accept().await {}
+        });
+
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_send_stream().await.unwrap();
+
+            for _ in 0..CHUNK_COUNT {
+                stream.send(Bytes::from(vec![42; CHUNK_LEN])).await.unwrap();
+            }
+
+            // close the connection while the stream still has outstanding data
+            drop(stream);
+
+            if close_and_flush {
+                connection.close_and_flush().await;
+            } else {
+                drop(connection);
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let closed_connections = closed_connections.0.lock().unwrap();
+    assert_eq!(closed_connections.len(), 1);
+    match closed_connections[0] {
+        crate::connection::Error::Closed { initiator, .. } => {
+            assert_eq!(initiator, endpoint::Location::Remote);
+        }
+        other => panic!("expected the peer to close the connection, got {:?}", other),
+    }
+}
+
+/// Ensures dropping a connection in the middle of a transfer flushes the outstanding data and
+/// notifies the peer with a CONNECTION_CLOSE frame instead of letting it time out
+#[test]
+fn drop_graceful_close_test() {
+    graceful_close(false);
+}
+
+/// Ensures `close_and_flush` flushes the outstanding data before closing the connection
+#[test]
+fn close_and_flush_test() {
+    graceful_close(true);
+}
+
+/// Ensures degraded paths are reported by both endpoints without the server initiating a
+/// migration
+#[test]
+fn degraded_path_test() {
+    use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const LEN: u64 = 1_000_000;
+
+    /// Counts path degradations and the migrations they trigger
+    #[derive(Clone, Default)]
+    struct QualityEvents {
+        path_degraded: Arc<AtomicUsize>,
+        migration_triggered: Arc<AtomicUsize>,
+    }
+
+    impl Subscriber for QualityEvents {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_path_degraded(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::PathDegraded,
+        ) {
+            self.path_degraded.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_migration_triggered(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::MigrationTriggered,
+        ) {
+            self.migration_triggered.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let server_events = QualityEvents::default();
+    let client_events = QualityEvents::default();
+
+    let model = Model::default();
+    // lose well above the 5% of packets at which a path is considered degraded
+    model.set_drop_rate(0.2);
+
+    test(model, |handle| {
+        let server_addr = server_with(handle, |io| {
+            Ok(Server::builder()
+                .with_io(io)?
+                .with_tls(SERVER_CERTS)?
+                .with_event((server_events.clone(), events()))?
+                .start()?)
+        })?;
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event((client_events.clone(), events()))?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            let stream = connection.open_bidirectional_stream().await.unwrap();
+            let (mut recv, mut send) = stream.split();
+
+            let mut send_data = Data::new(LEN);
+            let mut recv_data = send_data;
+
+            primary::spawn(async move {
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    send.send(chunk).await.unwrap();
+                }
+                send.finish().unwrap();
+            });
+
+            while let Some(chunk) = recv.receive().await.unwrap() {
+                recv_data.receive(&[chunk]);
+            }
+            assert!(recv_data.is_finished());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    // both endpoints observe the losses on the path
+    assert!(client_events.path_degraded.load(Ordering::Relaxed) > 0);
+    assert!(server_events.path_degraded.load(Ordering::Relaxed) > 0);
+    // the client has no other path to move to and the server never initiates a migration
+    assert_eq!(client_events.migration_triggered.load(Ordering::Relaxed), 0);
+    assert_eq!(server_events.migration_triggered.load(Ordering::Relaxed), 0);
+}
+
+/// Ensures a connection survives a NAT rebinding while data is actively flowing
+#[test]
+fn migrate_under_load() {
+    use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const LEN: u64 = 10_000_000;
+    const REBIND_OFFSET: u64 = 1_000_000;
+
+    /// Counts path migrations and spurious losses
+    #[derive(Clone, Default)]
+    struct MigrationEvents {
+        active_path_updated: Arc<AtomicUsize>,
+        spurious_loss_detected: Arc<AtomicUsize>,
+    }
+
+    impl Subscriber for MigrationEvents {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_active_path_updated(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::ActivePathUpdated,
+        ) {
+            self.active_path_updated.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_spurious_loss_detected(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::SpuriousLossDetected,
+        ) {
+            self.spurious_loss_detected.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let server_events = MigrationEvents::default();
+    let client_events = MigrationEvents::default();
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(server_events.clone())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+
+            let mut recv_data = Data::new(LEN);
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                recv_data.receive(&[chunk]);
+            }
+            assert!(recv_data.is_finished());
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(client_events.clone())?
+            .start()?;
+        let client_addr = client.local_addr()?;
+        let handle = handle.clone();
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_send_stream().await.unwrap();
+
+            let mut send_data = Data::new(LEN);
+            let mut is_rebound = false;
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                stream.send(chunk).await.unwrap();
+
+                if !is_rebound && send_data.offset() >= REBIND_OFFSET {
+                    // the client's source port changes in the middle of the transfer
+                    handle.rebind(client_addr);
+                    is_rebound = true;
+                }
+            }
+
+            // wait for the server to acknowledge all of the data
+            stream.close().await.unwrap();
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    assert!(
+        server_events.active_path_updated.load(Ordering::Relaxed) > 0,
+        "the server should migrate to the rebound address"
+    );
+    assert_eq!(
+        server_events.spurious_loss_detected.load(Ordering::Relaxed),
+        0
+    );
+    assert_eq!(
+        client_events.spurious_loss_detected.load(Ordering::Relaxed),
+        0
+    );
+}
+
+/// Ensures connection attempts over the per-prefix limit are refused without creating a
+/// connection on the server
+#[test]
+fn connection_limiter_test() {
+    use crate::provider::{
+        endpoint_limits::connection_limiter::{ConnectionLimiter, ConnectionLimiterConfig},
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+    };
+    use s2n_quic_core::transport;
+    use std::sync::{Arc, Mutex};
+
+    /// Counts the connections started on the server and the attempts it refused
+    #[derive(Clone, Default)]
+    struct Attempts(Arc<Mutex<(usize, usize)>>);
+
+    impl Subscriber for Attempts {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_started(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::ConnectionStarted,
+        ) {
+            self.0.lock().unwrap().0 += 1;
+        }
+
+        fn on_endpoint_connection_attempt_failed(
+            &mut self,
+            _meta: &events::EndpointMeta,
+            event: &events::EndpointConnectionAttemptFailed,
+        ) {
+            if let crate::connection::Error::Transport { code, .. } = event.error {
+                if code == transport::Error::CONNECTION_REFUSED.code {
+                    self.0.lock().unwrap().1 += 1;
+                }
+            }
+        }
+    }
+
+    let attempts = Attempts::default();
+
+    // all of the simulated endpoints share a /24 prefix
+    let limiter = ConnectionLimiter::new(ConnectionLimiterConfig {
+        max_connections_per_prefix: 1,
+        ..Default::default()
+    });
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_endpoint_limits(limiter.clone())?
+            .with_event((limiter.clone(), (attempts.clone(), events())))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            while let Some(mut connection) = server.accept().await {
+                // hold on to the connection until the peer closes it
+                spawn(async move { while let Ok(Some(_)) = connection.accept().await {} });
+            }
+        });
+
+        let first = build_client(handle)?;
+        let second = build_client(handle)?;
+        let limiter = limiter.clone();
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let connection = first.connect(connect.clone()).await.unwrap();
+
+            let error = second.connect(connect).await.unwrap_err();
+            match error {
+                crate::connection::Error::Transport { code, .. } => {
+                    assert_eq!(code, transport::Error::CONNECTION_REFUSED.code);
+                }
+                other => panic!("expected a transport error, got {:?}", other),
+            }
+
+            drop(connection);
+            delay(Duration::from_secs(1)).await;
+
+            // all of the connections have been released
+            assert_eq!(limiter.connection_count(), 0);
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    // the refused attempt was answered without starting a connection
+    let (started, refused) = *attempts.0.lock().unwrap();
+    assert_eq!(started, 1);
+    assert_eq!(refused, 1);
+}
+
+/// Attempts connections at 10 times the capacity of the server and ensures the attempts over
+/// capacity are refused without disrupting the transfers on the accepted connections
+#[test]
+fn admission_controller_test() {
+    use crate::provider::{
+        endpoint_limits::admission_controller::{
+            AdmissionController, AdmissionLimiter, ServerLoad,
+        },
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+    };
+    use s2n_quic_core::{stream::testing::Data, transport};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const CAPACITY: usize = 4;
+    const ATTEMPTS: usize = CAPACITY * 10;
+    const LEN: u64 = 1_000_000;
+
+    /// Treats each open connection as a fixed share of the server's resources
+    struct Capacity;
+
+    impl AdmissionController for Capacity {
+        fn should_accept(&mut self, server_load: &ServerLoad) -> bool {
+            server_load.connection_count < CAPACITY
+        }
+    }
+
+    /// Counts the connections started on the server
+    #[derive(Clone, Default)]
+    struct Started(Arc<AtomicUsize>);
+
+    impl Subscriber for Started {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_connection_started(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            _event: &events::ConnectionStarted,
+        ) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let started = Started::default();
+
+    test(Model::default(), |handle| {
+        let server_addr = server_with(handle, |io| {
+            Ok(Server::builder()
+                .with_io(io)?
+                .with_tls(SERVER_CERTS)?
+                .with_event((started.clone(), events()))?
+                .with_endpoint_limits(AdmissionLimiter::new(Capacity))?
+                .start()?)
+        })?;
+        let connect = Connect::new(server_addr).with_server_name("localhost");
+
+        let mut clients = vec![];
+        for _ in 0..CAPACITY {
+            clients.push(build_client(handle)?);
+        }
+
+        let mut refused_clients = vec![];
+        for _ in 0..ATTEMPTS {
+            refused_clients.push(build_client(handle)?);
+        }
+
+        primary::spawn(async move {
+            let mut connections = vec![];
+            for client in clients {
+                let connection = client.connect(connect.clone()).await.unwrap();
+                connections.push((client, connection));
+            }
+
+            for (client, mut connection) in connections {
+                primary::spawn(async move {
+                    let stream = connection.open_bidirectional_stream().await.unwrap();
+                    let (mut recv, mut send) = stream.split();
+
+                    let mut send_data = Data::new(LEN);
+                    let mut recv_data = send_data;
+
+                    primary::spawn(async move {
+                        while let Some(chunk) = send_data.send_one(usize::MAX) {
+                            send.send(chunk).await.unwrap();
+                        }
+                        send.finish().unwrap();
+                    });
+
+                    while let Some(chunk) = recv.receive().await.unwrap() {
+                        recv_data.receive(&[chunk]);
+                    }
+                    assert!(recv_data.is_finished());
+
+                    drop(connection);
+                    drop(client);
+                });
+            }
+
+            // flood the server while the accepted connections are transferring data
+            for client in refused_clients {
+                let connect = connect.clone();
+                primary::spawn(async move {
+                    let error = client.connect(connect).await.unwrap_err();
+                    match error {
+                        crate::connection::Error::Transport { code, .. } => {
+                            assert_eq!(code, transport::Error::CONNECTION_REFUSED.code);
+                        }
+                        other => panic!("expected a transport error, got {:?}", other),
+                    }
+                });
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    // the refused attempts never started a connection on the server
+    assert_eq!(started.0.load(Ordering::Relaxed), CAPACITY);
+}
+
+/// Ensures datagrams are padded according to the traffic shaping configuration without
+/// preventing the path MTU from being discovered
+#[test]
+fn traffic_shaping_test() {
+    use crate::{
+        provider::{
+            event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+            limits::{Limits, TrafficShapingConfig},
+        },
+        Client,
+    };
+    use s2n_quic_core::{crypto::tls::testing::certificates, path::MINIMUM_MTU};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the length of each sent datagram along with the MTU updates
+    #[derive(Clone, Default)]
+    struct Datagrams {
+        sent: Arc<Mutex<Vec<u16>>>,
+        mtu: Arc<Mutex<Vec<u16>>>,
+        /// The number of datagrams which coalesced an Initial and a Handshake packet
+        coalesced: Arc<Mutex<usize>>,
+    }
+
+    /// The long header packets written into the datagram currently being sent
+    #[derive(Default)]
+    struct Packets {
+        initial: bool,
+        handshake: bool,
+    }
+
+    impl Subscriber for Datagrams {
+        type ConnectionContext = Packets;
+
+        fn create_connection_context(
+            &mut self,
+            _meta: &ConnectionMeta,
+            _info: &ConnectionInfo,
+        ) -> Self::ConnectionContext {
+            Packets::default()
+        }
+
+        fn on_packet_sent(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::PacketSent,
+        ) {
+            match event.packet_header {
+                events::PacketHeader::Initial { .. } => context.initial = true,
+                events::PacketHeader::Handshake { .. } => context.handshake = true,
+                _ => {}
+            }
+        }
+
+        fn on_datagram_sent(
+            &mut self,
+            context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::DatagramSent,
+        ) {
+            self.sent.lock().unwrap().push(event.len);
+
+            let packets = core::mem::take(context);
+            if packets.initial && packets.handshake {
+                *self.coalesced.lock().unwrap() += 1;
+            }
+        }
+
+        fn on_mtu_updated(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::MtuUpdated,
+        ) {
+            self.mtu.lock().unwrap().push(event.mtu);
+        }
+    }
+
+    fn run(config: TrafficShapingConfig) -> (Vec<u16>, u16) {
+        let datagrams = Datagrams::default();
+        let limits = Limits::new().with_traffic_shaping(config).unwrap();
+
+        test(Model::default(), |handle| {
+            let server_addr = server(handle)?;
+            client_with(handle, server_addr, |io| {
+                Ok(Client::builder()
+                    .with_io(io)?
+                    .with_tls(certificates::CERT_PEM)?
+                    .with_limits(limits)?
+                    .with_event((datagrams.clone(), events()))?
+                    .start()?)
+            })
+        })
+        .unwrap();
+
+        let sent = datagrams.sent.lock().unwrap().clone();
+        let mtu = datagrams.mtu.lock().unwrap().iter().copied().max().unwrap();
+
+        assert!(!sent.is_empty());
+        // padding doesn't interfere with MTU probes
+        assert!(mtu > MINIMUM_MTU, "the path MTU should be discovered");
+        // padding doesn't prevent the handshake packets from being coalesced
+        assert!(
+            *datagrams.coalesced.lock().unwrap() > 0,
+            "the Initial and Handshake packets should be coalesced"
+        );
+
+        (sent, mtu)
+    }
+
+    let (sent, mtu) = run(TrafficShapingConfig {
+        pad_to_mtu: true,
+        ..Default::default()
+    });
+    for len in sent {
+        assert!((MINIMUM_MTU..=mtu).contains(&len), "unexpected len {}", len);
+    }
+
+    let (sent, _mtu) = run(TrafficShapingConfig {
+        min_packet_size: 1000,
+        ..Default::default()
+    });
+    for len in sent {
+        assert!(len >= 1000, "unexpected len {}", len);
+    }
+
+    const QUANTUM: u16 = 100;
+    let (sent, _mtu) = run(TrafficShapingConfig {
+        quantize_to: QUANTUM,
+        ..Default::default()
+    });
+    assert!(sent.iter().any(|len| *len < MINIMUM_MTU));
+    for len in sent {
+        // only datagrams that are limited by the path MTU can't be rounded up
+        assert!(
+            len % QUANTUM == 0 || len > MINIMUM_MTU,
+            "unexpected len {}",
+            len
+        );
+    }
+}
+
+/// Ensures a path migrated to discovers its own MTU rather than inheriting the MTU of the
+/// previous path
+#[test]
+fn migrate_to_smaller_mtu_path() {
+    use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+    use s2n_quic_core::{
+        crypto::tls::testing::certificates,
+        path::{IPV4_MIN_HEADER_LEN, IPV6_MIN_HEADER_LEN, UDP_HEADER_LEN},
+        stream::testing::Data,
+    };
+    use std::sync::{Arc, Mutex};
+
+    const LEN: u64 = 1_000_000;
+    const REBIND_OFFSET: u64 = 100_000;
+    // a standard Ethernet path
+    const ETHERNET_MAX_UDP_PAYLOAD: u16 = 1500 - IPV4_MIN_HEADER_LEN - UDP_HEADER_LEN;
+    // an IPv6 tunnel with the minimum IPv6 MTU
+    const TUNNEL_MAX_UDP_PAYLOAD: u16 = 1280 - IPV6_MIN_HEADER_LEN - UDP_HEADER_LEN;
+
+    /// Records the MTU updates for each path
+    #[derive(Clone, Default)]
+    struct MtuUpdates(Arc<Mutex<Vec<(u64, u16, events::MtuUpdatedCause)>>>);
+
+    impl Subscriber for MtuUpdates {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_mtu_updated(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::MtuUpdated,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.path_id, event.mtu, event.cause.clone()));
+        }
+    }
+
+    let server_events = MtuUpdates::default();
+
+    let model = Model::default();
+    model.set_max_udp_payload(ETHERNET_MAX_UDP_PAYLOAD);
+
+    test(model.clone(), |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event((server_events.clone(), events()))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+
+            let mut recv_data = Data::new(LEN);
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                recv_data.receive(&[chunk]);
+            }
+            assert!(recv_data.is_finished());
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?;
+        let client_addr = client.local_addr()?;
+        let handle = handle.clone();
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_send_stream().await.unwrap();
+
+            let mut send_data = Data::new(LEN);
+            let mut is_rebound = false;
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                stream.send(chunk).await.unwrap();
+
+                if !is_rebound && send_data.offset() >= REBIND_OFFSET {
+                    // give the original path time to complete its search
+                    delay(Duration::from_secs(1)).await;
+
+                    // the client moves to a tunnel with a smaller MTU
+                    model.set_max_udp_payload(TUNNEL_MAX_UDP_PAYLOAD);
+                    handle.rebind(client_addr);
+                    is_rebound = true;
+                }
+            }
+
+            stream.close().await.unwrap();
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let updates = server_events.0.lock().unwrap();
+
+    assert!(
+        updates
+            .iter()
+            .any(|(path_id, mtu, _)| { *path_id == 0 && *mtu == ETHERNET_MAX_UDP_PAYLOAD }),
+        "the original path should discover the Ethernet MTU: {:?}",
+        updates
+    );
+
+    let migrated: Vec<_> = updates
+        .iter()
+        .filter(|(path_id, _, _)| *path_id != 0)
+        .collect();
+    assert!(
+        migrated
+            .iter()
+            .any(|(_, _, cause)| matches!(cause, events::MtuUpdatedCause::NewPath { .. })),
+        "the migrated path should start a new search: {:?}",
+        updates
+    );
+    for (_, mtu, _) in migrated {
+        assert!(
+            *mtu <= TUNNEL_MAX_UDP_PAYLOAD,
+            "the migrated path should not exceed the tunnel MTU: {:?}",
+            updates
+        );
+    }
+}
+
+#[test]
+fn packet_loss_rate_test() {
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+    use std::sync::{Arc, Mutex};
+
+    const LEN: u64 = 1_000_000;
+
+    fn run(drop_rate: f64) -> (f64, f64) {
+        let rates = Arc::new(Mutex::new(None));
+
+        let model = Model::default();
+        model.set_drop_rate(drop_rate);
+
+        test(model, |handle| {
+            let mut server = Server::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(SERVER_CERTS)?
+                .with_event(events())?
+                .start()?;
+            let server_addr = server.local_addr()?;
+
+            primary::spawn(async move {
+                let mut connection = server.accept().await.unwrap();
+                let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+
+                let mut recv_data = Data::new(LEN);
+                while let Some(chunk) = stream.receive().await.unwrap() {
+                    recv_data.receive(&[chunk]);
+                }
+                assert!(recv_data.is_finished());
+            });
+
+            let client = crate::Client::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(certificates::CERT_PEM)?
+                .with_event(events())?
+                .start()?;
+            let rates = rates.clone();
+
+            primary::spawn(async move {
+                let connect = Connect::new(server_addr).with_server_name("localhost");
+                let mut connection = client.connect(connect).await.unwrap();
+                let mut stream = connection.open_send_stream().await.unwrap();
+
+                let mut send_data = Data::new(LEN);
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    stream.send(chunk).await.unwrap();
+                }
+                stream.close().await.unwrap();
+
+                *rates.lock().unwrap() =
+                    Some((connection.packet_loss_rate(), connection.burst_loss_rate()));
+            });
+
+            Ok(())
+        })
+        .unwrap();
+
+        let rates = rates.lock().unwrap().take();
+        rates.expect("the client should finish sending")
+    }
+
+    assert_eq!((0.0, 0.0), run(0.0));
+
+    let (packet_loss_rate, burst_loss_rate) = run(0.05);
+    assert!(
+        packet_loss_rate > 0.0 && packet_loss_rate < 0.5,
+        "unexpected packet loss rate {}",
+        packet_loss_rate
+    );
+    assert!(
+        burst_loss_rate > 0.0 && burst_loss_rate <= packet_loss_rate,
+        "unexpected burst loss rate {}",
+        burst_loss_rate
+    );
+}
+
+#[test]
+fn connection_histograms_test() {
+    use s2n_quic_core::{
+        connection::limits::Limits, crypto::tls::testing::certificates, stream::testing::Data,
+    };
+
+    const LEN: u64 = 1_000_000;
+    const NETWORK_DELAY: Duration = Duration::from_millis(50);
+
+    let model = Model::default();
+    model.set_delay(NETWORK_DELAY);
+
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+
+            // the server didn't enable the histograms
+            assert!(connection.rtt_histogram().unwrap().is_none());
+
+            let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+            while stream.receive().await.unwrap().is_some() {}
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .with_limits(Limits::new().with_histograms(true).unwrap())?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_send_stream().await.unwrap();
+
+            let mut send_data = Data::new(LEN);
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                stream.send(chunk).await.unwrap();
+            }
+            stream.close().await.unwrap();
+
+            // sending after the first second closes the throughput interval in progress
+            delay(Duration::from_secs(1)).await;
+            connection.ping().unwrap();
+            delay(NETWORK_DELAY * 4).await;
+
+            let rtt = connection.rtt_histogram().unwrap().unwrap();
+            assert!(!rtt.is_empty());
+            let min_rtt = (NETWORK_DELAY * 2).as_micros() as u64;
+            assert!(rtt.min() >= min_rtt, "unexpected rtt {:?}", rtt);
+
+            let throughput = connection.throughput_histogram().unwrap().unwrap();
+            assert!(!throughput.is_empty());
+            assert!(
+                throughput.max() > 0,
+                "unexpected throughput {:?}",
+                throughput
+            );
+
+            connection.reset_histograms().unwrap();
+            assert!(connection.rtt_histogram().unwrap().unwrap().is_empty());
+            assert!(connection
+                .throughput_histogram()
+                .unwrap()
+                .unwrap()
+                .is_empty());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn stream_metrics_test() {
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+
+    const LEN: u64 = 1_000_000;
+
+    let model = Model::default();
+    model.set_drop_rate(0.05);
+
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+
+            let mut recv_data = Data::new(LEN);
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                recv_data.receive(&[chunk]);
+            }
+            assert!(recv_data.is_finished());
+            drop(stream);
+
+            // the stream is committed to the connection totals once it's finalized
+            delay(Duration::from_millis(100)).await;
+
+            let metrics = connection.stream_metrics().unwrap();
+            assert!(metrics.bytes_received >= LEN, "{:?}", metrics);
+            assert_eq!(metrics.frames_sent, 0);
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_send_stream().await.unwrap();
+
+            let mut send_data = Data::new(LEN);
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                stream.send(chunk).await.unwrap();
+            }
+
+            // streams which are still open aren't included
+            assert_eq!(
+                connection.stream_metrics().unwrap(),
+                crate::stream::MetricsTotals::default()
+            );
+
+            stream.close().await.unwrap();
+            drop(stream);
+            delay(Duration::from_millis(100)).await;
+
+            let metrics = connection.stream_metrics().unwrap();
+            // each byte is sent once as new data, and again for each time it was lost
+            assert_eq!(metrics.bytes_sent - metrics.retransmitted_bytes, LEN);
+            assert!(metrics.retransmitted_bytes > 0, "{:?}", metrics);
+            assert!(metrics.frames_sent >= LEN / 1500, "{:?}", metrics);
+            assert_eq!(metrics.bytes_received, 0);
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn reset_stats_test() {
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+
+    const FIRST_LEN: u64 = 100_000;
+    const SECOND_LEN: u64 = 50_000;
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            while let Ok(Some(mut stream)) = connection.accept_receive_stream().await {
+                while stream.receive().await.unwrap().is_some() {}
+            }
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            for len in [FIRST_LEN, SECOND_LEN] {
+                let mut stream = connection.open_send_stream().await.unwrap();
+                let mut send_data = Data::new(len);
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    stream.send(chunk).await.unwrap();
+                }
+                stream.close().await.unwrap();
+                drop(stream);
+
+                // wait for the stream to be added to the connection totals
+                while connection.stream_metrics().unwrap().bytes_sent == 0 {
+                    delay(Duration::from_millis(10)).await;
+                }
+
+                // every byte sent since the last reset is counted exactly once
+                let totals = connection.stream_metrics().unwrap();
+                assert_eq!(totals.bytes_sent, len);
+                assert!(connection.write_amplification().unwrap().payload_bytes >= len);
+
+                connection.reset_stats().unwrap();
+
+                assert_eq!(
+                    connection.stream_metrics().unwrap(),
+                    crate::stream::MetricsTotals::default()
+                );
+                assert_eq!(
+                    connection.write_amplification().unwrap(),
+                    crate::stream::WriteAmplification::default()
+                );
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn write_amplification_test() {
+    use crate::stream::WriteAmplification;
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+    use std::sync::{Arc, Mutex};
+
+    const LEN: u64 = 1_000_000;
+
+    fn run(drop_rate: f64) -> (WriteAmplification, WriteAmplification) {
+        let amplification = Arc::new(Mutex::new(None));
+
+        let model = Model::default();
+        model.set_drop_rate(drop_rate);
+
+        test(model, |handle| {
+            let mut server = Server::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(SERVER_CERTS)?
+                .with_event(events())?
+                .start()?;
+            let server_addr = server.local_addr()?;
+
+            primary::spawn(async move {
+                let mut connection = server.accept().await.unwrap();
+                let mut stream = connection
+                    .accept_bidirectional_stream()
+                    .await
+                    .unwrap()
+                    .unwrap();
+
+                let mut recv_data = Data::new(LEN);
+                while let Some(chunk) = stream.receive().await.unwrap() {
+                    recv_data.receive(&[chunk]);
+                }
+                assert!(recv_data.is_finished());
+
+                // keep the stream open so the client can query it after it's finished sending
+                delay(Duration::from_secs(1)).await;
+            });
+
+            let client = crate::Client::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(certificates::CERT_PEM)?
+                .with_event(events())?
+                .start()?;
+            let amplification = amplification.clone();
+
+            primary::spawn(async move {
+                let connect = Connect::new(server_addr).with_server_name("localhost");
+                let mut connection = client.connect(connect).await.unwrap();
+                let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+                let mut send_data = Data::new(LEN);
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    stream.send(chunk).await.unwrap();
+                }
+                stream.close().await.unwrap();
+
+                let stream_amplification = stream.write_amplification().unwrap();
+                assert_eq!(stream_amplification.payload_bytes, LEN);
+
+                let connection_amplification = connection.write_amplification().unwrap();
+                assert!(connection_amplification.payload_bytes >= LEN);
+
+                *amplification.lock().unwrap() =
+                    Some((stream_amplification, connection_amplification));
+            });
+
+            Ok(())
+        })
+        .unwrap();
+
+        let amplification = amplification.lock().unwrap().take();
+        amplification.expect("the client should finish sending")
+    }
+
+    let (stream, connection) = run(0.0);
+    let lossless = (stream.ratio().unwrap(), connection.ratio().unwrap());
+    // only the frame headers are added without loss
+    assert!(lossless.0 > 1.0 && lossless.0 < 1.05, "{:?}", stream);
+    // the connection only counts STREAM frames, so packets carrying ACKs don't add overhead
+    assert!(lossless.1 > 1.0 && lossless.1 < 1.05, "{:?}", connection);
+
+    // retransmissions increase the amount of data on the wire
+    let (stream, connection) = run(0.1);
+    assert!(stream.ratio().unwrap() > lossless.0, "{:?}", stream);
+    assert!(connection.ratio().unwrap() > lossless.1, "{:?}", connection);
+}
+
+#[test]
+fn coalesce_connections_test() {
+    use s2n_quic_core::crypto::tls::testing::certificates;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    const LIMIT: usize = 10;
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let closed = Arc::new(AtomicUsize::new(0));
+
+    test(Model::default(), |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        let server_accepted = accepted.clone();
+        let server_closed = closed.clone();
+        spawn(async move {
+            while let Some(mut connection) = server.accept().await {
+                server_accepted.fetch_add(1, Ordering::Relaxed);
+                let server_closed = server_closed.clone();
+                spawn(async move {
+                    while let Ok(Some(_stream)) = connection.accept_bidirectional_stream().await {}
+                    server_closed.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        });
+
+        let client = crate::Client::builder()
+            .coalesce_connections(true)
+            .with_max_streams_per_connection(LIMIT)
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?;
+
+        let client_accepted = accepted.clone();
+        let client_closed = closed.clone();
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+
+            // concurrent attempts are spread over as many handshakes as the limit requires
+            let attempts = (0..LIMIT * 2).map(|_| client.connect(connect.clone()));
+            let connections = futures::future::try_join_all(attempts).await.unwrap();
+
+            let mut handles = HashMap::new();
+            for connection in &connections {
+                *handles.entry(connection.id()).or_insert(0) += 1;
+            }
+            assert_eq!(handles.len(), 2);
+            assert!(handles.values().all(|count| *count == LIMIT));
+
+            // the connections are at the limit, so another attempt opens a new connection
+            let mut connection = client.connect(connect.clone()).await.unwrap();
+            assert!(!handles.contains_key(&connection.id()));
+
+            delay(Duration::from_millis(100)).await;
+            assert_eq!(client_accepted.load(Ordering::Relaxed), 3);
+
+            // the pool doesn't keep connections open once the applications drop their handles
+            drop(connections);
+            delay(Duration::from_millis(100)).await;
+            assert_eq!(client_closed.load(Ordering::Relaxed), 2);
+
+            // connections which reached the stream limit are no longer shared
+            let mut streams = vec![];
+            for _ in 0..LIMIT {
+                streams.push(connection.open_bidirectional_stream().await.unwrap());
+            }
+            let other = client.connect(connect).await.unwrap();
+            assert_ne!(other.id(), connection.id());
+
+            delay(Duration::from_millis(100)).await;
+            assert_eq!(client_accepted.load(Ordering::Relaxed), 4);
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn connection_send_bandwidth_test() {
+    use s2n_quic_core::{
+        crypto::tls::testing::certificates, recovery::bandwidth::Bandwidth, stream::testing::Data,
+        time::Timestamp,
+    };
+    use s2n_quic_platform::io::testing::time::now;
+    use std::sync::{Arc, Mutex};
+
+    const STREAMS: usize = 10;
+    // each stream sends at 500 Kbps for 2 seconds
+    const STREAM_RATE: u64 = 500_000;
+    const SEND_INTERVAL: Duration = Duration::from_millis(100);
+    const CHUNK_LEN: usize = (STREAM_RATE / 8 / 10) as usize;
+    const LEN: u64 = CHUNK_LEN as u64 * 20;
+
+    // returns the combined throughput of all of the streams, in bits per second
+    fn run(send_bandwidth: Option<Bandwidth>) -> u64 {
+        let start = Arc::new(Mutex::new(None::<Timestamp>));
+        let end = Arc::new(Mutex::new(None::<Timestamp>));
+
+        test(Model::default(), |handle| {
+            let mut server = Server::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(SERVER_CERTS)?
+                .with_event(events())?
+                .start()?;
+            let server_addr = server.local_addr()?;
+
+            let end = end.clone();
+            primary::spawn(async move {
+                let mut connection = server.accept().await.unwrap();
+                let finished = Arc::new(Mutex::new(0));
+
+                for _ in 0..STREAMS {
+                    let mut stream = connection.accept_receive_stream().await.unwrap().unwrap();
+                    let finished = finished.clone();
+                    let end = end.clone();
+
+                    primary::spawn(async move {
+                        let mut recv_data = Data::new(LEN);
+                        while let Some(chunk) = stream.receive().await.unwrap() {
+                            recv_data.receive(&[chunk]);
+                        }
+                        assert!(recv_data.is_finished());
+
+                        let mut finished = finished.lock().unwrap();
+                        *finished += 1;
+                        if *finished == STREAMS {
+                            *end.lock().unwrap() = Some(now());
+                        }
+                    });
+                }
+            });
+
+            let client = crate::Client::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(certificates::CERT_PEM)?
+                .with_event(events())?
+                .start()?;
+            let start = start.clone();
+
+            primary::spawn(async move {
+                let connect = Connect::new(server_addr).with_server_name("localhost");
+                let mut connection = client.connect(connect).await.unwrap();
+
+                if let Some(send_bandwidth) = send_bandwidth {
+                    connection.set_send_bandwidth(send_bandwidth).unwrap();
+                }
+
+                *start.lock().unwrap() = Some(now());
+
+                for _ in 0..STREAMS {
+                    let mut stream = connection.open_send_stream().await.unwrap();
+
+                    primary::spawn(async move {
+                        let mut send_data = Data::new(LEN);
+                        while let Some(chunk) = send_data.send_one(CHUNK_LEN) {
+                            stream.send(chunk).await.unwrap();
+                            delay(SEND_INTERVAL).await;
+                        }
+                        stream.close().await.unwrap();
+                    });
+                }
+
+                // keep the connection open until the peer has received everything
+                delay(Duration::from_secs(60)).await;
+            });
+
+            Ok(())
+        })
+        .unwrap();
+
+        let start = start.lock().unwrap().expect("the client should connect");
+        let end = end
+            .lock()
+            .unwrap()
+            .expect("the server should receive all streams");
+        let elapsed = end - start;
+
+        (STREAMS as u64 * LEN * 8) * 1_000_000 / elapsed.as_micros() as u64
+    }
+
+    // without a connection limit the streams send at their combined rate of 5 Mbps
+    let unlimited = run(None);
+    assert!(unlimited > 2_000_000, "unexpected throughput {}", unlimited);
+
+    let limit = 1_000_000;
+    let limited = run(Some(Bandwidth::from_bits_per_second(limit)));
+    assert!(limited <= limit, "throughput {} exceeds the limit", limited);
+    assert!(
+        limited > limit * 8 / 10,
+        "unexpected throughput {}",
+        limited
+    );
+}
+
+/// Ensures handshakes succeed through a middlebox that drops packets containing TLS alerts
+///
+/// QUIC doesn't use the TLS record layer so the middlebox compatibility mode from RFC 8446
+/// Appendix D.4 is not used. Instead, large ClientHellos are split across multiple CRYPTO frames
+/// and alerts are carried in CONNECTION_CLOSE frames.
+#[test]
+fn middlebox_test() {
+    use crate::provider::{packet_interceptor::PacketInterceptor, tls};
+    use s2n_codec::DecoderBufferMut;
+    use s2n_quic_core::{
+        crypto::tls::testing::certificates,
+        event::api::Subject,
+        frame::{Frame, FrameMut},
+        packet::interceptor::Packet,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Drops any received packet which contains a TLS alert
+    #[derive(Clone, Default)]
+    struct Middlebox {
+        /// The number of received Initial packets that contained CRYPTO frames
+        initial_crypto_packets: Arc<AtomicUsize>,
+        /// The number of received packets that were dropped
+        dropped_alerts: Arc<AtomicUsize>,
+    }
+
+    impl PacketInterceptor for Middlebox {
+        fn intercept_rx_payload<'a>(
+            &mut self,
+            _subject: &Subject,
+            packet: &Packet,
+            payload: DecoderBufferMut<'a>,
+        ) -> DecoderBufferMut<'a> {
+            let payload = payload.into_less_safe_slice();
+
+            let mut has_crypto = false;
+            let mut has_alert = false;
+
+            // decode a copy of the payload so the original is passed through untouched
+            let mut frames = payload.to_vec();
+            let mut buffer = DecoderBufferMut::new(&mut frames);
+            while !buffer.is_empty() {
+                let (frame, remaining) = match buffer.decode::<FrameMut>() {
+                    Ok(value) => value,
+                    Err(_) => break,
+                };
+
+                match frame {
+                    Frame::Crypto(_) => has_crypto = true,
+                    Frame::ConnectionClose(frame) => {
+                        // TLS alerts are carried as CRYPTO_ERROR codes (0x0100-0x01ff)
+                        has_alert |= (0x100..=0x1ff).contains(&frame.error_code.as_u64());
+                    }
+                    _ => {}
+                }
+
+                buffer = remaining;
+            }
+
+            if has_alert {
+                self.dropped_alerts.fetch_add(1, Ordering::Relaxed);
+                return DecoderBufferMut::new(&mut []);
+            }
+
+            if has_crypto && packet.number.space().is_initial() {
+                self.initial_crypto_packets.fetch_add(1, Ordering::Relaxed);
+            }
+
+            DecoderBufferMut::new(payload)
+        }
+    }
+
+    // offer enough protocols that the ClientHello doesn't fit in a single packet
+    fn application_protocols(include_h3: bool) -> Vec<String> {
+        let mut protocols: Vec<_> = (0..20).map(|i| format!("{:0>100}", i)).collect();
+        if include_h3 {
+            protocols.push("h3".to_string());
+        }
+        protocols
+    }
+
+    fn run(include_h3: bool) -> (Middlebox, Middlebox) {
+        let server_middlebox = Middlebox::default();
+        let client_middlebox = Middlebox::default();
+
+        test(Model::default(), |handle| {
+            let mut server = Server::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(SERVER_CERTS)?
+                .with_event(events())?
+                .with_packet_interceptor(server_middlebox.clone())?
+                .start()?;
+            let server_addr = server.local_addr()?;
+
+            spawn(async move {
+                while let Some(mut connection) = server.accept().await {
+                    // keep the connection open until the client closes it
+                    spawn(async move {
+                        while let Ok(Some(_)) = connection.accept_bidirectional_stream().await {}
+                    });
+                }
+            });
+
+            let client_tls = tls::default::Client::builder()
+                .with_certificate(certificates::CERT_PEM)?
+                .with_application_protocols(application_protocols(include_h3).iter())?
+                .build()?;
+
+            let client = crate::Client::builder()
+                .with_io(handle.builder().build()?)?
+                .with_tls(client_tls)?
+                .with_event(events())?
+                .with_packet_interceptor(client_middlebox.clone())?
+                .start()?;
+
+            primary::spawn(async move {
+                let connect = Connect::new(server_addr).with_server_name("localhost");
+                let connection = client.connect(connect).await;
+
+                if include_h3 {
+                    let connection = connection.unwrap();
+                    assert_eq!(connection.application_protocol().unwrap(), &b"h3"[..]);
+                } else {
+                    // the handshake still fails, even though the alert never arrives
+                    assert!(connection.is_err());
+                }
+            });
+
+            Ok(())
+        })
+        .unwrap();
+
+        (server_middlebox, client_middlebox)
+    }
+
+    let (server, client) = run(true);
+    // the ClientHello was split across multiple Initial packets
+    assert!(server.initial_crypto_packets.load(Ordering::Relaxed) > 1);
+    assert_eq!(client.dropped_alerts.load(Ordering::Relaxed), 0);
+
+    // the server rejects the connection with a `no_application_protocol` alert
+    let (_server, client) = run(false);
+    assert!(client.dropped_alerts.load(Ordering::Relaxed) > 0);
+}
+
+/// The handshake packets that are dropped in each `handshake_loss_recovery` scenario
+#[derive(Clone, Copy, Debug)]
+enum HandshakeLoss {
+    /// The client's first Initial packet
+    ClientInitial,
+    /// The server's first Initial and Handshake packets
+    ServerInitialHandshake,
+    /// The client's Handshake packet containing the Finished message
+    ClientHandshake,
+    /// The server's first packet containing the HANDSHAKE_DONE frame
+    HandshakeDone,
+}
+
+/// The default amount of time a handshake has to recover from a scenario
+const HANDSHAKE_LOSS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The one-way delay of the simulated network
+const HANDSHAKE_LOSS_DELAY: Duration = Duration::from_millis(50);
+
+/// Drops the handshake packets for a `HandshakeLoss` scenario and returns the number of
+/// additional round trips it took the client to confirm the handshake compared to a lossless
+/// handshake
+///
+/// Panics if the handshake is not confirmed within `timeout`.
+fn handshake_loss_recovery(loss: HandshakeLoss, timeout: Duration) -> u32 {
+    let rtt = HANDSHAKE_LOSS_DELAY * 2;
+    let baseline = handshake_confirmation_time(None, timeout);
+    let elapsed = handshake_confirmation_time(Some(loss), timeout);
+
+    assert!(
+        elapsed > baseline,
+        "{:?}: recovering from loss should take longer than {:?}",
+        loss,
+        baseline
+    );
+
+    let extra = elapsed - baseline;
+    ((extra.as_nanos() + rtt.as_nanos() - 1) / rtt.as_nanos()) as u32
+}
+
+/// Returns the amount of time it took the client to confirm the handshake
+fn handshake_confirmation_time(loss: Option<HandshakeLoss>, timeout: Duration) -> Duration {
+    use crate::provider::{
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        packet_interceptor::PacketInterceptor,
+    };
+    use s2n_codec::{DecoderBufferMut, EncoderBuffer};
+    use s2n_quic_core::{
+        event::api::Subject,
+        frame::{Frame, FrameMut},
+        packet::{
+            interceptor::{Datagram, Packet},
+            number::PacketNumberSpace,
+        },
+        time::Timestamp,
+    };
+    use s2n_quic_platform::io::testing::time::now;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Target {
+        /// A packet in the given space containing a CRYPTO frame
+        Crypto(PacketNumberSpace),
+        /// A packet containing a HANDSHAKE_DONE frame
+        HandshakeDone,
+    }
+
+    /// Drops the first transmitted datagram containing each of the `targets`
+    ///
+    /// Dropping the entire datagram, rather than clearing the packet payload, ensures the
+    /// peer never acknowledges the packet and it is declared lost by the sender.
+    struct DropFirst {
+        targets: Vec<Target>,
+        drop_datagram: bool,
+    }
+
+    impl DropFirst {
+        fn new(targets: &[Target]) -> Self {
+            Self {
+                targets: targets.to_vec(),
+                drop_datagram: false,
+            }
+        }
+    }
+
+    impl PacketInterceptor for DropFirst {
+        fn intercept_tx_payload<'a>(
+            &mut self,
+            _subject: &Subject,
+            packet: &Packet,
+            payload: &mut EncoderBuffer<'a>,
+        ) {
+            if self.targets.is_empty() {
+                return;
+            }
+
+            let space = packet.number.space();
+            let (written, _) = payload.split_mut();
+            let mut frames = written.to_vec();
+            let mut buffer = DecoderBufferMut::new(&mut frames);
+
+            while !buffer.is_empty() {
+                let (frame, remaining) = match buffer.decode::<FrameMut>() {
+                    Ok(value) => value,
+                    Err(_) => break,
+                };
+
+                let target = match frame {
+                    Frame::Crypto(_) => Some(Target::Crypto(space)),
+                    Frame::HandshakeDone(_) => Some(Target::HandshakeDone),
+                    _ => None,
+                };
+
+                if let Some(index) = self.targets.iter().position(|t| Some(*t) == target) {
+                    self.targets.remove(index);
+                    self.drop_datagram = true;
+                }
+
+                buffer = remaining;
+            }
+        }
+
+        fn intercept_tx_datagram<'a>(
+            &mut self,
+            _subject: &Subject,
+            _datagram: &Datagram,
+            payload: &mut EncoderBuffer<'a>,
+        ) {
+            if core::mem::take(&mut self.drop_datagram) {
+                payload.set_position(0);
+            }
+        }
+    }
+
+    /// Records the time the handshake was confirmed
+    #[derive(Clone, Default)]
+    struct Confirmed(Arc<Mutex<Option<Timestamp>>>);
+
+    impl Subscriber for Confirmed {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_handshake_status_updated(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::HandshakeStatusUpdated,
+        ) {
+            if matches!(event.status, events::HandshakeStatus::Confirmed { .. }) {
+                self.0.lock().unwrap().get_or_insert_with(now);
+            }
+        }
+    }
+
+    let (client_targets, server_targets): (&[Target], &[Target]) = match loss {
+        None => (&[], &[]),
+        Some(HandshakeLoss::ClientInitial) => (&[Target::Crypto(PacketNumberSpace::Initial)], &[]),
+        Some(HandshakeLoss::ServerInitialHandshake) => (
+            &[],
+            &[
+                Target::Crypto(PacketNumberSpace::Initial),
+                Target::Crypto(PacketNumberSpace::Handshake),
+            ],
+        ),
+        Some(HandshakeLoss::ClientHandshake) => {
+            (&[Target::Crypto(PacketNumberSpace::Handshake)], &[])
+        }
+        Some(HandshakeLoss::HandshakeDone) => (&[], &[Target::HandshakeDone]),
+    };
+
+    let confirmed = Confirmed::default();
+    let start = Arc::new(Mutex::new(None::<Timestamp>));
+
+    let model = Model::default();
+    model.set_delay(HANDSHAKE_LOSS_DELAY);
+
+    test(model, |handle| {
+        let server = server_with(handle, |io| {
+            Ok(Server::builder()
+                .with_io(io)?
+                .with_tls(SERVER_CERTS)?
+                .with_event(events())?
+                .with_packet_interceptor(DropFirst::new(server_targets))?
+                .start()?)
+        })?;
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(s2n_quic_core::crypto::tls::testing::certificates::CERT_PEM)?
+            .with_event(confirmed.clone())?
+            .with_packet_interceptor(DropFirst::new(client_targets))?
+            .start()?;
+
+        let start = start.clone();
+        primary::spawn(async move {
+            *start.lock().unwrap() = Some(now());
+
+            let connect = Connect::new(server).with_server_name("localhost");
+            let connection = client.connect(connect).await.unwrap();
+
+            // keep the connection open until the handshake is confirmed
+            delay(timeout).await;
+            drop(connection);
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let start = start
+        .lock()
+        .unwrap()
+        .expect("the client should start connecting");
+    let confirmed = confirmed
+        .0
+        .lock()
+        .unwrap()
+        .expect("the client should confirm the handshake");
+    let elapsed = confirmed - start;
+
+    assert!(
+        elapsed <= timeout,
+        "{:?}: the handshake took {:?}",
+        loss,
+        elapsed
+    );
+
+    elapsed
+}
+
+#[test]
+fn handshake_loss_recovery_test() {
+    // the PTO is based on the initial RTT of 333ms until the first RTT sample, so recovering
+    // the client's first flight takes about a second
+    const MAX_EXTRA_ROUND_TRIPS: u32 = 15;
+
+    for loss in [
+        HandshakeLoss::ClientInitial,
+        HandshakeLoss::ServerInitialHandshake,
+        HandshakeLoss::ClientHandshake,
+        HandshakeLoss::HandshakeDone,
+    ] {
+        let extra_round_trips = handshake_loss_recovery(loss, HANDSHAKE_LOSS_TIMEOUT);
+        assert!(
+            extra_round_trips <= MAX_EXTRA_ROUND_TRIPS,
+            "{:?}: recovery took {} extra round trips",
+            loss,
+            extra_round_trips
+        );
+    }
+}
+
+#[test]
+fn handshake_probe_suppression_test() {
+    use crate::provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber};
+    use s2n_quic_core::crypto::tls::testing::certificates;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct State {
+        is_confirmed: bool,
+        pings_before_confirmed: usize,
+        pings_after_confirmed: usize,
+    }
+
+    /// Counts the PING frames sent in 1-RTT packets before and after the handshake is confirmed
+    #[derive(Clone, Default)]
+    struct Pings(Arc<Mutex<State>>);
+
+    impl Subscriber for Pings {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_handshake_status_updated(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::HandshakeStatusUpdated,
+        ) {
+            if matches!(event.status, events::HandshakeStatus::Confirmed { .. }) {
+                self.0.lock().unwrap().is_confirmed = true;
+            }
+        }
+
+        fn on_frame_sent(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::FrameSent,
+        ) {
+            if !matches!(event.packet_header, events::PacketHeader::OneRtt { .. })
+                || !matches!(event.frame, events::Frame::Ping { .. })
+            {
+                return;
+            }
+
+            let mut state = self.0.lock().unwrap();
+            if state.is_confirmed {
+                state.pings_after_confirmed += 1;
+            } else {
+                state.pings_before_confirmed += 1;
+            }
+        }
+    }
+
+    let pings = Pings::default();
+
+    let model = Model::default();
+    // a slow path with a 200ms RTT
+    model.set_delay(Duration::from_millis(100));
+
+    test(model, |handle| {
+        let server_addr = server(handle)?;
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(pings.clone())?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+
+            // the handshake is complete but not yet confirmed, since the client is waiting on
+            // the HANDSHAKE_DONE frame
+            connection.keep_alive(true).unwrap();
+            connection.ping().unwrap();
+
+            delay(Duration::from_secs(1)).await;
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let state = pings.0.lock().unwrap();
+    assert!(state.is_confirmed);
+    assert_eq!(
+        state.pings_before_confirmed, 0,
+        "probes should be held back until the handshake is confirmed"
+    );
+    assert!(
+        state.pings_after_confirmed > 0,
+        "the held back PING should be sent once the handshake is confirmed"
+    );
+}
+
+/// Both endpoints upload over a shared bottleneck at the same time
+///
+/// Neither direction should be starved by the other, so the transfers finish at around the
+/// same time.
+#[test]
+fn bidirectional_fairness_test() {
+    use crate::{provider::limits::Limits, stream::BidirectionalStream};
+    use s2n_quic_core::{crypto::tls::testing::certificates, stream::testing::Data};
+    use s2n_quic_platform::io::testing::time::now;
+    use std::sync::{Arc, Mutex};
+
+    const LEN: u64 = 2_000_000;
+
+    /// Sends `LEN` bytes on the stream while receiving `LEN` bytes from the peer
+    ///
+    /// Returns once the peer's data was received, with the time it took.
+    async fn transfer(stream: BidirectionalStream) -> Duration {
+        let start = now();
+        let (mut recv, mut send) = stream.split();
+
+        spawn(async move {
+            let mut send_data = Data::new(LEN);
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                send.send(chunk).await.unwrap();
+            }
+            send.finish().unwrap();
+        });
+
+        let mut recv_data = Data::new(LEN);
+        while let Some(chunk) = recv.receive().await.unwrap() {
+            recv_data.receive(&[chunk]);
+        }
+        assert!(recv_data.is_finished());
+
+        now() - start
+    }
+
+    let durations: Arc<Mutex<Vec<Duration>>> = Default::default();
+    let limits = Limits::new().with_fairness_ratio(1).unwrap();
+
+    let model = Model::default();
+    model.set_delay(Duration::from_millis(25));
+    // both directions share a bottleneck which drops packets once 100 are in flight
+    model.set_max_inflight(100);
+
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_limits(limits)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        let server_durations = durations.clone();
+        spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+            let duration = transfer(stream).await;
+            server_durations.lock().unwrap().push(duration);
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_limits(limits)?
+            .with_event(events())?
+            .start()?;
+
+        let client_durations = durations.clone();
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let stream = connection.open_bidirectional_stream().await.unwrap();
+            let duration = transfer(stream).await;
+            client_durations.lock().unwrap().push(duration);
+
+            // wait for the server to finish receiving the client's data
+            while client_durations.lock().unwrap().len() < 2 {
+                delay(Duration::from_millis(10)).await;
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    let durations = durations.lock().unwrap();
+    assert_eq!(durations.len(), 2);
+
+    let fastest = durations.iter().min().unwrap();
+    let slowest = durations.iter().max().unwrap();
+
+    // both directions get a fair share of the bottleneck
+    assert!(
+        *slowest < *fastest * 2,
+        "the transfers should finish at around the same time: {:?}",
+        durations
+    );
+}
+
+/// Ensures both endpoints report a potential deadlock when neither application reads
+#[test]
+fn potential_deadlock_test() {
+    use crate::{
+        provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        stream::BidirectionalStream,
+    };
+    use s2n_quic_core::{
+        connection::limits::Limits, crypto::tls::testing::certificates, stream::testing::Data,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts the reported potential deadlocks
+    #[derive(Clone, Default)]
+    struct Deadlocks(Arc<AtomicUsize>);
+
+    impl Subscriber for Deadlocks {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_potential_deadlock(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::PotentialDeadlock,
+        ) {
+            assert!(!event.blocked_stream_ids.is_empty());
+            assert!(event.unread_lens.iter().all(|len| *len > 0));
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Writes to the stream without ever reading from it
+    async fn write_only(mut stream: BidirectionalStream) {
+        let mut send_data = Data::new(u64::MAX);
+        while let Some(chunk) = send_data.send_one(usize::MAX) {
+            if stream.send(chunk).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    // keep the windows small so both endpoints are blocked quickly
+    let limits = Limits::new()
+        .with_bidirectional_local_data_window(16 * 1024)
+        .unwrap()
+        .with_bidirectional_remote_data_window(16 * 1024)
+        .unwrap();
+    let server_deadlocks = Deadlocks::default();
+    let client_deadlocks = Deadlocks::default();
+
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_limits(limits)?
+            .with_event((server_deadlocks.clone(), events()))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            while let Ok(Some(stream)) = connection.accept_bidirectional_stream().await {
+                spawn(write_only(stream));
+            }
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_limits(limits)?
+            .with_event((client_deadlocks.clone(), events()))?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let stream = connection.open_bidirectional_stream().await.unwrap();
+            spawn(write_only(stream));
+
+            // give both endpoints more than the default timeout to detect the deadlock
+            delay(Duration::from_secs(10)).await;
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    assert!(server_deadlocks.0.load(Ordering::Relaxed) > 0);
+    assert!(client_deadlocks.0.load(Ordering::Relaxed) > 0);
+}
+
+/// Ensures a server with an amplification factor of 1 sends no more than it received until the
+/// client's address is validated
+#[test]
+fn amplification_factor_test() {
+    use crate::provider::{
+        event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        limits::Limits,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Records the length of each datagram sent and received by the server
+    #[derive(Clone, Default)]
+    struct Datagrams {
+        sent: Arc<Mutex<Vec<u16>>>,
+        received: Arc<Mutex<Vec<u16>>>,
+    }
+
+    impl Subscriber for Datagrams {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_datagram_sent(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::DatagramSent,
+        ) {
+            self.sent.lock().unwrap().push(event.len);
+        }
+
+        fn on_datagram_received(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::DatagramReceived,
+        ) {
+            self.received.lock().unwrap().push(event.len);
+        }
+    }
+
+    let datagrams = Datagrams::default();
+    let limits = Limits::new().with_amplification_factor(1).unwrap();
+
+    test(Model::default(), |handle| {
+        let server_addr = server_with(handle, |io| {
+            Ok(Server::builder()
+                .with_io(io)?
+                .with_tls(SERVER_CERTS)?
+                .with_limits(limits)?
+                .with_event((datagrams.clone(), events()))?
+                .start()?)
+        })?;
+
+        // the client only completes the exchange once the server validated its address
+        client(handle, server_addr)?;
+
+        Ok(())
+    })
+    .unwrap();
+
+    let sent = datagrams.sent.lock().unwrap();
+    let received = datagrams.received.lock().unwrap();
+
+    // the server's first flight is exactly the size of the client's Initial
+    assert_eq!(sent[0], received[0]);
+}
+
+/// Ensures the sender stalls while the receiver's budget of unread bytes is used up
+#[test]
+fn recv_budget_test() {
+    use crate::{
+        provider::event::{events, ConnectionInfo, ConnectionMeta, Subscriber},
+        stream::BidirectionalStream,
+    };
+    use s2n_quic_core::{
+        connection::limits::Limits, crypto::tls::testing::certificates, stream::testing::Data,
+    };
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    /// Counts the stream bytes sent by an endpoint
+    #[derive(Clone, Default)]
+    struct StreamBytes(Arc<AtomicU64>);
+
+    impl StreamBytes {
+        fn get(&self) -> u64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Subscriber for StreamBytes {
+        type ConnectionContext = ();
+
+        fn create_connection_context(&mut self, _meta: &ConnectionMeta, _info: &ConnectionInfo) {}
+
+        fn on_frame_sent(
+            &mut self,
+            _context: &mut Self::ConnectionContext,
+            _meta: &ConnectionMeta,
+            event: &events::FrameSent,
+        ) {
+            if let events::Frame::Stream { len, .. } = event.frame {
+                self.0.fetch_add(len as u64, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Reads from the stream until at least `len` bytes were read
+    async fn read(stream: &mut BidirectionalStream, len: u64) -> u64 {
+        let mut read = 0;
+        while read < len {
+            let chunk = stream.receive().await.unwrap().unwrap();
+            read += chunk.len() as u64;
+        }
+        read
+    }
+
+    const BUDGET: u64 = 64 * 1024;
+    const RTT: Duration = Duration::from_millis(100);
+
+    // only the budget limits the connection once the initial window is used up
+    let limits = Limits::new()
+        .with_data_window(BUDGET)
+        .unwrap()
+        .with_bidirectional_remote_data_window(16 * BUDGET)
+        .unwrap()
+        .with_max_buffered_recv_bytes(BUDGET)
+        .unwrap();
+    let client_bytes = StreamBytes::default();
+
+    let model = Model::default();
+    model.set_delay(RTT / 2);
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_limits(limits)?
+            .with_event(events())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        let sent = client_bytes.clone();
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+            let mut stream = connection
+                .accept_bidirectional_stream()
+                .await
+                .unwrap()
+                .unwrap();
+
+            // the application doesn't read, so the client fills the budget
+            while connection.recv_budget_remaining().unwrap() > 0 {
+                delay(Duration::from_millis(1)).await;
+            }
+
+            // the client stops sending within 2 RTTs
+            delay(RTT * 2).await;
+            let stalled = sent.get();
+            delay(RTT * 10).await;
+            assert_eq!(sent.get(), stalled);
+
+            // reading less than half of the budget doesn't let the client send more
+            let mut consumed = read(&mut stream, BUDGET / 4).await;
+            delay(RTT * 4).await;
+            assert_eq!(sent.get(), stalled);
+            assert_eq!(connection.recv_budget_remaining().unwrap(), consumed);
+
+            // once the buffered bytes drop below half of the budget, the client fills it again
+            consumed += read(&mut stream, BUDGET / 2 - consumed).await;
+            assert!(connection.recv_budget_remaining().unwrap() >= BUDGET / 2);
+            delay(RTT * 2).await;
+            assert_eq!(connection.recv_budget_remaining().unwrap(), 0);
+            assert!(sent.get() > stalled);
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event((client_bytes.clone(), events()))?
+            .start()?;
+
+        spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+            let mut send_data = Data::new(u64::MAX);
+            while let Some(chunk) = send_data.send_one(usize::MAX) {
+                if stream.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(())
+    })
+    .unwrap();
+}
+
+/// Ensures the audit logger records the lifecycle of a connection
+#[test]
+fn audit_log_test() {
+    use crate::provider::event::audit::{AuditLogger, Record, RecordKind, Target};
+    use s2n_quic_core::crypto::tls::testing::certificates;
+    use std::{io, sync::mpsc};
+
+    /// Forwards the records to the test
+    struct Channel(mpsc::Sender<Record>);
+
+    impl Target for Channel {
+        fn write(&mut self, record: &Record) -> io::Result<()> {
+            let _ = self.0.send(record.clone());
+            Ok(())
+        }
+    }
+
+    let (sender, records) = mpsc::channel();
+    let logger = AuditLogger::builder()
+        .with_target(Channel(sender))
+        .build()
+        .unwrap();
+    let mut client_addr = None;
+
+    test(Model::default(), |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event((logger.clone(), events()))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let mut connection = server.accept().await.unwrap();
+
+            // echo each stream until the client closes the connection
+            while let Ok(Some(mut stream)) = connection.accept_bidirectional_stream().await {
+                while let Some(chunk) = stream.receive().await.unwrap() {
+                    stream.send(chunk).await.unwrap();
+                }
+                stream.finish().unwrap();
+            }
+        });
+
+        let client = crate::Client::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?;
+        client_addr = Some(client.local_addr()?);
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let mut connection = client.connect(connect).await.unwrap();
+            let mut stream = connection.open_bidirectional_stream().await.unwrap();
+
+            stream.send(Bytes::from_static(b"audit")).await.unwrap();
+            stream.finish().unwrap();
+            while stream.receive().await.unwrap().is_some() {}
+
+            connection.close(123u8.into());
+        });
+
+        Ok(())
+    })
+    .unwrap();
+
+    // the records are written by a background thread
+    let mut kinds = vec![];
+    while !matches!(kinds.last(), Some(RecordKind::ConnectionClosed { .. })) {
+        let record = records.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(record.peer_address, client_addr.unwrap());
+        kinds.push(record.kind);
+    }
+
+    let position = |expected: &RecordKind| {
+        kinds
+            .iter()
+            .position(|kind| kind == expected)
+            .unwrap_or_else(|| panic!("{:?} was not recorded in {:?}", expected, kinds))
+    };
+
+    position(&RecordKind::ConnectionEstablished {
+        server_name: Some("localhost".to_string()),
+    });
+    assert!(kinds
+        .iter()
+        .any(|kind| matches!(kind, RecordKind::KeyUpdate { generation: 0, .. })));
+    let opened = position(&RecordKind::StreamOpened { stream_id: 0 });
+    let closed = position(&RecordKind::StreamClosed { stream_id: 0 });
+    assert!(opened < closed);
+    assert!(!kinds
+        .iter()
+        .any(|kind| matches!(kind, RecordKind::AuthenticationFailure { .. })));
+    assert_eq!(logger.dropped_records(), 0);
+}
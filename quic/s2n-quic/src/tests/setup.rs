@@ -74,11 +74,21 @@ pub fn server(handle: &Handle) -> Result<SocketAddr> {
 }
 
 pub fn client(handle: &Handle, server_addr: SocketAddr) -> Result {
-    let client = Client::builder()
-        .with_io(handle.builder().build().unwrap())?
-        .with_tls(certificates::CERT_PEM)?
-        .with_event(events())?
-        .start()?;
+    client_with(handle, server_addr, |io| {
+        Ok(Client::builder()
+            .with_io(io)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(events())?
+            .start()?)
+    })
+}
+
+pub fn client_with<F: FnOnce(Io) -> Result<Client>>(
+    handle: &Handle,
+    server_addr: SocketAddr,
+    build: F,
+) -> Result {
+    let client = build(handle.builder().build().unwrap())?;
 
     primary::spawn(async move {
         let connect = Connect::new(server_addr).with_server_name("localhost");